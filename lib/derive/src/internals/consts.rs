@@ -0,0 +1,132 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{self, Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Error, Expr, Ident, Item, StaticMutability, Token, Type};
+
+use super::{crate_path, ident_name};
+
+const CONST_ATTR_NAME: &str = "reflect_const";
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ConstArgs {
+    /// An explicit path to the `static_reflect` crate (`crate = "..."`)
+    pub crate_path: Option<syn::Path>,
+}
+
+impl Parse for ConstArgs {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        let mut args = ConstArgs { crate_path: None };
+        while !input.is_empty() {
+            if input.peek(Token![crate]) {
+                let keyword = input.parse::<Token![crate]>()?;
+                if args.crate_path.is_some() {
+                    return Err(Error::new(keyword.span, "Duplicate crate option"));
+                }
+                input.parse::<Token![=]>()?;
+                args.crate_path = Some(input.parse::<syn::LitStr>()?.parse::<syn::Path>()?);
+            } else if input.peek(syn::Ident) {
+                let ident = input.parse::<Ident>()?;
+                return Err(Error::new(
+                    ident.span(),
+                    format_args!("Invalid flag: {}", ident),
+                ));
+            } else {
+                return Err(input.error("Unexpected token"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// If the type is a string reference (`&str` or `&'static str`)
+fn is_str_ref(ty: &Type) -> bool {
+    match *ty {
+        Type::Reference(ref reference) if reference.mutability.is_none() => {
+            matches!(*reference.elem, Type::Path(ref path) if path.qself.is_none() && path.path.is_ident("str"))
+        }
+        _ => false,
+    }
+}
+
+pub fn handle_item(item: &Item, args: ConstArgs) -> Result<TokenStream, syn::Error> {
+    let (name, ty, is_static) = match *item {
+        Item::Const(ref item) => {
+            if !item.generics.params.is_empty() {
+                return Err(Error::new(
+                    item.generics.span(),
+                    format!("#[{}] doesn't support generic constants", CONST_ATTR_NAME),
+                ));
+            }
+            (&item.ident, &*item.ty, false)
+        }
+        Item::Static(ref item) => {
+            if let StaticMutability::Mut(token) = item.mutability {
+                return Err(Error::new(
+                    token.span,
+                    format!(
+                        "#[{}] doesn't support `static mut`, since its bytes may change",
+                        CONST_ATTR_NAME
+                    ),
+                ));
+            }
+            (&item.ident, &*item.ty, true)
+        }
+        _ => {
+            return Err(Error::new(
+                item.span(),
+                format!("Invalid target for #[{}]", CONST_ATTR_NAME),
+            ))
+        }
+    };
+    let krate = crate_path(args.crate_path);
+    let const_name = format_ident!("_CONST_{}", name);
+    let reflected_name = ident_name(name);
+    let value: Expr = syn::parse_quote!(#name);
+    let (type_info, array_len, bytes) = if is_str_ref(ty) {
+        (
+            quote!(&<str as #krate::StaticReflect>::TYPE_INFO),
+            quote!(None),
+            quote!(#value.as_bytes()),
+        )
+    } else {
+        let bytes = if is_static {
+            // The static is immutable, and `ConstBytes` has no interior mutability
+            quote!(unsafe {
+                ::core::slice::from_raw_parts(
+                    ::core::ptr::addr_of!(#value) as *const u8,
+                    ::core::mem::size_of::<#ty>(),
+                )
+            })
+        } else {
+            // SAFETY: `ConstBytes` types have no padding and no pointers
+            quote!({
+                const __REFLECT_BYTES: [u8; ::core::mem::size_of::<#ty>()] = unsafe {
+                    ::core::mem::transmute::<#ty, [u8; ::core::mem::size_of::<#ty>()]>(#value)
+                };
+                &__REFLECT_BYTES
+            })
+        };
+        (
+            quote!(<#ty as #krate::consts::ConstBytes>::ELEMENT_TYPE),
+            quote!(<#ty as #krate::consts::ConstBytes>::ARRAY_LEN),
+            bytes,
+        )
+    };
+    Ok(quote! {
+        #item
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        pub const #const_name: #krate::consts::ConstDeclaration = #krate::consts::ConstDeclaration {
+            name: #reflected_name,
+            type_info: #type_info,
+            array_len: #array_len,
+            bytes: #bytes,
+            is_static: #is_static,
+        };
+    })
+}
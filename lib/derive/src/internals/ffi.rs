@@ -0,0 +1,101 @@
+//! The rules for the FFI safety of types, as seen by the macros
+//!
+//! See `static_reflect::types::ffi` for the documented rules.
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{GenericArgument, PathArguments, Type};
+
+/// Why a type isn't safe to pass across an `extern "C"` boundary
+#[derive(Copy, Clone, Debug)]
+pub enum FfiRule {
+    Warning(&'static str),
+    Unsafe(&'static str),
+}
+
+const SLICE_MESSAGE: &str = "slices have no C equivalent";
+
+/// Apply the rules to the syntax of the type,
+/// returning `None` if there isn't any rule for it
+pub fn ffi_rule(ty: &Type) -> Option<FfiRule> {
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => {
+            let last = path.path.segments.last()?;
+            match (last.ident.to_string().as_str(), &last.arguments) {
+                ("char", PathArguments::None) => Some(FfiRule::Unsafe(
+                    "`char` has no C equivalent, use `u32` instead",
+                )),
+                ("u128" | "i128", PathArguments::None) => Some(FfiRule::Warning(
+                    "128-bit integers don't have a stable ABI on every target",
+                )),
+                ("str", PathArguments::None) => Some(FfiRule::Unsafe(SLICE_MESSAGE)),
+                // A box is a pointer, unless it points to a slice
+                ("Box", PathArguments::AngleBracketed(ref args)) => match args.args.first() {
+                    Some(GenericArgument::Type(ref target)) => pointer_rule(target),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        Type::Tuple(ref tuple) if !tuple.elems.is_empty() => {
+            Some(FfiRule::Unsafe("tuples have an unspecified layout"))
+        }
+        Type::Slice(_) => Some(FfiRule::Unsafe(SLICE_MESSAGE)),
+        Type::Array(ref array) => ffi_rule(&array.elem),
+        Type::Ptr(ref ptr) => pointer_rule(&ptr.elem),
+        Type::Reference(ref reference) => pointer_rule(&reference.elem),
+        Type::BareFn(ref func) => {
+            let is_rust_abi = match func.abi {
+                // A plain `extern fn` uses the C calling convention
+                Some(ref abi) => abi.name.as_ref().is_some_and(|name| name.value() == "Rust"),
+                None => true,
+            };
+            if is_rust_abi {
+                Some(FfiRule::Unsafe(
+                    "function pointers without `extern \"C\"` use the Rust calling convention",
+                ))
+            } else {
+                None
+            }
+        }
+        Type::Paren(ref inner) => ffi_rule(&inner.elem),
+        Type::Group(ref inner) => ffi_rule(&inner.elem),
+        _ => None,
+    }
+}
+
+/// Pointers are always safe, unless they point to a slice
+fn pointer_rule(target: &Type) -> Option<FfiRule> {
+    match *target {
+        Type::Slice(_) => Some(FfiRule::Unsafe("pointers to slices have no C equivalent")),
+        Type::Path(ref path) if path.path.is_ident("str") => {
+            Some(FfiRule::Unsafe("pointers to `str` have no C equivalent"))
+        }
+        Type::Paren(ref inner) => pointer_rule(&inner.elem),
+        Type::Group(ref inner) => pointer_rule(&inner.elem),
+        _ => None,
+    }
+}
+
+/// The FFI safety of a field or argument, as an expression of type `FfiSafety`
+///
+/// The rules are applied to the declared type, and the reason is prefixed with the context.
+/// Without a rule, this is the `FFI_SAFETY` of the reflected type
+/// (which differs from the declared type with `assume_repr`).
+pub fn ffi_safety_expr(
+    krate: &syn::Path,
+    context: &str,
+    declared_type: &Type,
+    reflected_type: &Type,
+) -> TokenStream {
+    match ffi_rule(declared_type) {
+        Some(FfiRule::Warning(message)) => {
+            let reason = format!("{}: {}", context, message);
+            quote!(#krate::types::ffi::FfiSafety::Warning(#reason))
+        }
+        Some(FfiRule::Unsafe(message)) => {
+            let reason = format!("{}: {}", context, message);
+            quote!(#krate::types::ffi::FfiSafety::Unsafe(#reason))
+        }
+        None => quote!(<#reflected_type as #krate::StaticReflect>::FFI_SAFETY),
+    }
+}
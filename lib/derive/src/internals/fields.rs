@@ -1,96 +1,527 @@
+use std::collections::{HashMap, HashSet};
+
 use indexmap::IndexMap;
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::{format_ident, quote};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 use syn::{
-    parse_quote, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, GenericParam, Generics,
-    Type, TypeParamBound,
+    parse_quote, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Field, GenericArgument,
+    GenericParam, Generics, PathArguments, TraitBoundModifier, Type, TypeParamBound,
+    WherePredicate,
 };
 
-use super::{determine_repr, Repr};
+use static_reflect_internals::options::{
+    AccessorOptions, DeriveContainerOptions, DeriveFieldOptions, DeriveVariantOptions, LintOptions,
+};
+use static_reflect_internals::{analyze_struct, erase_lifetimes, AnalyzedStruct};
 
-#[derive(Debug)]
-#[non_exhaustive]
-pub struct DeriveFieldOptions {
-    /// Treat this array field as opaque and unsized.
-    ///
-    /// For example,
-    /// ````ignore
-    /// struct PyTuple {
-    ///     ob_refcnt: usize,
-    ///     ob_size: usize,
-    ///     #[reflect(opaque_array)]
-    ///     ob_items: [PyObject; 1]
-    /// }
-    /// ````
-    /// The final field will have type `PyObject` instead of `[PyObject; 1]`.
-    /// The actual size won't really be known at compile time,
-    /// so it's up to the user to ensure no attempts are made at stack allocation
-    ///
-    /// The array must be in trailing position
-    pub opaque_array: bool,
-    /// Assume the field has the same underlying representation as the specified type.
-    ///
-    /// Useful if the type is known to be FFI-safe,
-    /// but the field's type doesn't actually implement `StaticReflect`
-    pub assume_repr: Option<syn::Type>,
-}
-impl DeriveFieldOptions {
-    pub fn parse_attrs(attrs: &[syn::Attribute]) -> Result<DeriveFieldOptions, syn::Error> {
-        let mut result = None;
-        for attr in attrs {
-            if attr.path().is_ident("reflect") || attr.path().is_ident("static_reflect") {
-                attr.parse_nested_meta(|meta| {
-                    if result.is_some() {
-                        return Err(meta.error("Conflicting #[reflect(...)] field options"));
-                    }
-                    let mut args = DeriveFieldOptions::default();
-                    if meta.path.is_ident("opaque_array") {
-                        args.opaque_array = true;
-                    } else if meta.path.is_ident("assume_repr") {
-                        let value = meta.value()?;
-                        let type_str = value.parse::<syn::LitStr>()?;
-                        let desired_type =
-                            syn::parse_str::<Type>(&type_str.value()).map_err(|cause| {
-                                syn::Error::new(
-                                    type_str.span(),
-                                    format_args!("Invalid type: {}", cause),
-                                )
-                            })?;
-                        args.assume_repr = Some(desired_type);
-                    } else {
-                        return Err(Error::new_spanned(&meta.path, format_args!("Invalid flag")));
-                    }
-                    // validate args
-                    if args.assume_repr.is_some() && args.opaque_array {
-                        return Err(Error::new_spanned(
-                            &meta.path,
-                            "opaque_array is incompatible with assume_repr",
-                        ));
+use super::ffi::ffi_safety_expr;
+use super::{crate_path, determine_align, determine_repr, ident_name, Repr};
+
+/// The prefix reserved for all identifiers introduced by the generated code.
+///
+/// Fields are forbidden from starting with this prefix,
+/// so it is the only thing that can possibly collide with generated items.
+pub const RESERVED_PREFIX: &str = "__reflect_";
+
+/// Create an identifier for a generated item,
+/// which is guaranteed to start with the [RESERVED_PREFIX]
+pub fn reserved_ident(name: impl std::fmt::Display, span: Span) -> Ident {
+    Ident::new(&format!("{}{}", RESERVED_PREFIX, name), span)
+}
+
+/// The hidden trait holding the field definitions of a type
+/// (as associated items, so they can use its generic parameters)
+fn field_trait_name(name: &Ident) -> Ident {
+    reserved_ident(format_args!("FieldTrait_{}", name), name.span())
+}
+
+/// The items generated alongside the `StaticReflect` implementation
+///
+/// These are grouped by kind, and always emitted in the same order
+/// (regardless of which options requested them),
+/// so that the expansion only changes where the input did.
+#[derive(Default)]
+struct GeneratedItems {
+    /// Hidden helper items, and the `FieldReflect` implementation
+    impls: Vec<TokenStream>,
+    /// Inherent items requested by options (like `#[reflect(schema)]`)
+    inherent: Vec<TokenStream>,
+    /// Compile-time assertions about the layout
+    checks: Vec<TokenStream>,
+    /// Additional items of the `StaticReflect` implementation
+    reflect_items: Vec<TokenStream>,
+}
+
+/// Assert that the type assumed by `assume_repr` has the same layout as the field's real type.
+///
+/// This catches the most common mistakes, like assuming `i32` for a `u64` field.
+fn assume_repr_layout_check(
+    field: &FieldName,
+    span: Span,
+    real_type: &Type,
+    assumed_type: &Type,
+) -> TokenStream {
+    let message = |property: &str| {
+        let message = format!(
+            "Field `{}` assumes the representation of `{}`, but `{}` has a different {}",
+            field.access(),
+            assumed_type.to_token_stream(),
+            real_type.to_token_stream(),
+            property
+        );
+        escape_format(message)
+    };
+    let size_message = message("size");
+    let align_message = message("alignment");
+    quote_spanned! { span =>
+        assert!(
+            std::mem::size_of::<#real_type>() == std::mem::size_of::<#assumed_type>(),
+            #size_message
+        );
+        assert!(
+            std::mem::align_of::<#real_type>() == std::mem::align_of::<#assumed_type>(),
+            #align_message
+        );
+    }
+}
+
+/// Assert that a field with a pointer discipline (`#[reflect(ptr = "...")]`)
+/// is actually a pointer (possibly non-null or optional)
+fn pointer_field_check(
+    krate: &syn::Path,
+    name: &Ident,
+    field: &FieldName,
+    span: Span,
+    field_type: &Type,
+) -> TokenStream {
+    let message = escape_format(format!(
+        "Field `{}` of `{}` has a pointer discipline, but isn't a pointer",
+        field.access(),
+        name
+    ));
+    quote_spanned! { span =>
+        assert!(
+            matches!(
+                <#field_type as #krate::StaticReflect>::TYPE_INFO.nullable(),
+                #krate::types::TypeInfo::Pointer { .. }
+            ),
+            #message
+        );
+    }
+}
+
+/// Assert that a field with named bits (`#[reflect(bitset(...))]`) is an integer,
+/// with enough bits for all of the names
+///
+/// The bit width comes from the real type of the field,
+/// so an opaque array includes the bits of all its elements.
+fn bitset_field_check(
+    krate: &syn::Path,
+    name: &Ident,
+    field: &FieldName,
+    span: Span,
+    (field_type, real_type): (&Type, &Type),
+    count: usize,
+) -> TokenStream {
+    let type_message = escape_format(format!(
+        "Field `{}` of `{}` has a bitset, but isn't an integer (or an array of integers)",
+        field.access(),
+        name
+    ));
+    let capacity_message = escape_format(format!(
+        "Field `{}` of `{}` has {} bitset names, which don't fit in its bits",
+        field.access(),
+        name,
+        count
+    ));
+    quote_spanned! { span =>
+        assert!(
+            matches!(
+                <#field_type as #krate::StaticReflect>::TYPE_INFO,
+                #krate::types::TypeInfo::Integer(_)
+            ),
+            #type_message
+        );
+        assert!(
+            #count <= ::core::mem::size_of::<#real_type>() * 8,
+            #capacity_message
+        );
+    }
+}
+
+/// Assert that the offset computed for a field matches the real offset.
+///
+/// This pinpoints the first field whose offset diverges,
+/// instead of only noticing that the total size doesn't match.
+fn offset_check(
+    krate: &syn::Path,
+    self_ty: &TokenStream,
+    name: &Ident,
+    field: &FieldName,
+    span: Span,
+) -> TokenStream {
+    let access = field.access();
+    let message = escape_format(format!(
+        "Field `{}` of `{}` has a different offset than computed from the previous fields (is the type #[repr(C)]?)",
+        access, name
+    ));
+    quote_spanned! { span =>
+        assert!(
+            <#self_ty as #krate::FieldReflect>::NAMED_FIELD_INFO.#access.offset()
+                == core::mem::offset_of!(#self_ty, #access),
+            #message
+        );
+    }
+}
+
+/// Warn about suspicious layouts, as requested by `#[reflect(lint)]`
+///
+/// Proc macros can't emit warnings on stable, and the layout is only known
+/// after expansion anyway. Instead, a compile-time condition selects
+/// between a deprecated and a normal version of a function,
+/// so calling it triggers the `deprecated` lint with our message.
+fn lint_checks(
+    krate: &syn::Path,
+    name: &Ident,
+    repr: Option<Repr>,
+    options: &LintOptions,
+) -> TokenStream {
+    let lint_type = reserved_ident("Lint", name.span());
+    let max_padding = match options.max_padding {
+        Some(ref max_padding) => quote!(#max_padding),
+        None => quote!(#krate::types::lint::DEFAULT_MAX_PADDING),
+    };
+    let padding_message = match options.max_padding {
+        Some(ref max_padding) => format!(
+            "`{}` has more than {} bytes of padding",
+            name,
+            max_padding.base10_digits()
+        ),
+        None => format!(
+            "`{}` has more padding than allowed (configure with `lint(max_padding = N)`)",
+            name
+        ),
+    };
+    let mut lints = vec![
+        (
+            format_ident!("excessive_padding", span = name.span()),
+            format!("{} (reflect lint: excessive padding)", padding_message),
+            quote!(#krate::types::lint::has_excessive_padding(&__reflect_INFO, #max_padding)),
+        ),
+        (
+            format_ident!("field_order", span = name.span()),
+            format!(
+                "`{}` would be smaller with its fields sorted by decreasing alignment (reflect lint: field order)",
+                name
+            ),
+            quote!(#krate::types::lint::could_reorder_fields(&__reflect_INFO)),
+        ),
+        (
+            format_ident!("improper_ctypes", span = name.span()),
+            format!(
+                "`{0}` isn't FFI-safe, see `<{0} as StaticReflect>::FFI_SAFETY` for the reason (reflect lint: improper ctypes)",
+                name
+            ),
+            quote!(!<#name as #krate::StaticReflect>::FFI_SAFETY.is_safe()),
+        ),
+    ];
+    // The discriminant of a repr(C) enum is meant to match C's `int`
+    let explicit_width = match repr {
+        Some(Repr::Integer { signed, bits }) => {
+            Some(format!("{}{}", if signed { 'i' } else { 'u' }, bits))
+        }
+        Some(Repr::IntegerPointerSized { signed }) => {
+            Some(if signed { "isize" } else { "usize" }.into())
+        }
+        _ => None,
+    };
+    if let Some(width) = explicit_width {
+        lints.push((
+            format_ident!("discriminant_width", span = name.span()),
+            format!(
+                "`{}` has a wider discriminant (`{}`) than its values need (reflect lint: discriminant width)",
+                name, width
+            ),
+            quote!(#krate::types::lint::has_wide_discriminant(&__reflect_INFO)),
+        ));
+    }
+    let warnings = lints.iter().map(|(lint, message, _)| {
+        quote!(
+            #[deprecated(note = #message)]
+            const fn #lint() {}
+        )
+    });
+    let fallbacks = lints.iter().map(|(lint, _, _)| quote!(const fn #lint() {}));
+    let calls = lints.iter().map(|(lint, _, condition)| {
+        quote_spanned!(name.span() => #lint_type::<{ #condition }>::#lint();)
+    });
+    quote!(
+        #[allow(non_upper_case_globals)]
+        const _: () = {
+            const __reflect_INFO: #krate::types::TypeInfo =
+                <#name as #krate::StaticReflect>::TYPE_INFO;
+            #[allow(non_camel_case_types)]
+            struct #lint_type<const WARN: bool>;
+            #[allow(dead_code)]
+            impl #lint_type<true> {
+                #(#warnings)*
+            }
+            #[allow(dead_code)]
+            impl #lint_type<false> {
+                #(#fallbacks)*
+            }
+            #(#calls)*
+        };
+    )
+}
+
+/// The messages for a mismatched size and alignment, naming the type
+///
+/// The offsets of each field are checked separately (see [offset_check]).
+fn layout_mismatch_messages(name: &Ident) -> (String, String) {
+    (
+        escape_format(format!(
+            "`{}` has a different size than computed from its fields",
+            name
+        )),
+        escape_format(format!(
+            "`{}` has a different alignment than the largest alignment of its fields",
+            name
+        )),
+    )
+}
+
+/// Escape a message so it can be used as a format string
+fn escape_format(message: String) -> String {
+    message.replace('{', "{{").replace('}', "}}")
+}
+
+/// A 32-bit FNV-1a hash, used to shorten names in the minimal profile.
+///
+/// Unlike the standard library's hasher, this is stable between compiler versions.
+pub fn fnv1a_hash(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Reject fields whose type is unsized (or points to a trait object), suggesting an alternative
+///
+/// Otherwise, these would only fail with confusing trait bound errors in the generated code.
+/// This is purely syntactic, so it misses unsized types hidden behind aliases.
+fn check_unsized_fields(input: &DeriveInput) -> Result<(), syn::Error> {
+    // Type parameters declared `?Sized`, either inline or in the where clause
+    let mut maybe_unsized = HashSet::new();
+    let is_maybe_sized = |bound: &TypeParamBound| {
+        matches!(bound, TypeParamBound::Trait(bound)
+            if matches!(bound.modifier, TraitBoundModifier::Maybe(_)))
+    };
+    for param in input.generics.type_params() {
+        if param.bounds.iter().any(is_maybe_sized) {
+            maybe_unsized.insert(param.ident.clone());
+        }
+    }
+    if let Some(ref where_clause) = input.generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if let WherePredicate::Type(ref predicate) = *predicate {
+                if let Type::Path(ref path) = predicate.bounded_ty {
+                    if let (None, Some(ident)) = (&path.qself, path.path.get_ident()) {
+                        if predicate.bounds.iter().any(is_maybe_sized) {
+                            maybe_unsized.insert(ident.clone());
+                        }
                     }
-                    result = Some(args);
-                    Ok(())
-                })?;
+                }
+            }
+        }
+    }
+    let fields: Vec<&Field> = match input.data {
+        Data::Struct(ref data) => data.fields.iter().collect(),
+        Data::Union(ref data) => data.fields.named.iter().collect(),
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .collect(),
+    };
+    for field in fields {
+        if let Some(message) = unsized_type_message(&field.ty, &maybe_unsized) {
+            return Err(syn::Error::new(field.ty.span(), message));
+        }
+        if is_trait_object_pointer(&field.ty) {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "Pointers to trait objects can't be reflected, \
+                since the layout of their vtable isn't stable. \
+                Use an opaque handle to the object instead (see `static_reflect::macros::opaque_handle`)",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check if the type is a (fat) pointer to a trait object,
+/// like `Box<dyn Trait>` or `Option<&dyn Trait>`
+///
+/// Like the check for unsized types, this misses trait objects hidden behind aliases.
+fn is_trait_object_pointer(ty: &Type) -> bool {
+    fn is_trait_object(ty: &Type) -> bool {
+        match *ty {
+            Type::Paren(ref paren) => is_trait_object(&paren.elem),
+            Type::Group(ref group) => is_trait_object(&group.elem),
+            Type::TraitObject(_) => true,
+            _ => false,
+        }
+    }
+    match *ty {
+        Type::Paren(ref paren) => is_trait_object_pointer(&paren.elem),
+        Type::Group(ref group) => is_trait_object_pointer(&group.elem),
+        Type::Reference(ref reference) => is_trait_object(&reference.elem),
+        Type::Ptr(ref ptr) => is_trait_object(&ptr.elem),
+        Type::Path(ref path) if path.qself.is_none() => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return false,
+            };
+            let argument = match segment.arguments {
+                PathArguments::AngleBracketed(ref args) => {
+                    args.args.iter().find_map(|arg| match *arg {
+                        GenericArgument::Type(ref ty) => Some(ty),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+            match (segment.ident.to_string().as_str(), argument) {
+                ("Box" | "Rc" | "Arc" | "NonNull", Some(target)) => is_trait_object(target),
+                ("Option" | "ManuallyDrop", Some(inner)) => is_trait_object_pointer(inner),
+                _ => false,
             }
         }
-        Ok(result.unwrap_or_else(DeriveFieldOptions::default))
+        _ => false,
     }
 }
-#[allow(clippy::derivable_impls)]
-impl Default for DeriveFieldOptions {
-    fn default() -> DeriveFieldOptions {
-        DeriveFieldOptions {
-            // Most fields are not trailing arrays
-            opaque_array: false,
-            // This is unsafe
-            assume_repr: None,
+
+/// Explain why a field's type is unsized (and what to use instead),
+/// or `None` if it isn't obviously unsized
+fn unsized_type_message(ty: &Type, maybe_unsized: &HashSet<Ident>) -> Option<String> {
+    match *ty {
+        Type::Paren(ref paren) => unsized_type_message(&paren.elem, maybe_unsized),
+        Type::Group(ref group) => unsized_type_message(&group.elem, maybe_unsized),
+        Type::Slice(ref slice) => {
+            let elem = slice.elem.to_token_stream();
+            Some(format!(
+                "Slices are unsized, so they can't be reflected. \
+                Use `AsmSlice<{elem}>` (from `static_reflect::builtins`), a pointer to the elements, \
+                or a trailing `[{elem}; N]` array marked #[reflect(opaque_array)] \
+                (or `[{elem}; 0]` with #[reflect(unsized_tail)] on the struct)",
+                elem = elem
+            ))
+        }
+        Type::Path(ref path) if path.qself.is_none() && path.path.is_ident("str") => Some(
+            "`str` is unsized, so it can't be reflected. \
+            Use `AsmStr` (from `static_reflect::builtins`) or a pointer to the bytes"
+                .into(),
+        ),
+        Type::TraitObject(_) => Some(
+            "Trait objects are unsized, so they can't be reflected. \
+            Use an opaque handle to the object instead (see `static_reflect::macros::opaque_handle`)"
+                .into(),
+        ),
+        Type::Path(ref path) if path.qself.is_none() => {
+            let ident = path.path.get_ident()?;
+            if maybe_unsized.contains(ident) {
+                Some(format!(
+                    "`{ident}` may be unsized (`?Sized`), so it can't be reflected. \
+                    Remove the `?Sized` bound, or use a pointer to `{ident}`",
+                    ident = ident
+                ))
+            } else {
+                None
+            }
         }
+        _ => None,
     }
 }
 
 pub fn derive_static_reflect(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
     let name = &input.ident;
     let repr = determine_repr(input)?;
+    let align = determine_align(input)?;
+    let options = DeriveContainerOptions::parse_attrs(&input.attrs)?;
+    let krate = &crate_path(options.crate_path.clone());
+    if options.newtype_unit {
+        let r = newtype_unit_impl(input, repr, &options, krate)?;
+        super::utils::debug_derive("StaticReflect", &input.ident, &r);
+        super::manifest::emit_record(input)?;
+        return Ok(r);
+    }
+    if options.pod && !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(pod)] is only supported on structs",
+        ));
+    }
+    if options.minimal && !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(profile = \"minimal\")] is only supported on structs",
+        ));
+    }
+    if options.unsized_tail && !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(unsized_tail)] is only supported on structs",
+        ));
+    }
+    if options.lazy_fields && !matches!(input.data, Data::Union(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(lazy_fields)] is only supported on unions",
+        ));
+    }
+    if options.lazy_fields && !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(lazy_fields)] isn't supported on generic types",
+        ));
+    }
+    if options.lint.is_some() && !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(lint)] isn't supported on generic types",
+        ));
+    }
+    if options.variant_names && (options.niche || !matches!(input.data, Data::Enum(_))) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(variant_names)] is only supported on C-style enums",
+        ));
+    }
+    if options.schema && !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(schema)] is only supported on structs",
+        ));
+    }
+    if options.accessors.is_some() && !matches!(input.data, Data::Struct(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(accessors)] is only supported on structs",
+        ));
+    }
+    if options.niche && !matches!(input.data, Data::Enum(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(niche)] is only supported on enums",
+        ));
+    }
+    if align.is_some() && matches!(input.data, Data::Enum(_)) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[repr(align(...))] is only supported on structs and unions",
+        ));
+    }
     if repr != Some(Repr::C) && !matches!(input.data, Data::Enum(_)) {
         return Err(syn::Error::new(
             name.span(),
@@ -98,34 +529,80 @@ pub fn derive_static_reflect(input: &DeriveInput) -> Result<TokenStream, syn::Er
         ));
     }
 
-    let generics = add_type_bounds(&input.generics, &[parse_quote!(::reflect::StaticReflect)]);
+    check_unsized_fields(input)?;
+
+    let generics = add_type_bounds(&input.generics, &[parse_quote!(#krate::StaticReflect)]);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let mut extra_defs = Vec::new();
+    let mut items = GeneratedItems::default();
+    if let (true, Data::Struct(ref data)) = (options.schema, &input.data) {
+        items
+            .inherent
+            .push(schema_info(data, name, krate, &generics)?);
+    }
+    if let (Some(ref accessors), Data::Struct(ref data)) = (&options.accessors, &input.data) {
+        items.inherent.push(accessor_methods(
+            data,
+            name,
+            krate,
+            &generics,
+            accessors,
+            options.unsized_tail,
+        )?);
+    }
     let static_type = match input.data {
-        Data::Struct(ref data) => handle_type(
-            StructHandler::new(data, name),
+        Data::Struct(_) => handle_type(
+            StructHandler::new(analyze_struct(input)?, krate),
+            krate,
             name,
-            quote!(#impl_generics),
-            quote!(#ty_generics),
-            quote!(#where_clause),
-            &mut extra_defs,
+            &generics,
+            &mut items,
+        )?,
+        Data::Enum(ref data) if options.niche => niche_static_type(data, repr, name, krate)?,
+        Data::Enum(ref data) => {
+            items.checks.push(enum_validation(name, krate));
+            if options.variant_names {
+                items.inherent.push(variant_name_methods(data, name)?);
+            }
+            enum_static_type(data, repr, name, krate)?
+        }
+        Data::Union(ref data) if options.lazy_fields => lazy_union_static_type(
+            UnionTypeHandler {
+                data,
+                name,
+                krate,
+                align,
+            },
+            &mut items,
         )?,
-        Data::Enum(ref data) => enum_static_type(data, repr, name)?,
         Data::Union(ref data) => handle_type(
-            UnionTypeHandler { data, name },
+            UnionTypeHandler {
+                data,
+                name,
+                krate,
+                align,
+            },
+            krate,
             name,
-            quote!(#impl_generics),
-            quote!(#ty_generics),
-            quote!(#where_clause),
-            &mut extra_defs,
+            &generics,
+            &mut items,
         )?,
     };
 
+    if let Some(ref lint) = options.lint {
+        items.checks.push(lint_checks(krate, name, repr, lint));
+    }
+
+    let GeneratedItems {
+        impls,
+        inherent,
+        checks,
+        reflect_items,
+    } = items;
     let r = quote! {
-        #(#extra_defs)*
-        unsafe impl #impl_generics static_reflect::StaticReflect for #name #ty_generics #where_clause {
-            const TYPE_INFO: static_reflect::types::TypeInfo = {
+        #(#impls)*
+        unsafe impl #impl_generics #krate::StaticReflect for #name #ty_generics #where_clause {
+            const TYPE_INFO: #krate::types::TypeInfo = {
                 /*
                  * NOTE: All our fields are assumed to implement `StaticReflect`,
                  * because there is no other way they could show up
@@ -133,29 +610,90 @@ pub fn derive_static_reflect(input: &DeriveInput) -> Result<TokenStream, syn::Er
                  */
                 #static_type
             };
+            #(#reflect_items)*
         }
+        #(#inherent)*
+        #(#checks)*
     };
     super::utils::debug_derive("StaticReflect", &input.ident, &r);
+    super::manifest::emit_record(input)?;
     Ok(r)
 }
+/// Check the field doesn't use a reserved name,
+/// or a reflected name that's already used by another field
+fn check_field_name(
+    field: &FieldInfo<'_>,
+    reflected_names: &mut HashMap<String, Ident>,
+) -> syn::Result<()> {
+    if let FieldName::Named { name } = field.name {
+        if name.to_string().starts_with(RESERVED_PREFIX) {
+            return Err(syn::Error::new(
+                name.span(),
+                format_args!(
+                    "Field names starting with `{}` are reserved for generated code",
+                    RESERVED_PREFIX
+                ),
+            ));
+        }
+    }
+    if let Some(ref reflected_name) = field.reflected_name {
+        let span = field.span;
+        if let Some(existing) = reflected_names.get(reflected_name) {
+            return Err(syn::Error::new(
+                span,
+                format_args!(
+                    "Field is reflected with the name {:?}, which is already used by `{}`",
+                    reflected_name, existing
+                ),
+            ));
+        }
+        let ident = match field.name {
+            FieldName::Named { name } => name.clone(),
+            FieldName::Tuple { .. } => unreachable!("tuple fields are unnamed"),
+        };
+        reflected_names.insert(reflected_name.clone(), ident);
+    }
+    Ok(())
+}
 fn handle_type<'a, T: TypeHandler<'a>>(
     mut target: T,
+    krate: &syn::Path,
     name: &Ident,
-    impl_generics: TokenStream,
-    ty_generics: TokenStream,
-    where_clause: TokenStream,
-    extra_defs: &mut Vec<TokenStream>,
+    generics: &Generics,
+    items: &mut GeneratedItems,
 ) -> Result<TokenStream, syn::Error> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    /*
+     * Free items can't use the type (or const) parameters of a generic type,
+     * so its definitions are associated consts of the hidden field trait,
+     * where the type is known as `Self`.
+     */
+    let is_generic = generics
+        .params
+        .iter()
+        .any(|param| !matches!(param, GenericParam::Lifetime(_)));
+    let self_ty = if is_generic {
+        quote!(Self)
+    } else {
+        quote!(#name)
+    };
     let mut field_info: IndexMap<FieldName<'a>, TokenStream> = IndexMap::new();
     let mut field_associated_types = Vec::new();
     let mut field_defs = Vec::new();
+    let mut reflected_names: HashMap<String, Ident> = HashMap::new();
+    let mut layout_checks = Vec::new();
+    let mut ffi_safety = Vec::new();
     target.handle_fields(|field| {
+        check_field_name(&field, &mut reflected_names)?;
+        layout_checks.extend(field.layout_check);
+        layout_checks.push(offset_check(krate, &self_ty, name, &field.name, field.span));
+        ffi_safety.push(field.ffi_safety);
         let field_name = field.name;
         let field_type = &field.static_type;
         field_info.insert(field_name.clone(), field.static_def.clone());
         let associated_type_name = field_name.associated_type_name();
         field_associated_types.push(quote!(type #associated_type_name = #field_type;));
-        let field_def_type = T::field_def_type(Some(quote!(#field_type)));
+        let field_def_type = T::field_def_type(krate, Some(quote!(#field_type)));
         match field_name {
             FieldName::Tuple { index: _ } => {
                 field_defs.push(quote!(pub #field_def_type));
@@ -164,34 +702,108 @@ fn handle_type<'a, T: TypeHandler<'a>>(
                 field_defs.push(quote!(pub #name: #field_def_type));
             }
         }
+        Ok(())
     })?;
-    let field_info_struct_name = Ident::new(&format!("_FieldInfo{}", name), name.span());
-    let field_info_trait_name = Ident::new(&format!("_FieldTrait{}", name), name.span());
+    let field_info_struct_name = reserved_ident(format_args!("FieldInfo_{}", name), name.span());
+    let field_info_trait_name = field_trait_name(name);
+    let (layout_checks_decl, layout_checks_def, layout_checks_eval) = if is_generic {
+        // Checked whenever the `TYPE_INFO` of an instantiation is used
+        (
+            quote!(
+                const __reflect_LAYOUT_CHECKS: ();
+            ),
+            quote!(const __reflect_LAYOUT_CHECKS: () = { #(#layout_checks)* };),
+            quote!(let () = <Self as #field_info_trait_name>::__reflect_LAYOUT_CHECKS;),
+        )
+    } else {
+        if !layout_checks.is_empty() {
+            // NOTE: Free constants are always evaluated, unlike `TYPE_INFO`
+            items.checks.push(quote!(
+                const _: () = {
+                    #(#layout_checks)*
+                };
+            ));
+        }
+        (quote!(), quote!(), quote!())
+    };
+    let layout_rows = field_info.keys().map(|field| {
+        let access = field.access();
+        let field_name = field.display_name();
+        quote!(#krate::types::report::FieldLayout {
+            name: #field_name,
+            computed_offset: <Self as #krate::FieldReflect>::NAMED_FIELD_INFO.#access.offset(),
+            real_offset: Some(core::mem::offset_of!(Self, #access)),
+        })
+    });
     let associated_type_names = field_info.keys().map(FieldName::associated_type_name);
+    // The field types never mention lifetimes (see `erase_lifetimes`)
+    let mut info_generics = generics.clone();
+    info_generics.params = info_generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, GenericParam::Lifetime(_)))
+        .collect();
+    info_generics.where_clause = None;
+    let (info_impl_generics, info_ty_generics, _) = info_generics.split_for_impl();
+    let is_tuple_style = target.is_tuple_style();
+    let field_access = field_info
+        .keys()
+        .map(|name| name.access())
+        .collect::<Vec<_>>();
+    let field_def_type_name = T::field_def_type(krate, None);
+    let type_def_type = T::type_def_type(krate);
+    let header = quote! {
+        use ::core::mem::{align_of, size_of};
+        use #type_def_type;
+        #layout_checks_eval
+        let __reflect_fields = <Self as #field_info_trait_name>::__reflect_FIELDS;
+    };
+    let (layout_consts_decl, layout_consts_def) = target.layout_consts();
+    let field_reflect_items = target.field_reflect_items()?;
+    let checked_consts = target.checked_field_reflect_consts();
+    if !is_generic && !checked_consts.is_empty() {
+        // Report invalid items even if they're never used
+        items.checks.push(quote!(
+            const _: () = {
+                #(let _ = <#name as #krate::FieldReflect>::#checked_consts;)*
+            };
+        ));
+    }
+    let static_def = target.create_static_def(header);
     let field_info_struct_def = {
         let fields = quote!(#(#field_defs),*);
-        let fields = if target.is_tuple_style() {
+        let fields = if is_tuple_style {
             // NOTE: I guess a tuple-struct needs a semicolon but a regular struct doesn't....
             quote!((#fields);)
         } else {
             quote!({ #fields })
         };
         quote! {
-            #[allow(missing_docs)]
+            #[allow(missing_docs, non_camel_case_types)]
             #[doc(hidden)]
-            pub struct #field_info_struct_name #fields
+            pub struct #field_info_struct_name #info_impl_generics #fields
         }
     };
-    extra_defs.push(quote!(
+    items.impls.push(quote!(
         #field_info_struct_def
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, non_upper_case_globals)]
         #[doc(hidden)]
         trait #field_info_trait_name {
             #(type #associated_type_names;)*
+            const __reflect_LAYOUT_DEBUG: &'static [#krate::types::report::FieldLayout];
+            const __reflect_FIELDS: &'static [#field_def_type_name];
+            #layout_consts_decl
+            const __reflect_DEF: &'static #type_def_type;
+            #layout_checks_decl
         }
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, non_upper_case_globals)]
         impl #impl_generics #field_info_trait_name for #name #ty_generics #where_clause {
             #(#field_associated_types)*
+            const __reflect_LAYOUT_DEBUG: &'static [#krate::types::report::FieldLayout] = &[#(#layout_rows),*];
+            const __reflect_FIELDS: &'static [#field_def_type_name] = &[#(<Self as #krate::FieldReflect>::NAMED_FIELD_INFO.#field_access.erase()),*];
+            #layout_consts_def
+            const __reflect_DEF: &'static #type_def_type = &#static_def;
+            #layout_checks_def
         }
     ));
     let field_inits = field_info
@@ -201,82 +813,555 @@ fn handle_type<'a, T: TypeHandler<'a>>(
             FieldName::Named { name } => quote!(#name: #def),
         })
         .collect::<Vec<TokenStream>>();
-    let field_inits = if target.is_tuple_style() {
+    let field_inits = if is_tuple_style {
         quote!((#(#field_inits,)*))
     } else {
         quote!({#(#field_inits,)*})
     };
-    extra_defs.push(quote!(
-        unsafe impl #impl_generics static_reflect::FieldReflect for #name #ty_generics #where_clause {
-            type NamedFieldInfo = #field_info_struct_name;
+    items.impls.push(quote!(
+        unsafe impl #impl_generics #krate::FieldReflect for #name #ty_generics #where_clause {
+            type NamedFieldInfo = #field_info_struct_name #info_ty_generics;
             const NAMED_FIELD_INFO: Self::NamedFieldInfo = #field_info_struct_name #field_inits;
+            const LAYOUT_DEBUG: &'static [#krate::types::report::FieldLayout] =
+                <Self as #field_info_trait_name>::__reflect_LAYOUT_DEBUG;
+            #field_reflect_items
         }
     ));
-    let field_access = field_info
-        .keys()
-        .map(|name| name.access())
+    items
+        .reflect_items
+        .push(ffi_safety_item(krate, &ffi_safety));
+    let into_type = T::def_into_type(
+        krate,
+        quote!(<Self as #field_info_trait_name>::__reflect_DEF),
+    );
+    Ok(into_type)
+}
+/// The `FFI_SAFETY` of a structure or union, combining the safety of its fields
+fn ffi_safety_item(krate: &syn::Path, fields: &[TokenStream]) -> TokenStream {
+    quote!(
+        const FFI_SAFETY: #krate::types::ffi::FfiSafety =
+            #krate::types::ffi::FfiSafety::all(&[#(#fields),*]);
+    )
+}
+/// If the tokens mention any of the specified identifiers
+fn mentions_any(tokens: TokenStream, idents: &[&Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ref ident) => idents.contains(&ident),
+        proc_macro2::TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+/// An inherent `SCHEMA_INFO` constant, describing a struct
+/// with its type parameters left symbolic (`#[reflect(schema)]`)
+fn schema_info(
+    data: &DataStruct,
+    name: &Ident,
+    krate: &syn::Path,
+    generics: &Generics,
+) -> Result<TokenStream, syn::Error> {
+    let type_params = generics
+        .type_params()
+        .map(|param| &param.ident)
         .collect::<Vec<_>>();
-    let field_def_type_name = T::field_def_type(None);
-    let type_def_type = T::type_def_type();
-    let header = quote! {
-        use static_reflect::{StaticReflect, FieldReflect};
-        use static_reflect::types::TypeInfo;
-        use #field_def_type_name;
-        use #type_def_type;
-        const _FIELDS: &'static [#field_def_type_name] = &[#(<#name as FieldReflect>::NAMED_FIELD_INFO.#field_access.erase()),*];
+    // Fields mentioning const parameters (like `[u8; N]`) also have a generic layout
+    let generic_params = type_params
+        .iter()
+        .copied()
+        .chain(generics.const_params().map(|param| &param.ident))
+        .collect::<Vec<_>>();
+    let mut known_layout = true;
+    let mut fields = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let DeriveFieldOptions {
+            opaque_array,
+            assume_repr,
+            rename,
+            ..
+        } = DeriveFieldOptions::parse_attrs(&field.attrs)?;
+        let mut field_type = erase_lifetimes(&field.ty);
+        if let Some(assumed_type) = assume_repr {
+            field_type = erase_lifetimes(&assumed_type);
+        } else if let (true, Type::Array(array)) = (opaque_array, &field_type) {
+            field_type = (*array.elem).clone();
+        }
+        let parameter = match field_type {
+            Type::Path(ref path) if path.qself.is_none() => path
+                .path
+                .get_ident()
+                .and_then(|ident| type_params.iter().position(|param| *param == ident)),
+            _ => None,
+        };
+        let value_type = if let Some(param_index) = parameter {
+            let param_name = ident_name(type_params[param_index]);
+            known_layout = false;
+            quote!(&#krate::types::TypeInfo::TypeParameter {
+                name: #param_name,
+                index: #param_index,
+            })
+        } else if matches!(field_type, Type::Ptr(_)) {
+            // Pointers have the same layout regardless of their target
+            quote!(&<#field_type as #krate::StaticReflect>::TYPE_INFO)
+        } else if mentions_any(field_type.to_token_stream(), &generic_params) {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "#[reflect(schema)] only supports generic parameters used directly as the type of a field (or behind a pointer)",
+            ));
+        } else {
+            quote!(&<#field_type as #krate::StaticReflect>::TYPE_INFO)
+        };
+        let access = match field.ident {
+            Some(ref ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+        };
+        let offset = if known_layout {
+            quote!(Some(::core::mem::offset_of!(Self, #access)))
+        } else {
+            quote!(None)
+        };
+        let field_name = match (&field.ident, rename) {
+            (None, _) => quote!(None),
+            (Some(_), Some(rename)) => quote!(Some(#rename)),
+            (Some(ident), None) => {
+                let ident = ident_name(ident);
+                quote!(Some(#ident))
+            }
+        };
+        fields.push(quote!(#krate::types::schema::SchemaFieldDef {
+            name: #field_name,
+            value_type: #value_type,
+            offset: #offset,
+            index: #index,
+        }));
+    }
+    let type_param_names = type_params.iter().map(|param| ident_name(param));
+    let type_name = ident_name(name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The schema of this type, with its type parameters left symbolic
+            ///
+            /// This is the same for every instantiation of the type.
+            pub const SCHEMA_INFO: #krate::types::schema::SchemaStructureDef =
+                #krate::types::schema::SchemaStructureDef {
+                    name: #type_name,
+                    type_parameters: &[#(#type_param_names),*],
+                    fields: &[#(#fields),*],
+                };
+        }
+    })
+}
+/// The (possibly renamed) names of the variants of an enum
+fn reflected_variant_names(data: &DataEnum) -> Result<Vec<String>, syn::Error> {
+    let mut seen: HashMap<String, &Ident> = HashMap::new();
+    let mut names = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let options = DeriveVariantOptions::parse_attrs(&variant.attrs)?;
+        let reflected_name = match options.rename {
+            Some(ref rename) => rename.value(),
+            None => ident_name(&variant.ident),
+        };
+        if let Some(existing) = seen.get(&reflected_name) {
+            return Err(syn::Error::new(
+                variant.span(),
+                format_args!(
+                    "Variant is reflected with the name {:?}, which is already used by `{}`",
+                    reflected_name, existing
+                ),
+            ));
+        }
+        seen.insert(reflected_name.clone(), &variant.ident);
+        names.push(reflected_name);
+    }
+    Ok(names)
+}
+/// Inherent methods converting the variants of a C-style enum
+/// to and from their reflected names (`#[reflect(variant_names)]`)
+fn variant_name_methods(data: &DataEnum, name: &Ident) -> Result<TokenStream, syn::Error> {
+    if !is_c_style_enum(data) {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(variant_names)] is only supported on C-style enums",
+        ));
+    }
+    let names = reflected_variant_names(data)?;
+    let variants = data.variants.iter().map(|variant| &variant.ident);
+    let to_name = variants
+        .clone()
+        .zip(&names)
+        .map(|(variant, reflected_name)| quote!(Self::#variant => #reflected_name));
+    let from_name = variants
+        .zip(&names)
+        .map(|(variant, reflected_name)| quote!(#reflected_name => Some(Self::#variant)));
+    Ok(quote! {
+        impl #name {
+            /// The name of this variant, as recorded in its reflected definition
+            #[allow(dead_code)]
+            pub const fn variant_name(&self) -> &'static str {
+                match *self {
+                    #(#to_name,)*
+                }
+            }
+            /// Find the variant with the specified (reflected) name
+            #[allow(dead_code)]
+            pub fn from_variant_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#from_name,)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+/// Inherent `get_field` and `set_field` methods for each field of a struct (`#[reflect(accessors)]`)
+///
+/// The accessors are plain field accesses, but are checked to be consistent
+/// with the reflected definition of each field: the offsets must be the same,
+/// and the reflected type must have the same size as the real one
+/// (unless the field is an opaque array).
+fn accessor_methods(
+    data: &DataStruct,
+    name: &Ident,
+    krate: &syn::Path,
+    generics: &Generics,
+    options: &AccessorOptions,
+    unsized_tail: bool,
+) -> Result<TokenStream, syn::Error> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let check_name = reserved_ident("ACCESSOR_CHECKS", name.span());
+    let mut methods = Vec::new();
+    let mut checks = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let field_ident = match field.ident {
+            Some(ref ident) => ident,
+            None => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "#[reflect(accessors)] requires named fields",
+                ))
+            }
+        };
+        let field_options = DeriveFieldOptions::parse_attrs(&field.attrs)?;
+        let is_tail = unsized_tail && index + 1 == data.fields.len();
+        let accessor_name = match field_options.rename {
+            Some(ref rename) => {
+                if syn::parse_str::<Ident>(&rename.value()).is_err() {
+                    return Err(syn::Error::new(
+                        rename.span(),
+                        "#[reflect(accessors)] requires renamed fields to be valid identifiers",
+                    ));
+                }
+                rename.value()
+            }
+            None => ident_name(field_ident),
+        };
+        let getter = format_ident!("get_{}", accessor_name, span = field_ident.span());
+        let setter = format_ident!("set_{}", accessor_name, span = field_ident.span());
+        let vis = options.vis.as_ref().unwrap_or(&field.vis);
+        let ty = &field.ty;
+        let span = field.span();
+        let getter_doc = format!("Get the value of the field `{}`", field_ident);
+        let setter_doc = format!("Set the value of the field `{}`", field_ident);
+        methods.push(quote_spanned! { span =>
+            #[doc = #getter_doc]
+            #[inline]
+            #[allow(dead_code)]
+            #vis fn #getter(&self) -> #ty
+            where
+                #ty: Copy,
+            {
+                let () = Self::#check_name;
+                self.#field_ident
+            }
+            #[doc = #setter_doc]
+            #[inline]
+            #[allow(dead_code)]
+            #vis fn #setter(&mut self, value: #ty) {
+                let () = Self::#check_name;
+                self.#field_ident = value;
+            }
+        });
+        let offset_message = escape_format(format!(
+            "Accessor `{}` of `{}` doesn't match the reflected offset of the field",
+            getter, name
+        ));
+        checks.push(quote_spanned! { span =>
+            assert!(
+                <Self as #krate::FieldReflect>::NAMED_FIELD_INFO.#field_ident.offset()
+                    == core::mem::offset_of!(Self, #field_ident),
+                #offset_message
+            );
+        });
+        if !field_options.opaque_array && !is_tail {
+            let size_message = escape_format(format!(
+                "Accessor `{}` of `{}` doesn't match the reflected size of the field (is `assume_repr` correct?)",
+                getter, name
+            ));
+            checks.push(quote_spanned! { span =>
+                assert!(
+                    __reflect_reflected_size(&<Self as #krate::FieldReflect>::NAMED_FIELD_INFO.#field_ident)
+                        == core::mem::size_of::<#ty>(),
+                    #size_message
+                );
+            });
+        }
+    }
+    // Generic types are checked whenever an accessor is instantiated
+    let eager_check = if generics.params.is_empty() {
+        quote!(const _: () = #name::#check_name;)
+    } else {
+        quote!()
     };
-    let static_def = target.create_static_def(header);
-    let into_type = T::def_into_type(quote!(_DEF));
-    Ok(quote!({
-        const _DEF: &'static #type_def_type = &#static_def;
-        #into_type
-    }))
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            const #check_name: () = {
+                const fn __reflect_reflected_size<T: #krate::StaticReflect>(
+                    _field: &#krate::types::FieldDef<T>,
+                ) -> usize {
+                    core::mem::size_of::<T>()
+                }
+                #(#checks)*
+            };
+            #(#methods)*
+        }
+        #eager_check
+    })
 }
 fn is_c_style_enum(data: &DataEnum) -> bool {
     /*
-     * TODO: Should the following be considered a 'c-style' enum?
-     * enum Test {
-     *     One,
-     *     Two {  },
-     *     Three( )
-     * }
+     * NOTE: We require all variants to be unit-variants (like `One`),
+     * so empty variants like `Two { }` or `Three( )` are excluded.
      *
-     * Right now it *is*, because `fields.is_empty`,
-     * although we could require them all to be unit-variants (like `One`)
+     * Only "unit-only" enums support casting to an integer,
+     * which is how we determine the values of the discriminants.
      */
-    data.variants.iter().all(|var| var.fields.is_empty())
+    data.variants
+        .iter()
+        .all(|var| matches!(var.fields, syn::Fields::Unit))
+}
+/// Reflect an enum with one empty variant and one variant wrapping a non-zero type.
+///
+/// The compiler stores the empty variant in the niche of the wrapped type,
+/// which is checked by comparing their sizes.
+fn niche_static_type(
+    data: &DataEnum,
+    repr: Option<Repr>,
+    name: &Ident,
+    krate: &syn::Path,
+) -> Result<TokenStream, syn::Error> {
+    if repr.is_some() {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(niche)] enums can't have an explicit #[repr], which disables the niche optimization",
+        ));
+    }
+    let shape_error = || {
+        syn::Error::new(
+            name.span(),
+            "#[reflect(niche)] requires exactly two variants: one empty, and one with a single field",
+        )
+    };
+    if data.variants.len() != 2 {
+        return Err(shape_error());
+    }
+    let (none_variant, some_variant) = match (&data.variants[0], &data.variants[1]) {
+        (first, second) if first.fields.is_empty() && second.fields.len() == 1 => (first, second),
+        (first, second) if second.fields.is_empty() && first.fields.len() == 1 => (second, first),
+        _ => return Err(shape_error()),
+    };
+    let wrapped_type = &some_variant.fields.iter().next().unwrap().ty;
+    let type_name = ident_name(name);
+    let none_name = ident_name(&none_variant.ident);
+    let some_name = ident_name(&some_variant.ident);
+    let size_message = format!(
+        "The niche optimization doesn't apply to `{}`, so it's larger than the wrapped type",
+        name
+    );
+    Ok(quote!({
+        const fn __reflect_assert_non_zero<T: #krate::types::SimpleNonZeroRepr>() {}
+        __reflect_assert_non_zero::<#wrapped_type>();
+        assert!(
+            std::mem::size_of::<#name>() == std::mem::size_of::<#wrapped_type>(),
+            #size_message
+        );
+        #[allow(non_upper_case_globals)]
+        const __reflect_DEF: &'static #krate::types::NicheDef = &#krate::types::NicheDef {
+            name: #type_name,
+            wrapped: &<#wrapped_type as #krate::StaticReflect>::TYPE_INFO,
+            // Non-zero types always use zero as their niche
+            none_value: 0,
+            none_variant: #none_name,
+            some_variant: #some_name,
+        };
+        #krate::types::TypeInfo::Niche(__reflect_DEF)
+    }))
+}
+/// Reflect a `#[repr(transparent)]` integer newtype as the integer it wraps,
+/// along with its unit (`#[reflect(newtype_unit)]`).
+fn newtype_unit_impl(
+    input: &DeriveInput,
+    repr: Option<Repr>,
+    options: &DeriveContainerOptions,
+    krate: &syn::Path,
+) -> Result<TokenStream, syn::Error> {
+    let name = &input.ident;
+    let error = |message: &str| Err(syn::Error::new(name.span(), message));
+    let field = match input.data {
+        Data::Struct(ref data) if data.fields.len() == 1 => data.fields.iter().next().unwrap(),
+        _ => return error("#[reflect(newtype_unit)] requires a struct with exactly one field"),
+    };
+    if repr != Some(Repr::Transparent) {
+        return error("#[reflect(newtype_unit)] requires #[repr(transparent)]");
+    }
+    if !input.generics.params.is_empty() {
+        return error("#[reflect(newtype_unit)] isn't supported on generic types");
+    }
+    if options.pod
+        || options.minimal
+        || options.unsized_tail
+        || options.schema
+        || options.accessors.is_some()
+        || options.lint.is_some()
+    {
+        return error("#[reflect(newtype_unit)] can't be combined with other layout options");
+    }
+    if let Some(unit) = DeriveFieldOptions::parse_attrs(&field.attrs)?.unit {
+        return Err(syn::Error::new(
+            unit.span(),
+            "The unit of a newtype is given by #[reflect(newtype_unit = \"...\")]",
+        ));
+    }
+    let unit = match options.newtype_unit_name {
+        Some(ref unit) => unit.value(),
+        None => ident_name(name),
+    };
+    let field_type = erase_lifetimes(&field.ty);
+    Ok(quote! {
+        unsafe impl #krate::StaticReflect for #name {
+            const TYPE_INFO: #krate::types::TypeInfo = {
+                // The unit is only meaningful for integers
+                const fn __reflect_assert_int<T: #krate::PrimInt>() {}
+                __reflect_assert_int::<#field_type>();
+                <#field_type as #krate::StaticReflect>::TYPE_INFO
+            };
+            const UNIT: ::core::option::Option<&'static str> = ::core::option::Option::Some(#unit);
+        }
+    })
+}
+/// Eagerly check the definition of a C-style enum is consistent.
+///
+/// Rustc already rejects duplicate discriminants, so this is only a safeguard
+/// against mistakes in the generated definition.
+fn enum_validation(name: &Ident, krate: &syn::Path) -> TokenStream {
+    let message = |problem: &str| escape_format(format!("`{}` {}", name, problem));
+    let duplicate = message("has variants with duplicate discriminants");
+    let out_of_range = message("has a discriminant that doesn't fit in its repr");
+    let index_mismatch = message("has variants with inconsistent indexes");
+    let invalid = message("has an invalid enum definition");
+    quote!(
+        const _: () = {
+            use #krate::types::validate::EnumDefError;
+            if let #krate::types::TypeInfo::CStyleEnum(def) =
+                <#name as #krate::StaticReflect>::TYPE_INFO
+            {
+                match def.validate() {
+                    Ok(()) => {}
+                    Err(EnumDefError::DuplicateDiscriminant { .. }) => panic!(#duplicate),
+                    Err(EnumDefError::DiscriminantOutOfRange { .. }) => panic!(#out_of_range),
+                    Err(EnumDefError::IndexMismatch { .. }) => panic!(#index_mismatch),
+                    Err(_) => panic!(#invalid),
+                }
+            }
+        };
+    )
 }
 fn enum_static_type(
     data: &DataEnum,
     repr: Option<Repr>,
     name: &Ident,
+    krate: &syn::Path,
 ) -> Result<TokenStream, syn::Error> {
     let size = quote!(std::mem::size_of::<#name>());
     let equivalent_integer = match repr {
         Some(Repr::C) => {
-            // Determine the equivalent unsigned integer representation
-            quote!(static_reflect::types::IntType {
-                size: static_reflect::types::IntSize::unwrap_from_bytes(#size),
-                signed: false
+            /*
+             * Like C's `int`, the discriminant of a repr(C) enum is always signed
+             * (even if none of its values are negative).
+             * However, it may be larger than an int if the values don't fit.
+             */
+            quote!(#krate::types::IntType {
+                size: #krate::types::IntSize::unwrap_from_bytes(#size),
+                signed: true
             })
         }
         Some(Repr::Integer { bits, signed }) => {
-            quote!(static_reflect::types::IntType {
-                size: static_reflect::types::IntSize::unwrap_from_bytes(#bits as usize / 8),
+            quote!(#krate::types::IntType {
+                size: #krate::types::IntSize::unwrap_from_bytes(#bits as usize / 8),
+                signed: #signed
+            })
+        }
+        Some(Repr::IntegerPointerSized { signed }) => {
+            quote!(#krate::types::IntType {
+                size: #krate::types::IntSize::POINTER,
                 signed: #signed
             })
         }
         _ => {
             return Err(syn::Error::new(
                 name.span(),
-                "Enum types must be either #[repr(C)] or #[repr(Int)]",
+                "Enum types must be either #[repr(C)] or #[repr(Int)], \
+                where Int is one of u8, u16, u32, u64, usize, i8, i16, i32, i64 or isize",
             ))
         }
     };
     if is_c_style_enum(data) {
-        // C-style enum
-        // TODO: Strict typing
-        Ok(quote!(static_reflect::types::TypeInfo::Integer(#equivalent_integer)))
+        let mut seen_explicit = false;
+        let variants = data
+            .variants
+            .iter()
+            .zip(reflected_variant_names(data)?)
+            .enumerate()
+            .map(|(index, (variant, reflected_name))| {
+                let variant_name = &variant.ident;
+                /*
+                 * We can't evaluate the discriminant expressions ourselves,
+                 * so we cast the variant to an integer in the generated code.
+                 * Truncating to the discriminant's width gives the two's complement bits.
+                 */
+                let bits = quote!(__reflect_DISCRIMINANT.truncate_bits(#name::#variant_name as i64 as u64));
+                let discriminant = if variant.discriminant.is_some() {
+                    seen_explicit = true;
+                    quote!(#krate::types::DiscriminantValue::ExplicitInteger { bits: #bits })
+                } else if seen_explicit {
+                    quote!(#krate::types::DiscriminantValue::ImplicitlyOffset { bits: #bits })
+                } else {
+                    quote!(#krate::types::DiscriminantValue::Default { declaration_index: #index })
+                };
+                quote!(#krate::types::CStyleEnumVariant {
+                    index: #index,
+                    name: #reflected_name,
+                    discriminant: #discriminant,
+                })
+            })
+            .collect::<Vec<_>>();
+        let type_name = ident_name(name);
+        Ok(quote!({
+            #[allow(non_upper_case_globals)]
+            const __reflect_DISCRIMINANT: #krate::types::IntType = #equivalent_integer;
+            #[allow(non_upper_case_globals)]
+            const __reflect_DEF: &'static #krate::types::CStyleEnumDef = &#krate::types::CStyleEnumDef {
+                name: #type_name,
+                discriminant: __reflect_DISCRIMINANT,
+                variants: &[#(#variants),*],
+                valid_range: None,
+                has_gaps: false,
+            }.compute_flags();
+            #krate::types::TypeInfo::CStyleEnum(__reflect_DEF)
+        }))
     } else {
         Err(syn::Error::new(
             Span::call_site(),
@@ -286,11 +1371,28 @@ fn enum_static_type(
 }
 trait TypeHandler<'a> {
     fn is_tuple_style(&self) -> bool;
-    fn field_def_type(field_type: Option<TokenStream>) -> TokenStream;
-    fn type_def_type() -> TokenStream;
-    fn def_into_type(def_ref: TokenStream) -> TokenStream;
-    fn handle_fields<F: FnMut(FieldInfo<'a>)>(&mut self, handler: F) -> syn::Result<()>;
+    fn field_def_type(krate: &syn::Path, field_type: Option<TokenStream>) -> TokenStream;
+    fn type_def_type(krate: &syn::Path) -> TokenStream;
+    fn def_into_type(krate: &syn::Path, def_ref: TokenStream) -> TokenStream;
+    fn handle_fields<F: FnMut(FieldInfo<'a>) -> syn::Result<()>>(
+        &mut self,
+        handler: F,
+    ) -> syn::Result<()>;
+    /// The declaration and definition of any associated consts describing the layout,
+    /// which are added to the hidden field trait
+    fn layout_consts(&self) -> (TokenStream, TokenStream) {
+        (quote!(), quote!())
+    }
     fn create_static_def(self, header: TokenStream) -> TokenStream;
+    /// Additional items of the `FieldReflect` impl
+    fn field_reflect_items(&self) -> syn::Result<TokenStream> {
+        Ok(quote!())
+    }
+    /// The additional consts of the `FieldReflect` impl which may fail to evaluate,
+    /// so they need to be checked even if they're never used
+    fn checked_field_reflect_consts(&self) -> Vec<Ident> {
+        Vec::new()
+    }
 }
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum FieldName<'a> {
@@ -307,143 +1409,311 @@ impl FieldName<'_> {
             FieldName::Named { name } => quote!(#name),
         }
     }
+    /// The name of the field, as it's reflected at runtime
+    ///
+    /// Tuple fields are named by their index.
+    pub fn display_name(&self) -> String {
+        match *self {
+            FieldName::Tuple { index } => index.to_string(),
+            FieldName::Named { name } => ident_name(name),
+        }
+    }
     pub fn associated_type_name(&self) -> Ident {
         match *self {
-            FieldName::Tuple { index } => format_ident!("_Tuple_{}", index),
+            FieldName::Tuple { index } => format_ident!("{}Tuple_{}", RESERVED_PREFIX, index),
             FieldName::Named { name } => name.clone(),
         }
     }
 }
 struct FieldInfo<'a> {
     name: FieldName<'a>,
+    /// The name recorded in the field's definition (after renaming),
+    /// or `None` for tuple fields
+    reflected_name: Option<String>,
+    span: Span,
     static_type: Type,
     static_def: TokenStream,
+    /// Compile-time assertions about the field's layout (if any)
+    layout_check: Option<TokenStream>,
+    /// The FFI safety of the field, as an expression of type `FfiSafety`
+    ffi_safety: TokenStream,
 }
 struct StructHandler<'a> {
-    name: &'a Ident,
-    data: &'a DataStruct,
-    current_offset: TokenStream,
+    analyzed: AnalyzedStruct<'a>,
+    krate: &'a syn::Path,
+    /// The hidden trait holding the computed offsets
+    field_trait: Ident,
 }
 impl<'a> StructHandler<'a> {
-    fn new(data: &'a DataStruct, name: &'a Ident) -> Self {
+    fn new(analyzed: AnalyzedStruct<'a>, krate: &'a syn::Path) -> Self {
         StructHandler {
-            name,
-            data,
-            current_offset: quote!(0),
+            field_trait: field_trait_name(analyzed.name),
+            analyzed,
+            krate,
         }
     }
 }
 impl<'a> TypeHandler<'a> for StructHandler<'a> {
     fn is_tuple_style(&self) -> bool {
-        matches!(self.data.fields, syn::Fields::Unnamed(_))
+        self.analyzed.is_tuple_style()
     }
 
-    fn field_def_type(field_type: Option<TokenStream>) -> TokenStream {
+    fn field_def_type(krate: &syn::Path, field_type: Option<TokenStream>) -> TokenStream {
         match field_type {
-            Some(inner) => quote!(static_reflect::types::FieldDef<#inner>),
-            None => quote!(static_reflect::types::FieldDef),
+            Some(inner) => quote!(#krate::types::FieldDef<#inner>),
+            None => quote!(#krate::types::FieldDef),
         }
     }
 
-    fn type_def_type() -> TokenStream {
-        quote!(static_reflect::types::StructureDef)
+    fn type_def_type(krate: &syn::Path) -> TokenStream {
+        quote!(#krate::types::StructureDef)
     }
 
-    fn def_into_type(def_ref: TokenStream) -> TokenStream {
-        quote!(static_reflect::types::TypeInfo::Structure(#def_ref))
+    fn def_into_type(krate: &syn::Path, def_ref: TokenStream) -> TokenStream {
+        quote!(#krate::types::TypeInfo::Structure(#def_ref))
     }
 
-    fn handle_fields<F: FnMut(FieldInfo<'a>)>(&mut self, mut handler: F) -> syn::Result<()> {
+    fn handle_fields<F: FnMut(FieldInfo<'a>) -> syn::Result<()>>(
+        &mut self,
+        mut handler: F,
+    ) -> syn::Result<()> {
+        let krate = self.krate;
+        let name = self.analyzed.name;
+        let minimal = self.analyzed.options.minimal;
         /*
-         * NOTE: Layout algorithm for repr(C) given in reference
-         * https://doc.rust-lang.org/reference/type-layout.html#reprc-structs
-         * We have to use recursion to compute offsets :(
+         * The options, types and offsets of the fields are resolved by `analyze_struct`,
+         * which is shared with other macros so they agree with our layout.
+         * The offsets are computed all at once by `layout_consts`.
          */
-        let mut current_offset = self.current_offset.clone();
-        for (index, field) in self.data.fields.iter().enumerate() {
-            let DeriveFieldOptions {
-                opaque_array,
-                assume_repr,
-            } = DeriveFieldOptions::parse_attrs(&field.attrs)?;
-            let field_name = match field.ident {
+        let field_trait = &self.field_trait;
+        for field in &self.analyzed.fields {
+            let index = field.index;
+            let field_name = match field.field.ident {
                 Some(ref name) => FieldName::Named { name },
                 None => FieldName::Tuple { index },
             };
-            let mut field_type = field.ty.clone();
-            let original_type = field_type.clone();
-            if opaque_array {
-                if index + 1 != self.data.fields.len() {
-                    return Err(syn::Error::new(
-                        field.span(),
-                        "Opaque array must be last field",
-                    ));
-                }
-                match field_type.clone() {
-                    Type::Array(array) => {
-                        field_type = *array.elem;
-                    }
-                    _ => {
-                        return Err(syn::Error::new(
-                            field.span(),
-                            "Type must be an array to be marked 'opaque_array'",
-                        ))
-                    }
-                }
+            let field_type = &field.reflected_type;
+            let original_type = &field.declared_type;
+            let DeriveFieldOptions {
+                ref assume_repr,
+                assume_repr_unchecked,
+                ref endian,
+                ref ptr,
+                ref bitset,
+                ..
+            } = field.options;
+            let opaque_array = field.opaque_array;
+            let assume_repr_used = assume_repr.is_some();
+            let mut layout_check = None;
+            if assume_repr_used && !assume_repr_unchecked {
+                layout_check = Some(assume_repr_layout_check(
+                    &field_name,
+                    field.field.span(),
+                    original_type,
+                    field_type,
+                ));
             }
-            if let Some(assumed_type) = assume_repr {
-                field_type = assumed_type;
+            let name_field_value = match field.reflected_name {
+                Some(ref reflected_name) if !minimal => quote!(Some(#reflected_name)),
+                _ => quote!(None),
+            };
+            let endianness = match endian {
+                Some(endian) => quote!({
+                    // Byte order is meaningless for anything but numbers
+                    const fn __reflect_assert_number<T: #krate::PrimNumber>() {}
+                    __reflect_assert_number::<#field_type>();
+                    Some(#krate::types::Endian::#endian)
+                }),
+                None => quote!(None),
+            };
+            // Calls to the `with_*` methods of the `FieldMeta`
+            let mut meta = Vec::new();
+            if let Some(ref discipline) = ptr {
+                let check =
+                    pointer_field_check(krate, name, &field_name, field.field.span(), field_type);
+                layout_check = Some(quote!(#layout_check #check));
+                meta.push(quote!(.with_pointer_discipline(
+                    #krate::types::PointerDiscipline::#discipline
+                )));
             }
-            /*
-             * If the current offset is not a multiple of the field's alignment,
-             * add the necessary padding bytes.
-             */
-            current_offset = quote!({
-                let old_offset = #current_offset;
-                /*
-                 * NOTE: Must use #original_type instead of #field_type
-                 * There is a chance an option like #[opaque_array] changed the size
-                 */
-                let rem = old_offset % std::mem::align_of::<#original_type>();
-                old_offset + (if rem == 0 { 0 } else { std::mem::align_of::<#original_type>() - rem })
-            });
-            let name_field_value = match field_name {
-                FieldName::Tuple { .. } => quote!(None),
-                FieldName::Named { name } => quote!(Some(stringify!(#name))),
+            if let Some(ref bitset) = bitset {
+                let check = bitset_field_check(
+                    krate,
+                    name,
+                    &field_name,
+                    bitset.span,
+                    (field_type, original_type),
+                    bitset.names.len(),
+                );
+                layout_check = Some(quote!(#layout_check #check));
+                let names = &bitset.names;
+                meta.push(quote!(.with_bitset(#krate::types::bitset::BitsetDef {
+                    names: &[#(#names),*],
+                })));
+            }
+            let ffi_safety = ffi_safety_expr(
+                krate,
+                &format!("field `{}.{}`", ident_name(name), field_name.display_name()),
+                original_type,
+                field_type,
+            );
+            let meta = if meta.is_empty() {
+                quote!(&#krate::types::FieldMeta::NONE)
+            } else {
+                quote!({
+                    const META: #krate::types::FieldMeta = #krate::types::FieldMeta::NONE #(#meta)*;
+                    &META
+                })
             };
-            let static_def = quote!(::static_reflect::types::FieldDef {
+            let offset_index = Literal::usize_unsuffixed(index);
+            let static_def = quote!(#krate::types::FieldDef {
                 name: #name_field_value,
-                value_type: ::static_reflect::types::TypeId::<#field_type>::get(),
-                offset: #current_offset,
-                index: #index
+                value_type: #krate::types::TypeId::<#field_type>::get(),
+                offset: <Self as #field_trait>::__reflect_OFFSETS[#offset_index],
+                index: #index,
+                endianness: #endianness,
+                assumed: #assume_repr_used,
+                opaque_array: #opaque_array,
+                meta: #meta,
             });
             handler(FieldInfo {
                 name: field_name,
-                static_type: field_type,
+                reflected_name: field.reflected_name.clone(),
+                span: field.field.span(),
+                static_type: field_type.clone(),
                 static_def,
-            });
-            // NOTE: Must use size_of<#original_type> (See above)
-            current_offset = quote!((#current_offset) + std::mem::size_of::<#original_type>());
+                layout_check,
+                ffi_safety,
+            })?;
         }
-        self.current_offset = current_offset;
         Ok(())
     }
 
+    fn layout_consts(&self) -> (TokenStream, TokenStream) {
+        // The offset of each field, followed by the end of the last field
+        let offsets_len = Literal::usize_unsuffixed(self.analyzed.fields.len() + 1);
+        let offsets = self.analyzed.offsets_expr();
+        let decl = quote!(
+            const __reflect_OFFSETS: [usize; #offsets_len];
+        );
+        let def = quote!(
+            const __reflect_OFFSETS: [usize; #offsets_len] = #offsets;
+        );
+        (decl, def)
+    }
+
+    fn field_reflect_items(&self) -> syn::Result<TokenStream> {
+        let krate = self.krate;
+        // The macros expand to the location of their span, which is the name of the type
+        let source = quote_spanned!(self.analyzed.name.span() => #krate::types::SourceLocation {
+            file: ::core::file!(),
+            line: ::core::line!(),
+        });
+        let dynamic_size = self.analyzed.len_field.map(|index| {
+            quote!(
+                const DYNAMIC_SIZE: ::core::option::Option<#krate::types::DynamicSize> =
+                    ::core::option::Option::Some(#krate::types::DynamicSize::for_tail(
+                        &<Self as #krate::StaticReflect>::TYPE_INFO,
+                        #index,
+                    ));
+            )
+        });
+        // Units are optional metadata, so they're omitted by the minimal profile
+        let field_units = if self.analyzed.options.minimal {
+            None
+        } else {
+            let units = self.analyzed.fields.iter().map(|field| {
+                let field_type = &field.reflected_type;
+                match field.options.unit {
+                    Some(ref unit) => quote!({
+                        // Only plain integers can be given a unit
+                        const fn __reflect_assert_int<T: #krate::PrimInt>() {}
+                        __reflect_assert_int::<#field_type>();
+                        ::core::option::Option::Some(#unit)
+                    }),
+                    None => quote!(<#field_type as #krate::StaticReflect>::UNIT),
+                }
+            });
+            Some(quote!(
+                const FIELD_UNITS: &'static [::core::option::Option<&'static str>] =
+                    #krate::types::known_units(&[#(#units),*]);
+            ))
+        };
+        Ok(quote!(
+            const SOURCE: ::core::option::Option<#krate::types::SourceLocation> =
+                ::core::option::Option::Some(#source);
+            #dynamic_size
+            #field_units
+        ))
+    }
+
+    fn checked_field_reflect_consts(&self) -> Vec<Ident> {
+        match self.analyzed.len_field {
+            Some(_) => vec![format_ident!("DYNAMIC_SIZE")],
+            None => Vec::new(),
+        }
+    }
+
     fn create_static_def(self, header: TokenStream) -> TokenStream {
-        let name = self.name;
-        let current_offset = &self.current_offset;
+        let name = self.analyzed.name;
+        let field_trait = &self.field_trait;
+        let len = Literal::usize_unsuffixed(self.analyzed.fields.len());
+        let is_pod = self.analyzed.options.pod;
+        let has_unsized_tail = self.analyzed.options.unsized_tail;
+        let krate = self.krate;
+        let (size_message, align_message) = layout_mismatch_messages(name);
+        let min_alignment = min_alignment(self.analyzed.align);
+        /*
+         * With an unsized tail, only the header is reflected.
+         * The per-field offset checks already verify the header's layout,
+         * so we only need to check it fits (rustc may pad after a `[T; 0]` tail).
+         */
+        let (reflected_size, size_check) = if has_unsized_tail {
+            let tail = Literal::usize_unsuffixed(self.analyzed.fields.len() - 1);
+            (
+                quote!(<Self as #field_trait>::__reflect_OFFSETS[#tail]),
+                quote!(def.size > size_of::<Self>()),
+            )
+        } else {
+            (quote!(size_of::<Self>()), quote!(def.size != expected_size))
+        };
+        let reflected_name = if self.analyzed.options.minimal {
+            let hash = format!("{:08x}", fnv1a_hash(&ident_name(name)));
+            quote!(#hash)
+        } else {
+            let name = ident_name(name);
+            quote!(#name)
+        };
+        let assert_pod = if is_pod {
+            // Point at the name of the type if it doesn't implement `Pod`
+            let self_ty = quote_spanned!(name.span() => Self);
+            quote! {
+                const fn __reflect_assert_pod<T: #krate::bytemuck::Pod>() {}
+                __reflect_assert_pod::<#self_ty>();
+            }
+        } else {
+            quote!()
+        };
         quote!({
-            use std::mem::{size_of, align_of};
             #header
+            #assert_pod
             let def = StructureDef {
-                name: stringify!(#name),
-                fields: _FIELDS,
-                size: size_of::<#name>(),
-                alignment: align_of::<#name>(),
+                name: #reflected_name,
+                fields: __reflect_fields,
+                size: #reflected_size,
+                alignment: align_of::<Self>(),
+                is_pod: #is_pod,
+                has_unsized_tail: #has_unsized_tail,
+                needs_drop: ::core::mem::needs_drop::<Self>(),
+            };
+            let current_offset = <Self as #field_trait>::__reflect_OFFSETS[#len];
+            // Round up to the next multiple of the alignment (trailing padding)
+            let expected_size = {
+                let rem = current_offset % align_of::<Self>();
+                current_offset + (if rem == 0 { 0 } else { align_of::<Self>() - rem })
             };
-            let current_offset = #current_offset;
-            let expected_size = current_offset + current_offset % align_of::<#name>();
-            // In the case of zero-fields, default to alignment of `()`
-            let mut expected_alignment = align_of::<()>();
+            // In the case of zero-fields, default to alignment of `()` (or the explicit alignment)
+            let mut expected_alignment = #min_alignment;
             {
                 // NOTE: Can't use for-loop since iterators aren't const
                 let mut index = 0;
@@ -455,11 +1725,11 @@ impl<'a> TypeHandler<'a> for StructHandler<'a> {
                     index += 1;
                 }
             }
-            if def.size != expected_size {
-                panic!("Mismatched size");
+            if #size_check {
+                panic!(#size_message);
             }
             if def.alignment != expected_alignment {
-                panic!("Mismatched alignments")
+                panic!(#align_message)
             }
             def
         })
@@ -468,28 +1738,35 @@ impl<'a> TypeHandler<'a> for StructHandler<'a> {
 struct UnionTypeHandler<'a> {
     data: &'a DataUnion,
     name: &'a Ident,
+    krate: &'a syn::Path,
+    /// The minimum alignment from `#[repr(align(N))]`
+    align: Option<usize>,
 }
 impl<'a> TypeHandler<'a> for UnionTypeHandler<'a> {
     fn is_tuple_style(&self) -> bool {
         false // unions can't have tuple-fields
     }
 
-    fn field_def_type(field_type: Option<TokenStream>) -> TokenStream {
+    fn field_def_type(krate: &syn::Path, field_type: Option<TokenStream>) -> TokenStream {
         match field_type {
-            None => quote!(static_reflect::types::UnionFieldDef),
-            Some(inner) => quote!(static_reflect::types::UnionFieldDef<#inner>),
+            None => quote!(#krate::types::UnionFieldDef),
+            Some(inner) => quote!(#krate::types::UnionFieldDef<#inner>),
         }
     }
 
-    fn type_def_type() -> TokenStream {
-        quote!(static_reflect::types::UntaggedUnionDef)
+    fn type_def_type(krate: &syn::Path) -> TokenStream {
+        quote!(#krate::types::UntaggedUnionDef)
     }
 
-    fn def_into_type(def_ref: TokenStream) -> TokenStream {
-        quote!(static_reflect::types::TypeInfo::UntaggedUnion(#def_ref))
+    fn def_into_type(krate: &syn::Path, def_ref: TokenStream) -> TokenStream {
+        quote!(#krate::types::TypeInfo::UntaggedUnion(#def_ref))
     }
 
-    fn handle_fields<F: FnMut(FieldInfo<'a>)>(&mut self, mut handler: F) -> syn::Result<()> {
+    fn handle_fields<F: FnMut(FieldInfo<'a>) -> syn::Result<()>>(
+        &mut self,
+        mut handler: F,
+    ) -> syn::Result<()> {
+        let krate = self.krate;
         /*
          * NOTE: Layout algorithm for repr(C) given in reference
          * https://doc.rust-lang.org/reference/type-layout.html#reprc-unions
@@ -500,6 +1777,13 @@ impl<'a> TypeHandler<'a> for UnionTypeHandler<'a> {
             let DeriveFieldOptions {
                 opaque_array,
                 assume_repr,
+                assume_repr_unchecked,
+                rename,
+                endian,
+                ptr,
+                bitset,
+                unit,
+                ..
             } = DeriveFieldOptions::parse_attrs(&field.attrs)?;
             if opaque_array {
                 return Err(syn::Error::new(
@@ -507,45 +1791,123 @@ impl<'a> TypeHandler<'a> for UnionTypeHandler<'a> {
                     "opaque_array is not supported on unions",
                 ));
             }
+            if let Some(ptr) = ptr {
+                return Err(syn::Error::new(
+                    ptr.span(),
+                    "ptr is not supported on unions",
+                ));
+            }
+            if let Some(endian) = endian {
+                return Err(syn::Error::new(
+                    endian.span(),
+                    "endian is not supported on unions",
+                ));
+            }
+            if let Some(bitset) = bitset {
+                return Err(syn::Error::new(
+                    bitset.span,
+                    "bitset is not supported on unions",
+                ));
+            }
+            if let Some(unit) = unit {
+                return Err(syn::Error::new(
+                    unit.span(),
+                    "unit is not supported on unions",
+                ));
+            }
             let field_name = field.ident.as_ref().expect("Need named fields");
-            let mut field_type = field.ty.clone();
-            if let Some(assumed_type) = assume_repr {
+            let mut field_type = erase_lifetimes(&field.ty);
+            let declared_type = field_type.clone();
+            let mut layout_check = None;
+            if let Some(assumed_type) = assume_repr.as_ref().map(erase_lifetimes) {
+                if !assume_repr_unchecked {
+                    layout_check = Some(assume_repr_layout_check(
+                        &FieldName::Named { name: field_name },
+                        field.span(),
+                        &field_type,
+                        &assumed_type,
+                    ));
+                }
                 field_type = assumed_type;
             }
-            let static_def = quote!(::static_reflect::types::UnionFieldDef {
-                name: stringify!(#field_name),
-                value_type: ::static_reflect::types::TypeId::<#field_type>::get(),
+            let reflected_name = match rename {
+                Some(ref rename) => rename.value(),
+                None => ident_name(field_name),
+            };
+            let name_value = match rename {
+                Some(rename) => quote!(#rename),
+                None => quote!(#reflected_name),
+            };
+            let static_def = quote!(#krate::types::UnionFieldDef {
+                name: #name_value,
+                value_type: #krate::types::TypeId::<#field_type>::get(),
                 index: #index
             });
+            let ffi_safety = ffi_safety_expr(
+                krate,
+                &format!("field `{}.{}`", ident_name(self.name), reflected_name),
+                &declared_type,
+                &field_type,
+            );
             handler(FieldInfo {
                 name: FieldName::Named { name: field_name },
+                reflected_name: Some(reflected_name),
+                span: field.span(),
                 static_type: field_type,
                 static_def,
-            });
+                layout_check,
+                ffi_safety,
+            })?;
         }
         Ok(())
     }
 
     fn create_static_def(self, header: TokenStream) -> TokenStream {
+        let krate = self.krate;
+        self.union_def(
+            &quote!(Self),
+            header,
+            quote!(#krate::types::UnionFields::Eager(__reflect_fields)),
+            quote!(&__reflect_fields[index]),
+        )
+    }
+}
+impl<'a> UnionTypeHandler<'a> {
+    /// The definition of the union, checking its layout against its fields
+    ///
+    /// The fields are checked through `field`, which is an expression
+    /// giving a reference to the field with the specified `index`.
+    fn union_def(
+        &self,
+        self_ty: &TokenStream,
+        header: TokenStream,
+        fields: TokenStream,
+        field: TokenStream,
+    ) -> TokenStream {
         let name = self.name;
+        let krate = self.krate;
+        let (size_message, align_message) = layout_mismatch_messages(name);
+        let min_alignment = min_alignment(self.align);
+        let type_name = ident_name(name);
         quote!({
-            use std::mem::{size_of, align_of};
             #header
             let def = UntaggedUnionDef {
-                name: stringify!(#name),
-                fields: _FIELDS,
-                size: size_of::<#name>(),
-                alignment: align_of::<#name>(),
+                name: #type_name,
+                fields: #fields,
+                size: size_of::<#self_ty>(),
+                alignment: align_of::<#self_ty>(),
+                needs_drop: ::core::mem::needs_drop::<#self_ty>(),
             };
             // In the case of zero-fields, default to alignment and size of `()`
-            let mut expected_alignment = align_of::<()>();
+            let mut expected_alignment = #min_alignment;
             let mut expected_size = size_of::<()>();
             {
                 // NOTE: Can't use for-loop since iterators aren't const
                 let mut index = 0;
                 while index < def.fields.len() {
-                    let alignment = def.fields[index].value_type.type_ref().alignment();
-                    let size = def.fields[index].value_type.type_ref().size();
+                    let field: &#krate::types::UnionFieldDef = #field;
+                    let alignment = field.value_type.type_ref().alignment();
+                    let size = field.value_type.type_ref().size();
                     if alignment > expected_alignment {
                         expected_alignment = alignment;
                     }
@@ -563,16 +1925,98 @@ impl<'a> TypeHandler<'a> for UnionTypeHandler<'a> {
                 }
             }
             if def.size != expected_size {
-                panic!("Mismatched size");
+                panic!(#size_message);
             }
             if def.alignment != expected_alignment {
-                panic!("Mismatched alignments")
+                panic!(#align_message)
             }
             def
         })
     }
 }
 
+/// The static type of a union derived with `#[reflect(lazy_fields)]`
+///
+/// Instead of the array of fields (and the `NamedFieldInfo` it's built from),
+/// this generates a function matching each index to its field,
+/// with every field in a separate constant.
+/// Lazy unions are never generic, so everything can name the type directly.
+fn lazy_union_static_type(
+    mut target: UnionTypeHandler,
+    items: &mut GeneratedItems,
+) -> Result<TokenStream, syn::Error> {
+    let name = target.name;
+    let krate = target.krate;
+    let lookup = reserved_ident(format_args!("lazy_fields_{}", name), name.span());
+    let mut reflected_names = HashMap::new();
+    let mut layout_checks = Vec::new();
+    let mut arms = Vec::new();
+    let mut ffi_safety = Vec::new();
+    target.handle_fields(|field| {
+        check_field_name(&field, &mut reflected_names)?;
+        layout_checks.extend(field.layout_check);
+        ffi_safety.push(field.ffi_safety);
+        let index = arms.len();
+        let def = field.static_def;
+        arms.push(quote!(#index => {
+            const FIELD: #krate::types::UnionFieldDef = #def.erase();
+            Some(&FIELD)
+        }));
+        Ok(())
+    })?;
+    if !layout_checks.is_empty() {
+        items.checks.push(quote!(
+            const _: () = {
+                #(#layout_checks)*
+            };
+        ));
+    }
+    items
+        .reflect_items
+        .push(ffi_safety_item(krate, &ffi_safety));
+    let count = arms.len();
+    items.impls.push(quote!(
+        #[allow(non_snake_case)]
+        #[doc(hidden)]
+        const fn #lookup(index: usize) -> Option<&'static #krate::types::UnionFieldDef> {
+            match index {
+                #(#arms)*
+                _ => None,
+            }
+        }
+    ));
+    let header = quote! {
+        use ::core::mem::{align_of, size_of};
+        use #krate::types::UntaggedUnionDef;
+    };
+    let def = target.union_def(
+        &quote!(#name),
+        header,
+        quote!(#krate::types::UnionFields::Lazy {
+            count: #count,
+            lookup: #lookup,
+        }),
+        quote!(match #lookup(index) {
+            Some(field) => field,
+            None => panic!("Missing field"),
+        }),
+    );
+    Ok(quote!({
+        #[allow(non_upper_case_globals)]
+        const __reflect_DEF: #krate::types::UntaggedUnionDef = #def;
+        #krate::types::TypeInfo::UntaggedUnion(&__reflect_DEF)
+    }))
+}
+
+/// The alignment of a type before considering its fields,
+/// which is only more than one with `#[repr(align(N))]`
+fn min_alignment(align: Option<usize>) -> TokenStream {
+    match align {
+        Some(align) => quote!(#align),
+        None => quote!(align_of::<()>()),
+    }
+}
+
 fn add_type_bounds(generics: &Generics, bounds: &[TypeParamBound]) -> Generics {
     let mut generics = generics.clone();
     for param in &mut generics.params {
@@ -1,16 +1,22 @@
 use itertools::Itertools;
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
 use quote::{ToTokens, TokenStreamExt};
 use syn::parse::{self, Parse, ParseStream};
 use syn::spanned::Spanned;
 use syn::Signature;
 use syn::{
-    Attribute, Error, Expr, FnArg, ForeignItem, Item, ItemFn, ItemForeignMod, Lit, Meta,
-    ReturnType, Type,
+    Attribute, Error, Expr, FnArg, ForeignItem, Item, ItemFn, ItemForeignMod, Lit, Meta, Pat,
+    PatType, ReturnType, Token, Type,
 };
 
+use super::ffi::ffi_safety_expr;
+use super::{crate_path, ident_name};
+use static_reflect_internals::options::pointer_discipline_variant;
+
 const FUNC_ATTR_NAME: &str = "reflect_func";
+/// The attribute used to annotate individual parameters
+const PARAM_ATTR_NAME: &str = "reflect";
 
 #[derive(Debug)]
 #[non_exhaustive]
@@ -18,6 +24,21 @@ pub struct FuncArgs {
     /// Link against the hardcoded/absolute address
     /// instead of using dynamic linking
     pub absolute: bool,
+    /// An explicit path to the `static_reflect` crate (`crate = "..."`)
+    pub crate_path: Option<syn::Path>,
+    /// The shared library expected to define the function (`library = "..."`)
+    pub library: Option<syn::LitStr>,
+    /// The function may be missing at runtime
+    pub weak: bool,
+    /// The function may unwind, even though it uses the `"C"` ABI
+    pub may_unwind: bool,
+    /// Whether the function is documented to be thread-safe
+    /// (`thread_safe` or `not_thread_safe`)
+    pub thread_safety: Option<ThreadSafety>,
+    /// The name of a struct of function pointers to generate (`vtable = "..."`)
+    pub vtable: Option<Ident>,
+    /// Add the function to the global registry (`static_reflect::funcs::registry`)
+    pub register: bool,
 }
 
 impl Parse for FuncArgs {
@@ -25,19 +46,69 @@ impl Parse for FuncArgs {
         let mut args = FuncArgs {
             // By default, we want to use dynamic linking
             absolute: false,
+            crate_path: None,
+            library: None,
+            weak: false,
+            may_unwind: false,
+            thread_safety: None,
+            vtable: None,
+            register: false,
         };
         while !input.is_empty() {
-            if input.peek(syn::Ident) {
+            if input.peek(Token![crate]) {
+                let keyword = input.parse::<Token![crate]>()?;
+                if args.crate_path.is_some() {
+                    return Err(Error::new(keyword.span, "Duplicate crate option"));
+                }
+                input.parse::<Token![=]>()?;
+                args.crate_path = Some(input.parse::<syn::LitStr>()?.parse::<syn::Path>()?);
+            } else if input.peek(syn::Ident) {
                 let ident = input.parse::<Ident>()?;
                 match &*ident.to_string() {
                     "absolute" => {
                         args.absolute = true;
                     }
+                    "weak" => {
+                        args.weak = true;
+                    }
+                    "may_unwind" => {
+                        args.may_unwind = true;
+                    }
+                    "register" => {
+                        args.register = true;
+                    }
+                    "thread_safe" | "not_thread_safe" => {
+                        if args.thread_safety.is_some() {
+                            return Err(Error::new(ident.span(), "Duplicate thread safety option"));
+                        }
+                        args.thread_safety = Some(if ident == "thread_safe" {
+                            ThreadSafety::Safe
+                        } else {
+                            ThreadSafety::Unsafe
+                        });
+                    }
+                    "library" => {
+                        if args.library.is_some() {
+                            return Err(Error::new(ident.span(), "Duplicate library option"));
+                        }
+                        input.parse::<Token![=]>()?;
+                        args.library = Some(input.parse::<syn::LitStr>()?);
+                    }
+                    "vtable" => {
+                        if args.vtable.is_some() {
+                            return Err(Error::new(ident.span(), "Duplicate vtable option"));
+                        }
+                        input.parse::<Token![=]>()?;
+                        args.vtable = Some(input.parse::<syn::LitStr>()?.parse::<Ident>()?);
+                    }
                     _ => return Err(input.error(format_args!("Invalid flag: {}", ident))),
                 }
             } else {
                 return Err(input.error("Unexpected token"));
             }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
         Ok(args)
     }
@@ -53,6 +124,12 @@ struct FunctionDefOpts {
     location: FunctionLocation,
     /// Whether the function is considered unsafe
     is_unsafe: bool,
+    /// The function was marked `#[reflect_func(may_unwind)]`
+    may_unwind: bool,
+    /// The function was marked `#[reflect_func(thread_safe)]` or `#[reflect_func(not_thread_safe)]`
+    thread_safety: ThreadSafety,
+    /// The path to the `static_reflect` crate
+    krate: syn::Path,
 }
 
 /// Ensure that the function is either marked `#[no_mangle]`
@@ -100,6 +177,207 @@ fn determine_foreign_link_name(attrs: &[Attribute]) -> Result<Option<String>, sy
     Ok(None)
 }
 
+/// The direction of a parameter (see `static_reflect::funcs::ParamDirection`)
+#[derive(Debug, Clone, Copy)]
+enum ParamDirection {
+    In,
+    Out,
+    InOut,
+}
+
+/// The options of a parameter, from its `#[reflect(...)]` attributes
+#[derive(Debug, Clone)]
+struct ParamOptions {
+    direction: ParamDirection,
+    /// The pointer must not be null (`non_null`)
+    non_null: bool,
+    /// The integer must be within the inclusive range (`range(min, max)`)
+    range: Option<(i128, i128)>,
+    /// The variant of the pointer's `PointerDiscipline` (`ptr = "..."`)
+    pointer_discipline: Option<&'static str>,
+    /// The semantic unit of the integer (`unit = "..."`)
+    unit: Option<syn::LitStr>,
+}
+impl ParamOptions {
+    /// The `static_reflect::funcs::ArgContract` of the parameter,
+    /// or `None` if it's unconstrained
+    fn contract(&self, krate: &syn::Path) -> Option<TokenStream> {
+        if !self.non_null && self.range.is_none() && self.pointer_discipline.is_none() {
+            return None;
+        }
+        let non_null = self.non_null;
+        let range = match self.range {
+            Some((min, max)) => {
+                let (min, max) = (
+                    proc_macro2::Literal::i128_suffixed(min),
+                    proc_macro2::Literal::i128_suffixed(max),
+                );
+                quote!(Some((#min, #max)))
+            }
+            None => quote!(None),
+        };
+        let pointer_discipline = match self.pointer_discipline {
+            Some(variant) => {
+                let variant = Ident::new(variant, Span::call_site());
+                quote!(Some(#krate::types::PointerDiscipline::#variant))
+            }
+            None => quote!(None),
+        };
+        Some(quote!(#krate::funcs::ArgContract {
+            non_null: #non_null,
+            range: #range,
+            pointer_discipline: #pointer_discipline,
+        }))
+    }
+}
+
+/// The primitive integer types, which support `range(min, max)`
+const INTEGER_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize",
+];
+
+/// If the type is one of the [INTEGER_TYPES]
+fn is_primitive_integer(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .is_some_and(|ident| INTEGER_TYPES.contains(&&*ident.to_string())),
+        _ => false,
+    }
+}
+
+/// Parse a (possibly negative) integer literal
+fn parse_signed_int(input: ParseStream) -> Result<i128, Error> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let lit: syn::LitInt = input.parse()?;
+    let value: i128 = lit.base10_parse()?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Parse the options of the parameter from its `#[reflect(...)]` attributes
+fn parse_param_options(param: &PatType) -> Result<ParamOptions, Error> {
+    let mut direction = None;
+    let mut non_null = false;
+    let mut range = None;
+    let mut pointer_discipline = None;
+    let mut unit = None;
+    for attr in &param.attrs {
+        if attr.path().is_ident(PARAM_ATTR_NAME) {
+            attr.parse_nested_meta(|meta| {
+                let parsed = if meta.path.is_ident("out") {
+                    ParamDirection::Out
+                } else if meta.path.is_ident("inout") {
+                    ParamDirection::InOut
+                } else if meta.path.is_ident("non_null") {
+                    if !matches!(*param.ty, Type::Ptr(_)) {
+                        return Err(Error::new(
+                            param.ty.span(),
+                            "#[reflect(non_null)] is only supported on raw pointer parameters",
+                        ));
+                    }
+                    if non_null {
+                        return Err(meta.error("Duplicate non_null option"));
+                    }
+                    non_null = true;
+                    return Ok(());
+                } else if meta.path.is_ident("ptr") {
+                    if !matches!(*param.ty, Type::Ptr(_)) {
+                        return Err(Error::new(
+                            param.ty.span(),
+                            "#[reflect(ptr = \"...\")] is only supported on raw pointer parameters",
+                        ));
+                    }
+                    if pointer_discipline.is_some() {
+                        return Err(meta.error("Duplicate ptr option"));
+                    }
+                    let value = meta.value()?.parse::<syn::LitStr>()?;
+                    pointer_discipline = Some(pointer_discipline_variant(&value)?);
+                    return Ok(());
+                } else if meta.path.is_ident("unit") {
+                    if !is_primitive_integer(&param.ty) {
+                        return Err(Error::new(
+                            param.ty.span(),
+                            "#[reflect(unit = \"...\")] is only supported on primitive integer parameters",
+                        ));
+                    }
+                    if unit.is_some() {
+                        return Err(meta.error("Duplicate unit option"));
+                    }
+                    unit = Some(meta.value()?.parse::<syn::LitStr>()?);
+                    return Ok(());
+                } else if meta.path.is_ident("range") {
+                    if !is_primitive_integer(&param.ty) {
+                        return Err(Error::new(
+                            param.ty.span(),
+                            "#[reflect(range(...))] is only supported on primitive integer parameters",
+                        ));
+                    }
+                    if range.is_some() {
+                        return Err(meta.error("Duplicate range option"));
+                    }
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let min = parse_signed_int(&content)?;
+                    content.parse::<Token![,]>()?;
+                    let max = parse_signed_int(&content)?;
+                    if !content.is_empty() {
+                        return Err(content.error("Expected range(min, max)"));
+                    }
+                    if min > max {
+                        return Err(meta.error(format!("Empty range: {} > {}", min, max)));
+                    }
+                    let unsigned = param.ty.to_token_stream().to_string().starts_with('u');
+                    if unsigned && min < 0 {
+                        return Err(meta.error("Negative range for an unsigned parameter"));
+                    }
+                    range = Some((min, max));
+                    return Ok(());
+                } else {
+                    return Err(Error::new_spanned(&meta.path, "Invalid flag"));
+                };
+                if direction.is_some() {
+                    return Err(meta.error("Conflicting parameter directions"));
+                }
+                direction = Some(parsed);
+                Ok(())
+            })?;
+        }
+    }
+    let direction = direction.unwrap_or(ParamDirection::In);
+    match direction {
+        ParamDirection::In => {}
+        ParamDirection::Out | ParamDirection::InOut => match *param.ty {
+            Type::Ptr(ref ptr) if ptr.mutability.is_some() => {}
+            _ => {
+                return Err(Error::new(
+                    param.ty.span(),
+                    "Out parameters must be mutable pointers",
+                ))
+            }
+        },
+    }
+    Ok(ParamOptions {
+        direction,
+        non_null,
+        range,
+        pointer_discipline,
+        unit,
+    })
+}
+
+/// Remove our `#[reflect(...)]` attributes from the parameters,
+/// since rustc doesn't allow them in the emitted item.
+fn strip_param_attrs(sig: &mut Signature) {
+    for input in &mut sig.inputs {
+        if let FnArg::Typed(ref mut param) = *input {
+            param
+                .attrs
+                .retain(|attr| !attr.path().is_ident(PARAM_ATTR_NAME));
+        }
+    }
+}
+
 pub fn handle_item(item: &Item, args: FuncArgs) -> Result<TokenStream, syn::Error> {
     match *item {
         Item::Fn(ref func) => handle_fn_def(func, args),
@@ -112,13 +390,28 @@ pub fn handle_item(item: &Item, args: FuncArgs) -> Result<TokenStream, syn::Erro
 }
 
 fn handle_fn_def(item: &ItemFn, args: FuncArgs) -> Result<TokenStream, syn::Error> {
+    if let Some(ref vtable) = args.vtable {
+        return Err(syn::Error::new(
+            vtable.span(),
+            "Vtables are only supported on blocks of foreign functions",
+        ));
+    }
+    let krate = crate_path(args.crate_path);
     let location = if args.absolute {
+        if args.library.is_some() || args.weak {
+            return Err(syn::Error::new(
+                item.sig.ident.span(),
+                "Linkage options aren't supported with absolute locations",
+            ));
+        }
         let name = &item.sig.ident;
         FunctionLocation::AbsoluteAddress(quote!({ #name as *const () }))
     } else {
         let name = determine_fn_link_name(item)?;
         FunctionLocation::DynamicallyLinked {
             link_name: name.map(|s| quote!(#s)),
+            library: args.library,
+            weak: args.weak,
         }
     };
     let def = emit_def_from_signature(
@@ -127,16 +420,69 @@ fn handle_fn_def(item: &ItemFn, args: FuncArgs) -> Result<TokenStream, syn::Erro
             assume_c_abi: false,
             location,
             is_unsafe: item.sig.unsafety.is_some(),
+            may_unwind: args.may_unwind,
+            thread_safety: args.thread_safety.unwrap_or(ThreadSafety::Unknown),
+            krate,
         },
     )?;
     let verify_types = types_from_signature(&item.sig);
     let def_const = def.make_constant(&verify_types);
+    let registration = if args.register {
+        def.make_registration()
+    } else {
+        quote!()
+    };
+    let invoker = make_invoker(&item.sig, &def.krate);
+    let mut item = item.clone();
+    strip_param_attrs(&mut item.sig);
     Ok(quote! {
         #def_const
+        #registration
+        #invoker
         #item
     })
 }
 
+/// Emit the hidden `_INVOKE_name` thunk, calling the function with type-erased arguments
+/// (see `static_reflect::funcs::Invoker`)
+///
+/// Generic functions don't get one, since the thunk can't be generic.
+fn make_invoker(sig: &Signature, krate: &syn::Path) -> TokenStream {
+    if !sig.generics.params.is_empty() {
+        return quote!();
+    }
+    let name = &sig.ident;
+    let const_name = format_ident!("_INVOKE_{}", ident_name(name));
+    let arg_types = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match *arg {
+            FnArg::Typed(ref arg) => Some(&*arg.ty),
+            FnArg::Receiver(_) => None,
+        })
+        .collect_vec();
+    let count = arg_types.len();
+    let indexes = 0..count;
+    let call = quote!(#name(#(::std::ptr::read(args[#indexes] as *const #arg_types)),*));
+    let body = match sig.output {
+        ReturnType::Type(_, ref ty) if matches!(**ty, Type::Never(_)) => quote!(#call),
+        ReturnType::Type(_, ref ty) => quote!((ret as *mut #ty).write(#call);),
+        ReturnType::Default => quote!(#call;),
+    };
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        pub const #const_name: #krate::funcs::Invoker = {
+            unsafe fn invoke(args: &[*mut ::std::ffi::c_void], ret: *mut ::std::ffi::c_void) {
+                assert_eq!(args.len(), #count, "Wrong number of arguments");
+                let _ = ret;
+                unsafe { #body }
+            }
+            invoke
+        };
+    }
+}
+
 fn handle_foreign_mod(
     item: &ItemForeignMod,
     default_args: FuncArgs,
@@ -148,11 +494,15 @@ fn handle_foreign_mod(
             "Absolute locations aren't supported in foreign functions",
         ));
     }
-    match item.abi.name.as_ref() {
-        Some(abi_name) if &*abi_name.value() == "C" => {}
-        None => {}
+    let krate = crate_path(default_args.crate_path);
+    let unwinding_abi = match item.abi.name.as_ref() {
+        Some(abi_name) if &*abi_name.value() == "C" => false,
+        Some(abi_name) if &*abi_name.value() == "C-unwind" => true,
+        None => false,
         _ => return Err(Error::new(item.abi.span(), "Expected C ABI")),
-    }
+    };
+    let abi = &item.abi;
+    let mut vtable_entries = Vec::new();
     let mut result_static_defs = Vec::new();
     let mut result_items = Vec::new();
     for item in &item.items {
@@ -164,19 +514,26 @@ fn handle_foreign_mod(
                 for attr in &item.attrs {
                     if attr.path().is_ident(FUNC_ATTR_NAME) {
                         // NOTE: This attribute is removed from the result_item
-                        attr.parse_nested_meta(|meta| {
-                            if override_args.is_some() {
-                                return Err(meta
-                                    .error(format!("Conflicting #[{FUNC_ATTR_NAME}] attributes")));
-                            }
-                            override_args = Some(FuncArgs::parse(meta.input)?);
-                            Ok(())
-                        })?;
+                        if override_args.is_some() {
+                            return Err(Error::new(
+                                attr.span(),
+                                format!("Conflicting #[{FUNC_ATTR_NAME}] attributes"),
+                            ));
+                        }
+                        override_args = Some(match attr.meta {
+                            Meta::Path(_) => syn::parse2::<FuncArgs>(TokenStream::new())?,
+                            _ => attr.parse_args::<FuncArgs>()?,
+                        });
                     } else {
                         result_item.attrs.push(attr.clone());
                     }
                 }
                 // Handle overriding args
+                let mut library = default_args.library.clone();
+                let mut weak = default_args.weak;
+                let mut may_unwind = default_args.may_unwind || unwinding_abi;
+                let mut register = default_args.register;
+                let mut thread_safety = default_args.thread_safety;
                 if let Some(override_args) = override_args {
                     if override_args.absolute {
                         return Err(syn::Error::new(
@@ -184,15 +541,76 @@ fn handle_foreign_mod(
                             "Absolute locations aren't supported in foreign functions",
                         ));
                     }
+                    if override_args.crate_path.is_some() {
+                        return Err(syn::Error::new(
+                            item.span(),
+                            "The crate can only be specified for the whole extern block",
+                        ));
+                    }
+                    if override_args.vtable.is_some() {
+                        return Err(syn::Error::new(
+                            item.span(),
+                            "The vtable can only be specified for the whole extern block",
+                        ));
+                    }
+                    if override_args.library.is_some() {
+                        library = override_args.library;
+                    }
+                    weak |= override_args.weak;
+                    may_unwind |= override_args.may_unwind;
+                    register |= override_args.register;
+                    if override_args.thread_safety.is_some() {
+                        thread_safety = override_args.thread_safety;
+                    }
+                }
+                let link_name = determine_foreign_link_name(&item.attrs)?;
+                if default_args.vtable.is_some() {
+                    if let Some(ref variadic) = item.sig.variadic {
+                        return Err(syn::Error::new(
+                            variadic.span(),
+                            "Variadic functions aren't supported in vtables",
+                        ));
+                    }
+                    vtable_entries.push(VtableEntry {
+                        name: item.sig.ident.clone(),
+                        docs: item
+                            .attrs
+                            .iter()
+                            .filter(|attr| attr.path().is_ident("doc"))
+                            .cloned()
+                            .collect(),
+                        argument_types: types_from_signature(&item.sig)
+                            .split_last()
+                            .unwrap()
+                            .1
+                            .to_vec(),
+                        output: item.sig.output.clone(),
+                        symbol: link_name
+                            .clone()
+                            .unwrap_or_else(|| ident_name(&item.sig.ident)),
+                        weak,
+                    });
                 }
-                let link_name = determine_foreign_link_name(&item.attrs)?.map(|s| quote!(#s));
+                let link_name = link_name.map(|s| quote!(#s));
                 let args = FunctionDefOpts {
-                    location: FunctionLocation::DynamicallyLinked { link_name },
+                    location: FunctionLocation::DynamicallyLinked {
+                        link_name,
+                        library,
+                        weak,
+                    },
                     assume_c_abi: true,
                     is_unsafe: true, // All foreign defs are unsafe
+                    may_unwind,
+                    thread_safety: thread_safety.unwrap_or(ThreadSafety::Unknown),
+                    krate: krate.clone(),
                 };
                 let verify_types = types_from_signature(&item.sig);
-                result_static_defs.push((emit_def_from_signature(&item.sig, args)?, verify_types));
+                result_static_defs.push((
+                    emit_def_from_signature(&item.sig, args)?,
+                    verify_types,
+                    register,
+                ));
+                strip_param_attrs(&mut result_item.sig);
                 result_items.push(ForeignItem::Fn(result_item));
             }
             _ => {
@@ -203,50 +621,204 @@ fn handle_foreign_mod(
     }
     let function_def_consts = result_static_defs
         .iter()
-        .map(|(def, verify_types)| def.make_constant(verify_types))
+        .map(|(def, verify_types, register)| {
+            let def_const = def.make_constant(verify_types);
+            if *register {
+                let registration = def.make_registration();
+                quote!(#def_const #registration)
+            } else {
+                def_const
+            }
+        })
         .collect_vec();
+    let vtable = match default_args.vtable {
+        Some(ref name) => emit_vtable(name, abi, &vtable_entries, &krate),
+        None => quote!(),
+    };
     Ok(quote! {
         #(#function_def_consts)*
-        extern "C" {
+        #abi {
             #(#result_items)*
         }
+        #vtable
     })
 }
 
+/// A function in the vtable generated by `#[reflect_func(vtable = "...")]`
+struct VtableEntry {
+    name: Ident,
+    docs: Vec<Attribute>,
+    argument_types: Vec<Type>,
+    output: ReturnType,
+    symbol: String,
+    weak: bool,
+}
+
+/// Emit a `#[repr(C)]` struct with a function pointer for each foreign function,
+/// in the order they were declared.
+fn emit_vtable(
+    vtable: &Ident,
+    abi: &syn::Abi,
+    entries: &[VtableEntry],
+    krate: &syn::Path,
+) -> TokenStream {
+    let mut fields = Vec::new();
+    let mut resolved = Vec::new();
+    let mut declarations = Vec::new();
+    for entry in entries {
+        let VtableEntry {
+            ref name,
+            ref docs,
+            ref argument_types,
+            ref output,
+            ref symbol,
+            weak,
+        } = *entry;
+        let fn_type = quote!(unsafe #abi fn(#(#argument_types),*) #output);
+        let transmuted = quote!(unsafe { ::core::mem::transmute::<*const (), #fn_type>(ptr) });
+        if weak {
+            fields.push(quote!(#(#docs)* pub #name: Option<#fn_type>));
+            resolved.push(quote! {
+                #name: match loader(#symbol) {
+                    Some(ptr) if !ptr.is_null() => Some(#transmuted),
+                    _ => None,
+                }
+            });
+        } else {
+            fields.push(quote!(#(#docs)* pub #name: #fn_type));
+            resolved.push(quote! {
+                #name: match loader(#symbol) {
+                    Some(ptr) if !ptr.is_null() => #transmuted,
+                    _ => return Err(#krate::funcs::MissingSymbol { symbol: #symbol }),
+                }
+            });
+        }
+        let const_name = format_ident!("_FUNC_{}", name);
+        declarations.push(quote!(#const_name.into_erased()));
+    }
+    quote! {
+        /// Pointers to the functions of an extern block, in the order they were declared
+        #[repr(C)]
+        #[derive(Copy, Clone, Debug)]
+        pub struct #vtable {
+            #(#fields,)*
+        }
+        impl #vtable {
+            /// The declarations of the functions, in the same order as the fields
+            pub const DECLARATIONS: &'static [#krate::funcs::FunctionDeclaration] = &[#(#declarations),*];
+            /// Resolve each function by its link name, using the specified loader
+            ///
+            /// Weak functions that can't be found are left as `None`,
+            /// while any other missing function is an error.
+            ///
+            /// ## Safety
+            /// The loader must return pointers to functions with the declared signatures.
+            pub unsafe fn from_loader(
+                mut loader: impl FnMut(&str) -> Option<*const ()>,
+            ) -> Result<Self, #krate::funcs::MissingSymbol> {
+                Ok(#vtable {
+                    #(#resolved,)*
+                })
+            }
+        }
+    }
+}
+
 fn emit_def_from_signature(
     item: &Signature,
     opts: FunctionDefOpts,
 ) -> Result<StaticFunctionDef, syn::Error> {
-    match item.abi.as_ref().and_then(|abi| abi.name.as_ref()) {
-        Some(abi_name) if &*abi_name.value() == "C" => {}
-        None if opts.assume_c_abi => {}
+    let unwind = match item.abi.as_ref().and_then(|abi| abi.name.as_ref()) {
+        Some(abi_name) if &*abi_name.value() == "C-unwind" => UnwindBehavior::MayUnwind,
+        _ if opts.may_unwind => UnwindBehavior::MayUnwind,
+        Some(abi_name) if &*abi_name.value() == "C" => {
+            if opts.assume_c_abi {
+                // Foreign functions are defined by other code, which may not be Rust
+                UnwindBehavior::Unknown
+            } else {
+                // Rust aborts instead of unwinding out of an `extern "C"` function
+                UnwindBehavior::Aborts
+            }
+        }
+        None if opts.assume_c_abi => UnwindBehavior::Unknown,
         _ => return Err(Error::new(item.span(), "Expected C ABI")),
-    }
+    };
+    let krate = &opts.krate;
     let mut argument_types = Vec::new();
+    let mut argument_directions = Vec::new();
+    let mut argument_contracts = Vec::new();
+    let mut argument_units = Vec::new();
+    let mut ffi_safety = Vec::new();
     let mut static_arg_types = Vec::new();
-    for input in &item.inputs {
+    let func_name = ident_name(&item.ident);
+    for (index, input) in item.inputs.iter().enumerate() {
         match input {
             FnArg::Receiver(ref item) => return Err(Error::new(item.span(), "Invalid input")),
             FnArg::Typed(ref item) => {
                 let ty = &item.ty;
                 static_arg_types.push(quote!(#ty));
-                argument_types.push(quote!(<#ty as static_reflect::StaticReflect>::TYPE_INFO))
+                argument_types.push(quote!(<#ty as #krate::StaticReflect>::TYPE_INFO));
+                let options = parse_param_options(item)?;
+                let direction = match options.direction {
+                    ParamDirection::In => quote!(In),
+                    ParamDirection::Out => quote!(Out),
+                    ParamDirection::InOut => quote!(InOut),
+                };
+                argument_directions.push(quote!(#krate::funcs::ParamDirection::#direction));
+                argument_contracts.push(options.contract(krate));
+                argument_units.push(match options.unit {
+                    Some(ref unit) => quote!(::core::option::Option::Some(#unit)),
+                    None => quote!(<#ty as #krate::StaticReflect>::UNIT),
+                });
+                let context = match *item.pat {
+                    Pat::Ident(ref pat) => {
+                        format!("argument `{}` of `{}`", ident_name(&pat.ident), func_name)
+                    }
+                    _ => format!("argument {} of `{}`", index, func_name),
+                };
+                ffi_safety.push(ffi_safety_expr(krate, &context, ty, ty));
             }
         }
     }
     let return_type = match item.output {
-        ReturnType::Default => quote!(&static_reflect::types::TypeInfo::Unit),
+        ReturnType::Default => quote!(&#krate::types::TypeInfo::Unit),
         ReturnType::Type(_, ref ty) => {
-            quote!(&<#ty as static_reflect::StaticReflect>::TYPE_INFO)
+            ffi_safety.push(ffi_safety_expr(
+                krate,
+                &format!("return type of `{}`", func_name),
+                ty,
+                ty,
+            ));
+            quote!(&<#ty as #krate::StaticReflect>::TYPE_INFO)
         }
     };
+    // Only record the contracts if some argument has one
+    let argument_contracts = if argument_contracts.iter().all(Option::is_none) {
+        Vec::new()
+    } else {
+        argument_contracts
+            .into_iter()
+            .map(|contract| {
+                contract.unwrap_or_else(|| quote!(#krate::funcs::ArgContract::UNCONSTRAINED))
+            })
+            .collect()
+    };
     let signature = StaticSignatureDef {
         argument_types,
+        argument_directions,
+        argument_contracts,
+        argument_units,
         return_type,
+        ffi_safety,
+        krate: krate.clone(),
     };
     Ok(StaticFunctionDef {
-        name: item.ident.to_string(),
+        krate: krate.clone(),
+        name: func_name,
         location: opts.location,
+        unwind,
+        thread_safety: opts.thread_safety,
+        span: item.ident.span(),
         signature,
         is_unsafe: opts.is_unsafe,
         static_return_type: match item.output {
@@ -278,26 +850,47 @@ pub fn types_from_signature(sig: &Signature) -> Vec<Type> {
 // Emit
 #[derive(Clone, Debug)]
 struct StaticFunctionDef {
+    krate: syn::Path,
     name: String,
     is_unsafe: bool,
     location: FunctionLocation,
+    unwind: UnwindBehavior,
+    thread_safety: ThreadSafety,
+    /// The span of the function's name, where its source location is captured
+    span: Span,
     signature: StaticSignatureDef,
     static_return_type: TokenStream,
     static_arg_types: TokenStream,
 }
 impl StaticFunctionDef {
+    fn const_name(&self) -> Ident {
+        format_ident!("_FUNC_{}", self.name)
+    }
+    /// Place the function in the link section of the global registry
+    /// (see `static_reflect::funcs::registry`)
+    fn make_registration(&self) -> TokenStream {
+        let const_name = self.const_name();
+        let krate = &self.krate;
+        quote! {
+            const _: () = {
+                const __REFLECT_DECLARATION: #krate::funcs::FunctionDeclaration =
+                    #const_name.into_erased();
+                #krate::funcs::registry::submit!(&__REFLECT_DECLARATION);
+            };
+        }
+    }
     fn make_constant(&self, verify_types: &[Type]) -> TokenStream {
-        let const_name = format!("_FUNC_{}", self.name);
-        let const_name = Ident::new(&const_name, Span::call_site());
+        let const_name = self.const_name();
         let def = self;
+        let krate = &self.krate;
         let return_type = &self.static_return_type;
         let arg_types = &self.static_arg_types;
         quote! {
             #[doc(hidden)]
             #[allow(non_snake_case)]
-            pub const #const_name: static_reflect::funcs::FunctionDeclaration<#return_type, #arg_types> = {
+            pub const #const_name: #krate::funcs::FunctionDeclaration<#return_type, #arg_types> = {
                 // Verify all the types implement [StaticReflect]
-                #(let _ = <#verify_types as static_reflect::StaticReflect>::TYPE_INFO;)*
+                #(let _ = <#verify_types as #krate::StaticReflect>::TYPE_INFO;)*
                 #def
             };
         }
@@ -307,49 +900,126 @@ impl StaticFunctionDef {
 #[derive(Clone, Debug)]
 struct StaticSignatureDef {
     argument_types: Vec<TokenStream>,
+    argument_directions: Vec<TokenStream>,
+    argument_contracts: Vec<TokenStream>,
+    argument_units: Vec<TokenStream>,
     return_type: TokenStream,
+    ffi_safety: Vec<TokenStream>,
+    krate: syn::Path,
+}
+
+/// Whether a function may unwind (see `static_reflect::funcs::UnwindBehavior`)
+#[derive(Debug, Clone, Copy)]
+enum UnwindBehavior {
+    Aborts,
+    MayUnwind,
+    Unknown,
+}
+impl ToTokens for UnwindBehavior {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append(Ident::new(
+            match *self {
+                UnwindBehavior::Aborts => "Aborts",
+                UnwindBehavior::MayUnwind => "MayUnwind",
+                UnwindBehavior::Unknown => "Unknown",
+            },
+            Span::call_site(),
+        ));
+    }
+}
+
+/// Whether a function is thread-safe (see `static_reflect::funcs::ThreadSafety`)
+#[derive(Debug, Clone, Copy)]
+pub enum ThreadSafety {
+    Safe,
+    Unsafe,
+    Unknown,
+}
+impl ToTokens for ThreadSafety {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append(Ident::new(
+            match *self {
+                ThreadSafety::Safe => "Safe",
+                ThreadSafety::Unsafe => "Unsafe",
+                ThreadSafety::Unknown => "Unknown",
+            },
+            Span::call_site(),
+        ));
+    }
 }
 
 #[derive(Clone, Debug)]
 enum FunctionLocation {
-    DynamicallyLinked { link_name: Option<TokenStream> },
+    DynamicallyLinked {
+        link_name: Option<TokenStream>,
+        library: Option<syn::LitStr>,
+        weak: bool,
+    },
     AbsoluteAddress(TokenStream),
 }
 
 impl ToTokens for StaticFunctionDef {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let StaticFunctionDef {
+            ref krate,
             ref name,
             ref signature,
             ref location,
+            unwind,
+            thread_safety,
+            span,
             ref is_unsafe,
             ref static_return_type,
             static_arg_types: ref staitc_arg_types,
         } = *self;
-        tokens.append_all(quote!(static_reflect::funcs::FunctionDeclaration::<#static_return_type, #staitc_arg_types> {
-            name: #name,
-            is_unsafe: #is_unsafe,
-            signature: #signature,
-            location: #location,
-            return_type: ::std::marker::PhantomData,
-            arg_types: ::std::marker::PhantomData,
-        }));
+        let location = location.emit(krate);
+        // The macros expand to the location of their span, which is the function's name
+        let source = quote_spanned!(span => #krate::types::SourceLocation {
+            file: ::core::file!(),
+            line: ::core::line!(),
+        });
+        tokens.append_all(
+            quote!(#krate::funcs::FunctionDeclaration::<#static_return_type, #staitc_arg_types> {
+                name: #name,
+                is_unsafe: #is_unsafe,
+                signature: #signature,
+                location: #location,
+                unwind: #krate::funcs::UnwindBehavior::#unwind,
+                thread_safety: #krate::funcs::ThreadSafety::#thread_safety,
+                source: Some(#source),
+                return_type: ::std::marker::PhantomData,
+                arg_types: ::std::marker::PhantomData,
+            }),
+        );
     }
 }
 
-impl ToTokens for FunctionLocation {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        tokens.append_all(match *self {
-            FunctionLocation::DynamicallyLinked { link_name: None } => {
-                quote!(Some(static_reflect::funcs::FunctionLocation::DynamicallyLinked { link_name: None }))
-            },
-            FunctionLocation::DynamicallyLinked { link_name: Some(ref name) } => {
-                quote!(Some(static_reflect::funcs::FunctionLocation::DynamicallyLinked { link_name: Some(#name) }))
-            },
+impl FunctionLocation {
+    fn emit(&self, krate: &syn::Path) -> TokenStream {
+        match *self {
+            FunctionLocation::DynamicallyLinked {
+                ref link_name,
+                ref library,
+                weak,
+            } => {
+                let link_name = match *link_name {
+                    Some(ref name) => quote!(Some(#name)),
+                    None => quote!(None),
+                };
+                let library = match *library {
+                    Some(ref library) => quote!(Some(#library)),
+                    None => quote!(None),
+                };
+                quote!(Some(#krate::funcs::FunctionLocation::DynamicallyLinked {
+                    link_name: #link_name,
+                    library: #library,
+                    weak: #weak,
+                }))
+            }
             FunctionLocation::AbsoluteAddress(ref value) => {
-                quote!(Some(static_reflect::funcs::FunctionLocation::AbsoluteAddress(#value)))
-            },
-        });
+                quote!(Some(#krate::funcs::FunctionLocation::AbsoluteAddress(#value)))
+            }
+        }
     }
 }
 
@@ -357,13 +1027,23 @@ impl ToTokens for StaticSignatureDef {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let StaticSignatureDef {
             ref argument_types,
+            ref argument_directions,
+            ref argument_contracts,
+            ref argument_units,
             ref return_type,
+            ref ffi_safety,
+            ref krate,
         } = *self;
-        tokens.append_all(quote!(static_reflect::funcs::SignatureDef {
+        tokens.append_all(quote!(#krate::funcs::SignatureDef {
             argument_types: &[#(#argument_types),*],
+            argument_directions: &[#(#argument_directions),*],
+            argument_contracts: &[#(#argument_contracts),*],
+            // Only listed if some argument has a unit, like the contracts
+            argument_units: #krate::types::known_units(&[#(#argument_units),*]),
             return_type: #return_type,
             // We use C FFI
-            calling_convention: static_reflect::funcs::CallingConvention::StandardC
+            calling_convention: #krate::funcs::CallingConvention::StandardC,
+            ffi_safety: #krate::types::ffi::FfiSafety::all(&[#(#ffi_safety),*])
         }))
     }
 }
@@ -0,0 +1,167 @@
+//! An opt-in manifest of the reflected types, for external tooling
+//!
+//! If `STATIC_REFLECT_EMIT_MANIFEST` is set to a directory,
+//! each successful derive writes a JSON record describing the type and its options.
+//! The records of each crate are placed in a subdirectory named after the crate,
+//! with one file per type, so parallel invocations of rustc never write to the same file.
+//! Each file is written to a temporary path and then renamed into place,
+//! so readers never see a partial record.
+//!
+//! The format of a record is versioned by [MANIFEST_VERSION]:
+//! ````json
+//! {"version": 1, "crate": "example", "file": "src/lib.rs", "type": "Point", "kind": "struct",
+//!  "options": ["pod"], "fields": [{"name": "x", "options": ["rename = \"left\""]}]}
+//! ````
+//! The options are the contents of each `#[reflect(...)]` attribute, as written.
+//! Fields of tuple structs are named by their index,
+//! and enums have a list of `"variants"` instead of `"fields"`.
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Fields, Meta, Token};
+
+use super::fields::fnv1a_hash;
+
+/// The environment variable naming the directory to write the manifest to
+pub const MANIFEST_ENV: &str = "STATIC_REFLECT_EMIT_MANIFEST";
+/// The version of the record format, bumped whenever it changes incompatibly
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Write the record for the type, if the manifest is enabled
+pub fn emit_record(input: &DeriveInput) -> Result<(), syn::Error> {
+    let dir = match std::env::var_os(MANIFEST_ENV) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => return Ok(()),
+    };
+    let krate = std::env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "unknown".into());
+    let file = Span::call_site().file();
+    let record = render_record(input, &krate, &file)?;
+    let type_name = input.ident.to_string();
+    let file_name = format!(
+        "{}-{:08x}.json",
+        type_name,
+        fnv1a_hash(&format!("{}::{}", file, type_name))
+    );
+    write_atomically(&dir.join(&krate), &file_name, &record).map_err(|cause| {
+        syn::Error::new(
+            input.ident.span(),
+            format_args!(
+                "Failed to write the manifest record to {} (from ${}): {}",
+                dir.display(),
+                MANIFEST_ENV,
+                cause
+            ),
+        )
+    })
+}
+
+fn render_record(input: &DeriveInput, krate: &str, file: &str) -> Result<String, syn::Error> {
+    let kind = match input.data {
+        Data::Struct(_) => "struct",
+        Data::Enum(_) => "enum",
+        Data::Union(_) => "union",
+    };
+    let mut record = format!(
+        "{{\"version\": {}, \"crate\": {}, \"file\": {}, \"type\": {}, \"kind\": {}, \"options\": {}",
+        MANIFEST_VERSION,
+        json_string(krate),
+        json_string(file),
+        json_string(&input.ident.to_string()),
+        json_string(kind),
+        json_options(&input.attrs)?,
+    );
+    let fields: Vec<(String, &[syn::Attribute])> = match input.data {
+        Data::Struct(ref data) => named_fields(&data.fields),
+        Data::Union(ref data) => data
+            .fields
+            .named
+            .iter()
+            .map(|field| (field.ident.as_ref().unwrap().to_string(), &*field.attrs))
+            .collect(),
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .map(|variant| (variant.ident.to_string(), &*variant.attrs))
+            .collect(),
+    };
+    record.push_str(if matches!(input.data, Data::Enum(_)) {
+        ", \"variants\": ["
+    } else {
+        ", \"fields\": ["
+    });
+    for (index, (name, attrs)) in fields.iter().enumerate() {
+        if index > 0 {
+            record.push_str(", ");
+        }
+        write!(
+            record,
+            "{{\"name\": {}, \"options\": {}}}",
+            json_string(name),
+            json_options(attrs)?
+        )
+        .unwrap();
+    }
+    record.push_str("]}\n");
+    Ok(record)
+}
+
+fn named_fields(fields: &Fields) -> Vec<(String, &[syn::Attribute])> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let name = match field.ident {
+                Some(ref ident) => ident.to_string(),
+                None => index.to_string(),
+            };
+            (name, &*field.attrs)
+        })
+        .collect()
+}
+
+/// The options of all the `#[reflect(...)]` attributes, as a JSON array of strings
+fn json_options(attrs: &[syn::Attribute]) -> Result<String, syn::Error> {
+    let mut result = String::from("[");
+    let mut first = true;
+    for attr in attrs {
+        if attr.path().is_ident("reflect") || attr.path().is_ident("static_reflect") {
+            let options = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            for option in options {
+                if !first {
+                    result.push_str(", ");
+                }
+                first = false;
+                result.push_str(&json_string(&option.to_token_stream().to_string()));
+            }
+        }
+    }
+    result.push(']');
+    Ok(result)
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Write the file to a temporary path, then rename it into place
+fn write_atomically(dir: &Path, file_name: &str, contents: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let temp = dir.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, dir.join(file_name))
+}
@@ -1,48 +1,42 @@
-use std::str::FromStr;
-
+use self::consts::ConstArgs;
 use self::func::FuncArgs;
-use proc_macro2::TokenStream;
-use syn::{spanned::Spanned, DeriveInput, Item};
+use proc_macro2::{Ident, Span, TokenStream};
+use proc_macro_crate::FoundCrate;
+use syn::{parse_quote, Item};
+
+pub use static_reflect_internals::ident_name;
+pub use static_reflect_internals::repr::{determine_align, determine_repr, Repr};
 
+pub mod consts;
+pub mod ffi;
 pub mod fields;
 pub mod func;
-mod utils;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Repr {
-    C,
-    Transparent,
-    Integer { signed: bool, bits: u32 },
-}
+pub mod manifest;
+pub mod utils;
 
-pub fn determine_repr(input: &DeriveInput) -> Result<Option<Repr>, syn::Error> {
-    let mut result = None;
-    for attr in &input.attrs {
-        if attr.meta.path().is_ident("repr") {
-            attr.parse_nested_meta(|meta| {
-                if result.is_some() {
-                    return Err(meta.error("Encountered multiple repr(...) attributes"));
-                }
-                let s = meta.path.require_ident()?.to_string();
-                result = Some(match &*s {
-                    "C" => Repr::C,
-                    "transparent" => Repr::Transparent,
-                    "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" => {
-                        let signed = match s.chars().next() {
-                            Some('i') => true,
-                            Some('u') => false,
-                            _ => unreachable!(),
-                        };
-                        let bits = u32::from_str(&s[1..]).unwrap();
-                        Repr::Integer { signed, bits }
-                    }
-                    _ => return Err(syn::Error::new(meta.path.span(), "Unknown #[repr])")),
-                });
-                Ok(())
-            })?;
+/// Determine the path to the `static_reflect` crate, used by all generated code
+///
+/// An explicit `crate = "..."` option always takes priority.
+/// Otherwise, the dependency is looked up in the caller's `Cargo.toml`,
+/// so it still works if the dependency is renamed.
+///
+/// If the crate isn't a direct dependency (for example, if it's used through a re-export),
+/// this falls back to `::static_reflect`.
+pub fn crate_path(explicit: Option<syn::Path>) -> syn::Path {
+    if let Some(path) = explicit {
+        return path;
+    }
+    match proc_macro_crate::crate_name("static-reflect") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            parse_quote!(::#name)
         }
+        /*
+         * NOTE: The main crate never uses its own derive,
+         * so `Itself` means a doctest or integration test (which use the crate by name).
+         */
+        Ok(FoundCrate::Itself) | Err(_) => parse_quote!(::static_reflect),
     }
-    Ok(result)
 }
 
 pub fn derive_reflect_func(args: FuncArgs, input: &Item) -> Result<TokenStream, ::syn::Error> {
@@ -52,3 +46,11 @@ pub fn derive_reflect_func(args: FuncArgs, input: &Item) -> Result<TokenStream,
 
     Ok(result)
 }
+
+pub fn derive_reflect_const(args: ConstArgs, input: &Item) -> Result<TokenStream, ::syn::Error> {
+    let result = self::consts::handle_item(input, args)?;
+
+    self::utils::debug_proc_macro("reflect_const", &self::utils::item_name(input), &result);
+
+    Ok(result)
+}
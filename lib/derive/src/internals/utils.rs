@@ -15,6 +15,7 @@ pub fn item_name(item: &Item) -> Box<dyn Display> {
     let ident: &Ident = match *item {
         Item::Fn(ref f) => &f.sig.ident,
         Item::Static(ref s) => &s.ident,
+        Item::Const(ref c) => &c.ident,
         Item::Struct(ref s) => &s.ident,
         _ => return Box::new(format!("{}", quote!(item))) as Box<dyn Display>,
     };
@@ -4,6 +4,121 @@ mod internals;
 
 use syn::{parse_macro_input, DeriveInput, Item};
 
+/// Derive an implementation of `StaticReflect` (and `FieldReflect`)
+///
+/// ## Container options
+/// The type itself may be annotated with `#[reflect(...)]`:
+/// - `pod` - Assert the structure implements `bytemuck::Pod` (requires the `bytemuck` feature)
+/// - `niche` - Reflect an enum with one empty variant and one variant wrapping a `SimpleNonZeroRepr` type
+///   (like `Option<NonNull<T>>`) as a `TypeInfo::Niche`. The enum must not have a `#[repr]`.
+/// - `crate = "path::to::static_reflect"` - Override the path to the `static_reflect` crate,
+///   which is otherwise found by looking up the (possibly renamed) dependency in `Cargo.toml`
+/// - `profile = "minimal"` - Omit names from the structure's metadata, keeping only its layout
+///   (which is still `layout_eq` to the default `"full"` profile)
+/// - `unsized_tail` - The last field is an array whose real length is only known at runtime
+///   (like `[T; 0]`). It's reflected as its element type, and the reflected size
+///   only covers the header before it (see `StructureDef::has_unsized_tail`).
+///   With `unsized_tail(len = "field")`, the named integer field of the header holds the number of elements,
+///   which is recorded in `FieldReflect::DYNAMIC_SIZE` to compute the full size of an instance
+///   (see `static_reflect::types::ops::dynamic_size_of`).
+/// - `lazy_fields` - Look up the fields of a union through a generated function (`UnionFields::Lazy`),
+///   instead of building an array of every field. This is meant for unions with hundreds of members,
+///   so it also skips generating their `NamedFieldInfo` (and the `FieldReflect` impl).
+///   Not supported on generic types.
+/// - `lint` (or `lint(max_padding = N)`) - Warn about suspicious layouts:
+///   excessive padding, fields that could be reordered to save space,
+///   integer discriminants wider than needed (see `static_reflect::types::lint`),
+///   and types which aren't FFI-safe (see below).
+///   The warnings are reported through the `deprecated` lint, pointing at the type.
+///   Not supported on generic types.
+/// - `variant_names` - Generate inherent `variant_name(&self)` and `from_variant_name(&str)` methods
+///   for a C-style enum, converting each variant to and from its reflected name.
+/// - `schema` - Generate an inherent `SCHEMA_INFO` constant for a struct
+///   (a `static_reflect::types::schema::SchemaStructureDef`), which is the same for every instantiation.
+///   Fields whose type is a type parameter are recorded as `TypeInfo::TypeParameter`,
+///   and offsets which depend on the type parameters are omitted.
+/// - `accessors` (or `accessors(vis = "pub")`) - Generate inherent `get_name(&self)` and `set_name(&mut self, value)`
+///   methods for each field of a struct, named after its (possibly renamed) reflected name.
+///   Each accessor has the visibility of its field, unless `vis` overrides it for all of them.
+///   Getters return a copy of the field, so they require its type to be `Copy`.
+///   The accessors are checked at compile time to match the reflected offset of each field,
+///   along with its reflected size (which catches a wrong `assume_repr_unchecked`).
+/// - `newtype_unit` (or `newtype_unit = "milliseconds"`) - Reflect a `#[repr(transparent)]` newtype
+///   wrapping a primitive integer as that integer, recording a semantic unit in `StaticReflect::UNIT`
+///   (the name of the type, unless one is given). The `TYPE_INFO` is the same as the integer's,
+///   so units are only compared when requested (see `funcs::coerce::CoercionRules::compare_units`).
+///
+/// ## Variant options
+/// The variants of a C-style enum may be annotated with `#[reflect(rename = "name")]`
+/// to record them under a different name.
+///
+/// ## Alignment
+/// Structs and unions may request a minimum alignment with `#[repr(C, align(N))]`.
+/// This also works for zero-sized markers like `#[repr(C, align(64))] struct CacheAligned;`,
+/// which force the alignment of the fields after them (and of the containing type).
+///
+/// ## Field options
+/// Fields may be annotated with `#[reflect(...)]`:
+/// - `opaque_array` - Treat a trailing array as its element type
+/// - `assume_repr = "Type"` - Assume the field has the representation of `Type`
+///   (its size and alignment are checked at compile time)
+/// - `assume_repr_unchecked = "Type"` - Like `assume_repr`, but skipping the layout checks
+/// - `rename = "name"` - Record the field under a different name
+/// - `endian = "big"` (or `"little"`) - Record the byte order of a numeric field
+/// - `ptr = "owned"` (or `"borrowed"`, `"raw"`) - Record who owns the target of a pointer field.
+///   Pointer fields without it are reported by the audit as undocumented.
+/// - `bitset(names = "A,B,C")` - Name the bits of an integer field used as a set of flags,
+///   starting from the least significant bit (see `types::bitset`)
+/// - `unit = "milliseconds"` - Record the semantic unit of a primitive integer field
+///   in `FieldReflect::FIELD_UNITS` (fields of a `newtype_unit` type have its unit automatically)
+///
+/// ## Layout checks
+/// The offset computed for each field is checked against its real offset at compile time,
+/// along with the size and alignment of the whole type.
+/// Both offsets are also recorded in `FieldReflect::LAYOUT_DEBUG`,
+/// which `FieldReflect::layout_report()` prints as a table.
+///
+/// ## FFI safety
+/// The declared types of the fields are checked against rules similar to rustc's `improper_ctypes`
+/// (see `static_reflect::types::ffi`), and the result is recorded in `StaticReflect::FFI_SAFETY`.
+/// A field without a rule (like a nested struct) is as safe as the `FFI_SAFETY` of its reflected type.
+/// `#[reflect_func]` does the same for the arguments and return type of a function,
+/// recording the result in `SignatureDef::ffi_safety`.
+///
+/// ## Generated items
+/// All identifiers introduced by the generated code start with the reserved
+/// prefix `__reflect_`. Fields whose names start with this prefix are rejected,
+/// as are fields that end up with the same (possibly renamed) name.
+///
+/// ## Names
+/// Types, fields and variants are reflected under their names in the source code,
+/// which may contain non-ASCII characters. Raw identifiers are reflected
+/// without their `r#` prefix, so a field named `r#type` is reflected as `type`.
+///
+/// ## Generics
+/// Structs and unions with type parameters are supported (like `struct Pair<T>`),
+/// as long as each parameter implements `StaticReflect`.
+/// Each instantiation is reflected separately, and its layout is only checked
+/// once its `TYPE_INFO` is used.
+///
+/// ## Manifest
+/// Tooling can find out which types were reflected (and with which options) without running the code,
+/// by setting `STATIC_REFLECT_EMIT_MANIFEST` to a directory while building.
+/// Each derive then writes a JSON record to `<dir>/<crate>/<Type>-<hash>.json`,
+/// with the source file, the kind of type, and the `#[reflect(...)]` options of the type and its fields (or variants).
+/// The records have a `"version"`, which is bumped whenever the format changes incompatibly.
+/// Nothing is written unless the variable is set.
+/// Cargo doesn't know about the variable, so crates which are already built need to be rebuilt
+/// (for example, after a `cargo clean -p <crate>`) to write their records.
+///
+/// ## Other macros
+/// The options and layout of structs are resolved by `static_reflect_internals::analyze_struct`,
+/// which other procedural macros can use to agree with the reflected layout.
+///
+/// ## Lifetimes
+/// Types with lifetime parameters are supported (like `struct View<'a>`).
+/// The lifetimes don't affect the layout, so the field types
+/// are reflected with all their lifetimes replaced by `'static`.
 #[proc_macro_derive(StaticReflect, attributes(reflect, static_reflect))]
 pub fn derive_static_reflect(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: DeriveInput = parse_macro_input!(raw_input as DeriveInput);
@@ -13,6 +128,57 @@ pub fn derive_static_reflect(raw_input: proc_macro::TokenStream) -> proc_macro::
     }
 }
 
+/// Reflect the signature of a function (or block of foreign functions)
+///
+/// ## Options
+/// - `absolute` - Locate the function by its address instead of dynamic linking
+/// - `crate = "path::to::static_reflect"` - Override the path to the `static_reflect` crate
+///   (only allowed on the whole block of foreign functions)
+/// - `library = "libm.so.6"` - Record the shared library expected to define the function
+/// - `weak` - Record that the function may be missing at runtime
+/// - `may_unwind` - Record that the function may unwind, even though it uses the `"C"` ABI
+///
+/// Functions using the `"C-unwind"` ABI are always recorded as `UnwindBehavior::MayUnwind`.
+/// Otherwise, functions defined in Rust are recorded as `UnwindBehavior::Aborts`,
+/// while foreign functions are `UnwindBehavior::Unknown`.
+///
+/// - `thread_safe` - Record that the function is documented to be safe to call from multiple threads at once
+/// - `not_thread_safe` - Record that the function must not be called from multiple threads at once
+///
+/// Otherwise, the thread safety of a function is `ThreadSafety::Unknown`.
+///
+/// - `vtable = "Name"` - Generate a `#[repr(C)]` struct with a function pointer field
+///   for each foreign function, in the order they were declared
+///   (only allowed on the whole block of foreign functions).
+///   Its `Name::DECLARATIONS` list the corresponding `FunctionDeclaration`s in the same order,
+///   and `Name::from_loader` resolves each function by its link name.
+///   Weak functions are optional fields, left as `None` if they're missing.
+/// - `register` - Add the function to the global `static_reflect::funcs::registry()`,
+///   by placing its declaration in a link section (requires the `register` feature).
+///   The registry sees the functions of every crate linked into the binary.
+///
+/// The declaration of a function `name` is the hidden constant `_FUNC_name`,
+/// which is also used for raw identifiers (`r#match` is declared by `_FUNC_match`).
+/// Non-generic functions defined in Rust also get a hidden `_INVOKE_name` constant,
+/// which calls the function with type-erased arguments (see `static_reflect::funcs::Invoker`).
+///
+/// Inside a block of foreign functions, the `library`, `weak`, `may_unwind`, `register` and thread safety
+/// options of the block are the defaults for each function, which may override them with its own `#[reflect_func(...)]`.
+///
+/// ## Parameter options
+/// Pointer parameters may be annotated with `#[reflect(...)]`
+/// to record their `ParamDirection`:
+/// - `out` - The function writes its result through the pointer
+/// - `inout` - The function both reads and writes through the pointer
+///
+/// Parameters may also record their preconditions (`ArgContract`):
+/// - `non_null` - The raw pointer must not be null
+/// - `range(min, max)` - The primitive integer must be within the inclusive range
+/// - `ptr = "owned"` (or `"borrowed"`, `"raw"`) - Who owns the target of the raw pointer
+///
+/// A primitive integer parameter may record its semantic unit with `unit = "milliseconds"`,
+/// which is listed in `SignatureDef::argument_units`
+/// (parameters of a `newtype_unit` type have its unit automatically).
 #[proc_macro_attribute]
 pub fn reflect_func(
     args: proc_macro::TokenStream,
@@ -26,3 +192,29 @@ pub fn reflect_func(
         Err(e) => e.into_compile_error().into(),
     }
 }
+
+/// Reflect the type and contents of a `const` or `static` item
+///
+/// This generates a hidden `_CONST_name` constant with its `static_reflect::consts::ConstDeclaration`,
+/// whose bytes are captured at compile time.
+///
+/// The type must implement `static_reflect::consts::ConstBytes`
+/// (primitive numbers, `bool`, and arrays of them), or be a `&str`.
+/// These are the types that can soundly be viewed as bytes,
+/// since they don't have any padding, pointers or interior mutability.
+/// A `static mut` is rejected, since its contents may change.
+///
+/// ## Options
+/// - `crate = "path::to::static_reflect"` - Override the path to the `static_reflect` crate
+#[proc_macro_attribute]
+pub fn reflect_const(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input: Item = parse_macro_input!(input as Item);
+    let args = parse_macro_input!(args as internals::consts::ConstArgs);
+    match internals::derive_reflect_const(args, &input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
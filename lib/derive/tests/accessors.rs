@@ -0,0 +1,101 @@
+//! Accessors generated by `#[reflect(accessors)]`
+use static_reflect::macros::assert_fully_const;
+use static_reflect::{FieldReflect, StaticReflect};
+
+mod fixture {
+    use static_reflect::StaticReflect;
+
+    #[derive(StaticReflect, Default)]
+    #[reflect(accessors)]
+    #[repr(C)]
+    pub struct Counter {
+        pub number: u32,
+        #[reflect(rename = "total")]
+        pub sum: u64,
+        #[reflect(assume_repr = "i16")]
+        pub(crate) flags: u16,
+        limit: u8,
+        pub r#type: bool,
+    }
+
+    impl Counter {
+        /// The private accessor is only visible inside this module
+        pub fn limit(&self) -> u8 {
+            self.get_limit()
+        }
+    }
+
+    #[derive(StaticReflect)]
+    #[reflect(accessors(vis = "pub"))]
+    #[repr(C)]
+    pub struct Hidden {
+        secret: i32,
+    }
+
+    impl Hidden {
+        pub fn new(secret: i32) -> Self {
+            Hidden { secret }
+        }
+    }
+}
+
+#[derive(StaticReflect)]
+#[reflect(accessors)]
+#[repr(C)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+use fixture::{Counter, Hidden};
+
+#[test]
+fn round_trip() {
+    let mut counter = Counter::default();
+    counter.set_number(42);
+    counter.set_total(7);
+    counter.set_flags(3);
+    counter.set_type(true);
+    assert_eq!(counter.get_number(), 42);
+    assert_eq!(counter.number, 42);
+    // Renamed fields are accessed by their reflected name
+    assert_eq!(counter.get_total(), 7);
+    assert_eq!(counter.sum, 7);
+    assert_eq!(counter.get_flags(), 3);
+    assert!(counter.get_type());
+    assert_eq!(counter.limit(), 0);
+}
+
+#[test]
+fn visibility_override() {
+    let mut hidden = Hidden::new(-1);
+    assert_eq!(hidden.get_secret(), -1);
+    hidden.set_secret(5);
+    assert_eq!(hidden.get_secret(), 5);
+}
+
+#[test]
+fn generic() {
+    let mut pair = Pair {
+        first: 1.5f64,
+        second: 2.5,
+    };
+    pair.set_second(pair.get_first());
+    assert_eq!(pair.second, 1.5);
+    let mut ints = Pair {
+        first: 1u8,
+        second: 2,
+    };
+    ints.set_first(3);
+    assert_eq!(ints.get_first(), 3);
+}
+
+#[test]
+fn consistent_with_reflection() {
+    let fields = <Counter as FieldReflect>::NAMED_FIELD_INFO;
+    assert_eq!(fields.sum.name, Some("total"));
+    assert_eq!(fields.sum.offset(), std::mem::offset_of!(Counter, sum));
+    assert_eq!(Counter::TYPE_INFO.size(), std::mem::size_of::<Counter>());
+}
+
+assert_fully_const!(Pair<u32>);
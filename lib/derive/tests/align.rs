@@ -0,0 +1,74 @@
+//! Zero-sized `#[repr(C, align(N))]` markers, used to force the alignment of other fields
+use static_reflect::macros::{assert_fully_const, field_offset, generate_layout_tests};
+use static_reflect::types::TypeInfo;
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C, align(64))]
+struct CacheAligned;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Counter {
+    flag: u8,
+    // The marker itself is zero-sized, so it only pads the fields after it
+    _align: CacheAligned,
+    count: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(C, align(16))]
+struct Padded {
+    value: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C, align(8))]
+union AlignedUnion {
+    small: u16,
+    byte: u8,
+}
+
+generate_layout_tests!(
+    Counter {
+        flag,
+        _align,
+        count
+    },
+    Padded { value }
+);
+
+#[test]
+fn marker() {
+    match CacheAligned::TYPE_INFO {
+        TypeInfo::Structure(def) => {
+            assert!(def.fields.is_empty());
+            assert_eq!(def.size, 0);
+            assert_eq!(def.alignment, 64);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn aligned_member() {
+    assert_eq!(Counter::NAMED_FIELD_INFO._align.offset, 64);
+    assert_eq!(Counter::NAMED_FIELD_INFO.count.offset, 64);
+    assert_eq!(field_offset!(Counter, count), 64);
+    assert_eq!(Counter::TYPE_INFO.alignment(), 64);
+    assert_eq!(Counter::TYPE_INFO.size(), 128);
+}
+
+#[test]
+fn aligned_fields() {
+    assert_eq!(Padded::TYPE_INFO.alignment(), 16);
+    assert_eq!(Padded::TYPE_INFO.size(), 16);
+    assert!(matches!(
+        AlignedUnion::TYPE_INFO,
+        TypeInfo::UntaggedUnion(_)
+    ));
+    assert_eq!(AlignedUnion::TYPE_INFO.size(), 8);
+    assert_eq!(AlignedUnion::TYPE_INFO.alignment(), 8);
+}
+
+assert_fully_const!(CacheAligned, Counter, Padded, AlignedUnion);
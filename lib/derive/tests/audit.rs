@@ -0,0 +1,216 @@
+//! Audits of escape hatches in reflected types
+use static_reflect::macros::{assert_fully_const, define_extern_type};
+use static_reflect::types::audit::{audit, AuditEntry, AuditKind};
+use static_reflect::types::PointerDiscipline;
+use static_reflect::{FieldReflect, StaticReflect};
+use std::ptr::NonNull;
+
+struct Handle;
+define_extern_type!(Handle);
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Header {
+    #[reflect(assume_repr = "i32")]
+    flags: u32,
+    #[reflect(ptr = "owned")]
+    handle: *mut Handle,
+    #[reflect(opaque_array)]
+    data: [u8; 16],
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Packet(u64, Header);
+
+/// An extern type with the same name as a real structure
+mod ffi {
+    use static_reflect::macros::define_extern_type;
+
+    pub struct Widget {
+        _opaque: [u8; 0],
+    }
+    define_extern_type!(Widget);
+
+    pub struct Gadget {
+        _opaque: [u8; 0],
+    }
+    define_extern_type!(Gadget => Gadget, namespace = "ffi");
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Widget {
+    id: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Gadget {
+    id: u32,
+}
+
+/// Pointers with and without a documented discipline
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Buffers {
+    #[reflect(ptr = "owned")]
+    owned: *mut u8,
+    #[reflect(ptr = "borrowed")]
+    borrowed: Option<NonNull<u8>>,
+    #[reflect(ptr = "raw")]
+    raw: *const u8,
+    undocumented: NonNull<u8>,
+    len: usize,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Pool {
+    count: u32,
+    buffers: Buffers,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Plain {
+    first: u32,
+    second: f64,
+}
+
+fn entry(path: &str, kind: AuditKind, type_name: &str) -> AuditEntry {
+    AuditEntry {
+        path: path.into(),
+        kind,
+        type_name: type_name.into(),
+    }
+}
+
+#[test]
+fn header() {
+    let report = audit(&[&Header::TYPE_INFO]);
+    assert_eq!(
+        report.entries,
+        vec![
+            entry("Header.flags", AuditKind::AssumedRepr, "i32"),
+            entry("Header.handle", AuditKind::UntypedPointer, "*mut void"),
+            entry("Header.data", AuditKind::OpaqueArray, "u8"),
+        ]
+    );
+    assert_eq!(report.visited_fields, 3);
+    assert_eq!(report.count(AuditKind::AssumedRepr), 1);
+    assert_eq!(report.count(AuditKind::Extern), 0);
+}
+
+#[test]
+fn nested_paths() {
+    let report = audit(&[&Packet::TYPE_INFO, &Plain::TYPE_INFO]);
+    let paths = report
+        .entries
+        .iter()
+        .map(|entry| entry.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        ["Packet.1.flags", "Packet.1.handle", "Packet.1.data"]
+    );
+    assert_eq!(report.visited_fields, 2 + 3 + 2);
+}
+
+#[test]
+fn opaque_roots() {
+    let report = audit(&[&Handle::TYPE_INFO]);
+    assert_eq!(
+        report.entries,
+        vec![entry("extern Handle", AuditKind::Extern, "extern Handle")]
+    );
+    assert!(audit(&[&Plain::TYPE_INFO]).is_empty());
+}
+
+#[test]
+fn display() {
+    let report = audit(&[&Header::TYPE_INFO]);
+    assert_eq!(
+        report.to_string(),
+        "\
+path           kind                  type
+Header.flags   assume_repr           i32
+Header.handle  untyped pointer       *mut void
+Header.data    opaque_array          u8
+3 fields visited, 1 assume_repr, 1 opaque_array, 1 untyped pointer
+"
+    );
+}
+
+#[test]
+fn name_collision() {
+    let report = audit(&[&Widget::TYPE_INFO, &ffi::Widget::TYPE_INFO]);
+    assert_eq!(
+        report.entries,
+        vec![
+            entry("extern Widget", AuditKind::Extern, "extern Widget"),
+            entry("extern Widget", AuditKind::NameCollision, "extern Widget"),
+        ]
+    );
+    assert_eq!(report.count(AuditKind::NameCollision), 1);
+    // Neither type collides on its own
+    assert_eq!(
+        audit(&[&ffi::Widget::TYPE_INFO]).count(AuditKind::NameCollision),
+        0
+    );
+    // A namespace avoids the collision entirely
+    assert_eq!(ffi::Gadget::TYPE_INFO.nominal_name(), Some("ffi::Gadget"));
+    let report = audit(&[&Gadget::TYPE_INFO, &ffi::Gadget::TYPE_INFO]);
+    assert_eq!(report.count(AuditKind::NameCollision), 0);
+}
+
+#[test]
+fn pointer_disciplines() {
+    let fields = Buffers::NAMED_FIELD_INFO;
+    assert_eq!(
+        [
+            fields.owned.meta.pointer_discipline,
+            fields.borrowed.meta.pointer_discipline,
+            fields.raw.meta.pointer_discipline,
+            fields.undocumented.meta.pointer_discipline,
+            fields.len.meta.pointer_discipline,
+        ],
+        [
+            Some(PointerDiscipline::Owned),
+            Some(PointerDiscipline::Borrowed),
+            Some(PointerDiscipline::Raw),
+            None,
+            None,
+        ]
+    );
+    let report = audit(&[&Buffers::TYPE_INFO]);
+    assert_eq!(
+        report.entries,
+        vec![
+            entry("Buffers.owned", AuditKind::UntypedPointer, "*mut void"),
+            entry("Buffers.raw", AuditKind::UntypedPointer, "*const void"),
+            entry(
+                "Buffers.undocumented",
+                AuditKind::UndocumentedPointer,
+                "NonZero<*mut void>"
+            ),
+            entry(
+                "Buffers.undocumented",
+                AuditKind::UntypedPointer,
+                "NonZero<*mut void>"
+            ),
+        ]
+    );
+    assert_eq!(report.count(AuditKind::UndocumentedPointer), 1);
+    // Nested structures are checked too
+    let report = audit(&[&Pool::TYPE_INFO, &Packet::TYPE_INFO]);
+    assert_eq!(report.count(AuditKind::UndocumentedPointer), 1);
+    let entry = report
+        .entries
+        .iter()
+        .find(|entry| entry.kind == AuditKind::UndocumentedPointer)
+        .unwrap();
+    assert_eq!(entry.path, "Pool.buffers.undocumented");
+}
+
+assert_fully_const!(Header, Packet, Widget, Gadget, Buffers, Pool, Plain);
@@ -0,0 +1,83 @@
+//! Integer fields whose bits are named flags
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::audit::{audit, AuditKind};
+use static_reflect::types::bitset::{test_bit, BitsetDef};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Mapping {
+    address: u64,
+    #[reflect(bitset(names = "READABLE, WRITABLE, EXECUTABLE"))]
+    protection: u8,
+    #[reflect(endian = "big", bitset(names = "DIRTY,ACCESSED"))]
+    status: u16,
+    /// The bits continue from one byte to the next
+    #[reflect(opaque_array, bitset(names = "A,B,C,D,E,F,G,H,I"))]
+    features: [u8; 2],
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Region {
+    mapping: Mapping,
+    len: u64,
+}
+
+fn mapping(protection: u8, status: u16, features: [u8; 2]) -> Mapping {
+    Mapping {
+        address: 0x1000,
+        protection,
+        status: status.to_be(),
+        features,
+    }
+}
+
+#[test]
+fn names() {
+    let protection = Mapping::NAMED_FIELD_INFO.protection.meta.bitset.unwrap();
+    assert_eq!(
+        protection,
+        BitsetDef {
+            names: &["READABLE", "WRITABLE", "EXECUTABLE"]
+        }
+    );
+    assert_eq!(protection.bit_index("WRITABLE"), Some(1));
+    assert_eq!(protection.bit_index("EXECUTABLE"), Some(2));
+    assert_eq!(protection.bit_index("SHARED"), None);
+    assert_eq!(protection.to_string(), "READABLE | WRITABLE | EXECUTABLE");
+    assert_eq!(Mapping::NAMED_FIELD_INFO.address.meta.bitset, None);
+}
+
+#[test]
+fn read_bits() {
+    let value = mapping(0b101, 0b10, [0, 1]);
+    let base = &value as *const Mapping as *const u8;
+    let fields = Mapping::NAMED_FIELD_INFO;
+    unsafe {
+        assert_eq!(test_bit(base, &fields.protection, "READABLE"), Some(true));
+        assert_eq!(test_bit(base, &fields.protection, "WRITABLE"), Some(false));
+        assert_eq!(test_bit(base, &fields.protection, "EXECUTABLE"), Some(true));
+        assert_eq!(test_bit(base, &fields.protection, "SHARED"), None);
+        assert_eq!(test_bit(base, &fields.address, "READABLE"), None);
+        // Converted from big endian
+        assert_eq!(test_bit(base, &fields.status, "DIRTY"), Some(false));
+        assert_eq!(test_bit(base, &fields.status, "ACCESSED"), Some(true));
+        // The ninth bit is the first bit of the second byte
+        assert_eq!(test_bit(base, &fields.features, "A"), Some(false));
+        assert_eq!(test_bit(base, &fields.features, "I"), Some(true));
+    }
+}
+
+#[test]
+fn audited() {
+    let report = audit(&[&Mapping::TYPE_INFO]);
+    assert_eq!(report.count(AuditKind::Bitset), 3);
+    assert_eq!(report.count(AuditKind::OpaqueArray), 1);
+    // Including the fields of nested structures
+    let report = audit(&[&Region::TYPE_INFO]);
+    assert_eq!(report.count(AuditKind::Bitset), 3);
+    assert_eq!(report.entries[0].path, "Region.mapping.protection");
+}
+
+assert_fully_const!(Mapping, Region);
@@ -0,0 +1,63 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use static_reflect::builtins::AsmOption;
+
+/// Counts how many times it's dropped
+struct DropCounter(Rc<Cell<u32>>);
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn take() {
+    let drops = Rc::new(Cell::new(0));
+    let mut option = AsmOption::some(DropCounter(drops.clone()));
+    let value = option.take().unwrap();
+    assert!(!option.is_present());
+    assert!(option.take().is_none());
+    assert_eq!(drops.get(), 0);
+    drop(value);
+    assert_eq!(drops.get(), 1);
+    // Absent options can be dropped implicitly
+    drop(option);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn drop_in_place() {
+    let drops = Rc::new(Cell::new(0));
+    let mut option = AsmOption::some(DropCounter(drops.clone()));
+    unsafe { option.drop_in_place() };
+    assert!(!option.is_present());
+    assert_eq!(drops.get(), 1);
+    // Dropping again does nothing
+    unsafe { option.drop_in_place() };
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn assume_valid() {
+    let drops = Rc::new(Cell::new(0));
+    let option = AsmOption::some(DropCounter(drops.clone()));
+    let value = unsafe { option.assume_valid() }.unwrap();
+    assert_eq!(drops.get(), 0);
+    drop(value);
+    assert_eq!(drops.get(), 1);
+    // Values that don't need to be dropped can't leak
+    drop(AsmOption::some(5u32));
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "Implicitly dropped an AsmOption")
+)]
+fn implicit_drop_leaks() {
+    let drops = Rc::new(Cell::new(0));
+    drop(AsmOption::some(DropCounter(drops.clone())));
+    // Without debug assertions, the value is silently leaked
+    assert_eq!(drops.get(), 0);
+}
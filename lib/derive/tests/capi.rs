@@ -0,0 +1,204 @@
+use std::mem::MaybeUninit;
+
+use static_reflect::builtins::{AsmOption, AsmSlice, AsmStr};
+use static_reflect::capi::{SrFieldDesc, SrStatus, SrTypeKind, SrVariantDesc};
+use static_reflect::funcs::{FunctionDeclaration, SignatureDef};
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::TypeInfo;
+use static_reflect::{reflect_func, StaticReflect};
+
+// Declared the same way foreign code would see them
+//
+// The types are only ever used as opaque handles
+#[allow(improper_ctypes)]
+extern "C" {
+    fn sr_type_name(info: *const TypeInfo, out: *mut AsmStr) -> SrStatus;
+    fn sr_type_size(info: *const TypeInfo, out: *mut usize) -> SrStatus;
+    fn sr_type_alignment(info: *const TypeInfo, out: *mut usize) -> SrStatus;
+    fn sr_type_kind(info: *const TypeInfo, out: *mut SrTypeKind) -> SrStatus;
+    fn sr_struct_field_count(info: *const TypeInfo, out: *mut usize) -> SrStatus;
+    fn sr_struct_field(info: *const TypeInfo, index: usize, out: *mut SrFieldDesc) -> SrStatus;
+    fn sr_enum_variant_count(info: *const TypeInfo, out: *mut usize) -> SrStatus;
+    fn sr_enum_variant(info: *const TypeInfo, index: usize, out: *mut SrVariantDesc) -> SrStatus;
+    fn sr_function_name(decl: *const FunctionDeclaration, out: *mut AsmStr) -> SrStatus;
+    fn sr_function_signature(
+        decl: *const FunctionDeclaration,
+        out: *mut *const SignatureDef,
+    ) -> SrStatus;
+    fn sr_signature_arg_count(sig: *const SignatureDef, out: *mut usize) -> SrStatus;
+    fn sr_signature_arg(
+        sig: *const SignatureDef,
+        index: usize,
+        out: *mut *const TypeInfo,
+    ) -> SrStatus;
+    fn sr_signature_return(sig: *const SignatureDef, out: *mut *const TypeInfo) -> SrStatus;
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Example {
+    small: u8,
+    big: u64,
+    ptr: *mut Example,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Tuple(u16, u32);
+
+#[derive(StaticReflect, Copy, Clone)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Color {
+    Red = 1,
+    Green,
+    Blue = 7,
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn capi_example_func(_first: u32, _second: f64) -> u8 {
+    0
+}
+
+unsafe fn asm_str(s: AsmStr) -> &'static str {
+    std::str::from_utf8(std::slice::from_raw_parts(s.bytes_ptr(), s.len())).unwrap()
+}
+
+unsafe fn query<T>(func: impl FnOnce(*mut T) -> SrStatus) -> T {
+    let mut out = MaybeUninit::uninit();
+    assert_eq!(func(out.as_mut_ptr()), SrStatus::Ok);
+    out.assume_init()
+}
+
+#[test]
+fn type_queries() {
+    let info: *const TypeInfo = &Example::TYPE_INFO;
+    unsafe {
+        assert_eq!(asm_str(query(|out| sr_type_name(info, out))), "Example");
+        assert_eq!(
+            query(|out| sr_type_size(info, out)),
+            std::mem::size_of::<Example>()
+        );
+        assert_eq!(
+            query(|out| sr_type_alignment(info, out)),
+            std::mem::align_of::<Example>()
+        );
+        assert_eq!(query(|out| sr_type_kind(info, out)), SrTypeKind::Structure);
+        assert_eq!(
+            asm_str(query(|out| sr_type_name(&u32::TYPE_INFO, out))),
+            "u32"
+        );
+        assert_eq!(
+            query(|out| sr_type_kind(&f64::TYPE_INFO, out)),
+            SrTypeKind::Float
+        );
+        let slice = TypeInfo::Slice {
+            element_type: &u8::TYPE_INFO,
+        };
+        assert_eq!(
+            query(|out| sr_type_alignment(&slice, out)),
+            std::mem::align_of::<AsmSlice<u8>>()
+        );
+    }
+}
+
+#[test]
+fn struct_fields() {
+    let info: *const TypeInfo = &Example::TYPE_INFO;
+    let def = match Example::TYPE_INFO {
+        TypeInfo::Structure(def) => def,
+        _ => unreachable!(),
+    };
+    unsafe {
+        let count = query(|out| sr_struct_field_count(info, out));
+        assert_eq!(count, def.fields.len());
+        for (index, expected) in def.fields.iter().enumerate() {
+            let desc = query(|out| sr_struct_field(info, index, out));
+            assert_eq!(Some(asm_str(desc.name)), expected.name);
+            assert_eq!(desc.offset, expected.offset);
+            assert_eq!(desc.index, expected.index);
+            assert_eq!(*desc.value_type, *expected.value_type.type_ref());
+        }
+        let mut desc = MaybeUninit::uninit();
+        assert_eq!(
+            sr_struct_field(info, count, desc.as_mut_ptr()),
+            SrStatus::OutOfBounds
+        );
+        // Tuple fields have an empty name
+        let desc = query(|out| sr_struct_field(&Tuple::TYPE_INFO, 1, out));
+        assert_eq!(asm_str(desc.name), "");
+        assert_eq!(desc.offset, 4);
+    }
+}
+
+#[test]
+fn enum_variants() {
+    let info: *const TypeInfo = &Color::TYPE_INFO;
+    let def = match Color::TYPE_INFO {
+        TypeInfo::CStyleEnum(def) => def,
+        _ => unreachable!(),
+    };
+    unsafe {
+        assert_eq!(query(|out| sr_enum_variant_count(info, out)), 3);
+        for (index, expected) in def.variants.iter().enumerate() {
+            let desc = query(|out| sr_enum_variant(info, index, out));
+            assert_eq!(asm_str(desc.name), expected.name);
+            assert_eq!(desc.index, expected.index);
+            assert_eq!(desc.discriminant, expected.discriminant.bits());
+        }
+        let desc = query(|out| sr_enum_variant(info, 1, out));
+        assert_eq!(desc.discriminant, Color::Green as u8 as u64);
+    }
+}
+
+#[test]
+fn signatures() {
+    let decl = &_FUNC_capi_example_func as *const _ as *const FunctionDeclaration;
+    unsafe {
+        assert_eq!(
+            asm_str(query(|out| sr_function_name(decl, out))),
+            "capi_example_func"
+        );
+        let sig = query(|out| sr_function_signature(decl, out));
+        assert_eq!(query(|out| sr_signature_arg_count(sig, out)), 2);
+        assert_eq!(*query(|out| sr_signature_arg(sig, 0, out)), u32::TYPE_INFO);
+        assert_eq!(*query(|out| sr_signature_arg(sig, 1, out)), f64::TYPE_INFO);
+        assert_eq!(*query(|out| sr_signature_return(sig, out)), u8::TYPE_INFO);
+    }
+}
+
+#[test]
+fn errors() {
+    let mut out = MaybeUninit::<usize>::uninit();
+    unsafe {
+        assert_eq!(
+            sr_type_size(std::ptr::null(), out.as_mut_ptr()),
+            SrStatus::NullPointer
+        );
+        assert_eq!(
+            sr_type_size(&u8::TYPE_INFO, std::ptr::null_mut()),
+            SrStatus::NullPointer
+        );
+        assert_eq!(
+            sr_struct_field_count(&u8::TYPE_INFO, out.as_mut_ptr()),
+            SrStatus::WrongKind
+        );
+        assert_eq!(
+            sr_enum_variant_count(&Example::TYPE_INFO, out.as_mut_ptr()),
+            SrStatus::WrongKind
+        );
+        // Opaque types don't report their placeholder layout
+        let handle = TypeInfo::Extern { name: "CapiHandle" };
+        assert_eq!(
+            sr_type_size(&handle, out.as_mut_ptr()),
+            SrStatus::UnknownLayout
+        );
+        assert_eq!(
+            sr_type_alignment(&AsmOption::<u32>::TYPE_INFO, out.as_mut_ptr()),
+            SrStatus::UnknownLayout
+        );
+    }
+}
+
+assert_fully_const!(Example, Tuple, Color);
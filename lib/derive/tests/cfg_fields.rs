@@ -0,0 +1,81 @@
+//! Fields guarded by `#[cfg(...)]` are stripped before the derive runs,
+//! so the reflected layout always matches the active configuration.
+use static_reflect::macros::{assert_fully_const, field_offset, generate_layout_tests};
+use static_reflect::types::{FieldDef, FieldMeta, TypeId, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct ActiveField {
+    first: u8,
+    #[cfg(test)]
+    only_in_tests: u64,
+    last: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct InactiveField {
+    first: u8,
+    #[cfg(any())]
+    never: u64,
+    #[cfg_attr(any(), reflect(rename = "never_renamed"))]
+    last: u16,
+}
+
+generate_layout_tests!(
+    ActiveField {
+        first,
+        only_in_tests,
+        last
+    },
+    InactiveField { first, last },
+);
+
+fn fields(info: &TypeInfo) -> &'static [FieldDef] {
+    match *info {
+        TypeInfo::Structure(def) => def.fields,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn active_field() {
+    let fields = fields(&ActiveField::TYPE_INFO);
+    assert_eq!(fields.len(), 3);
+    assert_eq!(
+        ActiveField::NAMED_FIELD_INFO.only_in_tests,
+        FieldDef {
+            name: Some("only_in_tests"),
+            value_type: TypeId::<u64>::get(),
+            offset: field_offset!(ActiveField, only_in_tests),
+            index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
+        }
+    );
+    assert_eq!(fields[2].index, 2);
+}
+
+#[test]
+fn inactive_field() {
+    let fields = fields(&InactiveField::TYPE_INFO);
+    assert_eq!(fields.len(), 2);
+    assert_eq!(
+        InactiveField::NAMED_FIELD_INFO.last,
+        FieldDef {
+            name: Some("last"),
+            value_type: TypeId::<u16>::get(),
+            offset: field_offset!(InactiveField, last),
+            index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
+        }
+    );
+}
+
+assert_fully_const!(ActiveField, InactiveField);
@@ -0,0 +1,96 @@
+use std::os::raw::c_void;
+
+use static_reflect::funcs::{
+    CallingConvention, ClosureDecl, ClosureError, ParamDirection, SignatureDef,
+};
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::ffi::FfiSafety;
+use static_reflect::types::TypeInfo;
+use static_reflect::{reflect_func, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Adder {
+    offset: i32,
+}
+
+/// The trampoline for a closure `|x| x + adder.offset`
+#[no_mangle]
+#[reflect_func]
+unsafe extern "C" fn adder_trampoline(ctx: *mut c_void, x: i32) -> i32 {
+    x + (*(ctx as *mut Adder)).offset
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn no_context(x: i32) -> i32 {
+    x
+}
+
+const SIGNATURE: SignatureDef = SignatureDef {
+    argument_types: &[i32::TYPE_INFO],
+    argument_directions: &[ParamDirection::In],
+    argument_contracts: &[],
+    argument_units: &[],
+    return_type: &i32::TYPE_INFO,
+    calling_convention: CallingConvention::StandardC,
+    ffi_safety: FfiSafety::Safe,
+};
+
+#[test]
+fn consistent() {
+    let decl =
+        ClosureDecl::from_parts(SIGNATURE, _FUNC_adder_trampoline.erase(), &Adder::TYPE_INFO)
+            .unwrap();
+    assert_eq!(
+        ClosureDecl::from_trampoline(_FUNC_adder_trampoline.erase(), &Adder::TYPE_INFO),
+        Ok(decl)
+    );
+    assert_eq!(decl.context_type, &Adder::TYPE_INFO);
+    assert_eq!(decl.trampoline.name, "adder_trampoline");
+    // Actually invoke the closure through its trampoline
+    let mut adder = Adder { offset: 5 };
+    let result = unsafe { adder_trampoline(&mut adder as *mut Adder as *mut c_void, 3) };
+    assert_eq!(result, 8);
+}
+
+#[test]
+fn inconsistent() {
+    assert_eq!(
+        ClosureDecl::from_parts(SIGNATURE, _FUNC_no_context.erase(), &Adder::TYPE_INFO),
+        Err(ClosureError::MissingContext)
+    );
+    assert_eq!(
+        ClosureDecl::from_trampoline(_FUNC_no_context.erase(), &Adder::TYPE_INFO),
+        Err(ClosureError::MissingContext)
+    );
+    let extra_argument = SignatureDef {
+        argument_types: &[i32::TYPE_INFO, i32::TYPE_INFO],
+        argument_directions: &[ParamDirection::In, ParamDirection::In],
+        argument_contracts: &[],
+        argument_units: &[],
+        ..SIGNATURE
+    };
+    assert_eq!(
+        ClosureDecl::from_parts(
+            extra_argument,
+            _FUNC_adder_trampoline.erase(),
+            &Adder::TYPE_INFO
+        ),
+        Err(ClosureError::MismatchedArguments)
+    );
+    let wrong_return = SignatureDef {
+        return_type: &TypeInfo::Unit,
+        ..SIGNATURE
+    };
+    assert_eq!(
+        ClosureDecl::from_parts(
+            wrong_return,
+            _FUNC_adder_trampoline.erase(),
+            &Adder::TYPE_INFO
+        ),
+        Err(ClosureError::MismatchedReturnType)
+    );
+}
+
+assert_fully_const!(Adder);
@@ -0,0 +1,46 @@
+//! Definitions built by hand, shared by the tests which need a layout the derive wouldn't produce
+//!
+//! Each test crate only uses some of these.
+#![allow(dead_code)]
+use static_reflect::types::{FieldDef, FieldMeta, StructureDef, TypeId};
+use static_reflect::StaticReflect;
+
+/// A named field of type `T`, without any options
+pub const fn field<T: StaticReflect>(name: &'static str, offset: usize, index: usize) -> FieldDef {
+    FieldDef {
+        name: Some(name),
+        ..unnamed_field::<T>(offset, index)
+    }
+}
+
+/// A tuple field of type `T`, without any options
+pub const fn unnamed_field<T: StaticReflect>(offset: usize, index: usize) -> FieldDef {
+    FieldDef {
+        name: None,
+        value_type: TypeId::erased::<T>(),
+        offset,
+        index,
+        endianness: None,
+        assumed: false,
+        opaque_array: false,
+        meta: &FieldMeta::NONE,
+    }
+}
+
+/// A structure with the specified layout, which isn't POD and doesn't need to be dropped
+pub const fn structure(
+    name: &'static str,
+    fields: &'static [FieldDef],
+    size: usize,
+    alignment: usize,
+) -> StructureDef {
+    StructureDef {
+        name,
+        fields,
+        size,
+        alignment,
+        is_pod: false,
+        has_unsized_tail: false,
+        needs_drop: false,
+    }
+}
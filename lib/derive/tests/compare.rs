@@ -0,0 +1,109 @@
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::compare::CompareOptions;
+use static_reflect::types::{StructureDef, TypeInfo};
+use static_reflect::StaticReflect;
+
+mod common;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+/// The same layout as `Point`, with the field names swapped
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Swapped {
+    y: u32,
+    x: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Wide {
+    x: u64,
+    y: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Small {
+    First = 1,
+    Second = 2,
+}
+
+#[derive(StaticReflect)]
+#[repr(u32)]
+#[allow(dead_code)]
+enum Large {
+    First = 1,
+    Second = 2,
+}
+
+/// `Point`, with its fields listed in the opposite order
+static POINT_REORDERED: StructureDef = common::structure(
+    "Point",
+    &[
+        common::field::<u32>("y", 4, 0),
+        common::field::<u32>("x", 0, 1),
+    ],
+    8,
+    4,
+);
+
+fn structure(info: &TypeInfo) -> &'static StructureDef {
+    match *info {
+        TypeInfo::Structure(def) => def,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn strict_matches_eq() {
+    let point = structure(&Point::TYPE_INFO);
+    assert!(point.equivalent(point, CompareOptions::STRICT));
+    assert!(!point.equivalent(structure(&Swapped::TYPE_INFO), CompareOptions::STRICT));
+    assert!(!point.equivalent(&POINT_REORDERED, CompareOptions::STRICT));
+    assert_eq!(CompareOptions::default(), CompareOptions::STRICT);
+}
+
+#[test]
+fn reordered_fields() {
+    let point = structure(&Point::TYPE_INFO);
+    assert!(point.equivalent(&POINT_REORDERED, CompareOptions::IGNORE_ORDER));
+    // Ignoring names matches fields by offset, so the indexes still differ
+    assert!(!point.equivalent(&POINT_REORDERED, CompareOptions::LAYOUT));
+    let lenient = CompareOptions {
+        ignore_order: true,
+        ignore_names: true,
+        require_same_size: true,
+    };
+    assert!(point.equivalent(&POINT_REORDERED, lenient));
+}
+
+#[test]
+fn renamed_fields() {
+    let point = structure(&Point::TYPE_INFO);
+    let swapped = structure(&Swapped::TYPE_INFO);
+    assert!(point.equivalent(swapped, CompareOptions::LAYOUT));
+    assert!(Point::TYPE_INFO.layout_eq(&Swapped::TYPE_INFO));
+    // Matching by name puts the fields at different offsets
+    assert!(!point.equivalent(swapped, CompareOptions::IGNORE_ORDER));
+    assert!(!Point::TYPE_INFO.layout_eq(&Wide::TYPE_INFO));
+}
+
+#[test]
+fn enum_sizes() {
+    let same_values = CompareOptions {
+        require_same_size: false,
+        ..CompareOptions::LAYOUT
+    };
+    assert!(!Small::TYPE_INFO.layout_eq(&Large::TYPE_INFO));
+    assert!(Small::TYPE_INFO.equivalent(&Large::TYPE_INFO, same_values));
+    assert!(!Small::TYPE_INFO.equivalent(&Large::TYPE_INFO, CompareOptions::STRICT));
+}
+
+assert_fully_const!(Point, Swapped, Wide, Small, Large);
@@ -0,0 +1,61 @@
+use static_reflect::consts::ConstDeclaration;
+use static_reflect::{reflect_const, StaticReflect, TypeInfo};
+
+#[reflect_const]
+static TABLE: [u16; 8] = [1, 2, 4, 8, 16, 32, 64, 0xFF00];
+
+#[reflect_const]
+const MAGIC: u32 = 0xCAFE_BABE;
+
+#[reflect_const]
+pub const GREETING: &str = "こんにちは";
+
+#[reflect_const]
+static FLAGS: [bool; 3] = [true, false, true];
+
+#[test]
+fn static_table() {
+    assert_eq!(_CONST_TABLE.name, "TABLE");
+    assert_eq!(*_CONST_TABLE.type_info, u16::TYPE_INFO);
+    assert_eq!(_CONST_TABLE.array_len, Some(8));
+    let expected = TABLE
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(_CONST_TABLE.bytes, &*expected);
+    // The bytes of a static are its own memory
+    assert_eq!(_CONST_TABLE.address(), Some(TABLE.as_ptr() as *const u8));
+    assert_eq!(_CONST_TABLE.value::<[u16; 8]>(), Some(TABLE));
+    assert_eq!(_CONST_TABLE.value::<[u16; 4]>(), None);
+    assert_eq!(_CONST_TABLE.value::<[i16; 8]>(), None);
+}
+
+#[test]
+fn const_value() {
+    const DECL: ConstDeclaration = _CONST_MAGIC;
+    assert_eq!(DECL.name, "MAGIC");
+    assert_eq!(*DECL.type_info, u32::TYPE_INFO);
+    assert_eq!(DECL.array_len, None);
+    assert_eq!(DECL.bytes, &MAGIC.to_ne_bytes());
+    assert_eq!(DECL.address(), None);
+    assert_eq!(DECL.value::<u32>(), Some(MAGIC));
+    assert_eq!(DECL.value::<u64>(), None);
+}
+
+#[test]
+fn strings() {
+    assert_eq!(*_CONST_GREETING.type_info, TypeInfo::Str);
+    assert_eq!(_CONST_GREETING.bytes, GREETING.as_bytes());
+    assert_eq!(_CONST_GREETING.array_len, None);
+}
+
+#[test]
+fn bools() {
+    assert_eq!(_CONST_FLAGS.bytes, &[1, 0, 1]);
+    assert_eq!(_CONST_FLAGS.value::<[bool; 3]>(), Some(FLAGS));
+    let invalid = ConstDeclaration {
+        bytes: &[1, 2, 1],
+        .._CONST_FLAGS
+    };
+    assert_eq!(invalid.value::<[bool; 3]>(), None);
+}
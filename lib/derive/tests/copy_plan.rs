@@ -0,0 +1,127 @@
+//! Copy plans, which copy the meaningful bytes of a type and skip its padding
+use std::mem::size_of;
+
+use static_reflect::macros::{assert_fully_const, define_extern_type};
+use static_reflect::types::ops::{CopyPlan, UnsupportedType};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Padded {
+    small: u8,
+    // 3 bytes of padding
+    medium: u32,
+    tiny: u8,
+    // 7 bytes of padding
+    large: u64,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union Number {
+    int: u32,
+    byte: u8,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Nested {
+    first: u16,
+    // 6 bytes of padding
+    inner: Padded,
+    number: Number,
+    last: u8,
+    // 3 bytes of trailing padding
+}
+
+struct Opaque;
+define_extern_type!(Opaque);
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct HasOpaque {
+    value: u32,
+    opaque: *mut Opaque,
+}
+
+/// The padding is filled with this byte before copying
+const POISON: u8 = 0xAA;
+
+#[test]
+fn padded_runs() {
+    let plan = CopyPlan::analyze(&Padded::TYPE_INFO).unwrap();
+    // The adjacent `medium` and `tiny` are merged into a single run
+    assert_eq!(plan.runs(), &[0..1, 4..9, 16..24]);
+    assert_eq!(plan.size(), size_of::<Padded>());
+    assert_eq!(plan.copied_bytes(), 14);
+}
+
+#[test]
+fn nested_runs() {
+    let plan = CopyPlan::analyze(&Nested::TYPE_INFO).unwrap();
+    assert_eq!(plan.size(), 40);
+    // Padded's runs are offset by 8, and the union is copied entirely
+    assert_eq!(plan.runs(), &[0..2, 8..9, 12..17, 24..37]);
+}
+
+#[test]
+fn primitive_runs() {
+    let runs = |info| CopyPlan::analyze(info).unwrap().runs().to_vec();
+    assert_eq!(runs(&u64::TYPE_INFO), vec![0..8]);
+    // Any byte of a union may be meaningful
+    assert_eq!(runs(&Number::TYPE_INFO), vec![0..4]);
+    assert_eq!(runs(&<()>::TYPE_INFO), vec![]);
+}
+
+#[test]
+fn opaque_types() {
+    // Pointers to opaque types are fine
+    assert_eq!(
+        CopyPlan::analyze(&HasOpaque::TYPE_INFO).unwrap().runs(),
+        &[0..4, 8..16]
+    );
+    assert_eq!(
+        CopyPlan::analyze(&Opaque::TYPE_INFO),
+        Err(UnsupportedType {
+            type_name: "extern Opaque".into()
+        })
+    );
+}
+
+#[test]
+fn execute_preserves_padding() {
+    let plan = CopyPlan::analyze(&Nested::TYPE_INFO).unwrap();
+    let src = Nested {
+        first: 0x1234,
+        inner: Padded {
+            small: 1,
+            medium: 0xDEAD_BEEF,
+            tiny: 2,
+            large: u64::MAX - 7,
+        },
+        number: Number { int: 42 },
+        last: 7,
+    };
+    let mut dst = [POISON; size_of::<Nested>()];
+    unsafe {
+        plan.execute(&src as *const Nested as *const u8, dst.as_mut_ptr());
+    }
+    let copied = unsafe { std::ptr::read_unaligned(dst.as_ptr() as *const Nested) };
+    assert_eq!(copied.first, src.first);
+    assert_eq!(copied.inner, src.inner);
+    assert_eq!(unsafe { copied.number.int }, 42);
+    assert_eq!(copied.last, src.last);
+    for (offset, byte) in dst.iter().enumerate() {
+        if !plan.runs().iter().any(|run| run.contains(&offset)) {
+            assert_eq!(
+                *byte, POISON,
+                "Padding at offset {} was overwritten",
+                offset
+            );
+        }
+    }
+    let padding = dst.len() - plan.copied_bytes();
+    assert_eq!(padding, 6 + 3 + 7 + 3);
+}
+
+assert_fully_const!(Padded, Number, Nested, HasOpaque);
@@ -0,0 +1,220 @@
+//! Differences between two versions of a structure
+use static_reflect::types::diff::{
+    diff_structures, diff_types, DiffOptions, FieldChange, TypeDiff,
+};
+use static_reflect::types::{StructureDef, TypeInfo};
+use static_reflect::StaticReflect;
+
+mod common;
+use common::{field, structure};
+
+const OLD: StructureDef = structure(
+    "Header",
+    &[
+        field::<u32>("magic", 0, 0),
+        field::<u32>("length", 4, 1),
+        field::<u16>("flags", 8, 2),
+    ],
+    12,
+    4,
+);
+
+const COMPATIBLE: DiffOptions = DiffOptions {
+    allow_trailing_fields: false,
+};
+const TRAILING: DiffOptions = DiffOptions {
+    allow_trailing_fields: true,
+};
+
+#[test]
+fn unchanged() {
+    let diff = diff_structures(&OLD, &OLD);
+    assert!(diff.is_unchanged());
+    assert!(diff.is_abi_compatible(COMPATIBLE));
+    assert_eq!(diff.to_string(), "Header: size 12, alignment 4");
+    assert_eq!(
+        diff_types(&TypeInfo::Structure(&OLD), &TypeInfo::Structure(&OLD)),
+        TypeDiff::Unchanged
+    );
+}
+
+#[test]
+fn renamed() {
+    const NEW: StructureDef = structure(
+        "Header",
+        &[
+            field::<u32>("magic", 0, 0),
+            field::<u32>("size", 4, 1),
+            field::<u16>("flags", 8, 2),
+        ],
+        12,
+        4,
+    );
+    let diff = diff_structures(&OLD, &NEW);
+    assert_eq!(diff.fields.len(), 1);
+    let change = diff.fields[0];
+    assert!(change.is_renamed() && !change.is_moved() && !change.is_retyped());
+    assert!(diff.is_abi_compatible(COMPATIBLE));
+    assert_eq!(
+        diff.to_string(),
+        "Header: size 12, alignment 4\n  ~ length -> size: u32 at 4"
+    );
+}
+
+#[test]
+fn moved_and_retyped() {
+    const NEW: StructureDef = structure(
+        "Header",
+        &[
+            field::<u32>("magic", 0, 0),
+            field::<u16>("flags", 4, 1),
+            field::<u32>("length", 8, 2),
+        ],
+        12,
+        4,
+    );
+    let diff = diff_structures(&OLD, &NEW);
+    assert_eq!(diff.fields.len(), 2);
+    assert!(diff.fields.iter().all(FieldChange::is_moved));
+    assert!(!diff.is_abi_compatible(COMPATIBLE));
+    assert_eq!(
+        diff.to_string(),
+        "Header: size 12, alignment 4\n  ~ length: u32 at 4 -> 8\n  ~ flags: u16 at 8 -> 4"
+    );
+    const RETYPED: StructureDef = structure(
+        "Header",
+        &[
+            field::<u32>("magic", 0, 0),
+            field::<i32>("length", 4, 1),
+            field::<u16>("flags", 8, 2),
+        ],
+        12,
+        4,
+    );
+    let diff = diff_structures(&OLD, &RETYPED);
+    assert!(diff.fields[0].is_retyped());
+    // Changing the signedness changes the layout
+    assert!(!diff.is_abi_compatible(COMPATIBLE));
+    assert_eq!(diff.fields[0].to_string(), "~ length: u32 -> i32 at 4");
+}
+
+#[test]
+fn added_and_removed() {
+    const REMOVED: StructureDef = structure(
+        "Header",
+        &[field::<u32>("magic", 0, 0), field::<u16>("flags", 8, 1)],
+        12,
+        4,
+    );
+    let diff = diff_structures(&OLD, &REMOVED);
+    // The index of flags changed, which isn't reported
+    assert_eq!(diff.fields.len(), 1);
+    assert!(matches!(diff.fields[0], FieldChange::Removed(field) if field.name == Some("length")));
+    assert!(!diff.is_abi_compatible(TRAILING));
+    assert_eq!(
+        diff.to_string(),
+        "Header: size 12, alignment 4\n  - length: u32 at 4"
+    );
+}
+
+#[test]
+fn trailing_fields() {
+    const GROWN: StructureDef = structure(
+        "HeaderV2",
+        &[
+            field::<u32>("magic", 0, 0),
+            field::<u32>("length", 4, 1),
+            field::<u16>("flags", 8, 2),
+            field::<u32>("checksum", 12, 3),
+        ],
+        16,
+        4,
+    );
+    let diff = diff_structures(&OLD, &GROWN);
+    assert_eq!(diff.fields.len(), 1);
+    assert!(!diff.is_abi_compatible(COMPATIBLE));
+    assert!(diff.is_abi_compatible(TRAILING));
+    assert_eq!(
+        diff.to_string(),
+        "Header -> HeaderV2: size 12 -> 16, alignment 4\n  + checksum: u32 at 12"
+    );
+    // A field added in the padding doesn't count as trailing
+    const PADDING: StructureDef = structure(
+        "Header",
+        &[
+            field::<u32>("magic", 0, 0),
+            field::<u32>("length", 4, 1),
+            field::<u16>("flags", 8, 2),
+            field::<u16>("version", 10, 3),
+        ],
+        12,
+        4,
+    );
+    let diff = diff_structures(&OLD, &PADDING);
+    assert!(!diff.is_abi_compatible(TRAILING));
+}
+
+#[test]
+fn alignment() {
+    const ALIGNED: StructureDef = StructureDef {
+        alignment: 8,
+        size: 16,
+        ..OLD
+    };
+    let diff = diff_structures(&OLD, &ALIGNED);
+    assert!(diff.fields.is_empty());
+    assert!(!diff.is_unchanged());
+    assert!(!diff.is_abi_compatible(TRAILING));
+    assert_eq!(diff.to_string(), "Header: size 12 -> 16, alignment 4 -> 8");
+}
+
+#[test]
+fn other_kinds() {
+    let diff = diff_types(&u32::TYPE_INFO, &i32::TYPE_INFO);
+    assert_eq!(
+        diff,
+        TypeDiff::Replaced {
+            old: "u32".into(),
+            new: "i32".into(),
+            layout_eq: false,
+        }
+    );
+    assert!(!diff.is_abi_compatible(COMPATIBLE));
+    assert_eq!(diff.to_string(), "u32 -> i32");
+    let diff = diff_types(&<*const u8>::TYPE_INFO, &<*mut u8>::TYPE_INFO);
+    assert!(!diff.is_abi_compatible(COMPATIBLE));
+    let diff = diff_types(&TypeInfo::Structure(&OLD), &u32::TYPE_INFO);
+    assert!(matches!(diff, TypeDiff::Replaced { .. }));
+}
+
+mod v1 {
+    #[derive(static_reflect::StaticReflect)]
+    #[repr(C)]
+    pub union Value {
+        pub int: u32,
+        pub float: f32,
+    }
+}
+
+mod v2 {
+    #[derive(static_reflect::StaticReflect)]
+    #[repr(C)]
+    pub union Value {
+        pub integer: u32,
+        pub float: f32,
+        pub wide: u64,
+    }
+}
+
+#[test]
+fn unions() {
+    let diff = match diff_types(&v1::Value::TYPE_INFO, &v2::Value::TYPE_INFO) {
+        TypeDiff::Fields(diff) => diff,
+        other => panic!("Unexpected diff: {}", other),
+    };
+    assert_eq!(
+        diff.to_string(),
+        "Value: size 4 -> 8, alignment 4 -> 8\n  ~ int -> integer: u32 at 0\n  + wide: u64 at 0"
+    );
+    assert!(!diff.is_abi_compatible(TRAILING));
+}
@@ -0,0 +1,183 @@
+//! Rendering names into fixed buffers, without allocating
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{self, Display, Write};
+
+use static_reflect::funcs::ArgContract;
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::audit::audit;
+use static_reflect::types::{IntType, PointerDiscipline};
+use static_reflect::{FieldReflect, StaticReflect, TypeInfo};
+
+/// Counts the allocations made by the current thread
+struct CountingAllocator;
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations made by the closure (on this thread)
+fn count_allocations<T>(func: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = func();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+/// Writes into a fixed buffer, truncating (and failing) once it's full
+struct FixedBuffer {
+    buffer: [u8; 256],
+    len: usize,
+}
+impl FixedBuffer {
+    fn new() -> FixedBuffer {
+        FixedBuffer {
+            buffer: [0; 256],
+            len: 0,
+        }
+    }
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.buffer[..self.len]).unwrap()
+    }
+}
+impl Write for FixedBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let remaining = self.buffer.len() - self.len;
+        let written = s.len().min(remaining);
+        self.buffer[self.len..self.len + written].copy_from_slice(&s.as_bytes()[..written]);
+        self.len += written;
+        if written < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Render a value into a fixed buffer, checking that it doesn't allocate
+fn render(value: &dyn Display) -> FixedBuffer {
+    let mut buffer = FixedBuffer::new();
+    let (result, allocations) = count_allocations(|| write!(buffer, "{}", value));
+    result.unwrap();
+    assert_eq!(
+        allocations,
+        0,
+        "Allocated while rendering {:?}",
+        buffer.as_str()
+    );
+    buffer
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Inner {
+    value: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(C)]
+struct Pair<T, U> {
+    first: T,
+    second: U,
+    inner: Inner,
+}
+
+const LEVEL_0: TypeInfo = TypeInfo::Magic {
+    id: &"an_opaque_handle_with_a_rather_long_name",
+    extra: None,
+};
+const LEVEL_1: TypeInfo = TypeInfo::NonZero(&LEVEL_0);
+const LEVEL_2: TypeInfo = TypeInfo::Magic {
+    id: &"an_opaque_wrapper_with_a_rather_long_name",
+    extra: Some(&LEVEL_1),
+};
+const LEVEL_3: TypeInfo = TypeInfo::Optional(&LEVEL_2);
+const LEVEL_4: TypeInfo = TypeInfo::Slice {
+    element_type: &LEVEL_3,
+};
+const LEVEL_5: TypeInfo = TypeInfo::Magic {
+    id: &"an_opaque_container_with_a_rather_long_name",
+    extra: Some(&LEVEL_4),
+};
+const LEVEL_6: TypeInfo = TypeInfo::Slice {
+    element_type: &LEVEL_5,
+};
+const LEVEL_7: TypeInfo = TypeInfo::Magic {
+    id: &"an_opaque_collection_with_a_rather_long_name",
+    extra: Some(&LEVEL_6),
+};
+const DEEP: TypeInfo = TypeInfo::Magic {
+    id: &"the_outermost_opaque_type_with_a_long_name",
+    extra: Some(&LEVEL_7),
+};
+const DEEP_NAME: &str = "magic::the_outermost_opaque_type_with_a_long_name<\
+    magic::an_opaque_collection_with_a_rather_long_name<[\
+    magic::an_opaque_container_with_a_rather_long_name<[\
+    Option<magic::an_opaque_wrapper_with_a_rather_long_name<\
+    NonZero<magic::an_opaque_handle_with_a_rather_long_name>>>]>]>>";
+// Too long to fit in the buffer
+const _: () = assert!(DEEP_NAME.len() > 256);
+
+#[test]
+fn write_name() {
+    let mut buffer = FixedBuffer::new();
+    let (result, allocations) = count_allocations(|| LEVEL_4.write_name(&mut buffer));
+    result.unwrap();
+    assert_eq!(allocations, 0);
+    assert_eq!(
+        buffer.as_str(),
+        "[Option<magic::an_opaque_wrapper_with_a_rather_long_name<\
+        NonZero<magic::an_opaque_handle_with_a_rather_long_name>>>]"
+    );
+    assert_eq!(buffer.as_str(), LEVEL_4.to_string());
+}
+
+#[test]
+fn truncation() {
+    assert_eq!(DEEP.to_string(), DEEP_NAME);
+    let mut buffer = FixedBuffer::new();
+    let (result, allocations) = count_allocations(|| DEEP.write_name(&mut buffer));
+    // Running out of space is an error, not a panic
+    assert_eq!(result, Err(fmt::Error));
+    assert_eq!(allocations, 0);
+    assert_eq!(buffer.len, 256);
+    assert_eq!(buffer.as_str(), &DEEP_NAME[..256]);
+}
+
+#[test]
+fn no_allocations() {
+    assert_eq!(render(&u64::TYPE_INFO).as_str(), "u64");
+    assert_eq!(render(&IntType::ISIZE).as_str(), IntType::ISIZE.rust_name());
+    assert_eq!(render(&<Pair<u8, f32>>::TYPE_INFO).as_str(), "Pair");
+    assert_eq!(
+        render(&<Pair<u8, f32>>::SCHEMA_INFO).as_str(),
+        "Pair<T, U> { first: T, second: U, inner: Inner }"
+    );
+    let contract = ArgContract {
+        non_null: true,
+        range: Some((-1, 4096)),
+        pointer_discipline: Some(PointerDiscipline::Borrowed),
+    };
+    assert_eq!(render(&contract).as_str(), "borrowed, non-null, -1..=4096");
+    // The reports allocate when they're built, but not when they're displayed
+    let report = <Pair<u8, *mut u8>>::layout_report();
+    assert_eq!(render(&report).as_str(), report.to_string());
+    let report = audit(&[&<Pair<u8, *mut u8>>::TYPE_INFO]);
+    assert_eq!(render(&report).as_str(), report.to_string());
+}
+
+assert_fully_const!(Inner, Pair<u32, u32>);
@@ -0,0 +1,62 @@
+use std::mem::ManuallyDrop;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::downcast::TypeMismatch;
+use static_reflect::types::TypeId;
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect, Debug, PartialEq)]
+#[repr(C)]
+struct Sample {
+    count: u32,
+    ratio: f64,
+    next: *mut Sample,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+union Number {
+    int: i64,
+    float: ManuallyDrop<f64>,
+}
+
+#[test]
+fn type_id() {
+    let erased = TypeId::erased::<Sample>();
+    assert_eq!(erased.downcast::<Sample>(), Ok(TypeId::<Sample>::get()));
+    assert_eq!(
+        TypeId::erased::<u32>().downcast::<i32>(),
+        Err(TypeMismatch {
+            expected: "i32".into(),
+            actual: "u32".into(),
+        })
+    );
+}
+
+#[test]
+fn fields() {
+    let fields = Sample::NAMED_FIELD_INFO;
+    assert_eq!(fields.count.erase().downcast::<u32>(), Ok(fields.count));
+    assert_eq!(fields.ratio.erase().downcast::<f64>(), Ok(fields.ratio));
+    // Pointers are untyped, so any pointer type matches
+    assert!(fields.next.erase().downcast::<*mut u8>().is_ok());
+    let err = fields.ratio.erase().downcast::<u64>().unwrap_err();
+    assert_eq!(err.expected, "u64");
+    assert_eq!(err.actual, "f64");
+    assert_eq!(err.to_string(), "Expected type u64, but found f64");
+}
+
+#[test]
+fn union_fields() {
+    let fields = Number::NAMED_FIELD_INFO;
+    assert_eq!(fields.int.erase().downcast::<i64>(), Ok(fields.int));
+    assert_eq!(
+        fields.float.erase().downcast::<f64>().unwrap().value_type,
+        TypeId::<f64>::get()
+    );
+    let err = fields.int.erase().downcast::<Sample>().unwrap_err();
+    assert_eq!(err.expected, Sample::TYPE_INFO.to_string());
+    assert_eq!(err.actual, "i64");
+}
+
+assert_fully_const!(Sample, Number);
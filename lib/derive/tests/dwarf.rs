@@ -0,0 +1,301 @@
+//! Converting reflected types into DWARF, then parsing it back with `gimli::read`
+use std::collections::HashMap;
+use std::mem::{offset_of, size_of};
+
+use gimli::constants::{self, DwTag};
+use gimli::read::{AttributeValue, EndianSlice, EntriesTreeNode, UnitOffset};
+use gimli::write::{EndianVec, Sections};
+use gimli::{LittleEndian, RunTimeEndian};
+use static_reflect::dwarf::{DwarfError, DwarfTypes};
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::TypeInfo;
+use static_reflect::{reflect_func, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Shape {
+    Circle,
+    Square = 4,
+    Triangle,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Sprite {
+    id: u32,
+    position: Point,
+    shape: Shape,
+    visible: bool,
+    data: *const u8,
+    #[reflect(opaque_array)]
+    corners: [Point; 4],
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Pair(u8, i64);
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn move_sprite(_sprite: *mut Sprite, _dx: f32, _dy: f32) {}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn sprite_id(sprite: *const Sprite) -> u32 {
+    unsafe { (*sprite).id }
+}
+
+/// A parsed entry, with the attributes we care about
+#[derive(Clone, Debug)]
+struct Die {
+    tag: DwTag,
+    name: Option<String>,
+    member_location: Option<u64>,
+    byte_size: Option<u64>,
+    const_value: Option<i64>,
+    type_ref: Option<UnitOffset>,
+    children: Vec<Die>,
+}
+
+/// Write the unit out, and parse all of its entries back
+fn round_trip(types: DwarfTypes) -> (Vec<Die>, HashMap<UnitOffset, Die>) {
+    let mut unit = types.into_unit();
+    let mut sections = Sections::new(EndianVec::new(LittleEndian));
+    unit.write(&mut sections).unwrap();
+    let dwarf = gimli::read::Dwarf::load(|id| -> Result<_, gimli::Error> {
+        let data = sections.get(id).map_or(&[][..], |section| section.slice());
+        Ok(EndianSlice::new(data, RunTimeEndian::Little))
+    })
+    .unwrap();
+    let header = dwarf.units().next().unwrap().expect("Missing unit");
+    let unit = dwarf.unit(header).unwrap();
+    let mut tree = unit.entries_tree(None).unwrap();
+    let root = tree.root().unwrap();
+    let mut by_offset = HashMap::new();
+    let root = parse(&dwarf, &unit, root, &mut by_offset);
+    (root.children, by_offset)
+}
+
+type Reader<'a> = EndianSlice<'a, RunTimeEndian>;
+
+fn parse(
+    dwarf: &gimli::read::Dwarf<Reader>,
+    unit: &gimli::read::Unit<Reader>,
+    node: EntriesTreeNode<Reader>,
+    by_offset: &mut HashMap<UnitOffset, Die>,
+) -> Die {
+    let entry = node.entry();
+    let offset = entry.offset();
+    let name = entry
+        .attr_value(constants::DW_AT_name)
+        .unwrap()
+        .map(|value| {
+            let name = dwarf.attr_string(unit, value).unwrap();
+            name.to_string().unwrap().to_owned()
+        });
+    let udata = |at| entry.attr(at).unwrap().and_then(|attr| attr.udata_value());
+    let member_location = udata(constants::DW_AT_data_member_location);
+    let byte_size = udata(constants::DW_AT_byte_size);
+    let const_value = entry
+        .attr(constants::DW_AT_const_value)
+        .unwrap()
+        .and_then(|attr| attr.sdata_value());
+    let type_ref = match entry.attr_value(constants::DW_AT_type).unwrap() {
+        Some(AttributeValue::UnitRef(offset)) => Some(offset),
+        None => None,
+        Some(other) => panic!("Unexpected type reference: {:?}", other),
+    };
+    let tag = entry.tag();
+    let mut children = Vec::new();
+    let mut iter = node.children();
+    while let Some(child) = iter.next().unwrap() {
+        children.push(parse(dwarf, unit, child, by_offset));
+    }
+    let die = Die {
+        tag,
+        name,
+        member_location,
+        byte_size,
+        const_value,
+        type_ref,
+        children,
+    };
+    by_offset.insert(offset, die.clone());
+    die
+}
+
+fn find<'a>(entries: &'a [Die], tag: DwTag, name: &str) -> &'a Die {
+    entries
+        .iter()
+        .find(|die| die.tag == tag && die.name.as_deref() == Some(name))
+        .unwrap_or_else(|| panic!("Missing {} {}", tag, name))
+}
+
+#[test]
+fn structures() {
+    let mut types = DwarfTypes::new();
+    let sprite = types.add_type(&Sprite::TYPE_INFO).unwrap();
+    // Adding the same type again reuses its entry
+    assert_eq!(types.add_type(&Sprite::TYPE_INFO).unwrap(), sprite);
+    types.add_type(&Pair::TYPE_INFO).unwrap();
+    let (entries, by_offset) = round_trip(types);
+    let sprite = find(&entries, constants::DW_TAG_structure_type, "Sprite");
+    assert_eq!(sprite.byte_size, Some(size_of::<Sprite>() as u64));
+    let members = sprite
+        .children
+        .iter()
+        .map(|member| {
+            assert_eq!(member.tag, constants::DW_TAG_member);
+            (
+                member.name.as_deref().unwrap(),
+                member.member_location.unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        members,
+        [
+            ("id", offset_of!(Sprite, id) as u64),
+            ("position", offset_of!(Sprite, position) as u64),
+            ("shape", offset_of!(Sprite, shape) as u64),
+            ("visible", offset_of!(Sprite, visible) as u64),
+            ("data", offset_of!(Sprite, data) as u64),
+            ("corners", offset_of!(Sprite, corners) as u64),
+        ]
+    );
+    let member_type = |name: &str| {
+        let member = find(&sprite.children, constants::DW_TAG_member, name);
+        &by_offset[&member.type_ref.unwrap()]
+    };
+    let id = member_type("id");
+    assert_eq!(
+        (id.tag, id.name.as_deref(), id.byte_size),
+        (constants::DW_TAG_base_type, Some("u32"), Some(4))
+    );
+    assert_eq!(member_type("visible").name.as_deref(), Some("bool"));
+    // Nested structures are emitted once, and referenced
+    let position = member_type("position");
+    assert_eq!(position.tag, constants::DW_TAG_structure_type);
+    assert_eq!(position.name.as_deref(), Some("Point"));
+    let y = find(&position.children, constants::DW_TAG_member, "y");
+    assert_eq!(y.member_location, Some(offset_of!(Point, y) as u64));
+    let corners = member_type("corners");
+    assert_eq!(corners.tag, constants::DW_TAG_array_type);
+    assert_eq!(
+        by_offset[&corners.type_ref.unwrap()].name.as_deref(),
+        Some("Point")
+    );
+    // Pointers are untyped, so `*const u8` points to `const void`
+    let data = member_type("data");
+    assert_eq!(data.tag, constants::DW_TAG_pointer_type);
+    let target = &by_offset[&data.type_ref.unwrap()];
+    assert_eq!(
+        (target.tag, target.type_ref),
+        (constants::DW_TAG_const_type, None)
+    );
+    // Tuple fields are named by their index
+    let pair = find(&entries, constants::DW_TAG_structure_type, "Pair");
+    let names = pair
+        .children
+        .iter()
+        .map(|member| {
+            (
+                member.name.as_deref().unwrap(),
+                member.member_location.unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("__0", 0), ("__1", offset_of!(Pair, 1) as u64)]);
+}
+
+#[test]
+fn enumerations() {
+    let mut types = DwarfTypes::new();
+    types.add_type(&Shape::TYPE_INFO).unwrap();
+    let (entries, by_offset) = round_trip(types);
+    let shape = find(&entries, constants::DW_TAG_enumeration_type, "Shape");
+    assert_eq!(shape.byte_size, Some(1));
+    assert_eq!(
+        by_offset[&shape.type_ref.unwrap()].name.as_deref(),
+        Some("u8")
+    );
+    let enumerators = shape
+        .children
+        .iter()
+        .map(|variant| {
+            assert_eq!(variant.tag, constants::DW_TAG_enumerator);
+            (
+                variant.name.as_deref().unwrap(),
+                variant.const_value.unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(enumerators, [("Circle", 0), ("Square", 4), ("Triangle", 5)]);
+}
+
+#[test]
+fn signatures() {
+    let mut types = DwarfTypes::new();
+    let move_sprite = types.add_signature(&_FUNC_move_sprite.signature).unwrap();
+    let sprite_id = types.add_signature(&_FUNC_sprite_id.signature).unwrap();
+    assert_ne!(move_sprite, sprite_id);
+    let (entries, by_offset) = round_trip(types);
+    let subroutines = entries
+        .iter()
+        .filter(|die| die.tag == constants::DW_TAG_subroutine_type)
+        .collect::<Vec<_>>();
+    assert_eq!(subroutines.len(), 2);
+    // Returning nothing is like `void`
+    assert_eq!(subroutines[0].type_ref, None);
+    let params = subroutines[0]
+        .children
+        .iter()
+        .map(|param| {
+            assert_eq!(param.tag, constants::DW_TAG_formal_parameter);
+            let param_type = &by_offset[&param.type_ref.unwrap()];
+            (param_type.tag, param_type.name.as_deref())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        params,
+        [
+            (constants::DW_TAG_pointer_type, None),
+            (constants::DW_TAG_base_type, Some("f32")),
+            (constants::DW_TAG_base_type, Some("f32")),
+        ]
+    );
+    let return_type = &by_offset[&subroutines[1].type_ref.unwrap()];
+    assert_eq!(return_type.name.as_deref(), Some("u32"));
+}
+
+#[test]
+fn unsupported() {
+    let mut types = DwarfTypes::new();
+    let parameter = TypeInfo::TypeParameter {
+        name: "T",
+        index: 0,
+    };
+    assert_eq!(
+        types.add_type(&parameter),
+        Err(DwarfError {
+            type_name: parameter.to_string()
+        })
+    );
+    // Extern types are declarations without a layout
+    types.add_type(&TypeInfo::Extern { name: "FILE" }).unwrap();
+    let (entries, _) = round_trip(types);
+    assert_eq!(
+        find(&entries, constants::DW_TAG_structure_type, "FILE").byte_size,
+        None
+    );
+}
+
+assert_fully_const!(Point, Shape, Sprite, Pair);
@@ -0,0 +1,252 @@
+use std::mem::MaybeUninit;
+use std::num::NonZeroU32;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::encode::{decode_value, encode_value, DecodeError, EncodeError};
+use static_reflect::types::{
+    DiscriminantValue, FieldDef, IntType, TaggedUnionDef, TaggedUnionStyle, TaggedUnionVariant,
+    TypeInfo,
+};
+use static_reflect::StaticReflect;
+
+mod common;
+use common::{structure, unnamed_field};
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+enum Kind {
+    Small = -2,
+    Large = 300,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Header {
+    flag: bool,
+    // Three bytes of padding
+    #[reflect(endian = "big")]
+    length: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Record {
+    header: Header,
+    kind: Kind,
+    id: NonZeroU32,
+    value: f64,
+    delta: i16,
+    unit: (),
+}
+
+const RECORD: Record = Record {
+    header: Header {
+        flag: true,
+        length: 0x0102_0304u32.to_be(),
+    },
+    kind: Kind::Large,
+    id: NonZeroU32::new(7).unwrap(),
+    value: 1.5,
+    delta: -3,
+    unit: (),
+};
+
+fn encode<T: StaticReflect>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let mut out = Vec::new();
+    unsafe { encode_value(value as *const T as *const u8, &T::TYPE_INFO, &mut out)? };
+    Ok(out)
+}
+
+fn decode<T: StaticReflect>(bytes: &[u8]) -> Result<(T, usize), DecodeError> {
+    let mut value = MaybeUninit::<T>::zeroed();
+    let consumed = unsafe { decode_value(bytes, &T::TYPE_INFO, value.as_mut_ptr() as *mut u8)? };
+    Ok((unsafe { value.assume_init() }, consumed))
+}
+
+#[test]
+fn format() {
+    let mut expected = vec![1u8];
+    expected.extend_from_slice(&0x0102_0304u32.to_le_bytes());
+    expected.extend_from_slice(&300i32.to_le_bytes());
+    expected.extend_from_slice(&7u32.to_le_bytes());
+    expected.extend_from_slice(&1.5f64.to_bits().to_le_bytes());
+    expected.extend_from_slice(&(-3i16).to_le_bytes());
+    assert_eq!(encode(&RECORD).unwrap(), expected);
+    // No padding
+    assert_eq!(expected.len(), 1 + 4 + 4 + 4 + 8 + 2);
+    assert!(expected.len() < std::mem::size_of::<Record>());
+}
+
+#[test]
+fn round_trip() {
+    let records = [
+        RECORD,
+        Record {
+            header: Header {
+                flag: false,
+                length: 0,
+            },
+            kind: Kind::Small,
+            id: NonZeroU32::MAX,
+            value: f64::NEG_INFINITY,
+            delta: i16::MIN,
+            unit: (),
+        },
+    ];
+    for record in records {
+        let bytes = encode(&record).unwrap();
+        assert_eq!(decode::<Record>(&bytes).unwrap(), (record, bytes.len()));
+    }
+    // Trailing bytes are left alone
+    let mut bytes = encode(&Kind::Small).unwrap();
+    bytes.push(0xFF);
+    assert_eq!(decode::<Kind>(&bytes).unwrap(), (Kind::Small, 4));
+}
+
+#[test]
+fn unexpected_end() {
+    let bytes = encode(&RECORD).unwrap();
+    let truncated = &bytes[..bytes.len() - 1];
+    assert_eq!(
+        decode::<Record>(truncated).unwrap_err(),
+        DecodeError::UnexpectedEnd {
+            offset: 21,
+            needed: 2,
+            remaining: 1
+        }
+    );
+    assert!(matches!(
+        decode::<Header>(&[]),
+        Err(DecodeError::UnexpectedEnd { offset: 0, .. })
+    ));
+}
+
+#[test]
+fn invalid_values() {
+    let mut bytes = encode(&RECORD).unwrap();
+    bytes[0] = 2;
+    assert_eq!(
+        decode::<Record>(&bytes).unwrap_err(),
+        DecodeError::InvalidValue {
+            type_name: "bool".into(),
+            offset: 0
+        }
+    );
+    assert!(matches!(
+        decode::<Kind>(&0i32.to_le_bytes()),
+        Err(DecodeError::InvalidValue { offset: 0, .. })
+    ));
+    assert!(matches!(
+        decode::<NonZeroU32>(&[0; 4]),
+        Err(DecodeError::InvalidValue { .. })
+    ));
+}
+
+#[test]
+fn unsupported() {
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    struct WithPointer {
+        id: u32,
+        ptr: *mut u8,
+    }
+    let value = WithPointer {
+        id: 1,
+        ptr: std::ptr::null_mut(),
+    };
+    let mut out = vec![0xAA];
+    let result = unsafe {
+        encode_value(
+            &value as *const _ as *const u8,
+            &WithPointer::TYPE_INFO,
+            &mut out,
+        )
+    };
+    assert_eq!(
+        result,
+        Err(EncodeError::Unsupported {
+            type_name: "*mut void".into()
+        })
+    );
+    // The partial encoding of `id` is discarded
+    assert_eq!(out, [0xAA]);
+    assert!(matches!(
+        decode::<*mut u8>(&[0; 8]),
+        Err(DecodeError::Unsupported { .. })
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+enum Shape {
+    Circle(u32),
+    Line(u16, u8),
+}
+
+const fn variant(
+    index: usize,
+    name: &'static str,
+    fields: &'static [FieldDef],
+    size: usize,
+    alignment: usize,
+) -> TaggedUnionVariant {
+    TaggedUnionVariant {
+        index,
+        equivalent_structure: structure(name, fields, size, alignment),
+        discriminant: DiscriminantValue::Default {
+            declaration_index: index,
+        },
+    }
+}
+
+/// The layout of `Shape`, like `struct { tag: c_int, payload: union { .. } }`
+static SHAPE: TaggedUnionDef = TaggedUnionDef {
+    name: "Shape",
+    style: TaggedUnionStyle::Traditional,
+    discriminant_type: IntType::I32,
+    variants: &[
+        variant(0, "Circle", &[unnamed_field::<u32>(0, 0)], 4, 4),
+        variant(
+            1,
+            "Line",
+            &[unnamed_field::<u16>(0, 0), unnamed_field::<u8>(2, 1)],
+            4,
+            2,
+        ),
+    ],
+    size: 8,
+    alignment: 4,
+    needs_drop: false,
+};
+
+#[test]
+fn tagged_union() {
+    let info = TypeInfo::TaggedUnion(&SHAPE);
+    assert_eq!(std::mem::size_of::<Shape>(), SHAPE.size);
+    for (shape, payload) in [
+        (Shape::Circle(0xABCD), vec![0xCD, 0xAB, 0, 0]),
+        (Shape::Line(0x1234, 9), vec![0x34, 0x12, 9]),
+    ] {
+        let mut bytes = Vec::new();
+        unsafe { encode_value(&shape as *const Shape as *const u8, &info, &mut bytes).unwrap() };
+        let tag = match shape {
+            Shape::Circle(_) => 0i32,
+            Shape::Line(..) => 1,
+        };
+        assert_eq!(bytes[..4], tag.to_le_bytes());
+        assert_eq!(bytes[4..], payload);
+        let mut decoded = MaybeUninit::<Shape>::zeroed();
+        let consumed =
+            unsafe { decode_value(&bytes, &info, decoded.as_mut_ptr() as *mut u8).unwrap() };
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(unsafe { decoded.assume_init() }, shape);
+    }
+    let mut decoded = MaybeUninit::<Shape>::zeroed();
+    assert!(matches!(
+        unsafe { decode_value(&[2, 0, 0, 0], &info, decoded.as_mut_ptr() as *mut u8) },
+        Err(DecodeError::InvalidValue { offset: 0, .. })
+    ));
+}
+
+assert_fully_const!(Kind, Header, Record);
@@ -0,0 +1,111 @@
+use static_reflect::macros::{assert_fully_const, field_offset};
+use static_reflect::types::path::FieldPath;
+use static_reflect::types::value::ReflectValue;
+use static_reflect::types::{Endian, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(Copy, Clone, Debug, StaticReflect)]
+#[repr(C)]
+struct Packet {
+    kind: u8,
+    #[reflect(endian = "big")]
+    length: u16,
+    #[reflect(endian = "little")]
+    sequence: u32,
+    #[reflect(endian = "big")]
+    checksum: i64,
+    #[reflect(endian = "big")]
+    scale: f32,
+    native: u32,
+}
+
+fn endianness(name: &str) -> Option<Endian> {
+    match Packet::TYPE_INFO {
+        TypeInfo::Structure(def) => {
+            def.fields
+                .iter()
+                .find(|field| field.name == Some(name))
+                .unwrap()
+                .endianness
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Craft a packet's bytes by hand, in wire order
+fn packet_bytes() -> Vec<u8> {
+    let mut bytes = vec![0u8; std::mem::size_of::<Packet>()];
+    let mut put = |offset: usize, data: &[u8]| {
+        bytes[offset..offset + data.len()].copy_from_slice(data);
+    };
+    put(field_offset!(Packet, kind), &[7]);
+    put(field_offset!(Packet, length), &0x1234u16.to_be_bytes());
+    put(
+        field_offset!(Packet, sequence),
+        &0xDEAD_BEEFu32.to_le_bytes(),
+    );
+    put(field_offset!(Packet, checksum), &(-2i64).to_be_bytes());
+    put(field_offset!(Packet, scale), &1.5f32.to_be_bytes());
+    put(field_offset!(Packet, native), &42u32.to_ne_bytes());
+    bytes
+}
+
+fn read(bytes: &[u8], path: &str) -> ReflectValue {
+    let compiled = FieldPath::parse(path)
+        .unwrap()
+        .compile(&Packet::TYPE_INFO)
+        .unwrap();
+    unsafe { compiled.read(bytes.as_ptr()) }
+}
+
+fn integer(value: ReflectValue) -> i128 {
+    match value {
+        ReflectValue::Integer { int_type, bits } => int_type.extend_bits(bits),
+        _ => panic!("Expected an integer: {}", value),
+    }
+}
+
+#[test]
+fn recorded_endianness() {
+    assert_eq!(endianness("kind"), None);
+    assert_eq!(endianness("length"), Some(Endian::Big));
+    assert_eq!(endianness("sequence"), Some(Endian::Little));
+    assert_eq!(endianness("native"), None);
+    assert_eq!(
+        Packet::NAMED_FIELD_INFO.checksum.endianness,
+        Some(Endian::Big)
+    );
+}
+
+#[test]
+fn read_mixed_endian() {
+    let bytes = packet_bytes();
+    assert_eq!(integer(read(&bytes, "kind")), 7);
+    assert_eq!(integer(read(&bytes, "length")), 0x1234);
+    assert_eq!(integer(read(&bytes, "sequence")), 0xDEAD_BEEF);
+    assert_eq!(integer(read(&bytes, "checksum")), -2);
+    assert_eq!(read(&bytes, "scale"), ReflectValue::F32(1.5));
+    assert_eq!(integer(read(&bytes, "native")), 42);
+}
+
+#[test]
+fn write_mixed_endian() {
+    let mut bytes = vec![0u8; std::mem::size_of::<Packet>()];
+    for (path, value) in [
+        ("kind", read(&packet_bytes(), "kind")),
+        ("length", read(&packet_bytes(), "length")),
+        ("sequence", read(&packet_bytes(), "sequence")),
+        ("checksum", read(&packet_bytes(), "checksum")),
+        ("scale", read(&packet_bytes(), "scale")),
+        ("native", read(&packet_bytes(), "native")),
+    ] {
+        let compiled = FieldPath::parse(path)
+            .unwrap()
+            .compile(&Packet::TYPE_INFO)
+            .unwrap();
+        unsafe { compiled.write(bytes.as_mut_ptr(), value) };
+    }
+    assert_eq!(bytes, packet_bytes());
+}
+
+assert_fully_const!(Packet);
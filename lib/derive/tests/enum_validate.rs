@@ -0,0 +1,201 @@
+//! Consistency checks for hand-written enum definitions
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::validate::EnumDefError;
+use static_reflect::types::{
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, IntType, TaggedUnionDef, TaggedUnionStyle,
+    TaggedUnionVariant, TypeInfo,
+};
+use static_reflect::StaticReflect;
+
+mod common;
+use common::structure;
+
+#[derive(StaticReflect)]
+#[repr(i8)]
+#[allow(dead_code)]
+enum Derived {
+    First,
+    Negative = -5,
+    Implicit,
+    Last = 100,
+}
+
+const fn variant(index: usize, discriminant: DiscriminantValue) -> CStyleEnumVariant {
+    CStyleEnumVariant {
+        index,
+        name: "Variant",
+        discriminant,
+    }
+}
+
+fn c_style(discriminant: IntType, variants: &'static [CStyleEnumVariant]) -> CStyleEnumDef {
+    CStyleEnumDef {
+        name: "HandWritten",
+        discriminant,
+        variants,
+        valid_range: None,
+        has_gaps: false,
+    }
+    .compute_flags()
+}
+
+#[test]
+fn derived_enums_are_valid() {
+    match Derived::TYPE_INFO {
+        TypeInfo::CStyleEnum(def) => assert_eq!(def.validate(), Ok(())),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn duplicate_discriminant() {
+    const VARIANTS: &[CStyleEnumVariant] = &[
+        variant(
+            0,
+            DiscriminantValue::Default {
+                declaration_index: 0,
+            },
+        ),
+        variant(1, DiscriminantValue::ExplicitInteger { bits: 5 }),
+        variant(2, DiscriminantValue::ExplicitInteger { bits: 0 }),
+    ];
+    let def = c_style(IntType::U8, VARIANTS);
+    assert_eq!(
+        def.validate(),
+        Err(EnumDefError::DuplicateDiscriminant {
+            first: 0,
+            second: 2
+        })
+    );
+}
+
+#[test]
+fn discriminant_out_of_range() {
+    const VARIANTS: &[CStyleEnumVariant] =
+        &[variant(0, DiscriminantValue::ExplicitInteger { bits: 300 })];
+    let def = c_style(IntType::U8, VARIANTS);
+    assert_eq!(
+        def.validate(),
+        Err(EnumDefError::DiscriminantOutOfRange { position: 0 })
+    );
+    // Negative values may be truncated or sign-extended
+    const SIGNED: &[CStyleEnumVariant] = &[
+        variant(0, DiscriminantValue::ExplicitInteger { bits: 0xFF }),
+        variant(1, DiscriminantValue::ExplicitInteger { bits: -2i64 as u64 }),
+    ];
+    let signed = c_style(IntType::I8, SIGNED);
+    assert_eq!(signed.validate(), Ok(()));
+}
+
+#[test]
+fn index_mismatch() {
+    const VARIANTS: &[CStyleEnumVariant] = &[
+        variant(
+            0,
+            DiscriminantValue::Default {
+                declaration_index: 0,
+            },
+        ),
+        variant(
+            2,
+            DiscriminantValue::Default {
+                declaration_index: 2,
+            },
+        ),
+    ];
+    let def = c_style(IntType::U32, VARIANTS);
+    assert_eq!(
+        def.validate(),
+        Err(EnumDefError::IndexMismatch {
+            position: 1,
+            index: 2
+        })
+    );
+}
+
+#[test]
+fn inconsistent_flags() {
+    const VARIANTS: &[CStyleEnumVariant] = &[
+        variant(0, DiscriminantValue::ExplicitInteger { bits: 1 }),
+        variant(1, DiscriminantValue::ExplicitInteger { bits: 3 }),
+    ];
+    let def = c_style(IntType::U8, VARIANTS);
+    assert_eq!(def.validate(), Ok(()));
+    let stale = CStyleEnumDef {
+        has_gaps: false,
+        ..def
+    };
+    assert_eq!(stale.validate(), Err(EnumDefError::InconsistentFlags));
+    let missing = CStyleEnumDef {
+        valid_range: None,
+        ..def
+    };
+    assert_eq!(missing.validate(), Err(EnumDefError::InconsistentFlags));
+}
+
+fn tagged(size: usize, variants: &'static [TaggedUnionVariant]) -> TaggedUnionDef {
+    TaggedUnionDef {
+        name: "Tagged",
+        style: TaggedUnionStyle::Traditional,
+        discriminant_type: IntType::U8,
+        variants,
+        size,
+        alignment: 2,
+        needs_drop: false,
+    }
+}
+
+#[test]
+fn tagged_union() {
+    static VARIANTS: [TaggedUnionVariant; 2] = [
+        TaggedUnionVariant {
+            index: 0,
+            equivalent_structure: structure("Variant", &[], 4, 2),
+            discriminant: DiscriminantValue::Default {
+                declaration_index: 0,
+            },
+        },
+        TaggedUnionVariant {
+            index: 1,
+            equivalent_structure: structure("Variant", &[], 2, 2),
+            discriminant: DiscriminantValue::Default {
+                declaration_index: 1,
+            },
+        },
+    ];
+    // The tag is padded to the variant's alignment: [tag, <padding>, u16, u16]
+    assert_eq!(tagged(6, &VARIANTS).validate(), Ok(()));
+    assert_eq!(
+        tagged(8, &VARIANTS).validate(),
+        Err(EnumDefError::LayoutMismatch)
+    );
+    assert_eq!(
+        tagged(4, &VARIANTS).validate(),
+        Err(EnumDefError::VariantTooLarge { position: 0 })
+    );
+}
+
+#[test]
+fn tagged_union_discriminants() {
+    static VARIANTS: [TaggedUnionVariant; 2] = [
+        TaggedUnionVariant {
+            index: 0,
+            equivalent_structure: structure("Variant", &[], 2, 2),
+            discriminant: DiscriminantValue::ExplicitInteger { bits: 7 },
+        },
+        TaggedUnionVariant {
+            index: 1,
+            equivalent_structure: structure("Variant", &[], 2, 2),
+            discriminant: DiscriminantValue::ExplicitInteger { bits: 7 },
+        },
+    ];
+    assert_eq!(
+        tagged(4, &VARIANTS).validate(),
+        Err(EnumDefError::DuplicateDiscriminant {
+            first: 0,
+            second: 1
+        })
+    );
+}
+
+assert_fully_const!(Derived);
@@ -0,0 +1,320 @@
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::{
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, IntType, TypeInfo,
+};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+enum NegativeC {
+    MinusOne = -1,
+    Zero,
+    Big = 1000,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(i8)]
+enum NegativeByte {
+    Min = -128,
+    MinusOne = -1,
+    Zero = 0,
+    Max = 127,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Unsigned {
+    First,
+    Second,
+    Last = 255,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(i16)]
+#[allow(dead_code)]
+enum Shuffled {
+    One = 1,
+    MinusTwo = -2,
+    Zero = 0,
+    MinusOne = -1,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(usize)]
+#[allow(dead_code)]
+enum PointerSized {
+    First,
+    Second = 1 << 20,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(isize)]
+#[allow(dead_code)]
+enum SignedPointerSized {
+    MinusOne = -1,
+    Zero,
+}
+
+fn enum_def(info: &TypeInfo) -> &'static CStyleEnumDef {
+    match *info {
+        TypeInfo::CStyleEnum(def) => def,
+        _ => panic!("Expected a C-style enum: {}", info),
+    }
+}
+
+#[test]
+fn repr_c_is_signed() {
+    let def = enum_def(&NegativeC::TYPE_INFO);
+    assert_eq!(def.discriminant, IntType::I32);
+    assert_eq!(
+        def.variants[0].discriminant,
+        DiscriminantValue::ExplicitInteger { bits: 0xFFFF_FFFF }
+    );
+    assert_eq!(
+        def.variants[1].discriminant,
+        DiscriminantValue::ImplicitlyOffset { bits: 0 }
+    );
+    assert_eq!(
+        def.discriminant
+            .extend_bits(def.variants[0].discriminant.bits()),
+        -1
+    );
+    for variant in [NegativeC::MinusOne, NegativeC::Zero, NegativeC::Big] {
+        let found = def.variant_for_discriminant(variant as i64 as u64).unwrap();
+        assert_eq!(found.name, format!("{:?}", variant));
+    }
+    assert_eq!(def.variant_for_discriminant(7), None);
+}
+
+#[test]
+fn repr_i8_round_trip() {
+    let def = enum_def(&NegativeByte::TYPE_INFO);
+    assert_eq!(def.discriminant, IntType::I8);
+    assert_eq!(
+        def.variants[0].discriminant,
+        DiscriminantValue::ExplicitInteger { bits: 0x80 }
+    );
+    for variant in [
+        NegativeByte::Min,
+        NegativeByte::MinusOne,
+        NegativeByte::Zero,
+        NegativeByte::Max,
+    ] {
+        // Both the sign-extended and the truncated bits should work
+        let sign_extended = def.variant_for_discriminant(variant as i64 as u64).unwrap();
+        let truncated = def.variant_for_discriminant(variant as u8 as u64).unwrap();
+        assert_eq!(sign_extended, truncated);
+        assert_eq!(sign_extended.name, format!("{:?}", variant));
+        assert_eq!(
+            def.discriminant
+                .extend_bits(sign_extended.discriminant.bits()),
+            variant as i128
+        );
+    }
+    assert_eq!(def.variant_for_discriminant(5), None);
+}
+
+#[test]
+fn repr_u8() {
+    let def = enum_def(&Unsigned::TYPE_INFO);
+    assert_eq!(def.discriminant, IntType::U8);
+    assert!(def.has_explicit_discriminants());
+    assert_eq!(
+        def.variants[1].discriminant,
+        DiscriminantValue::Default {
+            declaration_index: 1
+        }
+    );
+    assert_eq!(def.variant_for_discriminant(255).unwrap().name, "Last");
+    assert_eq!(
+        def.discriminant
+            .extend_bits(def.variants[2].discriminant.bits()),
+        255
+    );
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(i8)]
+#[reflect(variant_names)]
+enum Renamed {
+    #[reflect(rename = "minus_one")]
+    MinusOne = -1,
+    Zero,
+    #[reflect(rename = "one")]
+    One,
+}
+
+#[test]
+fn renamed_variants() {
+    let def = enum_def(&Renamed::TYPE_INFO);
+    let names = def.variants.iter().map(|v| v.name).collect::<Vec<_>>();
+    assert_eq!(names, ["minus_one", "Zero", "one"]);
+    assert_eq!(Renamed::MinusOne.variant_name(), "minus_one");
+    assert_eq!(Renamed::from_variant_name("one"), Some(Renamed::One));
+    assert_eq!(Renamed::from_variant_name("One"), None);
+    assert_eq!(def.name_of(-1), Some("minus_one"));
+}
+
+#[test]
+fn name_of_out_of_range() {
+    let def = enum_def(&NegativeByte::TYPE_INFO);
+    assert_eq!(def.name_of(-128), Some("Min"));
+    assert_eq!(def.name_of(127), Some("Max"));
+    // Would truncate to `-128` and `-1` if not range checked
+    assert_eq!(def.name_of(128), None);
+    assert_eq!(def.name_of(255), None);
+    let def = enum_def(&Unsigned::TYPE_INFO);
+    assert_eq!(def.name_of(255), Some("Last"));
+    assert_eq!(def.name_of(-1), None);
+}
+
+#[test]
+fn contiguous() {
+    let def = enum_def(&Shuffled::TYPE_INFO);
+    assert_eq!(def.is_contiguous(), Some((-2, 1)));
+    assert_eq!(def.density(), 1.0);
+    let names = def
+        .sorted_by_discriminant()
+        .iter()
+        .map(|variant| variant.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["MinusTwo", "MinusOne", "Zero", "One"]);
+    let def = enum_def(&Renamed::TYPE_INFO);
+    assert_eq!(def.is_contiguous(), Some((-1, 1)));
+}
+
+#[test]
+fn sparse() {
+    let def = enum_def(&NegativeByte::TYPE_INFO);
+    assert_eq!(def.is_contiguous(), None);
+    assert_eq!(def.density(), 4.0 / 256.0);
+    let values = def
+        .sorted_by_discriminant()
+        .iter()
+        .map(|variant| def.discriminant_value(variant))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [-128, -1, 0, 127]);
+    let def = enum_def(&Unsigned::TYPE_INFO);
+    assert_eq!(def.is_contiguous(), None);
+    assert_eq!(def.density(), 3.0 / 256.0);
+    assert_eq!(def.sorted_by_discriminant().last().unwrap().name, "Last");
+    let def = enum_def(&NegativeC::TYPE_INFO);
+    assert_eq!(def.sorted_by_discriminant()[0].name, "MinusOne");
+    assert_eq!(def.density(), 3.0 / 1002.0);
+}
+
+#[test]
+fn tables_are_total() {
+    const fn variant(index: usize, bits: u64) -> CStyleEnumVariant {
+        CStyleEnumVariant {
+            index,
+            name: "Variant",
+            discriminant: DiscriminantValue::ExplicitInteger { bits },
+        }
+    }
+    let empty = CStyleEnumDef {
+        name: "Empty",
+        discriminant: IntType::U8,
+        variants: &[],
+        valid_range: None,
+        has_gaps: false,
+    }
+    .compute_flags();
+    assert_eq!((empty.valid_range, empty.has_gaps), (None, false));
+    assert_eq!(empty.is_contiguous(), None);
+    assert_eq!(empty.density(), 0.0);
+    assert!(empty.sorted_by_discriminant().is_empty());
+    // Duplicates are invalid, but only counted once
+    const DUPLICATES: CStyleEnumDef = CStyleEnumDef {
+        name: "Duplicates",
+        discriminant: IntType::U8,
+        variants: &[variant(0, 1), variant(1, 0), variant(2, 1)],
+        valid_range: None,
+        has_gaps: false,
+    }
+    .compute_flags();
+    assert!(DUPLICATES.validate().is_err());
+    assert_eq!(
+        (DUPLICATES.valid_range, DUPLICATES.has_gaps),
+        (Some((0, 1)), false)
+    );
+    assert_eq!(DUPLICATES.is_contiguous(), Some((0, 1)));
+    assert_eq!(DUPLICATES.density(), 1.0);
+    let indexes = DUPLICATES
+        .sorted_by_discriminant()
+        .iter()
+        .map(|variant| variant.index)
+        .collect::<Vec<_>>();
+    assert_eq!(indexes, [1, 0, 2]);
+    // The widest possible range doesn't overflow
+    const EXTREMES: CStyleEnumDef = CStyleEnumDef {
+        name: "Extremes",
+        discriminant: IntType::I64,
+        variants: &[variant(0, i64::MIN as u64), variant(1, i64::MAX as u64)],
+        valid_range: None,
+        has_gaps: false,
+    }
+    .compute_flags();
+    assert_eq!(
+        (EXTREMES.valid_range, EXTREMES.has_gaps),
+        (Some((i64::MIN.into(), i64::MAX.into())), true)
+    );
+    assert_eq!(EXTREMES.is_contiguous(), None);
+    assert!(EXTREMES.density() > 0.0);
+}
+
+#[test]
+fn validity_flags() {
+    // Dense
+    let def = enum_def(&Shuffled::TYPE_INFO);
+    assert_eq!((def.valid_range, def.has_gaps), (Some((-2, 1)), false));
+    let def = enum_def(&Renamed::TYPE_INFO);
+    assert_eq!((def.valid_range, def.has_gaps), (Some((-1, 1)), false));
+    // Gapped
+    let def = enum_def(&Unsigned::TYPE_INFO);
+    assert_eq!((def.valid_range, def.has_gaps), (Some((0, 255)), true));
+    // Negative ranges
+    let def = enum_def(&NegativeByte::TYPE_INFO);
+    assert_eq!((def.valid_range, def.has_gaps), (Some((-128, 127)), true));
+    assert!(!def.covers_all_bit_patterns());
+    let def = enum_def(&NegativeC::TYPE_INFO);
+    assert_eq!((def.valid_range, def.has_gaps), (Some((-1, 1000)), true));
+    // The derive fills in the same flags as a hand-written definition would
+    for info in [
+        &Shuffled::TYPE_INFO,
+        &Unsigned::TYPE_INFO,
+        &NegativeByte::TYPE_INFO,
+        &NegativeC::TYPE_INFO,
+    ] {
+        let def = enum_def(info);
+        assert_eq!((*def).compute_flags(), *def);
+        assert_eq!(def.validate(), Ok(()));
+    }
+}
+
+#[test]
+fn pointer_sized() {
+    let def = enum_def(&PointerSized::TYPE_INFO);
+    assert_eq!(def.discriminant, IntType::USIZE);
+    assert_eq!(
+        def.variants[1].discriminant,
+        DiscriminantValue::ExplicitInteger { bits: 1 << 20 }
+    );
+    assert_eq!(PointerSized::TYPE_INFO.size(), std::mem::size_of::<usize>());
+    let def = enum_def(&SignedPointerSized::TYPE_INFO);
+    assert_eq!(def.discriminant, IntType::ISIZE);
+    assert_eq!(def.variants[0].discriminant.bits(), usize::MAX as u64);
+    assert_eq!(def.valid_range, Some((-1, 0)));
+    assert_eq!(def.validate(), Ok(()));
+}
+
+assert_fully_const!(
+    NegativeC,
+    NegativeByte,
+    Unsigned,
+    Shuffled,
+    PointerSized,
+    SignedPointerSized,
+    Renamed,
+);
@@ -0,0 +1,70 @@
+//! Snapshots of the code generated by the derive
+//!
+//! The internals are compiled directly into this test,
+//! so the expansion can be checked without going through rustc.
+//! Any change to the generated code shows up as a diff of the snapshots
+//! in `tests/expand`, which can be updated by running with `EXPAND=overwrite`.
+use std::path::Path;
+
+use syn::DeriveInput;
+
+#[allow(dead_code)]
+#[path = "../src/internals/mod.rs"]
+mod internals;
+
+/// Expand the derive, and compare the formatted output against its snapshot
+fn check_snapshot(name: &str, input: DeriveInput) {
+    let tokens = internals::fields::derive_static_reflect(&input).unwrap();
+    let expanded = internals::utils::rustfmt(&tokens.to_string()).unwrap();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/expand")
+        .join(format!("{}.expanded.rs", name));
+    if std::env::var("EXPAND").as_deref() == Ok("overwrite") {
+        std::fs::write(&path, &expanded).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|cause| panic!("Missing snapshot {}: {}", path.display(), cause));
+    pretty_assertions::assert_eq!(
+        expected,
+        expanded,
+        "Expansion of {} changed (run with EXPAND=overwrite to update)",
+        name
+    );
+}
+
+#[test]
+fn packet() {
+    check_snapshot(
+        "packet",
+        syn::parse_quote! {
+            #[repr(C)]
+            #[reflect(crate = "static_reflect")]
+            pub struct Packet<'a> {
+                pub tag: u8,
+                #[reflect(rename = "len")]
+                pub length: u32,
+                pub payload: *const u8,
+                pub header: Header<'a>,
+                #[reflect(opaque_array)]
+                pub checksum: [u16; 2],
+            }
+        },
+    );
+}
+
+#[test]
+fn color() {
+    check_snapshot(
+        "color",
+        syn::parse_quote! {
+            #[repr(u8)]
+            #[reflect(crate = "static_reflect", variant_names)]
+            pub enum Color {
+                Red,
+                Green = 4,
+                Blue,
+            }
+        },
+    );
+}
@@ -0,0 +1,90 @@
+unsafe impl static_reflect::StaticReflect for Color {
+    const TYPE_INFO: static_reflect::types::TypeInfo = {
+        {
+            #[allow(non_upper_case_globals)]
+            const __reflect_DISCRIMINANT: static_reflect::types::IntType =
+                static_reflect::types::IntType {
+                    size: static_reflect::types::IntSize::unwrap_from_bytes(8u32 as usize / 8),
+                    signed: false,
+                };
+            #[allow(non_upper_case_globals)]
+            const __reflect_DEF: &'static static_reflect::types::CStyleEnumDef =
+                &static_reflect::types::CStyleEnumDef {
+                    name: "Color",
+                    discriminant: __reflect_DISCRIMINANT,
+                    variants: &[
+                        static_reflect::types::CStyleEnumVariant {
+                            index: 0usize,
+                            name: "Red",
+                            discriminant: static_reflect::types::DiscriminantValue::Default {
+                                declaration_index: 0usize,
+                            },
+                        },
+                        static_reflect::types::CStyleEnumVariant {
+                            index: 1usize,
+                            name: "Green",
+                            discriminant:
+                                static_reflect::types::DiscriminantValue::ExplicitInteger {
+                                    bits: __reflect_DISCRIMINANT
+                                        .truncate_bits(Color::Green as i64 as u64),
+                                },
+                        },
+                        static_reflect::types::CStyleEnumVariant {
+                            index: 2usize,
+                            name: "Blue",
+                            discriminant:
+                                static_reflect::types::DiscriminantValue::ImplicitlyOffset {
+                                    bits: __reflect_DISCRIMINANT
+                                        .truncate_bits(Color::Blue as i64 as u64),
+                                },
+                        },
+                    ],
+                    valid_range: None,
+                    has_gaps: false,
+                }
+                .compute_flags();
+            static_reflect::types::TypeInfo::CStyleEnum(__reflect_DEF)
+        }
+    };
+}
+impl Color {
+    #[doc = r" The name of this variant, as recorded in its reflected definition"]
+    #[allow(dead_code)]
+    pub const fn variant_name(&self) -> &'static str {
+        match *self {
+            Self::Red => "Red",
+            Self::Green => "Green",
+            Self::Blue => "Blue",
+        }
+    }
+    #[doc = r" Find the variant with the specified (reflected) name"]
+    #[allow(dead_code)]
+    pub fn from_variant_name(name: &str) -> Option<Self> {
+        match name {
+            "Red" => Some(Self::Red),
+            "Green" => Some(Self::Green),
+            "Blue" => Some(Self::Blue),
+            _ => None,
+        }
+    }
+}
+const _: () = {
+    use static_reflect::types::validate::EnumDefError;
+    if let static_reflect::types::TypeInfo::CStyleEnum(def) =
+        <Color as static_reflect::StaticReflect>::TYPE_INFO
+    {
+        match def.validate() {
+            Ok(()) => {}
+            Err(EnumDefError::DuplicateDiscriminant { .. }) => {
+                panic!("`Color` has variants with duplicate discriminants")
+            }
+            Err(EnumDefError::DiscriminantOutOfRange { .. }) => {
+                panic!("`Color` has a discriminant that doesn't fit in its repr")
+            }
+            Err(EnumDefError::IndexMismatch { .. }) => {
+                panic!("`Color` has variants with inconsistent indexes")
+            }
+            Err(_) => panic!("`Color` has an invalid enum definition"),
+        }
+    }
+};
@@ -0,0 +1,254 @@
+#[allow(missing_docs, non_camel_case_types)]
+#[doc(hidden)]
+pub struct __reflect_FieldInfo_Packet {
+    pub tag: static_reflect::types::FieldDef<u8>,
+    pub length: static_reflect::types::FieldDef<u32>,
+    pub payload: static_reflect::types::FieldDef<*const u8>,
+    pub header: static_reflect::types::FieldDef<Header<'static>>,
+    pub checksum: static_reflect::types::FieldDef<u16>,
+}
+#[allow(non_camel_case_types, non_upper_case_globals)]
+#[doc(hidden)]
+trait __reflect_FieldTrait_Packet {
+    type tag;
+    type length;
+    type payload;
+    type header;
+    type checksum;
+    const __reflect_LAYOUT_DEBUG: &'static [static_reflect::types::report::FieldLayout];
+    const __reflect_FIELDS: &'static [static_reflect::types::FieldDef];
+    const __reflect_OFFSETS: [usize; 6];
+    const __reflect_DEF: &'static static_reflect::types::StructureDef;
+}
+#[allow(non_camel_case_types, non_upper_case_globals)]
+impl<'a> __reflect_FieldTrait_Packet for Packet<'a> {
+    type tag = u8;
+    type length = u32;
+    type payload = *const u8;
+    type header = Header<'static>;
+    type checksum = u16;
+    const __reflect_LAYOUT_DEBUG: &'static [static_reflect::types::report::FieldLayout] = &[
+        static_reflect::types::report::FieldLayout {
+            name: "tag",
+            computed_offset: <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+                .tag
+                .offset(),
+            real_offset: Some(core::mem::offset_of!(Self, tag)),
+        },
+        static_reflect::types::report::FieldLayout {
+            name: "length",
+            computed_offset: <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+                .length
+                .offset(),
+            real_offset: Some(core::mem::offset_of!(Self, length)),
+        },
+        static_reflect::types::report::FieldLayout {
+            name: "payload",
+            computed_offset: <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+                .payload
+                .offset(),
+            real_offset: Some(core::mem::offset_of!(Self, payload)),
+        },
+        static_reflect::types::report::FieldLayout {
+            name: "header",
+            computed_offset: <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+                .header
+                .offset(),
+            real_offset: Some(core::mem::offset_of!(Self, header)),
+        },
+        static_reflect::types::report::FieldLayout {
+            name: "checksum",
+            computed_offset: <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+                .checksum
+                .offset(),
+            real_offset: Some(core::mem::offset_of!(Self, checksum)),
+        },
+    ];
+    const __reflect_FIELDS: &'static [static_reflect::types::FieldDef] = &[
+        <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+            .tag
+            .erase(),
+        <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+            .length
+            .erase(),
+        <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+            .payload
+            .erase(),
+        <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+            .header
+            .erase(),
+        <Self as static_reflect::FieldReflect>::NAMED_FIELD_INFO
+            .checksum
+            .erase(),
+    ];
+    const __reflect_OFFSETS: [usize; 6] = {
+        let layouts: [(usize, usize); 5] = [
+            (::core::mem::size_of::<u8>(), ::core::mem::align_of::<u8>()),
+            (
+                ::core::mem::size_of::<u32>(),
+                ::core::mem::align_of::<u32>(),
+            ),
+            (
+                ::core::mem::size_of::<*const u8>(),
+                ::core::mem::align_of::<*const u8>(),
+            ),
+            (
+                ::core::mem::size_of::<Header<'static>>(),
+                ::core::mem::align_of::<Header<'static>>(),
+            ),
+            (
+                ::core::mem::size_of::<[u16; 2]>(),
+                ::core::mem::align_of::<[u16; 2]>(),
+            ),
+        ];
+        let mut offsets = [0; 6];
+        let mut offset = 0;
+        let mut index = 0;
+        while index < layouts.len() {
+            let (size, align) = layouts[index];
+            let rem = offset % align;
+            if rem != 0 {
+                offset += align - rem;
+            }
+            offsets[index] = offset;
+            offset += size;
+            index += 1;
+        }
+        offsets[index] = offset;
+        offsets
+    };
+    const __reflect_DEF: &'static static_reflect::types::StructureDef = &{
+        use core::mem::{align_of, size_of};
+        use static_reflect::types::StructureDef;
+        let __reflect_fields = <Self as __reflect_FieldTrait_Packet>::__reflect_FIELDS;
+        let def = StructureDef {
+            name: "Packet",
+            fields: __reflect_fields,
+            size: size_of::<Self>(),
+            alignment: align_of::<Self>(),
+            is_pod: false,
+            has_unsized_tail: false,
+            needs_drop: ::core::mem::needs_drop::<Self>(),
+        };
+        let current_offset = <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[5];
+        let expected_size = {
+            let rem = current_offset % align_of::<Self>();
+            current_offset
+                + (if rem == 0 {
+                    0
+                } else {
+                    align_of::<Self>() - rem
+                })
+        };
+        let mut expected_alignment = align_of::<()>();
+        {
+            let mut index = 0;
+            while index < def.fields.len() {
+                let alignment = def.fields[index].value_type.type_ref().alignment();
+                if alignment > expected_alignment {
+                    expected_alignment = alignment;
+                }
+                index += 1;
+            }
+        }
+        if def.size != expected_size {
+            panic!("`Packet` has a different size than computed from its fields");
+        }
+        if def.alignment != expected_alignment {
+            panic!("`Packet` has a different alignment than the largest alignment of its fields")
+        }
+        def
+    };
+}
+unsafe impl<'a> static_reflect::FieldReflect for Packet<'a> {
+    type NamedFieldInfo = __reflect_FieldInfo_Packet;
+    const NAMED_FIELD_INFO: Self::NamedFieldInfo = __reflect_FieldInfo_Packet {
+        tag: static_reflect::types::FieldDef {
+            name: Some("tag"),
+            value_type: static_reflect::types::TypeId::<u8>::get(),
+            offset: <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[0],
+            index: 0usize,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &static_reflect::types::FieldMeta::NONE,
+        },
+        length: static_reflect::types::FieldDef {
+            name: Some("len"),
+            value_type: static_reflect::types::TypeId::<u32>::get(),
+            offset: <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[1],
+            index: 1usize,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &static_reflect::types::FieldMeta::NONE,
+        },
+        payload: static_reflect::types::FieldDef {
+            name: Some("payload"),
+            value_type: static_reflect::types::TypeId::<*const u8>::get(),
+            offset: <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[2],
+            index: 2usize,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &static_reflect::types::FieldMeta::NONE,
+        },
+        header: static_reflect::types::FieldDef {
+            name: Some("header"),
+            value_type: static_reflect::types::TypeId::<Header<'static>>::get(),
+            offset: <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[3],
+            index: 3usize,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &static_reflect::types::FieldMeta::NONE,
+        },
+        checksum: static_reflect::types::FieldDef {
+            name: Some("checksum"),
+            value_type: static_reflect::types::TypeId::<u16>::get(),
+            offset: <Self as __reflect_FieldTrait_Packet>::__reflect_OFFSETS[4],
+            index: 4usize,
+            endianness: None,
+            assumed: false,
+            opaque_array: true,
+            meta: &static_reflect::types::FieldMeta::NONE,
+        },
+    };
+    const LAYOUT_DEBUG: &'static [static_reflect::types::report::FieldLayout] =
+        <Self as __reflect_FieldTrait_Packet>::__reflect_LAYOUT_DEBUG;
+    const SOURCE: ::core::option::Option<static_reflect::types::SourceLocation> =
+        ::core::option::Option::Some(static_reflect::types::SourceLocation {
+            file: ::core::file!(),
+            line: ::core::line!(),
+        });
+    const FIELD_UNITS: &'static [::core::option::Option<&'static str>] =
+        static_reflect::types::known_units(&[
+            <u8 as static_reflect::StaticReflect>::UNIT,
+            <u32 as static_reflect::StaticReflect>::UNIT,
+            <*const u8 as static_reflect::StaticReflect>::UNIT,
+            <Header<'static> as static_reflect::StaticReflect>::UNIT,
+            <u16 as static_reflect::StaticReflect>::UNIT,
+        ]);
+}
+unsafe impl<'a> static_reflect::StaticReflect for Packet<'a> {
+    const TYPE_INFO: static_reflect::types::TypeInfo = {
+        static_reflect::types::TypeInfo::Structure(
+            <Self as __reflect_FieldTrait_Packet>::__reflect_DEF,
+        )
+    };
+    const FFI_SAFETY: static_reflect::types::ffi::FfiSafety =
+        static_reflect::types::ffi::FfiSafety::all(&[
+            <u8 as static_reflect::StaticReflect>::FFI_SAFETY,
+            <u32 as static_reflect::StaticReflect>::FFI_SAFETY,
+            <*const u8 as static_reflect::StaticReflect>::FFI_SAFETY,
+            <Header<'static> as static_reflect::StaticReflect>::FFI_SAFETY,
+            <u16 as static_reflect::StaticReflect>::FFI_SAFETY,
+        ]);
+}
+const _: () = {
+    assert ! (< Packet as static_reflect :: FieldReflect > :: NAMED_FIELD_INFO . tag . offset () == core :: mem :: offset_of ! (Packet , tag) , "Field `tag` of `Packet` has a different offset than computed from the previous fields (is the type #[repr(C)]?)");
+    assert ! (< Packet as static_reflect :: FieldReflect > :: NAMED_FIELD_INFO . length . offset () == core :: mem :: offset_of ! (Packet , length) , "Field `length` of `Packet` has a different offset than computed from the previous fields (is the type #[repr(C)]?)");
+    assert ! (< Packet as static_reflect :: FieldReflect > :: NAMED_FIELD_INFO . payload . offset () == core :: mem :: offset_of ! (Packet , payload) , "Field `payload` of `Packet` has a different offset than computed from the previous fields (is the type #[repr(C)]?)");
+    assert ! (< Packet as static_reflect :: FieldReflect > :: NAMED_FIELD_INFO . header . offset () == core :: mem :: offset_of ! (Packet , header) , "Field `header` of `Packet` has a different offset than computed from the previous fields (is the type #[repr(C)]?)");
+    assert ! (< Packet as static_reflect :: FieldReflect > :: NAMED_FIELD_INFO . checksum . offset () == core :: mem :: offset_of ! (Packet , checksum) , "Field `checksum` of `Packet` has a different offset than computed from the previous fields (is the type #[repr(C)]?)");
+};
@@ -0,0 +1,118 @@
+//! Extern types whose layout is only registered at runtime
+//!
+//! NOTE: The registry is global, so each test uses its own extern types.
+use std::alloc::Layout;
+
+use static_reflect::macros::{assert_fully_const, define_extern_type};
+use static_reflect::registry::{
+    extern_layout, register_type, registered_type, set_extern_layout, RegistryError,
+};
+use static_reflect::types::ops::{CopyPlan, StructLayoutBuilder};
+use static_reflect::StaticReflect;
+
+pub struct FooHandle {
+    _opaque: [u8; 0],
+}
+define_extern_type!(FooHandle);
+
+pub struct Conflicting {
+    _opaque: [u8; 0],
+}
+define_extern_type!(Conflicting);
+
+pub struct Unregistered {
+    _opaque: [u8; 0],
+}
+define_extern_type!(Unregistered);
+
+/// An extern type with the same name as a real structure
+mod ffi {
+    use static_reflect::macros::define_extern_type;
+
+    pub struct Colliding {
+        _opaque: [u8; 0],
+    }
+    define_extern_type!(Colliding);
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct Colliding {
+    id: u32,
+}
+
+#[test]
+fn structure_with_extern_field() {
+    set_extern_layout("FooHandle", 24, 8).unwrap();
+    assert_eq!(FooHandle::TYPE_INFO.resolved_size(), Some(24));
+    assert_eq!(FooHandle::TYPE_INFO.resolved_alignment(), Some(8));
+    let mut builder = StructLayoutBuilder::new();
+    assert_eq!(builder.field(&u8::TYPE_INFO), Ok(0));
+    assert_eq!(builder.field(&FooHandle::TYPE_INFO), Ok(8));
+    assert_eq!(builder.field(&u16::TYPE_INFO), Ok(32));
+    assert_eq!(builder.offsets(), &[0, 8, 32]);
+    assert_eq!(builder.finish(), Layout::from_size_align(40, 8).unwrap());
+    let plan = CopyPlan::analyze(&FooHandle::TYPE_INFO).unwrap();
+    assert_eq!(plan.runs().to_vec(), vec![0..24]);
+}
+
+#[test]
+fn conflicting_layouts() {
+    set_extern_layout("Conflicting", 16, 8).unwrap();
+    // Setting the same layout again is fine
+    set_extern_layout("Conflicting", 16, 8).unwrap();
+    let error = set_extern_layout("Conflicting", 32, 8).unwrap_err();
+    assert_eq!(
+        error,
+        RegistryError::ConflictingLayout {
+            name: "Conflicting".into(),
+            existing: Layout::from_size_align(16, 8).unwrap(),
+            requested: Layout::from_size_align(32, 8).unwrap(),
+        }
+    );
+    assert_eq!(Conflicting::TYPE_INFO.resolved_size(), Some(16));
+    assert!(matches!(
+        set_extern_layout("Invalid", 4, 3),
+        Err(RegistryError::InvalidLayout { .. })
+    ));
+}
+
+#[test]
+fn unregistered() {
+    assert_eq!(extern_layout("Unregistered"), None);
+    assert_eq!(Unregistered::TYPE_INFO.resolved_size(), None);
+    assert_eq!(u32::TYPE_INFO.resolved_size(), Some(4));
+    assert!(StructLayoutBuilder::new()
+        .field(&Unregistered::TYPE_INFO)
+        .is_err());
+    assert!(CopyPlan::analyze(&Unregistered::TYPE_INFO).is_err());
+}
+
+#[test]
+fn conflicting_types() {
+    register_type(&Colliding::TYPE_INFO).unwrap();
+    // Registering the same type again is fine
+    register_type(&Colliding::TYPE_INFO).unwrap();
+    let error = register_type(&ffi::Colliding::TYPE_INFO).unwrap_err();
+    assert_eq!(
+        error,
+        RegistryError::ConflictingType {
+            name: "Colliding".into(),
+            existing: &Colliding::TYPE_INFO,
+            requested: &ffi::Colliding::TYPE_INFO,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Conflicting types named Colliding: struct Colliding was already registered, not extern Colliding"
+    );
+    assert_eq!(registered_type("Colliding"), Some(&Colliding::TYPE_INFO));
+    assert_eq!(
+        register_type(&u32::TYPE_INFO),
+        Err(RegistryError::UnnamedType {
+            type_name: "u32".into()
+        })
+    );
+}
+
+assert_fully_const!(Colliding);
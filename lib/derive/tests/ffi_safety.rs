@@ -0,0 +1,194 @@
+//! Tracking which types are safe to pass across `extern "C"` boundaries
+#![allow(improper_ctypes_definitions)]
+use static_reflect::funcs::SignatureDef;
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::audit::{audit_signatures, AuditKind, AuditReport};
+use static_reflect::types::ffi::FfiSafety;
+use static_reflect::{reflect_func, StaticReflect};
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct WithChar {
+    code: u32,
+    #[reflect(assume_repr = "u32")]
+    letter: char,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Halves {
+    low: u64,
+    high: u64,
+}
+
+/// The alignment of `u128` differs between compilers, so it's given explicitly
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C, align(16))]
+struct WithWide {
+    #[reflect(assume_repr_unchecked = "Halves")]
+    wide: u128,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C, align(8))]
+struct WithTuple {
+    #[reflect(assume_repr_unchecked = "u64")]
+    pair: (u32, u32),
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Safe {
+    flag: bool,
+    count: u32,
+    letters: *const u8,
+    empty: (),
+}
+
+/// The warning comes first, but the unsafe field is more severe
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Nested {
+    wide: WithWide,
+    inner: WithChar,
+    safe: Safe,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union CharOrInt {
+    int: u32,
+    #[reflect(assume_repr = "u32")]
+    letter: char,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct CharArray {
+    len: u32,
+    #[reflect(assume_repr = "u32")]
+    letters: [char; 1],
+}
+
+#[reflect_func(absolute)]
+extern "C" fn char_code(value: WithChar) -> u32 {
+    value.code
+}
+
+#[reflect_func(absolute)]
+extern "C" fn pick_letter(value: CharOrInt, index: u32) -> Safe {
+    let _ = (value, index);
+    Safe {
+        flag: false,
+        count: 0,
+        letters: std::ptr::null(),
+        empty: (),
+    }
+}
+
+#[reflect_func(absolute)]
+extern "C" fn add(first: u32, second: u32) -> u32 {
+    first + second
+}
+
+#[test]
+fn structs() {
+    assert_eq!(
+        WithChar::FFI_SAFETY,
+        FfiSafety::Unsafe("field `WithChar.letter`: `char` has no C equivalent, use `u32` instead")
+    );
+    assert_eq!(
+        WithWide::FFI_SAFETY,
+        FfiSafety::Warning(
+            "field `WithWide.wide`: 128-bit integers don't have a stable ABI on every target"
+        )
+    );
+    assert_eq!(
+        WithTuple::FFI_SAFETY,
+        FfiSafety::Unsafe("field `WithTuple.pair`: tuples have an unspecified layout")
+    );
+    assert_eq!(Safe::FFI_SAFETY, FfiSafety::Safe);
+    assert_eq!(
+        CharArray::FFI_SAFETY.reason(),
+        Some("field `CharArray.letters`: `char` has no C equivalent, use `u32` instead")
+    );
+    // Primitives (and types without fields) are always safe
+    assert!(u32::FFI_SAFETY.is_safe());
+    assert!(<*mut WithChar>::FFI_SAFETY.is_safe());
+}
+
+#[test]
+fn nested() {
+    assert_eq!(Nested::FFI_SAFETY, WithChar::FFI_SAFETY);
+    assert_eq!(
+        Nested::FFI_SAFETY.to_string(),
+        "not FFI-safe: field `WithChar.letter`: `char` has no C equivalent, use `u32` instead"
+    );
+    assert_eq!(
+        CharOrInt::FFI_SAFETY,
+        FfiSafety::Unsafe(
+            "field `CharOrInt.letter`: `char` has no C equivalent, use `u32` instead"
+        )
+    );
+}
+
+#[test]
+fn combining() {
+    let warning = FfiSafety::Warning("first");
+    let unsafe_ = FfiSafety::Unsafe("second");
+    assert_eq!(FfiSafety::Safe.or(warning), warning);
+    assert_eq!(warning.or(unsafe_), unsafe_);
+    assert_eq!(unsafe_.or(FfiSafety::Unsafe("third")), unsafe_);
+    assert_eq!(FfiSafety::all(&[]), FfiSafety::Safe);
+    assert_eq!(FfiSafety::all(&[warning, unsafe_, warning]), unsafe_);
+    assert_eq!(FfiSafety::default(), FfiSafety::Safe);
+    assert_eq!(warning.to_string(), "possibly not FFI-safe: first");
+    assert_eq!(FfiSafety::Safe.to_string(), "FFI-safe");
+}
+
+#[test]
+fn signatures() {
+    assert_eq!(_FUNC_char_code.signature.ffi_safety, WithChar::FFI_SAFETY);
+    assert_eq!(
+        _FUNC_pick_letter.signature.ffi_safety,
+        CharOrInt::FFI_SAFETY
+    );
+    assert!(_FUNC_add.signature.ffi_safety.is_safe());
+    assert_eq!(
+        SignatureDef::of::<u32, (u32, WithWide)>().ffi_safety,
+        WithWide::FFI_SAFETY
+    );
+    assert_eq!(
+        SignatureDef::of::<WithTuple, ()>().ffi_safety,
+        WithTuple::FFI_SAFETY
+    );
+    // Only informational, so it doesn't affect matching
+    assert!(SignatureDef::of::<u32, (u32, u32)>().matches_signature(&_FUNC_add.signature));
+}
+
+#[test]
+fn audits() {
+    let report = audit_signatures(&[
+        ("char_code", &_FUNC_char_code.signature),
+        ("add", &_FUNC_add.signature),
+    ]);
+    assert_eq!(report.count(AuditKind::AssumedRepr), 1);
+    assert_eq!(report.entries[0].path, "char_code.0.letter");
+    assert_eq!(report.count(AuditKind::FfiUnsafe), 1);
+    let entry = &report.entries[1];
+    assert_eq!(entry.path, "char_code");
+    assert_eq!(entry.kind, AuditKind::FfiUnsafe);
+    assert_eq!(entry.type_name, "fn(WithChar) -> u32");
+    assert_eq!(report.entries.len(), 2);
+    // Structures are recorded explicitly
+    let mut report = AuditReport::default();
+    report.record_ffi_safety("Safe", "Safe", Safe::FFI_SAFETY);
+    report.record_ffi_safety("WithWide", "WithWide", WithWide::FFI_SAFETY);
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].kind, AuditKind::FfiWarning);
+    assert!(report
+        .to_string()
+        .ends_with("0 fields visited, 1 ffi warning\n"));
+}
+
+assert_fully_const!(WithChar, WithWide, WithTuple, Safe, Nested, CharOrInt, CharArray);
@@ -0,0 +1,61 @@
+//! Walking the entire graph of a type at compile time (`TypeInfo::const_validate`)
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::TypeInfo;
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct Inner {
+    first: u32,
+    second: Option<std::num::NonZeroU8>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct Outer {
+    inner: Inner,
+    #[reflect(bitset(names = "READ, WRITE"))]
+    flags: u8,
+    pointer: *mut Outer,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum Kind {
+    Empty,
+    Full = 8,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub union Either {
+    inner: std::mem::ManuallyDrop<Inner>,
+    number: u64,
+}
+
+assert_fully_const!(Inner, Outer, Kind, Either, (), u64, *mut Outer);
+
+const OUTER_NODES: usize = Outer::TYPE_INFO.const_validate();
+
+#[test]
+fn counts_nodes() {
+    assert_eq!(u32::TYPE_INFO.const_validate(), 1);
+    // `Inner`, `u32`, the niche option and the non-zero `u8` it wraps (which itself wraps a `u8`)
+    assert_eq!(Inner::TYPE_INFO.const_validate(), 5);
+    // Pointers are opaque, so they can't form a cycle
+    assert_eq!(OUTER_NODES, 1 + 5 + 1 + 1);
+    // The variants of a C-style enum aren't types
+    assert_eq!(Kind::TYPE_INFO.const_validate(), 1);
+    assert_eq!(Either::TYPE_INFO.const_validate(), 1 + 5 + 1);
+}
+
+#[test]
+#[should_panic(expected = "TypeInfo graph is nested too deeply")]
+fn depth_limit() {
+    let mut info: &'static TypeInfo = &TypeInfo::Bool;
+    for _ in 0..=TypeInfo::MAX_CONST_DEPTH {
+        info = Box::leak(Box::new(TypeInfo::NonZero(info)));
+    }
+    info.const_validate();
+}
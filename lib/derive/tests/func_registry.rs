@@ -0,0 +1,90 @@
+//! Functions marked `#[reflect_func(register)]` are collected into the global registry
+use static_reflect::funcs::{self, registry, FunctionLocation, ThreadSafety};
+use static_reflect::reflect_func;
+
+#[no_mangle]
+#[reflect_func(register)]
+extern "C" fn registered_add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[reflect_func(absolute)]
+extern "C" fn unregistered_sub(a: u32, b: u32) -> u32 {
+    a - b
+}
+
+#[reflect_func(library = "libm.so.6", thread_safe)]
+extern "C" {
+    #[allow(dead_code)]
+    #[reflect_func(register)]
+    fn cbrt(x: f64) -> f64;
+    #[allow(dead_code)]
+    fn hypot(x: f64, y: f64) -> f64;
+}
+
+mod first {
+    use static_reflect::reflect_func;
+    #[reflect_func(absolute, register)]
+    pub extern "C" fn duplicated() -> u8 {
+        1
+    }
+}
+
+mod second {
+    use static_reflect::reflect_func;
+    #[reflect_func(absolute, register)]
+    pub extern "C" fn duplicated() -> u8 {
+        2
+    }
+}
+
+#[test]
+fn iterate() {
+    let names = funcs::registry().map(|decl| decl.name).collect::<Vec<_>>();
+    for expected in ["registered_add", "cbrt", "duplicated"] {
+        assert!(names.contains(&expected), "Missing {expected} in {names:?}");
+    }
+    assert!(!names.contains(&"unregistered_sub"));
+    assert!(!names.contains(&"hypot"));
+    assert_eq!(registry::iter().count(), funcs::registry().count());
+}
+
+#[test]
+fn find() {
+    assert_eq!(
+        registry::find("registered_add"),
+        [&_FUNC_registered_add.into_erased()]
+    );
+    assert_eq!(registry::find("cbrt"), [&_FUNC_cbrt.into_erased()]);
+    assert!(registry::find("unregistered_sub").is_empty());
+    assert!(registry::find("missing").is_empty());
+    let mut addresses = registry::find("duplicated")
+        .into_iter()
+        .map(|decl| match decl.location {
+            Some(FunctionLocation::AbsoluteAddress(address)) => address,
+            _ => panic!("Expected an absolute address: {decl:?}"),
+        })
+        .collect::<Vec<_>>();
+    addresses.sort();
+    let mut expected = vec![
+        first::duplicated as *const (),
+        second::duplicated as *const (),
+    ];
+    expected.sort();
+    assert_eq!(addresses, expected);
+    assert_eq!(
+        unsafe { std::mem::transmute::<*const (), extern "C" fn() -> u8>(addresses[0])() }
+            + unsafe { std::mem::transmute::<*const (), extern "C" fn() -> u8>(addresses[1])() },
+        3
+    );
+}
+
+#[test]
+fn thread_safety() {
+    // A scheduler can check the registered functions before calling them from other threads
+    let cbrt = registry::find("cbrt")[0];
+    assert_eq!(cbrt.thread_safety, ThreadSafety::Safe);
+    assert_eq!(cbrt.assert_thread_safe(), Ok(()));
+    let add = registry::find("registered_add")[0];
+    assert!(add.assert_thread_safe().is_err());
+}
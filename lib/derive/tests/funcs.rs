@@ -1,8 +1,14 @@
 use std::marker::PhantomData;
 use std::os::raw::c_void;
 
-use static_reflect::funcs::{FunctionDeclaration, FunctionLocation, SignatureDef};
-use static_reflect::types::{FloatSize, TypeInfo};
+use static_reflect::funcs::codegen::{to_extern_block, CodegenError};
+use static_reflect::funcs::{
+    ArgContract, CallingConvention, FunctionDeclaration, FunctionLocation, MissingSymbol,
+    NotThreadSafe, ParamDirection, ResolutionHint, SignatureDef, ThreadSafety, UnwindBehavior,
+};
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::ffi::FfiSafety;
+use static_reflect::types::{FloatSize, IntType, PointerDiscipline, TypeInfo};
 use static_reflect::{reflect_func, StaticReflect};
 
 #[reflect_func]
@@ -26,7 +32,7 @@ extern "C" fn absolute_address_example(x: f64, y: f64) -> f64 {
     (x * x + y * y).sqrt()
 }
 
-#[reflect_func]
+#[reflect_func(library = "libm.so.6", vtable = "LibmVtable")]
 extern "C" {
     /*
      * TODO: These are considered 'dead' even though DuckAsm uses them
@@ -37,7 +43,86 @@ extern "C" {
     #[link_name = "sqrtf"]
     fn sqrt(small: f32) -> f32;
     #[allow(dead_code)]
+    #[reflect_func(library = "libc.so.6", weak)]
     fn malloc(size: usize) -> *mut c_void;
+    #[allow(dead_code)]
+    fn frexp(value: f64, #[reflect(out)] exponent: *mut i32) -> f64;
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C-unwind" fn may_panic(value: i32) -> i32 {
+    assert!(value >= 0, "negative value");
+    value
+}
+
+#[reflect_func(may_unwind)]
+extern "C" {
+    #[allow(dead_code)]
+    fn throws_exception(code: i32);
+    #[allow(dead_code)]
+    fn qsort_callback(value: i32) -> i32;
+}
+
+#[reflect_func]
+extern "C-unwind" {
+    #[allow(dead_code)]
+    fn unwinding_callback(value: i32);
+}
+
+#[reflect_func(thread_safe)]
+extern "C" {
+    #[allow(dead_code)]
+    fn strlen(value: *const u8) -> usize;
+    #[allow(dead_code)]
+    #[reflect_func(not_thread_safe)]
+    fn strtok(value: *mut u8, delimiters: *const u8) -> *mut u8;
+}
+
+#[no_mangle]
+#[reflect_func(not_thread_safe)]
+extern "C" fn increment_counter() -> u32 {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Returns a status, with the actual results written through out-parameters
+#[no_mangle]
+#[reflect_func]
+unsafe extern "C" fn get_pair(
+    #[reflect(out)] out_a: *mut u32,
+    #[reflect(out)] out_b: *mut f32,
+    #[reflect(inout)] counter: *mut u32,
+    flags: u32,
+) -> i32 {
+    *out_a = 7 + flags;
+    *out_b = 2.5;
+    *counter += 1;
+    0
+}
+
+/// Fills a buffer, whose length must be a positive page count
+#[no_mangle]
+#[reflect_func]
+unsafe extern "C" fn fill_pages(
+    #[reflect(out, non_null)] buffer: *mut u8,
+    #[reflect(range(1, 4096))] pages: u32,
+    #[reflect(range(-1, 255))] value: i16,
+    flags: u32,
+) -> i32 {
+    let _ = (buffer, pages, value, flags);
+    0
+}
+
+/// Frees a buffer allocated by the library, logging its borrowed name
+#[no_mangle]
+#[reflect_func]
+unsafe extern "C" fn release_buffer(
+    #[reflect(ptr = "owned", non_null)] buffer: *mut u8,
+    #[reflect(ptr = "borrowed")] name: *const u8,
+    len: usize,
+) {
+    let _ = (buffer, name, len);
 }
 
 #[test]
@@ -47,11 +132,23 @@ fn extern_block() {
         FunctionDeclaration::<*mut c_void, (usize,)> {
             name: "malloc",
             is_unsafe: true, // Foreign functions are always unsafe (in spite of lack of keyword)
-            location: Some(FunctionLocation::DynamicallyLinked { link_name: None }),
+            // NOTE: Overrides the library of the block
+            location: Some(FunctionLocation::DynamicallyLinked {
+                link_name: None,
+                library: Some("libc.so.6"),
+                weak: true,
+            }),
+            unwind: UnwindBehavior::Unknown,
+            thread_safety: ThreadSafety::Unknown,
+            source: None,
             signature: SignatureDef {
                 argument_types: &[usize::TYPE_INFO],
-                return_type: &TypeInfo::Pointer,
-                calling_convention: Default::default()
+                argument_directions: &[ParamDirection::In],
+                argument_contracts: &[],
+                argument_units: &[],
+                return_type: &TypeInfo::MUT_POINTER,
+                calling_convention: Default::default(),
+                ffi_safety: FfiSafety::Safe,
             },
             return_type: PhantomData,
             arg_types: PhantomData
@@ -63,12 +160,21 @@ fn extern_block() {
             name: "sqrt",
             is_unsafe: true, // NOTE: Foreign function
             location: Some(FunctionLocation::DynamicallyLinked {
-                link_name: Some("sqrtf")
+                link_name: Some("sqrtf"),
+                library: Some("libm.so.6"),
+                weak: false,
             }),
+            unwind: UnwindBehavior::Unknown,
+            thread_safety: ThreadSafety::Unknown,
+            source: None,
             signature: SignatureDef {
                 argument_types: &[f32::TYPE_INFO],
+                argument_directions: &[ParamDirection::In],
+                argument_contracts: &[],
+                argument_units: &[],
                 return_type: &f32::TYPE_INFO,
-                calling_convention: Default::default()
+                calling_convention: Default::default(),
+                ffi_safety: FfiSafety::Safe,
             },
             return_type: PhantomData,
             arg_types: PhantomData
@@ -85,11 +191,22 @@ fn rust_funcs() {
         FunctionDeclaration::<f32, (u32, *mut String)> {
             name: "dynamically_linked",
             is_unsafe: true,
-            location: Some(FunctionLocation::DynamicallyLinked { link_name: None }),
+            location: Some(FunctionLocation::DynamicallyLinked {
+                link_name: None,
+                library: None,
+                weak: false,
+            }),
+            unwind: UnwindBehavior::Aborts,
+            thread_safety: ThreadSafety::Unknown,
+            source: None,
             signature: SignatureDef {
-                argument_types: &[u32::TYPE_INFO, TypeInfo::Pointer],
+                argument_types: &[u32::TYPE_INFO, TypeInfo::MUT_POINTER],
+                argument_directions: &[ParamDirection::In, ParamDirection::In],
+                argument_contracts: &[],
+                argument_units: &[],
                 return_type: &TypeInfo::F32,
-                calling_convention: Default::default()
+                calling_convention: Default::default(),
+                ffi_safety: FfiSafety::Safe,
             },
             return_type: PhantomData,
             arg_types: PhantomData
@@ -101,8 +218,13 @@ fn rust_funcs() {
             name: "stupid_name",
             is_unsafe: false,
             location: Some(FunctionLocation::DynamicallyLinked {
-                link_name: Some("better_name")
+                link_name: Some("better_name"),
+                library: None,
+                weak: false,
             }),
+            unwind: UnwindBehavior::Aborts,
+            thread_safety: ThreadSafety::Unknown,
+            source: None,
             signature: SignatureDef {
                 argument_types: &[
                     f32::TYPE_INFO,
@@ -110,8 +232,12 @@ fn rust_funcs() {
                         size: FloatSize::Single
                     }
                 ],
+                argument_directions: &[ParamDirection::In, ParamDirection::In],
+                argument_contracts: &[],
+                argument_units: &[],
                 return_type: &TypeInfo::Unit,
-                calling_convention: Default::default()
+                calling_convention: Default::default(),
+                ffi_safety: FfiSafety::Safe,
             },
             return_type: PhantomData,
             arg_types: PhantomData
@@ -125,13 +251,368 @@ fn rust_funcs() {
             location: Some(FunctionLocation::AbsoluteAddress(
                 absolute_address_example as *const ()
             )),
+            unwind: UnwindBehavior::Aborts,
+            thread_safety: ThreadSafety::Unknown,
+            source: None,
             signature: SignatureDef {
                 argument_types: &[f64::TYPE_INFO, f64::TYPE_INFO],
+                argument_directions: &[ParamDirection::In, ParamDirection::In],
+                argument_contracts: &[],
+                argument_units: &[],
                 return_type: &f64::TYPE_INFO,
-                calling_convention: Default::default()
+                calling_convention: Default::default(),
+                ffi_safety: FfiSafety::Safe,
             },
             return_type: PhantomData,
             arg_types: PhantomData
         }
     );
 }
+
+#[test]
+fn out_params() {
+    assert_eq!(
+        _FUNC_get_pair.signature.argument_directions,
+        &[
+            ParamDirection::Out,
+            ParamDirection::Out,
+            ParamDirection::InOut,
+            ParamDirection::In
+        ]
+    );
+    assert_eq!(
+        _FUNC_frexp.signature.argument_directions,
+        &[ParamDirection::In, ParamDirection::Out]
+    );
+    // The attributes are stripped, so the function is still callable
+    let (mut a, mut b, mut counter) = (0u32, 0f32, 5u32);
+    assert_eq!(unsafe { get_pair(&mut a, &mut b, &mut counter, 1) }, 0);
+    assert_eq!((a, b, counter), (8, 2.5, 6));
+}
+
+#[test]
+fn contracts() {
+    let signature = &_FUNC_fill_pages.signature;
+    assert_eq!(
+        signature.argument_directions,
+        &[
+            ParamDirection::Out,
+            ParamDirection::In,
+            ParamDirection::In,
+            ParamDirection::In
+        ]
+    );
+    assert_eq!(
+        signature.contract(0),
+        Some(ArgContract {
+            non_null: true,
+            range: None,
+            pointer_discipline: None,
+        })
+    );
+    assert_eq!(
+        signature.contract(1),
+        Some(ArgContract {
+            non_null: false,
+            range: Some((1, 4096)),
+            pointer_discipline: None,
+        })
+    );
+    assert_eq!(signature.contract(2).unwrap().range, Some((-1, 255)));
+    assert!(signature.contract(3).unwrap().is_unconstrained());
+    assert_eq!(signature.contract(4), None);
+    assert_eq!(signature.contract(0).unwrap().to_string(), "non-null");
+    assert_eq!(signature.contract(1).unwrap().to_string(), "1..=4096");
+    assert_eq!(signature.contract(3).unwrap().to_string(), "unconstrained");
+    assert!(signature.contract(1).unwrap().allows_int(4096));
+    assert!(!signature.contract(1).unwrap().allows_int(0));
+    assert!(!signature
+        .contract(0)
+        .unwrap()
+        .allows_ptr(std::ptr::null::<u8>()));
+    // Functions without any contracts don't record them
+    assert!(_FUNC_get_pair.signature.argument_contracts.is_empty());
+    assert!(_FUNC_get_pair
+        .signature
+        .contract(0)
+        .unwrap()
+        .is_unconstrained());
+}
+
+#[test]
+fn pointer_disciplines() {
+    let signature = &_FUNC_release_buffer.signature;
+    assert_eq!(
+        signature.contract(0),
+        Some(ArgContract {
+            non_null: true,
+            range: None,
+            pointer_discipline: Some(PointerDiscipline::Owned),
+        })
+    );
+    assert_eq!(
+        signature.contract(1).unwrap().pointer_discipline,
+        Some(PointerDiscipline::Borrowed)
+    );
+    assert!(signature.contract(2).unwrap().is_unconstrained());
+    assert_eq!(
+        signature.contract(0).unwrap().to_string(),
+        "owned, non-null"
+    );
+    assert_eq!(signature.contract(1).unwrap().to_string(), "borrowed");
+}
+
+#[test]
+fn resolution_hints() {
+    assert_eq!(
+        _FUNC_sqrt.resolution_hint(),
+        Some(ResolutionHint {
+            symbol: "sqrtf",
+            library: Some("libm.so.6"),
+            weak: false,
+        })
+    );
+    let malloc = _FUNC_malloc.resolution_hint().unwrap();
+    assert_eq!(malloc.to_string(), "malloc from libc.so.6 (weak)");
+    assert_eq!(
+        _FUNC_frexp.location.unwrap().to_string(),
+        "dynamically linked from libm.so.6"
+    );
+    assert_eq!(
+        _FUNC_stupid_name.resolution_hint().unwrap().to_string(),
+        "better_name"
+    );
+    assert_eq!(_FUNC_absolute_address_example.resolution_hint(), None);
+}
+
+#[test]
+fn extern_block_codegen() {
+    let source = to_extern_block(&[
+        *_FUNC_sqrt.erase(),
+        *_FUNC_malloc.erase(),
+        *_FUNC_frexp.erase(),
+        *_FUNC_stupid_name.erase(),
+    ])
+    .unwrap();
+    // NOTE: usize is reflected as an integer of the same width
+    assert_eq!(
+        source,
+        format!(
+            r#"extern "C" {{
+    #[link_name = "sqrtf"]
+    pub fn sqrt(arg0: f32) -> f32;
+    pub fn malloc(arg0: {}) -> *mut ::core::ffi::c_void;
+    pub fn frexp(arg0: f64, arg1: *mut ::core::ffi::c_void) -> f64;
+    #[link_name = "better_name"]
+    pub fn stupid_name(arg0: f32, arg1: f32);
+}}
+"#,
+            usize::TYPE_INFO
+        )
+    );
+    // Round trip through syn, to check it's valid Rust
+    let parsed = syn::parse_str::<syn::ItemForeignMod>(&source).unwrap();
+    assert_eq!(parsed.items.len(), 4);
+    assert_eq!(
+        to_extern_block(&[*_FUNC_absolute_address_example.erase()]),
+        Err(CodegenError::AbsoluteAddress {
+            function: "absolute_address_example"
+        })
+    );
+}
+
+#[test]
+fn unwinding() {
+    assert_eq!(_FUNC_may_panic.unwind, UnwindBehavior::MayUnwind);
+    assert!(_FUNC_may_panic.may_unwind());
+    assert_eq!(_FUNC_throws_exception.unwind, UnwindBehavior::MayUnwind);
+    assert_eq!(_FUNC_qsort_callback.unwind, UnwindBehavior::MayUnwind);
+    assert_eq!(_FUNC_unwinding_callback.unwind, UnwindBehavior::MayUnwind);
+    // Rust aborts instead of unwinding out of `extern "C"`
+    assert!(!_FUNC_stupid_name.may_unwind());
+    // Nothing is known about other foreign functions, so they might unwind
+    assert_eq!(_FUNC_sqrt.unwind, UnwindBehavior::Unknown);
+    assert!(_FUNC_sqrt.may_unwind());
+    assert_eq!(UnwindBehavior::MayUnwind.to_string(), "may unwind");
+    assert_eq!(
+        to_extern_block(&[*_FUNC_may_panic.erase(), *_FUNC_stupid_name.erase()]).unwrap(),
+        r#"extern "C" {
+    #[link_name = "better_name"]
+    pub fn stupid_name(arg0: f32, arg1: f32);
+}
+extern "C-unwind" {
+    pub fn may_panic(arg0: i32) -> i32;
+}
+"#
+    );
+}
+
+#[test]
+fn thread_safety() {
+    // The block's option is the default
+    assert_eq!(_FUNC_strlen.thread_safety, ThreadSafety::Safe);
+    assert_eq!(_FUNC_strlen.assert_thread_safe(), Ok(()));
+    // Which each function may override
+    assert_eq!(_FUNC_strtok.thread_safety, ThreadSafety::Unsafe);
+    let error = _FUNC_strtok.assert_thread_safe().unwrap_err();
+    assert_eq!(
+        error,
+        NotThreadSafe {
+            name: "strtok",
+            thread_safety: ThreadSafety::Unsafe
+        }
+    );
+    assert_eq!(error.to_string(), "Function strtok isn't thread-safe");
+    assert_eq!(_FUNC_increment_counter.thread_safety, ThreadSafety::Unsafe);
+    // Nothing is known about unmarked functions
+    assert_eq!(_FUNC_sqrt.thread_safety, ThreadSafety::Unknown);
+    assert_eq!(_FUNC_stupid_name.thread_safety, ThreadSafety::Unknown);
+    assert!(!_FUNC_sqrt.thread_safety.is_thread_safe());
+    assert_eq!(
+        _FUNC_sqrt.assert_thread_safe().unwrap_err().to_string(),
+        "Function sqrt isn't known to be thread-safe"
+    );
+    // Erasing preserves the thread safety
+    assert_eq!(
+        _FUNC_strtok.into_erased().thread_safety,
+        ThreadSafety::Unsafe
+    );
+    assert_eq!(ThreadSafety::Safe.to_string(), "thread-safe");
+}
+
+#[test]
+fn downcast() {
+    let erased = *_FUNC_frexp.erase();
+    assert_eq!(erased.downcast::<f64, (f64, *mut i32)>(), Ok(_FUNC_frexp));
+    let err = erased.downcast::<f32, (f32,)>().unwrap_err();
+    assert_eq!(err.expected, "fn(f32) -> f32");
+    assert_eq!(
+        err.actual,
+        format!("fn(f64, {}) -> f64", TypeInfo::MUT_POINTER)
+    );
+    assert!(_FUNC_stupid_name
+        .erase()
+        .downcast::<(), (f32, f32)>()
+        .is_ok());
+}
+
+#[test]
+fn anonymous_signatures() {
+    const HYPOT: SignatureDef = SignatureDef::of::<f64, (f64, f64)>();
+    assert_eq!(HYPOT, _FUNC_absolute_address_example.signature);
+    assert!(_FUNC_absolute_address_example.signature_matches(&HYPOT));
+    assert_eq!(HYPOT.to_string(), "fn(f64, f64) -> f64");
+    // The directions of the arguments are ignored
+    let frexp = SignatureDef::of::<f64, (f64, *mut i32)>();
+    assert_eq!(frexp.argument_directions, &[ParamDirection::In; 2]);
+    assert_ne!(frexp, _FUNC_frexp.signature);
+    assert!(_FUNC_frexp.signature_matches(&frexp));
+    assert!(frexp.matches_signature(&_FUNC_frexp.signature));
+    // Different argument or return types
+    assert!(!_FUNC_sqrt.signature_matches(&SignatureDef::of::<f64, (f64,)>()));
+    assert!(!_FUNC_sqrt.signature_matches(&SignatureDef::of::<(), (f32,)>()));
+    assert!(!_FUNC_stupid_name.signature_matches(&SignatureDef::of::<(), (f32,)>()));
+    assert!(!_FUNC_frexp.signature_matches(&HYPOT));
+    assert!(_FUNC_stupid_name.signature_matches(&SignatureDef::of::<(), (f32, f32)>()));
+    assert_eq!(SignatureDef::of::<(), ()>(), SignatureDef::default());
+    assert_eq!(SignatureDef::of::<(), ()>().to_string(), "fn()");
+}
+
+extern "C" fn fake_sqrt(value: f32) -> f32 {
+    value.sqrt()
+}
+extern "C" fn fake_frexp(value: f64, exponent: *mut i32) -> f64 {
+    unsafe { *exponent = 0 };
+    value
+}
+
+#[test]
+fn vtable() {
+    assert_eq!(
+        LibmVtable::DECLARATIONS,
+        &[
+            *_FUNC_sqrt.erase(),
+            *_FUNC_malloc.erase(),
+            *_FUNC_frexp.erase()
+        ]
+    );
+    let mut requested = Vec::new();
+    let vtable = unsafe {
+        LibmVtable::from_loader(|symbol| {
+            requested.push(symbol.to_string());
+            match symbol {
+                "sqrtf" => Some(fake_sqrt as *const ()),
+                "frexp" => Some(fake_frexp as *const ()),
+                _ => None,
+            }
+        })
+    }
+    .unwrap();
+    // Resolved by link name, in declaration order
+    assert_eq!(requested, ["sqrtf", "malloc", "frexp"]);
+    assert_eq!(unsafe { (vtable.sqrt)(16.0) }, 4.0);
+    // The weak function is optional
+    assert!(vtable.malloc.is_none());
+    let err = unsafe { LibmVtable::from_loader(|_| None) }.unwrap_err();
+    assert_eq!(err, MissingSymbol { symbol: "sqrtf" });
+    assert_eq!(err.to_string(), "Missing symbol: sqrtf");
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum SmallEnum {
+    First,
+    Second,
+}
+
+#[derive(StaticReflect)]
+#[repr(i64)]
+#[allow(dead_code)]
+enum WideEnum {
+    First,
+}
+
+#[test]
+fn variadic_promotions() {
+    const I32: TypeInfo = TypeInfo::Integer(IntType::I32);
+    const U32: TypeInfo = TypeInfo::Integer(IntType::U32);
+    let table: &[(TypeInfo, TypeInfo)] = &[
+        (bool::TYPE_INFO, I32),
+        (u8::TYPE_INFO, U32),
+        (u16::TYPE_INFO, U32),
+        (u32::TYPE_INFO, U32),
+        (u64::TYPE_INFO, u64::TYPE_INFO),
+        (i8::TYPE_INFO, I32),
+        (i16::TYPE_INFO, I32),
+        (i32::TYPE_INFO, I32),
+        (i64::TYPE_INFO, i64::TYPE_INFO),
+        (usize::TYPE_INFO, usize::TYPE_INFO),
+        (isize::TYPE_INFO, isize::TYPE_INFO),
+        (f32::TYPE_INFO, f64::TYPE_INFO),
+        (f64::TYPE_INFO, f64::TYPE_INFO),
+        (<()>::TYPE_INFO, <()>::TYPE_INFO),
+        (<*const u8>::TYPE_INFO, <*const u8>::TYPE_INFO),
+        (<*mut u16>::TYPE_INFO, <*mut u16>::TYPE_INFO),
+        (std::num::NonZeroU8::TYPE_INFO, U32),
+        (TypeInfo::NonZero(&i16::TYPE_INFO), I32),
+        (
+            TypeInfo::NonZero(&u64::TYPE_INFO),
+            TypeInfo::NonZero(&u64::TYPE_INFO),
+        ),
+        (SmallEnum::TYPE_INFO, U32),
+        (WideEnum::TYPE_INFO, WideEnum::TYPE_INFO),
+    ];
+    for (original, promoted) in table {
+        assert_eq!(
+            CallingConvention::StandardC.promote(original),
+            *promoted,
+            "Promoting {}",
+            original
+        );
+        // Promotion is idempotent
+        assert_eq!(CallingConvention::StandardC.promote(promoted), *promoted);
+    }
+}
+
+assert_fully_const!(SmallEnum, WideEnum);
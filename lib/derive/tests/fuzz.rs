@@ -0,0 +1,152 @@
+//! Running reflected functions on fixed inputs, through their fuzz harnesses
+use std::ffi::c_void;
+
+use static_reflect::builtins::{AsmSlice, AsmStr};
+use static_reflect::funcs::fuzz::{make_harness, make_harness_with_arena, Outcome, Unsupported};
+use static_reflect::reflect_func;
+use static_reflect::types::value::ReflectValue;
+use static_reflect::types::IntType;
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn fuzz_checksum(seed: u32, double: bool, text: AsmStr, values: AsmSlice<u16>) -> u64 {
+    let text = unsafe { std::slice::from_raw_parts(text.bytes_ptr(), text.len()) };
+    let values = unsafe { std::slice::from_raw_parts(values.ptr, values.len) };
+    let sum = seed as u64
+        + text.iter().map(|&b| b as u64).sum::<u64>()
+        + values.iter().map(|&v| v as u64).sum::<u64>();
+    if double {
+        sum * 2
+    } else {
+        sum
+    }
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C-unwind" fn fuzz_divide(a: i32, b: i32) -> i32 {
+    assert_ne!(b, 0, "division by zero");
+    a / b
+}
+
+#[no_mangle]
+#[reflect_func]
+unsafe extern "C" fn fuzz_deref(value: *const u32) -> u32 {
+    *value
+}
+
+fn u64_value(bits: u64) -> ReflectValue {
+    ReflectValue::Integer {
+        int_type: IntType::U64,
+        bits,
+    }
+}
+
+#[test]
+fn decode_arguments() {
+    let harness = make_harness(&_FUNC_fuzz_checksum.into_erased(), _INVOKE_fuzz_checksum).unwrap();
+    let mut input = Vec::new();
+    input.extend_from_slice(&7u32.to_le_bytes());
+    input.push(0);
+    // The text "ab", followed by two values
+    input.extend_from_slice(&2u16.to_le_bytes());
+    input.extend_from_slice(b"ab");
+    input.extend_from_slice(&2u16.to_le_bytes());
+    input.extend_from_slice(&100u16.to_le_bytes());
+    input.extend_from_slice(&1000u16.to_le_bytes());
+    let expected = 7 + (b'a' + b'b') as u64 + 1100;
+    assert_eq!(
+        unsafe { harness.run(&input) },
+        Outcome::Returned(u64_value(expected))
+    );
+    // Only the lowest bit of a bool is used
+    input[4] = 0xFF;
+    assert_eq!(
+        unsafe { harness.run(&input) },
+        Outcome::Returned(u64_value(expected * 2))
+    );
+    // Lengths are clamped to the remaining input, so this has one value
+    input.truncate(input.len() - 1);
+    assert_eq!(
+        unsafe { harness.run(&input) },
+        Outcome::Returned(u64_value(2 * (7 + (b'a' + b'b') as u64 + 100)))
+    );
+    // Invalid UTF-8 is truncated to the valid prefix
+    let mut input = vec![0, 0, 0, 0, 0];
+    input.extend_from_slice(&3u16.to_le_bytes());
+    input.extend_from_slice(&[b'a', 0xFF, b'b']);
+    assert_eq!(
+        unsafe { harness.run(&input) },
+        Outcome::Returned(u64_value(b'a' as u64))
+    );
+    // Missing lengths are empty, but missing fixed-width values skip the input
+    assert_eq!(
+        unsafe { harness.run(&[1, 0, 0, 0, 0]) },
+        Outcome::Returned(u64_value(1))
+    );
+    assert_eq!(unsafe { harness.run(&[1, 0, 0]) }, Outcome::Skipped);
+    assert_eq!(unsafe { harness.run(&[]) }, Outcome::Skipped);
+}
+
+#[test]
+fn panics() {
+    let harness = make_harness(&_FUNC_fuzz_divide.into_erased(), _INVOKE_fuzz_divide).unwrap();
+    let input = [(-9i32).to_le_bytes(), 3i32.to_le_bytes()].concat();
+    assert_eq!(
+        unsafe { harness.run(&input) },
+        Outcome::Returned(ReflectValue::Integer {
+            int_type: IntType::I32,
+            bits: (-3i32) as u32 as u64,
+        })
+    );
+    let input = [1i32.to_le_bytes(), 0i32.to_le_bytes()].concat();
+    match unsafe { harness.run(&input) } {
+        Outcome::Panicked(Some(message)) => assert!(
+            message.contains("division by zero"),
+            "Unexpected message: {}",
+            message
+        ),
+        other => panic!("Expected a panic, got {:?}", other),
+    }
+}
+
+#[test]
+fn pointers() {
+    let decl = _FUNC_fuzz_deref.into_erased();
+    assert_eq!(
+        make_harness(&decl, _INVOKE_fuzz_deref).err(),
+        Some(Unsupported {
+            function: "fuzz_deref",
+            index: Some(0),
+            type_name: decl.signature.argument_types[0].to_string(),
+        })
+    );
+    static VALUES: [u32; 3] = [10, 20, 30];
+    let harness = make_harness_with_arena(&decl, _INVOKE_fuzz_deref, |choice| {
+        &VALUES[(choice % 3) as usize] as *const u32 as *mut c_void
+    })
+    .unwrap();
+    assert_eq!(
+        unsafe { harness.run(&4u64.to_le_bytes()) },
+        Outcome::Returned(ReflectValue::Integer {
+            int_type: IntType::U32,
+            bits: 20,
+        })
+    );
+    assert_eq!(unsafe { harness.run(&[4]) }, Outcome::Skipped);
+}
+
+mod entry {
+    static_reflect::fuzz_reflected!(super::_FUNC_fuzz_divide, super::_INVOKE_fuzz_divide);
+}
+
+#[test]
+fn libfuzzer_entry() {
+    let input = [8i32.to_le_bytes(), 2i32.to_le_bytes()].concat();
+    assert_eq!(
+        entry::LLVMFuzzerTestOneInput(input.as_ptr(), input.len()),
+        0
+    );
+    // Short inputs are skipped, while panics abort the process (which libFuzzer reports)
+    assert_eq!(entry::LLVMFuzzerTestOneInput(std::ptr::null(), 0), 0);
+}
@@ -0,0 +1,153 @@
+//! Matching on types by their `TypeInfoKey`
+use std::collections::HashMap;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::key::TypeInfoKey;
+use static_reflect::{StaticReflect, TypeInfo};
+
+mod first {
+    use static_reflect::StaticReflect;
+
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    #[reflect(lazy_fields)]
+    pub union Value {
+        pub int: u32,
+    }
+}
+
+mod second {
+    use static_reflect::StaticReflect;
+
+    /// Identical to `first::Point`
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+
+    /// Only differs from `first::Value` in the type of its field
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    #[reflect(lazy_fields)]
+    pub union Value {
+        pub int: f32,
+    }
+}
+
+/// Like `Point`, but with its fields swapped
+#[derive(StaticReflect)]
+#[repr(C)]
+struct SwappedPoint {
+    y: u32,
+    x: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Line {
+    start: first::Point,
+    end: first::Point,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Color {
+    Red,
+    Green = 7,
+}
+
+const POINT: TypeInfoKey = TypeInfo::key(&first::Point::TYPE_INFO);
+const LINE: TypeInfoKey = TypeInfoKey::of::<Line>();
+const COLOR: TypeInfoKey = TypeInfoKey::of::<Color>();
+const U32: TypeInfoKey = TypeInfoKey::of::<u32>();
+
+fn describe(info: &TypeInfo) -> &'static str {
+    match info.key() {
+        POINT if info.key_eq(&first::Point::TYPE_INFO) => "point",
+        LINE => "line",
+        COLOR => "color",
+        U32 => "u32",
+        _ => "unknown",
+    }
+}
+
+#[test]
+fn match_arms() {
+    assert_eq!(describe(&first::Point::TYPE_INFO), "point");
+    assert_eq!(describe(&Line::TYPE_INFO), "line");
+    assert_eq!(describe(&Color::TYPE_INFO), "color");
+    assert_eq!(describe(&u32::TYPE_INFO), "u32");
+    assert_eq!(describe(&i32::TYPE_INFO), "unknown");
+    assert_eq!(describe(&SwappedPoint::TYPE_INFO), "unknown");
+}
+
+#[test]
+fn distinct_types() {
+    let keys = [
+        POINT,
+        LINE,
+        COLOR,
+        U32,
+        TypeInfoKey::of::<SwappedPoint>(),
+        TypeInfoKey::of::<i32>(),
+        TypeInfoKey::of::<u64>(),
+        TypeInfoKey::of::<*const u32>(),
+        TypeInfoKey::of::<*mut u32>(),
+        TypeInfoKey::of::<()>(),
+        TypeInfoKey::of::<bool>(),
+        TypeInfoKey::of::<std::ptr::NonNull<u8>>(),
+        TypeInfoKey::of::<Option<std::ptr::NonNull<u8>>>(),
+    ];
+    for (index, key) in keys.iter().enumerate() {
+        for other in &keys[index + 1..] {
+            assert_ne!(key, other);
+        }
+    }
+}
+
+#[test]
+fn equal_structures() {
+    assert_eq!(first::Point::TYPE_INFO, second::Point::TYPE_INFO);
+    assert_eq!(POINT, TypeInfoKey::of::<second::Point>());
+    assert_eq!(POINT.hash(), second::Point::TYPE_INFO.key().hash());
+    assert_eq!(describe(&second::Point::TYPE_INFO), "point");
+    assert!(second::Point::TYPE_INFO.key_eq(&first::Point::TYPE_INFO));
+    assert!(!SwappedPoint::TYPE_INFO.key_eq(&first::Point::TYPE_INFO));
+    // The key computed at runtime is the same as the constant
+    assert_eq!(Line::TYPE_INFO.key(), LINE);
+}
+
+#[test]
+fn lazy_unions() {
+    // The fields of lazy unions aren't hashed, so these collide
+    assert_eq!(
+        TypeInfoKey::of::<first::Value>(),
+        TypeInfoKey::of::<second::Value>()
+    );
+    assert!(!first::Value::TYPE_INFO.key_eq(&second::Value::TYPE_INFO));
+    assert!(first::Value::TYPE_INFO.key_eq(&first::Value::TYPE_INFO));
+}
+
+#[test]
+fn map_keys() {
+    let mut names = HashMap::new();
+    names.insert(POINT, "point");
+    names.insert(LINE, "line");
+    assert_eq!(
+        names.get(&TypeInfoKey::of::<second::Point>()),
+        Some(&"point")
+    );
+    assert_eq!(names.get(&COLOR), None);
+}
+
+assert_fully_const!(SwappedPoint, Line, Color);
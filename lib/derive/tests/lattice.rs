@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::lattice::{can_convert_lossless, can_widen, common_arithmetic_type};
+use static_reflect::types::{FloatSize, IntType, PrimitiveType, TypeId};
+use static_reflect::StaticReflect;
+
+/// All the primitives in the order used by the tables
+const NAMES: [&str; 13] = [
+    "()", "bool", "ptr", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
+];
+
+fn primitive(name: &str) -> PrimitiveType {
+    match name {
+        "()" => PrimitiveType::Unit,
+        "bool" => PrimitiveType::Bool,
+        "ptr" => PrimitiveType::Pointer,
+        "u8" => PrimitiveType::Integer(IntType::U8),
+        "u16" => PrimitiveType::Integer(IntType::U16),
+        "u32" => PrimitiveType::Integer(IntType::U32),
+        "u64" => PrimitiveType::Integer(IntType::U64),
+        "i8" => PrimitiveType::Integer(IntType::I8),
+        "i16" => PrimitiveType::Integer(IntType::I16),
+        "i32" => PrimitiveType::Integer(IntType::I32),
+        "i64" => PrimitiveType::Integer(IntType::I64),
+        "f32" => PrimitiveType::Float {
+            size: FloatSize::Single,
+        },
+        "f64" => PrimitiveType::Float {
+            size: FloatSize::Double,
+        },
+        _ => panic!("Unknown primitive: {}", name),
+    }
+}
+
+fn all_primitives() -> Vec<PrimitiveType> {
+    NAMES.iter().map(|name| primitive(name)).collect()
+}
+
+/// Whether the row type can be converted losslessly into the column type
+#[rustfmt::skip]
+const LOSSLESS: [&str; 13] = [
+//   ()  bool ptr u8 u16 u32 u64 i8 i16 i32 i64 f32 f64
+    "x   .    .   .  .   .   .   .  .   .   .   .   .", // ()
+    ".   x    .   .  .   .   .   .  .   .   .   .   .", // bool
+    ".   .    x   .  .   .   .   .  .   .   .   .   .", // ptr
+    ".   .    .   x  x   x   x   .  x   x   x   x   x", // u8
+    ".   .    .   .  x   x   x   .  .   x   x   x   x", // u16
+    ".   .    .   .  .   x   x   .  .   .   x   .   x", // u32
+    ".   .    .   .  .   .   x   .  .   .   .   .   .", // u64
+    ".   .    .   .  .   .   .   x  x   x   x   x   x", // i8
+    ".   .    .   .  .   .   .   .  x   x   x   x   x", // i16
+    ".   .    .   .  .   .   .   .  .   x   x   .   x", // i32
+    ".   .    .   .  .   .   .   .  .   .   x   .   .", // i64
+    ".   .    .   .  .   .   .   .  .   .   .   x   x", // f32
+    ".   .    .   .  .   .   .   .  .   .   .   .   x", // f64
+];
+
+/// The common arithmetic type of the row and column types (`-` for none)
+#[rustfmt::skip]
+const COMMON: [&str; 13] = [
+//   ()  bool ptr u8  u16 u32 u64 i8  i16 i32 i64 f32 f64
+    "-   -    -   -   -   -   -   -   -   -   -   -   -", // ()
+    "-   -    -   -   -   -   -   -   -   -   -   -   -", // bool
+    "-   -    -   -   -   -   -   -   -   -   -   -   -", // ptr
+    "-   -    -   i32 i32 u32 u64 i32 i32 i32 i64 f32 f64", // u8
+    "-   -    -   i32 i32 u32 u64 i32 i32 i32 i64 f32 f64", // u16
+    "-   -    -   u32 u32 u32 u64 u32 u32 u32 i64 f32 f64", // u32
+    "-   -    -   u64 u64 u64 u64 u64 u64 u64 u64 f32 f64", // u64
+    "-   -    -   i32 i32 u32 u64 i32 i32 i32 i64 f32 f64", // i8
+    "-   -    -   i32 i32 u32 u64 i32 i32 i32 i64 f32 f64", // i16
+    "-   -    -   i32 i32 u32 u64 i32 i32 i32 i64 f32 f64", // i32
+    "-   -    -   i64 i64 i64 u64 i64 i64 i64 i64 f32 f64", // i64
+    "-   -    -   f32 f32 f32 f32 f32 f32 f32 f32 f32 f64", // f32
+    "-   -    -   f64 f64 f64 f64 f64 f64 f64 f64 f64 f64", // f64
+];
+
+fn table(rows: &[&'static str; 13]) -> Vec<Vec<&'static str>> {
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<&str> = row.split_whitespace().collect();
+            assert_eq!(cells.len(), NAMES.len(), "Invalid row: {:?}", row);
+            cells
+        })
+        .collect()
+}
+
+#[test]
+fn lossless_conversions() {
+    let table = table(&LOSSLESS);
+    for (i, from) in NAMES.iter().enumerate() {
+        for (j, to) in NAMES.iter().enumerate() {
+            let expected = match table[i][j] {
+                "x" => true,
+                "." => false,
+                cell => panic!("Invalid cell: {}", cell),
+            };
+            assert_eq!(
+                can_convert_lossless(primitive(from), primitive(to)),
+                expected,
+                "{} -> {}",
+                from,
+                to
+            );
+            if let (PrimitiveType::Integer(a), PrimitiveType::Integer(b)) =
+                (primitive(from), primitive(to))
+            {
+                assert_eq!(can_widen(a, b), expected, "{} -> {}", from, to);
+            }
+        }
+    }
+}
+
+#[test]
+fn common_types() {
+    let table = table(&COMMON);
+    for (i, a) in NAMES.iter().enumerate() {
+        for (j, b) in NAMES.iter().enumerate() {
+            let expected = match table[i][j] {
+                "-" => None,
+                name => Some(primitive(name)),
+            };
+            assert_eq!(
+                common_arithmetic_type(primitive(a), primitive(b)),
+                expected,
+                "common({}, {})",
+                a,
+                b
+            );
+        }
+    }
+}
+
+#[test]
+fn partial_order() {
+    let all = all_primitives();
+    for &a in &all {
+        for &b in &all {
+            let expected = if a == b {
+                Some(Ordering::Equal)
+            } else if can_convert_lossless(a, b) {
+                Some(Ordering::Less)
+            } else if can_convert_lossless(b, a) {
+                Some(Ordering::Greater)
+            } else {
+                None
+            };
+            assert_eq!(a.partial_cmp(&b), expected, "{:?} vs {:?}", a, b);
+            // Transitivity
+            for &c in &all {
+                if a <= b && b <= c {
+                    assert!(a <= c, "{:?} <= {:?} <= {:?}", a, b, c);
+                }
+            }
+        }
+    }
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct NotPrimitive {
+    value: u32,
+}
+
+#[test]
+fn type_id_queries() {
+    let u8_type = TypeId::<u8>::get();
+    let i64_type = TypeId::<i64>::get();
+    let f32_type = TypeId::<f32>::get();
+    let struct_type = TypeId::<NotPrimitive>::get();
+    assert_eq!(
+        u8_type.common_arithmetic_type(i64_type),
+        Some(primitive("i64"))
+    );
+    assert_eq!(
+        i64_type.common_arithmetic_type(f32_type),
+        Some(primitive("f32"))
+    );
+    assert_eq!(u8_type.common_arithmetic_type(struct_type), None);
+    assert_eq!(TypeId::<bool>::get().common_arithmetic_type(u8_type), None);
+    assert_eq!(u8_type.can_widen(i64_type), Some(true));
+    assert_eq!(i64_type.can_widen(u8_type), Some(false));
+    assert_eq!(u8_type.can_widen(f32_type), None);
+    assert_eq!(u8_type.can_convert_lossless(f32_type), Some(true));
+    assert_eq!(i64_type.can_convert_lossless(f32_type), Some(false));
+    assert_eq!(struct_type.can_convert_lossless(u8_type), None);
+}
+
+assert_fully_const!(NotPrimitive);
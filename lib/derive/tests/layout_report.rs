@@ -0,0 +1,101 @@
+//! Reports comparing the computed layout against the real one
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::report::{FieldLayout, LayoutReport};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Padded {
+    small: u8,
+    large: u64,
+    #[reflect(rename = "renamed")]
+    medium: u16,
+    #[reflect(assume_repr = "i32")]
+    assumed: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Pair(u8, *mut Padded);
+
+#[test]
+fn padded_report() {
+    let report = Padded::layout_report();
+    println!("{}", report);
+    assert!(report.type_name.ends_with("Padded"));
+    let names = report
+        .fields
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+    // The source name is reported, not the renamed one
+    assert_eq!(names, ["small", "large", "medium", "assumed"]);
+    for field in report.fields {
+        assert_eq!(
+            Some(field.computed_offset),
+            field.real_offset,
+            "{}",
+            field.name
+        );
+    }
+    assert!(report.is_consistent());
+    assert_eq!(report.mismatches().count(), 0);
+}
+
+#[test]
+fn tuple_report() {
+    let report = Pair::layout_report();
+    println!("{}", report);
+    assert_eq!(report.fields.len(), 2);
+    assert_eq!(report.fields[0].name, "0");
+    assert_eq!(report.fields[1].name, "1");
+    assert_eq!(
+        report.fields[1].real_offset,
+        Some(std::mem::offset_of!(Pair, 1))
+    );
+    assert!(report.is_consistent());
+}
+
+#[test]
+fn display_mismatch() {
+    const FIELDS: &[FieldLayout] = &[
+        FieldLayout {
+            name: "first",
+            computed_offset: 0,
+            real_offset: Some(0),
+        },
+        FieldLayout {
+            name: "second",
+            computed_offset: 4,
+            real_offset: Some(8),
+        },
+        FieldLayout {
+            name: "unknown",
+            computed_offset: 12,
+            real_offset: None,
+        },
+    ];
+    let report = LayoutReport {
+        type_name: "Example",
+        fields: FIELDS,
+    };
+    assert!(!report.is_consistent());
+    assert_eq!(
+        report
+            .mismatches()
+            .map(|field| field.name)
+            .collect::<Vec<_>>(),
+        ["second"]
+    );
+    assert_eq!(
+        report.to_string(),
+        "Layout of Example:
+  field    computed      real
+  first           0         0
+  second          4         8  MISMATCH
+  unknown        12         ?
+"
+    );
+}
+
+assert_fully_const!(Padded, Pair);
@@ -0,0 +1,149 @@
+//! Unions derived with `#[reflect(lazy_fields)]`, which look up their fields through a function
+use std::mem::{align_of, size_of};
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::{TypeId, TypeInfo, UnionFieldDef, UnionFields};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+pub struct Large {
+    first: u64,
+    second: u64,
+    third: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+#[reflect(lazy_fields)]
+#[allow(dead_code)]
+union ScriptValue {
+    m00: u8,
+    m01: u16,
+    m02: u32,
+    m03: u64,
+    m04: i8,
+    m05: i16,
+    m06: i32,
+    m07: i64,
+    m08: f32,
+    m09: f64,
+    m10: bool,
+    m11: *mut u8,
+    m12: u8,
+    m13: u16,
+    m14: u32,
+    m15: u64,
+    m16: i8,
+    m17: i16,
+    m18: i32,
+    m19: i64,
+    m20: f32,
+    m21: f64,
+    m22: bool,
+    m23: *mut u8,
+    m24: u8,
+    m25: u16,
+    m26: u32,
+    m27: u64,
+    m28: i8,
+    m29: i16,
+    m30: i32,
+    m31: i64,
+    m32: f32,
+    m33: f64,
+    m34: bool,
+    m35: *mut u8,
+    m36: u8,
+    m37: u16,
+    m38: u32,
+    m39: u64,
+    m40: i8,
+    m41: i16,
+    m42: i32,
+    m43: i64,
+    m44: f32,
+    m45: f64,
+    m46: bool,
+    m47: *mut u8,
+    m48: u8,
+    #[reflect(rename = "last")]
+    m49: Large,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+#[allow(dead_code)]
+union Eager {
+    small: u8,
+    large: Large,
+}
+
+fn union_def(info: &TypeInfo) -> &'static static_reflect::types::UntaggedUnionDef {
+    match *info {
+        TypeInfo::UntaggedUnion(def) => def,
+        _ => panic!("Expected a union: {}", info),
+    }
+}
+
+#[test]
+fn lazy_fields() {
+    let def = union_def(&ScriptValue::TYPE_INFO);
+    assert_eq!(def.name, "ScriptValue");
+    assert_eq!(def.size, size_of::<ScriptValue>());
+    assert_eq!(def.alignment, align_of::<ScriptValue>());
+    assert!(matches!(def.fields, UnionFields::Lazy { count: 50, .. }));
+    assert_eq!(def.fields.len(), 50);
+    assert_eq!(def.fields.as_slice(), None);
+    assert_eq!(
+        *def.fields.get(3).unwrap(),
+        UnionFieldDef {
+            name: "m03",
+            value_type: TypeId::<u64>::get().erase(),
+            index: 3,
+        }
+    );
+    assert_eq!(
+        def.fields.get(11).unwrap().value_type,
+        TypeId::<*mut u8>::get().erase()
+    );
+    assert_eq!(def.fields.get(50), None);
+    // Renamed fields are found by their new name
+    let last = def.field_def("last").unwrap();
+    assert_eq!(last.index, 49);
+    assert_eq!(*last.value_type.type_ref(), Large::TYPE_INFO);
+    assert_eq!(def.field_def("m49"), None);
+    assert_eq!(def.field_def("m20").unwrap().index, 20);
+    let names = def
+        .fields
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), 50);
+    assert_eq!(names[..3], ["m00", "m01", "m02"]);
+    assert!(def
+        .fields
+        .iter()
+        .enumerate()
+        .all(|(index, field)| field.index == index));
+    // Fields are compared by value, not by the address of the function
+    assert_eq!(ScriptValue::TYPE_INFO, ScriptValue::TYPE_INFO);
+    assert_eq!(
+        ScriptValue::TYPE_INFO.key(),
+        TypeInfo::key(&ScriptValue::TYPE_INFO)
+    );
+}
+
+#[test]
+fn eager_fields() {
+    let def = union_def(&Eager::TYPE_INFO);
+    assert_eq!(def.fields.len(), 2);
+    let fields = def.fields.as_slice().unwrap();
+    assert_eq!(fields[1].name, "large");
+    assert_eq!(def.fields.get(1), Some(&fields[1]));
+    assert_eq!(def.field_def("small").unwrap().index, 0);
+    assert_eq!(def.fields.iter().count(), 2);
+    assert_eq!(def.size, size_of::<Large>());
+}
+
+assert_fully_const!(Large, Eager);
@@ -0,0 +1,81 @@
+use std::marker::PhantomData;
+
+use static_reflect::macros::{assert_fully_const, field_offset, generate_layout_tests};
+use static_reflect::types::{TypeId, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct View<'a> {
+    ptr: *const u8,
+    len: usize,
+    _marker: PhantomData<&'a u8>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct Pair<'a, 'b: 'a> {
+    first: View<'a>,
+    flag: bool,
+    second: View<'b>,
+    callback: *const for<'c> extern "C" fn(&'c u8),
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub union Either<'a> {
+    view: std::mem::ManuallyDrop<View<'a>>,
+    raw: *const u8,
+}
+
+generate_layout_tests!(
+    View { ptr, len, _marker },
+    Pair {
+        first,
+        flag,
+        second,
+        callback
+    },
+);
+
+#[test]
+fn view() {
+    assert_eq!(View::NAMED_FIELD_INFO.len.offset, field_offset!(View, len));
+    assert_eq!(View::NAMED_FIELD_INFO._marker.offset, 16);
+    assert_eq!(
+        View::NAMED_FIELD_INFO._marker.value_type,
+        TypeId::<PhantomData<&'static u8>>::get()
+    );
+    assert_eq!(View::TYPE_INFO.size(), std::mem::size_of::<View>());
+}
+
+#[test]
+fn pair() {
+    assert_eq!(Pair::NAMED_FIELD_INFO.flag.offset, 16);
+    assert_eq!(Pair::NAMED_FIELD_INFO.second.offset, 24);
+    assert_eq!(Pair::NAMED_FIELD_INFO.callback.offset, 40);
+    assert_eq!(Pair::TYPE_INFO.size(), 48);
+}
+
+fn type_info_of<'a>(_view: &View<'a>) -> &'static TypeInfo {
+    &<View<'a> as StaticReflect>::TYPE_INFO
+}
+
+#[test]
+fn independent_of_lifetime() {
+    let byte = 7u8;
+    let local = View {
+        ptr: &byte,
+        len: 1,
+        _marker: PhantomData,
+    };
+    assert_eq!(type_info_of(&local), &View::<'static>::TYPE_INFO);
+    assert_eq!(
+        TypeId::<View<'static>>::get().erase(),
+        TypeId::<View<'_>>::get().erase()
+    );
+    assert!(matches!(Either::TYPE_INFO, TypeInfo::UntaggedUnion(_)));
+    assert_eq!(Either::TYPE_INFO.size(), 16);
+}
+
+assert_fully_const!(View<'static>, Pair<'static, 'static>, Either<'static>);
@@ -0,0 +1,213 @@
+//! Verifying a set of declarations against a (fake) loaded library
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use static_reflect::funcs::link::{LinkFailure, LinkedFunction, SymbolConflict, UnresolvedSymbol};
+use static_reflect::funcs::{link_check, FunctionDeclaration, FunctionLocation};
+use static_reflect::reflect_func;
+
+#[reflect_func(library = "libplugin.so")]
+extern "C" {
+    #[allow(dead_code)]
+    fn plugin_init(flags: u32) -> i32;
+    #[allow(dead_code)]
+    #[link_name = "plugin_shutdown_v2"]
+    fn plugin_shutdown();
+    #[allow(dead_code)]
+    fn plugin_missing(value: f64) -> f64;
+    #[allow(dead_code)]
+    #[reflect_func(weak)]
+    fn plugin_optional();
+}
+
+/// Another declaration of `plugin_init`, with a different signature
+mod conflicting {
+    use static_reflect::reflect_func;
+
+    #[reflect_func]
+    extern "C" {
+        #[allow(dead_code, clashing_extern_declarations)]
+        pub fn plugin_init() -> i32;
+    }
+}
+
+#[reflect_func(absolute)]
+extern "C" fn local_callback(value: u32) -> u32 {
+    value + 1
+}
+
+const UNLOCATED: FunctionDeclaration = FunctionDeclaration {
+    location: None,
+    ..(_FUNC_plugin_init.into_erased())
+};
+
+/// A resolver backed by a map, recording the symbols it was asked for
+struct FakeLibrary {
+    symbols: HashMap<&'static str, *const ()>,
+    requested: RefCell<Vec<String>>,
+}
+impl FakeLibrary {
+    fn new() -> Self {
+        FakeLibrary {
+            symbols: HashMap::from([
+                ("plugin_init", 0x1000 as *const ()),
+                ("plugin_shutdown", 0x2000 as *const ()),
+                ("plugin_shutdown_v2", 0x3000 as *const ()),
+            ]),
+            requested: RefCell::new(Vec::new()),
+        }
+    }
+    fn resolve(&self, symbol: &str) -> Option<*const ()> {
+        self.requested.borrow_mut().push(symbol.to_string());
+        self.symbols.get(symbol).copied()
+    }
+}
+
+#[test]
+fn resolved_symbols() {
+    let library = FakeLibrary::new();
+    let report = link_check(
+        &[_FUNC_plugin_init.erase(), _FUNC_plugin_shutdown.erase()],
+        |symbol| library.resolve(symbol),
+    );
+    assert!(report.is_ok());
+    assert_eq!(
+        report.resolved,
+        [
+            LinkedFunction {
+                declaration: _FUNC_plugin_init.erase(),
+                symbol: Some("plugin_init"),
+                address: 0x1000 as *const (),
+            },
+            // The link name takes precedence over the name of the function
+            LinkedFunction {
+                declaration: _FUNC_plugin_shutdown.erase(),
+                symbol: Some("plugin_shutdown_v2"),
+                address: 0x3000 as *const (),
+            },
+        ]
+    );
+    assert_eq!(
+        *library.requested.borrow(),
+        ["plugin_init", "plugin_shutdown_v2"]
+    );
+    let addresses = report.apply().unwrap();
+    assert_eq!(addresses.len(), 2);
+    assert_eq!(
+        addresses[_FUNC_plugin_shutdown.erase()],
+        0x3000 as *const ()
+    );
+}
+
+#[test]
+fn reports_every_failure() {
+    let library = FakeLibrary::new();
+    let report = link_check(
+        &[
+            _FUNC_plugin_missing.erase(),
+            _FUNC_plugin_init.erase(),
+            &UNLOCATED,
+            conflicting::_FUNC_plugin_init.erase(),
+            _FUNC_plugin_optional.erase(),
+        ],
+        |symbol| library.resolve(symbol),
+    );
+    assert!(!report.is_ok());
+    assert_eq!(report.resolved.len(), 1);
+    assert_eq!(
+        report.missing,
+        [
+            UnresolvedSymbol {
+                declaration: _FUNC_plugin_missing.erase(),
+                symbol: "plugin_missing",
+                weak: false,
+            },
+            UnresolvedSymbol {
+                declaration: _FUNC_plugin_optional.erase(),
+                symbol: "plugin_optional",
+                weak: true,
+            },
+        ]
+    );
+    assert_eq!(report.unlocated, [&UNLOCATED]);
+    let conflict = SymbolConflict {
+        symbol: "plugin_init",
+        first: _FUNC_plugin_init.erase(),
+        second: conflicting::_FUNC_plugin_init.erase(),
+    };
+    assert_eq!(report.conflicts, [conflict]);
+    // The weak function isn't a failure
+    assert_eq!(
+        report.failures(),
+        [
+            LinkFailure::Missing(report.missing[0]),
+            LinkFailure::Unlocated(&UNLOCATED),
+            LinkFailure::Conflict(conflict),
+        ]
+    );
+    let error = report.apply().unwrap_err();
+    assert_eq!(error.failures, report.failures());
+    let message = error.to_string();
+    assert!(message.starts_with("Failed to link 3 function(s):\n"));
+    assert!(message.contains("Missing symbol plugin_missing (expected in libplugin.so)"));
+    assert!(message.contains("Function plugin_init doesn't have a known location"));
+    assert!(message.contains("Conflicting declarations of plugin_init"));
+    // The conflicting symbol wasn't looked up again
+    assert_eq!(
+        *library.requested.borrow(),
+        ["plugin_missing", "plugin_init", "plugin_optional"]
+    );
+}
+
+#[test]
+fn weak_functions_may_be_missing() {
+    let library = FakeLibrary::new();
+    let report = link_check(
+        &[_FUNC_plugin_init.erase(), _FUNC_plugin_optional.erase()],
+        |symbol| library.resolve(symbol),
+    );
+    assert!(report.is_ok());
+    let addresses = report.apply().unwrap();
+    assert!(!addresses.contains_key(_FUNC_plugin_optional.erase()));
+}
+
+#[test]
+fn duplicates() {
+    let library = FakeLibrary::new();
+    // Another declaration of the same symbol, with the same signature
+    const RENAMED: FunctionDeclaration = FunctionDeclaration {
+        name: "renamed_init",
+        location: Some(FunctionLocation::DynamicallyLinked {
+            link_name: Some("plugin_init"),
+            library: None,
+            weak: false,
+        }),
+        ..(_FUNC_plugin_init.into_erased())
+    };
+    let report = link_check(
+        &[
+            _FUNC_plugin_init.erase(),
+            _FUNC_plugin_init.erase(),
+            &RENAMED,
+        ],
+        |symbol| library.resolve(symbol),
+    );
+    assert!(report.is_ok());
+    // Exact duplicates are ignored, while compatible declarations share the address
+    assert_eq!(report.resolved.len(), 2);
+    assert_eq!(report.resolved[1].declaration, &RENAMED);
+    assert_eq!(report.resolved[1].address, 0x1000 as *const ());
+    assert_eq!(*library.requested.borrow(), ["plugin_init"]);
+}
+
+#[test]
+fn absolute_addresses() {
+    let report = link_check(&[_FUNC_local_callback.erase()], |symbol| {
+        panic!("Unexpected lookup of {}", symbol)
+    });
+    assert_eq!(report.resolved[0].symbol, None);
+    assert_eq!(
+        report.resolved[0].address,
+        local_callback as extern "C" fn(u32) -> u32 as *const ()
+    );
+}
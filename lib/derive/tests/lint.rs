@@ -0,0 +1,90 @@
+//! The layout analysis behind `#[reflect(lint)]`
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::lint::{
+    could_reorder_fields, has_excessive_padding, has_wide_discriminant, minimal_discriminant_size,
+    padding_bytes, reordered_size,
+};
+use static_reflect::types::{IntSize, TypeInfo};
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct BadlyOrdered {
+    first: bool,
+    big: u64,
+    last: bool,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Packed {
+    big: u64,
+    small: u32,
+    tiny: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct OpaqueTail {
+    len: u8,
+    #[reflect(opaque_array)]
+    data: [u64; 4],
+}
+
+#[derive(StaticReflect)]
+#[repr(u64)]
+#[allow(dead_code)]
+enum Wide {
+    First,
+    Second = 300,
+}
+
+#[derive(StaticReflect)]
+#[repr(i16)]
+#[allow(dead_code)]
+enum Signed {
+    Negative = -129,
+    Positive = 1,
+}
+
+fn enum_def(info: &TypeInfo) -> &'static static_reflect::types::CStyleEnumDef {
+    match *info {
+        TypeInfo::CStyleEnum(def) => def,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn padding() {
+    assert_eq!(padding_bytes(&BadlyOrdered::TYPE_INFO), Some(14));
+    assert_eq!(reordered_size(&BadlyOrdered::TYPE_INFO), Some(16));
+    assert!(could_reorder_fields(&BadlyOrdered::TYPE_INFO));
+    assert!(has_excessive_padding(&BadlyOrdered::TYPE_INFO, 8));
+    assert_eq!(padding_bytes(&Packed::TYPE_INFO), Some(2));
+    assert!(!could_reorder_fields(&Packed::TYPE_INFO));
+    assert!(!has_excessive_padding(&Packed::TYPE_INFO, 2));
+}
+
+#[test]
+fn unknown_sizes() {
+    assert_eq!(padding_bytes(&OpaqueTail::TYPE_INFO), None);
+    assert_eq!(reordered_size(&OpaqueTail::TYPE_INFO), None);
+    assert_eq!(padding_bytes(&u32::TYPE_INFO), None);
+    assert!(!has_excessive_padding(&OpaqueTail::TYPE_INFO, 0));
+}
+
+#[test]
+fn discriminants() {
+    assert_eq!(
+        minimal_discriminant_size(enum_def(&Wide::TYPE_INFO)),
+        IntSize::Short
+    );
+    assert!(has_wide_discriminant(&Wide::TYPE_INFO));
+    assert_eq!(
+        minimal_discriminant_size(enum_def(&Signed::TYPE_INFO)),
+        IntSize::Short
+    );
+    assert!(!has_wide_discriminant(&Signed::TYPE_INFO));
+}
+
+assert_fully_const!(BadlyOrdered, Packed, OpaqueTail, Wide, Signed);
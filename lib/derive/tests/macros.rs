@@ -0,0 +1,117 @@
+// Import memoffset's macros alongside ours, to check nothing is ambiguous
+#[macro_use]
+extern crate memoffset;
+
+use std::alloc::Layout;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::macros::*;
+use static_reflect::types::ops::{self, UnsupportedType};
+use static_reflect::types::TypeInfo;
+use static_reflect::StaticReflect;
+
+#[repr(C)]
+struct Nested {
+    value: u32,
+}
+
+#[repr(C)]
+struct Example {
+    first: u8,
+    second: u32,
+    nested: Nested,
+}
+
+struct OpaqueNew;
+define_extern_type!(OpaqueNew);
+
+struct OpaqueRoot;
+static_reflect::sr_define_extern_type!(OpaqueRoot);
+
+struct OpaqueOld;
+#[allow(deprecated)]
+mod old_extern {
+    static_reflect::define_extern_type!(super::OpaqueOld => OpaqueOld);
+}
+
+#[test]
+fn namespaced_names() {
+    assert_eq!(field_offset!(Example, second as u32), 4);
+    assert_eq!(
+        static_reflect::macros::field_offset!(Example, nested.value),
+        8
+    );
+    assert_eq!(static_reflect::sr_field_offset!(Example, first as u8), 0);
+    assert_eq!(OpaqueNew::TYPE_INFO, TypeInfo::Extern { name: "OpaqueNew" });
+    assert_eq!(
+        OpaqueRoot::TYPE_INFO,
+        TypeInfo::Extern { name: "OpaqueRoot" }
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_names() {
+    assert_eq!(static_reflect::field_offset!(Example, second as u32), 4);
+    assert_eq!(static_reflect::field_offset!(Example, nested.value), 8);
+    assert_eq!(OpaqueOld::TYPE_INFO, TypeInfo::Extern { name: "OpaqueOld" });
+}
+
+#[test]
+fn alongside_memoffset() {
+    assert_eq!(field_offset!(Example, second), offset_of!(Example, second));
+    assert_eq!(
+        field_offset!(Example, nested.value),
+        offset_of!(Example, nested) + offset_of!(Nested, value)
+    );
+}
+
+#[derive(StaticReflect)]
+#[repr(C, align(64))]
+struct CacheLine {
+    counter: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Large {
+    first: u64,
+    second: u64,
+    third: u32,
+}
+
+const SLOT: Layout = max_layout!(u8, CacheLine, Large, f32);
+
+#[test]
+fn max_layouts() {
+    assert_eq!(SLOT.size(), 64);
+    assert_eq!(SLOT.align(), 64);
+    assert_eq!(
+        max_layout!(u16, Large),
+        Layout::from_size_align(24, 8).unwrap()
+    );
+    assert_eq!(
+        TypeInfo::max(&u8::TYPE_INFO, &Large::TYPE_INFO),
+        max_layout!(u8, Large)
+    );
+    let infos = [
+        &u8::TYPE_INFO,
+        &CacheLine::TYPE_INFO,
+        &Large::TYPE_INFO,
+        &f32::TYPE_INFO,
+    ];
+    assert_eq!(ops::max_layout(&infos), Ok(SLOT));
+    assert_eq!(ops::max_layout(&[]), Ok(Layout::new::<()>()));
+}
+
+#[test]
+fn max_layout_opaque() {
+    assert_eq!(
+        ops::max_layout(&[&u64::TYPE_INFO, &OpaqueNew::TYPE_INFO]),
+        Err(UnsupportedType {
+            type_name: "extern OpaqueNew".into()
+        })
+    );
+}
+
+assert_fully_const!(CacheLine, Large);
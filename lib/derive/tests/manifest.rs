@@ -0,0 +1,92 @@
+//! The opt-in manifest of reflected types, written by the derive
+//!
+//! Like the snapshot tests, the internals are compiled directly into this test.
+use std::path::Path;
+
+use syn::DeriveInput;
+
+#[allow(dead_code)]
+#[path = "../src/internals/mod.rs"]
+mod internals;
+
+use internals::manifest::{MANIFEST_ENV, MANIFEST_VERSION};
+
+/// Read all the records written for the crate, sorted by file name
+fn read_records(dir: &Path) -> Vec<String> {
+    let mut entries = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(name.ends_with(".json"), "Unexpected file {}", name);
+            std::fs::read_to_string(path).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn emit_records() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("manifest");
+    let _ = std::fs::remove_dir_all(&dir);
+    let packet: DeriveInput = syn::parse_quote! {
+        #[repr(C)]
+        #[reflect(crate = "static_reflect", pod)]
+        pub struct Packet {
+            pub tag: u8,
+            #[reflect(rename = "len", endian = "big")]
+            pub length: u32,
+        }
+    };
+    let color: DeriveInput = syn::parse_quote! {
+        #[repr(u8)]
+        #[reflect(variant_names)]
+        pub enum Color {
+            Red,
+            #[reflect(rename = "verde")]
+            Green,
+        }
+    };
+    // Disabled by default
+    internals::fields::derive_static_reflect(&packet).unwrap();
+    assert!(!dir.exists());
+    std::env::set_var(MANIFEST_ENV, &dir);
+    std::env::set_var("CARGO_CRATE_NAME", "fixtures");
+    internals::fields::derive_static_reflect(&packet).unwrap();
+    internals::fields::derive_static_reflect(&color).unwrap();
+    // Deriving again replaces the existing record
+    internals::fields::derive_static_reflect(&color).unwrap();
+    std::env::remove_var(MANIFEST_ENV);
+    let records = read_records(&dir.join("fixtures"));
+    assert_eq!(records.len(), 2, "{:#?}", records);
+    let file = proc_macro2::Span::call_site().file();
+    assert_eq!(
+        records[0],
+        format!(
+            concat!(
+                "{{\"version\": {}, \"crate\": \"fixtures\", \"file\": \"{}\", ",
+                "\"type\": \"Color\", \"kind\": \"enum\", \"options\": [\"variant_names\"], ",
+                "\"variants\": [{{\"name\": \"Red\", \"options\": []}}, ",
+                "{{\"name\": \"Green\", \"options\": [\"rename = \\\"verde\\\"\"]}}]}}\n"
+            ),
+            MANIFEST_VERSION, file
+        )
+    );
+    assert_eq!(
+        records[1],
+        format!(
+            concat!(
+                "{{\"version\": {}, \"crate\": \"fixtures\", \"file\": \"{}\", ",
+                "\"type\": \"Packet\", \"kind\": \"struct\", ",
+                "\"options\": [\"crate = \\\"static_reflect\\\"\", \"pod\"], ",
+                "\"fields\": [{{\"name\": \"tag\", \"options\": []}}, ",
+                "{{\"name\": \"length\", \"options\": ",
+                "[\"rename = \\\"len\\\"\", \"endian = \\\"big\\\"\"]}}]}}\n"
+            ),
+            MANIFEST_VERSION, file
+        )
+    );
+}
@@ -0,0 +1,202 @@
+use std::ffi::c_void;
+
+use static_reflect::builtins::{AsmOption, AsmSlice, AsmStr};
+use static_reflect::funcs::coerce::{CoercionRules, RustType};
+use static_reflect::funcs::marshal::{marshal_args, marshal_args_with, MarshalError, ToFfi};
+use static_reflect::types::TypeInfo;
+use static_reflect::{reflect_func, StaticReflect};
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn repeat_len(count: u32, text: AsmStr) -> u64 {
+    count as u64 * text.len() as u64
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn wide_args(first: u64, second: i64, values: AsmSlice<u16>, extra: AsmOption<AsmStr>) {
+    let _ = (first, second, values, extra);
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn sum_values(values: AsmSlice<u32>, out: *mut u64, scale: *const u32) {
+    let _ = (values, out, scale);
+}
+
+/// Read the argument behind the specified pointer
+unsafe fn read<T: Copy>(ptr: *mut c_void) -> T {
+    *(ptr as *const T)
+}
+
+#[test]
+fn marshal_str() {
+    let owned = String::from("hello");
+    let borrowed: &str = &owned;
+    let args = marshal_args(&_FUNC_repeat_len.signature, &[&3u32, &borrowed]).unwrap();
+    assert_eq!(args.len(), 2);
+    unsafe {
+        let count: u32 = read(args.pointers()[0]);
+        let text: AsmStr = read(args.pointers()[1]);
+        assert_eq!(count, 3);
+        assert_eq!(text.bytes_ptr() as *const u8, owned.as_ptr());
+        assert_eq!(text.len(), 5);
+        assert_eq!(repeat_len(count, text), 15);
+    }
+    // A `String` is passed by reference, just like a `&str`
+    let args = marshal_args(&_FUNC_repeat_len.signature, &[&7u32, &owned]).unwrap();
+    unsafe {
+        let text: AsmStr = read(args.get(1).unwrap());
+        assert_eq!(text.bytes_ptr() as *const u8, owned.as_ptr());
+    }
+    assert!(args.get(2).is_none());
+}
+
+#[test]
+fn widen_integers() {
+    let values = vec![1u16, 2, 3];
+    let args = marshal_args(
+        &_FUNC_wide_args.signature,
+        &[&7u8, &-1i8, &values, &Some("extra")],
+    )
+    .unwrap();
+    unsafe {
+        assert_eq!(read::<u64>(args.pointers()[0]), 7);
+        assert_eq!(read::<i64>(args.pointers()[1]), -1);
+        let slice: AsmSlice<u16> = read(args.pointers()[2]);
+        assert_eq!((slice.ptr as *const u16, slice.len), (values.as_ptr(), 3));
+        let extra = (args.pointers()[3] as *mut AsmOption<AsmStr>).read();
+        assert_eq!(extra.assume_valid().map(|text| text.len()), Some(5));
+    }
+    assert!(!None::<&str>.to_ffi().is_present());
+}
+
+#[test]
+fn mismatched_args() {
+    let signature = &_FUNC_repeat_len.signature;
+    assert_eq!(
+        marshal_args(signature, &[&3u32]).err(),
+        Some(MarshalError::ArgumentCount {
+            expected: 2,
+            actual: 1
+        })
+    );
+    let error = marshal_args(signature, &[&3u32, &4u32]).err().unwrap();
+    assert_eq!(
+        error,
+        MarshalError::ArgumentType {
+            index: 1,
+            expected: &TypeInfo::Str,
+            actual: &u32::TYPE_INFO,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Expected argument 1 to have type str, but got u32"
+    );
+    // Integers are only widened if it's lossless
+    assert!(marshal_args(signature, &[&3i32, &"text"]).is_err());
+    assert!(marshal_args(signature, &[&3u64, &"text"]).is_err());
+    assert!(marshal_args(signature, &[&3.0f32, &"text"]).is_err());
+}
+
+#[test]
+fn strict_matching() {
+    let signature = &_FUNC_repeat_len.signature;
+    assert!(signature.matches(&[RustType::of::<u32>(), RustType::of::<AsmStr>()]));
+    assert!(!signature.matches(&[RustType::of::<u32>(), RustType::of::<&str>()]));
+    assert!(!signature.matches(&[RustType::of::<u32>()]));
+    // Integers are never widened when matching
+    assert!(!signature.matches(&[RustType::of::<u8>(), RustType::of::<AsmStr>()]));
+}
+
+#[test]
+fn coercing_matching() {
+    let rules = CoercionRules::default();
+    let signature = &_FUNC_repeat_len.signature;
+    assert_eq!(RustType::of::<String>(), RustType::Str);
+    for text in [
+        RustType::of::<&str>(),
+        RustType::of::<&String>(),
+        RustType::of::<&mut str>(),
+    ] {
+        assert!(signature.matches_with(&[RustType::of::<u32>(), text], &rules));
+    }
+    assert!(!signature.matches_with(
+        &[RustType::of::<u32>(), RustType::of::<&str>()],
+        &CoercionRules {
+            strings: false,
+            ..rules
+        }
+    ));
+    let signature = &_FUNC_sum_values.signature;
+    assert!(signature.matches_with(
+        &[
+            RustType::of::<&[u32]>(),
+            RustType::of::<Option<&mut u64>>(),
+            RustType::of::<Option<&u32>>(),
+        ],
+        &rules
+    ));
+    // The elements must match exactly
+    assert!(!signature.matches_with(
+        &[
+            RustType::of::<&[u16]>(),
+            RustType::of::<Option<&mut u64>>(),
+            RustType::of::<Option<&u32>>(),
+        ],
+        &rules
+    ));
+    // A mutable pointer needs a mutable reference
+    assert!(!signature.matches_with(
+        &[
+            RustType::of::<&[u32]>(),
+            RustType::of::<Option<&u64>>(),
+            RustType::of::<Option<&u32>>(),
+        ],
+        &rules
+    ));
+    assert!(!signature.matches_with(
+        &[
+            RustType::of::<Vec<u32>>(),
+            RustType::of::<Option<&mut u64>>(),
+            RustType::of::<Option<&u32>>(),
+        ],
+        &CoercionRules::STRICT
+    ));
+}
+
+#[test]
+fn marshal_coerced() {
+    let values = vec![1u32, 2, 3];
+    let mut total = 0u64;
+    let scale = 7u32;
+    let out = Some(&mut total);
+    let signature = &_FUNC_sum_values.signature;
+    // Without the coercion, an `Option` is an `AsmOption`
+    assert_eq!(
+        marshal_args(signature, &[&values, &out, &Some(&scale)]).err(),
+        Some(MarshalError::ArgumentType {
+            index: 1,
+            expected: &signature.argument_types[1],
+            actual: &AsmOption::<u64>::TYPE_INFO,
+        })
+    );
+    let rules = CoercionRules::default();
+    let args = marshal_args_with(signature, &[&values, &out, &None::<&u32>], &rules).unwrap();
+    unsafe {
+        let slice: AsmSlice<u32> = read(args.pointers()[0]);
+        assert_eq!((slice.ptr as *const u32, slice.len), (values.as_ptr(), 3));
+        let out_ptr: *mut u64 = read(args.pointers()[1]);
+        assert_eq!(out_ptr as *const u64, *out.as_ref().unwrap() as *const u64);
+        let scale_ptr: *const u32 = read(args.pointers()[2]);
+        assert!(scale_ptr.is_null());
+    }
+    let scale_ref = Some(&scale);
+    let args =
+        marshal_args_with(signature, &[&values, &None::<&mut u64>, &scale_ref], &rules).unwrap();
+    unsafe {
+        assert!(read::<*mut u64>(args.pointers()[1]).is_null());
+        assert_eq!(read::<*const u32>(args.pointers()[2]), &scale as *const u32);
+    }
+}
@@ -0,0 +1,216 @@
+//! Migrating values between two versions of a structure
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::migrate::{self, FieldAction, IncompatibleReason, MigrateOptions};
+use static_reflect::types::{IntType, StructureDef, TypeInfo};
+use static_reflect::StaticReflect;
+
+mod common;
+
+mod v1 {
+    use static_reflect::StaticReflect;
+
+    #[derive(StaticReflect, Copy, Clone)]
+    #[repr(C)]
+    pub struct Snapshot {
+        pub id: u32,
+        pub delta: i16,
+        pub checksum: u16,
+        pub scale: f32,
+        pub old_name: u8,
+        pub level: i32,
+    }
+}
+
+#[derive(StaticReflect, Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+struct Snapshot {
+    // Moved before `id`
+    scale: f32,
+    // Widened from `i16`
+    delta: i64,
+    id: u32,
+    // Renamed from `old_name`
+    new_name: u8,
+    // Added
+    version: u8,
+    // Narrowed from `i32`
+    level: i16,
+}
+
+assert_fully_const!(v1::Snapshot, Snapshot);
+
+fn structure<T: StaticReflect>() -> &'static StructureDef {
+    match T::TYPE_INFO {
+        TypeInfo::Structure(def) => def,
+        _ => unreachable!(),
+    }
+}
+
+const RENAMES: MigrateOptions = MigrateOptions {
+    renames: &[("old_name", "new_name")],
+};
+
+#[test]
+fn field_actions() {
+    let plan = migrate::plan(
+        structure::<v1::Snapshot>(),
+        structure::<Snapshot>(),
+        RENAMES,
+    );
+    let actions = plan
+        .fields
+        .iter()
+        .map(|field| field.action)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actions,
+        [
+            FieldAction::CopySame {
+                old_offset: 8,
+                new_offset: 0,
+                size: 4,
+            },
+            FieldAction::Widen {
+                old_offset: 4,
+                new_offset: 8,
+                from: IntType::I16,
+                to: IntType::I64,
+            },
+            FieldAction::CopySame {
+                old_offset: 0,
+                new_offset: 16,
+                size: 4,
+            },
+            FieldAction::CopySame {
+                old_offset: 12,
+                new_offset: 20,
+                size: 1,
+            },
+            FieldAction::Default,
+            FieldAction::Incompatible(IncompatibleReason::Lossy {
+                from: IntType::I32,
+                to: IntType::I16,
+            }),
+        ]
+    );
+    assert!(!plan.is_complete());
+    assert_eq!(plan.incompatible().count(), 1);
+    let dropped = plan
+        .dropped
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+    assert_eq!(dropped, [Some("checksum")]);
+    assert_eq!(
+        plan.to_string(),
+        "Snapshot -> Snapshot
+  scale: copy from scale
+  delta: widen from delta (i16 -> i64)
+  id: copy from id
+  new_name: copy from old_name
+  version: default
+  level: incompatible with level (Not every i32 fits in i16)
+  checksum: dropped"
+    );
+    // Without the rename, the field is treated as new
+    let plan = migrate::plan(
+        structure::<v1::Snapshot>(),
+        structure::<Snapshot>(),
+        MigrateOptions::default(),
+    );
+    assert_eq!(plan.fields[3].action, FieldAction::Default);
+    assert_eq!(plan.dropped.len(), 2);
+}
+
+#[test]
+fn apply() {
+    let plan = migrate::plan(
+        structure::<v1::Snapshot>(),
+        structure::<Snapshot>(),
+        RENAMES,
+    );
+    let old = v1::Snapshot {
+        id: 0xDEAD_BEEF,
+        delta: -300,
+        checksum: 0xFFFF,
+        scale: 1.5,
+        old_name: b'n',
+        level: 70_000,
+    };
+    let mut new = Snapshot {
+        scale: 0.0,
+        delta: 0,
+        id: 0,
+        new_name: 0,
+        version: 2,
+        level: -1,
+    };
+    unsafe {
+        plan.apply(
+            &old as *const v1::Snapshot as *const u8,
+            &mut new as *mut Snapshot as *mut u8,
+        );
+    }
+    assert_eq!(
+        new,
+        Snapshot {
+            scale: 1.5,
+            delta: -300,
+            id: 0xDEAD_BEEF,
+            new_name: b'n',
+            // The new and incompatible fields are untouched
+            version: 2,
+            level: -1,
+        }
+    );
+}
+
+#[test]
+fn widening() {
+    use common::field;
+    use static_reflect::types::FieldDef;
+    const fn single(fields: &'static [FieldDef]) -> StructureDef {
+        StructureDef {
+            is_pod: true,
+            ..common::structure("Single", fields, 8, 8)
+        }
+    }
+    const U8: StructureDef = single(&[field::<u8>("value", 0, 0)]);
+    const I8: StructureDef = single(&[field::<i8>("value", 0, 0)]);
+    const U64: StructureDef = single(&[field::<u64>("value", 0, 0)]);
+    const I64: StructureDef = single(&[field::<i64>("value", 0, 0)]);
+    const F64: StructureDef = single(&[field::<f64>("value", 0, 0)]);
+    let migrate = |old: &StructureDef, new: &StructureDef, value: u64| {
+        let plan = migrate::plan(old, new, MigrateOptions::default());
+        let mut result = 0u64;
+        match plan.fields[0].action {
+            FieldAction::Incompatible(reason) => return Err(reason),
+            _ => unsafe {
+                plan.apply(
+                    &value as *const u64 as *const u8,
+                    &mut result as *mut u64 as *mut u8,
+                )
+            },
+        }
+        Ok(result)
+    };
+    // Unsigned integers are zero-extended, even into signed ones
+    assert_eq!(migrate(&U8, &U64, 0xFF), Ok(0xFF));
+    assert_eq!(migrate(&U8, &I64, 0xFF), Ok(0xFF));
+    // Signed integers are sign-extended
+    assert_eq!(migrate(&I8, &I64, 0xFF), Ok(u64::MAX));
+    assert_eq!(
+        migrate(&I8, &U64, 0xFF),
+        Err(IncompatibleReason::Lossy {
+            from: IntType::I8,
+            to: IntType::U64,
+        })
+    );
+    assert_eq!(
+        migrate(&U64, &F64, 1),
+        Err(IncompatibleReason::Retyped {
+            old: &u64::TYPE_INFO,
+            new: &f64::TYPE_INFO,
+        })
+    );
+}
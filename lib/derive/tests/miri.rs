@@ -0,0 +1,123 @@
+//! Tests exercising raw memory access, intended to be run under miri:
+//! ````sh
+//! cargo +nightly miri test -p static-reflect-derive --test miri
+//! ````
+//! They run (and pass) normally too.
+//!
+//! The `path`, `endian`, `capi`, `pod`, `zero_init`, `copy_plan` and `marshal` tests also access raw memory,
+//! and are checked the same way (under both stacked and tree borrows).
+use static_reflect::macros::{assert_fully_const, field_offset};
+use static_reflect::types::path::FieldPath;
+use static_reflect::types::value::ReflectValue;
+use static_reflect::types::IntType;
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Inner {
+    small: u8,
+    wide: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Pair(u16, Inner);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Mode {
+    Off,
+    On,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Outer {
+    mode: Mode,
+    pair: Pair,
+    after_enum: u32,
+}
+
+#[test]
+fn nested_offsets() {
+    let value = Outer {
+        mode: Mode::On,
+        pair: Pair(
+            3,
+            Inner {
+                small: 7,
+                wide: u64::MAX,
+            },
+        ),
+        after_enum: 42,
+    };
+    let base = &value as *const Outer as *const u8;
+    let wide = field_offset!(Outer, pair.1.wide as u64);
+    assert_eq!(unsafe { (base.add(wide) as *const u64).read() }, u64::MAX);
+    let small = field_offset!(Outer, pair.1.small);
+    assert_eq!(unsafe { base.add(small).read() }, 7);
+    let first = field_offset!(Outer, pair.0 as u16);
+    assert_eq!(unsafe { (base.add(first) as *const u16).read() }, 3);
+    let after_enum = field_offset!(Outer, after_enum as u32);
+    assert_eq!(unsafe { (base.add(after_enum) as *const u32).read() }, 42);
+    let mode = field_offset!(Outer, mode as Mode);
+    assert_eq!(unsafe { (base.add(mode) as *const Mode).read() }, Mode::On);
+}
+
+#[test]
+fn compiled_path_access() {
+    let mut value = Outer {
+        mode: Mode::Off,
+        pair: Pair(1, Inner { small: 2, wide: 3 }),
+        after_enum: 4,
+    };
+    let path = FieldPath::parse("pair.1.wide")
+        .unwrap()
+        .compile(&Outer::TYPE_INFO)
+        .unwrap();
+    let base = &mut value as *mut Outer as *mut u8;
+    unsafe {
+        assert_eq!(
+            path.read(base),
+            ReflectValue::Integer {
+                int_type: IntType::U64,
+                bits: 3
+            }
+        );
+        path.write(
+            base,
+            ReflectValue::Integer {
+                int_type: IntType::U64,
+                bits: 99,
+            },
+        );
+    }
+    assert_eq!(value.pair.1.wide, 99);
+}
+
+#[test]
+fn unaligned_values() {
+    // Deliberately misalign the value by one byte
+    let mut buffer = [0u8; 1 + 8];
+    let ptr = unsafe { buffer.as_mut_ptr().add(1) };
+    for value in [
+        ReflectValue::Integer {
+            int_type: IntType::I64,
+            bits: (-5i64) as u64,
+        },
+        ReflectValue::Integer {
+            int_type: IntType::U16,
+            bits: 0xBEEF,
+        },
+        ReflectValue::F64(2.5),
+        ReflectValue::Bool(true),
+    ] {
+        unsafe {
+            value.write(ptr);
+            assert_eq!(ReflectValue::read(ptr, value.primitive_type()), value);
+        }
+    }
+}
+
+assert_fully_const!(Inner, Pair, Mode, Outer);
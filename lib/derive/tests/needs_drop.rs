@@ -0,0 +1,80 @@
+//! Whether reflected types need to be dropped (`TypeInfo::needs_drop`)
+//!
+//! A plain `String` field is rejected by the derive before this ever matters,
+//! since `String` doesn't implement `StaticReflect` (its layout is unspecified).
+//! Owned data can only sneak in through `assume_repr` or a `Drop` impl on the type itself.
+use std::mem::ManuallyDrop;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::TypeInfo;
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct BorrowedText {
+    text: *mut String,
+    len: usize,
+}
+
+/// Has the same size and alignment as a `String`
+#[derive(StaticReflect)]
+#[repr(C)]
+struct RawString {
+    ptr: *mut u8,
+    capacity: usize,
+    len: usize,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct ManuallyDroppedText {
+    #[reflect(assume_repr = "RawString")]
+    text: ManuallyDrop<String>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Handle {
+    id: u32,
+}
+impl Drop for Handle {
+    fn drop(&mut self) {}
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+union HandleOrId {
+    handle: ManuallyDrop<Handle>,
+    id: u32,
+}
+
+#[test]
+fn pointers_dont_drop() {
+    assert!(!BorrowedText::TYPE_INFO.needs_drop());
+    assert!(!<*mut BorrowedText>::TYPE_INFO.needs_drop());
+    assert!(!u64::TYPE_INFO.needs_drop());
+}
+
+#[test]
+fn manually_drop() {
+    assert!(!ManuallyDroppedText::TYPE_INFO.needs_drop());
+    // Unions never drop their fields
+    assert!(!HandleOrId::TYPE_INFO.needs_drop());
+}
+
+#[test]
+fn drop_impl() {
+    assert!(Handle::TYPE_INFO.needs_drop());
+    match Handle::TYPE_INFO {
+        TypeInfo::Structure(def) => assert!(def.needs_drop),
+        _ => unreachable!(),
+    }
+}
+
+assert_fully_const!(
+    BorrowedText,
+    RawString,
+    ManuallyDroppedText,
+    Handle,
+    HandleOrId,
+);
@@ -0,0 +1,96 @@
+//! Enums using the niche of a non-zero type, like `Option<NonNull<T>>`
+use std::mem::{align_of, size_of};
+use std::num::NonZeroU32;
+use std::ptr::NonNull;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::{NicheDef, PrimitiveType, TypeId, TypeInfo};
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(niche)]
+#[allow(dead_code)]
+enum MaybeHandle {
+    None,
+    Some(NonNull<u8>),
+}
+
+/// The empty variant doesn't have to come first
+#[derive(StaticReflect)]
+#[reflect(niche)]
+#[allow(dead_code)]
+enum Count {
+    Known { value: NonZeroU32 },
+    Unknown,
+}
+
+#[test]
+fn maybe_handle() {
+    assert_eq!(
+        MaybeHandle::TYPE_INFO,
+        TypeInfo::Niche(&NicheDef {
+            name: "MaybeHandle",
+            wrapped: &TypeInfo::NonZero(&TypeInfo::MUT_POINTER),
+            none_value: 0,
+            none_variant: "None",
+            some_variant: "Some",
+        })
+    );
+    assert_eq!(MaybeHandle::TYPE_INFO.size(), size_of::<MaybeHandle>());
+    assert_eq!(
+        MaybeHandle::TYPE_INFO.alignment(),
+        align_of::<MaybeHandle>()
+    );
+    assert_eq!(MaybeHandle::TYPE_INFO.size(), size_of::<*mut u8>());
+    assert!(MaybeHandle::TYPE_INFO.is_zero_initializable());
+    assert_eq!(
+        TypeId::<MaybeHandle>::get().primitive(),
+        Some(PrimitiveType::Pointer)
+    );
+}
+
+#[test]
+fn reordered_variants() {
+    let def = match Count::TYPE_INFO {
+        TypeInfo::Niche(def) => def,
+        other => panic!("Unexpected type: {}", other),
+    };
+    assert_eq!(def.none_variant, "Unknown");
+    assert_eq!(def.some_variant, "Known");
+    assert_eq!(*def.wrapped, NonZeroU32::TYPE_INFO);
+    assert_eq!(Count::TYPE_INFO.size(), size_of::<u32>());
+    assert_eq!(Count::TYPE_INFO.to_string(), "Count");
+}
+
+#[test]
+fn std_option() {
+    let nullable = Option::<NonNull<u8>>::TYPE_INFO;
+    assert_ne!(nullable, NonNull::<u8>::TYPE_INFO);
+    assert_ne!(nullable, <*mut u8>::TYPE_INFO);
+    const EXPECTED: TypeInfo = TypeInfo::Niche(&NicheDef::option(&NonNull::<u8>::TYPE_INFO));
+    assert_eq!(nullable, EXPECTED);
+    let def = match nullable {
+        TypeInfo::Niche(def) => def,
+        other => panic!("Unexpected type: {}", other),
+    };
+    assert!(def.is_option());
+    assert_eq!(nullable.nullable(), <*mut u8>::TYPE_INFO);
+    assert_eq!(nullable.size(), size_of::<Option<NonNull<u8>>>());
+    assert!(nullable.is_zero_initializable());
+    assert_eq!(nullable.to_string(), "Option<NonZero<*mut void>>");
+    // A user-defined niche is never mistaken for a std `Option`
+    match MaybeHandle::TYPE_INFO {
+        TypeInfo::Niche(def) => assert!(!def.is_option()),
+        other => panic!("Unexpected type: {}", other),
+    }
+    assert_ne!(MaybeHandle::TYPE_INFO, nullable);
+
+    let count = Option::<NonZeroU32>::TYPE_INFO;
+    assert_ne!(count, NonZeroU32::TYPE_INFO);
+    assert_ne!(count, u32::TYPE_INFO);
+    assert_eq!(count.nullable(), u32::TYPE_INFO);
+    assert_eq!(count.size(), size_of::<u32>());
+    assert_eq!(count.to_string(), "Option<NonZero<u32>>");
+}
+
+assert_fully_const!(MaybeHandle, Count);
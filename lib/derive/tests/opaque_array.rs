@@ -0,0 +1,140 @@
+//! Arrays of structures with mixed alignment, in every position of a structure
+//!
+//! The offsets after an array must advance by the size of the whole array,
+//! even though only its element type is reflected.
+use std::mem::{offset_of, size_of};
+
+use static_reflect::macros::{assert_fully_const, generate_layout_tests};
+use static_reflect::types::{StructureDef, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Inner {
+    x: u16,
+}
+
+/// Has trailing padding, which is repeated for every element of an array
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Mixed {
+    wide: u32,
+    narrow: u8,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WithArray {
+    a: u8,
+    #[reflect(opaque_array)]
+    items: [Inner; 3],
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WithMixed {
+    a: u8,
+    b: u16,
+    #[reflect(opaque_array)]
+    items: [Mixed; 3],
+}
+
+/// The arrays end up in the middle, nested in other structures
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Outer {
+    a: u8,
+    first: WithArray,
+    b: u8,
+    second: WithMixed,
+    c: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Tuple(u8, #[reflect(opaque_array)] [Mixed; 2]);
+
+/// An array in the middle, reflected as its element type
+#[derive(StaticReflect)]
+#[repr(C)]
+struct AssumedMiddle {
+    a: u8,
+    #[reflect(assume_repr_unchecked = "Inner")]
+    items: [Inner; 3],
+    c: u16,
+    d: u8,
+}
+
+generate_layout_tests!(
+    WithArray { a, items },
+    WithMixed { a, b, items },
+    Outer { a, first, b, second, c },
+    Tuple { 0, 1 },
+    AssumedMiddle { a, items, c, d },
+);
+
+fn structure(info: &TypeInfo) -> &'static StructureDef {
+    match *info {
+        TypeInfo::Structure(def) => def,
+        _ => panic!("Expected a structure: {}", info),
+    }
+}
+
+fn offsets(info: &TypeInfo) -> Vec<usize> {
+    structure(info)
+        .fields
+        .iter()
+        .map(|field| field.offset)
+        .collect()
+}
+
+#[test]
+fn trailing_arrays() {
+    assert_eq!(offsets(&WithArray::TYPE_INFO), [0, 2]);
+    assert_eq!(WithArray::TYPE_INFO.size(), 8);
+    assert_eq!(offsets(&WithMixed::TYPE_INFO), [0, 2, 4]);
+    assert_eq!(WithMixed::TYPE_INFO.size(), 28);
+    assert_eq!(WithMixed::TYPE_INFO.alignment(), 4);
+    let items = WithMixed::NAMED_FIELD_INFO.items;
+    assert!(items.opaque_array);
+    assert_eq!(*items.value_type.type_ref(), Mixed::TYPE_INFO);
+    assert_eq!(offsets(&Tuple::TYPE_INFO), [0, offset_of!(Tuple, 1)]);
+    assert_eq!(Tuple::TYPE_INFO.size(), size_of::<Tuple>());
+}
+
+#[test]
+fn middle_arrays() {
+    assert_eq!(
+        offsets(&Outer::TYPE_INFO),
+        [
+            offset_of!(Outer, a),
+            offset_of!(Outer, first),
+            offset_of!(Outer, b),
+            offset_of!(Outer, second),
+            offset_of!(Outer, c),
+        ]
+    );
+    assert_eq!(offsets(&Outer::TYPE_INFO), [0, 2, 10, 12, 40]);
+    assert_eq!(Outer::TYPE_INFO.size(), 44);
+    assert_eq!(offsets(&AssumedMiddle::TYPE_INFO), [0, 2, 8, 10]);
+    assert_eq!(AssumedMiddle::TYPE_INFO.size(), size_of::<AssumedMiddle>());
+    let items = AssumedMiddle::NAMED_FIELD_INFO.items;
+    assert_eq!((items.assumed, items.opaque_array), (true, false));
+    for report in [
+        Outer::layout_report(),
+        WithMixed::layout_report(),
+        AssumedMiddle::layout_report(),
+    ] {
+        assert!(report.is_consistent(), "{}", report);
+    }
+}
+
+assert_fully_const!(
+    Inner,
+    Mixed,
+    WithArray,
+    WithMixed,
+    Outer,
+    Tuple,
+    AssumedMiddle,
+);
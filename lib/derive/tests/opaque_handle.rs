@@ -0,0 +1,86 @@
+//! Exposing trait objects through opaque handles (`opaque_handle!`)
+use std::ffi::c_void;
+use std::mem::size_of;
+use std::ptr::NonNull;
+
+use static_reflect::macros::{assert_fully_const, opaque_handle};
+use static_reflect::types::{NicheDef, TypeInfo};
+use static_reflect::{reflect_func, FieldReflect, StaticReflect};
+
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+opaque_handle!(
+    /// A `Box<dyn Shape>`
+    pub struct ShapeHandle
+);
+opaque_handle!(
+    struct PrivateHandle;
+);
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Scene {
+    shape: ShapeHandle,
+    parent: Option<ShapeHandle>,
+    count: u32,
+}
+
+assert_fully_const!(ShapeHandle, Option<ShapeHandle>, Scene);
+
+impl ShapeHandle {
+    fn create(shape: Box<dyn Shape>) -> ShapeHandle {
+        ShapeHandle::from_raw(NonNull::from(Box::leak(Box::new(shape))).cast())
+    }
+    unsafe fn destroy(self) {
+        drop(Box::from_raw(self.as_ptr() as *mut Box<dyn Shape>));
+    }
+}
+
+#[reflect_func(absolute)]
+extern "C" fn shape_area(handle: ShapeHandle) -> f64 {
+    let shape = unsafe { &*(handle.as_ptr() as *const Box<dyn Shape>) };
+    shape.area()
+}
+
+#[test]
+fn reflected_as_pointer() {
+    assert_eq!(ShapeHandle::TYPE_INFO, NonNull::<c_void>::TYPE_INFO);
+    assert_eq!(PrivateHandle::TYPE_INFO, ShapeHandle::TYPE_INFO);
+    const NULLABLE: NicheDef = NicheDef::option(&ShapeHandle::TYPE_INFO);
+    assert_eq!(Option::<ShapeHandle>::TYPE_INFO, TypeInfo::Niche(&NULLABLE));
+    assert_eq!(size_of::<Option<ShapeHandle>>(), size_of::<*mut c_void>());
+    assert_eq!(ShapeHandle::new(std::ptr::null_mut()), None);
+}
+
+#[test]
+fn handle_in_struct() {
+    let info = Scene::NAMED_FIELD_INFO;
+    assert_eq!(*info.shape.value_type.type_ref(), ShapeHandle::TYPE_INFO);
+    assert_eq!(info.parent.offset, size_of::<*mut c_void>());
+    assert_eq!(info.count.offset, 2 * size_of::<*mut c_void>());
+    let handle = ShapeHandle::create(Box::new(Square(3.0)));
+    let scene = Scene {
+        shape: handle,
+        parent: None,
+        count: 1,
+    };
+    assert_eq!(shape_area(scene.shape), 9.0);
+    assert_eq!((scene.parent, scene.count), (None, 1));
+    unsafe { handle.destroy() };
+}
+
+#[test]
+fn handle_in_signature() {
+    let signature = &_FUNC_shape_area.signature;
+    assert_eq!(signature.argument_types, [ShapeHandle::TYPE_INFO]);
+    assert_eq!(*signature.return_type, f64::TYPE_INFO);
+}
@@ -0,0 +1,106 @@
+//! Reporting which nested component of a type has an unknown layout
+use std::alloc::Layout;
+
+use static_reflect::macros::{assert_fully_const, define_extern_type};
+use static_reflect::registry::set_extern_layout;
+use static_reflect::types::ops::{CopyPlan, UnsupportedType};
+use static_reflect::types::{OpaqueTypeError, RegionError, TypeInfo, TypedRegion};
+use static_reflect::StaticReflect;
+
+pub struct Socket {
+    _opaque: [u8; 0],
+}
+define_extern_type!(Socket);
+
+/// Only registered by the `registered_layout` test, since the registry is global
+pub struct Timer {
+    _opaque: [u8; 0],
+}
+define_extern_type!(Timer);
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Inner {
+    flag: u8,
+    socket: Socket,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Middle(u16, Inner);
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Outer {
+    id: u32,
+    middle: Middle,
+    pointer: *mut Socket,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Scheduled {
+    id: u32,
+    timer: Timer,
+}
+
+#[test]
+fn nested_path() {
+    let error = Outer::TYPE_INFO.try_size().unwrap_err();
+    assert_eq!(
+        error,
+        OpaqueTypeError {
+            path: vec!["middle".into(), "1".into(), "socket".into()],
+            type_name: "extern Socket".into(),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Type has an unknown layout: extern Socket (at middle.1.socket)"
+    );
+    assert_eq!(Outer::TYPE_INFO.try_alignment(), Err(error));
+    // The const functions still use the size computed by the derive
+    assert_eq!(Outer::TYPE_INFO.size(), std::mem::size_of::<Outer>());
+}
+
+#[test]
+fn runtime_utilities() {
+    let unsupported = UnsupportedType {
+        type_name: "extern Socket".into(),
+    };
+    assert_eq!(
+        CopyPlan::analyze(&Outer::TYPE_INFO),
+        Err(unsupported.clone())
+    );
+    assert_eq!(
+        TypedRegion::of::<Outer>(4).stride(),
+        Err(RegionError::Unsupported(unsupported))
+    );
+}
+
+#[test]
+fn known_layouts() {
+    assert_eq!(u64::TYPE_INFO.try_size(), Ok(8));
+    // Pointers aren't followed
+    assert_eq!(
+        <*mut Socket>::TYPE_INFO.try_layout(),
+        Ok(Layout::new::<*mut Socket>())
+    );
+    assert_eq!(
+        TypeInfo::Extern { name: "Socket" }
+            .try_layout()
+            .unwrap_err()
+            .path,
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn registered_layout() {
+    assert!(Scheduled::TYPE_INFO.try_size().is_err());
+    set_extern_layout("Timer", 0, 1).unwrap();
+    assert_eq!(Scheduled::TYPE_INFO.try_size(), Ok(4));
+    assert_eq!(Scheduled::TYPE_INFO.try_alignment(), Ok(4));
+}
+
+assert_fully_const!(Inner, Middle, Outer, Scheduled);
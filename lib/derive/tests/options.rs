@@ -0,0 +1,222 @@
+//! The same runtime utilities, configured differently through `ReflectOptions`
+use std::alloc::Layout;
+use std::mem::size_of;
+
+use static_reflect::macros::{assert_fully_const, define_extern_type};
+use static_reflect::options::{OpaqueSizePolicy, ReflectOptions, UnionInspection};
+use static_reflect::types::audit::{audit, audit_with, AuditKind};
+use static_reflect::types::ops::{CopyPlan, UnsupportedType};
+use static_reflect::types::StructureDef;
+use static_reflect::verify::{verify_type_info, verify_type_info_with, MismatchKind};
+use static_reflect::{StaticReflect, TypeInfo};
+
+mod common;
+use common::{field, structure};
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Inner {
+    small: u8,
+    // 3 bytes of padding
+    large: u32,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union Payload {
+    int: u32,
+    ptr: *mut u8,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Outer {
+    inner: Inner,
+    payload: Payload,
+    tag: u8,
+    // 7 bytes of trailing padding
+}
+
+/// Only inspects the fields of the root, and ignores opaque types and unions
+const SHALLOW: ReflectOptions = ReflectOptions {
+    max_depth: 1,
+    opaque_size_policy: OpaqueSizePolicy::Sentinel,
+    union_inspection: UnionInspection::Skip,
+};
+
+struct Handle;
+define_extern_type!(Handle);
+
+/// A header embedding an extern value, whose size isn't known
+const WITH_HANDLE: TypeInfo = TypeInfo::Structure(&structure(
+    "WithHandle",
+    &[field::<u32>("id", 0, 0), field::<Handle>("handle", 4, 1)],
+    8,
+    4,
+));
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(dead_code)]
+struct Overlapping {
+    x: u32,
+    y: u32,
+}
+unsafe impl StaticReflect for Overlapping {
+    const TYPE_INFO: TypeInfo = TypeInfo::Structure(&StructureDef {
+        is_pod: true,
+        ..structure(
+            "Overlapping",
+            &[field::<u32>("x", 0, 0), field::<u32>("y", 0, 1)],
+            8,
+            4,
+        )
+    });
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union HidesMistake {
+    broken: Overlapping,
+    raw: u64,
+}
+
+#[test]
+fn defaults() {
+    let options = ReflectOptions::default();
+    assert_eq!(options, ReflectOptions::DEFAULT);
+    assert_eq!(options.max_depth, TypeInfo::MAX_CONST_DEPTH);
+    assert_eq!(options.opaque_size_policy, OpaqueSizePolicy::Error);
+    assert_eq!(options.union_inspection, UnionInspection::Raw);
+    assert_eq!(options.size_of(&Outer::TYPE_INFO), Ok(size_of::<Outer>()));
+}
+
+#[test]
+fn copy_plans() {
+    let plan = CopyPlan::analyze(&Outer::TYPE_INFO).unwrap();
+    assert_eq!(
+        CopyPlan::analyze_with(&Outer::TYPE_INFO, &ReflectOptions::default()).unwrap(),
+        plan
+    );
+    // The padding of `inner` is skipped, and the whole union is copied
+    assert_eq!(plan.runs(), &[0..1, 4..17]);
+    // The fields of `inner` aren't inspected, and the union isn't copied at all
+    let shallow = CopyPlan::analyze_with(&Outer::TYPE_INFO, &SHALLOW).unwrap();
+    assert_eq!(shallow.runs(), &[0..8, 16..17]);
+    assert_eq!(shallow.size(), size_of::<Outer>());
+    // Only the root as a whole
+    let root = ReflectOptions {
+        max_depth: 0,
+        ..ReflectOptions::DEFAULT
+    };
+    let whole = CopyPlan::analyze_with(&Outer::TYPE_INFO, &root).unwrap();
+    assert_eq!(whole.runs().to_vec(), vec![0..size_of::<Outer>()]);
+}
+
+#[test]
+fn opaque_sizes() {
+    assert_eq!(
+        CopyPlan::analyze(&WITH_HANDLE),
+        Err(UnsupportedType {
+            type_name: "extern Handle".into()
+        })
+    );
+    // The opaque field is skipped, without affecting the size of the structure
+    let plan = CopyPlan::analyze_with(&WITH_HANDLE, &SHALLOW).unwrap();
+    assert_eq!(plan.runs().to_vec(), vec![0..4]);
+    assert_eq!(plan.size(), 8);
+    let handle = Handle::TYPE_INFO;
+    assert!(ReflectOptions::DEFAULT.size_of(&handle).is_err());
+    assert_eq!(SHALLOW.size_of(&handle), Ok(TypeInfo::OPAQUE_SIZE));
+    let plan = CopyPlan::analyze_with(&handle, &SHALLOW).unwrap();
+    assert_eq!((plan.runs(), plan.size()), (&[][..], TypeInfo::OPAQUE_SIZE));
+}
+
+#[test]
+#[should_panic(expected = "extern Handle")]
+fn opaque_size_panics() {
+    let options = ReflectOptions {
+        opaque_size_policy: OpaqueSizePolicy::Panic,
+        ..ReflectOptions::DEFAULT
+    };
+    let _ = CopyPlan::analyze_with(&WITH_HANDLE, &options);
+}
+
+#[test]
+fn verification() {
+    let actual = Layout::new::<HidesMistake>();
+    let mismatch = verify_type_info(&HidesMistake::TYPE_INFO, actual).unwrap_err();
+    assert_eq!(mismatch.path, "HidesMistake.broken");
+    assert_eq!(
+        mismatch.kind,
+        MismatchKind::OverlappingFields {
+            first: "x".into(),
+            second: "y".into()
+        }
+    );
+    // Neither option inspects the broken field
+    let skip = ReflectOptions {
+        union_inspection: UnionInspection::Skip,
+        ..ReflectOptions::DEFAULT
+    };
+    assert_eq!(
+        verify_type_info_with(&HidesMistake::TYPE_INFO, actual, &skip),
+        Ok(())
+    );
+    assert_eq!(
+        verify_type_info_with(&HidesMistake::TYPE_INFO, actual, &SHALLOW),
+        Ok(())
+    );
+    // The size of the root is still checked
+    let wrong = Layout::new::<u32>();
+    assert!(matches!(
+        verify_type_info_with(&HidesMistake::TYPE_INFO, wrong, &SHALLOW),
+        Err(mismatch) if matches!(mismatch.kind, MismatchKind::Size { .. })
+    ));
+}
+
+#[test]
+fn audits() {
+    let report = audit(&[&Outer::TYPE_INFO]);
+    assert_eq!(
+        report,
+        audit_with(&[&Outer::TYPE_INFO], &ReflectOptions::DEFAULT)
+    );
+    assert_eq!(report.visited_fields, 7);
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].path, "Outer.payload.ptr");
+    assert_eq!(report.entries[0].kind, AuditKind::UntypedPointer);
+    // Only the fields of `Outer` itself are visited
+    let shallow = audit_with(&[&Outer::TYPE_INFO], &SHALLOW);
+    assert_eq!(shallow.visited_fields, 3);
+    assert!(shallow.entries.is_empty());
+}
+
+#[test]
+fn display() {
+    let info = Outer::TYPE_INFO;
+    assert_eq!(
+        info.display_with(&ReflectOptions::DEFAULT).to_string(),
+        "Outer { inner: Inner { small: u8, large: u32 }, \
+         payload: Payload { int: u32, ptr: *mut void }, tag: u8 }"
+    );
+    assert_eq!(
+        info.display_with(&SHALLOW).to_string(),
+        "Outer { inner: Inner, payload: Payload, tag: u8 }"
+    );
+    let skip = ReflectOptions {
+        union_inspection: UnionInspection::Skip,
+        ..ReflectOptions::DEFAULT
+    };
+    assert_eq!(
+        info.display_with(&skip).to_string(),
+        "Outer { inner: Inner { small: u8, large: u32 }, payload: Payload { .. }, tag: u8 }"
+    );
+    let root = ReflectOptions {
+        max_depth: 0,
+        ..ReflectOptions::DEFAULT
+    };
+    assert_eq!(info.display_with(&root).to_string(), info.to_string());
+}
+
+assert_fully_const!(Inner, Payload, Outer, HidesMistake);
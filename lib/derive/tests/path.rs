@@ -0,0 +1,135 @@
+use static_reflect::macros::{assert_fully_const, field_offset};
+use static_reflect::types::path::{FieldPath, PathError, PathSegment};
+use static_reflect::types::value::ReflectValue;
+use static_reflect::types::{IntType, TypeInfo};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Vec3 {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[derive(Copy, Clone, Debug, StaticReflect)]
+#[repr(C)]
+struct Stats(u8, i16, u64);
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union Raw {
+    signed: i32,
+    unsigned: u32,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+struct Player {
+    alive: bool,
+    position: Vec3,
+    stats: Stats,
+    raw: Raw,
+    target: *mut Player,
+}
+
+fn compile(path: &str) -> Result<(usize, &'static TypeInfo), PathError> {
+    let compiled = FieldPath::parse(path)?.compile(&Player::TYPE_INFO)?;
+    Ok((compiled.total_offset, compiled.leaf_type))
+}
+
+#[test]
+fn flat_offsets() {
+    assert_eq!(
+        compile("position.y"),
+        Ok((
+            field_offset!(Player, position) + field_offset!(Vec3, y),
+            &f32::TYPE_INFO
+        ))
+    );
+    assert_eq!(
+        compile("stats.2"),
+        Ok((
+            field_offset!(Player, stats) + field_offset!(Stats, 2),
+            &u64::TYPE_INFO
+        ))
+    );
+    assert_eq!(
+        compile("raw.unsigned"),
+        Ok((field_offset!(Player, raw), &u32::TYPE_INFO))
+    );
+    assert_eq!(
+        compile("position"),
+        Ok((field_offset!(Player, position), &Vec3::TYPE_INFO))
+    );
+}
+
+#[test]
+fn invalid_paths() {
+    assert_eq!(
+        compile("missing"),
+        Err(PathError::NoSuchField { segment: 0 })
+    );
+    assert_eq!(
+        compile("target.alive"),
+        Err(PathError::Indirection { segment: 1 })
+    );
+    assert_eq!(
+        compile("alive.x"),
+        Err(PathError::NotAggregate { segment: 1 })
+    );
+    assert_eq!(
+        FieldPath::parse("position..x"),
+        Err(PathError::InvalidSyntax { segment: 1 })
+    );
+    assert_eq!(
+        FieldPath::parse("stats.1").unwrap().segments(),
+        &[PathSegment::Name("stats".into()), PathSegment::Index(1)]
+    );
+}
+
+#[test]
+fn read_write() {
+    let mut player = Player {
+        alive: true,
+        position: Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+        stats: Stats(7, -3, 42),
+        raw: Raw { signed: -1 },
+        target: std::ptr::null_mut(),
+    };
+    let base = &mut player as *mut Player as *mut u8;
+    let z = FieldPath::parse("position.z")
+        .unwrap()
+        .compile(&Player::TYPE_INFO)
+        .unwrap();
+    let health = FieldPath::parse("stats.1")
+        .unwrap()
+        .compile(&Player::TYPE_INFO)
+        .unwrap();
+    unsafe {
+        assert_eq!(z.read(base), ReflectValue::F32(3.0));
+        match health.read(base) {
+            ReflectValue::Integer { int_type, bits } => {
+                assert_eq!(int_type, IntType::I16);
+                assert_eq!(int_type.extend_bits(bits), -3);
+            }
+            other => panic!("Unexpected value {}", other),
+        }
+        z.write(base, ReflectValue::F32(9.5));
+        health.write(
+            base,
+            ReflectValue::Integer {
+                int_type: IntType::I16,
+                bits: 12,
+            },
+        );
+    }
+    assert_eq!(player.position.z, 9.5);
+    assert_eq!(player.stats.1, 12);
+}
+
+assert_fully_const!(Vec3, Stats, Raw, Player);
@@ -0,0 +1,47 @@
+use bytemuck::{Pod, Zeroable};
+use static_reflect::macros::assert_fully_const;
+use static_reflect::pod::{cast_slice_reflect, CastError};
+use static_reflect::types::TypeInfo;
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable, StaticReflect)]
+#[reflect(pod)]
+#[repr(C)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct NotPod {
+    ptr: *mut u8,
+}
+
+#[test]
+fn pod_flag() {
+    match Point::TYPE_INFO {
+        TypeInfo::Structure(def) => assert!(def.is_pod),
+        _ => unreachable!(),
+    }
+    match NotPod::TYPE_INFO {
+        TypeInfo::Structure(def) => assert!(!def.is_pod),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn cast_slice() {
+    let points = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+    let bytes: &[u8] = bytemuck::cast_slice(&points);
+    assert_eq!(cast_slice_reflect::<Point>(bytes), Ok(&points[..]));
+    assert_eq!(
+        cast_slice_reflect::<Point>(&bytes[..12]),
+        Err(CastError::InvalidLength {
+            len: 12,
+            element_size: 8
+        })
+    );
+}
+
+assert_fully_const!(Point, NotPod);
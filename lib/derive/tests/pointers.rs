@@ -0,0 +1,49 @@
+use std::mem::{align_of, size_of};
+use std::ptr::NonNull;
+
+use static_reflect::macros::{assert_fully_const, generate_layout_tests};
+use static_reflect::types::{PrimitiveType, TypeId, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Buffers {
+    input: *const u8,
+    output: *mut u8,
+    scratch: NonNull<u8>,
+}
+
+generate_layout_tests!(Buffers {
+    input,
+    output,
+    scratch
+});
+
+#[test]
+fn mutability() {
+    let fields = Buffers::NAMED_FIELD_INFO;
+    assert_eq!(*fields.input.value_type.type_ref(), TypeInfo::CONST_POINTER);
+    assert_eq!(*fields.output.value_type.type_ref(), TypeInfo::MUT_POINTER);
+    assert_eq!(
+        *fields.scratch.value_type.type_ref(),
+        TypeInfo::NonZero(&TypeInfo::MUT_POINTER)
+    );
+    assert_ne!(TypeInfo::CONST_POINTER, TypeInfo::MUT_POINTER);
+    assert_eq!(TypeInfo::CONST_POINTER.to_string(), "*const void");
+    assert_eq!(TypeInfo::MUT_POINTER.to_string(), "*mut void");
+}
+
+#[test]
+fn same_layout() {
+    for info in [TypeInfo::CONST_POINTER, TypeInfo::MUT_POINTER] {
+        assert_eq!(info.size(), size_of::<*const u8>());
+        assert_eq!(info.alignment(), align_of::<*const u8>());
+    }
+    assert_eq!(
+        TypeId::<*const u8>::get().primitive(),
+        Some(PrimitiveType::Pointer)
+    );
+    assert_eq!(Buffers::TYPE_INFO.size(), 3 * size_of::<*const u8>());
+}
+
+assert_fully_const!(Buffers);
@@ -0,0 +1,89 @@
+#![feature(never_type)]
+use static_reflect::types::{primitives, FloatSize, IntType, PrimitiveType, TypeId, TypeInfo};
+use static_reflect::StaticReflect;
+
+/// The type information of every core type with a primitive representation
+const CORE_TYPES: &[TypeInfo] = &[
+    <()>::TYPE_INFO,
+    <!>::TYPE_INFO,
+    bool::TYPE_INFO,
+    <*mut u8>::TYPE_INFO,
+    <*const u8>::TYPE_INFO,
+    u8::TYPE_INFO,
+    u16::TYPE_INFO,
+    u32::TYPE_INFO,
+    u64::TYPE_INFO,
+    usize::TYPE_INFO,
+    i8::TYPE_INFO,
+    i16::TYPE_INFO,
+    i32::TYPE_INFO,
+    i64::TYPE_INFO,
+    isize::TYPE_INFO,
+    f32::TYPE_INFO,
+    f64::TYPE_INFO,
+];
+
+#[test]
+fn table_covers_core_types() {
+    for info in CORE_TYPES {
+        let primitive = TypeId::<()>::from_static(info).primitive().unwrap();
+        assert!(PrimitiveType::ALL.contains(&primitive), "{}", info);
+    }
+    for primitive in PrimitiveType::ALL {
+        assert!(
+            CORE_TYPES.contains(primitive.type_info()),
+            "{:?}",
+            primitive
+        );
+    }
+    // No duplicates
+    for (index, primitive) in PrimitiveType::ALL.iter().enumerate() {
+        assert!(!PrimitiveType::ALL[index + 1..].contains(primitive));
+    }
+}
+
+#[test]
+fn type_info_round_trip() {
+    for primitive in PrimitiveType::ALL {
+        let info = primitive.type_info();
+        assert_eq!(
+            TypeId::<()>::from_static(info).primitive(),
+            Some(*primitive)
+        );
+    }
+    assert_eq!(
+        PrimitiveType::Integer(IntType::I32).type_info(),
+        &i32::TYPE_INFO
+    );
+}
+
+#[test]
+fn names() {
+    for (info, meta) in primitives() {
+        match *info {
+            // Displayed as `*mut void`
+            TypeInfo::Pointer { .. } => assert_eq!(meta.rust_name, "*mut ()"),
+            _ => assert_eq!(meta.rust_name, info.to_string()),
+        }
+        assert_eq!(meta.size, info.size());
+        assert_eq!(meta.alignment, info.alignment());
+    }
+    assert_eq!(PrimitiveType::Never.c_name(), None);
+    assert_eq!(PrimitiveType::Unit.c_name(), Some("void"));
+    assert_eq!(PrimitiveType::Pointer.c_name(), Some("void*"));
+    assert_eq!(
+        PrimitiveType::Integer(IntType::U16).c_name(),
+        Some("uint16_t")
+    );
+    assert_eq!(
+        PrimitiveType::Integer(IntType::I64).c_name(),
+        Some("int64_t")
+    );
+    assert_eq!(
+        PrimitiveType::Float {
+            size: FloatSize::Single
+        }
+        .c_name(),
+        Some("float")
+    );
+}
@@ -0,0 +1,74 @@
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::TypeInfo;
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Nested {
+    value: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Full {
+    small: u8,
+    nested: Nested,
+    big: u64,
+}
+
+#[derive(StaticReflect)]
+#[reflect(profile = "minimal")]
+#[repr(C)]
+struct Minimal {
+    small: u8,
+    nested: Nested,
+    big: u64,
+}
+
+#[derive(StaticReflect)]
+#[reflect(profile = "minimal")]
+#[repr(C)]
+struct DifferentLayout {
+    small: u8,
+    big: u64,
+    nested: Nested,
+}
+
+fn def(info: &TypeInfo) -> &'static static_reflect::types::StructureDef {
+    match *info {
+        TypeInfo::Structure(def) => def,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn metadata_absent() {
+    let minimal = def(&Minimal::TYPE_INFO);
+    assert!(minimal.fields.iter().all(|field| field.name.is_none()));
+    assert_ne!(minimal.name, "Minimal");
+    assert_eq!(minimal.name.len(), 8);
+    assert!(minimal.name.chars().all(|c| c.is_ascii_hexdigit()));
+    // The name of a full profile type is retained
+    assert_eq!(def(&Full::TYPE_INFO).name, "Full");
+    assert_eq!(def(&Full::TYPE_INFO).fields[0].name, Some("small"));
+    // Static field access is unaffected
+    assert_eq!(Minimal::NAMED_FIELD_INFO.big.name, None);
+    assert_eq!(Minimal::NAMED_FIELD_INFO.big.offset, 8);
+}
+
+#[test]
+fn layout_equality() {
+    assert!(Minimal::TYPE_INFO.layout_eq(&Full::TYPE_INFO));
+    assert!(Full::TYPE_INFO.layout_eq(&Minimal::TYPE_INFO));
+    assert_ne!(Minimal::TYPE_INFO, Full::TYPE_INFO);
+    let (minimal, full) = (def(&Minimal::TYPE_INFO), def(&Full::TYPE_INFO));
+    assert_eq!(minimal.size, full.size);
+    assert_eq!(minimal.alignment, full.alignment);
+    for (a, b) in minimal.fields.iter().zip(full.fields) {
+        assert_eq!(a.offset, b.offset);
+        assert_eq!(a.value_type, b.value_type);
+    }
+    assert!(!DifferentLayout::TYPE_INFO.layout_eq(&Full::TYPE_INFO));
+}
+
+assert_fully_const!(Nested, Full, Minimal, DifferentLayout);
@@ -0,0 +1,98 @@
+//! Re-run the derive's test fixtures with `-Zrandomize-layout`
+//!
+//! Everything the derive accepts is `#[repr(C)]` (or has an explicit integer repr),
+//! so its reflected offsets and sizes must not change when the compiler
+//! shuffles the fields of `#[repr(Rust)]` types. This catches any code
+//! (generated or not) which accidentally depends on an unspecified layout,
+//! along with any assertions that would hide such a dependence.
+//!
+//! Every integration test besides `ui` (and this harness) is rebuilt
+//! once for each seed in [LAYOUT_SEEDS], in a separate directory
+//! under `target/randomize-layout`. This is equivalent to running:
+//! ````sh
+//! RUSTFLAGS="-Zrandomize-layout -Zlayout-seed=1" cargo +nightly test -p static-reflect-derive
+//! ````
+//! The flag is unstable, so the harness is skipped on a stable toolchain
+//! (unless `RUSTC_BOOTSTRAP` is set).
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+/// The seeds to randomize the layouts with
+///
+/// Using more than one seed makes it unlikely that a shuffled layout
+/// happens to match the declaration order everywhere.
+const LAYOUT_SEEDS: &[u64] = &[1, 2, 3];
+
+/// The tests which aren't fixtures
+const EXCLUDED_TESTS: &[&str] = &["ui", "randomize_layout"];
+
+/// If the compiler accepts unstable `-Z` flags
+fn supports_unstable_flags() -> bool {
+    if std::env::var_os("RUSTC_BOOTSTRAP").is_some() {
+        return true;
+    }
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    match Command::new(rustc).arg("--version").output() {
+        Ok(output) => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        }
+        Err(_) => false,
+    }
+}
+
+/// The names of the fixtures in the `tests` directory
+fn fixtures(manifest_dir: &Path) -> Vec<String> {
+    let mut names = std::fs::read_dir(manifest_dir.join("tests"))
+        .expect("Failed to read tests directory")
+        .map(|entry| entry.expect("Failed to read tests directory").path())
+        .filter(|path| path.extension() == Some(OsStr::new("rs")))
+        .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+        .filter(|name| !EXCLUDED_TESTS.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn randomize_layout() {
+    if !supports_unstable_flags() {
+        eprintln!("Skipping: -Zrandomize-layout requires a nightly compiler");
+        return;
+    }
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixtures = fixtures(manifest_dir);
+    assert!(!fixtures.is_empty());
+    let mut failures = Vec::new();
+    for seed in LAYOUT_SEEDS {
+        let mut command = Command::new(env!("CARGO"));
+        command
+            .current_dir(manifest_dir)
+            .env(
+                "CARGO_TARGET_DIR",
+                manifest_dir.join(format!("../../target/randomize-layout/seed-{}", seed)),
+            )
+            .env(
+                "RUSTFLAGS",
+                format!("-Zrandomize-layout -Zlayout-seed={}", seed),
+            )
+            .args([
+                "test",
+                "--quiet",
+                "--no-fail-fast",
+                "-p",
+                "static-reflect-derive",
+            ]);
+        for name in &fixtures {
+            command.args(["--test", name]);
+        }
+        let output = command.output().expect("Failed to run cargo");
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+            eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+            failures.push(*seed);
+        }
+    }
+    assert!(failures.is_empty(), "Failed with seeds: {:?}", failures);
+}
@@ -0,0 +1,166 @@
+//! Contiguous regions of values, described by a `TypedRegion`
+use std::alloc::Layout;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::encode::{encode_region, encode_value, EncodeError};
+use static_reflect::types::ops::{copy_region, ValueOpError};
+use static_reflect::types::{RegionError, TypeInfo, TypedRegion};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Particle {
+    position: u64,
+    charge: u8,
+}
+
+#[test]
+fn arithmetic() {
+    let region = TypedRegion::of::<Particle>(128);
+    assert_eq!(region.stride(), Ok(16));
+    assert_eq!(region.byte_len(), Ok(2048));
+    assert_eq!(region.layout(), Ok(Layout::new::<[Particle; 128]>()));
+    assert_eq!(region.element_offset(0), Some(0));
+    assert_eq!(region.element_offset(3), Some(48));
+    assert_eq!(region.element_offset(127), Some(2032));
+    assert_eq!(region.element_offset(128), None);
+    assert_eq!(region.to_string(), "[Particle; 128]");
+    // Empty regions are still aligned
+    let empty = TypedRegion::of::<Particle>(0);
+    assert_eq!(empty.byte_len(), Ok(0));
+    assert_eq!(empty.layout(), Ok(Layout::new::<[Particle; 0]>()));
+    assert_eq!(empty.element_offset(0), None);
+}
+
+#[test]
+fn overflow() {
+    let region = TypedRegion::of::<Particle>(usize::MAX / 8);
+    let overflow = Err(RegionError::Overflow {
+        type_name: "Particle".into(),
+        count: usize::MAX / 8,
+    });
+    assert_eq!(region.byte_len(), overflow);
+    assert_eq!(region.layout().map(|layout| layout.size()), overflow);
+    // The offsets of the elements before the overflow are still known
+    assert_eq!(region.element_offset(2), Some(32));
+    assert_eq!(region.element_offset(usize::MAX / 8 - 1), None);
+    assert_eq!(
+        region.byte_len().unwrap_err().to_string(),
+        format!(
+            "Region of {} elements of Particle is too large",
+            usize::MAX / 8
+        )
+    );
+    // The size fits in a usize, but exceeds the limit of a Layout
+    let region = TypedRegion::of::<u8>(usize::MAX);
+    assert_eq!(region.byte_len(), Ok(usize::MAX));
+    assert!(matches!(region.layout(), Err(RegionError::Overflow { .. })));
+}
+
+#[test]
+fn unknown_layout() {
+    static OPAQUE: TypeInfo = TypeInfo::Extern {
+        name: "region::Opaque",
+    };
+    let region = TypedRegion::new(&OPAQUE, 4);
+    assert!(matches!(
+        region.byte_len(),
+        Err(RegionError::Unsupported(_))
+    ));
+    assert_eq!(region.element_offset(1), None);
+    assert_eq!(region.contains_offset(0), None);
+}
+
+#[test]
+fn split_at() {
+    let region = TypedRegion::of::<Particle>(10);
+    let (head, tail) = region.split_at(4).unwrap();
+    assert_eq!(head, TypedRegion::of::<Particle>(4));
+    assert_eq!(tail, TypedRegion::of::<Particle>(6));
+    assert_eq!(region.element_offset(4), head.byte_len().ok());
+    let (head, tail) = region.split_at(10).unwrap();
+    assert_eq!((head.count, tail.count), (10, 0));
+    assert_eq!(region.split_at(11), None);
+}
+
+#[test]
+fn fault_offsets() {
+    let region = TypedRegion::of::<Particle>(4);
+    assert_eq!(region.contains_offset(0), Some((0, 0)));
+    assert_eq!(region.contains_offset(8), Some((0, 8)));
+    // Padding belongs to the element before it
+    assert_eq!(region.contains_offset(15), Some((0, 15)));
+    assert_eq!(region.contains_offset(16), Some((1, 0)));
+    assert_eq!(region.contains_offset(57), Some((3, 9)));
+    assert_eq!(region.contains_offset(64), None);
+    assert_eq!(region.contains_offset(usize::MAX), None);
+    // Zero-sized elements don't contain any bytes
+    assert_eq!(TypedRegion::of::<()>(4).contains_offset(0), None);
+}
+
+#[test]
+fn copy() {
+    let src: Vec<Particle> = (0..8)
+        .map(|i| Particle {
+            position: i * 100,
+            charge: i as u8,
+        })
+        .collect();
+    let mut dst = vec![Particle::default(); 8];
+    let region = TypedRegion::of::<Particle>(8);
+    unsafe {
+        copy_region(
+            src.as_ptr() as *const u8,
+            dst.as_mut_ptr() as *mut u8,
+            &region,
+        )
+    }
+    .unwrap();
+    assert_eq!(src, dst);
+    // The regions overlap if they're shifted by an element
+    let mut buffer = vec![Particle::default(); 9];
+    let base = buffer.as_mut_ptr() as *mut u8;
+    assert_eq!(
+        unsafe { copy_region(base, base.add(16), &region) },
+        Err(ValueOpError::Overlapping { size: 128 })
+    );
+}
+
+#[test]
+fn encode() {
+    let values = [1u16, 2, 0x0304];
+    let mut encoded = Vec::new();
+    let region = TypedRegion::of::<u16>(3);
+    unsafe { encode_region(values.as_ptr() as *const u8, &region, &mut encoded) }.unwrap();
+    assert_eq!(encoded, [1, 0, 2, 0, 4, 3]);
+    // The same as encoding each element
+    let particles = [Particle {
+        position: 7,
+        charge: 1,
+    }; 2];
+    let mut expected = Vec::new();
+    for particle in &particles {
+        unsafe {
+            encode_value(
+                particle as *const Particle as *const u8,
+                &Particle::TYPE_INFO,
+                &mut expected,
+            )
+        }
+        .unwrap();
+    }
+    let mut encoded = Vec::new();
+    let region = TypedRegion::of::<Particle>(2);
+    unsafe { encode_region(particles.as_ptr() as *const u8, &region, &mut encoded) }.unwrap();
+    assert_eq!(encoded, expected);
+    let region = TypedRegion::of::<u16>(usize::MAX);
+    assert_eq!(
+        unsafe { encode_region(values.as_ptr() as *const u8, &region, &mut encoded) },
+        Err(EncodeError::Overflow {
+            type_name: "u16".into(),
+            count: usize::MAX,
+        })
+    );
+}
+
+assert_fully_const!(Particle);
@@ -0,0 +1,154 @@
+//! Generic structures, and their symbolic schemas (`#[reflect(schema)]`)
+use std::mem::{align_of, size_of};
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::schema::{SchemaFieldDef, SchemaStructureDef};
+use static_reflect::types::{IntType, TypeId};
+use static_reflect::{FieldReflect, StaticReflect, TypeInfo};
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(C)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(C)]
+struct Tagged<T, U> {
+    tag: u8,
+    next: *mut Tagged<T, U>,
+    value: T,
+    #[reflect(rename = "trailer")]
+    extra: U,
+    count: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(C)]
+struct Plain(u16, u32);
+
+#[test]
+fn generic_type_info() {
+    let def = match Pair::<u32>::TYPE_INFO {
+        TypeInfo::Structure(def) => def,
+        other => panic!("Unexpected type: {}", other),
+    };
+    assert_eq!(def.name, "Pair");
+    assert_eq!(def.size, size_of::<Pair<u32>>());
+    assert_eq!(def.alignment, align_of::<Pair<u32>>());
+    let fields = def
+        .fields
+        .iter()
+        .map(|field| (field.name, field.value_type, field.offset))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        [
+            (Some("first"), TypeId::<u32>::get().erase(), 0),
+            (Some("second"), TypeId::<u32>::get().erase(), 4),
+        ]
+    );
+    assert_eq!(Pair::<u8>::TYPE_INFO.size(), 2);
+    assert_eq!(Pair::<u64>::NAMED_FIELD_INFO.second.offset, 8);
+    let info = Tagged::<u8, f64>::TYPE_INFO;
+    assert_eq!(info.size(), size_of::<Tagged<u8, f64>>());
+    assert_eq!(
+        info.field_def("trailer").unwrap().offset,
+        std::mem::offset_of!(Tagged<u8, f64>, extra)
+    );
+}
+
+#[test]
+fn schema() {
+    const PARAMETER: TypeInfo = TypeInfo::TypeParameter {
+        name: "T",
+        index: 0,
+    };
+    assert_eq!(
+        Pair::<u32>::SCHEMA_INFO,
+        SchemaStructureDef {
+            name: "Pair",
+            type_parameters: &["T"],
+            fields: &[
+                SchemaFieldDef {
+                    name: Some("first"),
+                    value_type: &PARAMETER,
+                    offset: None,
+                    index: 0,
+                },
+                SchemaFieldDef {
+                    name: Some("second"),
+                    value_type: &PARAMETER,
+                    offset: None,
+                    index: 1,
+                },
+            ],
+        }
+    );
+    // The schema doesn't depend on the instantiation
+    assert_eq!(Pair::<u32>::SCHEMA_INFO, Pair::<f64>::SCHEMA_INFO);
+    assert!(Pair::<u32>::SCHEMA_INFO.is_generic());
+    assert_eq!(
+        Pair::<u32>::SCHEMA_INFO.to_string(),
+        "Pair<T> { first: T, second: T }"
+    );
+}
+
+#[test]
+fn partially_known_offsets() {
+    let schema = Tagged::<u8, u16>::SCHEMA_INFO;
+    assert_eq!(
+        schema.to_string(),
+        "Tagged<T, U> { tag: u8, next: *mut void, value: T, trailer: U, count: u32 }"
+    );
+    let offsets = schema
+        .fields
+        .iter()
+        .map(|field| field.offset)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        offsets,
+        [Some(0), Some(size_of::<usize>()), None, None, None]
+    );
+    assert_eq!(
+        schema.fields[3].value_type,
+        &TypeInfo::TypeParameter {
+            name: "U",
+            index: 1
+        }
+    );
+    assert_eq!(
+        Tagged::<u8, u16>::SCHEMA_INFO,
+        Tagged::<u64, ()>::SCHEMA_INFO
+    );
+}
+
+#[test]
+fn non_generic_schema() {
+    let schema = Plain::SCHEMA_INFO;
+    assert!(!schema.is_generic());
+    assert_eq!(schema.type_parameters, &[] as &[&str]);
+    assert_eq!(schema.fields[1].offset, Some(4));
+    assert_eq!(
+        schema.fields[1].value_type,
+        &TypeInfo::Integer(IntType::U32)
+    );
+    assert_eq!(schema.to_string(), "Plain { 0: u16, 1: u32 }");
+}
+
+#[test]
+fn type_parameters_are_opaque() {
+    let param = TypeInfo::TypeParameter {
+        name: "T",
+        index: 0,
+    };
+    assert_eq!(param.to_string(), "T");
+    assert_eq!(param.resolved_layout(), None);
+    assert!(!param.is_zero_initializable());
+}
+
+assert_fully_const!(Pair<u32>, Tagged<u32, u32>, Plain);
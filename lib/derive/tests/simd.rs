@@ -0,0 +1,121 @@
+//! SIMD vectors as fields, which are more aligned than their lanes
+#![feature(portable_simd)]
+use std::mem::{align_of, offset_of, size_of};
+use std::simd::Simd;
+
+use static_reflect::macros::{assert_fully_const, generate_layout_tests};
+use static_reflect::types::{FloatSize, IntType, PrimitiveType, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Particle {
+    id: u32,
+    position: Simd<f32, 4>,
+    flags: Simd<u8, 16>,
+}
+
+generate_layout_tests!(Particle {
+    id,
+    position,
+    flags
+});
+
+#[test]
+fn portable() {
+    let position = Particle::NAMED_FIELD_INFO.position;
+    assert_eq!(
+        *position.value_type.type_ref(),
+        TypeInfo::Vector {
+            element: PrimitiveType::Float {
+                size: FloatSize::Single
+            },
+            lanes: 4,
+            alignment: align_of::<Simd<f32, 4>>(),
+        }
+    );
+    assert_eq!(position.offset, offset_of!(Particle, position));
+    assert_eq!(
+        <Simd<u8, 16>>::TYPE_INFO,
+        TypeInfo::Vector {
+            element: PrimitiveType::Integer(IntType::U8),
+            lanes: 16,
+            alignment: 16,
+        }
+    );
+    assert_eq!(Particle::TYPE_INFO.size(), size_of::<Particle>());
+    assert!(Particle::TYPE_INFO.is_zero_initializable());
+    assert_eq!(
+        <Simd<u32, 4>>::TYPE_INFO.can_transmute_to(&<Simd<f32, 4>>::TYPE_INFO),
+        Ok(())
+    );
+    assert_ne!(<Simd<u32, 4>>::TYPE_INFO, <Simd<i32, 4>>::TYPE_INFO);
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use std::arch::x86_64::{__m128, __m128i, __m256d};
+    use std::mem::{align_of, offset_of, size_of};
+
+    use static_reflect::macros::generate_layout_tests;
+    use static_reflect::types::{FloatSize, PrimitiveType, TypeInfo};
+    use static_reflect::{FieldReflect, StaticReflect};
+
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    pub struct Kernel {
+        pub scale: f32,
+        pub weights: __m128,
+        pub mask: __m128i,
+        pub tail: u8,
+    }
+
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    pub struct Wide {
+        pub count: u16,
+        pub values: __m256d,
+    }
+
+    generate_layout_tests!(
+        Kernel {
+            scale,
+            weights,
+            mask,
+            tail
+        },
+        Wide { count, values }
+    );
+
+    #[test]
+    fn m128() {
+        let weights = Kernel::NAMED_FIELD_INFO.weights;
+        assert_eq!(weights.offset, 16);
+        assert_eq!(weights.offset, offset_of!(Kernel, weights));
+        assert_eq!(
+            *weights.value_type.type_ref(),
+            TypeInfo::Vector {
+                element: PrimitiveType::Float {
+                    size: FloatSize::Single
+                },
+                lanes: 4,
+                alignment: 16,
+            }
+        );
+        assert_eq!(__m128::TYPE_INFO.alignment(), align_of::<__m128>());
+        assert_eq!(__m128::TYPE_INFO.size(), size_of::<__m128>());
+        assert_eq!(__m128i::TYPE_INFO.to_string(), "i64x2");
+        assert_eq!(Kernel::TYPE_INFO.alignment(), 16);
+        assert_eq!(Kernel::TYPE_INFO.size(), size_of::<Kernel>());
+    }
+
+    #[test]
+    fn m256() {
+        assert_eq!(Wide::NAMED_FIELD_INFO.values.offset, 32);
+        assert_eq!(Wide::TYPE_INFO.alignment(), 32);
+        assert_eq!(Wide::TYPE_INFO.size(), 64);
+        assert_eq!(__m256d::TYPE_INFO.to_string(), "f64x4");
+    }
+}
+
+assert_fully_const!(Particle);
@@ -3,11 +3,12 @@ use std::mem::{align_of, size_of};
 
 use pretty_assertions::assert_eq;
 
+use static_reflect::macros::{assert_fully_const, field_offset, generate_layout_tests};
 use static_reflect::types::{
-    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, FieldDef, IntType, StructureDef, TypeId,
-    TypeInfo,
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, FieldDef, FieldMeta, IntSize, IntType,
+    StructureDef, TypeId, TypeInfo,
 };
-use static_reflect::{field_offset, FieldReflect, StaticReflect};
+use static_reflect::{FieldReflect, StaticReflect};
 
 #[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
 #[repr(C)]
@@ -42,6 +43,9 @@ fn test_struct_types() {
                 ],
                 size: size_of::<Nested>(),
                 alignment: align_of::<Nested>(),
+                is_pod: false,
+                has_unsized_tail: false,
+                needs_drop: false,
             }
         },
     );
@@ -52,7 +56,11 @@ fn test_struct_types() {
             name: Some("cycle"),
             value_type: TypeId::<*mut SimpleStruct>::get(),
             offset: field_offset!(Nested, cycle),
-            index: 0
+            index: 0,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         }
     );
     assert_eq!(
@@ -61,7 +69,11 @@ fn test_struct_types() {
             name: Some("float"),
             value_type: TypeId::<f64>::get(),
             offset: field_offset!(Nested, float),
-            index: 1
+            index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         }
     );
     assert_eq!(
@@ -70,7 +82,11 @@ fn test_struct_types() {
             name: Some("number"),
             value_type: TypeId::<u64>::get(),
             offset: field_offset!(Nested, number),
-            index: 2
+            index: 2,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         }
     );
     const FIELDS: &[FieldDef] = &[
@@ -79,30 +95,50 @@ fn test_struct_types() {
             value_type: TypeId::erased::<*mut String>(),
             offset: field_offset!(SimpleStruct, text),
             index: 0,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: Some("number"),
             value_type: TypeId::erased::<u32>(),
             offset: field_offset!(SimpleStruct, number),
             index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: Some("float"),
             value_type: TypeId::erased::<f64>(),
             offset: field_offset!(SimpleStruct, float),
             index: 2,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: Some("b"),
             value_type: TypeId::erased::<bool>(),
             offset: field_offset!(SimpleStruct, b),
             index: 3,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: Some("unit"),
             value_type: TypeId::erased::<()>(),
             offset: field_offset!(SimpleStruct, unit),
             index: 4,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: Some("nested_struct"),
@@ -110,6 +146,10 @@ fn test_struct_types() {
             value_type: TypeId::erased::<Nested>(),
             offset: field_offset!(SimpleStruct, nested_struct),
             index: 5,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
     ];
     assert_eq!(
@@ -121,6 +161,9 @@ fn test_struct_types() {
                     fields: FIELDS,
                     size: size_of::<SimpleStruct>(),
                     alignment: align_of::<SimpleStruct>(),
+                    is_pod: false,
+                    has_unsized_tail: false,
+                    needs_drop: false,
                 }
             }
         )
@@ -139,12 +182,20 @@ fn test_tuple_struct() {
             value_type: TypeId::erased::<*mut String>(),
             offset: field_offset!(SimpleTupleStruct, 0),
             index: 0,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: None,
             value_type: TypeId::erased::<f32>(),
             offset: field_offset!(SimpleTupleStruct, 1),
             index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
         FieldDef {
             name: None,
@@ -152,6 +203,10 @@ fn test_tuple_struct() {
             value_type: TypeId::erased::<Nested>(),
             offset: field_offset!(SimpleTupleStruct, 2),
             index: 2,
+            endianness: None,
+            assumed: false,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         },
     ];
     assert_eq!(SimpleTupleStruct::NAMED_FIELD_INFO.0.erase(), FIELDS[0]);
@@ -164,12 +219,16 @@ fn test_tuple_struct() {
             fields: FIELDS,
             size: size_of::<SimpleTupleStruct>(),
             alignment: align_of::<SimpleTupleStruct>(),
+            is_pod: false,
+            has_unsized_tail: false,
+            needs_drop: false,
         })
     );
 }
 
-#[derive(StaticReflect)]
+#[derive(StaticReflect, Copy, Clone, Debug, PartialEq)]
 #[repr(C)]
+#[reflect(variant_names)]
 #[allow(dead_code)]
 enum SimpleEnum {
     Zero,
@@ -179,46 +238,81 @@ enum SimpleEnum {
     Implicit,
 }
 
-#[test] // Currently fails because of #2
+#[test]
 fn test_simple_enum() {
     assert_eq!(
         SimpleEnum::TYPE_INFO,
-        TypeInfo::CStyleEnum(&CStyleEnumDef {
-            name: "SimpleEnum",
-            discriminant: IntType::ISIZE,
-            variants: &[
-                CStyleEnumVariant {
-                    index: 0,
-                    name: "Zero",
-                    discriminant: DiscriminantValue::Default {
-                        declaration_index: 0,
+        TypeInfo::CStyleEnum(
+            &const {
+                CStyleEnumDef {
+                    name: "SimpleEnum",
+                    // Like C's `int`, repr(C) enums are signed
+                    discriminant: IntType {
+                        size: IntSize::unwrap_from_bytes(size_of::<SimpleEnum>()),
+                        signed: true,
                     },
-                },
-                CStyleEnumVariant {
-                    index: 1,
-                    name: "Two",
-                    discriminant: DiscriminantValue::ExplicitInteger { bits: 2 },
-                },
-                CStyleEnumVariant {
-                    index: 2,
-                    name: "Eight",
-                    discriminant: DiscriminantValue::ExplicitInteger { bits: 8 },
-                },
-                CStyleEnumVariant {
-                    index: 3,
-                    name: "Four",
-                    discriminant: DiscriminantValue::ExplicitInteger { bits: 4 },
-                },
-                CStyleEnumVariant {
-                    index: 4,
-                    name: "Implciit",
-                    discriminant: DiscriminantValue::ImplicitlyOffset { bits: 5 },
+                    variants: &[
+                        CStyleEnumVariant {
+                            index: 0,
+                            name: "Zero",
+                            discriminant: DiscriminantValue::Default {
+                                declaration_index: 0,
+                            },
+                        },
+                        CStyleEnumVariant {
+                            index: 1,
+                            name: "Two",
+                            discriminant: DiscriminantValue::ExplicitInteger { bits: 2 },
+                        },
+                        CStyleEnumVariant {
+                            index: 2,
+                            name: "Eight",
+                            discriminant: DiscriminantValue::ExplicitInteger { bits: 8 },
+                        },
+                        CStyleEnumVariant {
+                            index: 3,
+                            name: "Four",
+                            discriminant: DiscriminantValue::ExplicitInteger { bits: 4 },
+                        },
+                        CStyleEnumVariant {
+                            index: 4,
+                            name: "Implicit",
+                            discriminant: DiscriminantValue::ImplicitlyOffset { bits: 5 },
+                        },
+                    ],
+                    valid_range: Some((0, 8)),
+                    has_gaps: true,
                 }
-            ]
-        })
+            }
+        )
     )
 }
 
+#[test]
+fn test_simple_enum_names() {
+    let variants = [
+        SimpleEnum::Zero,
+        SimpleEnum::Two,
+        SimpleEnum::Eight,
+        SimpleEnum::Four,
+        SimpleEnum::Implicit,
+    ];
+    let def = match SimpleEnum::TYPE_INFO {
+        TypeInfo::CStyleEnum(def) => def,
+        _ => unreachable!(),
+    };
+    for (variant, reflected) in variants.into_iter().zip(def.variants) {
+        let name = variant.variant_name();
+        assert_eq!(name, reflected.name);
+        assert_eq!(def.name_of(variant as i128), Some(name));
+        assert_eq!(SimpleEnum::from_variant_name(name), Some(variant));
+    }
+    assert_eq!(def.name_of(5), Some("Implicit"));
+    assert_eq!(def.name_of(3), None);
+    assert_eq!(SimpleEnum::from_variant_name("Three"), None);
+    assert_eq!(SimpleEnum::from_variant_name("zero"), None);
+}
+
 #[derive(StaticReflect)]
 #[repr(C)]
 struct OpaqueArray {
@@ -238,6 +332,9 @@ fn test_options() {
         ],
         size: size_of::<OpaqueArray>(),
         alignment: align_of::<OpaqueArray>(),
+        is_pod: false,
+        has_unsized_tail: false,
+        needs_drop: false,
     });
     assert_eq!(OPAQUE_ARRAY_TYPE, OpaqueArray::TYPE_INFO);
     assert_eq!(
@@ -246,7 +343,11 @@ fn test_options() {
             name: Some("first"),
             value_type: TypeId::<i8>::get(), // It's actually a 'u8', but we assume_repr
             offset: field_offset!(OpaqueArray, first),
-            index: 0
+            index: 0,
+            endianness: None,
+            assumed: true,
+            opaque_array: false,
+            meta: &FieldMeta::NONE,
         }
     );
     assert_eq!(
@@ -255,7 +356,97 @@ fn test_options() {
             name: Some("array"),
             value_type: TypeId::<*mut String>::get(),
             offset: field_offset!(OpaqueArray, array),
-            index: 1
+            index: 1,
+            endianness: None,
+            assumed: false,
+            opaque_array: true,
+            meta: &FieldMeta::NONE,
         }
     );
 }
+
+generate_layout_tests!(
+    Nested {
+        cycle,
+        float,
+        number
+    },
+    SimpleStruct {
+        text,
+        number,
+        float,
+        b,
+        unit,
+        nested_struct
+    },
+    SimpleTupleStruct { 0, 1, 2 },
+    OpaqueArray { first, array },
+);
+
+#[test]
+fn test_field_def_by_name() {
+    // Resolved at compile time, without knowing the shape of the named field info
+    const NUMBER: Option<FieldDef> = SimpleStruct::TYPE_INFO.field_def("number");
+    const MISSING: Option<FieldDef> = SimpleStruct::TYPE_INFO.field_def("numbers");
+    assert_eq!(NUMBER, Some(SimpleStruct::NAMED_FIELD_INFO.number.erase()));
+    assert_eq!(MISSING, None);
+    assert_eq!(
+        Nested::TYPE_INFO
+            .field_def("float")
+            .map(|field| field.offset),
+        Some(field_offset!(Nested, float))
+    );
+    // Only structures have named fields
+    assert_eq!(u32::TYPE_INFO.field_def("number"), None);
+}
+
+#[test]
+fn leaf_fields() {
+    let leaves = SimpleStruct::TYPE_INFO
+        .leaf_fields()
+        .map(|leaf| leaf.to_string())
+        .collect::<Vec<_>>();
+    let nested = field_offset!(SimpleStruct, nested_struct);
+    let expected = [
+        format!("text @ {}: *mut void", field_offset!(SimpleStruct, text)),
+        format!("number @ {}: u32", field_offset!(SimpleStruct, number)),
+        format!("float @ {}: f64", field_offset!(SimpleStruct, float)),
+        format!("b @ {}: bool", field_offset!(SimpleStruct, b)),
+        format!("unit @ {}: ()", field_offset!(SimpleStruct, unit)),
+        format!("nested_struct.cycle @ {}: *mut void", nested),
+        format!(
+            "nested_struct.float @ {}: f64",
+            nested + field_offset!(Nested, float)
+        ),
+        format!(
+            "nested_struct.number @ {}: u64",
+            nested + field_offset!(Nested, number)
+        ),
+    ];
+    assert_eq!(leaves, expected);
+    if cfg!(target_pointer_width = "64") {
+        assert_eq!(leaves[6], "nested_struct.float @ 40: f64");
+    }
+    // Each path leads back to the same offset
+    for leaf in SimpleStruct::TYPE_INFO.leaf_fields() {
+        let compiled = leaf.path.compile(&SimpleStruct::TYPE_INFO).unwrap();
+        assert_eq!(compiled.total_offset, leaf.offset);
+        assert_eq!(compiled.leaf_type, leaf.info);
+    }
+    // Tuple fields are named by their index
+    let leaves = SimpleTupleStruct::TYPE_INFO
+        .leaf_fields()
+        .map(|leaf| leaf.path.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(leaves, ["0", "1", "2.cycle", "2.float", "2.number"]);
+    // Primitives don't have any fields
+    assert_eq!(u32::TYPE_INFO.leaf_fields().count(), 0);
+}
+
+assert_fully_const!(
+    Nested,
+    SimpleStruct,
+    SimpleTupleStruct,
+    SimpleEnum,
+    OpaqueArray,
+);
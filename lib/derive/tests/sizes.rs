@@ -0,0 +1,61 @@
+//! Using the sizes and alignments of reflected types as constants
+use std::mem::{align_of, size_of};
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Header {
+    magic: u32,
+    flags: u8,
+    length: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(C, align(32))]
+struct Aligned(u8);
+
+/// A buffer large enough to hold any of the types
+const BUFFER_SIZE: usize = [Header::SIZE, Aligned::SIZE, <u16 as StaticReflect>::SIZE][1];
+
+fn serialize(header: &Header) -> [u8; Header::SIZE] {
+    let mut buffer = [0u8; Header::SIZE];
+    buffer[..4].copy_from_slice(&header.magic.to_ne_bytes());
+    buffer[4] = header.flags;
+    buffer[8..].copy_from_slice(&header.length.to_ne_bytes());
+    buffer
+}
+
+#[test]
+fn const_sizes() {
+    assert_eq!(Header::SIZE, size_of::<Header>());
+    assert_eq!(Header::ALIGN, align_of::<Header>());
+    assert_eq!(Aligned::SIZE, 32);
+    assert_eq!(Aligned::ALIGN, 32);
+    assert_eq!(BUFFER_SIZE, 32);
+    assert_eq!(<*const Header as StaticReflect>::SIZE, size_of::<usize>());
+    assert_eq!(<bool as StaticReflect>::ALIGN, 1);
+    let bytes = serialize(&Header {
+        magic: 0xCAFE,
+        flags: 3,
+        length: 7,
+    });
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(bytes[4], 3);
+}
+
+#[test]
+fn matches_type_info() {
+    fn check<T: StaticReflect>() {
+        assert_eq!(T::SIZE, T::TYPE_INFO.size());
+        assert_eq!(T::ALIGN, T::TYPE_INFO.alignment());
+    }
+    check::<Header>();
+    check::<Aligned>();
+    check::<u16>();
+    check::<f64>();
+    check::<()>();
+}
+
+assert_fully_const!(Header, Aligned);
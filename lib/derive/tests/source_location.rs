@@ -0,0 +1,64 @@
+//! The locations where reflected items were declared
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::SourceLocation;
+use static_reflect::{reflect_func, FieldReflect, StaticReflect};
+
+const STRUCT_LINE: u32 = line!();
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Located {
+    value: u32,
+}
+
+const FUNC_LINE: u32 = line!();
+#[reflect_func]
+#[no_mangle]
+pub extern "C" fn located_func(value: u32) -> u32 {
+    value
+}
+
+fn assert_located(source: Option<SourceLocation>, declared_after: u32) {
+    let source = source.expect("Missing source location");
+    assert!(
+        source.file.ends_with("source_location.rs"),
+        "Unexpected file: {}",
+        source.file
+    );
+    assert!(
+        source.line > declared_after && source.line <= declared_after + 4,
+        "Unexpected line {} (expected shortly after {})",
+        source.line,
+        declared_after
+    );
+}
+
+#[test]
+fn struct_location() {
+    assert_located(<Located as FieldReflect>::SOURCE, STRUCT_LINE);
+}
+
+#[test]
+fn function_location() {
+    assert_located(_FUNC_located_func.source, FUNC_LINE);
+    let display = _FUNC_located_func.to_string();
+    assert!(
+        display.starts_with("fn located_func(u32) -> u32 (declared at "),
+        "Unexpected display: {}",
+        display
+    );
+}
+
+#[test]
+fn location_ignored_by_comparisons() {
+    let mut moved = _FUNC_located_func;
+    moved.source = Some(SourceLocation {
+        file: "elsewhere.rs",
+        line: 1,
+    });
+    assert_eq!(moved, _FUNC_located_func);
+    moved.source = None;
+    assert_eq!(moved, _FUNC_located_func);
+    assert_eq!(moved.to_string(), "fn located_func(u32) -> u32");
+}
+
+assert_fully_const!(Located);
@@ -0,0 +1,129 @@
+//! Compile-time checks that public types implement the expected standard traits
+//!
+//! Removing one of these impls fails to compile, rather than failing a test.
+//! Intentional omissions are documented on each type (and listed at the bottom).
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use static_reflect::builtins::{AsmOption, AsmSlice, AsmStr};
+use static_reflect::funcs::{
+    ArgContract, CallingConvention, ClosureDecl, ClosureError, FunctionDeclaration,
+    FunctionLocation, MissingSymbol, NotThreadSafe, ParamDirection, ResolutionHint, SignatureDef,
+    ThreadSafety, UnwindBehavior,
+};
+use static_reflect::types::{
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, Endian, FieldDef, FieldId, FloatSize,
+    IntSize, IntType, InvalidSizeErr, NicheDef, OpaqueTypeError, PointerDiscipline, PrimitiveMeta,
+    PrimitiveType, RegionError, StructureDef, TaggedUnionDef, TaggedUnionStyle, TaggedUnionVariant,
+    TypeId, TypeInfo, TypedRegion, UnionFieldDef, UnionFieldIter, UnionFields, UntaggedUnionDef,
+    ZeroInitError,
+};
+
+/// Plain values, which are cheap to copy and compare
+const fn value<T: Copy + Clone + Debug + PartialEq + Eq + Hash>() {}
+/// Errors and other values owning heap data
+const fn owned<T: Clone + Debug + PartialEq + Eq + Hash>() {}
+const fn ordered<T: Ord>() {}
+const fn default<T: Default>() {}
+const fn iterator<T: Iterator + Clone + Debug>() {}
+
+/// A return type without any standard traits
+struct Opaque;
+
+const _: () = {
+    // types
+    value::<IntSize>();
+    value::<IntType>();
+    value::<FloatSize>();
+    value::<Endian>();
+    value::<PointerDiscipline>();
+    value::<TaggedUnionStyle>();
+    value::<TypeInfo>();
+    value::<TypedRegion>();
+    value::<StructureDef>();
+    value::<FieldDef>();
+    value::<FieldDef<u32>>();
+    value::<CStyleEnumDef>();
+    value::<CStyleEnumVariant>();
+    value::<NicheDef>();
+    value::<DiscriminantValue>();
+    value::<TaggedUnionDef>();
+    value::<TaggedUnionVariant>();
+    value::<UntaggedUnionDef>();
+    value::<UnionFields>();
+    value::<UnionFieldDef>();
+    value::<UnionFieldDef<u32>>();
+    iterator::<UnionFieldIter>();
+    value::<PrimitiveType>();
+    value::<PrimitiveMeta>();
+    value::<TypeId>();
+    value::<FieldId>();
+    value::<InvalidSizeErr>();
+    owned::<ZeroInitError>();
+    owned::<OpaqueTypeError>();
+    owned::<RegionError>();
+    ordered::<IntSize>();
+    ordered::<FloatSize>();
+    ordered::<DiscriminantValue>();
+    default::<IntSize>();
+    default::<IntType>();
+    default::<PointerDiscipline>();
+
+    // funcs
+    value::<FunctionDeclaration>();
+    // The type parameters are only markers, so they don't need any traits
+    value::<FunctionDeclaration<Opaque, (Opaque,)>>();
+    value::<SignatureDef>();
+    value::<ClosureDecl>();
+    value::<ClosureError>();
+    value::<ParamDirection>();
+    value::<ArgContract>();
+    value::<CallingConvention>();
+    value::<UnwindBehavior>();
+    value::<ThreadSafety>();
+    value::<NotThreadSafe>();
+    value::<FunctionLocation>();
+    value::<ResolutionHint>();
+    value::<MissingSymbol>();
+    default::<SignatureDef>();
+    default::<ParamDirection>();
+    default::<ArgContract>();
+    default::<CallingConvention>();
+    default::<UnwindBehavior>();
+    default::<ThreadSafety>();
+};
+
+// builtins (which intentionally don't implement `PartialEq` or `Hash`)
+const fn view<T: Copy + Clone + Debug + Default>() {}
+const _: () = {
+    view::<AsmSlice<u32>>();
+    view::<AsmSlice<Opaque>>();
+    view::<AsmStr>();
+};
+
+#[test]
+fn runtime_traits() {
+    // Only shows whether the value is present, so it works for any type
+    let option = AsmOption::<Opaque>::default();
+    assert_eq!(format!("{:?}", option), "AsmOption { present: false, .. }");
+    assert!(AsmSlice::<u64>::default().len == 0);
+    assert!(AsmStr::default().is_empty());
+    assert_eq!(
+        SignatureDef::default().return_type,
+        &TypeInfo::Unit,
+        "The default signature should be `fn()`"
+    );
+    let mut discriminants = [
+        DiscriminantValue::ExplicitInteger { bits: 7 },
+        DiscriminantValue::Default {
+            declaration_index: 0,
+        },
+        DiscriminantValue::ImplicitlyOffset { bits: 3 },
+    ];
+    discriminants.sort();
+    assert_eq!(
+        discriminants.map(|value| value.bits()),
+        [0, 3, 7],
+        "Discriminants are sorted by value"
+    );
+}
@@ -0,0 +1,432 @@
+#![feature(never_type)]
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::ptr::NonNull;
+
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::transmute::TransmuteError;
+use static_reflect::types::{
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, IntType, TypeInfo,
+};
+use static_reflect::StaticReflect;
+
+/// The kind of validity invariant a primitive has
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Validity {
+    /// Any initialized bytes (integers and floats)
+    Any,
+    Bool,
+    Pointer,
+    NonZero,
+}
+
+fn primitives() -> Vec<(&'static str, TypeInfo, Validity)> {
+    use Validity::*;
+    vec![
+        ("u8", u8::TYPE_INFO, Any),
+        ("i8", i8::TYPE_INFO, Any),
+        ("bool", bool::TYPE_INFO, Bool),
+        ("u16", u16::TYPE_INFO, Any),
+        ("i16", i16::TYPE_INFO, Any),
+        ("u32", u32::TYPE_INFO, Any),
+        ("i32", i32::TYPE_INFO, Any),
+        ("f32", f32::TYPE_INFO, Any),
+        ("NonZeroU32", NonZeroU32::TYPE_INFO, NonZero),
+        ("u64", u64::TYPE_INFO, Any),
+        ("NonZeroUsize", NonZeroUsize::TYPE_INFO, NonZero),
+        ("i64", i64::TYPE_INFO, Any),
+        ("f64", f64::TYPE_INFO, Any),
+        ("usize", usize::TYPE_INFO, Any),
+        ("*mut u8", <*mut u8>::TYPE_INFO, Pointer),
+        ("*const u8", <*const u8>::TYPE_INFO, Pointer),
+        ("NonNull<u8>", NonNull::<u8>::TYPE_INFO, NonZero),
+        (
+            "Option<NonNull<u8>>",
+            Option::<NonNull<u8>>::TYPE_INFO,
+            Pointer,
+        ),
+    ]
+}
+
+#[test]
+fn primitive_matrix() {
+    for (source_name, source, source_validity) in primitives() {
+        for (target_name, target, target_validity) in primitives() {
+            let result = source.can_transmute_to(&target);
+            if source.size() != target.size() {
+                assert!(
+                    matches!(result, Err(TransmuteError::SizeMismatch { .. })),
+                    "{} -> {}: {:?}",
+                    source_name,
+                    target_name,
+                    result
+                );
+                continue;
+            }
+            let is_pointer = |validity: Validity, name: &str| {
+                validity == Validity::Pointer || name == "NonNull<u8>"
+            };
+            let source_is_pointer = is_pointer(source_validity, source_name);
+            let expected = match target_validity {
+                Validity::Any => true,
+                Validity::Bool => source_validity == Validity::Bool,
+                // Non-zero pointers can only come from other non-zero pointers
+                Validity::NonZero => {
+                    source_validity == Validity::NonZero
+                        && (source_is_pointer || !is_pointer(target_validity, target_name))
+                }
+                Validity::Pointer => source_is_pointer,
+            };
+            assert_eq!(
+                result.is_ok(),
+                expected,
+                "{} -> {}: {:?}",
+                source_name,
+                target_name,
+                result
+            );
+            if !expected {
+                // Niches are checked as their nullable representation
+                let checked_type = match target {
+                    TypeInfo::Niche(_) => target.nullable(),
+                    _ => target,
+                };
+                assert_eq!(
+                    result,
+                    Err(TransmuteError::InvalidValue {
+                        path: target.to_string(),
+                        type_name: checked_type.to_string(),
+                    })
+                );
+            }
+        }
+    }
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Padded {
+    small: u8,
+    big: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Words {
+    first: u32,
+    second: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C, align(4))]
+struct Halves {
+    low: u16,
+    high: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Flags {
+    enabled: bool,
+    level: u8,
+    count: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Bytes {
+    first: u8,
+    second: u8,
+    count: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+union IntOrFloat {
+    int: u32,
+    float: f32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+union IntOrShort {
+    int: u32,
+    short: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Wide {
+    A,
+    B,
+    C,
+    D,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Gapped {
+    A = 0,
+    B = 2,
+}
+
+#[derive(StaticReflect)]
+#[repr(i8)]
+#[allow(dead_code)]
+enum Signed {
+    MinusTwo = -2,
+    MinusOne,
+    Zero,
+    One,
+}
+
+#[derive(StaticReflect)]
+#[repr(i8)]
+#[allow(dead_code)]
+enum SignedGapped {
+    MinusTwo = -2,
+    One = 1,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Shifted {
+    A = 1,
+    B,
+    C,
+    D,
+}
+
+/// Claims to have the dense range `0..=3`, but only has two variants
+#[allow(dead_code)]
+#[repr(u8)]
+enum Lying {
+    First = 0,
+    Last = 3,
+}
+unsafe impl StaticReflect for Lying {
+    const TYPE_INFO: TypeInfo = TypeInfo::CStyleEnum(&CStyleEnumDef {
+        name: "Lying",
+        discriminant: IntType::U8,
+        variants: &[
+            CStyleEnumVariant {
+                index: 0,
+                name: "First",
+                discriminant: DiscriminantValue::ExplicitInteger { bits: 0 },
+            },
+            CStyleEnumVariant {
+                index: 1,
+                name: "Last",
+                discriminant: DiscriminantValue::ExplicitInteger { bits: 3 },
+            },
+        ],
+        valid_range: Some((0, 3)),
+        has_gaps: false,
+    });
+}
+
+#[test]
+fn structures() {
+    assert_eq!(Halves::TYPE_INFO.can_transmute_to(&u32::TYPE_INFO), Ok(()));
+    assert_eq!(u32::TYPE_INFO.can_transmute_to(&Halves::TYPE_INFO), Ok(()));
+    assert_eq!(
+        Words::TYPE_INFO.can_transmute_to(&Padded::TYPE_INFO),
+        Ok(())
+    );
+    // The padding after `small` is uninitialized
+    assert_eq!(
+        Padded::TYPE_INFO.can_transmute_to(&Words::TYPE_INFO),
+        Err(TransmuteError::Uninitialized {
+            path: "Words.first".into(),
+            type_name: "u32".into(),
+        })
+    );
+    assert_eq!(Flags::TYPE_INFO.can_transmute_to(&Bytes::TYPE_INFO), Ok(()));
+    let err = Bytes::TYPE_INFO
+        .can_transmute_to(&Flags::TYPE_INFO)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransmuteError::InvalidValue {
+            path: "Flags.enabled".into(),
+            type_name: "bool".into(),
+        }
+    );
+    assert_eq!(err.to_string(), "Flags.enabled (bool) may be invalid");
+    // Same size, but a smaller alignment
+    assert_eq!(
+        Bytes::TYPE_INFO.can_transmute_to(&f32::TYPE_INFO),
+        Err(TransmuteError::AlignmentMismatch {
+            source_alignment: 2,
+            target_alignment: 4,
+        })
+    );
+    assert!(matches!(
+        Words::TYPE_INFO.can_transmute_to(&u32::TYPE_INFO),
+        Err(TransmuteError::SizeMismatch {
+            source_size: 8,
+            target_size: 4,
+        })
+    ));
+}
+
+#[test]
+fn unions() {
+    // Unions are always valid targets
+    assert_eq!(
+        Halves::TYPE_INFO.can_transmute_to(&IntOrFloat::TYPE_INFO),
+        Ok(())
+    );
+    assert_eq!(
+        IntOrShort::TYPE_INFO.can_transmute_to(&IntOrFloat::TYPE_INFO),
+        Ok(())
+    );
+    // Every field initializes all the bytes
+    assert_eq!(
+        IntOrFloat::TYPE_INFO.can_transmute_to(&u32::TYPE_INFO),
+        Ok(())
+    );
+    // The upper half may be uninitialized
+    assert_eq!(
+        IntOrShort::TYPE_INFO.can_transmute_to(&u32::TYPE_INFO),
+        Err(TransmuteError::Uninitialized {
+            path: "u32".into(),
+            type_name: "u32".into(),
+        })
+    );
+    assert!(IntOrShort::TYPE_INFO
+        .can_transmute_to(&Halves::TYPE_INFO)
+        .is_err());
+}
+
+#[test]
+fn enums() {
+    assert_eq!(Color::TYPE_INFO.can_transmute_to(&u8::TYPE_INFO), Ok(()));
+    assert_eq!(Color::TYPE_INFO.can_transmute_to(&Wide::TYPE_INFO), Ok(()));
+    assert_eq!(
+        Gapped::TYPE_INFO.can_transmute_to(&Color::TYPE_INFO),
+        Ok(())
+    );
+    assert_eq!(bool::TYPE_INFO.can_transmute_to(&Color::TYPE_INFO), Ok(()));
+    let invalid = |name: &str| {
+        Err(TransmuteError::InvalidValue {
+            path: name.into(),
+            type_name: name.into(),
+        })
+    };
+    assert_eq!(
+        Wide::TYPE_INFO.can_transmute_to(&Color::TYPE_INFO),
+        invalid("Color")
+    );
+    assert_eq!(
+        u8::TYPE_INFO.can_transmute_to(&Color::TYPE_INFO),
+        invalid("Color")
+    );
+    // The gap between the discriminants is invalid
+    assert_eq!(
+        Color::TYPE_INFO.can_transmute_to(&Gapped::TYPE_INFO),
+        invalid("Gapped")
+    );
+    assert_eq!(
+        bool::TYPE_INFO.can_transmute_to(&Gapped::TYPE_INFO),
+        invalid("Gapped")
+    );
+    assert!(!Color::TYPE_INFO.is_never());
+}
+
+#[test]
+fn enum_validity_flags() {
+    let invalid = |name: &str| {
+        Err(TransmuteError::InvalidValue {
+            path: name.into(),
+            type_name: name.into(),
+        })
+    };
+    // Negative ranges
+    assert_eq!(
+        SignedGapped::TYPE_INFO.can_transmute_to(&Signed::TYPE_INFO),
+        Ok(())
+    );
+    assert_eq!(
+        Signed::TYPE_INFO.can_transmute_to(&SignedGapped::TYPE_INFO),
+        invalid("SignedGapped")
+    );
+    assert_eq!(bool::TYPE_INFO.can_transmute_to(&Signed::TYPE_INFO), Ok(()));
+    assert_eq!(
+        bool::TYPE_INFO.can_transmute_to(&SignedGapped::TYPE_INFO),
+        invalid("SignedGapped")
+    );
+    // Same bits, but `-2` and `-1` would be `254` and `255`
+    assert_eq!(
+        Signed::TYPE_INFO.can_transmute_to(&Wide::TYPE_INFO),
+        invalid("Wide")
+    );
+    // A dense range which doesn't start at zero
+    assert_eq!(
+        Color::TYPE_INFO.can_transmute_to(&Shifted::TYPE_INFO),
+        invalid("Shifted")
+    );
+    assert_eq!(
+        bool::TYPE_INFO.can_transmute_to(&Shifted::TYPE_INFO),
+        invalid("Shifted")
+    );
+    // Only the precomputed flags are consulted for dense enums,
+    // so inconsistent flags are trusted (and rejected by validation)
+    let lying = match Lying::TYPE_INFO {
+        TypeInfo::CStyleEnum(def) => def,
+        _ => unreachable!(),
+    };
+    assert!(lying.validate().is_err());
+    assert_eq!(Wide::TYPE_INFO.can_transmute_to(&Lying::TYPE_INFO), Ok(()));
+    assert_eq!(
+        Wide::TYPE_INFO.can_transmute_to(&Gapped::TYPE_INFO),
+        invalid("Gapped")
+    );
+}
+
+#[test]
+fn opaque() {
+    const OPAQUE: TypeInfo = TypeInfo::Extern { name: "Opaque" };
+    assert_eq!(
+        u32::TYPE_INFO.can_transmute_to(&OPAQUE),
+        Err(TransmuteError::Unsupported {
+            path: "extern Opaque".into(),
+            type_name: "extern Opaque".into(),
+        })
+    );
+    // Nothing can become the never type
+    assert_eq!(
+        <()>::TYPE_INFO.can_transmute_to(&<!>::TYPE_INFO),
+        Err(TransmuteError::InvalidValue {
+            path: "!".into(),
+            type_name: "!".into(),
+        })
+    );
+}
+
+assert_fully_const!(
+    Padded,
+    Words,
+    Halves,
+    Flags,
+    Bytes,
+    IntOrFloat,
+    IntOrShort,
+    Color,
+    Wide,
+    Gapped,
+    Signed,
+    SignedGapped,
+    Shifted,
+);
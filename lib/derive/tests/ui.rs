@@ -0,0 +1,8 @@
+//! Tests for the compile-time behavior of the derive (using trybuild)
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/fail/*.rs");
+}
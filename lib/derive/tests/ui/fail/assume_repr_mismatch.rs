@@ -0,0 +1,17 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WrongSize {
+    #[reflect(assume_repr = "i8")]
+    value: u64,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WrongAlignment {
+    #[reflect(assume_repr = "u32")]
+    bytes: [u8; 4],
+}
+
+fn main() {}
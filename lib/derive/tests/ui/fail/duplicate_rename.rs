@@ -0,0 +1,20 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Duplicate {
+    #[reflect(rename = "value")]
+    first: u32,
+    #[reflect(rename = "value")]
+    second: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct RenamedOntoExisting {
+    value: u32,
+    #[reflect(rename = "value")]
+    other: u32,
+}
+
+fn main() {}
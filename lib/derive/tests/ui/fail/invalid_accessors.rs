@@ -0,0 +1,34 @@
+use static_reflect::StaticReflect;
+
+/// The assumed type is too small, which only the accessors notice
+#[derive(StaticReflect)]
+#[reflect(accessors)]
+#[repr(C)]
+struct LyingSize {
+    first: u64,
+    #[reflect(assume_repr_unchecked = "u32")]
+    last: u64,
+}
+
+#[derive(StaticReflect)]
+#[reflect(accessors)]
+#[repr(C)]
+struct Tuple(u32, u32);
+
+#[derive(StaticReflect)]
+#[reflect(accessors)]
+#[repr(C)]
+struct BadRename {
+    #[reflect(rename = "not an identifier")]
+    field: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(accessors)]
+#[repr(C)]
+union Union {
+    first: u32,
+    second: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(u8, align(4))]
+enum AlignedEnum {
+    First,
+}
+
+fn main() {}
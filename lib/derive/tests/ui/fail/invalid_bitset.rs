@@ -0,0 +1,31 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct TooManyNames {
+    #[reflect(bitset(names = "A,B,C,D,E,F,G,H,I"))]
+    flags: u8,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct NotInteger {
+    #[reflect(bitset(names = "A"))]
+    ratio: f32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct DuplicateName {
+    #[reflect(bitset(names = "A,B,A"))]
+    flags: u8,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct EmptyName {
+    #[reflect(bitset(names = "A,,B"))]
+    flags: u8,
+}
+
+fn main() {}
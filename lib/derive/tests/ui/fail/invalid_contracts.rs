@@ -0,0 +1,39 @@
+use static_reflect::reflect_func;
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn non_null_integer(#[reflect(non_null)] value: u32) -> u32 {
+    value
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn range_pointer(#[reflect(range(0, 10))] value: *const u32) {
+    let _ = value;
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn empty_range(#[reflect(range(10, 1))] value: i32) -> i32 {
+    value
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn negative_unsigned(#[reflect(range(-1, 1))] value: u8) -> u8 {
+    value
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn duplicate_range(#[reflect(range(0, 1), range(0, 2))] value: i32) -> i32 {
+    value
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn owned_integer(#[reflect(ptr = "owned")] value: usize) -> usize {
+    value
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(len = "missing"))]
+#[repr(C)]
+struct UnknownField {
+    count: u32,
+    data: [u8; 0],
+}
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(len = "data"))]
+#[repr(C)]
+struct TailLength {
+    count: u32,
+    data: [u8; 0],
+}
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(len = "count"))]
+#[repr(C)]
+struct FloatLength {
+    count: f32,
+    data: [u8; 0],
+}
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(size = "count"))]
+#[repr(C)]
+struct UnknownOption {
+    count: u32,
+    data: [u8; 0],
+}
+
+fn main() {}
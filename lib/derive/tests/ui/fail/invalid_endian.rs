@@ -0,0 +1,17 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct NotNumber {
+    #[reflect(endian = "big")]
+    flag: bool,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct UnknownOrder {
+    #[reflect(endian = "middle")]
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(lazy_fields)]
+#[repr(C)]
+struct NotAUnion {
+    first: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(lazy_fields)]
+#[repr(C)]
+union Generic<T: Copy> {
+    first: u32,
+    second: T,
+}
+
+#[derive(StaticReflect)]
+#[reflect(lazy_fields)]
+#[repr(C)]
+union Duplicate {
+    first: u32,
+    #[reflect(rename = "first")]
+    second: u64,
+}
+
+fn main() {}
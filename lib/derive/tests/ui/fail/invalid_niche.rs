@@ -0,0 +1,28 @@
+use std::ptr::NonNull;
+
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(niche)]
+enum ThreeVariants {
+    None,
+    Some(NonNull<u8>),
+    Other(NonNull<u8>),
+}
+
+#[derive(StaticReflect)]
+#[reflect(niche)]
+#[repr(C)]
+enum ExplicitRepr {
+    None,
+    Some(NonNull<u8>),
+}
+
+#[derive(StaticReflect)]
+#[reflect(niche)]
+enum MaybeZero {
+    None,
+    Some(u32),
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use static_reflect::reflect_func;
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn not_pointer(#[reflect(out)] value: u32) -> u32 {
+    value
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn const_pointer(#[reflect(out)] value: *const u32) {
+    let _ = value;
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn conflicting(#[reflect(out, inout)] value: *mut u32) {
+    let _ = value;
+}
+
+fn main() {}
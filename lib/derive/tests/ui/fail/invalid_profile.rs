@@ -0,0 +1,17 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(profile = "tiny")]
+#[repr(C)]
+struct UnknownProfile {
+    value: u32,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[reflect(profile = "minimal")]
+#[repr(C)]
+union NotStruct {
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct NotPointer {
+    #[reflect(ptr = "owned")]
+    len: usize,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct UnknownDiscipline {
+    #[reflect(ptr = "shared")]
+    data: *const u8,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use static_reflect::reflect_const;
+
+#[reflect_const]
+static mut COUNTER: u32 = 0;
+
+#[reflect_const]
+const POINTER: *const u8 = std::ptr::null();
+
+#[reflect_const]
+static NESTED: [[u8; 2]; 2] = [[0; 2]; 2];
+
+#[reflect_const(library = "libfoo.so")]
+const WITH_OPTION: u8 = 0;
+
+#[reflect_const]
+fn not_a_constant() {}
+
+fn main() {}
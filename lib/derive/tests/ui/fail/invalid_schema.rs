@@ -0,0 +1,17 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(u8)]
+enum NotAStruct {
+    First,
+}
+
+#[derive(StaticReflect)]
+#[reflect(schema)]
+#[repr(C)]
+struct Nested<T> {
+    value: Option<T>,
+}
+
+fn main() {}
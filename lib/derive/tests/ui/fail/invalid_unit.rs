@@ -0,0 +1,30 @@
+use static_reflect::{reflect_func, StaticReflect};
+
+#[derive(StaticReflect)]
+#[reflect(newtype_unit)]
+#[repr(C)]
+struct NotTransparent(u32);
+
+#[derive(StaticReflect)]
+#[reflect(newtype_unit = "seconds")]
+#[repr(transparent)]
+struct FloatSeconds(f64);
+
+#[derive(StaticReflect)]
+#[reflect(newtype_unit)]
+#[repr(transparent)]
+struct TwoFields(u32, ());
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct FloatField {
+    #[reflect(unit = "meters")]
+    distance: f32,
+}
+
+#[reflect_func]
+extern "C" {
+    fn wait(#[reflect(unit = "milliseconds")] timeout: *const u32);
+}
+
+fn main() {}
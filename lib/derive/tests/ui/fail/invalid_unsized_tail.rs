@@ -0,0 +1,23 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail)]
+#[repr(C)]
+struct NotAnArray {
+    len: u32,
+    tail: u64,
+}
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail)]
+#[repr(C)]
+struct Empty {}
+
+#[derive(StaticReflect)]
+#[reflect(unsized_tail)]
+#[repr(u8)]
+enum NotAStruct {
+    First,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(variant_names)]
+#[repr(C)]
+struct NotAnEnum {
+    value: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(variant_names)]
+#[repr(u8)]
+enum DuplicateName {
+    #[reflect(rename = "Second")]
+    First,
+    Second,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+//! Lint warnings are reported through the `deprecated` lint,
+//! which is denied here so they show up in the expected output.
+#![deny(deprecated)]
+
+use static_reflect::StaticReflect;
+
+/// A `bool` next to a `u64` wastes 14 bytes, and reordering would save 8
+#[derive(StaticReflect)]
+#[reflect(lint)]
+#[repr(C)]
+struct BadlyOrdered {
+    first: bool,
+    big: u64,
+    last: bool,
+}
+
+/// Reordering can't help with the 7 bytes of trailing padding,
+/// which are allowed by the higher limit.
+#[derive(StaticReflect)]
+#[reflect(lint(max_padding = 8))]
+#[repr(C)]
+struct WellPacked {
+    big: u64,
+    flag: bool,
+}
+
+#[derive(StaticReflect)]
+#[reflect(lint(max_padding = 2))]
+#[repr(C)]
+struct TooMuchPadding {
+    big: u64,
+    small: u32,
+}
+
+#[derive(StaticReflect)]
+#[reflect(lint)]
+#[repr(u32)]
+#[allow(dead_code)]
+enum WideDiscriminant {
+    First,
+    Second = 200,
+}
+
+#[derive(StaticReflect)]
+#[reflect(lint)]
+#[repr(i8)]
+#[allow(dead_code)]
+enum NarrowDiscriminant {
+    Negative = -100,
+    Positive = 100,
+}
+
+/// A `char` has no C equivalent, even when it's reflected as a `u32`
+#[derive(StaticReflect)]
+#[reflect(lint)]
+#[repr(C)]
+struct HasChar {
+    #[reflect(assume_repr = "u32")]
+    letter: char,
+    code: u32,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use static_reflect::macros::assert_fully_const;
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(lazy_fields)]
+#[repr(C)]
+#[allow(dead_code)]
+union Lazy {
+    first: u32,
+    second: f32,
+}
+
+// The fields of a lazy union are only constructed at runtime
+assert_fully_const!(Lazy);
+
+fn main() {}
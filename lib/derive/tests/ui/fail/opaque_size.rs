@@ -0,0 +1,14 @@
+use static_reflect::macros::define_extern_type;
+use static_reflect::StaticReflect;
+
+pub struct Socket {
+    _opaque: [u8; 0],
+}
+define_extern_type!(Socket);
+
+// The layout of an extern type is unknown at compile time
+const SOCKET_SIZE: usize = Socket::SIZE;
+
+fn main() {
+    let _buffer = [0u8; SOCKET_SIZE];
+}
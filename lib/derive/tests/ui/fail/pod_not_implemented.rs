@@ -0,0 +1,10 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[reflect(pod)]
+#[repr(C)]
+struct NotPod {
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Reserved {
+    __reflect_FIELDS: u32,
+}
+
+fn main() {}
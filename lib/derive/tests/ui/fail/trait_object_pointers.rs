@@ -0,0 +1,39 @@
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use static_reflect::StaticReflect;
+
+trait Callback {}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Boxed {
+    callback: Box<dyn Callback>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Borrowed<'a> {
+    callback: &'a dyn Callback,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct RawPointer {
+    callback: *mut (dyn Callback + Send),
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Optional {
+    callback: Option<NonNull<dyn Callback>>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+union Shared {
+    callback: std::mem::ManuallyDrop<Arc<dyn Callback>>,
+    shared: std::mem::ManuallyDrop<std::sync::Arc<dyn Callback>>,
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct SliceField {
+    len: usize,
+    data: [u8],
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct StrField {
+    len: usize,
+    text: str,
+}
+
+trait Callback {}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct TraitObjectField {
+    len: usize,
+    callback: dyn Callback,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct MaybeUnsized<T: ?Sized> {
+    len: usize,
+    value: T,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct MaybeUnsizedWhere<T>
+where
+    T: ?Sized,
+{
+    len: usize,
+    value: T,
+}
+
+fn main() {}
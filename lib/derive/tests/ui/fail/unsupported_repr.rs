@@ -0,0 +1,15 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(u128)]
+enum Huge {
+    First,
+}
+
+#[derive(StaticReflect)]
+#[repr(packed)]
+struct Packed {
+    first: u8,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use static_reflect::StaticReflect;
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct SameLayout {
+    #[reflect(assume_repr = "i8")]
+    value: u8,
+}
+
+// The mismatch is intentional, so the checks are skipped
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Reinterpreted {
+    #[reflect(assume_repr_unchecked = "u8")]
+    buffer: [u8; 16],
+}
+
+fn main() {}
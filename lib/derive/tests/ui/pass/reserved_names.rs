@@ -0,0 +1,28 @@
+//! Field names resembling the old generated identifiers are fine,
+//! since only the reserved `__reflect_` prefix can collide.
+use static_reflect::{FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+#[allow(non_snake_case)]
+struct Collisions {
+    _FIELDS: u64,
+    _DEF: u32,
+    _Tuple_0: u32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Renamed {
+    #[reflect(rename = "second")]
+    first: u32,
+    #[reflect(rename = "first")]
+    second: u32,
+}
+
+fn main() {
+    let _ = Collisions::TYPE_INFO;
+    assert_eq!(Collisions::NAMED_FIELD_INFO._FIELDS.name, Some("_FIELDS"));
+    assert_eq!(Renamed::NAMED_FIELD_INFO.first.name, Some("second"));
+    assert_eq!(Renamed::NAMED_FIELD_INFO.second.name, Some("first"));
+}
@@ -0,0 +1,123 @@
+//! Non-ASCII and raw identifiers, which are passed through untouched
+//! (besides the `r#` prefix of raw identifiers)
+use static_reflect::funcs::codegen::{to_extern_block, CodegenError};
+use static_reflect::macros::{assert_fully_const, field_offset};
+use static_reflect::types::{CStyleEnumDef, TypeInfo};
+use static_reflect::{reflect_func, FieldReflect, StaticReflect};
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+pub struct 座標 {
+    横: u32,
+    縦: u32,
+    r#type: u8,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+pub struct タプル(u8, 座標);
+
+#[derive(StaticReflect)]
+#[reflect(variant_names)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum 色 {
+    赤,
+    r#青,
+}
+
+#[reflect_func(absolute)]
+pub extern "C" fn 面積(point: 座標) -> u32 {
+    point.横 * point.縦
+}
+
+#[reflect_func]
+#[export_name = "area_by_value"]
+pub extern "C" fn 面積を計算(point: 座標) -> u32 {
+    point.横 * point.縦
+}
+
+#[no_mangle]
+#[reflect_func]
+pub extern "C" fn r#match(point: 座標, kind: u8) -> bool {
+    point.r#type == kind
+}
+
+#[test]
+fn type_info() {
+    let def = match 座標::TYPE_INFO {
+        TypeInfo::Structure(def) => def,
+        other => panic!("Unexpected type: {}", other),
+    };
+    assert_eq!(def.name, "座標");
+    let names = def
+        .fields
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, [Some("横"), Some("縦"), Some("type")]);
+    assert_eq!(座標::NAMED_FIELD_INFO.縦.offset, 4);
+    assert_eq!(field_offset!(座標, r#type), 8);
+    assert_eq!(
+        座標::TYPE_INFO.field_def("縦").map(|field| field.offset),
+        Some(4)
+    );
+    assert_eq!(タプル::TYPE_INFO.to_string(), "タプル");
+    // Display passes the names through untouched
+    assert_eq!(座標::TYPE_INFO.to_string(), "座標");
+}
+
+#[test]
+fn enum_variants() {
+    let def: &CStyleEnumDef = match 色::TYPE_INFO {
+        TypeInfo::CStyleEnum(def) => def,
+        other => panic!("Unexpected type: {}", other),
+    };
+    assert_eq!(def.name, "色");
+    let names = def
+        .variants
+        .iter()
+        .map(|variant| variant.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["赤", "青"]);
+}
+
+#[test]
+fn functions() {
+    assert_eq!(_FUNC_面積.name, "面積");
+    assert_eq!(_FUNC_面積を計算.name, "面積を計算");
+    assert_eq!(_FUNC_match.name, "match");
+    assert_eq!(
+        _FUNC_match.resolution_hint().map(|hint| hint.symbol),
+        Some("match")
+    );
+}
+
+#[test]
+fn codegen() {
+    // Keywords are written as raw identifiers, and type names are written as-is
+    let source = to_extern_block(&[*_FUNC_match.erase()]).unwrap();
+    assert_eq!(
+        source,
+        r#"extern "C" {
+    pub fn r#match(arg0: 座標, arg1: u8) -> bool;
+}
+"#
+    );
+    let parsed = syn::parse_str::<syn::ItemForeignMod>(&source).unwrap();
+    assert_eq!(parsed.items.len(), 1);
+    // Items in an extern block can't have non-ASCII names (even with a link name)
+    let error = to_extern_block(&[*_FUNC_面積を計算.erase()]).unwrap_err();
+    assert_eq!(
+        error,
+        CodegenError::InvalidName {
+            function: "面積を計算"
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Function `面積を計算` can't be declared in an extern block (its name must be an ASCII identifier)"
+    );
+}
+
+assert_fully_const!(座標, タプル, 色);
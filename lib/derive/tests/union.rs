@@ -1,6 +1,7 @@
 use std::mem::{align_of, size_of};
 
-use static_reflect::types::{TypeId, TypeInfo, UnionFieldDef, UntaggedUnionDef};
+use static_reflect::macros::{assert_fully_const, generate_layout_tests};
+use static_reflect::types::{TypeId, TypeInfo, UnionFieldDef, UnionFields, UntaggedUnionDef};
 use static_reflect::{FieldReflect, StaticReflect};
 
 #[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
@@ -19,18 +20,21 @@ union SimpleUnion {
     nested: Nested,
 }
 
+generate_layout_tests!(Nested { float, number }, SimpleUnion { text, b, f, nested });
+
 #[test]
 fn test_union_types() {
     const EXPECTED_UNION: TypeInfo = TypeInfo::UntaggedUnion(&UntaggedUnionDef {
         name: "SimpleUnion",
-        fields: &[
+        fields: UnionFields::Eager(&[
             SimpleUnion::NAMED_FIELD_INFO.text.erase(),
             SimpleUnion::NAMED_FIELD_INFO.b.erase(),
             SimpleUnion::NAMED_FIELD_INFO.f.erase(),
             SimpleUnion::NAMED_FIELD_INFO.nested.erase(),
-        ],
+        ]),
         size: size_of::<SimpleUnion>(),
         alignment: align_of::<SimpleUnion>(),
+        needs_drop: false,
     });
     assert_eq!(EXPECTED_UNION, SimpleUnion::TYPE_INFO);
     assert_eq!(
@@ -66,3 +70,41 @@ fn test_union_types() {
         }
     );
 }
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Tagged {
+    tag: u8,
+    value: SimpleUnion,
+}
+
+#[test]
+fn leaf_fields() {
+    use static_reflect::types::leaves::LeafOptions;
+    // Unions are skipped by default
+    let leaves = Tagged::TYPE_INFO
+        .leaf_fields()
+        .map(|leaf| leaf.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(leaves, ["tag @ 0: u8"]);
+    let base = std::mem::offset_of!(Tagged, value);
+    let leaves = Tagged::TYPE_INFO
+        .leaf_fields_with(LeafOptions {
+            expand_unions: true,
+        })
+        .map(|leaf| leaf.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        leaves,
+        [
+            "tag @ 0: u8".to_string(),
+            format!("value.text @ {}: *mut void", base),
+            format!("value.b @ {}: bool", base),
+            format!("value.f @ {}: f32", base),
+            format!("value.nested.float @ {}: f64", base),
+            format!("value.nested.number @ {}: u64", base + 8),
+        ]
+    );
+}
+
+assert_fully_const!(Nested, SimpleUnion, Tagged);
@@ -0,0 +1,166 @@
+//! Integer newtypes with a semantic unit (`#[reflect(newtype_unit)]`)
+use static_reflect::funcs::coerce::{CoercionRules, RustType};
+use static_reflect::funcs::marshal::{marshal_args_with, MarshalError, ToFfi};
+use static_reflect::funcs::SignatureDef;
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::{IntType, TypeInfo};
+use static_reflect::{reflect_func, FieldReflect, StaticReflect};
+
+#[derive(StaticReflect, Copy, Clone, Debug, PartialEq)]
+#[reflect(newtype_unit = "milliseconds")]
+#[repr(transparent)]
+pub struct TimeoutMs(u32);
+
+/// The unit defaults to the name of the type
+#[derive(StaticReflect, Copy, Clone, Debug, PartialEq)]
+#[reflect(newtype_unit)]
+#[repr(transparent)]
+pub struct FdHandle(u32);
+
+impl ToFfi for TimeoutMs {
+    type Ffi = TimeoutMs;
+    fn to_ffi(&self) -> TimeoutMs {
+        *self
+    }
+}
+impl ToFfi for FdHandle {
+    type Ffi = FdHandle;
+    fn to_ffi(&self) -> FdHandle {
+        *self
+    }
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct PollRequest {
+    fd: FdHandle,
+    timeout: TimeoutMs,
+    #[reflect(unit = "events")]
+    max_events: u16,
+    flags: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Unitless {
+    first: u32,
+    second: u64,
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn wait_for(fd: FdHandle, timeout: TimeoutMs) -> i32 {
+    let _ = (fd, timeout);
+    0
+}
+
+#[no_mangle]
+#[reflect_func]
+extern "C" fn sleep_for(#[reflect(unit = "seconds")] duration: u32, flags: u32) {
+    let _ = (duration, flags);
+}
+
+#[test]
+fn newtype_reflects_as_integer() {
+    assert_eq!(TimeoutMs::TYPE_INFO, u32::TYPE_INFO);
+    assert_eq!(FdHandle::TYPE_INFO, TypeInfo::Integer(IntType::U32));
+    assert_eq!(TimeoutMs::UNIT, Some("milliseconds"));
+    assert_eq!(FdHandle::UNIT, Some("FdHandle"));
+    assert_eq!(u32::UNIT, None);
+}
+
+#[test]
+fn field_units() {
+    assert_eq!(
+        PollRequest::FIELD_UNITS,
+        [Some("FdHandle"), Some("milliseconds"), Some("events"), None]
+    );
+    // Listed only if some field has a unit
+    assert!(Unitless::FIELD_UNITS.is_empty());
+}
+
+#[test]
+fn argument_units() {
+    let signature = &_FUNC_wait_for.signature;
+    assert_eq!(signature.argument_types, [u32::TYPE_INFO, u32::TYPE_INFO]);
+    assert_eq!(
+        signature.argument_units,
+        [Some("FdHandle"), Some("milliseconds")]
+    );
+    assert_eq!(signature.unit(1), Some("milliseconds"));
+    assert_eq!(signature.unit(2), None);
+    assert_eq!(
+        _FUNC_sleep_for.signature.argument_units,
+        [Some("seconds"), None]
+    );
+    assert_eq!(
+        _FUNC_wait_for.to_string(),
+        format!(
+            "fn wait_for(u32 [FdHandle], u32 [milliseconds]) -> i32 (declared at {})",
+            _FUNC_wait_for.source.unwrap()
+        )
+    );
+}
+
+#[test]
+fn anonymous_signature_units() {
+    let expected = SignatureDef::of::<i32, (FdHandle, TimeoutMs)>();
+    assert_eq!(
+        expected.argument_units,
+        _FUNC_wait_for.signature.argument_units
+    );
+    assert_eq!(expected, _FUNC_wait_for.signature);
+    // Units are informational, so they don't affect whether the signatures match
+    let raw = SignatureDef::of::<i32, (u32, u32)>();
+    assert!(raw.argument_units.is_empty());
+    assert!(_FUNC_wait_for.signature_matches(&raw));
+}
+
+#[test]
+fn unit_strict_signature_check() {
+    let signature = &_FUNC_wait_for.signature;
+    let swapped = [RustType::of::<TimeoutMs>(), RustType::of::<FdHandle>()];
+    let correct = [RustType::of::<FdHandle>(), RustType::of::<TimeoutMs>()];
+    let unit_strict = CoercionRules {
+        compare_units: true,
+        ..CoercionRules::STRICT
+    };
+    // Both are `u32` at the ABI level, so the default check can't tell them apart
+    assert!(signature.matches(&swapped));
+    assert!(signature.matches_with(&swapped, &CoercionRules::default()));
+    assert!(!signature.matches_with(&swapped, &unit_strict));
+    assert!(signature.matches_with(&correct, &unit_strict));
+    // A plain integer doesn't have a unit
+    let plain = [RustType::of::<u32>(), RustType::of::<TimeoutMs>()];
+    assert!(signature.matches(&plain));
+    assert!(!signature.matches_with(&plain, &unit_strict));
+}
+
+#[test]
+fn unit_strict_marshalling() {
+    let signature = &_FUNC_wait_for.signature;
+    let (fd, timeout) = (FdHandle(3), TimeoutMs(500));
+    let unit_strict = CoercionRules {
+        compare_units: true,
+        ..CoercionRules::STRICT
+    };
+    assert!(marshal_args_with(signature, &[&timeout, &fd], &CoercionRules::STRICT).is_ok());
+    let error = marshal_args_with(signature, &[&timeout, &fd], &unit_strict)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        MarshalError::ArgumentUnit {
+            index: 0,
+            expected: Some("FdHandle"),
+            actual: Some("milliseconds"),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Expected argument 0 to be in FdHandle, but got milliseconds"
+    );
+    assert!(marshal_args_with(signature, &[&fd, &timeout], &unit_strict).is_ok());
+}
+
+assert_fully_const!(TimeoutMs, FdHandle, PollRequest, Unitless);
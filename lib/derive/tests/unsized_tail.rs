@@ -0,0 +1,161 @@
+//! Structures ending in an array whose real length is only known at runtime
+use static_reflect::macros::{assert_fully_const, generate_layout_tests};
+use static_reflect::types::ops::dynamic_size_of;
+use static_reflect::types::{DynamicSize, IntType, StructureDef, TypeId, TypeInfo};
+use static_reflect::{FieldReflect, StaticReflect};
+
+/// The tail starts at offset 6, but rustc pads the whole struct to 8 bytes
+#[derive(StaticReflect)]
+#[reflect(unsized_tail)]
+#[repr(C)]
+struct ZeroTail {
+    len: u32,
+    tag: u8,
+    data: [u16; 0],
+}
+
+/// A tail declared with a single element, like a C flexible array member
+#[derive(StaticReflect)]
+#[reflect(unsized_tail)]
+#[repr(C)]
+struct OpaqueTail {
+    count: u16,
+    #[reflect(opaque_array)]
+    items: [u32; 1],
+}
+
+/// A header holding the number of elements in its tail
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(len = "count"))]
+#[repr(C)]
+struct Message {
+    tag: u8,
+    count: u16,
+    payload: [u64; 0],
+}
+
+/// The length is stored in big-endian byte order
+#[derive(StaticReflect)]
+#[reflect(unsized_tail(len = "count"))]
+#[repr(C)]
+struct BigEndianMessage {
+    #[reflect(endian = "big")]
+    count: i32,
+    flags: u8,
+    payload: [u16; 0],
+}
+
+generate_layout_tests!(ZeroTail { len, tag, data }, OpaqueTail { count, items });
+
+fn structure(info: &TypeInfo) -> &'static StructureDef {
+    match *info {
+        TypeInfo::Structure(def) => def,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn zero_tail() {
+    let def = structure(&ZeroTail::TYPE_INFO);
+    assert!(def.has_unsized_tail);
+    assert_eq!(std::mem::size_of::<ZeroTail>(), 8);
+    assert_eq!(def.size, 6);
+    assert_eq!(ZeroTail::TYPE_INFO.size(), 6);
+    assert_eq!(def.alignment, 4);
+    assert_eq!(ZeroTail::NAMED_FIELD_INFO.len.offset, 0);
+    assert_eq!(ZeroTail::NAMED_FIELD_INFO.tag.offset, 4);
+    let data = ZeroTail::NAMED_FIELD_INFO.data;
+    assert_eq!(data.offset, 6);
+    assert!(data.opaque_array);
+    assert_eq!(data.value_type, TypeId::<u16>::get());
+}
+
+#[test]
+fn opaque_tail() {
+    let def = structure(&OpaqueTail::TYPE_INFO);
+    assert!(def.has_unsized_tail);
+    assert_eq!(std::mem::size_of::<OpaqueTail>(), 8);
+    assert_eq!(OpaqueTail::TYPE_INFO.size(), 4);
+    assert_eq!(OpaqueTail::NAMED_FIELD_INFO.count.offset, 0);
+    let items = OpaqueTail::NAMED_FIELD_INFO.items;
+    assert_eq!(items.offset, 4);
+    assert!(items.opaque_array);
+    assert_eq!(items.value_type, TypeId::<u32>::get());
+}
+
+/// The size of a Rust DST with the same header and `len` elements in its tail
+fn expected_size(tail_offset: usize, element_size: usize, align: usize, len: usize) -> usize {
+    (tail_offset + len * element_size).next_multiple_of(align)
+}
+
+#[test]
+fn dynamic_size() {
+    assert_eq!(<ZeroTail as FieldReflect>::DYNAMIC_SIZE, None);
+    let dynamic = <Message as FieldReflect>::DYNAMIC_SIZE.unwrap();
+    assert_eq!(
+        dynamic,
+        DynamicSize {
+            len_field_index: 1,
+            element: &TypeInfo::Integer(IntType::U64),
+        }
+    );
+    let def = structure(&Message::TYPE_INFO);
+    for count in [0u16, 1, 2, 7, 1000] {
+        let header = Message {
+            tag: 0xFF,
+            count,
+            payload: [],
+        };
+        let size =
+            unsafe { dynamic_size_of(&header as *const Message as *const u8, def, &dynamic) };
+        assert_eq!(
+            size,
+            Some(expected_size(
+                std::mem::offset_of!(Message, payload),
+                8,
+                std::mem::align_of::<Message>(),
+                count as usize
+            )),
+            "count = {}",
+            count
+        );
+    }
+}
+
+#[test]
+fn dynamic_size_endian() {
+    let dynamic = <BigEndianMessage as FieldReflect>::DYNAMIC_SIZE.unwrap();
+    assert_eq!(dynamic.len_field_index, 0);
+    let def = structure(&BigEndianMessage::TYPE_INFO);
+    let size_of = |count: i32| {
+        let header = BigEndianMessage {
+            count: count.to_be(),
+            flags: 0,
+            payload: [],
+        };
+        unsafe {
+            dynamic_size_of(
+                &header as *const BigEndianMessage as *const u8,
+                def,
+                &dynamic,
+            )
+        }
+    };
+    for count in [0, 1, 3, 256, 65537] {
+        assert_eq!(
+            size_of(count),
+            Some(expected_size(
+                std::mem::offset_of!(BigEndianMessage, payload),
+                2,
+                std::mem::align_of::<BigEndianMessage>(),
+                count as usize
+            )),
+            "count = {}",
+            count
+        );
+    }
+    // A negative length isn't valid
+    assert_eq!(size_of(-1), None);
+}
+
+assert_fully_const!(ZeroTail, OpaqueTail, Message, BigEndianMessage);
@@ -0,0 +1,138 @@
+//! Swapping and copying values that are only known through their `TypeInfo`
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::ops::{copy_value, swap_values, ValueOpError};
+use static_reflect::StaticReflect;
+
+#[derive(Copy, Clone, Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Point {
+    x: u32,
+    y: u64,
+    label: u8,
+}
+
+#[derive(Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Resource {
+    handle: u32,
+}
+impl Drop for Resource {
+    fn drop(&mut self) {}
+}
+
+fn ptr<T>(value: &mut T) -> *mut u8 {
+    value as *mut T as *mut u8
+}
+
+#[test]
+fn swap() {
+    let mut first = Point {
+        x: 1,
+        y: 2,
+        label: 3,
+    };
+    let mut second = Point {
+        x: 4,
+        y: 5,
+        label: 6,
+    };
+    unsafe { swap_values(ptr(&mut first), ptr(&mut second), &Point::TYPE_INFO) }.unwrap();
+    assert_eq!(
+        first,
+        Point {
+            x: 4,
+            y: 5,
+            label: 6
+        }
+    );
+    assert_eq!(
+        second,
+        Point {
+            x: 1,
+            y: 2,
+            label: 3
+        }
+    );
+    // Swapping a value with itself does nothing
+    unsafe { swap_values(ptr(&mut first), ptr(&mut first), &Point::TYPE_INFO) }.unwrap();
+    assert_eq!(
+        first,
+        Point {
+            x: 4,
+            y: 5,
+            label: 6
+        }
+    );
+}
+
+#[test]
+fn copy() {
+    let mut src = Point {
+        x: 7,
+        y: 8,
+        label: 9,
+    };
+    let mut dst = Point {
+        x: 0,
+        y: 0,
+        label: 0,
+    };
+    unsafe { copy_value(ptr(&mut src), ptr(&mut dst), &Point::TYPE_INFO) }.unwrap();
+    assert_eq!(dst, src);
+    let mut resource = Resource { handle: 1 };
+    let mut other = Resource { handle: 2 };
+    assert_eq!(
+        unsafe { copy_value(ptr(&mut resource), ptr(&mut other), &Resource::TYPE_INFO) },
+        Err(ValueOpError::NeedsDrop {
+            type_name: "Resource".into()
+        })
+    );
+    // Swapping is fine, since it doesn't duplicate anything
+    unsafe { swap_values(ptr(&mut resource), ptr(&mut other), &Resource::TYPE_INFO) }.unwrap();
+    assert_eq!((resource.handle, other.handle), (2, 1));
+}
+
+#[test]
+fn misaligned() {
+    let mut buffer = [0u64; 6];
+    let base = ptr(&mut buffer);
+    let err = unsafe { swap_values(base.add(4), base.add(24), &Point::TYPE_INFO) }.unwrap_err();
+    assert_eq!(
+        err,
+        ValueOpError::Misaligned {
+            required: 8,
+            actual: 4
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Pointer is aligned to 4 bytes, but the type requires 8"
+    );
+    assert!(matches!(
+        unsafe { copy_value(base, base.add(25), &Point::TYPE_INFO) },
+        Err(ValueOpError::Misaligned {
+            required: 8,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn overlapping() {
+    let mut buffer = [0u64; 6];
+    let base = ptr(&mut buffer);
+    // The values are 24 bytes, so these overlap by 16 bytes
+    let overlapping = Err(ValueOpError::Overlapping { size: 24 });
+    assert_eq!(
+        unsafe { swap_values(base, base.add(8), &Point::TYPE_INFO) },
+        overlapping
+    );
+    assert_eq!(
+        unsafe { copy_value(base.add(16), base, &Point::TYPE_INFO) },
+        overlapping
+    );
+    // Adjacent values are fine
+    unsafe { swap_values(base, base.add(24), &Point::TYPE_INFO) }.unwrap();
+}
+
+assert_fully_const!(Point, Resource);
@@ -0,0 +1,283 @@
+//! Checking manual implementations of `StaticReflect` against the real layout
+use static_reflect::macros::{assert_fully_const, assert_layout};
+use static_reflect::types::validate::EnumDefError;
+use static_reflect::types::{
+    CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, IntType, TypeId, UnionFieldDef,
+    UnionFields, UntaggedUnionDef,
+};
+use static_reflect::verify::{verify_layout, LayoutMismatch, MismatchKind};
+use static_reflect::{StaticReflect, TypeInfo};
+
+mod common;
+use common::{field, structure};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Derived {
+    first: u8,
+    second: u32,
+    nested: Inner,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Inner {
+    value: u16,
+}
+
+#[derive(StaticReflect)]
+#[repr(u16)]
+#[allow(dead_code)]
+enum DerivedEnum {
+    First,
+    Second,
+}
+
+/// Claims to be 4 bytes, but is actually 8
+#[repr(C)]
+#[allow(dead_code)]
+struct WrongSize(u32, u32);
+unsafe impl StaticReflect for WrongSize {
+    const TYPE_INFO: TypeInfo = TypeInfo::Integer(IntType::U32);
+}
+
+/// The fields are correctly sized, but both claim offset zero
+#[repr(C)]
+#[allow(dead_code)]
+struct Overlapping {
+    x: u32,
+    y: u32,
+}
+unsafe impl StaticReflect for Overlapping {
+    const TYPE_INFO: TypeInfo = TypeInfo::Structure(&structure(
+        "Manual",
+        &[field::<u32>("x", 0, 0), field::<u32>("y", 0, 1)],
+        8,
+        4,
+    ));
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct OutOfBounds {
+    x: u32,
+    y: u32,
+}
+unsafe impl StaticReflect for OutOfBounds {
+    const TYPE_INFO: TypeInfo = TypeInfo::Structure(&structure(
+        "Manual",
+        &[field::<u32>("x", 0, 0), field::<u32>("y", 8, 1)],
+        8,
+        4,
+    ));
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct Misaligned {
+    x: u32,
+    y: u32,
+}
+unsafe impl StaticReflect for Misaligned {
+    const TYPE_INFO: TypeInfo = TypeInfo::Structure(&structure(
+        "Manual",
+        &[field::<u32>("x", 0, 0), field::<u32>("y", 2, 1)],
+        8,
+        4,
+    ));
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+union TooSmall {
+    small: u16,
+    large: u32,
+}
+unsafe impl StaticReflect for TooSmall {
+    const TYPE_INFO: TypeInfo = TypeInfo::UntaggedUnion(&UntaggedUnionDef {
+        name: "TooSmall",
+        fields: UnionFields::Eager(&[
+            UnionFieldDef {
+                name: "small",
+                value_type: TypeId::<u16>::get().erase(),
+                index: 0,
+            },
+            UnionFieldDef {
+                name: "large",
+                value_type: TypeId::<u32>::get().erase(),
+                index: 1,
+            },
+        ]),
+        // Forgot about the larger field
+        size: 2,
+        alignment: 4,
+        needs_drop: false,
+    });
+}
+
+#[repr(u32)]
+#[allow(dead_code)]
+enum WideEnum {
+    First,
+}
+unsafe impl StaticReflect for WideEnum {
+    const TYPE_INFO: TypeInfo = TypeInfo::CStyleEnum(
+        &CStyleEnumDef {
+            name: "WideEnum",
+            discriminant: IntType::U8,
+            variants: &[CStyleEnumVariant {
+                name: "First",
+                index: 0,
+                discriminant: DiscriminantValue::Default {
+                    declaration_index: 0,
+                },
+            }],
+            valid_range: None,
+            has_gaps: false,
+        }
+        .compute_flags(),
+    );
+}
+
+#[repr(u8)]
+#[allow(dead_code)]
+enum DuplicateEnum {
+    First,
+    Second,
+}
+unsafe impl StaticReflect for DuplicateEnum {
+    const TYPE_INFO: TypeInfo = TypeInfo::CStyleEnum(
+        &CStyleEnumDef {
+            name: "DuplicateEnum",
+            discriminant: IntType::U8,
+            variants: &[
+                CStyleEnumVariant {
+                    name: "First",
+                    index: 0,
+                    discriminant: DiscriminantValue::Default {
+                        declaration_index: 0,
+                    },
+                },
+                CStyleEnumVariant {
+                    name: "Second",
+                    index: 1,
+                    discriminant: DiscriminantValue::Default {
+                        declaration_index: 0,
+                    },
+                },
+            ],
+            valid_range: None,
+            has_gaps: false,
+        }
+        .compute_flags(),
+    );
+}
+
+/// Contains a nested structure with overlapping fields
+#[repr(C)]
+#[allow(dead_code)]
+struct Outer {
+    inner: Overlapping,
+}
+unsafe impl StaticReflect for Outer {
+    const TYPE_INFO: TypeInfo = TypeInfo::Structure(&structure(
+        "Outer",
+        &[field::<Overlapping>("inner", 0, 0)],
+        8,
+        4,
+    ));
+}
+
+fn mismatch_kind<T: StaticReflect>() -> MismatchKind {
+    verify_layout::<T>().unwrap_err().kind
+}
+
+#[test]
+fn derived_types_are_valid() {
+    assert_eq!(verify_layout::<Derived>(), Ok(()));
+    assert_eq!(verify_layout::<DerivedEnum>(), Ok(()));
+    assert_layout!(u8, bool, (), f64, *mut u8, Derived, Inner, DerivedEnum);
+}
+
+#[test]
+fn size_and_alignment() {
+    assert_eq!(
+        verify_layout::<WrongSize>(),
+        Err(LayoutMismatch {
+            path: "u32".into(),
+            kind: MismatchKind::Size {
+                reflected: 4,
+                actual: 8
+            },
+        })
+    );
+    assert_eq!(
+        mismatch_kind::<WideEnum>(),
+        MismatchKind::DiscriminantWidth {
+            reflected: 1,
+            actual: 4
+        }
+    );
+}
+
+#[test]
+fn structure_fields() {
+    assert_eq!(
+        mismatch_kind::<Overlapping>(),
+        MismatchKind::OverlappingFields {
+            first: "x".into(),
+            second: "y".into()
+        }
+    );
+    assert_eq!(
+        mismatch_kind::<OutOfBounds>(),
+        MismatchKind::FieldOutOfBounds {
+            field: "y".into(),
+            end: 12,
+            size: 8
+        }
+    );
+    assert_eq!(
+        mismatch_kind::<Misaligned>(),
+        MismatchKind::MisalignedField {
+            field: "y".into(),
+            offset: 2,
+            alignment: 4
+        }
+    );
+    let nested = verify_layout::<Outer>().unwrap_err();
+    assert_eq!(nested.path, "Outer.inner");
+    assert_eq!(nested.to_string(), "Outer.inner: Fields x and y overlap");
+}
+
+#[test]
+fn unions_and_enums() {
+    assert_eq!(
+        verify_layout::<TooSmall>(),
+        Err(LayoutMismatch {
+            path: "TooSmall".into(),
+            kind: MismatchKind::UnionFieldTooLarge {
+                field: "large".into(),
+                field_size: 4,
+                union_size: 2
+            }
+        })
+    );
+    assert_eq!(
+        mismatch_kind::<DuplicateEnum>(),
+        MismatchKind::InvalidEnum(EnumDefError::DuplicateDiscriminant {
+            first: 0,
+            second: 1
+        })
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid layout for WrongSize: u32: Reflected size 4 doesn't match the real size 8"
+)]
+fn assert_layout_panics() {
+    assert_layout!(WrongSize);
+}
+
+assert_fully_const!(Derived, Inner, DerivedEnum);
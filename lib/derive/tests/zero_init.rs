@@ -0,0 +1,151 @@
+use std::mem::MaybeUninit;
+use std::num::NonZeroU32;
+use std::ptr::NonNull;
+
+use static_reflect::builtins::AsmOption;
+use static_reflect::macros::assert_fully_const;
+use static_reflect::types::{zero_init, IntType, NicheDef, TypeInfo, ZeroInitError};
+use static_reflect::StaticReflect;
+
+#[derive(Debug, PartialEq, StaticReflect)]
+#[repr(C)]
+struct Plain {
+    flag: bool,
+    small: u8,
+    big: u64,
+    float: f64,
+    ptr: *mut Plain,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WithNonZero {
+    first: u32,
+    id: NonZeroU32,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct WithNonNull {
+    ptr: NonNull<u8>,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Nested {
+    plain: Plain,
+    inner: WithNonNull,
+}
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Nullable {
+    id: Option<NonZeroU32>,
+    ptr: Option<NonNull<u8>>,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum StartsAtZero {
+    First,
+    Second,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum StartsAtOne {
+    First = 1,
+    Second,
+}
+
+#[test]
+fn zero_initializable() {
+    assert!(Plain::TYPE_INFO.is_zero_initializable());
+    assert!(Nullable::TYPE_INFO.is_zero_initializable());
+    assert!(StartsAtZero::TYPE_INFO.is_zero_initializable());
+    assert!(!StartsAtOne::TYPE_INFO.is_zero_initializable());
+    assert!(!WithNonZero::TYPE_INFO.is_zero_initializable());
+    assert!(!WithNonNull::TYPE_INFO.is_zero_initializable());
+    assert!(!Nested::TYPE_INFO.is_zero_initializable());
+}
+
+#[test]
+fn non_zero_recorded() {
+    assert_eq!(
+        NonZeroU32::TYPE_INFO,
+        TypeInfo::NonZero(&TypeInfo::Integer(IntType::U32))
+    );
+    assert_eq!(
+        NonNull::<u8>::TYPE_INFO,
+        TypeInfo::NonZero(&TypeInfo::MUT_POINTER)
+    );
+    // Optionals may be null again, which is recorded as a niche
+    const OPTIONAL_COUNT: TypeInfo = TypeInfo::Niche(&NicheDef::option(&NonZeroU32::TYPE_INFO));
+    assert_eq!(Option::<NonZeroU32>::TYPE_INFO, OPTIONAL_COUNT);
+    assert_eq!(Option::<NonZeroU32>::TYPE_INFO.nullable(), u32::TYPE_INFO);
+    assert_eq!(
+        Option::<NonNull<u8>>::TYPE_INFO.nullable(),
+        TypeInfo::MUT_POINTER
+    );
+    assert_eq!(NonZeroU32::TYPE_INFO.size(), 4);
+}
+
+#[test]
+fn zero_init_plain() {
+    let mut value = MaybeUninit::<Plain>::uninit();
+    unsafe {
+        zero_init(value.as_mut_ptr() as *mut u8, &Plain::TYPE_INFO).unwrap();
+        assert_eq!(
+            value.assume_init(),
+            Plain {
+                flag: false,
+                small: 0,
+                big: 0,
+                float: 0.0,
+                ptr: std::ptr::null_mut(),
+            }
+        );
+    }
+}
+
+#[test]
+fn zero_init_rejected() {
+    let mut value = MaybeUninit::<WithNonZero>::uninit();
+    let ptr = value.as_mut_ptr() as *mut u8;
+    unsafe { ptr.write_bytes(0xFF, std::mem::size_of::<WithNonZero>()) };
+    assert_eq!(
+        unsafe { zero_init(ptr, &WithNonZero::TYPE_INFO) },
+        Err(ZeroInitError {
+            type_name: "WithNonZero".into()
+        })
+    );
+    // Nothing was written
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, std::mem::size_of::<WithNonZero>()) };
+    assert!(bytes.iter().all(|&b| b == 0xFF));
+}
+
+#[test]
+fn zero_init_unknown_size() {
+    // Absent optionals are zero, but their layout isn't computed yet
+    let info = <AsmOption<u32>>::TYPE_INFO;
+    assert!(info.is_zero_initializable());
+    let mut value = MaybeUninit::<AsmOption<u32>>::uninit();
+    assert_eq!(
+        unsafe { zero_init(value.as_mut_ptr() as *mut u8, &info) },
+        Err(ZeroInitError {
+            type_name: info.to_string()
+        })
+    );
+}
+
+assert_fully_const!(
+    Plain,
+    WithNonZero,
+    WithNonNull,
+    Nested,
+    Nullable,
+    StartsAtZero,
+    StartsAtOne,
+);
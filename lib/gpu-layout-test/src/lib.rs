@@ -0,0 +1,73 @@
+//! An example of a third-party derive, which uses `static_reflect_internals::analyze_struct`
+//! to agree with the layout reflected by `#[derive(StaticReflect)]`
+//!
+//! `#[derive(GpuLayout)]` generates a `GPU_OFFSETS` constant with the offset of each field
+//! (followed by the end of the last field), and a `gpu_declaration()` method
+//! declaring the struct in a C-like shading language.
+//! It understands the same `#[reflect(...)]` options as the derive.
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, DeriveInput, Type};
+
+use static_reflect_internals::analyze_struct;
+
+#[proc_macro_derive(GpuLayout, attributes(reflect))]
+pub fn derive_gpu_layout(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match gpu_layout(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn gpu_layout(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let analyzed = analyze_struct(input)?;
+    let name = analyzed.name;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let offsets = analyzed.offsets_expr();
+    let offsets_len = analyzed.fields.len() + 1;
+    let members = analyzed.fields.iter().map(|field| {
+        let member = match field.reflected_name {
+            Some(ref name) => name.clone(),
+            None => format!("_{}", field.index),
+        };
+        // The unsized tail (or opaque array) becomes a flexible array member
+        let suffix = if field.opaque_array { "[]" } else { "" };
+        let declaration = format!("{} {}{};", gpu_type(&field.reflected_type), member, suffix);
+        let index = field.index;
+        quote!(
+            __gpu_declaration.push_str(&format!(
+                "    {} // offset {}\n",
+                #declaration,
+                Self::GPU_OFFSETS[#index]
+            ));
+        )
+    });
+    let header = format!("struct {} {{\n", name);
+    Ok(quote!(
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The offset of each field, followed by the end of the last field
+            pub const GPU_OFFSETS: [usize; #offsets_len] = #offsets;
+            /// Declare the struct in a C-like shading language
+            pub fn gpu_declaration() -> String {
+                let mut __gpu_declaration = String::from(#header);
+                #(#members)*
+                __gpu_declaration.push('}');
+                __gpu_declaration
+            }
+        }
+    ))
+}
+
+/// The name of a type in the shading language
+fn gpu_type(ty: &Type) -> String {
+    let name = ty.to_token_stream().to_string();
+    match &*name {
+        "f32" => "float".into(),
+        "f64" => "double".into(),
+        "u32" => "uint".into(),
+        "i32" => "int".into(),
+        "bool" => "bool".into(),
+        _ => name,
+    }
+}
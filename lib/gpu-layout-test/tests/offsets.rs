@@ -0,0 +1,80 @@
+//! The offsets computed by a third-party derive match the ones reflected by `static_reflect`
+use static_reflect::types::TypeInfo;
+use static_reflect::StaticReflect;
+use static_reflect_gpu_layout_test::GpuLayout;
+
+#[derive(StaticReflect, GpuLayout)]
+#[repr(C)]
+struct Light {
+    enabled: bool,
+    radius: f32,
+    height: f32,
+    #[reflect(rename = "strength")]
+    intensity: f64,
+    color: u32,
+}
+
+#[derive(StaticReflect, GpuLayout)]
+#[repr(C, align(16))]
+struct Aligned(u8, f32);
+
+/// A field whose representation is assumed, and an unsized tail
+#[derive(StaticReflect, GpuLayout)]
+#[reflect(unsized_tail(len = "count"))]
+#[repr(C)]
+struct Buffer {
+    #[reflect(assume_repr = "u32")]
+    flags: Flags,
+    count: u16,
+    data: [u64; 0],
+}
+
+#[repr(transparent)]
+struct Flags(#[allow(dead_code)] u32);
+
+/// The offsets of the fields, as reflected by `static_reflect`
+fn reflected_offsets(info: &TypeInfo) -> Vec<usize> {
+    match *info {
+        TypeInfo::Structure(def) => def.fields.iter().map(|field| field.offset).collect(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn matching_offsets() {
+    let cases: [(&TypeInfo, &[usize]); 3] = [
+        (&Light::TYPE_INFO, &Light::GPU_OFFSETS),
+        (&Aligned::TYPE_INFO, &Aligned::GPU_OFFSETS),
+        (&Buffer::TYPE_INFO, &Buffer::GPU_OFFSETS),
+    ];
+    for (info, offsets) in cases {
+        let (_end, fields) = offsets.split_last().unwrap();
+        assert_eq!(fields, reflected_offsets(info), "{}", info);
+    }
+    assert_eq!(Light::GPU_OFFSETS, [0, 4, 8, 16, 24, 28]);
+    assert_eq!(Aligned::GPU_OFFSETS, [0, 4, 8]);
+    // With an unsized tail, only the header is reflected
+    assert_eq!(Buffer::TYPE_INFO.size(), Buffer::GPU_OFFSETS[2]);
+}
+
+#[test]
+fn declarations() {
+    assert_eq!(
+        Light::gpu_declaration(),
+        "struct Light {
+    bool enabled; // offset 0
+    float radius; // offset 4
+    float height; // offset 8
+    double strength; // offset 16
+    uint color; // offset 24
+}"
+    );
+    assert_eq!(
+        Buffer::gpu_declaration(),
+        "struct Buffer {
+    uint flags; // offset 0
+    u16 count; // offset 4
+    u64 data[]; // offset 8
+}"
+    );
+}
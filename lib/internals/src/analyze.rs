@@ -0,0 +1,264 @@
+//! The fields of a struct, as they're reflected by the derive
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DataStruct, DeriveInput, Field, Type};
+
+use crate::options::{DeriveContainerOptions, DeriveFieldOptions};
+use crate::repr::{determine_align, determine_repr, Repr};
+use crate::{erase_lifetimes, ident_name};
+
+/// A struct whose options and fields have been resolved
+///
+/// See [analyze_struct].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AnalyzedStruct<'a> {
+    /// The name of the struct
+    pub name: &'a Ident,
+    /// The original definition of the struct
+    pub data: &'a DataStruct,
+    /// The representation of the struct, which is currently always [Repr::C]
+    pub repr: Repr,
+    /// The minimum alignment from `#[repr(align(N))]`, if any
+    pub align: Option<usize>,
+    /// The `#[reflect(...)]` options of the struct
+    pub options: DeriveContainerOptions,
+    /// The index of the field holding the length of the unsized tail
+    /// (`#[reflect(unsized_tail(len = "field"))]`), if any
+    pub len_field: Option<usize>,
+    /// The fields of the struct, in declaration order
+    pub fields: Vec<AnalyzedField<'a>>,
+}
+impl AnalyzedStruct<'_> {
+    /// If the struct is a tuple struct, whose fields are accessed by index
+    pub fn is_tuple_style(&self) -> bool {
+        matches!(self.data.fields, syn::Fields::Unnamed(_))
+    }
+    /// The size and alignment of each field (as a `(usize, usize)` tuple),
+    /// computed from its declared type
+    pub fn field_layouts(&self) -> Vec<TokenStream> {
+        self.fields
+            .iter()
+            .map(|field| {
+                /*
+                 * NOTE: Must use the declared type instead of the reflected one
+                 * There is a chance an option like #[opaque_array] changed the size
+                 */
+                let declared_type = &field.declared_type;
+                quote!((
+                    ::core::mem::size_of::<#declared_type>(),
+                    ::core::mem::align_of::<#declared_type>(),
+                ))
+            })
+            .collect()
+    }
+    /// A constant expression computing the offset of each field,
+    /// followed by the end of the last field (so its type is `[usize; N + 1]`)
+    ///
+    /// This only refers to `::core`, so it can be used by any crate.
+    /// It may refer to the type parameters of the struct,
+    /// in which case it must be evaluated where they're in scope
+    /// (for example, as an associated constant).
+    ///
+    /// With an unsized tail, the reflected size of the struct is the offset of the last field.
+    /// Otherwise, it's the end of the last field rounded up to the alignment.
+    pub fn offsets_expr(&self) -> TokenStream {
+        /*
+         * NOTE: Layout algorithm for repr(C) given in reference
+         * https://doc.rust-lang.org/reference/type-layout.html#reprc-structs
+         */
+        let field_layouts = self.field_layouts();
+        let len = Literal::usize_unsuffixed(field_layouts.len());
+        let offsets_len = Literal::usize_unsuffixed(field_layouts.len() + 1);
+        quote!({
+            let layouts: [(usize, usize); #len] = [#(#field_layouts),*];
+            let mut offsets = [0; #offsets_len];
+            let mut offset = 0;
+            // NOTE: Can't use for-loop since iterators aren't const
+            let mut index = 0;
+            while index < layouts.len() {
+                let (size, align) = layouts[index];
+                // Pad the offset to a multiple of the field's alignment
+                let rem = offset % align;
+                if rem != 0 {
+                    offset += align - rem;
+                }
+                offsets[index] = offset;
+                offset += size;
+                index += 1;
+            }
+            offsets[index] = offset;
+            offsets
+        })
+    }
+}
+
+/// A field of an [AnalyzedStruct]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AnalyzedField<'a> {
+    /// The original definition of the field
+    pub field: &'a Field,
+    /// The index of the field, in declaration order
+    pub index: usize,
+    /// The name recorded in the field's definition (after renaming),
+    /// or `None` for tuple fields
+    pub reflected_name: Option<String>,
+    /// The type of the field as it's declared, with all its lifetimes replaced by `'static`
+    pub declared_type: Type,
+    /// The type the field is reflected as, after applying `opaque_array` and `assume_repr`
+    pub reflected_type: Type,
+    /// Whether the field is reflected as the element type of an array,
+    /// either because of `opaque_array` or because it's the unsized tail
+    pub opaque_array: bool,
+    /// The `#[reflect(...)]` options of the field
+    pub options: DeriveFieldOptions,
+}
+impl AnalyzedField<'_> {
+    /// The identifier of the field, or `None` for tuple fields
+    pub fn ident(&self) -> Option<&Ident> {
+        self.field.ident.as_ref()
+    }
+    /// The tokens accessing the field (like `value.#access`)
+    pub fn access(&self) -> TokenStream {
+        match self.field.ident {
+            Some(ref ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(self.index);
+                quote!(#index)
+            }
+        }
+    }
+}
+
+/// Resolve the options, types and layout of a struct, exactly like `#[derive(StaticReflect)]`
+///
+/// Fails with the same errors as the derive, if the struct isn't `#[repr(C)]`
+/// or its `#[reflect(...)]` options are invalid.
+/// However, this doesn't check that the fields implement `StaticReflect`
+/// (or that the offsets match), which only happens when the generated code is compiled.
+pub fn analyze_struct(input: &DeriveInput) -> Result<AnalyzedStruct<'_>, syn::Error> {
+    let name = &input.ident;
+    let data = match input.data {
+        Data::Struct(ref data) => data,
+        _ => return Err(syn::Error::new(name.span(), "Only structs can be analyzed")),
+    };
+    let repr = determine_repr(input)?;
+    if repr != Some(Repr::C) {
+        return Err(syn::Error::new(
+            name.span(),
+            "StaticReflect requires repr(C)",
+        ));
+    }
+    let align = determine_align(input)?;
+    let options = DeriveContainerOptions::parse_attrs(&input.attrs)?;
+    if options.unsized_tail && data.fields.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "#[reflect(unsized_tail)] requires a trailing array field",
+        ));
+    }
+    let mut fields = Vec::with_capacity(data.fields.len());
+    for (index, field) in data.fields.iter().enumerate() {
+        fields.push(analyze_field(index, field, data, &options)?);
+    }
+    let len_field = match options.unsized_tail_len {
+        Some(ref len) => {
+            let index = fields
+                .iter()
+                .position(|field| {
+                    field
+                        .ident()
+                        .is_some_and(|ident| ident_name(ident) == len.value())
+                })
+                .ok_or_else(|| syn::Error::new(len.span(), "Unknown length field"))?;
+            if index + 1 == fields.len() {
+                return Err(syn::Error::new(
+                    len.span(),
+                    "The length of an unsized tail must be stored in the header",
+                ));
+            }
+            Some(index)
+        }
+        None => None,
+    };
+    Ok(AnalyzedStruct {
+        name,
+        data,
+        repr: Repr::C,
+        align,
+        options,
+        len_field,
+        fields,
+    })
+}
+
+fn analyze_field<'a>(
+    index: usize,
+    field: &'a Field,
+    data: &DataStruct,
+    container: &DeriveContainerOptions,
+) -> Result<AnalyzedField<'a>, syn::Error> {
+    /*
+     * Fields disabled by `#[cfg(...)]` never reach us, since rustc
+     * strips them before invoking the derive. This means the
+     * offsets (and indices) always match the active configuration.
+     */
+    let is_last = index + 1 == data.fields.len();
+    let options = DeriveFieldOptions::parse_attrs(&field.attrs)?;
+    if let (Some(rename), None) = (&options.rename, &field.ident) {
+        return Err(syn::Error::new(
+            rename.span(),
+            "Tuple fields can't be renamed",
+        ));
+    }
+    let declared_type = erase_lifetimes(&field.ty);
+    let mut reflected_type = declared_type.clone();
+    let mut opaque_array = options.opaque_array;
+    if container.unsized_tail && is_last {
+        if !matches!(declared_type, Type::Array(_)) {
+            return Err(syn::Error::new(
+                field.span(),
+                "The last field of an unsized_tail struct must be an array",
+            ));
+        }
+        // The tail is always treated as an opaque array
+        opaque_array = true;
+    }
+    if opaque_array {
+        if !is_last {
+            return Err(syn::Error::new(
+                field.span(),
+                "Opaque array must be last field",
+            ));
+        }
+        match declared_type {
+            Type::Array(ref array) => {
+                reflected_type = (*array.elem).clone();
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "Type must be an array to be marked 'opaque_array'",
+                ))
+            }
+        }
+    }
+    if let Some(ref assumed_type) = options.assume_repr {
+        reflected_type = erase_lifetimes(assumed_type);
+    }
+    let reflected_name = field.ident.as_ref().map(|ident| match options.rename {
+        Some(ref rename) => rename.value(),
+        None => ident_name(ident),
+    });
+    Ok(AnalyzedField {
+        field,
+        index,
+        reflected_name,
+        declared_type,
+        reflected_type,
+        opaque_array,
+        options,
+    })
+}
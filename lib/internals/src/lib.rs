@@ -0,0 +1,87 @@
+//! The analysis behind `#[derive(StaticReflect)]`, shared with other procedural macros
+//!
+//! Macros which need to agree with the layout reflected by `static-reflect`
+//! (for example, to generate declarations for another language)
+//! should use [analyze_struct] instead of re-implementing it.
+//! It parses the same `#[reflect(...)]` options, applies them the same way,
+//! and computes the same offsets as the derive (which uses it internally).
+//!
+//! ## Stability
+//! This crate is versioned along with `static-reflect-derive`.
+//! [analyze_struct] and the types it returns are meant to be semi-stable:
+//! new options may add fields (the structs are `#[non_exhaustive]`),
+//! but existing fields and methods keep their meaning within a minor version.
+//! Everything else is only public so the derive can use it.
+use proc_macro2::Ident;
+use syn::ext::IdentExt;
+use syn::visit_mut::{self, VisitMut};
+use syn::{BoundLifetimes, GenericParam, Lifetime, TraitBound, Type, TypeBareFn};
+
+pub mod analyze;
+pub mod options;
+pub mod repr;
+
+pub use self::analyze::{analyze_struct, AnalyzedField, AnalyzedStruct};
+
+/// The name of an identifier, as it's reflected at runtime
+///
+/// Raw identifiers are reflected without their `r#` prefix,
+/// so `r#type` is named `type` (just like `#[no_mangle]` would name its symbol).
+/// Non-ASCII identifiers are passed through untouched.
+pub fn ident_name(ident: &Ident) -> String {
+    ident.unraw().to_string()
+}
+
+/// Replace all the lifetimes in a field's type with `'static`
+///
+/// The generated helper items (like the struct holding the `FieldDef`s)
+/// don't have the lifetime parameters of the original type,
+/// and the lifetimes never affect the layout anyways.
+///
+/// Lifetimes bound by `for<'b>` (in function pointers and trait objects) are left alone.
+pub fn erase_lifetimes(ty: &Type) -> Type {
+    struct EraseLifetimes {
+        higher_ranked: Vec<Lifetime>,
+    }
+    impl EraseLifetimes {
+        fn with_bound<F: FnOnce(&mut Self)>(&mut self, bound: Option<&BoundLifetimes>, func: F) {
+            let old_len = self.higher_ranked.len();
+            if let Some(bound) = bound {
+                self.higher_ranked.extend(bound.lifetimes.iter().filter_map(
+                    |param| match *param {
+                        GenericParam::Lifetime(ref param) => Some(param.lifetime.clone()),
+                        _ => None,
+                    },
+                ));
+            }
+            func(self);
+            self.higher_ranked.truncate(old_len);
+        }
+    }
+    impl VisitMut for EraseLifetimes {
+        fn visit_bound_lifetimes_mut(&mut self, _bound: &mut BoundLifetimes) {}
+        fn visit_type_bare_fn_mut(&mut self, node: &mut TypeBareFn) {
+            let bound = node.lifetimes.clone();
+            self.with_bound(bound.as_ref(), |this| {
+                visit_mut::visit_type_bare_fn_mut(this, node)
+            });
+        }
+        fn visit_trait_bound_mut(&mut self, node: &mut TraitBound) {
+            let bound = node.lifetimes.clone();
+            self.with_bound(bound.as_ref(), |this| {
+                visit_mut::visit_trait_bound_mut(this, node)
+            });
+        }
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if !self.higher_ranked.contains(lifetime) {
+                *lifetime = Lifetime::new("'static", lifetime.span());
+            }
+        }
+    }
+    let mut ty = ty.clone();
+    EraseLifetimes {
+        higher_ranked: Vec::new(),
+    }
+    .visit_type_mut(&mut ty);
+    ty
+}
@@ -0,0 +1,444 @@
+//! The `#[reflect(...)]` options of a type, its fields and its variants
+use proc_macro2::{Ident, Span};
+use syn::{Error, Type};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DeriveFieldOptions {
+    /// Treat this array field as opaque and unsized.
+    ///
+    /// For example,
+    /// ````ignore
+    /// struct PyTuple {
+    ///     ob_refcnt: usize,
+    ///     ob_size: usize,
+    ///     #[reflect(opaque_array)]
+    ///     ob_items: [PyObject; 1]
+    /// }
+    /// ````
+    /// The final field will have type `PyObject` instead of `[PyObject; 1]`.
+    /// The actual size won't really be known at compile time,
+    /// so it's up to the user to ensure no attempts are made at stack allocation
+    ///
+    /// The array must be in trailing position
+    pub opaque_array: bool,
+    /// Assume the field has the same underlying representation as the specified type.
+    ///
+    /// Useful if the type is known to be FFI-safe,
+    /// but the field's type doesn't actually implement `StaticReflect`
+    ///
+    /// The size and alignment of the two types are checked at compile time,
+    /// unless the option is given as `assume_repr_unchecked`.
+    pub assume_repr: Option<syn::Type>,
+    /// Skip checking the layout of the `assume_repr` type
+    pub assume_repr_unchecked: bool,
+    /// Override the name of the field recorded in its reflected definition.
+    ///
+    /// This has no effect on the generated `NamedFieldInfo`,
+    /// which is always accessed using the original identifier.
+    pub rename: Option<syn::LitStr>,
+    /// The explicit byte order of the field (either `Big` or `Little`).
+    ///
+    /// Only integer and float fields may specify this.
+    pub endian: Option<Ident>,
+    /// The ownership discipline of a pointer field
+    /// (either `Owned`, `Borrowed` or `Raw`).
+    ///
+    /// Only pointer fields may specify this.
+    pub ptr: Option<Ident>,
+    /// The names of the bits of an integer field used as a set of flags
+    /// (`bitset(names = "...")`).
+    ///
+    /// Only integer fields (or opaque arrays of integers) may specify this,
+    /// with no more names than the field has bits.
+    pub bitset: Option<BitsetNames>,
+    /// The semantic unit of an integer field (`unit = "..."`), like `"milliseconds"`.
+    ///
+    /// Only primitive integer fields may specify this.
+    pub unit: Option<syn::LitStr>,
+}
+/// The names given to the bits of a field by `bitset(names = "A,B,C")`
+#[derive(Debug)]
+pub struct BitsetNames {
+    pub span: Span,
+    pub names: Vec<String>,
+}
+impl BitsetNames {
+    fn parse(value: &syn::LitStr) -> Result<BitsetNames, syn::Error> {
+        let mut names = Vec::new();
+        for name in value.value().split(',').map(str::trim) {
+            if name.is_empty() {
+                return Err(syn::Error::new(value.span(), "Bitset names can't be empty"));
+            }
+            if names.iter().any(|existing| existing == name) {
+                return Err(syn::Error::new(
+                    value.span(),
+                    format_args!("Duplicate bitset name `{}`", name),
+                ));
+            }
+            names.push(name.to_owned());
+        }
+        Ok(BitsetNames {
+            span: value.span(),
+            names,
+        })
+    }
+}
+impl DeriveFieldOptions {
+    pub fn parse_attrs(attrs: &[syn::Attribute]) -> Result<DeriveFieldOptions, syn::Error> {
+        let mut args = DeriveFieldOptions::default();
+        for attr in attrs {
+            if attr.path().is_ident("reflect") || attr.path().is_ident("static_reflect") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("opaque_array") {
+                        if args.opaque_array {
+                            return Err(meta.error("Duplicate opaque_array option"));
+                        }
+                        args.opaque_array = true;
+                    } else if meta.path.is_ident("assume_repr")
+                        || meta.path.is_ident("assume_repr_unchecked")
+                    {
+                        if args.assume_repr.is_some() {
+                            return Err(meta.error("Duplicate assume_repr option"));
+                        }
+                        args.assume_repr_unchecked = meta.path.is_ident("assume_repr_unchecked");
+                        let value = meta.value()?;
+                        let type_str = value.parse::<syn::LitStr>()?;
+                        let desired_type =
+                            syn::parse_str::<Type>(&type_str.value()).map_err(|cause| {
+                                syn::Error::new(
+                                    type_str.span(),
+                                    format_args!("Invalid type: {}", cause),
+                                )
+                            })?;
+                        args.assume_repr = Some(desired_type);
+                    } else if meta.path.is_ident("rename") {
+                        if args.rename.is_some() {
+                            return Err(meta.error("Duplicate rename option"));
+                        }
+                        let value = meta.value()?;
+                        args.rename = Some(value.parse::<syn::LitStr>()?);
+                    } else if meta.path.is_ident("endian") {
+                        if args.endian.is_some() {
+                            return Err(meta.error("Duplicate endian option"));
+                        }
+                        let value = meta.value()?.parse::<syn::LitStr>()?;
+                        let variant = match &*value.value() {
+                            "big" => "Big",
+                            "little" => "Little",
+                            _ => {
+                                return Err(syn::Error::new(
+                                    value.span(),
+                                    "Expected either \"big\" or \"little\"",
+                                ))
+                            }
+                        };
+                        args.endian = Some(Ident::new(variant, value.span()));
+                    } else if meta.path.is_ident("ptr") {
+                        if args.ptr.is_some() {
+                            return Err(meta.error("Duplicate ptr option"));
+                        }
+                        let value = meta.value()?.parse::<syn::LitStr>()?;
+                        args.ptr = Some(Ident::new(
+                            pointer_discipline_variant(&value)?,
+                            value.span(),
+                        ));
+                    } else if meta.path.is_ident("bitset") {
+                        if args.bitset.is_some() {
+                            return Err(meta.error("Duplicate bitset option"));
+                        }
+                        let mut names = None;
+                        meta.parse_nested_meta(|inner| {
+                            if !inner.path.is_ident("names") {
+                                return Err(inner.error("Expected names = \"...\""));
+                            }
+                            if names.is_some() {
+                                return Err(inner.error("Duplicate names option"));
+                            }
+                            names = Some(inner.value()?.parse::<syn::LitStr>()?);
+                            Ok(())
+                        })?;
+                        let names =
+                            names.ok_or_else(|| meta.error("Expected bitset(names = \"...\")"))?;
+                        args.bitset = Some(BitsetNames::parse(&names)?);
+                    } else if meta.path.is_ident("unit") {
+                        if args.unit.is_some() {
+                            return Err(meta.error("Duplicate unit option"));
+                        }
+                        args.unit = Some(meta.value()?.parse::<syn::LitStr>()?);
+                    } else {
+                        return Err(Error::new_spanned(&meta.path, format_args!("Invalid flag")));
+                    }
+                    // validate args
+                    if args.assume_repr.is_some() && args.opaque_array {
+                        return Err(Error::new_spanned(
+                            &meta.path,
+                            "opaque_array is incompatible with assume_repr",
+                        ));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(args)
+    }
+}
+#[allow(clippy::derivable_impls)]
+impl Default for DeriveFieldOptions {
+    fn default() -> DeriveFieldOptions {
+        DeriveFieldOptions {
+            // Most fields are not trailing arrays
+            opaque_array: false,
+            // This is unsafe
+            assume_repr: None,
+            assume_repr_unchecked: false,
+            // Use the name from the source code
+            rename: None,
+            // Use the native byte order
+            endian: None,
+            // Pointers are undocumented by default
+            ptr: None,
+            // Integers aren't flags by default
+            bitset: None,
+            // Integers don't have a unit by default
+            unit: None,
+        }
+    }
+}
+
+/// The variant of `PointerDiscipline` given to `ptr = "..."`
+/// (for either a field or a function parameter)
+pub fn pointer_discipline_variant(value: &syn::LitStr) -> Result<&'static str, syn::Error> {
+    Ok(match &*value.value() {
+        "owned" => "Owned",
+        "borrowed" => "Borrowed",
+        "raw" => "Raw",
+        _ => {
+            return Err(syn::Error::new(
+                value.span(),
+                "Expected either \"owned\", \"borrowed\" or \"raw\"",
+            ))
+        }
+    })
+}
+
+/// Options for the derive that apply to the whole type
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct DeriveContainerOptions {
+    /// Assert that the structure implements `bytemuck::Pod`,
+    /// recording that zero-copy casting is safe.
+    ///
+    /// Requires the `bytemuck` feature of the main crate.
+    pub pod: bool,
+    /// Omit optional metadata, keeping only the layout (`profile = "minimal"`).
+    ///
+    /// Field names are recorded as `None`, and the structure's name
+    /// is replaced by a short hash of the real name.
+    /// The offsets, types and sizes are exactly the same as the full profile,
+    /// and the same layout checks are performed.
+    pub minimal: bool,
+    /// Reflect a two-variant enum as using the niche of a non-zero type
+    /// (like `Option<NonNull<T>>`), instead of rejecting it as a complex enum.
+    pub niche: bool,
+    /// The last field is an array whose real length is only known at runtime.
+    ///
+    /// The array is reflected as its element type (like `opaque_array`),
+    /// and only the header before it is included in the reflected size.
+    /// The size check is relaxed to only verify the header fits inside the type,
+    /// since rustc may add trailing padding after a `[T; 0]` tail.
+    pub unsized_tail: bool,
+    /// The field holding the length of the unsized tail (`unsized_tail(len = "field")`),
+    /// which generates `FieldReflect::DYNAMIC_SIZE`
+    pub unsized_tail_len: Option<syn::LitStr>,
+    /// Look up the fields of a union through a generated function,
+    /// instead of building an array of every field.
+    ///
+    /// This is meant for unions with very many fields,
+    /// so the per-field `NamedFieldInfo` (and `FieldReflect`) isn't generated either.
+    pub lazy_fields: bool,
+    /// Emit warnings for suspicious layouts (`lint` or `lint(max_padding = N)`)
+    pub lint: Option<LintOptions>,
+    /// Generate inherent `variant_name` and `from_variant_name` methods
+    /// for a C-style enum, converting variants to and from their (possibly renamed) names.
+    pub variant_names: bool,
+    /// Generate an inherent `SCHEMA_INFO` constant for a struct,
+    /// with its type parameters left symbolic.
+    pub schema: bool,
+    /// Generate inherent `get_field` and `set_field` methods for each field of a struct
+    /// (`accessors` or `accessors(vis = "...")`)
+    pub accessors: Option<AccessorOptions>,
+    /// Reflect a `#[repr(transparent)]` integer newtype as the integer it wraps,
+    /// with a semantic unit (`newtype_unit` or `newtype_unit = "..."`).
+    ///
+    /// The unit defaults to the name of the type.
+    pub newtype_unit: bool,
+    /// The explicit unit given by `newtype_unit = "..."`
+    pub newtype_unit_name: Option<syn::LitStr>,
+    /// An explicit path to the `static_reflect` crate (`crate = "..."`),
+    /// overriding the one found in `Cargo.toml`.
+    pub crate_path: Option<syn::Path>,
+}
+/// Options for the variants of a C-style enum
+#[derive(Debug, Default)]
+pub struct DeriveVariantOptions {
+    /// Record the variant under a different name
+    pub rename: Option<syn::LitStr>,
+}
+impl DeriveVariantOptions {
+    pub fn parse_attrs(attrs: &[syn::Attribute]) -> Result<DeriveVariantOptions, syn::Error> {
+        let mut args = DeriveVariantOptions::default();
+        for attr in attrs {
+            if attr.path().is_ident("reflect") || attr.path().is_ident("static_reflect") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        if args.rename.is_some() {
+                            return Err(meta.error("Duplicate rename option"));
+                        }
+                        args.rename = Some(meta.value()?.parse::<syn::LitStr>()?);
+                    } else {
+                        return Err(Error::new_spanned(&meta.path, format_args!("Invalid flag")));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(args)
+    }
+}
+/// Options for the methods generated by `#[reflect(accessors)]`
+#[derive(Debug, Default)]
+pub struct AccessorOptions {
+    /// The visibility of every accessor,
+    /// overriding the visibility of the corresponding field
+    pub vis: Option<syn::Visibility>,
+}
+/// Options for the warnings emitted by `#[reflect(lint)]`
+#[derive(Debug, Default)]
+pub struct LintOptions {
+    /// The number of padding bytes allowed before warning
+    /// (defaults to `static_reflect::types::lint::DEFAULT_MAX_PADDING`)
+    pub max_padding: Option<syn::LitInt>,
+}
+impl DeriveContainerOptions {
+    pub fn parse_attrs(attrs: &[syn::Attribute]) -> Result<DeriveContainerOptions, syn::Error> {
+        let mut args = DeriveContainerOptions::default();
+        let mut seen_profile = false;
+        for attr in attrs {
+            if attr.path().is_ident("reflect") || attr.path().is_ident("static_reflect") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("pod") {
+                        if args.pod {
+                            return Err(meta.error("Duplicate pod option"));
+                        }
+                        args.pod = true;
+                    } else if meta.path.is_ident("niche") {
+                        if args.niche {
+                            return Err(meta.error("Duplicate niche option"));
+                        }
+                        args.niche = true;
+                    } else if meta.path.is_ident("unsized_tail") {
+                        if args.unsized_tail {
+                            return Err(meta.error("Duplicate unsized_tail option"));
+                        }
+                        args.unsized_tail = true;
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|inner| {
+                                if inner.path.is_ident("len") {
+                                    args.unsized_tail_len = Some(inner.value()?.parse()?);
+                                    Ok(())
+                                } else {
+                                    Err(inner.error("Unknown unsized_tail option"))
+                                }
+                            })?;
+                        }
+                    } else if meta.path.is_ident("lazy_fields") {
+                        if args.lazy_fields {
+                            return Err(meta.error("Duplicate lazy_fields option"));
+                        }
+                        args.lazy_fields = true;
+                    } else if meta.path.is_ident("variant_names") {
+                        if args.variant_names {
+                            return Err(meta.error("Duplicate variant_names option"));
+                        }
+                        args.variant_names = true;
+                    } else if meta.path.is_ident("schema") {
+                        if args.schema {
+                            return Err(meta.error("Duplicate schema option"));
+                        }
+                        args.schema = true;
+                    } else if meta.path.is_ident("lint") {
+                        if args.lint.is_some() {
+                            return Err(meta.error("Duplicate lint option"));
+                        }
+                        let mut lint = LintOptions::default();
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|inner| {
+                                if inner.path.is_ident("max_padding") {
+                                    let value = inner.value()?.parse::<syn::LitInt>()?;
+                                    value.base10_parse::<usize>()?;
+                                    lint.max_padding = Some(value);
+                                    Ok(())
+                                } else {
+                                    Err(inner.error("Unknown lint option"))
+                                }
+                            })?;
+                        }
+                        args.lint = Some(lint);
+                    } else if meta.path.is_ident("accessors") {
+                        if args.accessors.is_some() {
+                            return Err(meta.error("Duplicate accessors option"));
+                        }
+                        let mut accessors = AccessorOptions::default();
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|inner| {
+                                if inner.path.is_ident("vis") {
+                                    let value = inner.value()?.parse::<syn::LitStr>()?;
+                                    accessors.vis = Some(value.parse::<syn::Visibility>()?);
+                                    Ok(())
+                                } else {
+                                    Err(inner.error("Unknown accessors option"))
+                                }
+                            })?;
+                        }
+                        args.accessors = Some(accessors);
+                    } else if meta.path.is_ident("newtype_unit") {
+                        if args.newtype_unit {
+                            return Err(meta.error("Duplicate newtype_unit option"));
+                        }
+                        args.newtype_unit = true;
+                        if meta.input.peek(syn::Token![=]) {
+                            args.newtype_unit_name = Some(meta.value()?.parse()?);
+                        }
+                    } else if meta.path.is_ident("crate") {
+                        if args.crate_path.is_some() {
+                            return Err(meta.error("Duplicate crate option"));
+                        }
+                        let value = meta.value()?.parse::<syn::LitStr>()?;
+                        args.crate_path = Some(value.parse::<syn::Path>()?);
+                    } else if meta.path.is_ident("profile") {
+                        if seen_profile {
+                            return Err(meta.error("Duplicate profile option"));
+                        }
+                        seen_profile = true;
+                        let value = meta.value()?.parse::<syn::LitStr>()?;
+                        args.minimal = match &*value.value() {
+                            "minimal" => true,
+                            "full" => false,
+                            _ => {
+                                return Err(syn::Error::new(
+                                    value.span(),
+                                    "Expected either \"minimal\" or \"full\"",
+                                ))
+                            }
+                        };
+                    } else {
+                        return Err(Error::new_spanned(&meta.path, format_args!("Invalid flag")));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(args)
+    }
+}
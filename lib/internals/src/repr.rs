@@ -0,0 +1,107 @@
+//! The `#[repr(...)]` of a type
+use std::str::FromStr;
+
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::DeriveInput;
+
+/// The representation of a type, given by `#[repr(...)]`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Repr {
+    /// `#[repr(C)]`
+    C,
+    /// `#[repr(transparent)]`
+    Transparent,
+    /// A fixed-width integer, like `#[repr(u8)]` or `#[repr(i64)]`
+    Integer { signed: bool, bits: u32 },
+    /// `#[repr(usize)]` or `#[repr(isize)]`, whose width depends on the target
+    IntegerPointerSized { signed: bool },
+}
+
+/// The representations accepted by `determine_repr`, for error messages
+const ACCEPTED_REPRS: &str = "C, transparent, u8, u16, u32, u64, usize, i8, i16, i32, i64 or isize";
+
+/// Determine the representation of a type, ignoring `align(N)`
+///
+/// Returns `None` if the type has no `#[repr(...)]`.
+pub fn determine_repr(input: &DeriveInput) -> Result<Option<Repr>, syn::Error> {
+    let mut result = None;
+    for attr in &input.attrs {
+        if attr.meta.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("align") {
+                    // Handled separately by `determine_align`
+                    parse_align(&meta)?;
+                    return Ok(());
+                }
+                if result.is_some() {
+                    return Err(meta.error("Encountered multiple repr(...) attributes"));
+                }
+                let s = meta.path.require_ident()?.to_string();
+                result = Some(match &*s {
+                    "C" => Repr::C,
+                    "transparent" => Repr::Transparent,
+                    "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" => {
+                        let signed = match s.chars().next() {
+                            Some('i') => true,
+                            Some('u') => false,
+                            _ => unreachable!(),
+                        };
+                        let bits = u32::from_str(&s[1..]).unwrap();
+                        Repr::Integer { signed, bits }
+                    }
+                    "usize" => Repr::IntegerPointerSized { signed: false },
+                    "isize" => Repr::IntegerPointerSized { signed: true },
+                    _ => {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            format!(
+                                "Unsupported #[repr({})], expected one of {}",
+                                s, ACCEPTED_REPRS
+                            ),
+                        ))
+                    }
+                });
+                Ok(())
+            })?;
+        }
+    }
+    Ok(result)
+}
+
+/// Determine the minimum alignment requested by `#[repr(align(N))]`, if any
+pub fn determine_align(input: &DeriveInput) -> Result<Option<usize>, syn::Error> {
+    let mut result = None;
+    for attr in &input.attrs {
+        if attr.meta.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("align") {
+                    if result.is_some() {
+                        return Err(meta.error("Encountered multiple repr(align(...)) attributes"));
+                    }
+                    result = Some(parse_align(&meta)?);
+                } else if meta.input.peek(syn::token::Paren) {
+                    // Skip the arguments of any other repr
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            })?;
+        }
+    }
+    Ok(result)
+}
+
+fn parse_align(meta: &ParseNestedMeta) -> Result<usize, syn::Error> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    let lit: syn::LitInt = content.parse()?;
+    let align = lit.base10_parse::<usize>()?;
+    if !align.is_power_of_two() {
+        return Err(syn::Error::new(
+            lit.span(),
+            "Alignment must be a power of two",
+        ));
+    }
+    Ok(align)
+}
@@ -0,0 +1,14 @@
+//! The first of two crates which register functions independently
+//!
+//! See `static-reflect-registry-test-b` for the test that links them together.
+use static_reflect::reflect_func;
+
+#[reflect_func(absolute, register)]
+pub extern "C" fn alpha_add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[reflect_func(absolute, register)]
+pub extern "C" fn shared_name() -> u8 {
+    b'a'
+}
@@ -0,0 +1,12 @@
+//! The second of two crates which register functions independently
+use static_reflect::reflect_func;
+
+#[reflect_func(absolute, register)]
+pub extern "C" fn beta_mul(a: u32, b: u32) -> u32 {
+    a * b
+}
+
+#[reflect_func(absolute, register)]
+pub extern "C" fn shared_name() -> u8 {
+    b'b'
+}
@@ -0,0 +1,48 @@
+//! The registry of a binary sees the functions registered by every crate linked into it
+use static_reflect::funcs::{registry, FunctionDeclaration, FunctionLocation};
+
+// Neither crate is otherwise referenced, so only linking them brings in their functions
+extern crate static_reflect_registry_test_a as crate_a;
+extern crate static_reflect_registry_test_b as crate_b;
+
+/// Call a registered `extern "C" fn() -> u8` by its address
+///
+/// Function addresses aren't guaranteed to be unique (especially with optimizations),
+/// so the functions are told apart by calling them instead.
+fn call(decl: &FunctionDeclaration) -> u8 {
+    match decl.location {
+        Some(FunctionLocation::AbsoluteAddress(address)) => {
+            let func: extern "C" fn() -> u8 = unsafe { std::mem::transmute(address) };
+            func()
+        }
+        ref other => panic!("Unexpected location: {:?}", other),
+    }
+}
+
+#[test]
+fn both_crates() {
+    let [alpha] = registry::find("alpha_add")[..] else {
+        panic!("Expected a single alpha_add")
+    };
+    assert_eq!(alpha.signature, crate_a::_FUNC_alpha_add.signature);
+    let [beta] = registry::find("beta_mul")[..] else {
+        panic!("Expected a single beta_mul")
+    };
+    assert_eq!(beta.signature, crate_b::_FUNC_beta_mul.signature);
+    let mut shared = registry::find("shared_name")
+        .into_iter()
+        .map(call)
+        .collect::<Vec<_>>();
+    shared.sort_unstable();
+    assert_eq!(shared, [b'a', b'b']);
+}
+
+#[test]
+fn each_function_once() {
+    let mut names = registry::iter().map(|decl| decl.name).collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        ["alpha_add", "beta_mul", "shared_name", "shared_name"]
+    );
+}
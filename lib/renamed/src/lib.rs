@@ -0,0 +1,17 @@
+//! Tests that the derives work when `static-reflect` is renamed in `Cargo.toml`
+//!
+//! This crate also acts as a facade re-exporting the renamed crate.
+//! Crates using it through the facade need to specify the path explicitly:
+//! ````
+//! use static_reflect_renamed_test::reflect::StaticReflect;
+//!
+//! #[derive(StaticReflect)]
+//! #[reflect(crate = "static_reflect_renamed_test::reflect")]
+//! #[repr(C)]
+//! struct Point {
+//!     x: f32,
+//!     y: f32,
+//! }
+//! assert_eq!(Point::TYPE_INFO.size(), 8);
+//! ````
+pub use reflect;
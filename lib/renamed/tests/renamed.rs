@@ -0,0 +1,70 @@
+//! Derives using the crate under its renamed dependency name
+use std::ptr::NonNull;
+
+use reflect::types::TypeInfo;
+use reflect::{reflect_func, FieldReflect, StaticReflect};
+
+#[derive(StaticReflect)]
+#[repr(C)]
+struct Point {
+    x: u8,
+    #[reflect(endian = "big")]
+    y: u32,
+}
+
+#[derive(Copy, Clone, StaticReflect)]
+#[repr(C)]
+union Number {
+    int: u64,
+    float: f64,
+}
+
+#[derive(StaticReflect)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Color {
+    Red,
+    Green,
+}
+
+#[derive(StaticReflect)]
+#[reflect(niche)]
+#[allow(dead_code)]
+enum MaybeHandle {
+    None,
+    Some(NonNull<u8>),
+}
+
+#[reflect_func]
+#[no_mangle]
+pub extern "C" fn renamed_add(first: i32, second: i32) -> i32 {
+    first + second
+}
+
+#[reflect_func(crate = "reflect")]
+#[no_mangle]
+pub extern "C" fn renamed_explicit(value: u64) -> u64 {
+    value
+}
+
+#[test]
+fn derives() {
+    assert_eq!(Point::TYPE_INFO.size(), std::mem::size_of::<Point>());
+    assert_eq!(Point::NAMED_FIELD_INFO.y.offset(), 4);
+    assert!(Point::layout_report().is_consistent());
+    match Number::TYPE_INFO {
+        TypeInfo::UntaggedUnion(def) => assert_eq!(def.fields.len(), 2),
+        other => panic!("Unexpected type: {}", other),
+    }
+    assert_eq!(Number::NAMED_FIELD_INFO.float.name, "float");
+    assert!(matches!(Color::TYPE_INFO, TypeInfo::CStyleEnum(_)));
+    assert!(matches!(MaybeHandle::TYPE_INFO, TypeInfo::Niche(_)));
+}
+
+#[test]
+fn functions() {
+    assert_eq!(_FUNC_renamed_add.name, "renamed_add");
+    assert_eq!(_FUNC_renamed_add.signature.argument_types.len(), 2);
+    assert_eq!(_FUNC_renamed_explicit.name, "renamed_explicit");
+    assert_eq!(renamed_add(1, 2), 3);
+}
@@ -2,8 +2,11 @@
 //!
 //! These are mostly FFI-safe alternatives to the standard library
 //! types.
-use crate::{field_offset, StaticReflect, TypeInfo};
-use std::mem::MaybeUninit;
+use crate::macros::field_offset;
+use crate::{StaticReflect, TypeInfo};
+use std::fmt::{self, Debug, Formatter};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ptr::NonNull;
 
 /// A FFi-safe slice type (`&[T]`)
 ///
@@ -18,7 +21,13 @@ use std::mem::MaybeUninit;
 /// unsafe situations, this is often fine.
 /// The plus side is you can transmute to/from `[usize; 2]`
 /// without fear.
-#[derive(Debug)]
+///
+/// ## Traits
+/// This is `Copy` regardless of the element type, since it doesn't own the elements.
+/// It intentionally doesn't implement `PartialEq` or `Hash`,
+/// since it's unclear whether they should compare the addresses or the elements
+/// (and the elements can only be read unsafely).
+/// The [Default] is an empty slice, with a dangling pointer like `&[]`.
 #[repr(C)]
 pub struct AsmSlice<T> {
     /// A pointer to the start of the memory
@@ -30,13 +39,30 @@ pub struct AsmSlice<T> {
 }
 /// A clone implementation that blindly
 /// copies the underlying bytes.
-impl<T: StaticReflect> Clone for AsmSlice<T> {
+impl<T> Clone for AsmSlice<T> {
     #[inline]
     fn clone(&self) -> Self {
         *self
     }
 }
-impl<T: StaticReflect> Copy for AsmSlice<T> {}
+impl<T> Copy for AsmSlice<T> {}
+impl<T> Debug for AsmSlice<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsmSlice")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+impl<T> Default for AsmSlice<T> {
+    #[inline]
+    fn default() -> Self {
+        AsmSlice {
+            ptr: NonNull::dangling().as_ptr(),
+            len: 0,
+        }
+    }
+}
 impl<'a, T: 'a> From<&'a [T]> for AsmSlice<T> {
     #[inline]
     fn from(slice: &'a [T]) -> Self {
@@ -64,8 +90,11 @@ unsafe impl<T: Sync> Send for AsmSlice<T> {}
 /// The underlying is expected to be UTF8. However,
 /// like its [AsmSlice] counterpart, all fields are public
 /// and this type does not maintain any invariants.
+///
+/// Like [AsmSlice], this doesn't implement `PartialEq` or `Hash`.
+/// The [Default] is an empty string.
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct AsmStr {
     /// The underlying memory of the string
     pub bytes: AsmSlice<u8>,
@@ -103,7 +132,19 @@ impl<'a> From<&'a str> for AsmStr {
 /// Unlike the Rust type, this does not use the null-pointer
 /// optimization.
 ///
-/// NOTE: This type doesn't implement Drop.
+/// ## Ownership
+/// This type never drops its value automatically,
+/// since it's often shared with code on the other side of an FFI boundary.
+/// Whoever ends up owning a present value is responsible for it,
+/// either by moving it out with [AsmOption::take] (or [AsmOption::assume_valid]),
+/// or by explicitly dropping it with [AsmOption::drop_in_place].
+///
+/// With debug assertions enabled, implicitly dropping an option
+/// whose value is present (and needs to be dropped) panics,
+/// instead of silently leaking the value.
+/// This check is a `Drop` impl that only exists in debug builds,
+/// so `std::mem::needs_drop` is only true for an option with debug assertions.
+/// Its reflected [TypeInfo::needs_drop] is always false, matching release builds.
 ///
 /// ## Safety
 /// This type does not enforce its safety variants,
@@ -112,8 +153,14 @@ impl<'a> From<&'a str> for AsmStr {
 ///
 /// A valid type can only be in one of two states:
 /// 1. `{present: false, value: undefined}`
-/// 2. `{present: false, value: any}`
-#[derive(Debug)]
+/// 2. `{present: true, value: any}`
+///
+/// ## Traits
+/// The value may be uninitialized, so it can't be safely compared, hashed, cloned or printed.
+/// This intentionally doesn't implement `PartialEq`, `Hash` or `Clone`,
+/// and its `Debug` only shows whether the value is present.
+/// Use [AsmOption::assume_valid] to get an `Option` first.
+/// The [Default] is an absent value.
 #[repr(C)]
 pub struct AsmOption<T> {
     present: bool,
@@ -146,7 +193,10 @@ impl<T> AsmOption<T> {
         }
     }
     /// Create an option with a value
+    ///
+    /// The option takes ownership of the value, but will never drop it.
     #[inline]
+    #[must_use = "AsmOption never drops its value, which would be leaked"]
     pub fn some(value: T) -> AsmOption<T> {
         AsmOption {
             present: true,
@@ -163,18 +213,57 @@ impl<T> AsmOption<T> {
     /// If not, undefined behavior will result.
     #[inline]
     pub unsafe fn assume_valid(self) -> Option<T> {
+        let this = ManuallyDrop::new(self);
+        if this.present {
+            Some(this.value.as_ptr().read())
+        } else {
+            None
+        }
+    }
+    /// Take the value out of the option (if present),
+    /// leaving it absent
+    #[inline]
+    pub fn take(&mut self) -> Option<T> {
         if self.present {
-            Some(self.value.assume_init())
+            self.present = false;
+            // SAFETY: A present value is always initialized
+            Some(unsafe { self.value.as_ptr().read() })
         } else {
             None
         }
     }
+    /// Drop the value of the option (if present),
+    /// leaving it absent
+    ///
+    /// ## Safety
+    /// If the value is present, it must be valid.
+    /// This is mostly a concern for options written by foreign code.
+    #[inline]
+    pub unsafe fn drop_in_place(&mut self) {
+        if self.present {
+            self.present = false;
+            std::ptr::drop_in_place(self.value.as_mut_ptr());
+        }
+    }
     /// If the value of the option is present.
     #[inline]
     pub fn is_present(&self) -> bool {
         self.present
     }
 }
+impl<T> Debug for AsmOption<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsmOption")
+            .field("present", &self.present)
+            .finish_non_exhaustive()
+    }
+}
+impl<T> Default for AsmOption<T> {
+    #[inline]
+    fn default() -> Self {
+        AsmOption::none()
+    }
+}
 impl<T> From<Option<T>> for AsmOption<T> {
     fn from(o: Option<T>) -> AsmOption<T> {
         match o {
@@ -183,6 +272,20 @@ impl<T> From<Option<T>> for AsmOption<T> {
         }
     }
 }
+/// Detect values that are leaked by implicitly dropping the option
+///
+/// This is only a debugging aid, so it's omitted in release builds.
+#[cfg(debug_assertions)]
+impl<T> Drop for AsmOption<T> {
+    fn drop(&mut self) {
+        if self.present && std::mem::needs_drop::<T>() && !std::thread::panicking() {
+            panic!(
+                "Implicitly dropped an AsmOption<{}>, leaking its value (use take or drop_in_place)",
+                std::any::type_name::<T>()
+            );
+        }
+    }
+}
 unsafe impl<T: StaticReflect> StaticReflect for AsmOption<T> {
     const TYPE_INFO: TypeInfo = TypeInfo::Optional(&T::TYPE_INFO);
 }
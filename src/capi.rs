@@ -0,0 +1,422 @@
+//! A C API for querying reflection data at runtime
+//!
+//! All types are passed around as opaque `*const TypeInfo` handles,
+//! which the Rust side hands over to foreign code.
+//!
+//! Every function returns a [SrStatus] and writes its result through an out-pointer.
+//! None of these functions allocate, and panics never cross the FFI boundary.
+use crate::builtins::AsmStr;
+use crate::funcs::{FunctionDeclaration, SignatureDef};
+use crate::types::TypeInfo;
+use std::alloc::Layout;
+use std::panic::{self, UnwindSafe};
+
+/// The result of a call to the C API
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum SrStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// One of the pointers passed in was null
+    NullPointer = 1,
+    /// The type is the wrong kind for the query
+    WrongKind = 2,
+    /// The index is out of bounds
+    OutOfBounds = 3,
+    /// An unexpected panic occurred (this is a bug)
+    Panic = 4,
+    /// The layout of the type (or one of its fields) isn't known
+    ///
+    /// This includes extern types without a registered layout, type parameters and optionals.
+    UnknownLayout = 5,
+}
+
+/// The kind of a [TypeInfo], mirroring its variants
+///
+/// The discriminants are stable, regardless of the enabled features.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum SrTypeKind {
+    /// [TypeInfo::Unit]
+    Unit = 0,
+    /// [TypeInfo::Never]
+    Never = 1,
+    /// [TypeInfo::Bool]
+    Bool = 2,
+    /// [TypeInfo::Integer]
+    Integer = 3,
+    /// [TypeInfo::Float]
+    Float = 4,
+    /// [TypeInfo::Slice]
+    Slice = 5,
+    /// [TypeInfo::Str]
+    Str = 6,
+    /// [TypeInfo::Optional]
+    Optional = 7,
+    /// [TypeInfo::Pointer] (of either mutability)
+    Pointer = 8,
+    /// [TypeInfo::Structure]
+    Structure = 9,
+    /// [TypeInfo::UntaggedUnion]
+    UntaggedUnion = 10,
+    /// [TypeInfo::TaggedUnion]
+    TaggedUnion = 11,
+    /// [TypeInfo::CStyleEnum]
+    CStyleEnum = 12,
+    /// [TypeInfo::Extern]
+    Extern = 13,
+    /// [TypeInfo::Magic]
+    Magic = 14,
+    /// [TypeInfo::NonZero]
+    NonZero = 15,
+    /// [TypeInfo::Niche]
+    Niche = 16,
+    /// [TypeInfo::TypeParameter]
+    TypeParameter = 17,
+    /// [TypeInfo::Vector]
+    Vector = 18,
+}
+impl SrTypeKind {
+    /// Determine the kind of the specified type
+    pub fn of(info: &TypeInfo) -> SrTypeKind {
+        match *info {
+            TypeInfo::Unit => SrTypeKind::Unit,
+            #[cfg(feature = "never")]
+            TypeInfo::Never => SrTypeKind::Never,
+            TypeInfo::Bool => SrTypeKind::Bool,
+            TypeInfo::Integer(_) => SrTypeKind::Integer,
+            TypeInfo::Float { .. } => SrTypeKind::Float,
+            TypeInfo::Vector { .. } => SrTypeKind::Vector,
+            TypeInfo::Slice { .. } => SrTypeKind::Slice,
+            TypeInfo::Str => SrTypeKind::Str,
+            TypeInfo::Optional(_) => SrTypeKind::Optional,
+            TypeInfo::Pointer { .. } => SrTypeKind::Pointer,
+            TypeInfo::NonZero(_) => SrTypeKind::NonZero,
+            TypeInfo::Structure(_) => SrTypeKind::Structure,
+            TypeInfo::UntaggedUnion(_) => SrTypeKind::UntaggedUnion,
+            TypeInfo::TaggedUnion(_) => SrTypeKind::TaggedUnion,
+            TypeInfo::CStyleEnum(_) => SrTypeKind::CStyleEnum,
+            TypeInfo::Niche(_) => SrTypeKind::Niche,
+            TypeInfo::Extern { .. } => SrTypeKind::Extern,
+            TypeInfo::TypeParameter { .. } => SrTypeKind::TypeParameter,
+            TypeInfo::Magic { .. } => SrTypeKind::Magic,
+        }
+    }
+}
+
+/// A C-compatible description of a field
+///
+/// Mirrors [FieldDef](crate::types::FieldDef) and [UnionFieldDef](crate::types::UnionFieldDef).
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct SrFieldDesc {
+    /// The name of the field, which is empty for tuple fields
+    pub name: AsmStr,
+    /// The type of the field
+    pub value_type: *const TypeInfo,
+    /// The offset of the field in bytes
+    pub offset: usize,
+    /// The index of the field
+    pub index: usize,
+}
+
+/// A C-compatible description of a C-style enum variant
+///
+/// Mirrors [CStyleEnumVariant](crate::types::CStyleEnumVariant)
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct SrVariantDesc {
+    /// The name of the variant
+    pub name: AsmStr,
+    /// The index of the variant
+    pub index: usize,
+    /// The bits of the variant's discriminant
+    pub discriminant: u64,
+}
+
+/// Run the specified closure, converting panics into [SrStatus::Panic]
+#[inline]
+fn guard(func: impl FnOnce() -> Result<(), SrStatus> + UnwindSafe) -> SrStatus {
+    match panic::catch_unwind(func) {
+        Ok(Ok(())) => SrStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => SrStatus::Panic,
+    }
+}
+
+/// Convert a pointer to a reference, failing if it is null
+#[inline]
+unsafe fn deref<'a, T>(ptr: *const T) -> Result<&'a T, SrStatus> {
+    ptr.as_ref().ok_or(SrStatus::NullPointer)
+}
+
+/// Write a value to an out-pointer, failing if it is null
+#[inline]
+unsafe fn write_out<T>(out: *mut T, value: T) -> Result<(), SrStatus> {
+    if out.is_null() {
+        return Err(SrStatus::NullPointer);
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// The layout of the type, failing if it isn't known
+#[inline]
+fn layout(info: &TypeInfo) -> Result<Layout, SrStatus> {
+    info.try_layout_in_place()
+        .map_err(|_| SrStatus::UnknownLayout)
+}
+
+/// The name of the type, without any allocation
+///
+/// Primitives use their Rust names.
+/// Slices, optionals and vectors don't have a simple name,
+/// so they just use `[]`, `Option` and `vector` respectively.
+fn static_name(info: &TypeInfo) -> &'static str {
+    match *info {
+        TypeInfo::Unit => "()",
+        #[cfg(feature = "never")]
+        TypeInfo::Never => "!",
+        TypeInfo::Bool => "bool",
+        TypeInfo::Integer(tp) => match (tp.size.bytes(), tp.signed) {
+            (1, false) => "u8",
+            (2, false) => "u16",
+            (4, false) => "u32",
+            (8, false) => "u64",
+            (1, true) => "i8",
+            (2, true) => "i16",
+            (4, true) => "i32",
+            (8, true) => "i64",
+            _ => unreachable!(),
+        },
+        TypeInfo::Float { size } => match size {
+            crate::types::FloatSize::Single => "f32",
+            crate::types::FloatSize::Double => "f64",
+        },
+        TypeInfo::Vector { .. } => "vector",
+        TypeInfo::Slice { .. } => "[]",
+        TypeInfo::Str => "str",
+        TypeInfo::Optional(_) => "Option",
+        TypeInfo::Pointer { mutable: true } => "*mut void",
+        TypeInfo::Pointer { mutable: false } => "*const void",
+        TypeInfo::NonZero(inner) => static_name(inner),
+        TypeInfo::Structure(def) => def.name,
+        TypeInfo::UntaggedUnion(def) => def.name,
+        TypeInfo::TaggedUnion(def) => def.name,
+        TypeInfo::CStyleEnum(def) => def.name,
+        TypeInfo::Niche(def) => def.name,
+        TypeInfo::Extern { name } => name,
+        TypeInfo::TypeParameter { name, .. } => name,
+        TypeInfo::Magic { id, .. } => id,
+    }
+}
+
+/// Get the name of the specified type
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_type_name(info: *const TypeInfo, out: *mut AsmStr) -> SrStatus {
+    guard(|| write_out(out, AsmStr::from(static_name(deref(info)?))))
+}
+
+/// Get the size of the specified type, in bytes
+///
+/// Fails with [SrStatus::UnknownLayout] for opaque types (like [TypeInfo::try_layout]).
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_type_size(info: *const TypeInfo, out: *mut usize) -> SrStatus {
+    guard(|| write_out(out, layout(deref(info)?)?.size()))
+}
+
+/// Get the alignment of the specified type, in bytes
+///
+/// Fails with [SrStatus::UnknownLayout] for opaque types (like [TypeInfo::try_layout]).
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_type_alignment(info: *const TypeInfo, out: *mut usize) -> SrStatus {
+    guard(|| write_out(out, layout(deref(info)?)?.align()))
+}
+
+/// Get the kind of the specified type
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_type_kind(info: *const TypeInfo, out: *mut SrTypeKind) -> SrStatus {
+    guard(|| write_out(out, SrTypeKind::of(deref(info)?)))
+}
+
+/// Get the number of fields in the specified structure (or untagged union)
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_struct_field_count(info: *const TypeInfo, out: *mut usize) -> SrStatus {
+    guard(|| {
+        let count = match *deref(info)? {
+            TypeInfo::Structure(def) => def.fields.len(),
+            TypeInfo::UntaggedUnion(def) => def.fields.len(),
+            _ => return Err(SrStatus::WrongKind),
+        };
+        write_out(out, count)
+    })
+}
+
+/// Describe the field of the specified structure (or untagged union)
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_struct_field(
+    info: *const TypeInfo,
+    index: usize,
+    out: *mut SrFieldDesc,
+) -> SrStatus {
+    guard(|| {
+        let desc = match *deref(info)? {
+            TypeInfo::Structure(def) => {
+                let field = def.fields.get(index).ok_or(SrStatus::OutOfBounds)?;
+                SrFieldDesc {
+                    name: AsmStr::from(field.name.unwrap_or("")),
+                    value_type: field.value_type.type_ref(),
+                    offset: field.offset,
+                    index: field.index,
+                }
+            }
+            TypeInfo::UntaggedUnion(def) => {
+                let field = def.fields.get(index).ok_or(SrStatus::OutOfBounds)?;
+                SrFieldDesc {
+                    name: AsmStr::from(field.name),
+                    value_type: field.value_type.type_ref(),
+                    offset: field.offset(),
+                    index: field.index,
+                }
+            }
+            _ => return Err(SrStatus::WrongKind),
+        };
+        write_out(out, desc)
+    })
+}
+
+/// Get the number of variants in the specified C-style enum
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_enum_variant_count(info: *const TypeInfo, out: *mut usize) -> SrStatus {
+    guard(|| match *deref(info)? {
+        TypeInfo::CStyleEnum(def) => write_out(out, def.variants.len()),
+        _ => Err(SrStatus::WrongKind),
+    })
+}
+
+/// Describe the variant of the specified C-style enum
+///
+/// ## Safety
+/// The type must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_enum_variant(
+    info: *const TypeInfo,
+    index: usize,
+    out: *mut SrVariantDesc,
+) -> SrStatus {
+    guard(|| match *deref(info)? {
+        TypeInfo::CStyleEnum(def) => {
+            let variant = def.variants.get(index).ok_or(SrStatus::OutOfBounds)?;
+            write_out(
+                out,
+                SrVariantDesc {
+                    name: AsmStr::from(variant.name),
+                    index: variant.index,
+                    discriminant: variant.discriminant.bits(),
+                },
+            )
+        }
+        _ => Err(SrStatus::WrongKind),
+    })
+}
+
+/// Get the name of the specified function
+///
+/// ## Safety
+/// The declaration must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_function_name(
+    decl: *const FunctionDeclaration,
+    out: *mut AsmStr,
+) -> SrStatus {
+    guard(|| write_out(out, AsmStr::from(deref(decl)?.name)))
+}
+
+/// Get the signature of the specified function
+///
+/// ## Safety
+/// The declaration must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_function_signature(
+    decl: *const FunctionDeclaration,
+    out: *mut *const SignatureDef,
+) -> SrStatus {
+    guard(|| write_out(out, &deref(decl)?.signature as *const SignatureDef))
+}
+
+/// Get the number of arguments in the specified signature
+///
+/// ## Safety
+/// The signature must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_signature_arg_count(
+    sig: *const SignatureDef,
+    out: *mut usize,
+) -> SrStatus {
+    guard(|| write_out(out, deref(sig)?.argument_types.len()))
+}
+
+/// Get the type of the specified argument
+///
+/// ## Safety
+/// The signature must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_signature_arg(
+    sig: *const SignatureDef,
+    index: usize,
+    out: *mut *const TypeInfo,
+) -> SrStatus {
+    guard(|| {
+        let arg = deref(sig)?
+            .argument_types
+            .get(index)
+            .ok_or(SrStatus::OutOfBounds)?;
+        write_out(out, arg as *const TypeInfo)
+    })
+}
+
+/// Get the return type of the specified signature
+///
+/// ## Safety
+/// The signature must be a valid pointer (or null).
+/// The output must be valid for writes (or null).
+#[no_mangle]
+pub unsafe extern "C" fn sr_signature_return(
+    sig: *const SignatureDef,
+    out: *mut *const TypeInfo,
+) -> SrStatus {
+    guard(|| write_out(out, deref(sig)?.return_type as *const TypeInfo))
+}
@@ -0,0 +1,104 @@
+//! Reflection information on constant data, declared with `#[reflect_const]`
+//!
+//! Besides functions, generated code often needs to link against constant data
+//! (like lookup tables). Each reflected `const` or `static` records its type,
+//! along with a view of its bytes which is captured at compile time.
+//!
+//! Viewing a value as bytes is only sound if every byte is initialized,
+//! and none of them are part of a pointer. So the types of reflected constants
+//! are restricted to [ConstBytes]: primitive numbers (and `bool`), along with arrays of them.
+//! String constants (`&str`) are also supported, and are reflected as their UTF-8 contents.
+use crate::types::TypeInfo;
+use crate::StaticReflect;
+
+/// The declaration of a `const` or `static` item,
+/// whose type and contents are known to the static reflection system
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConstDeclaration {
+    /// The name of the item, as declared in the source code
+    pub name: &'static str,
+    /// The type of the value (or the type of its elements, if it's an array)
+    pub type_info: &'static TypeInfo,
+    /// The number of elements, if the value is an array
+    ///
+    /// Arrays aren't otherwise reflected (see the `opaque_array` option of the derive).
+    pub array_len: Option<usize>,
+    /// The bytes of the value, in native byte order
+    ///
+    /// For a string, these are its UTF-8 contents.
+    pub bytes: &'static [u8],
+    /// If the item is a `static`, so that it has a fixed address
+    ///
+    /// The bytes of a static are its own memory (see [ConstDeclaration::address]),
+    /// while the bytes of a `const` are a copy.
+    pub is_static: bool,
+}
+impl ConstDeclaration {
+    /// The address of the static, or `None` if the item is a `const`
+    ///
+    /// For a string, this is the address of its contents.
+    #[inline]
+    pub fn address(&self) -> Option<*const u8> {
+        if self.is_static {
+            Some(self.bytes.as_ptr())
+        } else {
+            None
+        }
+    }
+    /// Read the value as the specified type,
+    /// checking that it matches the reflected type
+    ///
+    /// Returns `None` if the types don't match.
+    pub fn value<T: ConstBytes>(&self) -> Option<T> {
+        let valid = match *T::ELEMENT_TYPE {
+            // The fields are public, so the bytes aren't guaranteed to come from a real `bool`
+            TypeInfo::Bool => self.bytes.iter().all(|&byte| byte <= 1),
+            _ => true,
+        };
+        if *self.type_info == *T::ELEMENT_TYPE
+            && self.array_len == T::ARRAY_LEN
+            && self.bytes.len() == std::mem::size_of::<T>()
+            && valid
+        {
+            // SAFETY: Besides `bool` (checked above), every bit pattern is a valid `ConstBytes` value
+            Some(unsafe { (self.bytes.as_ptr() as *const T).read_unaligned() })
+        } else {
+            None
+        }
+    }
+}
+
+/// A type whose values can soundly be viewed as bytes in a const context
+///
+/// This is implemented for the primitive numbers and `bool`,
+/// along with (non-nested) arrays of them.
+///
+/// ## Safety
+/// Every byte of a value must be initialized (there can't be any padding),
+/// and the type can't contain any pointers or interior mutability.
+pub unsafe trait ConstBytes: Copy + sealed::Sealed {
+    /// The reflected type of the value, or of its elements if it's an array
+    const ELEMENT_TYPE: &'static TypeInfo;
+    /// The number of elements, if the type is an array
+    const ARRAY_LEN: Option<usize>;
+}
+macro_rules! impl_const_bytes {
+    ($($target:ty),*) => {
+        $(unsafe impl ConstBytes for $target {
+            const ELEMENT_TYPE: &'static TypeInfo = &<$target as StaticReflect>::TYPE_INFO;
+            const ARRAY_LEN: Option<usize> = None;
+        }
+        impl sealed::Sealed for $target {})*
+    };
+}
+impl_const_bytes!(u8, u16, u32, u64, i8, i16, i32, i64, usize, isize, f32, f64, bool);
+/// Nested arrays aren't supported, since the element type must be reflected
+unsafe impl<T: ConstBytes + StaticReflect, const N: usize> ConstBytes for [T; N] {
+    const ELEMENT_TYPE: &'static TypeInfo = &T::TYPE_INFO;
+    const ARRAY_LEN: Option<usize> = Some(N);
+}
+impl<T: ConstBytes + StaticReflect, const N: usize> sealed::Sealed for [T; N] {}
+
+mod sealed {
+    pub trait Sealed {}
+}
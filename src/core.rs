@@ -1,7 +1,8 @@
 //! Implementations of [StaticReflect] for core types (for `#![no_std]`)
-use crate::types::{FloatSize, IntSize, IntType, SimpleNonZeroRepr, TypeInfo};
-use crate::{PrimFloat, PrimInt, StaticReflect};
+use crate::types::{FloatSize, IntSize, IntType, NicheDef, SimpleNonZeroRepr, TypeInfo};
+use crate::{PrimFloat, PrimInt, PrimNumber, StaticReflect};
 use core::ptr::NonNull;
+use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
 use std::num::{NonZeroI32, NonZeroU32, NonZeroU8, NonZeroUsize};
 
@@ -23,6 +24,7 @@ macro_rules! impl_ints {
             const SIGNED: bool = <$target>::MIN < 0;
             const INT_TYPE: IntType = IntType { size: Self::INT_SIZE, signed: Self::SIGNED };
         }
+        unsafe impl PrimNumber for $target {}
         impl crate::sealed::Sealed for $target {})*
     }
 }
@@ -39,12 +41,15 @@ impl crate::sealed::Sealed for f32 {}
 unsafe impl PrimFloat for f32 {
     const FLOAT_SIZE: FloatSize = FloatSize::Single;
 }
+unsafe impl PrimNumber for f32 {}
 impl crate::sealed::Sealed for f64 {}
 unsafe impl PrimFloat for f64 {
     const FLOAT_SIZE: FloatSize = FloatSize::Double;
 }
+unsafe impl PrimNumber for f64 {}
 
 // Builtin support for the never type
+#[cfg(feature = "never")]
 impl_primitive!(! => TypeInfo::Never);
 
 /// Support [StaticReflect] for [ManuallyDrop] by just representing the inner type
@@ -55,6 +60,13 @@ unsafe impl<T: StaticReflect> StaticReflect for ManuallyDrop<T> {
     };
 }
 
+/// Support [StaticReflect] for [PhantomData] as a zero-sized unit
+///
+/// Like pointers, the marked type doesn't need to implement [StaticReflect].
+unsafe impl<T: ?Sized> StaticReflect for PhantomData<T> {
+    const TYPE_INFO: TypeInfo = TypeInfo::Unit;
+}
+
 /// A pointer
 ///
 /// NOTE: The pointed-to value can be anything,
@@ -64,39 +76,41 @@ unsafe impl<T: StaticReflect> StaticReflect for ManuallyDrop<T> {
 /// doesn't maintain
 /// information about pointers (to avoid cycles).
 unsafe impl<T> StaticReflect for *mut T {
-    const TYPE_INFO: TypeInfo = TypeInfo::Pointer;
+    const TYPE_INFO: TypeInfo = TypeInfo::MUT_POINTER;
 }
 /// An immutable pointer
 ///
-/// The static reflection system makes no distinction between
-/// mutable and immutable pointers.
+/// Only the mutability is recorded, so this is distinct from `*mut T`
+/// (even though it has the same layout).
 unsafe impl<T> StaticReflect for *const T {
-    const TYPE_INFO: TypeInfo = TypeInfo::Pointer;
+    const TYPE_INFO: TypeInfo = TypeInfo::CONST_POINTER;
 }
 
 unsafe impl<T> SimpleNonZeroRepr for NonNull<T> {}
 unsafe impl<T> StaticReflect for NonNull<T> {
-    const TYPE_INFO: TypeInfo = TypeInfo::Pointer;
+    const TYPE_INFO: TypeInfo = TypeInfo::NonZero(&TypeInfo::MUT_POINTER);
 }
 unsafe impl SimpleNonZeroRepr for NonZeroUsize {}
 unsafe impl StaticReflect for NonZeroUsize {
-    const TYPE_INFO: TypeInfo = <usize as StaticReflect>::TYPE_INFO;
+    const TYPE_INFO: TypeInfo = TypeInfo::NonZero(&<usize as StaticReflect>::TYPE_INFO);
 }
 unsafe impl SimpleNonZeroRepr for NonZeroU32 {}
 unsafe impl StaticReflect for NonZeroU32 {
-    const TYPE_INFO: TypeInfo = u32::TYPE_INFO;
+    const TYPE_INFO: TypeInfo = TypeInfo::NonZero(&u32::TYPE_INFO);
 }
 unsafe impl SimpleNonZeroRepr for NonZeroU8 {}
 unsafe impl StaticReflect for NonZeroU8 {
-    const TYPE_INFO: TypeInfo = u8::TYPE_INFO;
+    const TYPE_INFO: TypeInfo = TypeInfo::NonZero(&u8::TYPE_INFO);
 }
 unsafe impl SimpleNonZeroRepr for NonZeroI32 {}
 unsafe impl StaticReflect for NonZeroI32 {
-    const TYPE_INFO: TypeInfo = i32::TYPE_INFO;
+    const TYPE_INFO: TypeInfo = TypeInfo::NonZero(&i32::TYPE_INFO);
 }
 
+/// An option using the niche of a non-zero type
+///
+/// This has the same representation as the wrapped type,
+/// except that zero (or null) is `None`. See [NicheDef::option].
 unsafe impl<T: SimpleNonZeroRepr> StaticReflect for Option<T> {
-    /// We have the representation as our internals,
-    /// except for the fact we might be null
-    const TYPE_INFO: TypeInfo = T::TYPE_INFO;
+    const TYPE_INFO: TypeInfo = TypeInfo::Niche(&NicheDef::option(&T::TYPE_INFO));
 }
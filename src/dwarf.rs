@@ -0,0 +1,386 @@
+//! Converting reflected types into DWARF debug info, using [gimli]
+//!
+//! This is useful for JIT compilers, which need to describe the types of their generated code
+//! to a debugger (for example, by registering an in-memory object file with GDB).
+//!
+//! Types are added to a [DwarfTypes] builder, which emits one debugging information entry
+//! for each distinct type and returns its [UnitEntryId].
+//! The populated [DwarfUnit] can then be taken with [DwarfTypes::into_unit],
+//! to be written out or merged with the rest of the caller's debug info.
+//!
+//! Since pointers are untyped, they are always emitted as pointers to `void`.
+//! Tagged unions, builtin optionals, generic parameters and magic types have no DWARF equivalent,
+//! and are rejected with a [DwarfError].
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use gimli::constants::{self, DwAte};
+use gimli::write::{AttributeValue, DwarfUnit, UnitEntryId};
+use gimli::{Encoding, Format};
+
+use crate::funcs::SignatureDef;
+use crate::types::{CStyleEnumDef, IntSize, StructureDef, TypeInfo, UntaggedUnionDef};
+
+#[doc(no_inline)]
+pub use gimli;
+
+/// An error indicating that a type has no DWARF equivalent
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DwarfError {
+    /// The name of the unsupported type
+    pub type_name: String,
+}
+impl DwarfError {
+    fn new(info: &TypeInfo) -> DwarfError {
+        DwarfError {
+            type_name: info.to_string(),
+        }
+    }
+}
+impl Display for DwarfError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Type can't be represented in DWARF: {}", self.type_name)
+    }
+}
+impl std::error::Error for DwarfError {}
+
+/// Builds the DWARF entries for reflected types, inside a single unit
+///
+/// Each distinct type is only emitted once, no matter how often it's added
+/// (or referenced by other types).
+#[derive(Debug)]
+pub struct DwarfTypes {
+    dwarf: DwarfUnit,
+    types: HashMap<TypeInfo, UnitEntryId>,
+    signatures: HashMap<SignatureDef, UnitEntryId>,
+}
+impl DwarfTypes {
+    /// Create a new unit, using the native pointer size and DWARF 5
+    pub fn new() -> DwarfTypes {
+        DwarfTypes::with_encoding(Encoding {
+            address_size: IntSize::POINTER.bytes() as u8,
+            format: Format::Dwarf32,
+            version: 5,
+        })
+    }
+    /// Create a new unit with the specified encoding
+    pub fn with_encoding(encoding: Encoding) -> DwarfTypes {
+        DwarfTypes::from_unit(DwarfUnit::new(encoding))
+    }
+    /// Add types to an existing unit
+    ///
+    /// The entries are added to the root of the unit.
+    pub fn from_unit(dwarf: DwarfUnit) -> DwarfTypes {
+        DwarfTypes {
+            dwarf,
+            types: HashMap::new(),
+            signatures: HashMap::new(),
+        }
+    }
+    /// The unit containing the emitted types
+    #[inline]
+    pub fn unit(&self) -> &DwarfUnit {
+        &self.dwarf
+    }
+    /// Take the unit containing the emitted types
+    #[inline]
+    pub fn into_unit(self) -> DwarfUnit {
+        self.dwarf
+    }
+    /// Add the specified type, returning its entry
+    pub fn add_type(&mut self, info: &TypeInfo) -> Result<UnitEntryId, DwarfError> {
+        if let Some(&id) = self.types.get(info) {
+            return Ok(id);
+        }
+        let id = match *info {
+            TypeInfo::Unit => self.unspecified("()"),
+            #[cfg(feature = "never")]
+            TypeInfo::Never => self.unspecified("!"),
+            TypeInfo::Bool => self.base_type("bool", constants::DW_ATE_boolean, 1),
+            TypeInfo::Integer(int_type) => self.base_type(
+                int_type.rust_name(),
+                if int_type.signed {
+                    constants::DW_ATE_signed
+                } else {
+                    constants::DW_ATE_unsigned
+                },
+                int_type.size.bytes(),
+            ),
+            TypeInfo::Float { size } => {
+                self.base_type(&info.to_string(), constants::DW_ATE_float, size.bytes())
+            }
+            TypeInfo::Vector {
+                element,
+                lanes,
+                alignment,
+            } => {
+                let element = self.add_type(element.type_info())?;
+                let id = self.entry(constants::DW_TAG_array_type, None);
+                let entry = self.dwarf.unit.get_mut(id);
+                entry.set(constants::DW_AT_GNU_vector, AttributeValue::FlagPresent);
+                entry.set(constants::DW_AT_type, AttributeValue::UnitRef(element));
+                entry.set(constants::DW_AT_byte_size, udata(info.size()));
+                entry.set(constants::DW_AT_alignment, udata(alignment));
+                let range = self.dwarf.unit.add(id, constants::DW_TAG_subrange_type);
+                self.dwarf
+                    .unit
+                    .get_mut(range)
+                    .set(constants::DW_AT_count, udata(lanes));
+                id
+            }
+            #[cfg(feature = "builtins")]
+            TypeInfo::Slice { element_type } => {
+                let element = self.add_type(element_type)?;
+                let ptr = self.pointer(true, Some(element));
+                self.ptr_len(&info.to_string(), ptr)?
+            }
+            #[cfg(feature = "builtins")]
+            TypeInfo::Str => {
+                let byte = self.add_type(&TypeInfo::Integer(crate::types::IntType::U8))?;
+                let ptr = self.pointer(true, Some(byte));
+                self.ptr_len("str", ptr)?
+            }
+            TypeInfo::Pointer { mutable } => self.pointer(mutable, None),
+            // DWARF can't express the invalid values
+            TypeInfo::NonZero(inner) => return self.add_type(inner),
+            TypeInfo::Structure(def) => self.add_structure(def)?,
+            TypeInfo::UntaggedUnion(def) => self.add_union(def)?,
+            TypeInfo::CStyleEnum(def) => self.add_c_style_enum(def)?,
+            TypeInfo::Niche(def) => {
+                let wrapped = self.add_type(def.wrapped)?;
+                let id = self.entry(constants::DW_TAG_typedef, Some(def.name));
+                self.dwarf
+                    .unit
+                    .get_mut(id)
+                    .set(constants::DW_AT_type, AttributeValue::UnitRef(wrapped));
+                id
+            }
+            TypeInfo::Extern { name } => {
+                let id = self.entry(constants::DW_TAG_structure_type, Some(name));
+                self.dwarf
+                    .unit
+                    .get_mut(id)
+                    .set(constants::DW_AT_declaration, AttributeValue::Flag(true));
+                id
+            }
+            #[cfg(feature = "builtins")]
+            TypeInfo::Optional(_) => return Err(DwarfError::new(info)),
+            TypeInfo::TaggedUnion(_) | TypeInfo::TypeParameter { .. } | TypeInfo::Magic { .. } => {
+                return Err(DwarfError::new(info))
+            }
+        };
+        self.types.insert(*info, id);
+        Ok(id)
+    }
+    /// Add a structure, with a member for each of its fields
+    ///
+    /// Fields without a name (in tuple structs) are named by their index, like `__0`.
+    /// An `opaque_array` field is emitted as an array of its element type,
+    /// without a length (since only the element type is reflected).
+    pub fn add_structure(&mut self, def: &'static StructureDef) -> Result<UnitEntryId, DwarfError> {
+        let key = TypeInfo::Structure(def);
+        if let Some(&id) = self.types.get(&key) {
+            return Ok(id);
+        }
+        let mut members = Vec::with_capacity(def.fields.len());
+        for field in def.fields {
+            let mut member_type = self.add_type(field.value_type.type_ref())?;
+            if field.opaque_array {
+                member_type = self.array(member_type);
+            }
+            let name = match field.name {
+                Some(name) => name.to_owned(),
+                None => format!("__{}", field.index),
+            };
+            members.push((name, member_type, field.offset));
+        }
+        let id = self.entry(constants::DW_TAG_structure_type, Some(def.name));
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_byte_size, udata(def.size));
+        entry.set(constants::DW_AT_alignment, udata(def.alignment));
+        for (name, member_type, offset) in members {
+            self.member(id, name.as_bytes(), member_type, offset);
+        }
+        self.types.insert(key, id);
+        Ok(id)
+    }
+    fn add_union(&mut self, def: &'static UntaggedUnionDef) -> Result<UnitEntryId, DwarfError> {
+        let mut members = Vec::with_capacity(def.fields.len());
+        for field in def.fields {
+            members.push((field.name, self.add_type(field.value_type.type_ref())?));
+        }
+        let id = self.entry(constants::DW_TAG_union_type, Some(def.name));
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_byte_size, udata(def.size));
+        entry.set(constants::DW_AT_alignment, udata(def.alignment));
+        for (name, member_type) in members {
+            self.member(id, name.as_bytes(), member_type, 0);
+        }
+        Ok(id)
+    }
+    /// Add a C-style enum, with an enumerator for each of its variants
+    pub fn add_c_style_enum(
+        &mut self,
+        def: &'static CStyleEnumDef,
+    ) -> Result<UnitEntryId, DwarfError> {
+        let key = TypeInfo::CStyleEnum(def);
+        if let Some(&id) = self.types.get(&key) {
+            return Ok(id);
+        }
+        let discriminant = self.add_type(&TypeInfo::Integer(def.discriminant))?;
+        let id = self.entry(constants::DW_TAG_enumeration_type, Some(def.name));
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_type, AttributeValue::UnitRef(discriminant));
+        entry.set(
+            constants::DW_AT_byte_size,
+            udata(def.discriminant.size.bytes()),
+        );
+        entry.set(constants::DW_AT_enum_class, AttributeValue::FlagPresent);
+        for variant in def.variants {
+            let value = def.discriminant_value(variant);
+            let value = if def.discriminant.signed {
+                AttributeValue::Sdata(value as i64)
+            } else {
+                AttributeValue::Udata(value as u64)
+            };
+            let enumerator = self.dwarf.unit.add(id, constants::DW_TAG_enumerator);
+            let enumerator = self.dwarf.unit.get_mut(enumerator);
+            enumerator.set(constants::DW_AT_name, string(variant.name));
+            enumerator.set(constants::DW_AT_const_value, value);
+        }
+        self.types.insert(key, id);
+        Ok(id)
+    }
+    /// Add the type of a function with the specified signature
+    ///
+    /// Functions returning the unit type are emitted without a return type (like `void`).
+    pub fn add_signature(&mut self, signature: &SignatureDef) -> Result<UnitEntryId, DwarfError> {
+        if let Some(&id) = self.signatures.get(signature) {
+            return Ok(id);
+        }
+        let return_type = match *signature.return_type {
+            TypeInfo::Unit => None,
+            ref other => Some(self.add_type(other)?),
+        };
+        let argument_types = signature
+            .argument_types
+            .iter()
+            .map(|arg| self.add_type(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let id = self.entry(constants::DW_TAG_subroutine_type, None);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_prototyped, AttributeValue::Flag(true));
+        if let Some(return_type) = return_type {
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(return_type));
+        }
+        for arg in argument_types {
+            let param = self.dwarf.unit.add(id, constants::DW_TAG_formal_parameter);
+            self.dwarf
+                .unit
+                .get_mut(param)
+                .set(constants::DW_AT_type, AttributeValue::UnitRef(arg));
+        }
+        self.signatures.insert(*signature, id);
+        Ok(id)
+    }
+
+    /// Add an entry to the root of the unit
+    fn entry(&mut self, tag: constants::DwTag, name: Option<&str>) -> UnitEntryId {
+        let root = self.dwarf.unit.root();
+        let id = self.dwarf.unit.add(root, tag);
+        if let Some(name) = name {
+            self.dwarf
+                .unit
+                .get_mut(id)
+                .set(constants::DW_AT_name, string(name));
+        }
+        id
+    }
+    fn unspecified(&mut self, name: &str) -> UnitEntryId {
+        self.entry(constants::DW_TAG_unspecified_type, Some(name))
+    }
+    fn base_type(&mut self, name: &str, encoding: DwAte, size: usize) -> UnitEntryId {
+        let id = self.entry(constants::DW_TAG_base_type, Some(name));
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(
+            constants::DW_AT_encoding,
+            AttributeValue::Encoding(encoding),
+        );
+        entry.set(constants::DW_AT_byte_size, udata(size));
+        id
+    }
+    /// A pointer to the specified type, or to `void` if it's unknown
+    fn pointer(&mut self, mutable: bool, target: Option<UnitEntryId>) -> UnitEntryId {
+        let target = if mutable {
+            target
+        } else {
+            let id = self.entry(constants::DW_TAG_const_type, None);
+            if let Some(target) = target {
+                self.dwarf
+                    .unit
+                    .get_mut(id)
+                    .set(constants::DW_AT_type, AttributeValue::UnitRef(target));
+            }
+            Some(id)
+        };
+        let id = self.entry(constants::DW_TAG_pointer_type, None);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_byte_size, udata(IntSize::POINTER.bytes()));
+        if let Some(target) = target {
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(target));
+        }
+        id
+    }
+    /// An array of the specified element, with an unknown length
+    fn array(&mut self, element: UnitEntryId) -> UnitEntryId {
+        let id = self.entry(constants::DW_TAG_array_type, None);
+        self.dwarf
+            .unit
+            .get_mut(id)
+            .set(constants::DW_AT_type, AttributeValue::UnitRef(element));
+        self.dwarf.unit.add(id, constants::DW_TAG_subrange_type);
+        id
+    }
+    /// A structure with a pointer and a length, like [AsmSlice](crate::builtins::AsmSlice)
+    #[cfg(feature = "builtins")]
+    fn ptr_len(&mut self, name: &str, ptr: UnitEntryId) -> Result<UnitEntryId, DwarfError> {
+        let len = self.add_type(&TypeInfo::Integer(crate::types::IntType::USIZE))?;
+        let word = IntSize::POINTER.bytes();
+        let id = self.entry(constants::DW_TAG_structure_type, Some(name));
+        self.dwarf
+            .unit
+            .get_mut(id)
+            .set(constants::DW_AT_byte_size, udata(word * 2));
+        self.member(id, b"ptr", ptr, 0);
+        self.member(id, b"len", len, word);
+        Ok(id)
+    }
+    fn member(
+        &mut self,
+        parent: UnitEntryId,
+        name: &[u8],
+        member_type: UnitEntryId,
+        offset: usize,
+    ) {
+        let id = self.dwarf.unit.add(parent, constants::DW_TAG_member);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(constants::DW_AT_name, AttributeValue::String(name.to_vec()));
+        entry.set(constants::DW_AT_type, AttributeValue::UnitRef(member_type));
+        entry.set(constants::DW_AT_data_member_location, udata(offset));
+    }
+}
+impl Default for DwarfTypes {
+    #[inline]
+    fn default() -> Self {
+        DwarfTypes::new()
+    }
+}
+
+#[inline]
+fn udata(value: usize) -> AttributeValue {
+    AttributeValue::Udata(value as u64)
+}
+#[inline]
+fn string(value: &str) -> AttributeValue {
+    AttributeValue::String(value.as_bytes().to_vec())
+}
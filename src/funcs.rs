@@ -1,10 +1,56 @@
 //! Reflection information on function declarations
-use crate::types::TypeInfo;
+use crate::types::downcast::TypeMismatch;
+use crate::types::ffi::FfiSafety;
+use crate::types::{FloatSize, IntSize, IntType, PointerDiscipline, SourceLocation, TypeInfo};
+use crate::StaticReflect;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "builtins")]
+pub mod coerce;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod link;
+#[cfg(feature = "builtins")]
+pub mod marshal;
+#[cfg(feature = "register")]
+pub mod registry;
+
+/// Iterate over all the functions marked `#[reflect_func(register)]`
+///
+/// See the [registry] module for details.
+#[cfg(feature = "register")]
+#[inline]
+pub fn registry() -> registry::Iter {
+    registry::iter()
+}
+
+/// Resolve every dynamically linked declaration with the specified resolver,
+/// reporting all the declarations that can't be called
+///
+/// The resolver looks up a symbol in the loaded library (returning `None` if it's missing).
+/// It's called at most once for each symbol.
+///
+/// See the [link] module for details.
+pub fn link_check(
+    decls: &[&'static FunctionDeclaration],
+    resolve: impl Fn(&str) -> Option<*const ()>,
+) -> link::LinkReport {
+    link::link_check(decls, resolve)
+}
+
 /// The declaration of a function whose information
 /// is known to the static reflection system
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// This is `#[repr(C)]` so that every instantiation has the same layout,
+/// which [FunctionDeclaration::erase] relies on.
+///
+/// The standard traits are implemented regardless of the type parameters,
+/// which are only markers.
+#[repr(C)]
 pub struct FunctionDeclaration<R = (), Args = ()> {
     /// The name of the function, as declared in the
     /// source code.
@@ -15,6 +61,15 @@ pub struct FunctionDeclaration<R = (), Args = ()> {
     ///
     /// Not all supported functions have a known location.
     pub location: Option<FunctionLocation>,
+    /// Whether the function may unwind (panic) across the FFI boundary
+    pub unwind: UnwindBehavior,
+    /// Whether the function may be called from multiple threads at once
+    pub thread_safety: ThreadSafety,
+    /// Where the function was declared, if known
+    ///
+    /// This is only meant for diagnostics,
+    /// so it's ignored when comparing (or hashing) declarations.
+    pub source: Option<SourceLocation>,
     /// The signature of the function, including
     /// its arguments and return types
     ///
@@ -25,6 +80,74 @@ pub struct FunctionDeclaration<R = (), Args = ()> {
     /// PhantomData: The argument types of the function
     pub arg_types: PhantomData<fn(Args) -> ()>,
 }
+impl<R, Args> Copy for FunctionDeclaration<R, Args> {}
+impl<R, Args> Clone for FunctionDeclaration<R, Args> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<R, Args> PartialEq for FunctionDeclaration<R, Args> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.is_unsafe == other.is_unsafe
+            && self.location == other.location
+            && self.unwind == other.unwind
+            && self.thread_safety == other.thread_safety
+            && self.signature == other.signature
+    }
+}
+impl<R, Args> Eq for FunctionDeclaration<R, Args> {}
+impl<R, Args> Hash for FunctionDeclaration<R, Args> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.is_unsafe.hash(state);
+        self.location.hash(state);
+        self.unwind.hash(state);
+        self.thread_safety.hash(state);
+        self.signature.hash(state);
+    }
+}
+impl<R, Args> Debug for FunctionDeclaration<R, Args> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionDeclaration")
+            .field("name", &self.name)
+            .field("is_unsafe", &self.is_unsafe)
+            .field("location", &self.location)
+            .field("unwind", &self.unwind)
+            .field("thread_safety", &self.thread_safety)
+            .field("source", &self.source)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+/// Displays the signature of the function, like `unsafe fn name(i32) -> u64`,
+/// followed by where it was declared (if known)
+impl<R, Args> Display for FunctionDeclaration<R, Args> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_unsafe {
+            f.write_str("unsafe ")?;
+        }
+        write!(f, "fn {}(", self.name)?;
+        for (index, arg) in self.signature.argument_types.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", arg)?;
+            if let Some(unit) = self.signature.unit(index) {
+                write!(f, " [{}]", unit)?;
+            }
+        }
+        f.write_str(")")?;
+        if *self.signature.return_type != TypeInfo::Unit {
+            write!(f, " -> {}", self.signature.return_type)?;
+        }
+        if let Some(source) = self.source {
+            write!(f, " (declared at {})", source)?;
+        }
+        Ok(())
+    }
+}
 impl<R, Args> FunctionDeclaration<R, Args> {
     /// If the function has a known location at runtime
     ///
@@ -35,23 +158,493 @@ impl<R, Args> FunctionDeclaration<R, Args> {
     pub fn has_known_location(&self) -> bool {
         self.location.is_some()
     }
+    /// If calls to the function may unwind,
+    /// so that callers need to be prepared to catch the unwinding
+    ///
+    /// This is conservatively true if the behavior is [UnwindBehavior::Unknown].
+    #[inline]
+    pub fn may_unwind(&self) -> bool {
+        self.unwind.may_unwind()
+    }
+    /// Check that the function is documented to be thread-safe
+    ///
+    /// This fails if its [ThreadSafety] is unknown, not just if it's known to be unsafe.
+    #[inline]
+    pub fn assert_thread_safe(&self) -> Result<(), NotThreadSafe> {
+        match self.thread_safety {
+            ThreadSafety::Safe => Ok(()),
+            thread_safety => Err(NotThreadSafe {
+                name: self.name,
+                thread_safety,
+            }),
+        }
+    }
+    /// The information needed to resolve the function at runtime,
+    /// if it's dynamically linked
+    ///
+    /// A plugin loader can use this to `dlopen` the right library
+    /// before looking up the symbol.
+    #[inline]
+    pub fn resolution_hint(&self) -> Option<ResolutionHint> {
+        match self.location {
+            Some(FunctionLocation::DynamicallyLinked {
+                link_name,
+                library,
+                weak,
+            }) => Some(ResolutionHint {
+                symbol: link_name.unwrap_or(self.name),
+                library,
+                weak,
+            }),
+            Some(FunctionLocation::AbsoluteAddress(_)) | None => None,
+        }
+    }
     /// Erase all statically known type information
     #[inline]
     pub fn erase(&'static self) -> &'static FunctionDeclaration<(), ()> {
+        // SAFETY: The type parameters only appear in `PhantomData`,
+        // and the layout is independent of them since the struct is `#[repr(C)]`
         unsafe { &*(self as *const Self as *const FunctionDeclaration<(), ()>) }
     }
+    /// Erase all statically known type information, returning a copy
+    ///
+    /// Unlike [FunctionDeclaration::erase], this can be used in a const context.
+    #[inline]
+    pub const fn into_erased(self) -> FunctionDeclaration<(), ()> {
+        FunctionDeclaration {
+            name: self.name,
+            is_unsafe: self.is_unsafe,
+            location: self.location,
+            unwind: self.unwind,
+            thread_safety: self.thread_safety,
+            source: self.source,
+            signature: self.signature,
+            return_type: PhantomData,
+            arg_types: PhantomData,
+        }
+    }
+    /// Reinterpret this declaration with the specified return and argument types,
+    /// checking that they match its signature
+    ///
+    /// This is the checked inverse of [FunctionDeclaration::erase].
+    pub fn downcast<R2: StaticReflect, Args2: ArgumentTypes>(
+        self,
+    ) -> Result<FunctionDeclaration<R2, Args2>, TypeMismatch> {
+        if *self.signature.return_type == R2::TYPE_INFO
+            && self.signature.argument_types == Args2::TYPES
+        {
+            Ok(FunctionDeclaration {
+                name: self.name,
+                is_unsafe: self.is_unsafe,
+                location: self.location,
+                unwind: self.unwind,
+                thread_safety: self.thread_safety,
+                source: self.source,
+                signature: self.signature,
+                return_type: PhantomData,
+                arg_types: PhantomData,
+            })
+        } else {
+            Err(TypeMismatch {
+                expected: SignatureDef::of::<R2, Args2>().to_string(),
+                actual: self.signature.to_string(),
+            })
+        }
+    }
+    /// Check if this function can be called through the expected signature
+    ///
+    /// This is the same as [SignatureDef::matches_signature],
+    /// so the directions, contracts and units of the arguments are ignored.
+    #[inline]
+    pub fn signature_matches(&self, expected: &SignatureDef) -> bool {
+        self.signature.matches_signature(expected)
+    }
+}
+
+/// A thunk calling a reflected function with type-erased arguments
+///
+/// The arguments are passed as a pointer to each value, in the same form as
+/// an [ArgBuffer](marshal::ArgBuffer), and the result is written through the second pointer.
+/// `#[reflect_func]` generates one for each (non-generic) function defined in Rust,
+/// as the hidden constant `_INVOKE_name`. Foreign functions don't have one.
+///
+/// The thunk panics if the number of arguments is wrong.
+///
+/// ## Safety
+/// Each pointer must point to a valid value of the corresponding argument type,
+/// which is moved out of it. The result pointer must be valid for writes of the return type
+/// (and properly aligned), unless it's zero-sized.
+/// Calling the function must also be safe.
+pub type Invoker = unsafe fn(args: &[*mut std::ffi::c_void], ret: *mut std::ffi::c_void);
+
+/// A tuple of argument types, whose reflected information is statically known
+///
+/// This is implemented for tuples of [StaticReflect] types (up to 12 elements),
+/// which are used as the `Args` parameter of a [FunctionDeclaration].
+pub trait ArgumentTypes {
+    /// The reflected information of each argument
+    const TYPES: &'static [TypeInfo];
+    /// The direction of each argument, which are all inputs
+    const DIRECTIONS: &'static [ParamDirection];
+    /// The unit of each argument (see [StaticReflect::UNIT]),
+    /// which is empty if none of the arguments have a unit
+    const UNITS: &'static [Option<&'static str>];
+    /// The most severe [StaticReflect::FFI_SAFETY] of the arguments
+    const FFI_SAFETY: FfiSafety;
+}
+macro_rules! impl_argument_types {
+    ($($arg:ident),*) => {
+        impl<$($arg: StaticReflect),*> ArgumentTypes for ($($arg,)*) {
+            const TYPES: &'static [TypeInfo] = &[$($arg::TYPE_INFO),*];
+            const DIRECTIONS: &'static [ParamDirection] = &[$(input_direction!($arg)),*];
+            const UNITS: &'static [Option<&'static str>] = if $($arg::UNIT.is_none() &&)* true {
+                &[]
+            } else {
+                &[$($arg::UNIT),*]
+            };
+            const FFI_SAFETY: FfiSafety = FfiSafety::all(&[$($arg::FFI_SAFETY),*]);
+        }
+    };
 }
+macro_rules! input_direction {
+    ($arg:ident) => {
+        ParamDirection::In
+    };
+}
+impl_argument_types!();
+impl_argument_types!(A);
+impl_argument_types!(A, B);
+impl_argument_types!(A, B, C);
+impl_argument_types!(A, B, C, D);
+impl_argument_types!(A, B, C, D, E);
+impl_argument_types!(A, B, C, D, E, F);
+impl_argument_types!(A, B, C, D, E, F, G);
+impl_argument_types!(A, B, C, D, E, F, G, H);
+impl_argument_types!(A, B, C, D, E, F, G, H, I);
+impl_argument_types!(A, B, C, D, E, F, G, H, I, J);
+impl_argument_types!(A, B, C, D, E, F, G, H, I, J, K);
+impl_argument_types!(A, B, C, D, E, F, G, H, I, J, K, L);
 /// The definition of a function's signature
 ///
 /// Includes its argument types, return type, and calling convention.
+/// A signature can be constructed on its own with [SignatureDef::of],
+/// to check indirect calls without any [FunctionDeclaration].
+///
+/// The [Default] is the signature of `extern "C" fn()`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SignatureDef {
     /// A list of argument types to the function
     pub argument_types: &'static [TypeInfo],
+    /// The direction of each argument,
+    /// corresponding to the [SignatureDef::argument_types].
+    ///
+    /// This is purely informational, and has no effect on the ABI.
+    pub argument_directions: &'static [ParamDirection],
+    /// The contract of each argument (its preconditions),
+    /// corresponding to the [SignatureDef::argument_types].
+    ///
+    /// This is empty if none of the arguments have a contract.
+    /// Use [SignatureDef::contract] to look up the contract of an argument either way.
+    pub argument_contracts: &'static [ArgContract],
+    /// The semantic unit of each integer argument (like `"milliseconds"`),
+    /// corresponding to the [SignatureDef::argument_types]
+    ///
+    /// This is given by `#[reflect(unit = "...")]`, or by the [StaticReflect::UNIT] of the argument's type.
+    /// Like the contracts, it's empty if none of the arguments have a unit.
+    /// Use [SignatureDef::unit] to look up the unit of an argument either way.
+    pub argument_units: &'static [Option<&'static str>],
     /// The return type of the function
     pub return_type: &'static TypeInfo,
     /// The calling convention
     pub calling_convention: CallingConvention,
+    /// Whether the arguments and return type can be passed across an `extern "C"` boundary
+    ///
+    /// This is the most severe [StaticReflect::FFI_SAFETY] of the argument types and return type
+    /// (see [crate::types::ffi]). Like the directions, it's purely informational.
+    pub ffi_safety: FfiSafety,
+}
+/// Displays the signature like a function pointer type, `fn(u32, f64) -> i32`
+impl Display for SignatureDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("fn(")?;
+        for (index, arg) in self.argument_types.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", arg)?;
+        }
+        f.write_str(")")?;
+        if *self.return_type != TypeInfo::Unit {
+            write!(f, " -> {}", self.return_type)?;
+        }
+        Ok(())
+    }
+}
+impl Default for SignatureDef {
+    #[inline]
+    fn default() -> Self {
+        SignatureDef {
+            argument_types: &[],
+            argument_directions: &[],
+            argument_contracts: &[],
+            argument_units: &[],
+            return_type: &TypeInfo::Unit,
+            calling_convention: CallingConvention::default(),
+            ffi_safety: FfiSafety::Safe,
+        }
+    }
+}
+impl SignatureDef {
+    /// The signature of an `extern "C"` function with the specified return and argument types,
+    /// like `SignatureDef::of::<f64, (f64, f64)>()`
+    ///
+    /// This is useful for checking indirect calls, where the expected signature is known
+    /// but there isn't any declaration of the function being called.
+    /// Every argument is an input without a contract,
+    /// and the units are the [StaticReflect::UNIT] of the argument types.
+    /// The FFI safety combines the [StaticReflect::FFI_SAFETY] of the argument types and return type.
+    ///
+    /// ## Example
+    /// ````
+    /// # use static_reflect::funcs::SignatureDef;
+    /// # use static_reflect::TypeInfo;
+    /// const HYPOT: SignatureDef = SignatureDef::of::<f64, (f64, f64)>();
+    /// assert_eq!(HYPOT.argument_types, &[TypeInfo::F64, TypeInfo::F64]);
+    /// assert_eq!(HYPOT.to_string(), "fn(f64, f64) -> f64");
+    /// assert!(HYPOT.matches_signature(&SignatureDef::of::<f64, (f64, f64)>()));
+    /// assert!(!HYPOT.matches_signature(&SignatureDef::of::<f32, (f32, f32)>()));
+    /// ````
+    pub const fn of<R: StaticReflect, Args: ArgumentTypes>() -> SignatureDef {
+        SignatureDef {
+            argument_types: Args::TYPES,
+            argument_directions: Args::DIRECTIONS,
+            argument_contracts: &[],
+            argument_units: Args::UNITS,
+            return_type: &R::TYPE_INFO,
+            calling_convention: CallingConvention::StandardC,
+            ffi_safety: Args::FFI_SAFETY.or(R::FFI_SAFETY),
+        }
+    }
+    /// Check if a function with this signature can be called through the other signature
+    ///
+    /// The argument types, return type and calling convention must all be the same.
+    /// Unlike the [PartialEq] implementation, the purely informational parts of the signatures
+    /// (the directions, contracts and units of the arguments, and the FFI safety) are ignored.
+    pub fn matches_signature(&self, other: &SignatureDef) -> bool {
+        self.argument_types == other.argument_types
+            && self.return_type == other.return_type
+            && self.calling_convention == other.calling_convention
+    }
+    /// The contract of the argument with the specified index
+    ///
+    /// Arguments without a contract have the default (unconstrained) one.
+    /// Returns `None` if there is no such argument.
+    pub fn contract(&self, index: usize) -> Option<ArgContract> {
+        if index >= self.argument_types.len() {
+            return None;
+        }
+        Some(
+            self.argument_contracts
+                .get(index)
+                .copied()
+                .unwrap_or_default(),
+        )
+    }
+    /// The unit of the argument with the specified index, if it has one
+    ///
+    /// Returns `None` if there is no such argument.
+    #[inline]
+    pub fn unit(&self, index: usize) -> Option<&'static str> {
+        self.argument_units.get(index).copied().flatten()
+    }
+}
+
+/// A Rust closure, exposed to C code through a "trampoline" function.
+///
+/// The trampoline takes a pointer to the closure's context as its first argument,
+/// followed by the closure's actual arguments:
+/// `extern "C" fn(ctx: *mut c_void, args...) -> ret`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClosureDecl {
+    /// The signature visible to callers of the closure,
+    /// excluding the context pointer
+    pub signature: SignatureDef,
+    /// The trampoline function, which takes the context as its first argument
+    pub trampoline: &'static FunctionDeclaration,
+    /// The type of the context (which the trampoline's first argument points to)
+    pub context_type: &'static TypeInfo,
+}
+impl ClosureDecl {
+    /// Combine the parts of a closure,
+    /// checking the trampoline is consistent with the signature.
+    ///
+    /// The trampoline's signature must be equal to the closure's
+    /// signature, with a leading pointer for the context.
+    pub fn from_parts(
+        signature: SignatureDef,
+        trampoline: &'static FunctionDeclaration,
+        context_type: &'static TypeInfo,
+    ) -> Result<ClosureDecl, ClosureError> {
+        let decl = ClosureDecl {
+            signature,
+            trampoline,
+            context_type,
+        };
+        decl.validate()?;
+        Ok(decl)
+    }
+    /// Create a closure from its trampoline,
+    /// with the signature implied by the trampoline's remaining arguments.
+    pub fn from_trampoline(
+        trampoline: &'static FunctionDeclaration,
+        context_type: &'static TypeInfo,
+    ) -> Result<ClosureDecl, ClosureError> {
+        let full = &trampoline.signature;
+        if full.argument_types.is_empty() {
+            return Err(ClosureError::MissingContext);
+        }
+        let signature = SignatureDef {
+            argument_types: &full.argument_types[1..],
+            argument_directions: full.argument_directions.get(1..).unwrap_or(&[]),
+            argument_contracts: full.argument_contracts.get(1..).unwrap_or(&[]),
+            argument_units: full.argument_units.get(1..).unwrap_or(&[]),
+            return_type: full.return_type,
+            calling_convention: full.calling_convention,
+            // The context is a pointer, which is always safe
+            ffi_safety: full.ffi_safety,
+        };
+        ClosureDecl::from_parts(signature, trampoline, context_type)
+    }
+    /// Check the trampoline is consistent with the closure's signature
+    pub fn validate(&self) -> Result<(), ClosureError> {
+        let full = &self.trampoline.signature;
+        match full.argument_types.first() {
+            Some(context) if matches!(context.nullable(), TypeInfo::Pointer { .. }) => {}
+            _ => return Err(ClosureError::MissingContext),
+        }
+        if full.argument_types[1..] != *self.signature.argument_types
+            || full.argument_directions.get(1..) != Some(self.signature.argument_directions)
+            || (0..self.signature.argument_types.len()).any(|index| {
+                full.contract(index + 1) != self.signature.contract(index)
+                    || full.unit(index + 1) != self.signature.unit(index)
+            })
+        {
+            return Err(ClosureError::MismatchedArguments);
+        }
+        if full.return_type != self.signature.return_type {
+            return Err(ClosureError::MismatchedReturnType);
+        }
+        if full.calling_convention != self.signature.calling_convention {
+            return Err(ClosureError::MismatchedCallingConvention);
+        }
+        Ok(())
+    }
+}
+/// An error indicating that a [ClosureDecl]'s trampoline
+/// is inconsistent with its signature
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClosureError {
+    /// The trampoline doesn't take a context pointer as its first argument
+    MissingContext,
+    /// The trampoline's remaining arguments differ from the closure's
+    MismatchedArguments,
+    /// The trampoline's return type differs from the closure's
+    MismatchedReturnType,
+    /// The trampoline's calling convention differs from the closure's
+    MismatchedCallingConvention,
+}
+impl Display for ClosureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            ClosureError::MissingContext => "Trampoline doesn't take a context pointer",
+            ClosureError::MismatchedArguments => "Trampoline has mismatched arguments",
+            ClosureError::MismatchedReturnType => "Trampoline has a mismatched return type",
+            ClosureError::MismatchedCallingConvention => {
+                "Trampoline has a mismatched calling convention"
+            }
+        })
+    }
+}
+impl std::error::Error for ClosureError {}
+
+/// The direction data flows through a parameter.
+///
+/// Functions using the C ABI often return their results
+/// by writing through pointer arguments ("out-parameters").
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ParamDirection {
+    /// A regular input parameter
+    #[default]
+    In,
+    /// A pointer that the function writes its result to,
+    /// without reading its initial value.
+    Out,
+    /// A pointer that the function both reads from and writes to
+    InOut,
+}
+
+/// The preconditions of an argument, which callers must uphold
+///
+/// This is purely informational (like the [ParamDirection]),
+/// and is recorded by `#[reflect(non_null)]`, `#[reflect(range(min, max))]`
+/// and `#[reflect(ptr = "...")]` parameter attributes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct ArgContract {
+    /// The argument is a pointer which must not be null
+    pub non_null: bool,
+    /// The argument is an integer which must be within the (inclusive) range
+    pub range: Option<(i128, i128)>,
+    /// The ownership discipline of a pointer argument, if documented
+    ///
+    /// For example, an [owned](PointerDiscipline::Owned) argument
+    /// transfers ownership of its target to the function.
+    pub pointer_discipline: Option<PointerDiscipline>,
+}
+impl ArgContract {
+    /// The contract of an argument without any preconditions
+    pub const UNCONSTRAINED: ArgContract = ArgContract {
+        non_null: false,
+        range: None,
+        pointer_discipline: None,
+    };
+    /// If the argument doesn't have any preconditions
+    #[inline]
+    pub fn is_unconstrained(&self) -> bool {
+        *self == ArgContract::UNCONSTRAINED
+    }
+    /// Check if an integer value satisfies the contract's range (if any)
+    #[inline]
+    pub fn allows_int(&self, value: i128) -> bool {
+        match self.range {
+            Some((min, max)) => min <= value && value <= max,
+            None => true,
+        }
+    }
+    /// Check if a pointer satisfies the contract's nullability
+    #[inline]
+    pub fn allows_ptr<T>(&self, ptr: *const T) -> bool {
+        !(self.non_null && ptr.is_null())
+    }
+}
+impl Display for ArgContract {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_unconstrained() {
+            return f.write_str("unconstrained");
+        }
+        let mut separator = "";
+        if let Some(discipline) = self.pointer_discipline {
+            write!(f, "{}", discipline)?;
+            separator = ", ";
+        }
+        if self.non_null {
+            write!(f, "{}non-null", separator)?;
+            separator = ", ";
+        }
+        if let Some((min, max)) = self.range {
+            write!(f, "{}{}..={}", separator, min, max)?;
+        }
+        Ok(())
+    }
 }
 
 /// The convention used to call code.
@@ -71,6 +664,148 @@ impl Default for CallingConvention {
         CallingConvention::StandardC
     }
 }
+impl CallingConvention {
+    /// The type an argument is actually passed as,
+    /// when it's part of the variadic tail of a call (like the arguments to `printf`)
+    ///
+    /// This applies C's default argument promotions:
+    /// - `f32` is passed as `f64`
+    /// - Integers narrower than `int` are passed as 32-bit integers, preserving their signedness
+    ///   (this also applies to non-zero integers and the discriminants of C-style enums)
+    /// - `bool` is passed as `i32`
+    ///
+    /// Every other type is passed unchanged.
+    /// Fixed arguments are never promoted, so this shouldn't be applied to them.
+    ///
+    /// This describes the ABI-level type of the argument, not a conversion of its value.
+    /// The caller is still responsible for widening the value
+    /// (zero-extending unsigned integers and sign-extending signed ones).
+    /// Since `int` can represent every value of a narrower type,
+    /// the promoted value is the same regardless of its signedness.
+    pub fn promote(self, info: &TypeInfo) -> TypeInfo {
+        match self {
+            CallingConvention::StandardC => match *info {
+                TypeInfo::Bool => TypeInfo::Integer(IntType::I32),
+                TypeInfo::Float {
+                    size: FloatSize::Single,
+                } => TypeInfo::Float {
+                    size: FloatSize::Double,
+                },
+                TypeInfo::Integer(tp) => TypeInfo::Integer(promote_int(tp)),
+                TypeInfo::NonZero(&TypeInfo::Integer(tp)) if promote_int(tp) != tp => {
+                    TypeInfo::Integer(promote_int(tp))
+                }
+                TypeInfo::CStyleEnum(def) if promote_int(def.discriminant) != def.discriminant => {
+                    TypeInfo::Integer(promote_int(def.discriminant))
+                }
+                other => other,
+            },
+        }
+    }
+}
+/// Promote an integer narrower than `int` to a 32-bit integer with the same signedness
+fn promote_int(tp: IntType) -> IntType {
+    if tp.size.bytes() < IntSize::Int.bytes() {
+        IntType {
+            size: IntSize::Int,
+            signed: tp.signed,
+        }
+    } else {
+        tp
+    }
+}
+
+/// What happens if a function panics (or otherwise unwinds)
+///
+/// Unwinding out of an `extern "C"` function aborts the process,
+/// while the `extern "C-unwind"` ABI allows it to propagate to the caller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum UnwindBehavior {
+    /// The function never unwinds into its caller
+    ///
+    /// This is the default for Rust functions defined as `extern "C"`,
+    /// where a panic aborts instead of unwinding.
+    Aborts,
+    /// The function may unwind into its caller
+    ///
+    /// This is the case for any function using the `extern "C-unwind"` ABI,
+    /// or which is marked with `#[reflect_func(may_unwind)]`.
+    MayUnwind,
+    /// It's unknown whether the function unwinds
+    ///
+    /// This is the default for foreign functions, which are defined by other code.
+    #[default]
+    Unknown,
+}
+impl UnwindBehavior {
+    /// If the function may unwind (conservatively true if unknown)
+    #[inline]
+    pub const fn may_unwind(self) -> bool {
+        !matches!(self, UnwindBehavior::Aborts)
+    }
+}
+impl Display for UnwindBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            UnwindBehavior::Aborts => "aborts",
+            UnwindBehavior::MayUnwind => "may unwind",
+            UnwindBehavior::Unknown => "unknown unwinding",
+        })
+    }
+}
+
+/// Whether a function may be called from multiple threads at once
+///
+/// This is only known if the function is marked with `#[reflect_func(thread_safe)]`
+/// or `#[reflect_func(not_thread_safe)]` (or its extern block is).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ThreadSafety {
+    /// The function is documented to be safe to call from multiple threads at once
+    Safe,
+    /// The function must not be called from multiple threads at once
+    Unsafe,
+    /// It's unknown whether the function is thread-safe
+    ///
+    /// This is the default, since nothing can be inferred from the signature.
+    #[default]
+    Unknown,
+}
+impl ThreadSafety {
+    /// If the function is known to be thread-safe (conservatively false if unknown)
+    #[inline]
+    pub const fn is_thread_safe(self) -> bool {
+        matches!(self, ThreadSafety::Safe)
+    }
+}
+impl Display for ThreadSafety {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            ThreadSafety::Safe => "thread-safe",
+            ThreadSafety::Unsafe => "not thread-safe",
+            ThreadSafety::Unknown => "unknown thread safety",
+        })
+    }
+}
+
+/// An error indicating that a function isn't known to be thread-safe
+///
+/// See [FunctionDeclaration::assert_thread_safe]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NotThreadSafe {
+    /// The name of the function
+    pub name: &'static str,
+    /// The thread safety of the function (never [ThreadSafety::Safe])
+    pub thread_safety: ThreadSafety,
+}
+impl Display for NotThreadSafe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.thread_safety {
+            ThreadSafety::Unsafe => write!(f, "Function {} isn't thread-safe", self.name),
+            _ => write!(f, "Function {} isn't known to be thread-safe", self.name),
+        }
+    }
+}
+impl std::error::Error for NotThreadSafe {}
 
 /// The location of the function
 ///
@@ -83,7 +818,78 @@ pub enum FunctionLocation {
         /// The name to be linked against,
         /// or `None` if it's the same as the function's name
         link_name: Option<&'static str>,
+        /// The shared library expected to define the function
+        /// (`#[reflect_func(library = "...")]`), if known
+        library: Option<&'static str>,
+        /// Whether the function may be missing at runtime (`#[reflect_func(weak)]`),
+        /// in which case failing to resolve it isn't an error
+        weak: bool,
     },
     /// The function is referred to by an absolute (hardcoded) address
     AbsoluteAddress(*const ()),
 }
+impl Display for FunctionLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            FunctionLocation::DynamicallyLinked {
+                link_name,
+                library,
+                weak,
+            } => {
+                f.write_str("dynamically linked")?;
+                if let Some(link_name) = link_name {
+                    write!(f, " as {}", link_name)?;
+                }
+                if let Some(library) = library {
+                    write!(f, " from {}", library)?;
+                }
+                if weak {
+                    f.write_str(" (weak)")?;
+                }
+                Ok(())
+            }
+            FunctionLocation::AbsoluteAddress(address) => write!(f, "at {:p}", address),
+        }
+    }
+}
+
+/// The information needed to resolve a dynamically linked function
+///
+/// See [FunctionDeclaration::resolution_hint]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResolutionHint {
+    /// The symbol to look up (the link name, or the name of the function)
+    pub symbol: &'static str,
+    /// The shared library expected to define the symbol, if known
+    pub library: Option<&'static str>,
+    /// Whether the symbol may be missing
+    pub weak: bool,
+}
+impl Display for ResolutionHint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol)?;
+        if let Some(library) = self.library {
+            write!(f, " from {}", library)?;
+        }
+        if self.weak {
+            f.write_str(" (weak)")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error resolving the function pointers of a vtable
+/// generated by `#[reflect_func(vtable = "...")]`
+///
+/// Weak functions are never reported as missing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MissingSymbol {
+    /// The symbol that couldn't be found
+    pub symbol: &'static str,
+}
+impl Display for MissingSymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing symbol: {}", self.symbol)
+    }
+}
+impl std::error::Error for MissingSymbol {}
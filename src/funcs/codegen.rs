@@ -0,0 +1,290 @@
+//! Generate Rust source code from reflected functions
+//!
+//! This is the reverse of `#[reflect_func]`, turning [FunctionDeclaration]s
+//! back into the `extern "C"` block that declares them.
+//!
+//! Structures, unions and enums are referred to by their names,
+//! so the generated code only compiles if types with the same names
+//! (and hopefully the same layout) are in scope.
+//! The names of arguments aren't reflected, so they're named `arg0`, `arg1`, etc.
+//!
+//! Names which are keywords are written as raw identifiers (like `r#match`).
+//! Non-ASCII type names are written as-is (they're valid Rust),
+//! but items in an `extern` block must have ASCII names,
+//! so functions with non-ASCII names are rejected ([CodegenError::InvalidName]).
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{FunctionDeclaration, FunctionLocation, UnwindBehavior};
+use crate::types::TypeInfo;
+
+/// An error generating the source code of a function
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodegenError {
+    /// A type can't be expressed in Rust syntax
+    UnsupportedType {
+        /// The name of the function using the type
+        function: &'static str,
+        /// The name of the type
+        type_name: String,
+    },
+    /// The function has an absolute address,
+    /// so it can't be declared in an `extern` block
+    AbsoluteAddress {
+        /// The name of the function
+        function: &'static str,
+    },
+    /// The name of the function isn't a valid ASCII identifier,
+    /// which is required for items in an `extern` block
+    InvalidName {
+        /// The name of the function
+        function: &'static str,
+    },
+}
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            CodegenError::UnsupportedType {
+                function,
+                ref type_name,
+            } => write!(
+                f,
+                "Type of `{}` can't be expressed in Rust syntax: {}",
+                function, type_name
+            ),
+            CodegenError::AbsoluteAddress { function } => {
+                write!(f, "Function `{}` has an absolute address", function)
+            }
+            CodegenError::InvalidName { function } => write!(
+                f,
+                "Function `{}` can't be declared in an extern block (its name must be an ASCII identifier)",
+                function
+            ),
+        }
+    }
+}
+impl std::error::Error for CodegenError {}
+
+/// Generate an `extern "C"` block declaring the specified functions
+///
+/// Functions that may unwind ([UnwindBehavior::MayUnwind]) are declared
+/// in a separate `extern "C-unwind"` block after it.
+///
+/// ## Example
+/// For a function reflected from `#[link_name = "sqrtf"] fn sqrt(small: f32) -> f32;`,
+/// the generated code is:
+/// ````text
+/// extern "C" {
+///     #[link_name = "sqrtf"]
+///     pub fn sqrt(arg0: f32) -> f32;
+/// }
+/// ````
+pub fn to_extern_block(decls: &[FunctionDeclaration]) -> Result<String, CodegenError> {
+    let (unwinding, aborting): (Vec<_>, Vec<_>) = decls
+        .iter()
+        .partition(|decl| decl.unwind == UnwindBehavior::MayUnwind);
+    let mut out = String::from("extern \"C\" {\n");
+    for decl in aborting {
+        write_decl(&mut out, decl)?;
+    }
+    out.push_str("}\n");
+    if !unwinding.is_empty() {
+        out.push_str("extern \"C-unwind\" {\n");
+        for decl in unwinding {
+            write_decl(&mut out, decl)?;
+        }
+        out.push_str("}\n");
+    }
+    Ok(out)
+}
+
+/// Write the declaration of a single function
+///
+/// NOTE: Writing to a `String` never fails
+fn write_decl(out: &mut String, decl: &FunctionDeclaration) -> Result<(), CodegenError> {
+    let function = decl.name;
+    match decl.location {
+        Some(FunctionLocation::DynamicallyLinked {
+            link_name: Some(link_name),
+            ..
+        }) => {
+            writeln!(out, "    #[link_name = {:?}]", link_name).unwrap();
+        }
+        Some(FunctionLocation::DynamicallyLinked {
+            link_name: None, ..
+        })
+        | None => {}
+        Some(FunctionLocation::AbsoluteAddress(_)) => {
+            return Err(CodegenError::AbsoluteAddress { function })
+        }
+    }
+    if !function.is_ascii() || !is_identifier(function) {
+        return Err(CodegenError::InvalidName { function });
+    }
+    write!(out, "    pub fn {}(", Identifier(function)).unwrap();
+    for (index, arg) in decl.signature.argument_types.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write!(
+            out,
+            "arg{}: {}",
+            index,
+            RustType::new(function, arg, false)?
+        )
+        .unwrap();
+    }
+    out.push(')');
+    match *decl.signature.return_type {
+        TypeInfo::Unit => {}
+        ref return_type => {
+            write!(out, " -> {}", RustType::new(function, return_type, true)?).unwrap();
+        }
+    }
+    out.push_str(";\n");
+    Ok(())
+}
+
+/// A type which is known to be expressible in Rust syntax
+struct RustType<'a>(&'a TypeInfo);
+impl<'a> RustType<'a> {
+    /// Check the type is expressible, recursively
+    ///
+    /// The never type is only allowed as a return type.
+    fn new(
+        function: &'static str,
+        info: &'a TypeInfo,
+        is_return: bool,
+    ) -> Result<RustType<'a>, CodegenError> {
+        let supported = match *info {
+            TypeInfo::Unit | TypeInfo::Bool | TypeInfo::Integer(_) | TypeInfo::Float { .. } => true,
+            TypeInfo::Pointer { .. } => true,
+            TypeInfo::NonZero(inner) => {
+                matches!(*inner, TypeInfo::Integer(_) | TypeInfo::Pointer { .. })
+            }
+            TypeInfo::Niche(def) if def.is_option() => {
+                RustType::new(function, def.wrapped, false)?;
+                true
+            }
+            TypeInfo::Structure(def) => is_identifier(def.name),
+            TypeInfo::UntaggedUnion(def) => is_identifier(def.name),
+            TypeInfo::CStyleEnum(def) => is_identifier(def.name),
+            TypeInfo::TaggedUnion(def) => is_identifier(def.name),
+            TypeInfo::Niche(def) => is_identifier(def.name),
+            TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } => {
+                false
+            }
+            ref other if other.is_never() => is_return,
+            ref other if other.is_str() => true,
+            ref other => match other.slice_element_type().or(other.optional_inner_type()) {
+                Some(inner) => {
+                    RustType::new(function, inner, false)?;
+                    true
+                }
+                None => false,
+            },
+        };
+        if supported {
+            Ok(RustType(info))
+        } else {
+            Err(CodegenError::UnsupportedType {
+                function,
+                type_name: format!("{}", info),
+            })
+        }
+    }
+}
+impl Display for RustType<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self.0 {
+            TypeInfo::Pointer { mutable: true } => f.write_str("*mut ::core::ffi::c_void"),
+            TypeInfo::Pointer { mutable: false } => f.write_str("*const ::core::ffi::c_void"),
+            TypeInfo::NonZero(&TypeInfo::Pointer { .. }) => {
+                f.write_str("::core::ptr::NonNull<::core::ffi::c_void>")
+            }
+            TypeInfo::NonZero(&TypeInfo::Integer(tp)) => {
+                // NonZeroU32, NonZeroI8, etc
+                let name = tp.rust_name();
+                write!(
+                    f,
+                    "::core::num::NonZero{}{}",
+                    if tp.signed { 'I' } else { 'U' },
+                    &name[1..]
+                )
+            }
+            TypeInfo::Niche(def) if def.is_option() => {
+                write!(f, "::core::option::Option<{}>", RustType(def.wrapped))
+            }
+            ref other if other.is_str() => f.write_str("::static_reflect::builtins::AsmStr"),
+            ref other => {
+                if let Some(element_type) = other.slice_element_type() {
+                    write!(
+                        f,
+                        "::static_reflect::builtins::AsmSlice<{}>",
+                        RustType(element_type)
+                    )
+                } else if let Some(inner) = other.optional_inner_type() {
+                    write!(
+                        f,
+                        "::static_reflect::builtins::AsmOption<{}>",
+                        RustType(inner)
+                    )
+                } else if let Some(name) = type_name(other) {
+                    write!(f, "{}", Identifier(name))
+                } else {
+                    // The rest are displayed by their Rust names
+                    write!(f, "{}", other)
+                }
+            }
+        }
+    }
+}
+
+/// The name of a structure, union or enum
+fn type_name(info: &TypeInfo) -> Option<&'static str> {
+    match *info {
+        TypeInfo::Structure(def) => Some(def.name),
+        TypeInfo::UntaggedUnion(def) => Some(def.name),
+        TypeInfo::CStyleEnum(def) => Some(def.name),
+        TypeInfo::TaggedUnion(def) => Some(def.name),
+        TypeInfo::Niche(def) => Some(def.name),
+        _ => None,
+    }
+}
+
+/// Keywords, which have to be written as raw identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords which can't even be used as raw identifiers
+const RESERVED_KEYWORDS: &[&str] = &["crate", "self", "Self", "super", "_"];
+
+/// An identifier, which is written as a raw identifier if it's a keyword
+struct Identifier<'a>(&'a str);
+impl Display for Identifier<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if KEYWORDS.contains(&self.0) {
+            f.write_str("r#")?;
+        }
+        f.write_str(self.0)
+    }
+}
+
+/// Check if a name can be used as an identifier (possibly a raw one)
+///
+/// The minimal profile replaces names with hashes, which often start with a digit.
+/// Non-ASCII letters are allowed, since they're valid in Rust identifiers.
+/// This is slightly more lenient than Rust's actual rules (Unicode's `XID_Start` and `XID_Continue`),
+/// but every name reflected by the derive is a valid identifier anyway.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first == '_' || first.is_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_alphanumeric()) && !RESERVED_KEYWORDS.contains(&name)
+}
@@ -0,0 +1,188 @@
+//! Opt-in coercions from Rust types to the FFI types of a signature
+//!
+//! By default, a signature only matches the exact types it was declared with
+//! (see [SignatureDef::matches]).
+//! However, Rust code calling a reflected function usually has a `&str` or `&[T]` on hand,
+//! rather than the [AsmStr](crate::builtins::AsmStr) or [AsmSlice](crate::builtins::AsmSlice) view the function expects.
+//! A set of [CoercionRules] describes which of these Rust types can stand in for an FFI type,
+//! and is consulted by [SignatureDef::matches_with] and [marshal_args_with](super::marshal::marshal_args_with).
+//!
+//! The coercions are purely views of the original values, which are never moved or copied.
+//!
+//! ## Units
+//! Integer newtypes marked `#[reflect(newtype_unit)]` are reflected as the integer they wrap,
+//! so a `TimeoutMs(u32)` matches any `u32` argument by default.
+//! Setting [CoercionRules::compare_units] also requires each argument to have the same
+//! [unit](SignatureDef::unit) as the value, so it can't be confused with a `FdHandle(u32)`.
+use super::SignatureDef;
+use crate::types::TypeInfo;
+use crate::StaticReflect;
+
+/// The type of a Rust value, as seen by the [CoercionRules]
+///
+/// Unlike a [TypeInfo], this can describe Rust types without a stable layout,
+/// like references and `Option`. The type of any [ToFfi](super::marshal::ToFfi) value
+/// is given by [RustType::of].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RustType {
+    /// A reflected type, which is passed as-is
+    Reflected(&'static TypeInfo),
+    /// A reflected integer newtype with a semantic unit (see [StaticReflect::UNIT]),
+    /// which is passed as the integer it wraps
+    WithUnit {
+        /// The reflected type of the newtype (the same as the integer)
+        value_type: &'static TypeInfo,
+        /// The unit of the newtype
+        unit: &'static str,
+    },
+    /// A string slice `str`, or an owned `String`
+    Str,
+    /// A slice `[T]`, or an owned `Vec<T>`
+    Slice {
+        /// The type of the elements
+        element_type: &'static TypeInfo,
+    },
+    /// A reference `&T` or `&mut T`
+    Ref {
+        /// Whether this is a mutable reference
+        mutable: bool,
+        /// The type of the referenced value
+        target: &'static RustType,
+    },
+    /// An `Option<T>`
+    Option(&'static RustType),
+}
+impl RustType {
+    /// The type of the specified [ToFfi](super::marshal::ToFfi) value
+    #[inline]
+    pub const fn of<T: super::marshal::ToFfi + ?Sized>() -> RustType {
+        T::RUST_TYPE
+    }
+    /// The type of a reflected value, including its unit (if any)
+    ///
+    /// This is the default [RUST_TYPE](super::marshal::ToFfi::RUST_TYPE) of a [ToFfi](super::marshal::ToFfi) type.
+    #[inline]
+    pub const fn reflected<T: StaticReflect>() -> RustType {
+        match T::UNIT {
+            Some(unit) => RustType::WithUnit {
+                value_type: &T::TYPE_INFO,
+                unit,
+            },
+            None => RustType::Reflected(&T::TYPE_INFO),
+        }
+    }
+    /// The unit of this type, if it's a [RustType::WithUnit]
+    #[inline]
+    pub fn unit(&self) -> Option<&'static str> {
+        match *self {
+            RustType::WithUnit { unit, .. } => Some(unit),
+            _ => None,
+        }
+    }
+    /// Strip any references to this type
+    const fn strip_refs(&self) -> &RustType {
+        match *self {
+            RustType::Ref { target, .. } => target.strip_refs(),
+            _ => self,
+        }
+    }
+}
+
+/// The coercions allowed from Rust types to the types of a signature
+///
+/// The [Default] allows all of them, while [CoercionRules::STRICT] allows none.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CoercionRules {
+    /// Allow a `&str` (or `String`) for a [TypeInfo::Str] argument
+    pub strings: bool,
+    /// Allow a `&[T]` (or `Vec<T>`) for a [TypeInfo::Slice] argument with the same elements
+    pub slices: bool,
+    /// Allow an `Option<&T>` for a nullable [TypeInfo::Pointer] argument,
+    /// where `None` is passed as null
+    ///
+    /// A `*mut` argument requires an `Option<&mut T>`.
+    pub optional_refs: bool,
+    /// Require each reflected value to have the same unit as its argument
+    ///
+    /// Unlike the other rules, this is a restriction rather than a coercion,
+    /// so it's disabled by both [CoercionRules::STRICT] and [CoercionRules::ALL].
+    /// A value without a unit only matches an argument without one.
+    pub compare_units: bool,
+}
+impl CoercionRules {
+    /// Only allow the exact types of a signature
+    pub const STRICT: CoercionRules = CoercionRules {
+        strings: false,
+        slices: false,
+        optional_refs: false,
+        compare_units: false,
+    };
+    /// Allow every coercion
+    pub const ALL: CoercionRules = CoercionRules {
+        strings: true,
+        slices: true,
+        optional_refs: true,
+        compare_units: false,
+    };
+    /// Check if a value of the specified Rust type can be passed as the specified argument type
+    ///
+    /// Reflected types are only allowed if they're identical.
+    /// Units aren't considered, since the argument's unit isn't part of its type
+    /// (see [SignatureDef::matches_with]).
+    pub fn allows(&self, rust_type: &RustType, expected: &TypeInfo) -> bool {
+        match *rust_type {
+            RustType::Reflected(info)
+            | RustType::WithUnit {
+                value_type: info, ..
+            } => return info == expected,
+            _ => {}
+        }
+        match (*rust_type.strip_refs(), *expected) {
+            (RustType::Str, TypeInfo::Str) => self.strings,
+            (
+                RustType::Slice { element_type },
+                TypeInfo::Slice {
+                    element_type: expected,
+                },
+            ) => self.slices && element_type == expected,
+            _ => match (*rust_type, *expected) {
+                (
+                    RustType::Option(&RustType::Ref { mutable, .. }),
+                    TypeInfo::Pointer {
+                        mutable: expected_mutable,
+                    },
+                ) => self.optional_refs && (mutable || !expected_mutable),
+                _ => false,
+            },
+        }
+    }
+}
+impl Default for CoercionRules {
+    #[inline]
+    fn default() -> Self {
+        CoercionRules::ALL
+    }
+}
+
+impl SignatureDef {
+    /// Check if the arguments of this signature are exactly the specified types
+    ///
+    /// This is the strict form of [SignatureDef::matches_with].
+    #[inline]
+    pub fn matches(&self, rust_types: &[RustType]) -> bool {
+        self.matches_with(rust_types, &CoercionRules::STRICT)
+    }
+    /// Check if values of the specified Rust types can be passed as the arguments of this signature,
+    /// using the specified coercions
+    ///
+    /// If [CoercionRules::compare_units] is set, the units must also match.
+    pub fn matches_with(&self, rust_types: &[RustType], rules: &CoercionRules) -> bool {
+        rust_types.len() == self.argument_types.len()
+            && rust_types.iter().zip(self.argument_types).enumerate().all(
+                |(index, (rust_type, expected))| {
+                    rules.allows(rust_type, expected)
+                        && (!rules.compare_units || rust_type.unit() == self.unit(index))
+                },
+            )
+    }
+}
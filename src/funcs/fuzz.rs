@@ -0,0 +1,327 @@
+//! Fuzz harnesses for reflected functions, decoding their arguments from raw bytes
+//!
+//! A [Harness] is built from a function's declaration and its [Invoker]
+//! (the hidden `_INVOKE_name` constant generated by `#[reflect_func]`).
+//! Each input is decoded into arguments according to the signature, in order:
+//! - Integers and floats are read as fixed-width little-endian values
+//! - Booleans are read as a byte, keeping only the lowest bit
+//! - An [AsmStr] is a little-endian `u16` length, followed by that many bytes
+//!   (truncated to the longest valid UTF-8 prefix)
+//! - An [AsmSlice] of integers, floats or booleans is a little-endian `u16` count,
+//!   followed by that many elements
+//! - Pointers are a little-endian `u64`, which is passed to the arena callback
+//!   given to [make_harness_with_arena]. Without an arena, pointers are unsupported.
+//!
+//! Lengths are clamped to the remaining input, so only the fixed-width values can run out.
+//! Nothing else is supported, and is rejected by [make_harness].
+//!
+//! The harness can be run from any fuzzer, or from the [fuzz_reflected] macro
+//! which defines the raw libFuzzer entry point.
+use super::{FunctionDeclaration, Invoker, SignatureDef};
+use crate::builtins::{AsmSlice, AsmStr};
+use crate::types::value::ReflectValue;
+use crate::types::{PrimitiveType, TypeId, TypeInfo};
+use std::ffi::c_void;
+use std::fmt::{self, Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+
+/// A callback giving the pointer to use for an argument, given the fuzzer's choice
+pub type Arena = Box<dyn Fn(u64) -> *mut c_void + Send + Sync>;
+
+/// An error indicating that a function can't be fuzzed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unsupported {
+    /// The name of the function
+    pub function: &'static str,
+    /// The index of the unsupported argument, or `None` for the return type
+    pub index: Option<usize>,
+    /// The name of the unsupported type
+    pub type_name: String,
+}
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(
+                f,
+                "Unable to fuzz {}: Unsupported type for argument {}: {}",
+                self.function, index, self.type_name
+            ),
+            None => write!(
+                f,
+                "Unable to fuzz {}: Unsupported return type: {}",
+                self.function, self.type_name
+            ),
+        }
+    }
+}
+impl std::error::Error for Unsupported {}
+
+/// How to decode an argument from the input
+#[derive(Copy, Clone, Debug)]
+enum Decoder {
+    /// A bool, integer or float
+    Primitive(PrimitiveType),
+    Pointer,
+    Str,
+    Slice(PrimitiveType),
+}
+impl Decoder {
+    fn new(info: &'static TypeInfo, has_arena: bool) -> Option<Decoder> {
+        Some(match *info {
+            TypeInfo::Pointer { .. } if has_arena => Decoder::Pointer,
+            TypeInfo::Str => Decoder::Str,
+            TypeInfo::Slice { element_type } => Decoder::Slice(value_type(element_type)?),
+            _ => Decoder::Primitive(value_type(info)?),
+        })
+    }
+}
+
+/// The primitive type of a bool, integer or float
+fn value_type(info: &'static TypeInfo) -> Option<PrimitiveType> {
+    match *info {
+        TypeInfo::Bool | TypeInfo::Integer(_) | TypeInfo::Float { .. } => {
+            TypeId::<()>::from_static(info).primitive()
+        }
+        _ => None,
+    }
+}
+
+/// The outcome of running the harness on an input
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// The input was too short to decode every argument, so the function wasn't called
+    Skipped,
+    /// The function returned the specified value
+    Returned(ReflectValue),
+    /// The function panicked, with the specified message (if it was a string)
+    Panicked(Option<String>),
+}
+
+/// A fuzz harness for a reflected function (see the [module documentation](self))
+pub struct Harness {
+    name: &'static str,
+    signature: SignatureDef,
+    invoker: Invoker,
+    decoders: Vec<Decoder>,
+    return_type: PrimitiveType,
+    arena: Option<Arena>,
+}
+impl Harness {
+    /// The signature of the fuzzed function
+    #[inline]
+    pub fn signature(&self) -> &SignatureDef {
+        &self.signature
+    }
+    /// Decode the arguments from the input, and call the function with them
+    ///
+    /// Panics are caught, and returned as [Outcome::Panicked].
+    ///
+    /// ## Safety
+    /// The invoker must call the declared function,
+    /// which must be safe to call with any values of its argument types
+    /// (and with the pointers given by the arena).
+    pub unsafe fn run(&self, data: &[u8]) -> Outcome {
+        let mut input = Input { data };
+        // Every argument fits in two words, and the buffers for slices are 8-byte aligned
+        let mut slots = vec![[0u64; 2]; self.decoders.len()];
+        let mut buffers: Vec<Vec<u64>> = Vec::new();
+        for (slot, decoder) in slots.iter_mut().zip(&self.decoders) {
+            let dest = slot.as_mut_ptr() as *mut u8;
+            match *decoder {
+                Decoder::Primitive(tp) => match input.value(tp) {
+                    Some(value) => value.write(dest),
+                    None => return Outcome::Skipped,
+                },
+                Decoder::Pointer => match input.take(8) {
+                    Some(bytes) => {
+                        let choice = u64::from_le_bytes(bytes.try_into().unwrap());
+                        let ptr = (self.arena.as_ref().unwrap())(choice);
+                        (dest as *mut *mut c_void).write(ptr);
+                    }
+                    None => return Outcome::Skipped,
+                },
+                Decoder::Str => {
+                    let len = input.length(1);
+                    let bytes = input.take(len).unwrap();
+                    let text = match std::str::from_utf8(bytes) {
+                        Ok(text) => text,
+                        Err(cause) => std::str::from_utf8(&bytes[..cause.valid_up_to()]).unwrap(),
+                    };
+                    (dest as *mut AsmStr).write(AsmStr::from(text));
+                }
+                Decoder::Slice(element) => {
+                    let size = element.type_info().size();
+                    let count = input.length(size);
+                    let mut buffer = vec![0u64; (count * size).div_ceil(8).max(1)];
+                    let elements = buffer.as_mut_ptr() as *mut u8;
+                    for index in 0..count {
+                        let value = input.value(element).unwrap();
+                        value.write(elements.add(index * size));
+                    }
+                    (dest as *mut AsmSlice<u8>).write(AsmSlice {
+                        ptr: elements,
+                        len: count,
+                    });
+                    // Moving the buffer doesn't move its heap allocation
+                    buffers.push(buffer);
+                }
+            }
+        }
+        let args = slots
+            .iter_mut()
+            .map(|slot| slot.as_mut_ptr() as *mut c_void)
+            .collect::<Vec<_>>();
+        let mut result = [0u64; 2];
+        let ret = result.as_mut_ptr() as *mut c_void;
+        let invoker = self.invoker;
+        match panic::catch_unwind(AssertUnwindSafe(|| invoker(&args, ret))) {
+            Ok(()) => Outcome::Returned(ReflectValue::read(ret as *const u8, self.return_type)),
+            Err(cause) => Outcome::Panicked(
+                cause
+                    .downcast_ref::<&str>()
+                    .map(|s| String::from(*s))
+                    .or_else(|| cause.downcast_ref::<String>().cloned()),
+            ),
+        }
+    }
+    /// Run the harness on an input, resuming any panic
+    ///
+    /// This is what a fuzzer should call, so that panics are reported as crashes.
+    ///
+    /// ## Safety
+    /// See [Harness::run].
+    pub unsafe fn fuzz(&self, data: &[u8]) {
+        if let Outcome::Panicked(message) = self.run(data) {
+            panic!(
+                "{} panicked: {}",
+                self.name,
+                message.as_deref().unwrap_or("<unknown>")
+            );
+        }
+    }
+}
+
+/// The remaining input
+struct Input<'a> {
+    data: &'a [u8],
+}
+impl<'a> Input<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+    /// Read a `u16` length prefix, clamped to the number of remaining elements of the specified size
+    fn length(&mut self, element_size: usize) -> usize {
+        let len = match self.take(2) {
+            Some(bytes) => u16::from_le_bytes([bytes[0], bytes[1]]) as usize,
+            None => 0,
+        };
+        len.min(self.data.len() / element_size)
+    }
+    fn value(&mut self, tp: PrimitiveType) -> Option<ReflectValue> {
+        let bytes = self.take(tp.type_info().size())?;
+        let mut word = [0u8; 8];
+        word[..bytes.len()].copy_from_slice(bytes);
+        let bits = u64::from_le_bytes(word);
+        Some(match tp {
+            PrimitiveType::Bool => ReflectValue::Bool(bits & 1 != 0),
+            PrimitiveType::Integer(int_type) => ReflectValue::Integer { int_type, bits },
+            PrimitiveType::Float { size } if size.bytes() == 4 => {
+                ReflectValue::F32(f32::from_bits(bits as u32))
+            }
+            PrimitiveType::Float { .. } => ReflectValue::F64(f64::from_bits(bits)),
+            _ => unreachable!("Unexpected type {:?}", tp),
+        })
+    }
+}
+
+/// Build a fuzz harness for the specified function, which is called through the invoker
+///
+/// Functions with pointer arguments need an arena (see [make_harness_with_arena]).
+pub fn make_harness(decl: &FunctionDeclaration, invoker: Invoker) -> Result<Harness, Unsupported> {
+    build(decl, invoker, None)
+}
+
+/// Build a fuzz harness for a function with pointer arguments,
+/// which are given by the arena
+pub fn make_harness_with_arena(
+    decl: &FunctionDeclaration,
+    invoker: Invoker,
+    arena: impl Fn(u64) -> *mut c_void + Send + Sync + 'static,
+) -> Result<Harness, Unsupported> {
+    build(decl, invoker, Some(Box::new(arena)))
+}
+
+fn build(
+    decl: &FunctionDeclaration,
+    invoker: Invoker,
+    arena: Option<Arena>,
+) -> Result<Harness, Unsupported> {
+    let signature = decl.signature;
+    let unsupported = |index: Option<usize>, info: &TypeInfo| Unsupported {
+        function: decl.name,
+        index,
+        type_name: info.to_string(),
+    };
+    let decoders = signature
+        .argument_types
+        .iter()
+        .enumerate()
+        .map(|(index, info)| {
+            Decoder::new(info, arena.is_some()).ok_or_else(|| unsupported(Some(index), info))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let return_type = match *signature.return_type {
+        TypeInfo::Unit => PrimitiveType::Unit,
+        TypeInfo::Pointer { .. } => PrimitiveType::Pointer,
+        ref info => value_type(info).ok_or_else(|| unsupported(None, info))?,
+    };
+    Ok(Harness {
+        name: decl.name,
+        signature,
+        invoker,
+        decoders,
+        return_type,
+        arena,
+    })
+}
+
+/// Define the raw libFuzzer entry point (`LLVMFuzzerTestOneInput`),
+/// fuzzing a reflected function
+///
+/// This takes the declaration and invoker of the function,
+/// which are the hidden `_FUNC_name` and `_INVOKE_name` constants generated by `#[reflect_func]`.
+/// Panics abort the process, which libFuzzer reports as a crash.
+///
+/// This is for linking against libFuzzer directly.
+/// With `libfuzzer-sys` (and `cargo fuzz`), call [Harness::fuzz] from its `fuzz_target!` instead,
+/// since it already defines the entry point.
+///
+/// The function must be safe to call with any values of its argument types.
+#[macro_export]
+macro_rules! fuzz_reflected {
+    ($decl:expr, $invoker:expr) => {
+        #[no_mangle]
+        pub extern "C" fn LLVMFuzzerTestOneInput(data: *const u8, size: usize) -> i32 {
+            static HARNESS: ::std::sync::OnceLock<$crate::funcs::fuzz::Harness> =
+                ::std::sync::OnceLock::new();
+            let harness = HARNESS.get_or_init(|| {
+                $crate::funcs::fuzz::make_harness(&$decl.into_erased(), $invoker)
+                    .unwrap_or_else(|cause| panic!("{}", cause))
+            });
+            let data = if size == 0 {
+                &[][..]
+            } else {
+                unsafe { ::std::slice::from_raw_parts(data, size) }
+            };
+            unsafe { harness.fuzz(data) };
+            0
+        }
+    };
+}
+#[doc(inline)]
+pub use crate::fuzz_reflected;
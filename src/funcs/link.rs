@@ -0,0 +1,244 @@
+//! Checking a set of reflected declarations against a loaded library
+//!
+//! A plugin loader can resolve every declaration at once with [link_check](super::link_check),
+//! reporting all the failures together instead of stopping at the first one.
+//! The symbols are looked up by an injected resolver (like `libloading::Library::get`),
+//! so this never loads any libraries itself.
+//!
+//! Each symbol is looked up once, using the link name of the declaration if it has one
+//! (see [FunctionDeclaration::resolution_hint]). Multiple declarations may refer to the same symbol,
+//! as long as they agree on its signature. Otherwise, the later ones are reported as conflicts.
+//!
+//! ## Example
+#![cfg_attr(feature = "derive", doc = "````")]
+#![cfg_attr(not(feature = "derive"), doc = "````ignore")]
+//! # use static_reflect::funcs::{link_check, FunctionDeclaration};
+//! # use static_reflect::reflect_func;
+//! # use std::collections::HashMap;
+//! #[reflect_func]
+//! extern "C" {
+//!     fn plugin_init(flags: u32) -> i32;
+//! }
+//! let symbols = HashMap::from([("plugin_init", 0x1000 as *const ())]);
+//! let report = link_check(&[_FUNC_plugin_init.erase()], |symbol| {
+//!     symbols.get(symbol).copied()
+//! });
+//! let addresses = report.apply().unwrap();
+//! assert_eq!(addresses[_FUNC_plugin_init.erase()], 0x1000 as *const ());
+//! ````
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use super::{FunctionDeclaration, FunctionLocation};
+
+/// The address of each successfully linked declaration
+///
+/// Weak functions that couldn't be found are absent.
+pub type LinkedAddresses = HashMap<&'static FunctionDeclaration, *const ()>;
+
+/// The result of checking a set of declarations with [link_check](super::link_check)
+///
+/// Each declaration is in exactly one of the lists (except for exact duplicates, which are ignored),
+/// in the order they were given.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkReport {
+    /// The declarations with a known address
+    pub resolved: Vec<LinkedFunction>,
+    /// The declarations whose symbol couldn't be found,
+    /// including weak ones (which aren't failures)
+    pub missing: Vec<UnresolvedSymbol>,
+    /// The declarations without a [location](FunctionDeclaration::location),
+    /// which can never be called
+    pub unlocated: Vec<&'static FunctionDeclaration>,
+    /// The declarations whose symbol was already declared with a different signature
+    pub conflicts: Vec<SymbolConflict>,
+}
+impl LinkReport {
+    /// If every declaration can be called
+    ///
+    /// Weak functions may be missing.
+    pub fn is_ok(&self) -> bool {
+        self.unlocated.is_empty()
+            && self.conflicts.is_empty()
+            && self.missing.iter().all(|missing| missing.weak)
+    }
+    /// Every failure in the report, ordered by their kind
+    pub fn failures(&self) -> Vec<LinkFailure> {
+        let missing = self
+            .missing
+            .iter()
+            .filter(|missing| !missing.weak)
+            .map(|missing| LinkFailure::Missing(*missing));
+        let unlocated = self
+            .unlocated
+            .iter()
+            .map(|decl| LinkFailure::Unlocated(decl));
+        let conflicts = self
+            .conflicts
+            .iter()
+            .map(|conflict| LinkFailure::Conflict(*conflict));
+        missing.chain(unlocated).chain(conflicts).collect()
+    }
+    /// Map each declaration to its address, failing if any of them can't be called
+    pub fn apply(&self) -> Result<LinkedAddresses, LinkError> {
+        let failures = self.failures();
+        if !failures.is_empty() {
+            return Err(LinkError { failures });
+        }
+        Ok(self
+            .resolved
+            .iter()
+            .map(|linked| (linked.declaration, linked.address))
+            .collect())
+    }
+}
+
+/// A declaration which was successfully linked
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LinkedFunction {
+    /// The linked declaration
+    pub declaration: &'static FunctionDeclaration,
+    /// The symbol it was resolved by,
+    /// or `None` if it has an [absolute address](FunctionLocation::AbsoluteAddress)
+    pub symbol: Option<&'static str>,
+    /// The address of the function
+    pub address: *const (),
+}
+
+/// A declaration whose symbol couldn't be resolved
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnresolvedSymbol {
+    /// The unresolved declaration
+    pub declaration: &'static FunctionDeclaration,
+    /// The symbol that couldn't be found
+    pub symbol: &'static str,
+    /// Whether the function is weak, so that it's allowed to be missing
+    pub weak: bool,
+}
+
+/// Two declarations of the same symbol with different signatures
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolConflict {
+    /// The symbol both declarations link against
+    pub symbol: &'static str,
+    /// The first declaration of the symbol, whose signature is used
+    pub first: &'static FunctionDeclaration,
+    /// The conflicting declaration, which isn't linked
+    pub second: &'static FunctionDeclaration,
+}
+
+/// A reason a declaration can't be called
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LinkFailure {
+    /// The symbol of a (non-weak) function couldn't be found
+    Missing(UnresolvedSymbol),
+    /// The declaration doesn't have a location
+    Unlocated(&'static FunctionDeclaration),
+    /// The symbol was already declared with a different signature
+    Conflict(SymbolConflict),
+}
+impl Display for LinkFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            LinkFailure::Missing(missing) => {
+                write!(f, "Missing symbol {}", missing.symbol)?;
+                if let Some(library) = missing
+                    .declaration
+                    .resolution_hint()
+                    .and_then(|hint| hint.library)
+                {
+                    write!(f, " (expected in {})", library)?;
+                }
+                Ok(())
+            }
+            LinkFailure::Unlocated(decl) => {
+                write!(f, "Function {} doesn't have a known location", decl.name)
+            }
+            LinkFailure::Conflict(conflict) => write!(
+                f,
+                "Conflicting declarations of {}: {} and {}",
+                conflict.symbol, conflict.first, conflict.second
+            ),
+        }
+    }
+}
+
+/// An error indicating that some declarations couldn't be linked
+///
+/// See [LinkReport::apply]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkError {
+    /// Every failure, in the same order as [LinkReport::failures]
+    pub failures: Vec<LinkFailure>,
+}
+impl Display for LinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to link {} function(s):", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for LinkError {}
+
+pub(super) fn link_check(
+    decls: &[&'static FunctionDeclaration],
+    resolve: impl Fn(&str) -> Option<*const ()>,
+) -> LinkReport {
+    let mut report = LinkReport::default();
+    // The first declaration of each symbol, along with its address (if found)
+    let mut symbols: HashMap<&'static str, (&'static FunctionDeclaration, Option<*const ()>)> =
+        HashMap::new();
+    let mut seen = HashSet::with_capacity(decls.len());
+    for &decl in decls {
+        // The registry may contain the same declaration more than once
+        if !seen.insert(decl) {
+            continue;
+        }
+        match decl.location {
+            None => report.unlocated.push(decl),
+            Some(FunctionLocation::AbsoluteAddress(address)) => {
+                report.resolved.push(LinkedFunction {
+                    declaration: decl,
+                    symbol: None,
+                    address,
+                });
+            }
+            Some(FunctionLocation::DynamicallyLinked {
+                link_name, weak, ..
+            }) => {
+                let symbol = link_name.unwrap_or(decl.name);
+                let address = match symbols.get(symbol) {
+                    Some(&(first, _)) if first.signature != decl.signature => {
+                        report.conflicts.push(SymbolConflict {
+                            symbol,
+                            first,
+                            second: decl,
+                        });
+                        continue;
+                    }
+                    Some(&(_, address)) => address,
+                    None => {
+                        let address = resolve(symbol).filter(|ptr| !ptr.is_null());
+                        symbols.insert(symbol, (decl, address));
+                        address
+                    }
+                };
+                match address {
+                    Some(address) => report.resolved.push(LinkedFunction {
+                        declaration: decl,
+                        symbol: Some(symbol),
+                        address,
+                    }),
+                    None => report.missing.push(UnresolvedSymbol {
+                        declaration: decl,
+                        symbol,
+                        weak,
+                    }),
+                }
+            }
+        }
+    }
+    report
+}
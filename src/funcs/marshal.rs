@@ -0,0 +1,438 @@
+//! Convert Rust values into the C-ABI representations expected by reflected functions
+//!
+//! The [ToFfi] trait gives the FFI-safe equivalent of common Rust types
+//! (`&str` becomes an [AsmStr], `&[T]` becomes an [AsmSlice], and so on).
+//! Then [marshal_args] checks a list of values against a [SignatureDef],
+//! packing them into an [ArgBuffer] which can be passed to a dynamic caller
+//! (like libffi's `ffi_call`, which takes an array of pointers to the arguments).
+//! [marshal_args_with] additionally applies a set of [CoercionRules],
+//! so an `Option<&T>` can be passed as a nullable pointer.
+//!
+//! ## Lifetimes
+//! The FFI representations of strings and slices *borrow* the original memory,
+//! without any lifetime attached to them ([AsmStr] and [AsmSlice] are just a pointer and a length).
+//! A value returned from [ToFfi::to_ffi] is only valid as long as the original value
+//! is neither dropped, moved, nor mutated. In particular,
+//! the [AsmStr] of a `String` points into its heap allocation,
+//! which is freed when the `String` is dropped.
+//!
+//! An [ArgBuffer] is tied to the lifetime of the values it was marshalled from,
+//! so the borrow checker enforces this as long as the buffer is alive.
+//! However, the pointers it gives out have no lifetime,
+//! and must not be used after the buffer is dropped.
+//!
+//! ## Ownership
+//! Marshalling never transfers ownership. The values are always borrowed,
+//! and nothing in an [ArgBuffer] is ever dropped.
+use super::coerce::{CoercionRules, RustType};
+use super::SignatureDef;
+use crate::builtins::{AsmOption, AsmSlice, AsmStr};
+use crate::types::lattice;
+use crate::types::TypeInfo;
+use crate::StaticReflect;
+use std::alloc::{self, Layout};
+use std::ffi::c_void;
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/// A type with an FFI-safe representation
+///
+/// See the [module documentation](self) for the lifetime of the result.
+pub trait ToFfi {
+    /// The FFI-safe representation of this type
+    type Ffi: StaticReflect;
+    /// The Rust type of this value, which determines how it can be coerced (see [CoercionRules])
+    const RUST_TYPE: RustType = RustType::reflected::<Self::Ffi>();
+    /// Convert this value into its FFI-safe representation,
+    /// which may borrow the memory of this value
+    fn to_ffi(&self) -> Self::Ffi;
+    /// The address this value is coerced to,
+    /// if it's a reference or an `Option` of a reference (where `None` is null)
+    #[inline]
+    fn coerced_pointer(&self) -> Option<*const c_void> {
+        None
+    }
+}
+
+macro_rules! impl_identity {
+    ($($target:ty),*) => {
+        $(impl ToFfi for $target {
+            type Ffi = $target;
+            #[inline]
+            fn to_ffi(&self) -> $target {
+                *self
+            }
+        })*
+    };
+}
+impl_identity!(u8, u16, u32, u64, i8, i16, i32, i64, usize, isize);
+impl_identity!((), bool, f32, f64, AsmStr);
+
+impl<T> ToFfi for *const T {
+    type Ffi = *const T;
+    #[inline]
+    fn to_ffi(&self) -> *const T {
+        *self
+    }
+}
+impl<T> ToFfi for *mut T {
+    type Ffi = *mut T;
+    #[inline]
+    fn to_ffi(&self) -> *mut T {
+        *self
+    }
+}
+impl<T> ToFfi for NonNull<T> {
+    type Ffi = NonNull<T>;
+    #[inline]
+    fn to_ffi(&self) -> NonNull<T> {
+        *self
+    }
+}
+impl<T: StaticReflect> ToFfi for AsmSlice<T> {
+    type Ffi = AsmSlice<T>;
+    #[inline]
+    fn to_ffi(&self) -> AsmSlice<T> {
+        *self
+    }
+}
+impl ToFfi for str {
+    type Ffi = AsmStr;
+    const RUST_TYPE: RustType = RustType::Str;
+    #[inline]
+    fn to_ffi(&self) -> AsmStr {
+        AsmStr::from(self)
+    }
+}
+impl ToFfi for String {
+    type Ffi = AsmStr;
+    const RUST_TYPE: RustType = RustType::Str;
+    #[inline]
+    fn to_ffi(&self) -> AsmStr {
+        AsmStr::from(self.as_str())
+    }
+}
+/// The elements are passed as-is, so they must already be FFI-safe
+impl<T: StaticReflect> ToFfi for [T] {
+    type Ffi = AsmSlice<T>;
+    const RUST_TYPE: RustType = RustType::Slice {
+        element_type: &T::TYPE_INFO,
+    };
+    #[inline]
+    fn to_ffi(&self) -> AsmSlice<T> {
+        AsmSlice::from(self)
+    }
+}
+/// The elements are passed as-is, so they must already be FFI-safe
+impl<T: StaticReflect> ToFfi for Vec<T> {
+    type Ffi = AsmSlice<T>;
+    const RUST_TYPE: RustType = RustType::Slice {
+        element_type: &T::TYPE_INFO,
+    };
+    #[inline]
+    fn to_ffi(&self) -> AsmSlice<T> {
+        AsmSlice::from(self.as_slice())
+    }
+}
+impl<T: ToFfi> ToFfi for Option<T> {
+    type Ffi = AsmOption<T::Ffi>;
+    const RUST_TYPE: RustType = RustType::Option(&T::RUST_TYPE);
+    #[inline]
+    fn to_ffi(&self) -> AsmOption<T::Ffi> {
+        match *self {
+            Some(ref value) => AsmOption::some(value.to_ffi()),
+            None => AsmOption::none(),
+        }
+    }
+    #[inline]
+    fn coerced_pointer(&self) -> Option<*const c_void> {
+        match *self {
+            Some(ref value) => value.coerced_pointer(),
+            None if matches!(T::RUST_TYPE, RustType::Ref { .. }) => Some(std::ptr::null()),
+            None => None,
+        }
+    }
+}
+impl<T: ToFfi + ?Sized> ToFfi for &T {
+    type Ffi = T::Ffi;
+    const RUST_TYPE: RustType = RustType::Ref {
+        mutable: false,
+        target: &T::RUST_TYPE,
+    };
+    #[inline]
+    fn to_ffi(&self) -> T::Ffi {
+        (**self).to_ffi()
+    }
+    #[inline]
+    fn coerced_pointer(&self) -> Option<*const c_void> {
+        Some(*self as *const T as *const c_void)
+    }
+}
+impl<T: ToFfi + ?Sized> ToFfi for &mut T {
+    type Ffi = T::Ffi;
+    const RUST_TYPE: RustType = RustType::Ref {
+        mutable: true,
+        target: &T::RUST_TYPE,
+    };
+    #[inline]
+    fn to_ffi(&self) -> T::Ffi {
+        (**self).to_ffi()
+    }
+    #[inline]
+    fn coerced_pointer(&self) -> Option<*const c_void> {
+        Some(&**self as *const T as *const c_void)
+    }
+}
+
+/// A value which can be marshalled, without knowing its type statically
+///
+/// This is implemented for every [ToFfi] type,
+/// so that different types can be passed to [marshal_args].
+pub trait AnyFfiValue {
+    /// The reflected type of the FFI representation
+    fn ffi_type(&self) -> &'static TypeInfo;
+    /// The layout of the FFI representation
+    fn ffi_layout(&self) -> Layout;
+    /// The Rust type of this value (see [ToFfi::RUST_TYPE])
+    fn rust_type(&self) -> RustType;
+    /// The address this value is coerced to (see [ToFfi::coerced_pointer])
+    fn coerced_pointer(&self) -> Option<*const c_void>;
+    /// Write the FFI representation of this value to the specified memory
+    ///
+    /// ## Safety
+    /// The memory must be valid for writes of [AnyFfiValue::ffi_layout],
+    /// and properly aligned.
+    unsafe fn write_ffi(&self, dest: *mut u8);
+}
+impl<T: ToFfi> AnyFfiValue for T {
+    #[inline]
+    fn ffi_type(&self) -> &'static TypeInfo {
+        &<T::Ffi as StaticReflect>::TYPE_INFO
+    }
+    #[inline]
+    fn ffi_layout(&self) -> Layout {
+        Layout::new::<T::Ffi>()
+    }
+    #[inline]
+    fn rust_type(&self) -> RustType {
+        T::RUST_TYPE
+    }
+    #[inline]
+    fn coerced_pointer(&self) -> Option<*const c_void> {
+        ToFfi::coerced_pointer(self)
+    }
+    #[inline]
+    unsafe fn write_ffi(&self, dest: *mut u8) {
+        (dest as *mut T::Ffi).write(self.to_ffi())
+    }
+}
+
+/// An error marshalling arguments that don't match a signature
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MarshalError {
+    /// The number of values differs from the number of arguments
+    ArgumentCount {
+        /// The number of arguments in the signature
+        expected: usize,
+        /// The number of values
+        actual: usize,
+    },
+    /// A value has the wrong type for its argument
+    ArgumentType {
+        /// The index of the argument
+        index: usize,
+        /// The type of the argument in the signature
+        expected: &'static TypeInfo,
+        /// The type of the value's FFI representation
+        actual: &'static TypeInfo,
+    },
+    /// A value has the wrong unit for its argument
+    /// (only checked if [CoercionRules::compare_units] is set)
+    ArgumentUnit {
+        /// The index of the argument
+        index: usize,
+        /// The unit of the argument in the signature
+        expected: Option<&'static str>,
+        /// The unit of the value
+        actual: Option<&'static str>,
+    },
+}
+impl Display for MarshalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            MarshalError::ArgumentCount { expected, actual } => {
+                write!(f, "Expected {} arguments, but got {}", expected, actual)
+            }
+            MarshalError::ArgumentType {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Expected argument {} to have type {}, but got {}",
+                index, expected, actual
+            ),
+            MarshalError::ArgumentUnit {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Expected argument {} to be in {}, but got {}",
+                index,
+                expected.unwrap_or("no unit"),
+                actual.unwrap_or("no unit")
+            ),
+        }
+    }
+}
+impl std::error::Error for MarshalError {}
+
+/// The marshalled arguments of a call, borrowing the original values
+///
+/// Each argument is stored in the FFI representation expected by the signature,
+/// in a single allocation. See the [module documentation](self) for the lifetimes.
+pub struct ArgBuffer<'a> {
+    storage: NonNull<u8>,
+    layout: Layout,
+    pointers: Vec<*mut c_void>,
+    marker: PhantomData<&'a dyn AnyFfiValue>,
+}
+impl ArgBuffer<'_> {
+    /// The number of arguments
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+    /// If there are no arguments
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+    /// A pointer to each argument, in order
+    ///
+    /// This is the form expected by libffi's `ffi_call` (the `void **avalue` parameter).
+    #[inline]
+    pub fn pointers(&self) -> &[*mut c_void] {
+        &self.pointers
+    }
+    /// A pointer to the specified argument, or `None` if it's out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<*mut c_void> {
+        self.pointers.get(index).copied()
+    }
+}
+impl Drop for ArgBuffer<'_> {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: Non-empty storage is always allocated with this layout
+            unsafe { alloc::dealloc(self.storage.as_ptr(), self.layout) }
+        }
+    }
+}
+
+/// Marshal the specified values into the arguments of a function with the given signature
+///
+/// The FFI representation of each value must have the same type as its argument,
+/// except that integers are widened into larger types (when [lattice::can_widen]).
+#[inline]
+pub fn marshal_args<'a>(
+    sig: &SignatureDef,
+    values: &[&'a dyn AnyFfiValue],
+) -> Result<ArgBuffer<'a>, MarshalError> {
+    marshal_args_with(sig, values, &CoercionRules::STRICT)
+}
+
+/// Marshal the specified values into the arguments of a function,
+/// also allowing the specified coercions
+///
+/// Strings and slices are always converted through [ToFfi],
+/// so the only additional conversion is an `Option<&T>` into a nullable pointer
+/// (when [CoercionRules::optional_refs] is enabled).
+pub fn marshal_args_with<'a>(
+    sig: &SignatureDef,
+    values: &[&'a dyn AnyFfiValue],
+    rules: &CoercionRules,
+) -> Result<ArgBuffer<'a>, MarshalError> {
+    if values.len() != sig.argument_types.len() {
+        return Err(MarshalError::ArgumentCount {
+            expected: sig.argument_types.len(),
+            actual: values.len(),
+        });
+    }
+    let mut layout = Layout::from_size_align(0, 1).unwrap();
+    let mut offsets = Vec::with_capacity(values.len());
+    for (index, (&value, expected)) in values.iter().zip(sig.argument_types).enumerate() {
+        let actual = value.ffi_type();
+        if rules.compare_units && value.rust_type().unit() != sig.unit(index) {
+            return Err(MarshalError::ArgumentUnit {
+                index,
+                expected: sig.unit(index),
+                actual: value.rust_type().unit(),
+            });
+        }
+        let slot = match (*expected, *actual) {
+            _ if expected == actual => value.ffi_layout(),
+            (TypeInfo::Integer(to), TypeInfo::Integer(from)) if lattice::can_widen(from, to) => {
+                Layout::from_size_align(to.size.bytes(), to.align()).unwrap()
+            }
+            (TypeInfo::Pointer { .. }, _) if coerces_to_pointer(value, expected, rules) => {
+                Layout::new::<*const c_void>()
+            }
+            _ => {
+                return Err(MarshalError::ArgumentType {
+                    index,
+                    expected,
+                    actual,
+                })
+            }
+        };
+        let (extended, offset) = layout.extend(slot).expect("Arguments are too large");
+        layout = extended;
+        offsets.push(offset);
+    }
+    let storage = if layout.size() == 0 {
+        // Dangling, but properly aligned for every argument
+        NonNull::new(layout.align() as *mut u8).unwrap()
+    } else {
+        // SAFETY: The layout has a non-zero size
+        let ptr = unsafe { alloc::alloc(layout) };
+        NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+    };
+    let mut buffer = ArgBuffer {
+        storage,
+        layout,
+        pointers: Vec::with_capacity(values.len()),
+        marker: PhantomData,
+    };
+    for ((&value, expected), offset) in values.iter().zip(sig.argument_types).zip(offsets) {
+        // SAFETY: The offset is in bounds, and aligned for the slot
+        unsafe {
+            let dest = storage.as_ptr().add(offset);
+            match (*expected, *value.ffi_type()) {
+                (TypeInfo::Integer(to), TypeInfo::Integer(from)) if from != to => {
+                    let mut narrow = 0u64;
+                    let narrow = &mut narrow as *mut u64 as *mut u8;
+                    value.write_ffi(narrow);
+                    let extended = from.extend_bits(from.read_bits(narrow));
+                    to.write_bits(dest, extended as u64);
+                }
+                (TypeInfo::Pointer { .. }, _) if expected != value.ffi_type() => {
+                    let ptr = value.coerced_pointer().expect("Missing pointer");
+                    (dest as *mut *const c_void).write(ptr);
+                }
+                _ => value.write_ffi(dest),
+            }
+            buffer.pointers.push(dest as *mut c_void);
+        }
+    }
+    Ok(buffer)
+}
+
+/// Whether the value is an `Option<&T>` which can be passed as the expected pointer
+fn coerces_to_pointer(value: &dyn AnyFfiValue, expected: &TypeInfo, rules: &CoercionRules) -> bool {
+    matches!(value.rust_type(), RustType::Option(_))
+        && rules.allows(&value.rust_type(), expected)
+        && value.coerced_pointer().is_some()
+}
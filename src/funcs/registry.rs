@@ -0,0 +1,339 @@
+//! A global registry of the functions marked `#[reflect_func(register)]`
+//!
+//! Each registered function places an entry in a dedicated link section
+//! (see [submit!](crate::funcs::registry::submit)). The linker merges the sections of every crate
+//! linked into the final binary, so the registry sees the functions of all of them,
+//! without any crate knowing about the others (like the `linkme` crate, but without any dependencies).
+//! Nothing runs before `main`: the entries are read directly from the section at runtime.
+//!
+//! Link sections are supported on ELF platforms (Linux, Android and the BSDs),
+//! macOS/iOS and Windows (see [LINK_SECTIONS_SUPPORTED]).
+//! On any other target (like WebAssembly), the entries are ignored,
+//! so the registry only contains the functions added explicitly with [register].
+//! Explicitly registered functions are included on every platform.
+//!
+//! ## Sections
+//! - ELF: the `static_reflect_funcs` section, delimited by the `__start_` and `__stop_`
+//!   symbols which the linker defines for any section whose name is a valid identifier
+//! - Mach-O: the `__DATA,__srfuncs` section, delimited by the `section$start` and `section$end` symbols
+//! - COFF: the `.srfuncs$m` section, which the linker sorts between the
+//!   `.srfuncs$a` and `.srfuncs$z` markers (by the suffix after the `$`)
+//!
+//! This crate always places an empty entry in the section,
+//! so that it exists even if no functions are registered.
+use super::FunctionDeclaration;
+use std::fmt::{self, Debug, Formatter};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Whether functions are collected from link sections on this target
+///
+/// If this is false, only the functions added with [register] are in the registry.
+pub const LINK_SECTIONS_SUPPORTED: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_vendor = "apple",
+    windows,
+));
+
+/// Place a function declaration in the registry's link section
+///
+/// This is what `#[reflect_func(register)]` expands to,
+/// and may also be used for hand-written declarations.
+/// The declaration must be a constant expression of type `&'static FunctionDeclaration`.
+///
+/// On targets without link sections (see [LINK_SECTIONS_SUPPORTED]),
+/// the entry is ignored and the function needs to be added with [register] instead.
+///
+/// ## Example
+/// ````
+/// # use static_reflect::funcs::{FunctionDeclaration, SignatureDef, ThreadSafety, UnwindBehavior, CallingConvention};
+/// # use static_reflect::funcs::registry;
+/// # use static_reflect::types::ffi::FfiSafety;
+/// # use static_reflect::TypeInfo;
+/// const DECL: FunctionDeclaration = FunctionDeclaration {
+///     name: "submitted_by_hand",
+///     is_unsafe: false,
+///     location: None,
+///     unwind: UnwindBehavior::Aborts,
+///     thread_safety: ThreadSafety::Unknown,
+///     source: None,
+///     signature: SignatureDef {
+///         argument_types: &[],
+///         argument_directions: &[],
+///         argument_contracts: &[],
+///         argument_units: &[],
+///         return_type: &TypeInfo::Unit,
+///         calling_convention: CallingConvention::StandardC,
+///         ffi_safety: FfiSafety::Safe,
+///     },
+///     return_type: std::marker::PhantomData,
+///     arg_types: std::marker::PhantomData,
+/// };
+/// registry::submit!(&DECL);
+/// if registry::LINK_SECTIONS_SUPPORTED {
+///     assert_eq!(registry::find("submitted_by_hand"), [&DECL]);
+/// }
+/// ````
+#[doc(inline)]
+pub use crate::__sr_submit_function as submit;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __sr_submit_function {
+    ($declaration:expr) => {
+        const _: () = {
+            #[used]
+            #[cfg_attr(
+                any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                    target_os = "illumos",
+                ),
+                unsafe(link_section = "static_reflect_funcs")
+            )]
+            #[cfg_attr(
+                target_vendor = "apple",
+                unsafe(link_section = "__DATA,__srfuncs,regular,no_dead_strip")
+            )]
+            #[cfg_attr(windows, unsafe(link_section = ".srfuncs$m"))]
+            static __REFLECT_ENTRY: $crate::funcs::registry::SectionEntry =
+                $crate::funcs::registry::SectionEntry::new($declaration);
+        };
+    };
+}
+
+/// An entry in the registry's link section (see [submit!](crate::funcs::registry::submit))
+///
+/// Every entry has the same size, so the section is an array of them.
+/// Empty entries are skipped, since the section may contain padding.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct SectionEntry(Option<&'static FunctionDeclaration>);
+// SAFETY: The declarations are immutable, and an `AbsoluteAddress` is only an address
+unsafe impl Sync for SectionEntry {}
+impl SectionEntry {
+    /// An entry for the specified declaration
+    #[inline]
+    pub const fn new(declaration: &'static FunctionDeclaration) -> Self {
+        SectionEntry(Some(declaration))
+    }
+    const EMPTY: SectionEntry = SectionEntry(None);
+}
+impl Debug for SectionEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("SectionEntry")
+            .field(&self.0.map(|decl| decl.name))
+            .finish()
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+))]
+mod section {
+    use super::SectionEntry;
+
+    /// Ensures the section exists, so that the linker defines its bounds
+    #[used]
+    #[unsafe(link_section = "static_reflect_funcs")]
+    static EMPTY: SectionEntry = SectionEntry::EMPTY;
+
+    // Only the addresses of the bounds are used
+    extern "C" {
+        #[link_name = "__start_static_reflect_funcs"]
+        static START: u8;
+        #[link_name = "__stop_static_reflect_funcs"]
+        static STOP: u8;
+    }
+
+    pub fn bounds() -> (*const SectionEntry, *const SectionEntry) {
+        // Referencing the empty entry keeps its object file in the link
+        std::hint::black_box(&EMPTY);
+        ((&raw const START).cast(), (&raw const STOP).cast())
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod section {
+    use super::SectionEntry;
+
+    /// Ensures the section exists, so that the linker defines its bounds
+    #[used]
+    #[unsafe(link_section = "__DATA,__srfuncs,regular,no_dead_strip")]
+    static EMPTY: SectionEntry = SectionEntry::EMPTY;
+
+    // Only the addresses of the bounds are used
+    extern "C" {
+        // The leading \x01 stops the symbol from being prefixed with an underscore
+        #[link_name = "\x01section$start$__DATA$__srfuncs"]
+        static START: u8;
+        #[link_name = "\x01section$end$__DATA$__srfuncs"]
+        static STOP: u8;
+    }
+
+    pub fn bounds() -> (*const SectionEntry, *const SectionEntry) {
+        // Referencing the empty entry keeps its object file in the link
+        std::hint::black_box(&EMPTY);
+        ((&raw const START).cast(), (&raw const STOP).cast())
+    }
+}
+
+#[cfg(windows)]
+mod section {
+    use super::SectionEntry;
+
+    /// Sorted before every entry
+    #[used]
+    #[unsafe(link_section = ".srfuncs$a")]
+    static START: SectionEntry = SectionEntry::EMPTY;
+    /// Sorted after every entry
+    #[used]
+    #[unsafe(link_section = ".srfuncs$z")]
+    static STOP: SectionEntry = SectionEntry::EMPTY;
+
+    pub fn bounds() -> (*const SectionEntry, *const SectionEntry) {
+        let start: *const SectionEntry = &START;
+        // Skip the start marker (which is empty anyway)
+        (start.wrapping_add(1), &STOP)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_vendor = "apple",
+    windows,
+)))]
+mod section {
+    use super::SectionEntry;
+
+    pub fn bounds() -> (*const SectionEntry, *const SectionEntry) {
+        (std::ptr::null(), std::ptr::null())
+    }
+}
+
+/// The entries in the link sections of every linked crate
+fn section_entries() -> &'static [SectionEntry] {
+    let (start, stop) = section::bounds();
+    let len = (stop as usize).saturating_sub(start as usize) / std::mem::size_of::<SectionEntry>();
+    if len == 0 {
+        return &[];
+    }
+    // SAFETY: The linker places every entry between the bounds,
+    // which are aligned since all the entries have the same type
+    unsafe { std::slice::from_raw_parts(start, len) }
+}
+
+/// The most recently registered function
+static HEAD: AtomicPtr<Registration> = AtomicPtr::new(ptr::null_mut());
+
+/// An entry in the registry, which links to the previously registered entry
+///
+/// These are added explicitly with [register],
+/// which works even on targets without link sections.
+pub struct Registration {
+    declaration: &'static FunctionDeclaration,
+    registered: AtomicBool,
+    next: AtomicPtr<Registration>,
+}
+// SAFETY: The declarations are immutable, and an `AbsoluteAddress` is only an address
+unsafe impl Sync for Registration {}
+impl Registration {
+    /// Create an entry for the specified declaration, which isn't yet registered
+    #[inline]
+    pub const fn new(declaration: &'static FunctionDeclaration) -> Self {
+        Registration {
+            declaration,
+            registered: AtomicBool::new(false),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+    /// The declaration of the registered function
+    #[inline]
+    pub fn declaration(&self) -> &'static FunctionDeclaration {
+        self.declaration
+    }
+}
+
+/// Add an entry to the registry
+///
+/// Registering the same entry more than once has no effect.
+/// Different entries may have the same name.
+/// A function that is also placed in the link section (by [submit!](crate::funcs::registry::submit))
+/// appears twice.
+pub fn register(registration: &'static Registration) {
+    if registration.registered.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let entry = registration as *const Registration as *mut Registration;
+    let mut head = HEAD.load(Ordering::Acquire);
+    loop {
+        registration.next.store(head, Ordering::Relaxed);
+        match HEAD.compare_exchange_weak(head, entry, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return,
+            Err(actual) => head = actual,
+        }
+    }
+}
+
+/// Iterate over all the registered functions
+///
+/// This includes the functions in the link sections of every linked crate,
+/// followed by the ones added explicitly with [register].
+/// The order is otherwise unspecified.
+#[inline]
+pub fn iter() -> Iter {
+    Iter {
+        section: section_entries().iter(),
+        next: HEAD.load(Ordering::Acquire),
+    }
+}
+
+/// Find all the registered functions with the specified name
+///
+/// Names aren't required to be unique, so this may return multiple declarations.
+pub fn find(name: &str) -> Vec<&'static FunctionDeclaration> {
+    iter().filter(|decl| decl.name == name).collect()
+}
+
+/// An iterator over the registered functions (see [iter])
+#[derive(Clone, Debug)]
+pub struct Iter {
+    section: std::slice::Iter<'static, SectionEntry>,
+    next: *const Registration,
+}
+impl Iterator for Iter {
+    type Item = &'static FunctionDeclaration;
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.section.by_ref() {
+            if let Some(declaration) = entry.0 {
+                return Some(declaration);
+            }
+        }
+        // SAFETY: Entries are always `'static`, and are never removed once registered
+        let registration: &'static Registration = unsafe { self.next.as_ref()? };
+        self.next = registration.next.load(Ordering::Acquire);
+        Some(registration.declaration)
+    }
+}
@@ -10,26 +10,42 @@
 //! I'd be happy to add more features as long as they align with the general philosophy
 //! of compile-time reflection.
 #![deny(missing_docs)]
-#![feature(
-    const_option, // We use Option::unwrap
-)]
 #![cfg_attr(feature = "never", feature(never_type))]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
 
 #[cfg(feature = "builtins")]
 pub mod builtins;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod consts;
+#[cfg(feature = "dwarf")]
+pub mod dwarf;
 pub mod funcs;
-mod macros;
+pub mod macros;
+pub mod options;
+#[cfg(feature = "bytemuck")]
+pub mod pod;
+pub mod registry;
+pub mod stable;
 pub mod types;
+pub mod verify;
 
 mod core;
+#[cfg(feature = "simd")]
+mod simd;
 
 pub use crate::types::TypeInfo;
 
-use crate::types::{FloatSize, IntSize, IntType};
+use crate::types::report::{FieldLayout, LayoutReport};
+use crate::types::{DynamicSize, FloatSize, IntSize, IntType, SourceLocation};
 use std::ops::{Add, Mul, Sub};
 
 #[cfg(feature = "derive")]
-pub use static_reflect_derive::{reflect_func, StaticReflect};
+pub use static_reflect_derive::{reflect_const, reflect_func, StaticReflect};
+
+#[cfg(feature = "bytemuck")]
+#[doc(hidden)]
+pub use bytemuck;
 
 /// The trait for types whose information can be accessed via static reflection.
 ///
@@ -52,9 +68,61 @@ pub use static_reflect_derive::{reflect_func, StaticReflect};
 ///
 /// The type must be `#[repr(C)]` or have some other
 /// form of FFI safety.
+///
+/// The derive checks all of this at compile time.
+/// Manual implementations should be tested with [verify::verify_layout]
+/// (or the [assert_layout](macros::assert_layout) macro),
+/// which catches mismatched sizes, alignments and field offsets.
+///
+/// ## Unsized types
+/// Only sized types can be reflected, since every type needs a fixed size and alignment.
+/// The derive rejects fields of an unsized type, suggesting one of these alternatives:
+/// - A slice `[T]` can be replaced with `AsmSlice<T>` (a pointer and a length,
+///   from the `builtins` module), a pointer to the elements,
+///   or a trailing fixed-size array marked `#[reflect(opaque_array)]`.
+///   A header followed by a variable number of elements can end with a `[T; 0]`
+///   array, and be marked `#[reflect(unsized_tail)]`.
+/// - A `str` can be replaced with `AsmStr` (also from `builtins`) or a pointer to its bytes
+/// - A trait object (`dyn Trait`) can only be reflected through a thin pointer,
+///   since its vtable has no stable layout. This includes pointers like `Box<dyn Trait>`
+///   and `&dyn Trait`, which are also rejected. An [opaque_handle](crate::macros::opaque_handle)
+///   wraps such a pointer, to be passed to reflected functions operating on the object.
+/// - A type parameter declared `?Sized` needs the bound removed,
+///   or to be stored behind a pointer
 pub unsafe trait StaticReflect {
     /// The static information about the type's representation
     const TYPE_INFO: TypeInfo;
+    /// The size of the type in bytes, the same as `Self::TYPE_INFO.size()`
+    ///
+    /// This is usable anywhere a constant is, including array lengths.
+    /// Types whose layout is unknown at compile time (extern types, magic types and type parameters)
+    /// don't have a meaningful size, so using it for them fails to compile
+    /// instead of giving the dummy value of [TypeInfo::size].
+    ///
+    /// ## Example
+    /// ````
+    /// # use static_reflect::StaticReflect;
+    /// let buffer = [0u8; <u64 as StaticReflect>::SIZE];
+    /// assert_eq!(buffer.len(), 8);
+    /// ````
+    const SIZE: usize = types::ops::const_max_layout(&[&Self::TYPE_INFO]).size();
+    /// The alignment of the type, the same as `Self::TYPE_INFO.alignment()`
+    ///
+    /// Like [StaticReflect::SIZE], this fails to compile for types whose layout is unknown.
+    const ALIGN: usize = types::ops::const_max_layout(&[&Self::TYPE_INFO]).align();
+    /// The semantic unit of an integer newtype, like `"milliseconds"`
+    ///
+    /// This is given by `#[reflect(newtype_unit)]`, and is `None` for every other type.
+    /// The unit isn't part of the [TYPE_INFO](StaticReflect::TYPE_INFO),
+    /// which is the same as the wrapped integer, so units never affect comparisons of types.
+    /// They're only checked when explicitly requested
+    /// (see [CoercionRules::compare_units](funcs::coerce::CoercionRules::compare_units)).
+    const UNIT: Option<&'static str> = None;
+    /// Whether the type can be passed across an `extern "C"` boundary
+    ///
+    /// The derive computes this from the types of the fields (see [types::ffi] for the rules).
+    /// Every other type is assumed to be safe.
+    const FFI_SAFETY: types::ffi::FfiSafety = types::ffi::FfiSafety::Safe;
 }
 
 /// A primitive integer type
@@ -86,12 +154,25 @@ pub unsafe trait PrimFloat: StaticReflect + Copy + sealed::Sealed {
     /// The size of this float
     const FLOAT_SIZE: FloatSize;
 }
+/// A primitive numeric type, either a [PrimInt] or a [PrimFloat]
+///
+/// The representation of these types depends on their [Endian](crate::types::Endian)ness.
+///
+/// ## Safety
+/// Must actually be one of the primitive integer or float types.
+pub unsafe trait PrimNumber: StaticReflect + Copy + sealed::Sealed {}
 
 /// A type that supports accessing its fields via reflection.
 ///
 /// All fields are assumed to be defined in a way that is compatible
 /// with the the C ABI. In other words, the type must be `#[repr(C)]`
 ///
+/// Besides the [NAMED_FIELD_INFO](FieldReflect::NAMED_FIELD_INFO),
+/// the derive records some metadata which isn't part of the [TypeInfo]
+/// (like the [SOURCE](FieldReflect::SOURCE) and the [FIELD_UNITS](FieldReflect::FIELD_UNITS)).
+/// It doesn't affect comparisons or the [TypeInfoKey](types::key::TypeInfoKey) of the type,
+/// and is only available from the type itself, not from a [TypeInfo] referring to it.
+///
 /// ## Safety
 /// Implementing this type incorrectly is undefined behavior.
 pub unsafe trait FieldReflect: StaticReflect {
@@ -129,6 +210,40 @@ pub unsafe trait FieldReflect: StaticReflect {
     ///
     /// This is a singleton value.
     const NAMED_FIELD_INFO: Self::NamedFieldInfo;
+    /// The offset of each field as computed by the derive,
+    /// alongside its real offset (if known).
+    ///
+    /// This is only meant for debugging (see [FieldReflect::layout_report]).
+    const LAYOUT_DEBUG: &'static [FieldLayout] = &[];
+    /// How to compute the full size of a structure with an unsized tail,
+    /// if the length of the tail is stored in its header
+    ///
+    /// This is only given by structures derived with `#[reflect(unsized_tail(len = "field"))]`.
+    /// See [types::ops::dynamic_size_of].
+    const DYNAMIC_SIZE: Option<DynamicSize> = None;
+    /// Where the structure was defined, if known
+    ///
+    /// This is recorded by the derive, for diagnostics.
+    const SOURCE: Option<SourceLocation> = None;
+    /// The semantic unit of each field, in declaration order
+    ///
+    /// This is either given explicitly by `#[reflect(unit = "...")]`,
+    /// or comes from the [StaticReflect::UNIT] of the field's type.
+    /// It's empty if none of the fields have a unit (or in the minimal profile).
+    const FIELD_UNITS: &'static [Option<&'static str>] = &[];
+
+    /// Build a table comparing the computed offset of each field with its real offset
+    ///
+    /// Printing this is the easiest way to find which field's layout diverged.
+    fn layout_report() -> LayoutReport
+    where
+        Self: Sized,
+    {
+        LayoutReport {
+            type_name: std::any::type_name::<Self>(),
+            fields: Self::LAYOUT_DEBUG,
+        }
+    }
 }
 
 mod sealed {
@@ -1,9 +1,53 @@
+//! The macros provided by this crate
+//!
+//! Because of `#[macro_export]`, these are also available at the crate root.
+//! Their names there are prefixed with `sr_`, to avoid colliding with
+//! similar macros from other crates (like `memoffset`).
+//!
+//! The unprefixed root names (`static_reflect::field_offset!`) are deprecated.
+
+#[doc(inline)]
+pub use crate::{
+    sr_assert_fully_const as assert_fully_const, sr_assert_layout as assert_layout,
+    sr_define_extern_type as define_extern_type, sr_field_offset as field_offset,
+    sr_generate_layout_tests as generate_layout_tests, sr_max_layout as max_layout,
+    sr_opaque_handle as opaque_handle,
+};
+
 /// Define a type's implementation of [StaticReflect](crate::StaticReflect) as an extern type
 ///
 /// See [TypeInfo::Extern](crate::types::TypeInfo::Extern)
+///
+/// If the layout of the type is only known at runtime,
+/// it can be registered with [set_extern_layout](crate::registry::set_extern_layout)
+/// under the same name.
+///
+/// ## Name collisions
+/// Extern types are only identified by their name,
+/// so an extern type named `Foo` is easily confused with a reflected structure `Foo`
+/// by anything that looks types up by name. This is detected by
+/// [register_type](crate::registry::register_type) and [audit](crate::types::audit::audit),
+/// but is best avoided entirely by giving the extern type a namespace,
+/// which is prefixed to its name:
+/// ````
+/// # use static_reflect::macros::define_extern_type;
+/// # use static_reflect::{StaticReflect, TypeInfo};
+/// pub struct Foo {
+///     _opaque: [u8; 0],
+/// }
+/// define_extern_type!(Foo => Foo, namespace = "ffi");
+/// assert_eq!(Foo::TYPE_INFO, TypeInfo::Extern { name: "ffi::Foo" });
+/// ````
 #[macro_export]
-macro_rules! define_extern_type {
-    ($target:ident) => ($crate::define_extern_type!($target => $target););
+macro_rules! sr_define_extern_type {
+    ($target:ident) => ($crate::macros::define_extern_type!($target => $target););
+    ($target:ty => $defined_path:path, namespace = $namespace:literal) => {
+        unsafe impl $crate::StaticReflect for $target {
+            const TYPE_INFO: $crate::TypeInfo = $crate::TypeInfo::Extern {
+                name: concat!($namespace, "::", stringify!($defined_path))
+            };
+        }
+    };
     ($target:ty => $defined_path:path) => {
         unsafe impl $crate::StaticReflect for $target {
             const TYPE_INFO: $crate::TypeInfo = $crate::TypeInfo::Extern {
@@ -13,16 +57,104 @@ macro_rules! define_extern_type {
     };
 }
 
+/// Define an opaque handle to an object, which is passed across FFI as a non-null pointer
+///
+/// Trait objects (`Box<dyn Trait>`, `&dyn Trait`) can't be reflected,
+/// since their pointers carry a vtable with no stable layout.
+/// Instead, an object can be exposed as an opaque handle,
+/// along with a table of reflected functions taking the handle as their first argument.
+/// The functions cast the handle back to the real object on the Rust side.
+///
+/// The handle is a `#[repr(transparent)]` wrapper around a `NonNull<c_void>`,
+/// reflected the same way as a `NonNull` pointer.
+/// Since it is never null, `Option<Handle>` is a nullable handle (see [SimpleNonZeroRepr](crate::types::SimpleNonZeroRepr)).
+///
+/// ## Example
+#[cfg_attr(feature = "derive", doc = "````")]
+#[cfg_attr(not(feature = "derive"), doc = "````ignore")]
+/// use static_reflect::macros::opaque_handle;
+/// # use static_reflect::{reflect_func, StaticReflect};
+/// # use std::ffi::c_void;
+/// # use std::ptr::NonNull;
+/// trait Plugin {
+///     fn run(&mut self) -> u32;
+/// }
+/// opaque_handle!(
+///     /// A `Box<dyn Plugin>`, owned by the host
+///     pub struct PluginHandle
+/// );
+///
+/// #[reflect_func(absolute)]
+/// extern "C" fn plugin_run(handle: PluginHandle) -> u32 {
+///     let plugin = handle.as_ptr() as *mut Box<dyn Plugin>;
+///     unsafe { (*plugin).run() }
+/// }
+/// # struct Counter(u32);
+/// # impl Plugin for Counter {
+/// #     fn run(&mut self) -> u32 {
+/// #         self.0 += 1;
+/// #         self.0
+/// #     }
+/// # }
+/// let plugin: Box<Box<dyn Plugin>> = Box::new(Box::new(Counter(0)));
+/// let handle = PluginHandle::from_raw(NonNull::from(Box::leak(plugin)).cast::<c_void>());
+/// assert_eq!(plugin_run(handle), 1);
+/// assert_eq!(PluginHandle::TYPE_INFO, NonNull::<c_void>::TYPE_INFO);
+/// # drop(unsafe { Box::from_raw(handle.as_ptr() as *mut Box<dyn Plugin>) });
+/// ````
+#[macro_export]
+macro_rules! sr_opaque_handle {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident $(;)?) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        $vis struct $name(::core::ptr::NonNull<::core::ffi::c_void>);
+        impl $name {
+            /// Wrap a pointer to the object
+            #[inline]
+            #[allow(dead_code)]
+            pub const fn from_raw(ptr: ::core::ptr::NonNull<::core::ffi::c_void>) -> Self {
+                $name(ptr)
+            }
+            /// Wrap a pointer to the object, returning `None` if it's null
+            #[inline]
+            #[allow(dead_code)]
+            pub fn new(ptr: *mut ::core::ffi::c_void) -> ::core::option::Option<Self> {
+                ::core::ptr::NonNull::new(ptr).map($name)
+            }
+            /// The pointer to the object
+            #[inline]
+            #[allow(dead_code)]
+            pub const fn as_ptr(self) -> *mut ::core::ffi::c_void {
+                self.0.as_ptr()
+            }
+        }
+        unsafe impl $crate::StaticReflect for $name {
+            const TYPE_INFO: $crate::TypeInfo =
+                <::core::ptr::NonNull<::core::ffi::c_void> as $crate::StaticReflect>::TYPE_INFO;
+        }
+        // Never null, so `Option<$name>` uses the null pointer as `None`
+        unsafe impl $crate::types::SimpleNonZeroRepr for $name {}
+        const _: () = {
+            assert!(::core::mem::size_of::<$name>() == ::core::mem::size_of::<*mut ::core::ffi::c_void>());
+            assert!(::core::mem::size_of::<::core::option::Option<$name>>() == ::core::mem::size_of::<$name>());
+        };
+    };
+}
+
 /// Get the integer offset of the specified field
 ///
 /// This is only well defined for `#[repr(C)]` types,
 /// since `#[repr(Rust)]` types don't have a well-defined layout.
 ///
-/// Consider using [`std::mem::offset_of`] instead.
+/// This is a thin wrapper around [`core::mem::offset_of`],
+/// which additionally checks the type of the field.
+/// It never reads (or creates references to) any memory,
+/// so it is sound under miri.
 ///
 /// ## Examples
 /// ````
-/// # use static_reflect::field_offset;
+/// # use static_reflect::macros::field_offset;
 /// # #[repr(C)]
 /// # struct Nested {
 /// #     nested: u32
@@ -45,7 +177,7 @@ macro_rules! define_extern_type {
 /// this macro can also be used in a const-eval context.
 /// ````
 /// # #![deny(unused_features, stable_features)]
-/// # use static_reflect::field_offset;
+/// # use static_reflect::macros::field_offset;
 /// struct Example {
 ///     first: u32,
 ///     second: u8,
@@ -56,14 +188,247 @@ macro_rules! define_extern_type {
 /// assert_eq!(SECOND_OFFSET, 4);
 /// ````
 #[macro_export]
-macro_rules! field_offset {
-    ($target:path, $($field:tt).+) => (field_offset!($target, $($field).* as _));
-    ($target:path, $($field:tt).+ as $expected_type:ty) => {
-        unsafe {
-            let uninit = core::mem::MaybeUninit::<$target>::uninit();
-            let base = uninit.as_ptr();
-            let ptr: *const $expected_type = core::ptr::addr_of!((*base)$(.$field)*);
-            ptr.cast::<u8>().offset_from(base as *const u8) as usize
+macro_rules! sr_field_offset {
+    ($target:path, $($field:tt).+) => ($crate::macros::field_offset!($target, $($field).* as _));
+    ($target:path, $($field:tt).+ as $expected_type:ty) => {{
+        /*
+         * Check the type of the field, without ever touching memory.
+         * The closure is never invoked.
+         */
+        let _ = |value: *const $target| -> *const $expected_type {
+            unsafe { core::ptr::addr_of!((*value)$(.$field)*) }
+        };
+        core::mem::offset_of!($target, $($field).+)
+    }}
+}
+
+/// Generate `#[test]` functions checking that the reflected layout
+/// of each type matches the layout computed by the compiler.
+///
+/// For every type, this checks that:
+/// 1. The reflected size and alignment equal `size_of` and `align_of`
+/// 2. Every reflected field fits inside the type
+///    (except an unsized tail, which only needs its header to fit)
+/// 3. The reflected offset of each listed field equals its real offset
+///    (as computed by [`core::mem::offset_of`])
+/// 4. The offset of every other field agrees with its real offset,
+///    according to the [layout report](crate::FieldReflect::layout_report)
+///    (which is only available for derived types)
+///
+/// Fields to check are listed in braces after the type (tuple fields by index).
+/// Since the fields of a union never have any offset,
+/// listing them checks their real offset is zero.
+///
+/// All types must implement [FieldReflect](crate::FieldReflect)
+/// and be in scope as plain identifiers.
+/// The tests are placed in a module named `layout_tests`,
+/// so this may only be invoked once per module.
+///
+/// ## Example
+#[cfg_attr(feature = "derive", doc = "````")]
+#[cfg_attr(not(feature = "derive"), doc = "````ignore")]
+/// use static_reflect::macros::generate_layout_tests;
+/// # use static_reflect::StaticReflect;
+/// #[derive(StaticReflect)]
+/// #[repr(C)]
+/// struct Point {
+///     x: u8,
+///     y: u32,
+/// }
+/// #[derive(StaticReflect)]
+/// #[repr(C)]
+/// struct Pair(u16, u64);
+///
+/// generate_layout_tests!(Point { x, y }, Pair { 0, 1 });
+/// # fn main() {}
+/// ````
+#[macro_export]
+macro_rules! sr_generate_layout_tests {
+    ($($target:ident $({ $($field:tt),* $(,)? })?),* $(,)?) => {
+        #[cfg(test)]
+        mod layout_tests {
+            $(
+                /*
+                 * NOTE: This module shadows the name of the type,
+                 * but the glob import still brings the type into scope.
+                 */
+                #[allow(non_snake_case)]
+                mod $target {
+                    #[allow(unused_imports)]
+                    use super::super::*;
+
+                    #[test]
+                    fn layout() {
+                        let info = <$target as $crate::StaticReflect>::TYPE_INFO;
+                        let has_unsized_tail = ::core::matches!(
+                            info,
+                            $crate::types::TypeInfo::Structure(def) if def.has_unsized_tail
+                        );
+                        if has_unsized_tail {
+                            // Only the header is reflected
+                            ::core::assert!(
+                                info.size() <= ::core::mem::size_of::<$target>(),
+                                "Reflected header of {} is larger than the type",
+                                ::core::stringify!($target)
+                            );
+                        } else {
+                            ::core::assert_eq!(
+                                info.size(),
+                                ::core::mem::size_of::<$target>(),
+                                "Reflected size of {}",
+                                ::core::stringify!($target)
+                            );
+                        }
+                        ::core::assert_eq!(
+                            info.alignment(),
+                            ::core::mem::align_of::<$target>(),
+                            "Reflected alignment of {}",
+                            ::core::stringify!($target)
+                        );
+                        let fields: ::std::vec::Vec<(usize, &'static $crate::types::TypeInfo)> = match info {
+                            $crate::types::TypeInfo::Structure(def) => def
+                                .fields
+                                .iter()
+                                .map(|field| (field.offset, field.value_type.type_ref()))
+                                .collect(),
+                            $crate::types::TypeInfo::UntaggedUnion(def) => def
+                                .fields
+                                .iter()
+                                .map(|field| (field.offset(), field.value_type.type_ref()))
+                                .collect(),
+                            _ => ::core::panic!("Expected a structure or union: {}", ::core::stringify!($target)),
+                        };
+                        let field_count = fields.len();
+                        for (index, (offset, value_type)) in fields.into_iter().enumerate() {
+                            if has_unsized_tail && index + 1 == field_count {
+                                continue;
+                            }
+                            ::core::assert!(
+                                offset + value_type.size() <= info.size(),
+                                "Field #{} of {} doesn't fit inside it",
+                                index,
+                                ::core::stringify!($target)
+                            );
+                        }
+                        let report = <$target as $crate::FieldReflect>::layout_report();
+                        ::core::assert!(
+                            report.is_consistent(),
+                            "Reflected offsets of {} disagree with the real ones:\n{}",
+                            ::core::stringify!($target),
+                            report
+                        );
+                        $($(
+                            ::core::assert_eq!(
+                                <$target as $crate::FieldReflect>::NAMED_FIELD_INFO.$field.offset(),
+                                ::core::mem::offset_of!($target, $field),
+                                "Reflected offset of {}.{}",
+                                ::core::stringify!($target),
+                                ::core::stringify!($field)
+                            );
+                        )*)?
+                    }
+                }
+            )*
+        }
+    };
+}
+
+/// Assert that the reflected layout of each type matches its real layout
+///
+/// This panics with the first [LayoutMismatch](crate::verify::LayoutMismatch)
+/// found by [verify_layout](crate::verify::verify_layout).
+/// It's intended for testing manual implementations of [StaticReflect](crate::StaticReflect).
+///
+/// ## Example
+/// ````
+/// use static_reflect::macros::assert_layout;
+/// # use static_reflect::{types::IntType, StaticReflect, TypeInfo};
+/// #[repr(transparent)]
+/// struct Handle(u32);
+/// unsafe impl StaticReflect for Handle {
+///     const TYPE_INFO: TypeInfo = TypeInfo::Integer(IntType::U32);
+/// }
+/// assert_layout!(Handle, u64);
+/// ````
+#[macro_export]
+macro_rules! sr_assert_layout {
+    ($($target:ty),+ $(,)?) => {{
+        $(
+            if let ::core::result::Result::Err(mismatch) = $crate::verify::verify_layout::<$target>() {
+                ::core::panic!(
+                    "Invalid layout for {}: {}",
+                    ::core::stringify!($target),
+                    mismatch
+                );
+            }
+        )+
+    }};
+}
+
+/// The largest size and alignment over a list of reflected types, computed at compile time
+///
+/// This is a `Layout` that can hold a value of any of the types,
+/// computed from their `TYPE_INFO` by [const_max_layout](crate::types::ops::const_max_layout).
+/// It fails to compile if any of the types are opaque.
+/// For types whose layouts are only known at runtime, use [max_layout](crate::types::ops::max_layout).
+///
+/// ## Example
+/// ````
+/// use static_reflect::macros::max_layout;
+/// # use std::alloc::Layout;
+/// const SLOT: Layout = max_layout!(u8, u32, f64);
+/// assert_eq!((SLOT.size(), SLOT.align()), (8, 8));
+/// ````
+#[macro_export]
+macro_rules! sr_max_layout {
+    ($($target:ty),+ $(,)?) => {
+        const {
+            $crate::types::ops::const_max_layout(&[
+                $(&<$target as $crate::StaticReflect>::TYPE_INFO),+
+            ])
         }
-    }
+    };
+}
+
+/// Assert at compile time that the entire `TYPE_INFO` graph of each type is constant
+///
+/// This evaluates [TypeInfo::const_validate](crate::types::TypeInfo::const_validate)
+/// in an anonymous constant, so it fails to compile if any part of the graph
+/// can't be reached at compile time (like the fields of a `#[reflect(lazy_fields)]` union),
+/// or if it is nested too deeply.
+///
+/// ## Example
+/// ````
+/// use static_reflect::macros::assert_fully_const;
+/// # use static_reflect::{types::IntType, StaticReflect, TypeInfo};
+/// #[repr(transparent)]
+/// struct Handle(u32);
+/// unsafe impl StaticReflect for Handle {
+///     const TYPE_INFO: TypeInfo = TypeInfo::Integer(IntType::U32);
+/// }
+/// assert_fully_const!(Handle, u64);
+/// ````
+#[macro_export]
+macro_rules! sr_assert_fully_const {
+    ($($target:ty),+ $(,)?) => {
+        $(
+            const _: usize = <$target as $crate::StaticReflect>::TYPE_INFO.const_validate();
+        )+
+    };
+}
+
+/// Deprecated alias for [sr_define_extern_type]
+#[macro_export]
+#[doc(hidden)]
+#[deprecated(note = "Use static_reflect::macros::define_extern_type instead")]
+macro_rules! define_extern_type {
+    ($($args:tt)*) => { $crate::macros::define_extern_type! { $($args)* } };
+}
+
+/// Deprecated alias for [sr_field_offset]
+#[macro_export]
+#[doc(hidden)]
+#[deprecated(note = "Use static_reflect::macros::field_offset instead")]
+macro_rules! field_offset {
+    ($($args:tt)*) => ($crate::macros::field_offset!($($args)*));
 }
@@ -0,0 +1,224 @@
+//! Options controlling the behavior of the runtime utilities
+//!
+//! Different consumers in the same process often want different behaviors from the same utility.
+//! For example, a JIT must reject any type whose layout is unknown,
+//! while a debug console would rather show as much as it can.
+//! Instead of being configured globally, a [ReflectOptions] is passed explicitly to each utility,
+//! so consumers never affect each other.
+//! The plain versions of the utilities (like [CopyPlan::analyze](crate::types::ops::CopyPlan::analyze))
+//! use the [Default] options, which match their documented behavior.
+//!
+//! ## Utilities
+//! | Utility | `max_depth` | `opaque_size_policy` | `union_inspection` |
+//! |---------|-------------|----------------------|--------------------|
+//! | [CopyPlan::analyze_with](crate::types::ops::CopyPlan::analyze_with) | Structures are copied entirely | Yes | Yes |
+//! | [verify_type_info_with](crate::verify::verify_type_info_with) | Fields aren't checked | No | Yes |
+//! | [audit_with](crate::types::audit::audit_with) | Fields aren't visited | No | Yes |
+//! | [TypeInfo::display_with] | Types are only named | No | Yes |
+//!
+//! Depths are counted from the root type, which is at depth zero.
+//! The `max_depth` column describes what happens to the types at the maximum depth (or deeper),
+//! so a `max_depth` of zero only considers the root type as a whole.
+use std::fmt::{self, Display, Formatter};
+
+use crate::types::{OpaqueTypeError, TypeInfo};
+
+/// How a utility handles a type whose size is unknown
+///
+/// The size of an extern type is unknown unless it was registered at runtime
+/// (see [crate::registry]), and the sizes of magic types and type parameters are never known.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum OpaqueSizePolicy {
+    /// Panic with the [OpaqueTypeError]
+    Panic,
+    /// Fail with the [OpaqueTypeError]
+    #[default]
+    Error,
+    /// Use [TypeInfo::OPAQUE_SIZE] as the size, like the const [TypeInfo::size]
+    ///
+    /// Utilities which access memory skip the opaque type instead.
+    Sentinel,
+}
+
+/// How a utility handles untagged unions
+///
+/// The active field of a union is unknown, so it can only be inspected as raw bytes.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum UnionInspection {
+    /// Ignore the contents of unions
+    Skip,
+    /// Treat the contents of unions as raw bytes, including every field
+    #[default]
+    Raw,
+}
+
+/// Options controlling the behavior of the runtime utilities
+///
+/// See the [module documentation](self) for the utilities accepting them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ReflectOptions {
+    /// How deep to descend into the fields of nested types
+    ///
+    /// By default, this is [TypeInfo::MAX_CONST_DEPTH],
+    /// which is deeper than any type accepted by [TypeInfo::const_validate].
+    pub max_depth: usize,
+    /// How to handle a type whose size is unknown
+    pub opaque_size_policy: OpaqueSizePolicy,
+    /// How to handle untagged unions
+    pub union_inspection: UnionInspection,
+}
+impl ReflectOptions {
+    /// The default options
+    pub const DEFAULT: ReflectOptions = ReflectOptions {
+        max_depth: TypeInfo::MAX_CONST_DEPTH,
+        opaque_size_policy: OpaqueSizePolicy::Error,
+        union_inspection: UnionInspection::Raw,
+    };
+    /// The size of the specified type, handled according to the [OpaqueSizePolicy]
+    ///
+    /// This includes the layouts of extern types registered at runtime.
+    /// Like [TypeInfo::try_size], a type is opaque if any of its components are.
+    ///
+    /// ## Panics
+    /// If the size is unknown and the policy is [OpaqueSizePolicy::Panic].
+    pub fn size_of(&self, info: &TypeInfo) -> Result<usize, OpaqueTypeError> {
+        match info.try_size() {
+            Ok(size) => Ok(size),
+            Err(cause) => match self.opaque_size_policy {
+                OpaqueSizePolicy::Panic => panic!("{}", cause),
+                OpaqueSizePolicy::Error => Err(cause),
+                // Structures with opaque fields still have a known size
+                OpaqueSizePolicy::Sentinel => {
+                    Ok(info.resolved_size().unwrap_or(TypeInfo::OPAQUE_SIZE))
+                }
+            },
+        }
+    }
+}
+impl Default for ReflectOptions {
+    #[inline]
+    fn default() -> Self {
+        ReflectOptions::DEFAULT
+    }
+}
+
+impl TypeInfo {
+    /// Display the type along with its fields, expanding nested types up to the maximum depth
+    ///
+    /// Structures and unions are written like `Name { field: Type }`,
+    /// or `Name(Type)` for tuple structs. Types at [ReflectOptions::max_depth] (or deeper)
+    /// are only named, the same as the plain `Display` (which is equivalent to a depth of zero).
+    /// Skipped unions are written like `Name { .. }`.
+    ///
+    /// ## Example
+    /// ````
+    /// # use static_reflect::options::ReflectOptions;
+    /// # use static_reflect::types::{FieldDef, FieldMeta, StructureDef, TypeId, TypeInfo};
+    /// # const fn field(name: &'static str, offset: usize) -> FieldDef {
+    /// #     FieldDef {
+    /// #         name: Some(name),
+    /// #         value_type: TypeId::erased::<f32>(),
+    /// #         offset,
+    /// #         index: offset / 4,
+    /// #         endianness: None,
+    /// #         assumed: false,
+    /// #         opaque_array: false,
+    /// #         meta: &FieldMeta::NONE,
+    /// #     }
+    /// # }
+    /// # const POINT: StructureDef = StructureDef {
+    /// #     name: "Point",
+    /// #     fields: &[field("x", 0), field("y", 4)],
+    /// #     size: 8,
+    /// #     alignment: 4,
+    /// #     is_pod: true,
+    /// #     has_unsized_tail: false,
+    /// #     needs_drop: false,
+    /// # };
+    /// let point = TypeInfo::Structure(&POINT);
+    /// assert_eq!(point.to_string(), "Point");
+    /// assert_eq!(
+    ///     point.display_with(&ReflectOptions::default()).to_string(),
+    ///     "Point { x: f32, y: f32 }"
+    /// );
+    /// ````
+    #[inline]
+    pub fn display_with<'a>(&'a self, options: &'a ReflectOptions) -> DisplayWith<'a> {
+        DisplayWith {
+            info: self,
+            options,
+            depth: 0,
+        }
+    }
+}
+
+/// Displays a type along with its fields (see [TypeInfo::display_with])
+#[derive(Copy, Clone, Debug)]
+pub struct DisplayWith<'a> {
+    info: &'a TypeInfo,
+    options: &'a ReflectOptions,
+    depth: usize,
+}
+impl DisplayWith<'_> {
+    fn nested<'b>(&'b self, info: &'b TypeInfo) -> DisplayWith<'b> {
+        DisplayWith {
+            info,
+            options: self.options,
+            depth: self.depth + 1,
+        }
+    }
+}
+impl Display for DisplayWith<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.depth >= self.options.max_depth {
+            return Display::fmt(self.info, f);
+        }
+        match *self.info {
+            TypeInfo::Structure(def) if def.fields.is_empty() => f.write_str(def.name),
+            TypeInfo::UntaggedUnion(def) if def.fields.is_empty() => f.write_str(def.name),
+            TypeInfo::Structure(def) if def.fields.iter().all(|field| field.name.is_none()) => {
+                write!(f, "{}(", def.name)?;
+                for (index, field) in def.fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", self.nested(field.value_type.type_ref()))?;
+                }
+                f.write_str(")")
+            }
+            TypeInfo::Structure(def) => {
+                write!(f, "{} {{ ", def.name)?;
+                for (index, field) in def.fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match field.name {
+                        Some(name) => write!(f, "{}: ", name)?,
+                        None => write!(f, "{}: ", field.index)?,
+                    }
+                    write!(f, "{}", self.nested(field.value_type.type_ref()))?;
+                }
+                f.write_str(" }")
+            }
+            TypeInfo::UntaggedUnion(def) => match self.options.union_inspection {
+                UnionInspection::Skip => write!(f, "{} {{ .. }}", def.name),
+                UnionInspection::Raw => {
+                    write!(f, "{} {{ ", def.name)?;
+                    for (index, field) in def.fields.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str(", ")?;
+                        }
+                        write!(
+                            f,
+                            "{}: {}",
+                            field.name,
+                            self.nested(field.value_type.type_ref())
+                        )?;
+                    }
+                    f.write_str(" }")
+                }
+            },
+            ref other => Display::fmt(other, f),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Integration with [bytemuck], for types that are
+//! both reflected and [Pod](bytemuck::Pod)
+//!
+//! Structures can record this with `#[reflect(pod)]`,
+//! which asserts the type implements `Pod` at compile time.
+use crate::StaticReflect;
+use bytemuck::{Pod, PodCastError};
+use std::fmt::{self, Display, Formatter};
+
+/// An error casting bytes to a reflected type
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CastError {
+    /// The reflected size of the type doesn't match its real size
+    ///
+    /// This indicates an incorrect implementation of [StaticReflect].
+    MismatchedSize {
+        /// The size given by the type's reflected information
+        reflected: usize,
+        /// The actual size of the type
+        actual: usize,
+    },
+    /// The length of the bytes is not a multiple of the type's size
+    InvalidLength {
+        /// The length of the bytes
+        len: usize,
+        /// The (reflected) size of each element
+        element_size: usize,
+    },
+    /// The underlying cast failed
+    Pod(PodCastError),
+}
+impl Display for CastError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            CastError::MismatchedSize { reflected, actual } => write!(
+                f,
+                "Reflected size {} doesn't match actual size {}",
+                reflected, actual
+            ),
+            CastError::InvalidLength { len, element_size } => write!(
+                f,
+                "Length {} is not a multiple of the element size {}",
+                len, element_size
+            ),
+            CastError::Pod(cause) => write!(f, "{}", cause),
+        }
+    }
+}
+impl std::error::Error for CastError {}
+
+/// Check the reflected size of `T`, returning it on success
+fn check_size<T: StaticReflect>(len: usize) -> Result<usize, CastError> {
+    let reflected = T::TYPE_INFO.size();
+    let actual = std::mem::size_of::<T>();
+    if reflected != actual {
+        return Err(CastError::MismatchedSize { reflected, actual });
+    }
+    if reflected != 0 && !len.is_multiple_of(reflected) {
+        return Err(CastError::InvalidLength {
+            len,
+            element_size: reflected,
+        });
+    }
+    Ok(reflected)
+}
+
+/// Cast the specified bytes into a slice of `T`,
+/// validating the length against the reflected size.
+#[inline]
+pub fn cast_slice_reflect<T: StaticReflect + Pod>(bytes: &[u8]) -> Result<&[T], CastError> {
+    check_size::<T>(bytes.len())?;
+    bytemuck::try_cast_slice(bytes).map_err(CastError::Pod)
+}
+
+/// Cast the specified mutable bytes into a slice of `T`,
+/// validating the length against the reflected size.
+#[inline]
+pub fn cast_slice_reflect_mut<T: StaticReflect + Pod>(
+    bytes: &mut [u8],
+) -> Result<&mut [T], CastError> {
+    check_size::<T>(bytes.len())?;
+    bytemuck::try_cast_slice_mut(bytes).map_err(CastError::Pod)
+}
@@ -0,0 +1,201 @@
+//! Layouts of extern types which are only known at runtime
+//!
+//! The size of a foreign object is sometimes only known at startup
+//! (for example, from a C `sizeof` query). Extern types declared with
+//! [define_extern_type](crate::macros::define_extern_type) can have their layout
+//! registered here, which [TypeInfo::resolved_size](crate::TypeInfo::resolved_size)
+//! (and everything built on it) will use.
+//!
+//! Layouts are registered by the name of the extern type, and can't be changed once set.
+//!
+//! ## Types by name
+//! Extern types only compare by name, so an extern type and a real structure
+//! can end up sharing a name in the same program. Consumers which look types up by name
+//! should [register_type] them first, which detects any such collision.
+use crate::TypeInfo;
+use std::alloc::Layout;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{OnceLock, RwLock};
+
+static EXTERN_LAYOUTS: OnceLock<RwLock<HashMap<String, Layout>>> = OnceLock::new();
+
+fn extern_layouts() -> &'static RwLock<HashMap<String, Layout>> {
+    EXTERN_LAYOUTS.get_or_init(Default::default)
+}
+
+static NAMED_TYPES: OnceLock<RwLock<HashMap<&'static str, &'static TypeInfo>>> = OnceLock::new();
+
+fn named_types() -> &'static RwLock<HashMap<&'static str, &'static TypeInfo>> {
+    NAMED_TYPES.get_or_init(Default::default)
+}
+
+/// Register the layout of the extern type with the specified name
+///
+/// Registering the same layout again has no effect,
+/// but registering a different one is an error.
+pub fn set_extern_layout(name: &str, size: usize, alignment: usize) -> Result<(), RegistryError> {
+    let requested =
+        Layout::from_size_align(size, alignment).map_err(|_| RegistryError::InvalidLayout {
+            name: name.into(),
+            size,
+            alignment,
+        })?;
+    // NOTE: The map is never left in an inconsistent state, so poisoning is harmless
+    let mut layouts = extern_layouts()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match layouts.get(name) {
+        Some(&existing) if existing == requested => Ok(()),
+        Some(&existing) => Err(RegistryError::ConflictingLayout {
+            name: name.into(),
+            existing,
+            requested,
+        }),
+        None => {
+            layouts.insert(name.into(), requested);
+            Ok(())
+        }
+    }
+}
+
+/// The layout registered for the extern type with the specified name (if any)
+pub fn extern_layout(name: &str) -> Option<Layout> {
+    extern_layouts()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(name)
+        .copied()
+}
+
+/// Register a nominal type by its [name](TypeInfo::nominal_name)
+///
+/// Registering the same type again has no effect,
+/// but registering a different type with the same name is an error.
+/// This includes an extern type with the same name as a structure.
+pub fn register_type(info: &'static TypeInfo) -> Result<(), RegistryError> {
+    let name = info
+        .nominal_name()
+        .ok_or_else(|| RegistryError::UnnamedType {
+            type_name: info.to_string(),
+        })?;
+    // NOTE: The map is never left in an inconsistent state, so poisoning is harmless
+    let mut types = named_types()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match types.get(name) {
+        Some(&existing) if *existing == *info => Ok(()),
+        Some(&existing) => Err(RegistryError::ConflictingType {
+            name: name.into(),
+            existing,
+            requested: info,
+        }),
+        None => {
+            types.insert(name, info);
+            Ok(())
+        }
+    }
+}
+
+/// The type registered with the specified name (if any)
+pub fn registered_type(name: &str) -> Option<&'static TypeInfo> {
+    named_types()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(name)
+        .copied()
+}
+
+/// An error registering the layout of an extern type,
+/// or registering a type by name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The alignment isn't a power of two,
+    /// or the size overflows when rounded up to it
+    InvalidLayout {
+        /// The name of the extern type
+        name: String,
+        /// The requested size
+        size: usize,
+        /// The requested alignment
+        alignment: usize,
+    },
+    /// A different layout has already been registered
+    ConflictingLayout {
+        /// The name of the extern type
+        name: String,
+        /// The layout which was already registered
+        existing: Layout,
+        /// The conflicting layout
+        requested: Layout,
+    },
+    /// The type doesn't have a name to register it by
+    UnnamedType {
+        /// The name of the (reflected) type
+        type_name: String,
+    },
+    /// A different type has already been registered with the same name
+    ConflictingType {
+        /// The name of both types
+        name: String,
+        /// The type which was already registered
+        existing: &'static TypeInfo,
+        /// The conflicting type
+        requested: &'static TypeInfo,
+    },
+}
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            RegistryError::InvalidLayout {
+                ref name,
+                size,
+                alignment,
+            } => write!(
+                f,
+                "Invalid layout for extern {}: size {}, alignment {}",
+                name, size, alignment
+            ),
+            RegistryError::ConflictingLayout {
+                ref name,
+                existing,
+                requested,
+            } => write!(
+                f,
+                "Conflicting layouts for extern {}: size {} and alignment {} were already registered, not {} and {}",
+                name,
+                existing.size(),
+                existing.align(),
+                requested.size(),
+                requested.align()
+            ),
+            RegistryError::UnnamedType { ref type_name } => {
+                write!(f, "Type {} doesn't have a name to register", type_name)
+            }
+            RegistryError::ConflictingType {
+                ref name,
+                existing,
+                requested,
+            } => write!(
+                f,
+                "Conflicting types named {}: {} was already registered, not {}",
+                name,
+                describe(existing),
+                describe(requested)
+            ),
+        }
+    }
+}
+impl std::error::Error for RegistryError {}
+
+/// Describe the kind of a nominal type, since both types have the same name
+fn describe(info: &TypeInfo) -> String {
+    match *info {
+        TypeInfo::Structure(_) => format!("struct {}", info),
+        TypeInfo::UntaggedUnion(_) => format!("union {}", info),
+        TypeInfo::TaggedUnion(_) | TypeInfo::CStyleEnum(_) | TypeInfo::Niche(_) => {
+            format!("enum {}", info)
+        }
+        _ => info.to_string(),
+    }
+}
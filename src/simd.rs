@@ -0,0 +1,76 @@
+//! Implementations of [StaticReflect] for SIMD vectors, as [TypeInfo::Vector]
+//!
+//! The vectors of `core::arch` are only available on their own architecture.
+//! The portable `std::simd::Simd` requires the `portable_simd` feature (and nightly).
+use crate::types::{PrimitiveType, TypeInfo};
+use crate::StaticReflect;
+use std::mem::{align_of, size_of};
+
+/// The type info of a vector `V`, with the specified number of lanes of type `E`
+///
+/// Fails to compile if the vector is padded, since its size must be exactly that of its lanes.
+const fn vector<V, E: StaticReflect>(lanes: usize) -> TypeInfo {
+    let element = PrimitiveType::of::<E>();
+    assert!(
+        size_of::<V>() == element.bytes() * lanes,
+        "The size of the vector doesn't match its lanes"
+    );
+    TypeInfo::Vector {
+        element,
+        lanes,
+        alignment: align_of::<V>(),
+    }
+}
+
+macro_rules! impl_arch_vectors {
+    ($arch:ident { $($vector:ident => [$element:ty; $lanes:literal]),* $(,)? }) => {
+        $(unsafe impl StaticReflect for core::arch::$arch::$vector {
+            const TYPE_INFO: TypeInfo = vector::<Self, $element>($lanes);
+        })*
+    };
+}
+
+// NOTE: The integer vectors don't have a lane type, so they use their declared representation
+#[cfg(target_arch = "x86_64")]
+impl_arch_vectors!(x86_64 {
+    __m128 => [f32; 4],
+    __m128d => [f64; 2],
+    __m128i => [i64; 2],
+    __m256 => [f32; 8],
+    __m256d => [f64; 4],
+    __m256i => [i64; 4],
+});
+#[cfg(target_arch = "x86")]
+impl_arch_vectors!(x86 {
+    __m128 => [f32; 4],
+    __m128d => [f64; 2],
+    __m128i => [i64; 2],
+    __m256 => [f32; 8],
+    __m256d => [f64; 4],
+    __m256i => [i64; 4],
+});
+#[cfg(target_arch = "aarch64")]
+impl_arch_vectors!(aarch64 {
+    float32x4_t => [f32; 4],
+    float64x2_t => [f64; 2],
+    int8x16_t => [i8; 16],
+    int16x8_t => [i16; 8],
+    int32x4_t => [i32; 4],
+    int64x2_t => [i64; 2],
+    uint8x16_t => [u8; 16],
+    uint16x8_t => [u16; 8],
+    uint32x4_t => [u32; 4],
+    uint64x2_t => [u64; 2],
+});
+
+/// A portable vector
+///
+/// Vectors whose number of lanes isn't a power of two (like `Simd<f32, 3>`)
+/// are padded, so they fail to compile.
+#[cfg(feature = "portable_simd")]
+unsafe impl<T, const N: usize> StaticReflect for std::simd::Simd<T, N>
+where
+    T: std::simd::SimdElement + StaticReflect,
+{
+    const TYPE_INFO: TypeInfo = vector::<Self, T>(N);
+}
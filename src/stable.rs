@@ -0,0 +1,93 @@
+//! A minimal subset of the API, which is frozen for long-term support
+//!
+//! Everything else in this crate may change between (pre-1.0) releases.
+//! The items re-exported here won't, except in a new major version:
+//! - The [StaticReflect] and [FieldReflect] traits (along with the derive, if the `derive` feature is enabled)
+//! - [TypeInfo](types::TypeInfo), [StructureDef](types::StructureDef), [FieldDef](types::FieldDef)
+//!   and [TypeId](types::TypeId), along with the integer and float types they refer to
+//! - [FieldMeta](types::FieldMeta), but only as an extension point:
+//!   the metadata recorded in it isn't part of the subset
+//!
+//! Only the variants of [TypeInfo](types::TypeInfo) for primitives, pointers and structures are stable:
+//! `Unit`, `Bool`, `Integer`, `Float`, `Pointer`, `NonZero` and `Structure`.
+//! New variants may be added at any time, so a match on a `TypeInfo` always needs a wildcard arm.
+//!
+//! The derive is only stable for plain structs (including renamed fields).
+//! Its other options (and its support for enums and unions) are outside the subset.
+//!
+//! ## Deriving through the facade
+//! This module can be used as the crate path of the derive,
+//! so that generated code only names the stable paths:
+#![cfg_attr(feature = "derive", doc = "````")]
+#![cfg_attr(not(feature = "derive"), doc = "````ignore")]
+//! use static_reflect::stable::StaticReflect;
+//! #[derive(StaticReflect)]
+//! #[reflect(crate = "static_reflect::stable")]
+//! #[repr(C)]
+//! struct Point {
+//!     x: f32,
+//!     y: f32,
+//! }
+//! assert_eq!(Point::TYPE_INFO.size(), 8);
+//! ````
+//!
+//! The snapshot test in `tests/stable_api.rs` names every item of the subset,
+//! so any breaking change to it fails to compile.
+
+/// The trait for types whose information can be accessed via static reflection
+///
+/// Stable, along with its associated `TYPE_INFO` (and the derive for plain structs).
+/// Its `UNIT` and `FFI_SAFETY` aren't stable.
+pub use crate::StaticReflect;
+
+/// A type that supports accessing its fields via reflection
+///
+/// Stable, except for `LAYOUT_DEBUG` and `layout_report`, which are only meant for debugging,
+/// and `DYNAMIC_SIZE`, `SOURCE` and `FIELD_UNITS`.
+pub use crate::FieldReflect;
+
+/// The stable subset of the [types](crate::types) module
+pub mod types {
+    /// Static information on the definition of a structure
+    ///
+    /// Stable, along with all of its fields and [StructureDef::field_def].
+    pub use crate::types::StructureDef;
+
+    /// The definition of a field
+    ///
+    /// Stable, along with all of its fields, [FieldDef::erase] and [FieldDef::offset].
+    pub use crate::types::FieldDef;
+
+    /// Additional metadata about a field
+    ///
+    /// Only the type itself and [FieldMeta::NONE] are stable.
+    /// Its contents are outside the subset, and it can't be built with a struct literal,
+    /// so new kinds of metadata can be added without breaking [FieldDef].
+    pub use crate::types::FieldMeta;
+
+    /// The static information about a type
+    ///
+    /// Only the variants for primitives, pointers and structures are stable,
+    /// along with [TypeInfo::size], [TypeInfo::alignment] and its `Display`.
+    pub use crate::types::TypeInfo;
+
+    /// A static reference to a type
+    ///
+    /// Stable, along with [TypeId::get], [TypeId::erase] and [TypeId::type_ref].
+    pub use crate::types::TypeId;
+
+    /// The types of integers and floats, which are referred to by [TypeInfo]
+    pub use crate::types::{FloatSize, IntSize, IntType};
+
+    /// Implementation details of the derive
+    #[doc(hidden)]
+    pub mod report {
+        pub use crate::types::report::FieldLayout;
+    }
+    #[doc(hidden)]
+    pub mod ffi {
+        pub use crate::types::ffi::FfiSafety;
+    }
+    #[doc(hidden)]
+    pub use crate::types::{known_units, SourceLocation};
+}
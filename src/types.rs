@@ -1,8 +1,29 @@
 //! The static type system
 use crate::{FieldReflect, PrimFloat, PrimInt, StaticReflect};
 
+pub mod audit;
+pub mod bitset;
+pub mod compare;
+pub mod diff;
+pub mod downcast;
+pub mod encode;
+pub mod ffi;
+pub mod key;
+pub mod lattice;
+pub mod leaves;
+pub mod lint;
+pub mod migrate;
+pub mod ops;
+pub mod path;
+pub mod report;
+pub mod schema;
+pub mod transmute;
+pub mod validate;
+pub mod value;
+
 use std::cmp::Ordering;
-use std::fmt::{self, Debug, Display, Formatter, Write};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 
 #[cfg(feature = "builtins")]
@@ -14,6 +35,9 @@ use std::alloc::Layout;
 ///
 /// If `T: SimpleNonZeroRepr` -> `sizeof(Option<T>) == sizeof(T) && repr(Option<T>) == repr(T)`
 ///
+/// Implementations should record this in their type info,
+/// by wrapping it in [TypeInfo::NonZero].
+///
 /// ## Safety
 /// Zero must never be a valid value for this type.
 ///
@@ -111,7 +135,7 @@ impl Default for IntSize {
     }
 }
 /// An error indicating that the size is invalid
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct InvalidSizeErr {
     /// The size in bytes that is considered invalid
     pub bytes: usize,
@@ -166,6 +190,71 @@ impl Default for FloatSize {
     }
 }
 
+/// The byte order of a numeric value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// The most significant byte comes first
+    Big,
+    /// The least significant byte comes first
+    Little,
+}
+impl Endian {
+    /// The byte order of the target platform
+    pub const NATIVE: Endian = if cfg!(target_endian = "big") {
+        Endian::Big
+    } else {
+        Endian::Little
+    };
+    /// Check if this is the byte order of the target platform
+    #[inline]
+    pub const fn is_native(self) -> bool {
+        matches!(
+            (self, Endian::NATIVE),
+            (Endian::Big, Endian::Big) | (Endian::Little, Endian::Little)
+        )
+    }
+}
+impl Display for Endian {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Endian::Big => "big",
+            Endian::Little => "little",
+        })
+    }
+}
+
+/// The ownership discipline of a pointer (`#[reflect(ptr = "...")]`)
+///
+/// This is only documentation for tools like linters,
+/// and has no effect on the layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PointerDiscipline {
+    /// The pointer owns its target, which must be freed along with it
+    Owned,
+    /// The pointer borrows its target, which is owned elsewhere
+    Borrowed,
+    /// The pointer has no particular discipline
+    ///
+    /// This is what undocumented pointers are assumed to be.
+    #[default]
+    Raw,
+}
+impl PointerDiscipline {
+    /// The name of the discipline, as given to `#[reflect(ptr = "...")]`
+    pub const fn name(self) -> &'static str {
+        match self {
+            PointerDiscipline::Owned => "owned",
+            PointerDiscipline::Borrowed => "borrowed",
+            PointerDiscipline::Raw => "raw",
+        }
+    }
+}
+impl Display for PointerDiscipline {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// An integer type
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct IntType {
@@ -174,6 +263,16 @@ pub struct IntType {
     /// If this integer is signed
     pub signed: bool,
 }
+/// The default is a signed `int`, like the default [IntSize]
+impl Default for IntType {
+    #[inline]
+    fn default() -> IntType {
+        IntType {
+            size: IntSize::default(),
+            signed: true,
+        }
+    }
+}
 impl IntType {
     /// Get the type of the specified primitive integer
     #[inline]
@@ -239,12 +338,94 @@ impl IntType {
     pub const I64: IntType = IntSize::Long.signed();
     /// The type of the signed `isize` integer
     pub const ISIZE: IntType = IntSize::POINTER.signed();
+    /// The name of this integer in Rust (like `u32` or `i8`)
+    pub const fn rust_name(self) -> &'static str {
+        match (self.size, self.signed) {
+            (IntSize::Byte, false) => "u8",
+            (IntSize::Short, false) => "u16",
+            (IntSize::Int, false) => "u32",
+            (IntSize::Long, false) => "u64",
+            (IntSize::Byte, true) => "i8",
+            (IntSize::Short, true) => "i16",
+            (IntSize::Int, true) => "i32",
+            (IntSize::Long, true) => "i64",
+        }
+    }
+    /// The name of this integer in C, using the fixed-width types from `<stdint.h>`
+    pub const fn c_name(self) -> &'static str {
+        match (self.size, self.signed) {
+            (IntSize::Byte, false) => "uint8_t",
+            (IntSize::Short, false) => "uint16_t",
+            (IntSize::Int, false) => "uint32_t",
+            (IntSize::Long, false) => "uint64_t",
+            (IntSize::Byte, true) => "int8_t",
+            (IntSize::Short, true) => "int16_t",
+            (IntSize::Int, true) => "int32_t",
+            (IntSize::Long, true) => "int64_t",
+        }
+    }
+    /// Read the raw bits of an integer of this type,
+    /// zero-extending them to a `u64`
+    ///
+    /// ## Safety
+    /// The pointer must be valid for reads of [IntSize::bytes].
+    /// It doesn't need to be aligned.
+    #[inline]
+    pub unsafe fn read_bits(self, ptr: *const u8) -> u64 {
+        match self.size {
+            IntSize::Byte => ptr.read() as u64,
+            IntSize::Short => (ptr as *const u16).read_unaligned() as u64,
+            IntSize::Int => (ptr as *const u32).read_unaligned() as u64,
+            IntSize::Long => (ptr as *const u64).read_unaligned(),
+        }
+    }
+    /// Write the raw bits of an integer of this type,
+    /// truncating them to the appropriate size.
+    ///
+    /// ## Safety
+    /// The pointer must be valid for writes of [IntSize::bytes].
+    /// It doesn't need to be aligned.
+    #[inline]
+    pub unsafe fn write_bits(self, ptr: *mut u8, bits: u64) {
+        match self.size {
+            IntSize::Byte => ptr.write(bits as u8),
+            IntSize::Short => (ptr as *mut u16).write_unaligned(bits as u16),
+            IntSize::Int => (ptr as *mut u32).write_unaligned(bits as u32),
+            IntSize::Long => (ptr as *mut u64).write_unaligned(bits),
+        }
+    }
+    /// Truncate the specified bits to the size of this integer,
+    /// zeroing any bits beyond its width.
+    ///
+    /// For signed integers, this gives the two's complement representation.
+    #[inline]
+    pub const fn truncate_bits(self, bits: u64) -> u64 {
+        let width = self.size.bytes() as u32 * 8;
+        if width == 64 {
+            bits
+        } else {
+            bits & ((1u64 << width) - 1)
+        }
+    }
+    /// Interpret the specified bits as a value of this type,
+    /// sign-extending if the integer is signed.
+    ///
+    /// Bits beyond the size of this integer are ignored.
+    #[inline]
+    pub const fn extend_bits(self, bits: u64) -> i128 {
+        let width = self.size.bytes() as u32 * 8;
+        let truncated = self.truncate_bits(bits);
+        if self.signed {
+            let shift = 64 - width;
+            (((truncated << shift) as i64) >> shift) as i128
+        } else {
+            truncated as i128
+        }
+    }
 }
 impl Display for IntType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_char(if self.signed { 'i' } else { 'u' })?;
-        write!(f, "{}", self.size.bytes() * 8)?;
-        Ok(())
+        f.write_str(self.rust_name())
     }
 }
 
@@ -429,6 +610,20 @@ pub enum TypeInfo {
         /// The size/precision of the float
         size: FloatSize,
     },
+    /// A SIMD vector of primitive lanes, like `__m128` or `Simd<f32, 4>`
+    ///
+    /// The vector is opaque, with a fixed layout:
+    /// its size is the size of the element times the number of lanes.
+    /// The alignment is recorded explicitly,
+    /// since it's usually larger than the alignment of the element.
+    Vector {
+        /// The type of each lane
+        element: PrimitiveType,
+        /// The number of lanes
+        lanes: usize,
+        /// The alignment of the whole vector, in bytes
+        alignment: usize,
+    },
     /// A slice of memory, represented as pointer + length
     ///
     /// The element type is needed, since array indexing implicitly
@@ -448,7 +643,9 @@ pub enum TypeInfo {
     Str,
     /// A very simple optional, represented as an [AsmOption](crate::builtins::AsmOption)
     ///
-    /// This **never** uses the null pointer optimization
+    /// This **never** uses the null pointer optimization.
+    /// Rust's own `Option<T>` is only reflected if it does,
+    /// as a [TypeInfo::Niche] (see [NicheDef::option]).
     #[cfg(feature = "builtins")]
     Optional(&'static TypeInfo),
     /// An untyped pointer
@@ -458,7 +655,17 @@ pub enum TypeInfo {
     /// Untyped pointers simplify the type system significantly.
     /// They also avoid cycles when defining structures
     /// in case a structure contains a pointer to itself.
-    Pointer,
+    ///
+    /// Only the mutability of the pointer is recorded (`*mut T` vs `*const T`).
+    Pointer {
+        /// Whether this is a mutable pointer (`*mut T` or `NonNull<T>`)
+        mutable: bool,
+    },
+    /// A type which is never zero (or null),
+    /// but otherwise has the same representation as the inner type.
+    ///
+    /// This is recorded by [SimpleNonZeroRepr] types like `NonNull` and `NonZeroU32`.
+    NonZero(&'static TypeInfo),
     /// A structure
     Structure(&'static StructureDef),
     /// An untagged union
@@ -473,13 +680,36 @@ pub enum TypeInfo {
     ///
     /// See [TypeInfo::TaggedUnion] for enums *with* data.
     CStyleEnum(&'static CStyleEnumDef),
+    /// An enum with one empty variant and one variant wrapping a [SimpleNonZeroRepr] type,
+    /// like `Option<NonNull<T>>` (or a user-defined equivalent).
+    ///
+    /// The empty variant is stored in the niche of the wrapped type,
+    /// so the enum has exactly the same size and alignment.
+    /// Unlike a plain [TypeInfo::Pointer], this records that null means "none",
+    /// while the wrapped [TypeInfo::NonZero] records that the value is otherwise never null.
+    Niche(&'static NicheDef),
     /// A named, transparent, extern type
     Extern {
         /// The name of the type
         ///
         /// Since this is all we have, it's what used
         /// to disambiguate between them.
+        ///
+        /// Nothing stops a real structure from having the same name,
+        /// which consumers keyed by name would conflate with the extern type.
+        /// Such collisions are detected by [register_type](crate::registry::register_type)
+        /// and reported by an [audit](audit::audit).
+        name: &'static str,
+    },
+    /// A generic type parameter, left symbolic
+    ///
+    /// This only appears in schemas generated by `#[reflect(schema)]` (see [schema::SchemaStructureDef]),
+    /// never in a `StaticReflect::TYPE_INFO`. Like an extern type, it has no known layout.
+    TypeParameter {
+        /// The name of the parameter, as declared in the source code (like `T`)
         name: &'static str,
+        /// The position of the parameter, among the type parameters of the generic type
+        index: usize,
     },
     /// A 'magic' type, with a user-defined meaning
     ///
@@ -502,9 +732,387 @@ impl TypeInfo {
     pub const F64: Self = TypeInfo::Float {
         size: FloatSize::Double,
     };
+    /// A mutable pointer, like `*mut T`
+    pub const MUT_POINTER: Self = TypeInfo::Pointer { mutable: true };
+    /// An immutable pointer, like `*const T`
+    pub const CONST_POINTER: Self = TypeInfo::Pointer { mutable: false };
+    /// Remove the [TypeInfo::NonZero] wrapper (if any),
+    /// giving the type info of a value that may be zero.
+    ///
+    /// A [TypeInfo::Niche] is replaced by the nullable representation of the wrapped type,
+    /// so `Option<NonNull<T>>` becomes a plain pointer.
+    #[inline]
+    pub const fn nullable(self) -> TypeInfo {
+        match self {
+            TypeInfo::NonZero(inner) => *inner,
+            TypeInfo::Niche(def) => def.wrapped.nullable(),
+            other => other,
+        }
+    }
+    /// Walk the entire graph of this type at compile time, returning the number of nodes visited
+    ///
+    /// This reads every name, field offset and discriminant reachable from the type,
+    /// so evaluating it in a constant (see [assert_fully_const!](crate::macros::assert_fully_const))
+    /// guarantees the whole graph is available without any runtime initialization.
+    /// Types reachable along multiple paths are counted once for each path.
+    ///
+    /// ## Panics
+    /// Unions with [lazy fields](UnionFields::Lazy) can't be walked,
+    /// since their fields are only constructed at runtime.
+    /// Types nested more than [TypeInfo::MAX_CONST_DEPTH] levels deep are rejected,
+    /// which guards against (currently impossible) cycles.
+    pub const fn const_validate(&self) -> usize {
+        self.const_walk(0)
+    }
+    /// The deepest nesting of types accepted by [TypeInfo::const_validate]
+    pub const MAX_CONST_DEPTH: usize = 128;
+    const fn const_walk(&self, depth: usize) -> usize {
+        if depth > TypeInfo::MAX_CONST_DEPTH {
+            panic!("TypeInfo graph is nested too deeply (is it cyclic?)");
+        }
+        let nested = match *self {
+            TypeInfo::Unit
+            | TypeInfo::Bool
+            | TypeInfo::Integer(_)
+            | TypeInfo::Float { .. }
+            | TypeInfo::Vector { .. }
+            | TypeInfo::Pointer { .. } => 0,
+            #[cfg(feature = "never")]
+            TypeInfo::Never => 0,
+            #[cfg(feature = "builtins")]
+            TypeInfo::Str => 0,
+            #[cfg(feature = "builtins")]
+            TypeInfo::Slice { element_type } => element_type.const_walk(depth + 1),
+            #[cfg(feature = "builtins")]
+            TypeInfo::Optional(inner) => inner.const_walk(depth + 1),
+            TypeInfo::NonZero(inner) => inner.const_walk(depth + 1),
+            TypeInfo::Structure(def) => def.const_walk_fields(depth),
+            TypeInfo::UntaggedUnion(def) => {
+                let _ = def.name.len();
+                let fields = match def.fields.as_slice() {
+                    Some(fields) => fields,
+                    None => panic!("Lazy union fields can't be walked at compile time"),
+                };
+                let mut count = 0;
+                let mut index = 0;
+                while index < fields.len() {
+                    let field = &fields[index];
+                    let _ = field.name.len();
+                    count += field.value_type.type_ref().const_walk(depth + 1);
+                    index += 1;
+                }
+                count
+            }
+            TypeInfo::TaggedUnion(def) => {
+                let _ = def.name.len();
+                let mut count = 0;
+                let mut index = 0;
+                while index < def.variants.len() {
+                    let variant = &def.variants[index];
+                    let _ = variant.discriminant.bits();
+                    // Each variant counts as a structure
+                    count += 1 + variant.equivalent_structure.const_walk_fields(depth + 1);
+                    index += 1;
+                }
+                count
+            }
+            TypeInfo::CStyleEnum(def) => {
+                let _ = def.name.len();
+                let mut index = 0;
+                while index < def.variants.len() {
+                    let variant = &def.variants[index];
+                    let _ = (variant.name.len(), variant.discriminant.bits());
+                    index += 1;
+                }
+                0
+            }
+            TypeInfo::Niche(def) => {
+                let _ = (
+                    def.name.len(),
+                    def.none_variant.len(),
+                    def.some_variant.len(),
+                );
+                def.wrapped.const_walk(depth + 1)
+            }
+            TypeInfo::Extern { name } | TypeInfo::TypeParameter { name, .. } => {
+                let _ = name.len();
+                0
+            }
+            TypeInfo::Magic { id, extra } => {
+                let _ = id.len();
+                match extra {
+                    Some(extra) => extra.const_walk(depth + 1),
+                    None => 0,
+                }
+            }
+        };
+        1 + nested
+    }
+    /// Check if two types have the same layout, ignoring names.
+    ///
+    /// Structures and unions must have the same size and alignment,
+    /// with fields at the same offsets whose types have the same layout (recursively).
+    /// C-style enums must have the same discriminant type and values.
+    /// All other types must be exactly equal.
+    ///
+    /// In particular, a structure derived with `#[reflect(profile = "minimal")]`
+    /// has the same layout as the full version.
+    ///
+    /// This is [TypeInfo::equivalent] using [CompareOptions::LAYOUT](compare::CompareOptions::LAYOUT).
+    #[inline]
+    pub fn layout_eq(&self, other: &TypeInfo) -> bool {
+        self.equivalent(other, compare::CompareOptions::LAYOUT)
+    }
+    /// Check if zero is a valid value of this type,
+    /// so that it can be initialized by writing zero bytes.
+    ///
+    /// Structures are zero-initializable if all their fields are,
+    /// while unions only need a single field to be.
+    /// C-style enums need a variant whose discriminant is zero,
+    /// and niche enums need their empty variant to be zero.
+    ///
+    /// Non-zero types, the never type, tagged unions and
+    /// opaque (extern or magic) types are conservatively assumed not to be.
+    pub fn is_zero_initializable(&self) -> bool {
+        match *self {
+            TypeInfo::Unit
+            | TypeInfo::Bool
+            | TypeInfo::Integer(_)
+            | TypeInfo::Float { .. }
+            | TypeInfo::Vector { .. }
+            | TypeInfo::Pointer { .. } => true,
+            // Null slices and absent optionals are both valid
+            #[cfg(feature = "builtins")]
+            TypeInfo::Slice { .. } | TypeInfo::Str | TypeInfo::Optional(_) => true,
+            TypeInfo::Structure(def) => def
+                .fields
+                .iter()
+                .all(|field| field.value_type.type_ref().is_zero_initializable()),
+            TypeInfo::UntaggedUnion(def) => def
+                .fields
+                .iter()
+                .any(|field| field.value_type.type_ref().is_zero_initializable()),
+            TypeInfo::CStyleEnum(def) => def.variant_for_discriminant(0).is_some(),
+            TypeInfo::Niche(def) => def.none_value == 0,
+            #[cfg(feature = "never")]
+            TypeInfo::Never => false,
+            TypeInfo::NonZero(_)
+            | TypeInfo::TaggedUnion(_)
+            | TypeInfo::Extern { .. }
+            | TypeInfo::TypeParameter { .. }
+            | TypeInfo::Magic { .. } => false,
+        }
+    }
+}
+
+/// Initialize a value of the specified type by writing zeroes
+/// over its memory (exactly [TypeInfo::try_size] bytes).
+///
+/// Fails (without writing anything) if zero isn't a valid value
+/// of the type, as determined by [TypeInfo::is_zero_initializable],
+/// or if the size of the type is unknown (like an optional or an unregistered extern type).
+///
+/// ## Safety
+/// The pointer must be valid for writes of the type's size.
+/// It doesn't need to be aligned.
+pub unsafe fn zero_init(ptr: *mut u8, info: &TypeInfo) -> Result<(), ZeroInitError> {
+    if !info.is_zero_initializable() {
+        return Err(ZeroInitError {
+            type_name: info.to_string(),
+        });
+    }
+    let size = info.try_size().map_err(|_| ZeroInitError {
+        type_name: info.to_string(),
+    })?;
+    std::ptr::write_bytes(ptr, 0, size);
+    Ok(())
+}
+/// An error indicating that a type can't be zero-initialized
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ZeroInitError {
+    /// The name of the type
+    pub type_name: String,
+}
+impl Display for ZeroInitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Type can't be zero-initialized: {}", self.type_name)
+    }
+}
+impl std::error::Error for ZeroInitError {}
+/// An error indicating that the layout of a type is unknown,
+/// because it contains an opaque component (see [TypeInfo::try_layout])
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OpaqueTypeError {
+    /// The path from the root type to the opaque component
+    ///
+    /// Each component is the name of a field (or the index of a tuple field),
+    /// or the name of a variant of a tagged union.
+    /// This is empty if the root type itself is opaque.
+    pub path: Vec<String>,
+    /// The name of the opaque component
+    pub type_name: String,
 }
+impl Display for OpaqueTypeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Type has an unknown layout: {}", self.type_name)?;
+        if !self.path.is_empty() {
+            write!(f, " (at {})", self.path.join("."))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for OpaqueTypeError {}
+/// A contiguous buffer of values of the same type, like `[T; count]`
+///
+/// Consecutive elements are separated by the stride of the element type:
+/// its size, rounded up to its alignment (like the elements of an array).
+/// The stride is computed from [TypeInfo::try_layout],
+/// so the layouts of extern types registered at runtime are included.
+///
+/// All the arithmetic is checked, so a region whose size
+/// doesn't fit in a `usize` is an error instead of wrapping around.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TypedRegion {
+    /// The type of each element
+    pub element: &'static TypeInfo,
+    /// The number of elements
+    pub count: usize,
+}
+impl TypedRegion {
+    /// A region of `count` elements of the specified type
+    #[inline]
+    pub const fn new(element: &'static TypeInfo, count: usize) -> TypedRegion {
+        TypedRegion { element, count }
+    }
+    /// A region of `count` elements of a reflected type
+    #[inline]
+    pub const fn of<T: StaticReflect>(count: usize) -> TypedRegion {
+        TypedRegion::new(&T::TYPE_INFO, count)
+    }
+    /// The distance between the start of consecutive elements, in bytes
+    ///
+    /// Fails if the layout of the element type is unknown.
+    pub fn stride(&self) -> Result<usize, RegionError> {
+        match self.element.try_layout() {
+            Ok(layout) => Ok(layout.pad_to_align().size()),
+            Err(cause) => Err(RegionError::Unsupported(cause.into())),
+        }
+    }
+    /// The total size of the region, in bytes
+    ///
+    /// Fails if this overflows a `usize`, or if the layout of the element type is unknown.
+    pub fn byte_len(&self) -> Result<usize, RegionError> {
+        self.stride()?
+            .checked_mul(self.count)
+            .ok_or_else(|| self.overflow())
+    }
+    /// The layout of the whole region, aligned like its element type
+    ///
+    /// Fails if the size overflows (including the limit of `isize::MAX` imposed by [Layout]),
+    /// or if the layout of the element type is unknown.
+    pub fn layout(&self) -> Result<Layout, RegionError> {
+        let size = self.byte_len()?;
+        let align = self.element.try_alignment().unwrap();
+        Layout::from_size_align(size, align).map_err(|_| self.overflow())
+    }
+    /// The offset of the element with the specified index, in bytes
+    ///
+    /// Returns `None` if the index is out of bounds,
+    /// or if the layout of the element type is unknown.
+    pub fn element_offset(&self, index: usize) -> Option<usize> {
+        if index >= self.count {
+            return None;
+        }
+        self.stride().ok()?.checked_mul(index)
+    }
+    /// Split the region into the elements before the index, and the elements starting at it
+    ///
+    /// The second region starts at [TypedRegion::element_offset] of the index
+    /// (or at the end of the region, if the index is equal to the count).
+    /// Returns `None` if the index is greater than the count.
+    pub fn split_at(&self, index: usize) -> Option<(TypedRegion, TypedRegion)> {
+        if index > self.count {
+            return None;
+        }
+        Some((
+            TypedRegion::new(self.element, index),
+            TypedRegion::new(self.element, self.count - index),
+        ))
+    }
+    /// Find the element containing the byte at the specified offset,
+    /// returning its index and the offset within it
+    ///
+    /// This maps an address (like the location of a fault)
+    /// back to the element it belongs to.
+    /// Bytes in the padding after an element belong to that element.
+    /// Returns `None` if the offset is outside the region,
+    /// or if the layout of the element type is unknown.
+    pub fn contains_offset(&self, offset: usize) -> Option<(usize, usize)> {
+        let stride = self.stride().ok()?;
+        if stride == 0 {
+            // Zero-sized elements don't contain any bytes
+            return None;
+        }
+        let index = offset / stride;
+        if index >= self.count {
+            return None;
+        }
+        Some((index, offset % stride))
+    }
+    fn overflow(&self) -> RegionError {
+        RegionError::Overflow {
+            type_name: self.element.to_string(),
+            count: self.count,
+        }
+    }
+}
+impl Display for TypedRegion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "[{}; {}]", self.element, self.count)
+    }
+}
+/// An error computing the layout of a [TypedRegion]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum RegionError {
+    /// The size of the region overflows
+    Overflow {
+        /// The name of the element type
+        type_name: String,
+        /// The number of elements
+        count: usize,
+    },
+    /// The layout of the element type is unknown
+    Unsupported(ops::UnsupportedType),
+}
+impl Display for RegionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            RegionError::Overflow {
+                ref type_name,
+                count,
+            } => write!(
+                f,
+                "Region of {} elements of {} is too large",
+                count, type_name
+            ),
+            RegionError::Unsupported(ref cause) => Display::fmt(cause, f),
+        }
+    }
+}
+impl std::error::Error for RegionError {}
 impl TypeInfo {
+    /// The dummy size of opaque types (extern types, magic types and type parameters)
+    ///
+    /// This is only a placeholder (see [TypeInfo::size]),
+    /// so use [TypeInfo::try_size] where the real size matters.
+    pub const OPAQUE_SIZE: usize = 0xFFFF_FFFF;
     /// The size of the type, in bytes
+    ///
+    /// For structures with an unsized tail ([StructureDef::has_unsized_tail]),
+    /// this is only the size of the header before the tail.
+    /// The full size is dynamic, depending on the length of the tail.
+    ///
+    /// Opaque types have the dummy size [TypeInfo::OPAQUE_SIZE].
     pub const fn size(&self) -> usize {
         use self::TypeInfo::*;
         use std::mem::size_of;
@@ -515,20 +1123,170 @@ impl TypeInfo {
             Bool => size_of::<bool>(),
             Integer(IntType { size, .. }) => size.bytes(),
             Float { size } => size.bytes(),
+            Vector { element, lanes, .. } => element.bytes() * lanes,
             #[cfg(feature = "builtins")]
             Slice { .. } => std::mem::size_of::<AsmSlice<()>>(),
             #[cfg(feature = "builtins")]
             Optional(_inner) => unimplemented!(),
-            Pointer => size_of::<*const ()>(),
+            Pointer { .. } => size_of::<*const ()>(),
+            NonZero(inner) => inner.size(),
             #[cfg(feature = "builtins")]
             Str => size_of::<AsmStr>(),
             Structure(def) => def.size,
             UntaggedUnion(def) => def.size,
             TaggedUnion(def) => def.size,
             CStyleEnum(def) => def.discriminant.size.bytes(),
+            Niche(def) => def.wrapped.size(),
             // Provide a dummy value
-            TypeInfo::Magic { .. } | TypeInfo::Extern { .. } => 0xFFFF_FFFF,
+            TypeInfo::Magic { .. } | TypeInfo::Extern { .. } | TypeInfo::TypeParameter { .. } => {
+                TypeInfo::OPAQUE_SIZE
+            }
+        }
+    }
+    /// The size of the type, including the layouts of extern types
+    /// registered at runtime (see [crate::registry])
+    ///
+    /// Returns `None` for extern types without a registered layout, magic types and type parameters.
+    /// Otherwise, this is the same as [TypeInfo::size].
+    pub fn resolved_size(&self) -> Option<usize> {
+        self.resolved_layout().map(|layout| layout.size())
+    }
+    /// The alignment of the type, including the layouts of extern types
+    /// registered at runtime (see [crate::registry])
+    ///
+    /// Returns `None` for extern types without a registered layout, magic types and type parameters.
+    pub fn resolved_alignment(&self) -> Option<usize> {
+        self.resolved_layout().map(|layout| layout.align())
+    }
+    /// The layout of the type, including the layouts of extern types
+    /// registered at runtime (see [crate::registry])
+    ///
+    /// Returns `None` for extern types without a registered layout, magic types and type parameters.
+    pub fn resolved_layout(&self) -> Option<Layout> {
+        match *self {
+            TypeInfo::Extern { name } => crate::registry::extern_layout(name),
+            TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } => None,
+            ref other if other.optional_inner_type().is_some() => None,
+            _ => Some(Layout::from_size_align(self.size(), self.alignment()).unwrap()),
+        }
+    }
+    /// The size of the type, failing if its layout is unknown
+    ///
+    /// See [TypeInfo::try_layout] for details.
+    #[inline]
+    pub fn try_size(&self) -> Result<usize, OpaqueTypeError> {
+        self.try_layout().map(|layout| layout.size())
+    }
+    /// The alignment of the type, failing if its layout is unknown
+    ///
+    /// See [TypeInfo::try_layout] for details.
+    #[inline]
+    pub fn try_alignment(&self) -> Result<usize, OpaqueTypeError> {
+        self.try_layout().map(|layout| layout.align())
+    }
+    /// The layout of the type, failing if it (or any of its components) is opaque
+    ///
+    /// Unlike [TypeInfo::resolved_layout], the fields of structures, unions and tagged unions
+    /// are checked recursively, so the error gives the path to the component
+    /// whose layout is unknown (an extern type without a registered layout,
+    /// a magic type, a type parameter or an optional).
+    /// Pointers are never followed, so they're fine regardless of what they point to.
+    ///
+    /// This is what runtime utilities (like [TypedRegion] and [ops::CopyPlan]) use,
+    /// while the const [TypeInfo::size] and [TypeInfo::alignment] remain for compile time.
+    ///
+    /// ## Example
+    /// ````
+    /// # use static_reflect::types::{OpaqueTypeError, TypeInfo};
+    /// let opaque = TypeInfo::Extern { name: "Handle" };
+    /// assert_eq!(
+    ///     opaque.try_size(),
+    ///     Err(OpaqueTypeError {
+    ///         path: vec![],
+    ///         type_name: "extern Handle".into(),
+    ///     })
+    /// );
+    /// assert_eq!(TypeInfo::F64.try_alignment(), Ok(8));
+    /// ````
+    pub fn try_layout(&self) -> Result<Layout, OpaqueTypeError> {
+        let mut path = Vec::new();
+        self.check_components(Some(&mut path))
+            .map_err(|opaque| OpaqueTypeError {
+                path,
+                type_name: opaque.to_string(),
+            })
+    }
+    /// Like [TypeInfo::try_layout], but giving the first opaque component
+    /// instead of the path to it, so this never allocates
+    #[cfg(feature = "capi")]
+    #[inline]
+    pub(crate) fn try_layout_in_place(&self) -> Result<Layout, TypeInfo> {
+        self.check_components(None)
+    }
+    /// Resolve the layout of each component of this type,
+    /// leaving the path to the first opaque one (which is returned as the error)
+    ///
+    /// The path is only tracked if one is given.
+    fn check_components(&self, mut path: Option<&mut Vec<String>>) -> Result<Layout, TypeInfo> {
+        match *self {
+            TypeInfo::Structure(def) => {
+                for field in def.fields {
+                    Self::check_component(field.value_type.type_ref(), &mut path, || match field
+                        .name
+                    {
+                        Some(name) => name.into(),
+                        None => field.index.to_string(),
+                    })?;
+                }
+            }
+            TypeInfo::UntaggedUnion(def) => {
+                for field in def.fields {
+                    Self::check_component(field.value_type.type_ref(), &mut path, || {
+                        field.name.into()
+                    })?;
+                }
+            }
+            TypeInfo::TaggedUnion(def) => {
+                for variant in def.variants {
+                    Self::check_component(
+                        &TypeInfo::Structure(&variant.equivalent_structure),
+                        &mut path,
+                        || variant.name().into(),
+                    )?;
+                }
+            }
+            TypeInfo::NonZero(inner) => return inner.check_components(path),
+            TypeInfo::Niche(def) => {
+                def.wrapped.check_components(path)?;
+            }
+            #[cfg(feature = "builtins")]
+            TypeInfo::Slice { .. } => return Ok(Layout::new::<AsmSlice<()>>()),
+            _ => {}
         }
+        self.resolved_layout().ok_or(*self)
+    }
+    /// Check the layout of a component of a type, named by the specified function
+    fn check_component(
+        component: &TypeInfo,
+        path: &mut Option<&mut Vec<String>>,
+        name: impl FnOnce() -> String,
+    ) -> Result<(), TypeInfo> {
+        if let Some(path) = path {
+            path.push(name());
+        }
+        component.check_components(path.as_deref_mut())?;
+        if let Some(path) = path {
+            path.pop();
+        }
+        Ok(())
+    }
+    /// The larger size and alignment of two types,
+    /// which is a layout that can hold a value of either of them
+    ///
+    /// Panics if either type is opaque (see [ops::const_max_layout]).
+    #[inline]
+    pub const fn max(a: &TypeInfo, b: &TypeInfo) -> Layout {
+        ops::const_max_layout(&[a, b])
     }
     /// The alignment of the type, matching `std::mem::align_of`
     pub const fn alignment(&self) -> usize {
@@ -537,7 +1295,7 @@ impl TypeInfo {
             TypeInfo::Unit => align_of::<()>(),
             #[cfg(feature = "never")]
             TypeInfo::Never => align_of::<!>(),
-            TypeInfo::Magic { .. } | TypeInfo::Extern { .. } => 0,
+            TypeInfo::Magic { .. } | TypeInfo::Extern { .. } | TypeInfo::TypeParameter { .. } => 0,
             TypeInfo::Bool => align_of::<bool>(),
             TypeInfo::Integer(tp) => tp.align(),
             TypeInfo::Float {
@@ -546,35 +1304,194 @@ impl TypeInfo {
             TypeInfo::Float {
                 size: FloatSize::Double,
             } => align_of::<f64>(),
+            TypeInfo::Vector { alignment, .. } => alignment,
             #[cfg(feature = "builtins")]
             TypeInfo::Slice { .. } | TypeInfo::Optional(_) => unimplemented!(),
-            TypeInfo::Pointer => align_of::<*const ()>(),
+            TypeInfo::Pointer { .. } => align_of::<*const ()>(),
+            TypeInfo::NonZero(inner) => inner.alignment(),
             #[cfg(feature = "builtins")]
             TypeInfo::Str => align_of::<AsmStr>(),
             TypeInfo::Structure(def) => def.alignment,
             TypeInfo::UntaggedUnion(def) => def.alignment,
             TypeInfo::CStyleEnum(def) => def.discriminant.align(),
+            TypeInfo::Niche(def) => def.wrapped.alignment(),
             TypeInfo::TaggedUnion(def) => def.alignment,
         }
     }
+    /// Whether values of this type need to be dropped,
+    /// matching `core::mem::needs_drop`
+    ///
+    /// If this is false, a buffer of values can be freed without running any destructors.
+    ///
+    /// Primitives, pointers and enums without data never need to be dropped.
+    /// Neither do slices and strings, which are only borrowed views of their data.
+    /// A [TypeInfo::Optional] is an [AsmOption](crate::builtins::AsmOption),
+    /// which doesn't drop its value either, regardless of the payload.
+    /// Extern and magic types are assumed not to need dropping,
+    /// since nothing is known about them.
+    pub const fn needs_drop(&self) -> bool {
+        match *self {
+            TypeInfo::Structure(def) => def.needs_drop,
+            TypeInfo::UntaggedUnion(def) => def.needs_drop,
+            TypeInfo::TaggedUnion(def) => def.needs_drop,
+            TypeInfo::NonZero(inner) => inner.needs_drop(),
+            TypeInfo::Niche(def) => def.wrapped.needs_drop(),
+            _ => false,
+        }
+    }
+    /// Find the field of a structure with the specified name
+    ///
+    /// Returns `None` if this isn't a [TypeInfo::Structure].
+    /// See [StructureDef::field_def] for details.
+    #[inline]
+    pub const fn field_def(&self, name: &str) -> Option<FieldDef> {
+        match *self {
+            TypeInfo::Structure(def) => def.field_def(name),
+            _ => None,
+        }
+    }
+}
+/// Accessors for the feature-dependent variants
+///
+/// These are available regardless of the enabled features,
+/// so code outside this module can handle the optional variants
+/// without repeating the `#[cfg(...)]` attributes of the enum declaration.
+impl TypeInfo {
+    /// Check if this is the never type `!`
+    ///
+    /// Always false without the `never` feature.
+    #[inline]
+    pub const fn is_never(&self) -> bool {
+        match *self {
+            #[cfg(feature = "never")]
+            TypeInfo::Never => true,
+            _ => false,
+        }
+    }
+    /// Check if this is one of the types from [crate::builtins]
+    /// (a slice, string or optional)
+    ///
+    /// Always false without the `builtins` feature.
+    #[inline]
+    pub const fn is_builtin(&self) -> bool {
+        self.is_str() || self.slice_element_type().is_some() || self.optional_inner_type().is_some()
+    }
+    /// Check if this is a builtin string (`AsmStr`)
+    #[inline]
+    pub const fn is_str(&self) -> bool {
+        match *self {
+            #[cfg(feature = "builtins")]
+            TypeInfo::Str => true,
+            _ => false,
+        }
+    }
+    /// The element type of a builtin slice (`AsmSlice`),
+    /// or `None` for any other type
+    #[inline]
+    pub const fn slice_element_type(&self) -> Option<&'static TypeInfo> {
+        match *self {
+            #[cfg(feature = "builtins")]
+            TypeInfo::Slice { element_type } => Some(element_type),
+            _ => None,
+        }
+    }
+    /// The inner type of a builtin optional (`AsmOption`),
+    /// or `None` for any other type
+    #[inline]
+    pub const fn optional_inner_type(&self) -> Option<&'static TypeInfo> {
+        match *self {
+            #[cfg(feature = "builtins")]
+            TypeInfo::Optional(inner) => Some(inner),
+            _ => None,
+        }
+    }
+    /// The name of a nominal type (a structure, union, enum or extern type),
+    /// or `None` for any other type
+    ///
+    /// Different types may have the same name (see [TypeInfo::Extern]).
+    /// The std `Option` (see [NicheDef::option]) isn't considered nominal,
+    /// since it's generic.
+    pub fn nominal_name(&self) -> Option<&'static str> {
+        match *self {
+            TypeInfo::Structure(def) => Some(def.name),
+            TypeInfo::UntaggedUnion(def) => Some(def.name),
+            TypeInfo::TaggedUnion(def) => Some(def.name),
+            TypeInfo::CStyleEnum(def) => Some(def.name),
+            TypeInfo::Niche(def) if !def.is_option() => Some(def.name),
+            TypeInfo::Extern { name } => Some(name),
+            _ => None,
+        }
+    }
+    /// Write the name of the type (the same as its `Display`) into any [fmt::Write]
+    ///
+    /// This never allocates, so it can render names into a fixed buffer
+    /// where `format!` isn't available (like the log messages of an embedded target).
+    /// The same applies to every `Display` implementation in this crate.
+    /// If the writer runs out of space, this returns its error instead of panicking.
+    ///
+    /// ## Example
+    /// ````
+    /// # use static_reflect::{StaticReflect, TypeInfo};
+    /// use std::fmt::{self, Write};
+    ///
+    /// /// Writes into a fixed buffer, failing once it's full
+    /// struct BoundedWriter<'a> {
+    ///     buffer: &'a mut [u8],
+    ///     len: usize,
+    /// }
+    /// impl Write for BoundedWriter<'_> {
+    ///     fn write_str(&mut self, s: &str) -> fmt::Result {
+    ///         let remaining = &mut self.buffer[self.len..];
+    ///         if s.len() > remaining.len() {
+    ///             return Err(fmt::Error);
+    ///         }
+    ///         remaining[..s.len()].copy_from_slice(s.as_bytes());
+    ///         self.len += s.len();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BoundedWriter { buffer: &mut buffer, len: 0 };
+    /// <*mut u8>::TYPE_INFO.write_name(&mut writer).unwrap();
+    /// assert_eq!(&writer.buffer[..writer.len], b"*mut void");
+    /// // The name doesn't fit in the remaining 7 bytes
+    /// assert!(<*const u8>::TYPE_INFO.write_name(&mut writer).is_err());
+    /// ````
+    #[inline]
+    pub fn write_name<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{}", self)
+    }
 }
 impl Display for TypeInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match *self {
             TypeInfo::Unit => f.write_str("()"),
+            #[cfg(feature = "never")]
             TypeInfo::Never => f.write_str("!"),
             TypeInfo::Bool => f.write_str("bool"),
             TypeInfo::Integer(tp) => write!(f, "{}", tp),
             TypeInfo::Float { size } => write!(f, "f{}", size.bytes() * 8),
+            TypeInfo::Vector { element, lanes, .. } => {
+                write!(f, "{}x{}", element.rust_name(), lanes)
+            }
+            #[cfg(feature = "builtins")]
             TypeInfo::Slice { element_type } => write!(f, "[{}]", element_type),
+            #[cfg(feature = "builtins")]
             TypeInfo::Str => f.write_str("str"),
+            #[cfg(feature = "builtins")]
             TypeInfo::Optional(inner_type) => write!(f, "Option<{}>", inner_type),
-            TypeInfo::Pointer => f.write_str("*mut void"),
+            TypeInfo::Pointer { mutable: true } => f.write_str("*mut void"),
+            TypeInfo::Pointer { mutable: false } => f.write_str("*const void"),
+            TypeInfo::NonZero(inner) => write!(f, "NonZero<{}>", inner),
             TypeInfo::Structure(def) => f.write_str(def.name),
             TypeInfo::UntaggedUnion(def) => f.write_str(def.name),
             TypeInfo::CStyleEnum(def) => f.write_str(def.name),
+            TypeInfo::Niche(def) if def.is_option() => write!(f, "Option<{}>", def.wrapped),
+            TypeInfo::Niche(def) => f.write_str(def.name),
             TypeInfo::TaggedUnion(def) => f.write_str(def.name),
             TypeInfo::Extern { name } => write!(f, "extern {}", name),
+            TypeInfo::TypeParameter { name, .. } => f.write_str(name),
             TypeInfo::Magic { id, extra: None } => write!(f, "magic::{}", id),
             TypeInfo::Magic {
                 id,
@@ -583,6 +1500,42 @@ impl Display for TypeInfo {
         }
     }
 }
+/// A location in the source code, given by `file!()` and `line!()`
+///
+/// This is recorded by the derives where the reflected item was defined,
+/// so diagnostics can point at it.
+/// It's only meant for diagnostics, so it never affects
+/// whether two types (or functions) are considered equal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SourceLocation {
+    /// The path of the file, relative to the root of the workspace (like `file!()`)
+    pub file: &'static str,
+    /// The line of the item's name, starting at one
+    pub line: u32,
+}
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// The specified units, or an empty slice if none of them are known
+///
+/// The derives record units with this,
+/// so that they're only listed when some field (or argument) has one.
+#[doc(hidden)]
+pub const fn known_units(
+    units: &'static [Option<&'static str>],
+) -> &'static [Option<&'static str>] {
+    let mut index = 0;
+    while index < units.len() {
+        if units[index].is_some() {
+            return units;
+        }
+        index += 1;
+    }
+    &[]
+}
 /// Static information on the definition of a structure
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct StructureDef {
@@ -591,9 +1544,128 @@ pub struct StructureDef {
     /// All of the fields defined in the structure
     pub fields: &'static [FieldDef],
     /// The total size of the structure (including padding)
+    ///
+    /// If the structure has an unsized tail, this is the size of the header
+    /// (the offset where the tail starts).
     pub size: usize,
     /// The required alignment of the structure
     pub alignment: usize,
+    /// Whether the structure is known to implement `bytemuck::Pod`
+    ///
+    /// If this is true, it is safe to cast the structure to and from raw bytes.
+    pub is_pod: bool,
+    /// Whether the last field is a dynamically sized tail (`#[reflect(unsized_tail)]`)
+    ///
+    /// The tail is an array whose real length is only known at runtime,
+    /// so the last field is reflected as its element type
+    /// and the size only covers the header before it.
+    pub has_unsized_tail: bool,
+    /// Whether the structure needs to be dropped (`core::mem::needs_drop`)
+    ///
+    /// Since reflected types are meant to be C-compatible, this is usually false.
+    pub needs_drop: bool,
+}
+impl StructureDef {
+    /// Walk the fields of the structure, as part of [TypeInfo::const_validate]
+    const fn const_walk_fields(&self, depth: usize) -> usize {
+        let _ = self.name.len();
+        let mut count = 0;
+        let mut index = 0;
+        while index < self.fields.len() {
+            let field = &self.fields[index];
+            if let Some(name) = field.name {
+                let _ = name.len();
+            }
+            let _ = field.offset;
+            if let Some(bitset) = field.meta.bitset {
+                let mut bit = 0;
+                while bit < bitset.names.len() {
+                    let _ = bitset.names[bit].len();
+                    bit += 1;
+                }
+            }
+            count += field.value_type.type_ref().const_walk(depth + 1);
+            index += 1;
+        }
+        count
+    }
+    /// Find the field with the specified (reflected) name
+    ///
+    /// This is a `const fn`, so generated code can resolve fields by name
+    /// without knowing the shape of [FieldReflect::NamedFieldInfo](crate::FieldReflect::NamedFieldInfo).
+    /// Renamed fields are found by their new name.
+    /// Tuple structs (and structures derived with `profile = "minimal"`) have no field names,
+    /// so this always returns `None` for them.
+    pub const fn field_def(&self, name: &str) -> Option<FieldDef> {
+        let mut index = 0;
+        while index < self.fields.len() {
+            let field = self.fields[index];
+            if let Some(field_name) = field.name {
+                if str_eq(field_name, name) {
+                    return Some(field);
+                }
+            }
+            index += 1;
+        }
+        None
+    }
+}
+/// How to compute the full size of a structure with an unsized tail,
+/// from the length stored in its header
+///
+/// This is given by [FieldReflect::DYNAMIC_SIZE](crate::FieldReflect::DYNAMIC_SIZE),
+/// for structures derived with `#[reflect(unsized_tail(len = "field"))]`.
+/// Such a structure is addressed through thin pointers, and
+/// its size can be computed with [ops::dynamic_size_of].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DynamicSize {
+    /// The index of the integer field holding the number of elements in the tail
+    pub len_field_index: usize,
+    /// The type of the elements in the tail
+    pub element: &'static TypeInfo,
+}
+impl DynamicSize {
+    /// Describe the unsized tail of the specified structure,
+    /// whose length is given by the field with the specified index
+    ///
+    /// The element type is the (reflected) type of the last field.
+    ///
+    /// Panics if the type isn't a structure with an unsized tail,
+    /// or if the field isn't an integer in its header.
+    pub const fn for_tail(info: &'static TypeInfo, len_field_index: usize) -> DynamicSize {
+        let def = match *info {
+            TypeInfo::Structure(def) if def.has_unsized_tail => def,
+            _ => panic!("Expected a structure with an unsized tail"),
+        };
+        if len_field_index + 1 >= def.fields.len() {
+            panic!("The length of an unsized tail must be stored in the header");
+        }
+        if !matches!(
+            *def.fields[len_field_index].value_type.type_ref(),
+            TypeInfo::Integer(_)
+        ) {
+            panic!("The length of an unsized tail must be an integer");
+        }
+        DynamicSize {
+            len_field_index,
+            element: def.fields[def.fields.len() - 1].value_type.type_ref(),
+        }
+    }
+}
+/// Compare two strings in a const context
+const fn str_eq(first: &str, second: &str) -> bool {
+    let (first, second) = (first.as_bytes(), second.as_bytes());
+    if first.len() != second.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < first.len() {
+        if first[index] != second[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
 }
 impl<T: StaticReflect> Copy for FieldDef<T> {}
 impl<T: StaticReflect> Clone for FieldDef<T> {
@@ -615,6 +1687,21 @@ pub struct FieldDef<T: StaticReflect = ()> {
     ///
     /// Should correspond to the order of declaration
     pub index: usize,
+    /// The byte order of the field, or `None` if it uses the native order
+    ///
+    /// This is only ever specified for integer and float fields,
+    /// and has no effect on the layout.
+    pub endianness: Option<Endian>,
+    /// Whether the field's representation is assumed (`#[reflect(assume_repr)]`),
+    /// so that `value_type` isn't the field's real type
+    pub assumed: bool,
+    /// Whether the field is an opaque array (`#[reflect(opaque_array)]`),
+    /// so that `value_type` is only the type of its elements
+    pub opaque_array: bool,
+    /// Additional metadata, which doesn't affect the layout of the field
+    ///
+    /// Fields without any use [FieldMeta::NONE].
+    pub meta: &'static FieldMeta,
 }
 impl<T: StaticReflect> FieldDef<T> {
     /// Erase the static type information from this field definition
@@ -625,6 +1712,10 @@ impl<T: StaticReflect> FieldDef<T> {
             value_type: self.value_type.erase(),
             offset: self.offset,
             index: self.index,
+            endianness: self.endianness,
+            assumed: self.assumed,
+            opaque_array: self.opaque_array,
+            meta: self.meta,
         }
     }
     /// The offset of the field, in bytes
@@ -633,10 +1724,54 @@ impl<T: StaticReflect> FieldDef<T> {
         self.offset
     }
 }
+/// Additional metadata about a field, which doesn't affect its layout
+///
+/// New kinds of metadata are added here instead of to the [FieldDef] itself,
+/// so adding one isn't a breaking change (the [FieldDef] is part of the [stable](crate::stable) API).
+/// For the same reason, this can't be built with a struct literal:
+/// start from [FieldMeta::NONE], and add each kind with its `with_*` method.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct FieldMeta {
+    /// The ownership discipline of a pointer field (`#[reflect(ptr = "...")]`),
+    /// or `None` if it isn't documented
+    ///
+    /// This is only ever specified for pointer fields.
+    /// Undocumented pointers are treated as [PointerDiscipline::Raw].
+    pub pointer_discipline: Option<PointerDiscipline>,
+    /// The names of the bits of an integer field used as a set of flags
+    /// (`#[reflect(bitset(names = "..."))]`), or `None` if it isn't one
+    ///
+    /// See the [bitset] module for details.
+    pub bitset: Option<bitset::BitsetDef>,
+}
+impl FieldMeta {
+    /// The metadata of a field without any
+    pub const NONE: FieldMeta = FieldMeta {
+        pointer_discipline: None,
+        bitset: None,
+    };
+    /// Document the ownership discipline of a pointer field
+    #[inline]
+    pub const fn with_pointer_discipline(self, discipline: PointerDiscipline) -> FieldMeta {
+        FieldMeta {
+            pointer_discipline: Some(discipline),
+            ..self
+        }
+    }
+    /// Name the bits of an integer field used as a set of flags
+    #[inline]
+    pub const fn with_bitset(self, bitset: bitset::BitsetDef) -> FieldMeta {
+        FieldMeta {
+            bitset: Some(bitset),
+            ..self
+        }
+    }
+}
 /// The definition of C-style enum
 ///
 /// The variants of a C-style enum may not have any data.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct CStyleEnumDef {
     /// The name of the enumeration
     pub name: &'static str,
@@ -646,22 +1781,243 @@ pub struct CStyleEnumDef {
     pub discriminant: IntType,
     /// The valid variants of this enum
     pub variants: &'static [CStyleEnumVariant],
+    /// The minimum and maximum (sign-extended) discriminant values,
+    /// or `None` if there are no variants
+    ///
+    /// This is precomputed from the variants by [CStyleEnumDef::compute_flags].
+    pub valid_range: Option<(i128, i128)>,
+    /// Whether some value within [CStyleEnumDef::valid_range] doesn't belong to any variant
+    ///
+    /// If this is `false`, an integer is a valid discriminant
+    /// exactly when it falls within the valid range.
+    /// This is precomputed from the variants by [CStyleEnumDef::compute_flags].
+    pub has_gaps: bool,
 }
 impl CStyleEnumDef {
+    /// Fill in [CStyleEnumDef::valid_range] and [CStyleEnumDef::has_gaps]
+    /// from the variants, ignoring their current values
+    ///
+    /// The derive always does this, and hand-written definitions should as well
+    /// (otherwise [CStyleEnumDef::validate] will reject them):
+    /// ````
+    /// # use static_reflect::types::{CStyleEnumDef, CStyleEnumVariant, DiscriminantValue, IntType};
+    /// const DEF: CStyleEnumDef = CStyleEnumDef {
+    ///     name: "Sparse",
+    ///     discriminant: IntType::I8,
+    ///     variants: &[
+    ///         CStyleEnumVariant {
+    ///             index: 0,
+    ///             name: "Negative",
+    ///             discriminant: DiscriminantValue::ExplicitInteger { bits: 0xFF },
+    ///         },
+    ///         CStyleEnumVariant {
+    ///             index: 1,
+    ///             name: "Positive",
+    ///             discriminant: DiscriminantValue::ExplicitInteger { bits: 1 },
+    ///         },
+    ///     ],
+    ///     valid_range: None,
+    ///     has_gaps: false,
+    /// }
+    /// .compute_flags();
+    /// assert_eq!(DEF.valid_range, Some((-1, 1)));
+    /// assert!(DEF.has_gaps);
+    /// ````
+    pub const fn compute_flags(self) -> CStyleEnumDef {
+        let (valid_range, has_gaps) = self.flags_from_variants();
+        CStyleEnumDef {
+            valid_range,
+            has_gaps,
+            ..self
+        }
+    }
+    /// Compute the valid range and whether it has gaps,
+    /// counting duplicate discriminants once
+    pub(crate) const fn flags_from_variants(&self) -> (Option<(i128, i128)>, bool) {
+        if self.variants.is_empty() {
+            return (None, false);
+        }
+        // NOTE: Can't use for-loop since iterators aren't const
+        let (mut min, mut max) = (i128::MAX, i128::MIN);
+        let mut distinct = 0;
+        let mut position = 0;
+        while position < self.variants.len() {
+            let value = self
+                .discriminant
+                .extend_bits(self.variants[position].discriminant.bits());
+            if value < min {
+                min = value;
+            }
+            if value > max {
+                max = value;
+            }
+            let mut first = 0;
+            while first < position {
+                let other = self
+                    .discriminant
+                    .extend_bits(self.variants[first].discriminant.bits());
+                if other == value {
+                    break;
+                }
+                first += 1;
+            }
+            if first == position {
+                distinct += 1;
+            }
+            position += 1;
+        }
+        (Some((min, max)), max - min + 1 != distinct)
+    }
     /// Determines whether this enum has any explicit discriminant values,
     /// overriding the defaults.
     ///
     /// If this is `false`, then the value of each variant's discriminant
-    /// is implicitly equal to its index
+    /// is implicitly equal to its index.
+    ///
+    /// Variants that are implicitly offset by a previous explicit discriminant
+    /// do not count as explicit themselves.
     #[inline]
     pub fn has_explicit_discriminants(&self) -> bool {
         self.variants
             .iter()
             .any(|variant| variant.discriminant.is_explicit())
     }
+    /// Find the variant whose discriminant has the specified bits
+    ///
+    /// Only the bits within the width of the discriminant are considered,
+    /// so a sign-extended value (like `-1i64 as u64`) will also match.
+    pub fn variant_for_discriminant(&self, bits: u64) -> Option<&CStyleEnumVariant> {
+        let bits = self.discriminant.truncate_bits(bits);
+        self.variants
+            .iter()
+            .find(|variant| self.discriminant.truncate_bits(variant.discriminant.bits()) == bits)
+    }
+    /// The name of the variant with the specified discriminant value
+    ///
+    /// Returns `None` if no variant has the discriminant,
+    /// or if the value doesn't fit in the discriminant's type.
+    pub fn name_of(&self, discriminant: i128) -> Option<&'static str> {
+        let bits = discriminant as u64;
+        if self.discriminant.extend_bits(bits) != discriminant {
+            return None;
+        }
+        self.variant_for_discriminant(bits)
+            .map(|variant| variant.name)
+    }
+    /// The (sign-extended) value of the variant's discriminant
+    #[inline]
+    pub fn discriminant_value(&self, variant: &CStyleEnumVariant) -> i128 {
+        self.discriminant.extend_bits(variant.discriminant.bits())
+    }
+    /// The distinct discriminant values, in ascending order
+    fn sorted_values(&self) -> Vec<i128> {
+        let mut values = self
+            .variants
+            .iter()
+            .map(|variant| self.discriminant_value(variant))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        values.dedup();
+        values
+    }
+    /// The minimum and maximum discriminant,
+    /// if every value between them belongs to a variant
+    ///
+    /// If this returns `Some((min, max))`, a `match` can be lowered into a jump table
+    /// indexed by `discriminant - min`.
+    /// Returns `None` if there are no variants.
+    ///
+    /// Duplicate discriminants (which are invalid, see [CStyleEnumDef::validate])
+    /// are only counted once.
+    #[inline]
+    pub fn is_contiguous(&self) -> Option<(i128, i128)> {
+        if self.has_gaps {
+            None
+        } else {
+            self.valid_range
+        }
+    }
+    /// The variants, sorted by their discriminant values
+    ///
+    /// This is the table to binary search when the discriminants aren't contiguous.
+    /// Variants with the same discriminant (which is invalid)
+    /// are kept in declaration order.
+    pub fn sorted_by_discriminant(&self) -> Vec<&CStyleEnumVariant> {
+        let mut variants = self.variants.iter().collect::<Vec<_>>();
+        variants.sort_by_key(|variant| self.discriminant_value(variant));
+        variants
+    }
+    /// The fraction of the values between the minimum and maximum discriminant
+    /// which belong to a variant
+    ///
+    /// This is `1.0` for contiguous discriminants (see [CStyleEnumDef::is_contiguous]),
+    /// and approaches zero for sparse ones.
+    /// An enum without any variants has a density of zero.
+    pub fn density(&self) -> f64 {
+        let values = self.sorted_values();
+        match (values.first(), values.last()) {
+            (Some(&min), Some(&max)) => values.len() as f64 / ((max - min) as f64 + 1.0),
+            _ => 0.0,
+        }
+    }
+}
+/// The definition of an enum using the niche of a non-zero type
+///
+/// This has exactly two variants: an empty one, which is represented by [NicheDef::none_value],
+/// and one wrapping a single [SimpleNonZeroRepr] value (which is never `none_value`).
+/// Rust's own `Option<T>` is reflected this way for every [SimpleNonZeroRepr] type
+/// (see [NicheDef::option]).
+/// For example:
+/// ````
+/// # use std::ptr::NonNull;
+/// enum MaybeHandle {
+///     None,
+///     Some(NonNull<u8>),
+/// }
+/// ````
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NicheDef {
+    /// The name of the enumeration
+    pub name: &'static str,
+    /// The type wrapped by the non-empty variant
+    ///
+    /// This determines the enum's size and alignment.
+    pub wrapped: &'static TypeInfo,
+    /// The bits used to represent the empty variant
+    ///
+    /// This is always zero for [SimpleNonZeroRepr] types.
+    pub none_value: u64,
+    /// The name of the empty variant
+    pub none_variant: &'static str,
+    /// The name of the variant wrapping a value
+    pub some_variant: &'static str,
+}
+impl NicheDef {
+    /// The definition of Rust's `Option<T>`,
+    /// where `T` is the (non-zero) wrapped type
+    ///
+    /// This is how `Option<T>` is reflected for every [SimpleNonZeroRepr] type,
+    /// so `Option<NonNull<T>>` is distinct from both `NonNull<T>` and `*mut T`.
+    #[inline]
+    pub const fn option(wrapped: &'static TypeInfo) -> NicheDef {
+        NicheDef {
+            name: "Option",
+            wrapped,
+            none_value: 0,
+            none_variant: "None",
+            some_variant: "Some",
+        }
+    }
+    /// If this is the definition of Rust's `Option<T>` (see [NicheDef::option])
+    ///
+    /// An equivalent user-defined enum with the same names is indistinguishable.
+    #[inline]
+    pub fn is_option(&self) -> bool {
+        *self == NicheDef::option(self.wrapped)
+    }
 }
 /// A variant in a C-style enum (a Rust enum without any data)
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct CStyleEnumVariant {
     /// The index of this variant, specifying the declaration order
     pub index: usize,
@@ -671,6 +2027,10 @@ pub struct CStyleEnumVariant {
     pub discriminant: DiscriminantValue,
 }
 /// The value of the discriminant
+///
+/// Discriminants are ordered by their [raw bits](DiscriminantValue::bits),
+/// which sorts the variants of an enum by value (as long as none of them are negative).
+/// Equal bits are ordered by how the value was specified, to be consistent with equality.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum DiscriminantValue {
     /// The discriminant has the default value,
@@ -689,15 +2049,32 @@ pub enum DiscriminantValue {
         bits: u64,
     },
     /// The discriminant has been specified explicitly
-    ///
-    /// This
     ExplicitInteger {
         /// The raw bits of the explicit discriminant's value.
         ///
         /// It is possible that this is a negative value, depending on the [IntType] of the discriminant.
+        /// These are the two's complement bits, truncated to the discriminant's width.
         bits: u64,
     },
 }
+impl Ord for DiscriminantValue {
+    fn cmp(&self, other: &DiscriminantValue) -> Ordering {
+        let kind = |value: &DiscriminantValue| match *value {
+            DiscriminantValue::Default { .. } => 0,
+            DiscriminantValue::ImplicitlyOffset { .. } => 1,
+            DiscriminantValue::ExplicitInteger { .. } => 2,
+        };
+        self.bits()
+            .cmp(&other.bits())
+            .then_with(|| kind(self).cmp(&kind(other)))
+    }
+}
+impl PartialOrd for DiscriminantValue {
+    #[inline]
+    fn partial_cmp(&self, other: &DiscriminantValue) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 impl DiscriminantValue {
     /// Whether this discriminant has been specified explicitly
     #[inline]
@@ -707,9 +2084,13 @@ impl DiscriminantValue {
     /// The bits of the discriminant.
     ///
     /// Depending on the [IntType] of the discriminant,
-    /// it is possible this is a negative value (even though the static type is `u64`)
+    /// it is possible this is a negative value (even though the static type is `u64`).
+    ///
+    /// Negative values are stored as two's complement, truncated to the width of
+    /// the discriminant (so `-1` in an `i8` discriminant is `0xFF`).
+    /// Use [IntType::extend_bits] to recover the signed value.
     #[inline]
-    pub fn bits(&self) -> u64 {
+    pub const fn bits(&self) -> u64 {
         match *self {
             DiscriminantValue::Default { declaration_index } => declaration_index as u64,
             DiscriminantValue::ImplicitlyOffset { bits }
@@ -720,7 +2101,7 @@ impl DiscriminantValue {
 /// The definition of a FFI-compatible enum with data.
 ///
 /// These are just FFI-compatible Rust enums annotated with `#[repr(C)]`.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct TaggedUnionDef {
     /// The name of the enum type
     pub name: &'static str,
@@ -739,13 +2120,15 @@ pub struct TaggedUnionDef {
     ///
     /// This should be equal to max(discriminant.align, max(variant.align for variant in variants))
     pub alignment: usize,
+    /// Whether the enum needs to be dropped (`core::mem::needs_drop`)
+    pub needs_drop: bool,
 }
 
 /// A variant in a tagged union (Rust-style enum)
 ///
 /// This mostly functions as a wrapper around a [StructureDef],
 /// which stores information on the variant's fields (and whether or
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct TaggedUnionVariant {
     /// The index of this variant, determining the declaration order
     pub index: usize,
@@ -769,7 +2152,7 @@ pub struct UntaggedUnionDef {
     /// The name of the union
     pub name: &'static str,
     /// The fields of the union
-    pub fields: &'static [UnionFieldDef],
+    pub fields: UnionFields,
     /// The size of the union, in bytes
     ///
     /// Should equal the size of its largest member
@@ -779,8 +2162,140 @@ pub struct UntaggedUnionDef {
     /// I believe this should equal the maximum
     /// of the alignments required alignment by its members
     pub alignment: usize,
+    /// Whether the union needs to be dropped (`core::mem::needs_drop`)
+    ///
+    /// Unions never drop their fields, so this is only true
+    /// if the union itself implements `Drop`.
+    pub needs_drop: bool,
+}
+
+impl UntaggedUnionDef {
+    /// Find the field with the specified (reflected) name
+    ///
+    /// With lazily constructed fields, this looks up each field in turn.
+    pub fn field_def(&self, name: &str) -> Option<&'static UnionFieldDef> {
+        self.fields.iter().find(|field| field.name == name)
+    }
 }
 
+/// The fields of an untagged union
+///
+/// Normally, these are an array of every field.
+/// Unions with very many fields can be derived with `#[reflect(lazy_fields)]`,
+/// which generates a function looking up each field by its index instead.
+/// This avoids building the array (and the `NamedFieldInfo` of the union),
+/// so unreferenced fields can be dropped by the linker.
+///
+/// Either way, the fields can be accessed with [UnionFields::get] and [UnionFields::iter].
+/// Since a function pointer can't be called at compile time,
+/// only the eager array is available to `const fn`s (through [UnionFields::as_slice]).
+///
+/// Two sets of fields are equal if they use the same storage and have equal fields.
+/// Lazy fields are compared (and hashed) by looking up each one, never by the function's address.
+#[derive(Copy, Clone, Debug)]
+pub enum UnionFields {
+    /// An array of every field, in order of their indexes
+    Eager(&'static [UnionFieldDef]),
+    /// A function looking up the field with the specified index
+    Lazy {
+        /// The number of fields
+        count: usize,
+        /// Look up the field with the specified index,
+        /// returning `None` if it is out of bounds
+        lookup: fn(usize) -> Option<&'static UnionFieldDef>,
+    },
+}
+impl UnionFields {
+    /// The number of fields
+    #[inline]
+    pub const fn len(&self) -> usize {
+        match *self {
+            UnionFields::Eager(fields) => fields.len(),
+            UnionFields::Lazy { count, .. } => count,
+        }
+    }
+    /// Whether there are no fields
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The field with the specified index
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'static UnionFieldDef> {
+        match *self {
+            UnionFields::Eager(fields) => fields.get(index),
+            UnionFields::Lazy { count, lookup } if index < count => lookup(index),
+            UnionFields::Lazy { .. } => None,
+        }
+    }
+    /// The array of fields, unless they are constructed lazily
+    #[inline]
+    pub const fn as_slice(&self) -> Option<&'static [UnionFieldDef]> {
+        match *self {
+            UnionFields::Eager(fields) => Some(fields),
+            UnionFields::Lazy { .. } => None,
+        }
+    }
+    /// Iterate over the fields, in order of their indexes
+    #[inline]
+    pub fn iter(&self) -> UnionFieldIter {
+        UnionFieldIter {
+            fields: *self,
+            index: 0,
+        }
+    }
+}
+impl PartialEq for UnionFields {
+    fn eq(&self, other: &UnionFields) -> bool {
+        match (*self, *other) {
+            (UnionFields::Eager(first), UnionFields::Eager(second)) => first == second,
+            (UnionFields::Lazy { .. }, UnionFields::Lazy { .. }) => {
+                self.len() == other.len() && self.iter().eq(other.iter())
+            }
+            _ => false,
+        }
+    }
+}
+impl Eq for UnionFields {}
+impl Hash for UnionFields {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        self.len().hash(state);
+        for field in self.iter() {
+            field.hash(state);
+        }
+    }
+}
+impl IntoIterator for UnionFields {
+    type Item = &'static UnionFieldDef;
+    type IntoIter = UnionFieldIter;
+    #[inline]
+    fn into_iter(self) -> UnionFieldIter {
+        self.iter()
+    }
+}
+/// An iterator over the [UnionFields] of a union
+#[derive(Clone, Debug)]
+pub struct UnionFieldIter {
+    fields: UnionFields,
+    index: usize,
+}
+impl Iterator for UnionFieldIter {
+    type Item = &'static UnionFieldDef;
+    #[inline]
+    fn next(&mut self) -> Option<&'static UnionFieldDef> {
+        let field = self.fields.get(self.index)?;
+        self.index += 1;
+        Some(field)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.fields.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+impl ExactSizeIterator for UnionFieldIter {}
+
 /// A field of a union which is known at compile-time
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct UnionFieldDef<T: StaticReflect = ()> {
@@ -817,7 +2332,7 @@ impl<T: StaticReflect> UnionFieldDef<T> {
 ///
 /// Although rust doesn't truly have a concept of 'primitives',
 /// these are the most basic types needed to construct all the others.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum PrimitiveType {
     /// The zero-length type '()'
     ///
@@ -843,93 +2358,204 @@ pub enum PrimitiveType {
 }
 impl PrimitiveType {
     /// The type information for this primitive type
-    pub fn type_info(&self) -> &TypeInfo {
+    pub fn type_info(&self) -> &'static TypeInfo {
         use self::FloatSize::*;
         use self::IntSize::*;
         use self::PrimitiveType::*;
         match *self {
             Unit => &TypeInfo::Unit,
+            #[cfg(feature = "never")]
             Never => &TypeInfo::Never,
             Bool => &TypeInfo::Bool,
-            Pointer => &TypeInfo::Pointer,
+            Pointer => &TypeInfo::MUT_POINTER,
             Integer(IntType {
                 size: Byte,
                 signed: true,
-            }) => &TypeInfo::Integer(IntType::U8),
+            }) => &TypeInfo::Integer(IntType::I8),
             Integer(IntType {
                 size: Short,
                 signed: true,
-            }) => &TypeInfo::Integer(IntType::U16),
+            }) => &TypeInfo::Integer(IntType::I16),
             Integer(IntType {
                 size: Int,
                 signed: true,
-            }) => &TypeInfo::Integer(IntType::U32),
+            }) => &TypeInfo::Integer(IntType::I32),
             Integer(IntType {
                 size: Long,
                 signed: true,
-            }) => &TypeInfo::Integer(IntType::U64),
+            }) => &TypeInfo::Integer(IntType::I64),
             Integer(IntType {
                 size: Byte,
                 signed: false,
-            }) => &TypeInfo::Integer(IntType::I8),
+            }) => &TypeInfo::Integer(IntType::U8),
             Integer(IntType {
                 size: Short,
                 signed: false,
-            }) => &TypeInfo::Integer(IntType::I16),
+            }) => &TypeInfo::Integer(IntType::U16),
             Integer(IntType {
                 size: Int,
                 signed: false,
-            }) => &TypeInfo::Integer(IntType::I32),
+            }) => &TypeInfo::Integer(IntType::U32),
             Integer(IntType {
                 size: Long,
                 signed: false,
-            }) => &TypeInfo::Integer(IntType::I64),
+            }) => &TypeInfo::Integer(IntType::U64),
             Float { size: Single } => &TypeInfo::Float { size: Single },
             Float { size: Double } => &TypeInfo::Float { size: Double },
         }
     }
 
     /// The number of bytes this type tales up
-    pub fn bytes(&self) -> usize {
-        match self {
-            PrimitiveType::Unit | PrimitiveType::Never => 0,
+    pub const fn bytes(&self) -> usize {
+        match *self {
+            PrimitiveType::Unit => 0,
+            #[cfg(feature = "never")]
+            PrimitiveType::Never => 0,
             PrimitiveType::Integer(tp) => tp.size.bytes(),
             PrimitiveType::Float { size } => size.bytes(),
             PrimitiveType::Pointer => {
-                assert_eq!(std::mem::size_of::<usize>(), std::mem::size_of::<*mut ()>());
+                assert!(std::mem::size_of::<usize>() == std::mem::size_of::<*mut ()>());
                 std::mem::size_of::<*mut ()>()
             }
             PrimitiveType::Bool => {
-                assert_eq!(std::mem::size_of::<bool>(), 1);
+                assert!(std::mem::size_of::<bool>() == 1);
                 1
             }
         }
     }
     /// The size of this type, in bytes
     #[inline]
-    pub fn size(self) -> usize {
+    pub const fn size(self) -> usize {
         self.bytes()
     }
+    /// The alignment of this type, matching `std::mem::align_of`
+    #[inline]
+    pub fn alignment(self) -> usize {
+        self.type_info().alignment()
+    }
+}
+/// A table of all the primitive types
+impl PrimitiveType {
+    /// Every primitive type, taken from the [StaticReflect] implementations of the core types
+    ///
+    /// Pointer-sized integers are omitted, since they're the same as one of the fixed-size ones.
+    pub const ALL: &'static [PrimitiveType] = &[
+        PrimitiveType::of::<()>(),
+        #[cfg(feature = "never")]
+        PrimitiveType::of::<!>(),
+        PrimitiveType::of::<bool>(),
+        PrimitiveType::of::<*mut ()>(),
+        PrimitiveType::of::<u8>(),
+        PrimitiveType::of::<u16>(),
+        PrimitiveType::of::<u32>(),
+        PrimitiveType::of::<u64>(),
+        PrimitiveType::of::<i8>(),
+        PrimitiveType::of::<i16>(),
+        PrimitiveType::of::<i32>(),
+        PrimitiveType::of::<i64>(),
+        PrimitiveType::of::<f32>(),
+        PrimitiveType::of::<f64>(),
+    ];
+    /// The primitive type corresponding to the specified type
+    ///
+    /// Panics (at compile time, if used in a constant) if the type isn't primitive.
+    pub(crate) const fn of<T: StaticReflect + ?Sized>() -> PrimitiveType {
+        match T::TYPE_INFO {
+            TypeInfo::Unit => PrimitiveType::Unit,
+            #[cfg(feature = "never")]
+            TypeInfo::Never => PrimitiveType::Never,
+            TypeInfo::Bool => PrimitiveType::Bool,
+            TypeInfo::Pointer { .. } => PrimitiveType::Pointer,
+            TypeInfo::Integer(tp) => PrimitiveType::Integer(tp),
+            TypeInfo::Float { size } => PrimitiveType::Float { size },
+            _ => panic!("Not a primitive type"),
+        }
+    }
+    /// The name of this type in Rust
+    ///
+    /// Untyped pointers are named `*mut ()`.
+    pub const fn rust_name(&self) -> &'static str {
+        match *self {
+            PrimitiveType::Unit => "()",
+            #[cfg(feature = "never")]
+            PrimitiveType::Never => "!",
+            PrimitiveType::Bool => "bool",
+            PrimitiveType::Pointer => "*mut ()",
+            PrimitiveType::Integer(tp) => tp.rust_name(),
+            PrimitiveType::Float {
+                size: FloatSize::Single,
+            } => "f32",
+            PrimitiveType::Float {
+                size: FloatSize::Double,
+            } => "f64",
+        }
+    }
+    /// The name of this type in C (using the fixed-width integers from `<stdint.h>`)
+    ///
+    /// Returns `None` for the never type, which C doesn't have.
+    /// The unit type is named `void`.
+    pub const fn c_name(&self) -> Option<&'static str> {
+        Some(match *self {
+            PrimitiveType::Unit => "void",
+            #[cfg(feature = "never")]
+            PrimitiveType::Never => return None,
+            PrimitiveType::Bool => "bool",
+            PrimitiveType::Pointer => "void*",
+            PrimitiveType::Integer(tp) => tp.c_name(),
+            PrimitiveType::Float {
+                size: FloatSize::Single,
+            } => "float",
+            PrimitiveType::Float {
+                size: FloatSize::Double,
+            } => "double",
+        })
+    }
+    /// Metadata describing this type, for use in code generators
+    pub fn meta(&self) -> PrimitiveMeta {
+        PrimitiveMeta {
+            primitive: *self,
+            rust_name: self.rust_name(),
+            c_name: self.c_name(),
+            size: self.size(),
+            alignment: self.alignment(),
+        }
+    }
+}
+/// Iterate over every primitive type (see [PrimitiveType::ALL]),
+/// along with its names and layout
+pub fn primitives() -> impl Iterator<Item = (&'static TypeInfo, PrimitiveMeta)> {
+    PrimitiveType::ALL
+        .iter()
+        .map(|primitive| (primitive.type_info(), primitive.meta()))
 }
-/// Compare two primitive types based on their sizes
+/// The names and layout of a [PrimitiveType]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PrimitiveMeta {
+    /// The primitive type
+    pub primitive: PrimitiveType,
+    /// The name of the type in Rust (see [PrimitiveType::rust_name])
+    pub rust_name: &'static str,
+    /// The name of the type in C (see [PrimitiveType::c_name])
+    pub c_name: Option<&'static str>,
+    /// The size of the type, in bytes
+    pub size: usize,
+    /// The alignment of the type, in bytes
+    pub alignment: usize,
+}
+/// Compare two primitive types based on whether they can be converted losslessly.
+///
+/// A type is "less than" another if all its values can be exactly represented by the other,
+/// as determined by [lattice::can_convert_lossless].
 impl PartialOrd for PrimitiveType {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (*self, *other) {
-            (
-                PrimitiveType::Integer(IntType {
-                    size: first,
-                    signed: first_signed,
-                }),
-                PrimitiveType::Integer(IntType {
-                    size: second,
-                    signed: second_signed,
-                }),
-            ) => Some(first.cmp(&second).then(first_signed.cmp(&second_signed))),
-            (PrimitiveType::Float { size: first }, PrimitiveType::Float { size: second }) => {
-                Some(first.cmp(&second))
-            }
-            (first, other) if first == other => Some(Ordering::Equal),
-            _ => None,
+        if self == other {
+            Some(Ordering::Equal)
+        } else if lattice::can_convert_lossless(*self, *other) {
+            Some(Ordering::Less)
+        } else if lattice::can_convert_lossless(*other, *self) {
+            Some(Ordering::Greater)
+        } else {
+            None
         }
     }
 }
@@ -998,7 +2624,7 @@ impl<T: StaticReflect> TypeId<T> {
     /// If this type is a pointer
     #[inline]
     pub fn is_ptr(self) -> bool {
-        matches!(self.primitive(), Some(PrimitiveType::Pointer { .. }))
+        matches!(self.primitive(), Some(PrimitiveType::Pointer))
     }
     /// If this type is a floating point number (of any size)
     #[inline]
@@ -1016,14 +2642,52 @@ impl<T: StaticReflect> TypeId<T> {
     pub fn primitive(self) -> Option<PrimitiveType> {
         Some(match *self.value {
             TypeInfo::Unit => PrimitiveType::Unit,
+            #[cfg(feature = "never")]
             TypeInfo::Never => PrimitiveType::Never,
             TypeInfo::Bool => PrimitiveType::Bool,
-            TypeInfo::Pointer => PrimitiveType::Pointer,
+            TypeInfo::Pointer { .. } => PrimitiveType::Pointer,
             TypeInfo::Integer(tp) => PrimitiveType::Integer(tp),
             TypeInfo::Float { size } => PrimitiveType::Float { size },
+            // Non-zero types are represented like any other primitive
+            TypeInfo::NonZero(inner) => return TypeId::<()>::from_static(inner).primitive(),
+            // So are niche enums, with the empty variant as zero
+            TypeInfo::Niche(def) => return TypeId::<()>::from_static(def.wrapped).primitive(),
             _ => return None,
         })
     }
+    /// Determine the common type of a binary arithmetic operation
+    /// (see [lattice::common_arithmetic_type])
+    ///
+    /// Returns `None` if either type isn't a numeric primitive.
+    #[inline]
+    pub fn common_arithmetic_type<U: StaticReflect>(
+        self,
+        other: TypeId<U>,
+    ) -> Option<PrimitiveType> {
+        lattice::common_arithmetic_type(self.primitive()?, other.primitive()?)
+    }
+    /// Check if this integer type can be widened into the other
+    /// (see [lattice::can_widen])
+    ///
+    /// Returns `None` if either type isn't an integer.
+    #[inline]
+    pub fn can_widen<U: StaticReflect>(self, other: TypeId<U>) -> Option<bool> {
+        match (*self.value, *other.value) {
+            (TypeInfo::Integer(from), TypeInfo::Integer(to)) => Some(lattice::can_widen(from, to)),
+            _ => None,
+        }
+    }
+    /// Check if this type can be converted into the other without losing information
+    /// (see [lattice::can_convert_lossless])
+    ///
+    /// Returns `None` if either type isn't a primitive.
+    #[inline]
+    pub fn can_convert_lossless<U: StaticReflect>(self, other: TypeId<U>) -> Option<bool> {
+        Some(lattice::can_convert_lossless(
+            self.primitive()?,
+            other.primitive()?,
+        ))
+    }
     /// A reference to the underlying type
     #[inline]
     pub const fn type_ref(self) -> &'static TypeInfo {
@@ -1074,7 +2738,7 @@ impl<T: StaticReflect> Debug for TypeId<T> {
 }
 
 /// A indexed identifier of a field
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct FieldId {
     /// The owner of the field
     pub owner: TypeId,
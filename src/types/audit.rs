@@ -0,0 +1,369 @@
+//! Audit how much of the reflected types is actually known
+//!
+//! Escape hatches like extern types, `assume_repr` and opaque arrays
+//! are sometimes necessary, but they hide part of the real layout.
+//! An [AuditReport] lists every place they are used.
+//!
+//! Since extern types only compare by name, the audit also reports
+//! any extern type sharing its name with another reachable type ([AuditKind::NameCollision]).
+//! Pointer fields without a documented ownership discipline are reported as well
+//! ([AuditKind::UndocumentedPointer]), for linters that check how pointers are used.
+//! Integer fields used as sets of named flags ([AuditKind::Bitset]) are listed too,
+//! since nothing checks that their bits are used as documented.
+//!
+//! The FFI safety of a type isn't part of its [TypeInfo] (see [crate::types::ffi]),
+//! so it's only reported for the signatures passed to [audit_signatures],
+//! or explicitly with [AuditReport::record_ffi_safety].
+use super::ffi::FfiSafety;
+use super::{FieldDef, TypeInfo, UnionFieldDef};
+use crate::funcs::SignatureDef;
+use crate::options::{ReflectOptions, UnionInspection};
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+/// The kind of escape hatch found by an [audit]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AuditKind {
+    /// An opaque extern type ([TypeInfo::Extern])
+    Extern,
+    /// A user-defined magic type ([TypeInfo::Magic])
+    Magic,
+    /// A field whose representation is assumed ([FieldDef::assumed])
+    AssumedRepr,
+    /// An opaque array field ([FieldDef::opaque_array])
+    OpaqueArray,
+    /// An integer field whose bits are named flags ([FieldMeta::bitset](super::FieldMeta::bitset))
+    Bitset,
+    /// A pointer, which doesn't record the type it points to
+    UntypedPointer,
+    /// A pointer field without a documented ownership discipline
+    /// ([FieldMeta::pointer_discipline](super::FieldMeta::pointer_discipline))
+    ///
+    /// Explicitly marking a field `#[reflect(ptr = "raw")]` documents it.
+    UndocumentedPointer,
+    /// An extern type with the same name as another (reachable) nominal type,
+    /// like a structure
+    ///
+    /// Consumers which look up types by name would conflate them.
+    NameCollision,
+    /// A type (or signature) which may not be FFI-safe ([FfiSafety::Warning])
+    FfiWarning,
+    /// A type (or signature) which isn't FFI-safe ([FfiSafety::Unsafe])
+    FfiUnsafe,
+}
+impl AuditKind {
+    /// All the kinds of escape hatches, in the order they're tallied
+    pub const ALL: [AuditKind; 10] = [
+        AuditKind::Extern,
+        AuditKind::Magic,
+        AuditKind::AssumedRepr,
+        AuditKind::OpaqueArray,
+        AuditKind::Bitset,
+        AuditKind::UntypedPointer,
+        AuditKind::UndocumentedPointer,
+        AuditKind::NameCollision,
+        AuditKind::FfiWarning,
+        AuditKind::FfiUnsafe,
+    ];
+    /// The name of the kind, as displayed in the report
+    pub const fn name(&self) -> &'static str {
+        match *self {
+            AuditKind::Extern => "extern",
+            AuditKind::Magic => "magic",
+            AuditKind::AssumedRepr => "assume_repr",
+            AuditKind::OpaqueArray => "opaque_array",
+            AuditKind::Bitset => "bitset",
+            AuditKind::UntypedPointer => "untyped pointer",
+            AuditKind::UndocumentedPointer => "undocumented pointer",
+            AuditKind::NameCollision => "name collision",
+            AuditKind::FfiWarning => "ffi warning",
+            AuditKind::FfiUnsafe => "ffi unsafe",
+        }
+    }
+}
+impl Display for AuditKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single use of an escape hatch
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct AuditEntry {
+    /// The path to the field (like `SimpleStruct.nested_struct.cycle`)
+    ///
+    /// Tuple fields are given by their index.
+    pub path: String,
+    /// The kind of escape hatch
+    pub kind: AuditKind,
+    /// The name of the (reflected) type
+    pub type_name: String,
+}
+
+/// The result of an [audit], listing every use of an escape hatch
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuditReport {
+    /// Every use of an escape hatch, in the order they were found
+    pub entries: Vec<AuditEntry>,
+    /// The total number of fields visited
+    pub visited_fields: usize,
+}
+impl AuditReport {
+    /// The number of entries of the specified kind
+    pub fn count(&self, kind: AuditKind) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .count()
+    }
+    /// Check if no escape hatches were found
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Record the FFI safety of a type (or signature), if it isn't safe
+    ///
+    /// This is how to report the [StaticReflect::FFI_SAFETY](crate::StaticReflect::FFI_SAFETY)
+    /// of a structure, which isn't known to [audit].
+    pub fn record_ffi_safety(&mut self, path: &str, type_name: &str, safety: FfiSafety) {
+        let kind = match safety {
+            FfiSafety::Safe => return,
+            FfiSafety::Warning(_) => AuditKind::FfiWarning,
+            FfiSafety::Unsafe(_) => AuditKind::FfiUnsafe,
+        };
+        self.entries.push(AuditEntry {
+            path: path.into(),
+            kind,
+            type_name: type_name.into(),
+        });
+    }
+}
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.path.len())
+            .chain(std::iter::once("path".len()))
+            .max()
+            .unwrap();
+        let kind_width = AuditKind::ALL
+            .iter()
+            .map(|kind| kind.name().len())
+            .max()
+            .unwrap();
+        writeln!(
+            f,
+            "{:<path_width$}  {:<kind_width$}  type",
+            "path",
+            "kind",
+            path_width = path_width,
+            kind_width = kind_width
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:<path_width$}  {:<kind_width$}  {}",
+                entry.path,
+                entry.kind.name(),
+                entry.type_name,
+                path_width = path_width,
+                kind_width = kind_width
+            )?;
+        }
+        write!(f, "{} fields visited", self.visited_fields)?;
+        for kind in AuditKind::ALL {
+            let count = self.count(kind);
+            if count > 0 {
+                write!(f, ", {} {}", count, kind)?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
+/// Walk the specified types (and all the types of their fields),
+/// reporting every use of an escape hatch.
+///
+/// Each occurrence is reported separately,
+/// so a type used by multiple fields is reported for each of them.
+/// Name collisions are reported after everything else,
+/// once for each occurrence of the extern type.
+///
+/// This uses the [Default] options (see [audit_with]).
+#[inline]
+pub fn audit(roots: &[&TypeInfo]) -> AuditReport {
+    audit_with(roots, &ReflectOptions::default())
+}
+
+/// Audit the specified types using the specified options
+///
+/// The fields of types at [ReflectOptions::max_depth] (or deeper) aren't visited,
+/// and neither are the fields of unions with [UnionInspection::Skip].
+pub fn audit_with(roots: &[&TypeInfo], options: &ReflectOptions) -> AuditReport {
+    let mut report = AuditReport::default();
+    let mut names = NamedTypes::default();
+    for root in roots {
+        let path = root.to_string();
+        visit_type(root, &path, 0, options, &mut report, &mut names);
+    }
+    names.report_collisions(&mut report);
+    report
+}
+
+/// Audit the types of the specified signatures, along with their FFI safety
+///
+/// Each signature is given along with the name of its function.
+/// The arguments are audited by index (like `add.0`) and the return type as `add.return`.
+/// Unsafe signatures are reported once, after their types,
+/// using [SignatureDef::ffi_safety](crate::funcs::SignatureDef::ffi_safety).
+///
+/// This uses the [Default] options, like [audit].
+pub fn audit_signatures(signatures: &[(&str, &SignatureDef)]) -> AuditReport {
+    let options = ReflectOptions::default();
+    let mut report = AuditReport::default();
+    let mut names = NamedTypes::default();
+    for &(name, signature) in signatures {
+        for (index, arg) in signature.argument_types.iter().enumerate() {
+            let path = format!("{}.{}", name, index);
+            visit_type(arg, &path, 0, &options, &mut report, &mut names);
+        }
+        let path = format!("{}.return", name);
+        visit_type(
+            signature.return_type,
+            &path,
+            0,
+            &options,
+            &mut report,
+            &mut names,
+        );
+        report.record_ffi_safety(name, &signature.to_string(), signature.ffi_safety);
+    }
+    names.report_collisions(&mut report);
+    report
+}
+
+/// The nominal types found by an audit, to detect name collisions
+#[derive(Default)]
+struct NamedTypes {
+    /// Each occurrence of an extern type, by path and name
+    externs: Vec<(String, &'static str)>,
+    /// The names of the other nominal types
+    others: HashSet<&'static str>,
+}
+impl NamedTypes {
+    fn report_collisions(self, report: &mut AuditReport) {
+        for (path, name) in self.externs {
+            if self.others.contains(name) {
+                report.entries.push(AuditEntry {
+                    path,
+                    kind: AuditKind::NameCollision,
+                    type_name: format!("extern {}", name),
+                });
+            }
+        }
+    }
+}
+
+fn visit_type(
+    info: &TypeInfo,
+    path: &str,
+    depth: usize,
+    options: &ReflectOptions,
+    report: &mut AuditReport,
+    names: &mut NamedTypes,
+) {
+    match (*info, info.nominal_name()) {
+        (TypeInfo::Extern { name }, _) => names.externs.push((path.into(), name)),
+        (_, Some(name)) => {
+            names.others.insert(name);
+        }
+        _ => {}
+    }
+    let mut record = |kind: AuditKind| {
+        report.entries.push(AuditEntry {
+            path: path.into(),
+            kind,
+            type_name: info.to_string(),
+        })
+    };
+    match *info {
+        TypeInfo::Extern { .. } => record(AuditKind::Extern),
+        TypeInfo::Magic { .. } => record(AuditKind::Magic),
+        TypeInfo::Pointer { .. } | TypeInfo::NonZero(TypeInfo::Pointer { .. }) => {
+            record(AuditKind::UntypedPointer)
+        }
+        TypeInfo::Structure(def) if depth < options.max_depth => {
+            for field in def.fields {
+                let path = field_path(path, field);
+                visit_field(field, &path, depth + 1, options, report, names);
+            }
+        }
+        TypeInfo::UntaggedUnion(def)
+            if depth < options.max_depth && options.union_inspection == UnionInspection::Raw =>
+        {
+            for field in def.fields {
+                let path = format!("{}.{}", path, field.name);
+                visit_union_field(field, &path, depth + 1, options, report, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn field_path(parent: &str, field: &FieldDef) -> String {
+    match field.name {
+        Some(name) => format!("{}.{}", parent, name),
+        None => format!("{}.{}", parent, field.index),
+    }
+}
+
+fn visit_field(
+    field: &FieldDef,
+    path: &str,
+    depth: usize,
+    options: &ReflectOptions,
+    report: &mut AuditReport,
+    names: &mut NamedTypes,
+) {
+    report.visited_fields += 1;
+    let value_type = field.value_type.type_ref();
+    let is_pointer = matches!(value_type.nullable(), TypeInfo::Pointer { .. });
+    for (flag, kind) in [
+        (field.assumed, AuditKind::AssumedRepr),
+        (field.opaque_array, AuditKind::OpaqueArray),
+        (field.meta.bitset.is_some(), AuditKind::Bitset),
+        (
+            is_pointer && field.meta.pointer_discipline.is_none(),
+            AuditKind::UndocumentedPointer,
+        ),
+    ] {
+        if flag {
+            report.entries.push(AuditEntry {
+                path: path.into(),
+                kind,
+                type_name: value_type.to_string(),
+            });
+        }
+    }
+    visit_type(value_type, path, depth, options, report, names);
+}
+
+fn visit_union_field(
+    field: &UnionFieldDef,
+    path: &str,
+    depth: usize,
+    options: &ReflectOptions,
+    report: &mut AuditReport,
+    names: &mut NamedTypes,
+) {
+    report.visited_fields += 1;
+    visit_type(
+        field.value_type.type_ref(),
+        path,
+        depth,
+        options,
+        report,
+        names,
+    );
+}
@@ -0,0 +1,72 @@
+//! Named bits of integer fields, which are used as sets of boolean flags
+//!
+//! A field annotated with `#[reflect(bitset(names = "READABLE,WRITABLE"))]`
+//! records a [BitsetDef] in its [FieldMeta](super::FieldMeta), naming each of its bits in order.
+//! This is only a logical view, distinct from C bit-fields:
+//! the field keeps its integer type, and the flags are counted from its least significant bit.
+//! In an `opaque_array` of integers, the bits continue into the following elements,
+//! so the flags of a `[u8; 8]` are the 64 bits of its bytes in order.
+//!
+//! The derive checks that the names fit in the bit width of the field.
+use super::value::ReflectValue;
+use super::{FieldDef, PrimitiveType, TypeInfo};
+use crate::StaticReflect;
+use std::fmt::{self, Display, Formatter};
+
+/// The names of the bits of a field, starting from the least significant bit
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitsetDef {
+    /// The name of each bit, in order
+    pub names: &'static [&'static str],
+}
+impl BitsetDef {
+    /// The index of the bit with the specified name
+    ///
+    /// Returns `None` if no bit has that name.
+    pub fn bit_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|&bit| bit == name)
+    }
+}
+impl Display for BitsetDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, name) in self.names.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check if the named bit of a field is set,
+/// given a pointer to the start of the structure containing it
+///
+/// If the field has an explicit [Endian](super::Endian)ness,
+/// each integer is converted to the native byte order first.
+/// Returns `None` if the field doesn't have a [BitsetDef] (with a bit of that name),
+/// or if its type isn't an integer.
+///
+/// ## Safety
+/// The base pointer must point to a valid value of the structure containing the field.
+pub unsafe fn test_bit<T: StaticReflect>(
+    base: *const u8,
+    field: &FieldDef<T>,
+    name: &str,
+) -> Option<bool> {
+    let index = field.meta.bitset?.bit_index(name)?;
+    let int_type = match *field.value_type.type_ref() {
+        TypeInfo::Integer(int_type) => int_type,
+        _ => return None,
+    };
+    let width = int_type.size.bytes() * 8;
+    let element = base.add(field.offset + (index / width) * int_type.size.bytes());
+    let mut value = ReflectValue::read(element, PrimitiveType::Integer(int_type));
+    if let Some(endian) = field.endianness {
+        value = value.convert_endian(endian);
+    }
+    match value {
+        ReflectValue::Integer { bits, .. } => Some((bits >> (index % width)) & 1 != 0),
+        _ => unreachable!(),
+    }
+}
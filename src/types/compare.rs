@@ -0,0 +1,217 @@
+//! Configurable comparisons between type definitions
+//!
+//! The derived `PartialEq` implementations compare everything exactly.
+//! This is the right default, but too strict for some purposes
+//! (like comparing against definitions loaded from an older build).
+//! The `equivalent` methods accept [CompareOptions] to relax the comparison.
+//!
+//! Both kinds of comparison walk the whole type tree, even for definitions at the same address.
+//! For large structures that's hundreds of nanoseconds,
+//! so prefer `std::ptr::eq` when the definitions are known to be shared.
+use super::{
+    CStyleEnumDef, CStyleEnumVariant, FieldDef, StructureDef, TypeInfo, UnionFieldDef,
+    UntaggedUnionDef,
+};
+
+/// Options controlling how strictly definitions are compared
+///
+/// The default options are [CompareOptions::STRICT].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CompareOptions {
+    /// Ignore the order (and indexes) of fields and variants,
+    /// matching them up by their names instead
+    pub ignore_order: bool,
+    /// Ignore the names of types, fields and variants,
+    /// matching fields up by their offsets and types
+    /// (and variants by their discriminants)
+    pub ignore_names: bool,
+    /// Require types to have identical sizes and alignments
+    pub require_same_size: bool,
+}
+impl CompareOptions {
+    /// Require everything to be equal, just like `PartialEq`
+    pub const STRICT: CompareOptions = CompareOptions {
+        ignore_order: false,
+        ignore_names: false,
+        require_same_size: true,
+    };
+    /// Only compare the layout of types, ignoring their names
+    ///
+    /// This is used by [TypeInfo::layout_eq].
+    pub const LAYOUT: CompareOptions = CompareOptions {
+        ignore_order: false,
+        ignore_names: true,
+        require_same_size: true,
+    };
+    /// Ignore the order and indexes of fields, matching them by name
+    pub const IGNORE_ORDER: CompareOptions = CompareOptions {
+        ignore_order: true,
+        ignore_names: false,
+        require_same_size: true,
+    };
+}
+impl Default for CompareOptions {
+    #[inline]
+    fn default() -> Self {
+        CompareOptions::STRICT
+    }
+}
+
+impl TypeInfo {
+    /// Check if two types are equivalent, according to the specified options
+    ///
+    /// Structures, unions and C-style enums are compared using their own `equivalent` methods.
+    /// Wrapper types (like [TypeInfo::NonZero]) are compared recursively,
+    /// and all other types must be exactly equal.
+    pub fn equivalent(&self, other: &TypeInfo, options: CompareOptions) -> bool {
+        match (*self, *other) {
+            (TypeInfo::Structure(first), TypeInfo::Structure(second)) => {
+                first.equivalent(second, options)
+            }
+            (TypeInfo::UntaggedUnion(first), TypeInfo::UntaggedUnion(second)) => {
+                first.equivalent(second, options)
+            }
+            (TypeInfo::CStyleEnum(first), TypeInfo::CStyleEnum(second)) => {
+                first.equivalent(second, options)
+            }
+            (TypeInfo::Niche(first), TypeInfo::Niche(second)) => {
+                first.none_value == second.none_value
+                    && (options.ignore_names
+                        || (first.name == second.name
+                            && first.none_variant == second.none_variant
+                            && first.some_variant == second.some_variant))
+                    && first.wrapped.equivalent(second.wrapped, options)
+            }
+            (TypeInfo::NonZero(first), TypeInfo::NonZero(second)) => {
+                first.equivalent(second, options)
+            }
+            (first, second) => {
+                match (
+                    first.slice_element_type(),
+                    second.slice_element_type(),
+                    first.optional_inner_type(),
+                    second.optional_inner_type(),
+                ) {
+                    (Some(first), Some(second), _, _) | (_, _, Some(first), Some(second)) => {
+                        first.equivalent(second, options)
+                    }
+                    _ => first == second,
+                }
+            }
+        }
+    }
+}
+
+impl StructureDef {
+    /// Check if two structures are equivalent, according to the specified options
+    ///
+    /// Fields must have the same offsets and metadata (like their endianness),
+    /// with equivalent types (compared recursively).
+    pub fn equivalent(&self, other: &StructureDef, options: CompareOptions) -> bool {
+        (options.ignore_names || self.name == other.name)
+            && (!options.require_same_size
+                || (self.size == other.size && self.alignment == other.alignment))
+            && self.is_pod == other.is_pod
+            && self.has_unsized_tail == other.has_unsized_tail
+            && self.needs_drop == other.needs_drop
+            && match_all(self.fields, other.fields, |a, b| {
+                field_equivalent(a, b, options)
+            })
+    }
+}
+fn field_equivalent(first: &FieldDef, second: &FieldDef, options: CompareOptions) -> bool {
+    first.offset == second.offset
+        && (options.ignore_names || first.name == second.name)
+        && (options.ignore_order || first.index == second.index)
+        && first.endianness == second.endianness
+        && first.assumed == second.assumed
+        && first.opaque_array == second.opaque_array
+        && first.meta.pointer_discipline == second.meta.pointer_discipline
+        && (options.ignore_names || first.meta.bitset == second.meta.bitset)
+        && first
+            .value_type
+            .type_ref()
+            .equivalent(second.value_type.type_ref(), options)
+}
+
+impl UntaggedUnionDef {
+    /// Check if two unions are equivalent, according to the specified options
+    ///
+    /// If names are ignored, fields are matched up by their types.
+    pub fn equivalent(&self, other: &UntaggedUnionDef, options: CompareOptions) -> bool {
+        (options.ignore_names || self.name == other.name)
+            && (!options.require_same_size
+                || (self.size == other.size && self.alignment == other.alignment))
+            && self.needs_drop == other.needs_drop
+            && match_all(
+                &self.fields.iter().collect::<Vec<_>>(),
+                &other.fields.iter().collect::<Vec<_>>(),
+                |a, b| union_field_equivalent(a, b, options),
+            )
+    }
+}
+fn union_field_equivalent(
+    first: &UnionFieldDef,
+    second: &UnionFieldDef,
+    options: CompareOptions,
+) -> bool {
+    (options.ignore_names || first.name == second.name)
+        && (options.ignore_order || first.index == second.index)
+        && first
+            .value_type
+            .type_ref()
+            .equivalent(second.value_type.type_ref(), options)
+}
+
+impl CStyleEnumDef {
+    /// Check if two C-style enums are equivalent, according to the specified options
+    ///
+    /// Variants must have the same discriminant values.
+    /// The signedness of the discriminants must always match,
+    /// but their sizes may differ if sizes aren't required to be the same.
+    pub fn equivalent(&self, other: &CStyleEnumDef, options: CompareOptions) -> bool {
+        let discriminants_match = if options.require_same_size {
+            self.discriminant == other.discriminant
+        } else {
+            self.discriminant.signed == other.discriminant.signed
+        };
+        (options.ignore_names || self.name == other.name)
+            && discriminants_match
+            && match_all(self.variants, other.variants, |a, b| {
+                variant_equivalent(a, b, options)
+            })
+    }
+}
+fn variant_equivalent(
+    first: &CStyleEnumVariant,
+    second: &CStyleEnumVariant,
+    options: CompareOptions,
+) -> bool {
+    first.discriminant.bits() == second.discriminant.bits()
+        && (options.ignore_names || first.name == second.name)
+        && (options.ignore_order || first.index == second.index)
+}
+
+/// Check that every item has a distinct equivalent in the other slice
+///
+/// Unless the order is ignored, equivalent items must have the same index,
+/// so this is the same as comparing them pairwise.
+fn match_all<T>(first: &[T], second: &[T], equivalent: impl Fn(&T, &T) -> bool) -> bool {
+    if first.len() != second.len() {
+        return false;
+    }
+    let mut matched = vec![false; second.len()];
+    first.iter().all(|a| {
+        let found = second
+            .iter()
+            .enumerate()
+            .position(|(index, b)| !matched[index] && equivalent(a, b));
+        match found {
+            Some(index) => {
+                matched[index] = true;
+                true
+            }
+            None => false,
+        }
+    })
+}
@@ -0,0 +1,377 @@
+//! Human-readable differences between two versions of a type,
+//! for reviewing changes to an ABI
+//!
+//! Fields are matched up by their names first.
+//! The remaining fields are then matched by their offsets and types,
+//! so a field that was only renamed isn't reported as removed and added.
+//! Fields that are exactly the same (except for their index) aren't reported.
+//!
+//! ## Compatibility
+//! A [StructureDiff] is ABI compatible ([StructureDiff::is_abi_compatible]) if:
+//! - The size and alignment of the structure are unchanged
+//! - No fields were removed or added
+//! - Every matched field has the same offset, and a type with the same layout
+//!   ([TypeInfo::layout_eq]). Renames are fine, since names aren't part of the ABI.
+//!
+//! With [DiffOptions::allow_trailing_fields], fields may also be added after the end
+//! of the old structure, which may then grow (but must keep its alignment).
+//! This is only compatible if the new fields are never accessed through the old definition,
+//! like in a structure which is only allocated by a library that records its own size.
+use super::{FieldDef, StructureDef, TypeInfo, UntaggedUnionDef};
+use std::fmt::{self, Display, Formatter};
+
+/// Options controlling which changes are considered ABI compatible
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DiffOptions {
+    /// Allow adding fields after the end of the old structure
+    pub allow_trailing_fields: bool,
+}
+
+/// A summary of a field, in either version of a type
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FieldSummary {
+    /// The name of the field, or `None` for the fields of a tuple struct
+    pub name: Option<&'static str>,
+    /// The index of the field
+    pub index: usize,
+    /// The offset of the field in bytes
+    pub offset: usize,
+    /// The type of the field
+    pub value_type: &'static TypeInfo,
+}
+impl FieldSummary {
+    pub(super) fn from_field(field: &FieldDef) -> FieldSummary {
+        FieldSummary {
+            name: field.name,
+            index: field.index,
+            offset: field.offset,
+            value_type: field.value_type.type_ref(),
+        }
+    }
+    /// The size of the field's type
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.value_type.size()
+    }
+}
+impl Display for FieldSummary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => f.write_str(name),
+            None => write!(f, "#{}", self.index),
+        }
+    }
+}
+
+/// A change to a single field
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FieldChange {
+    /// The field only exists in the new version
+    Added(FieldSummary),
+    /// The field only exists in the old version
+    Removed(FieldSummary),
+    /// The field exists in both versions, but was renamed, moved or retyped
+    Changed {
+        /// The field in the old version
+        old: FieldSummary,
+        /// The field in the new version
+        new: FieldSummary,
+    },
+}
+impl FieldChange {
+    /// If the field was renamed
+    pub fn is_renamed(&self) -> bool {
+        matches!(*self, FieldChange::Changed { old, new } if old.name != new.name)
+    }
+    /// If the offset of the field changed
+    pub fn is_moved(&self) -> bool {
+        matches!(*self, FieldChange::Changed { old, new } if old.offset != new.offset)
+    }
+    /// If the type of the field changed
+    pub fn is_retyped(&self) -> bool {
+        matches!(*self, FieldChange::Changed { old, new } if old.value_type != new.value_type)
+    }
+}
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            FieldChange::Added(field) => {
+                write!(f, "+ {}: {} at {}", field, field.value_type, field.offset)
+            }
+            FieldChange::Removed(field) => {
+                write!(f, "- {}: {} at {}", field, field.value_type, field.offset)
+            }
+            FieldChange::Changed { old, new } => {
+                write!(f, "~ {}", old)?;
+                if old.name != new.name {
+                    write!(f, " -> {}", new)?;
+                }
+                write!(f, ": ")?;
+                if old.value_type == new.value_type {
+                    write!(f, "{}", old.value_type)?;
+                } else {
+                    write!(f, "{} -> {}", old.value_type, new.value_type)?;
+                }
+                if old.offset == new.offset {
+                    write!(f, " at {}", old.offset)
+                } else {
+                    write!(f, " at {} -> {}", old.offset, new.offset)
+                }
+            }
+        }
+    }
+}
+
+/// The differences between two versions of a structure (or union)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StructureDiff {
+    /// The name of the old version
+    pub old_name: &'static str,
+    /// The name of the new version
+    pub new_name: &'static str,
+    /// The size of the old version
+    pub old_size: usize,
+    /// The size of the new version
+    pub new_size: usize,
+    /// The alignment of the old version
+    pub old_alignment: usize,
+    /// The alignment of the new version
+    pub new_alignment: usize,
+    /// The changed fields, with the fields of the old version first (in order),
+    /// followed by the fields that were added
+    pub fields: Vec<FieldChange>,
+}
+impl StructureDiff {
+    /// If nothing changed (except for names of the types)
+    pub fn is_unchanged(&self) -> bool {
+        self.fields.is_empty()
+            && self.old_size == self.new_size
+            && self.old_alignment == self.new_alignment
+    }
+    /// Whether the new version is ABI compatible with the old one
+    ///
+    /// See the [module documentation](self) for the rules.
+    pub fn is_abi_compatible(&self, options: DiffOptions) -> bool {
+        if self.old_alignment != self.new_alignment {
+            return false;
+        }
+        let grew = self.new_size > self.old_size;
+        if self.old_size != self.new_size && !(options.allow_trailing_fields && grew) {
+            return false;
+        }
+        self.fields.iter().all(|change| match *change {
+            FieldChange::Added(field) => {
+                options.allow_trailing_fields && field.offset >= self.old_size
+            }
+            FieldChange::Removed(_) => false,
+            FieldChange::Changed { old, new } => {
+                old.offset == new.offset && old.value_type.layout_eq(new.value_type)
+            }
+        })
+    }
+}
+impl Display for StructureDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.old_name)?;
+        if self.old_name != self.new_name {
+            write!(f, " -> {}", self.new_name)?;
+        }
+        if self.old_size == self.new_size {
+            write!(f, ": size {}", self.old_size)?;
+        } else {
+            write!(f, ": size {} -> {}", self.old_size, self.new_size)?;
+        }
+        if self.old_alignment == self.new_alignment {
+            write!(f, ", alignment {}", self.old_alignment)?;
+        } else {
+            write!(
+                f,
+                ", alignment {} -> {}",
+                self.old_alignment, self.new_alignment
+            )?;
+        }
+        for change in &self.fields {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compute the differences between two versions of a structure
+pub fn diff_structures(old: &StructureDef, new: &StructureDef) -> StructureDiff {
+    let old_fields = old
+        .fields
+        .iter()
+        .map(FieldSummary::from_field)
+        .collect::<Vec<_>>();
+    let new_fields = new
+        .fields
+        .iter()
+        .map(FieldSummary::from_field)
+        .collect::<Vec<_>>();
+    StructureDiff {
+        old_name: old.name,
+        new_name: new.name,
+        old_size: old.size,
+        new_size: new.size,
+        old_alignment: old.alignment,
+        new_alignment: new.alignment,
+        fields: diff_fields(&old_fields, &new_fields),
+    }
+}
+
+/// Compute the differences between two versions of an untagged union
+///
+/// Every field of a union is at offset zero,
+/// so renamed fields are matched up by their types alone.
+pub fn diff_unions(old: &UntaggedUnionDef, new: &UntaggedUnionDef) -> StructureDiff {
+    let summarize = |def: &UntaggedUnionDef| {
+        def.fields
+            .iter()
+            .map(|field| FieldSummary {
+                name: Some(field.name),
+                index: field.index,
+                offset: 0,
+                value_type: field.value_type.type_ref(),
+            })
+            .collect::<Vec<_>>()
+    };
+    StructureDiff {
+        old_name: old.name,
+        new_name: new.name,
+        old_size: old.size,
+        new_size: new.size,
+        old_alignment: old.alignment,
+        new_alignment: new.alignment,
+        fields: diff_fields(&summarize(old), &summarize(new)),
+    }
+}
+
+fn diff_fields(old: &[FieldSummary], new: &[FieldSummary]) -> Vec<FieldChange> {
+    let mut matches: Vec<Option<usize>> = vec![None; old.len()];
+    let mut matched_new = vec![false; new.len()];
+    // Match by name first
+    for (old_index, old_field) in old.iter().enumerate() {
+        if old_field.name.is_none() {
+            continue;
+        }
+        if let Some(new_index) =
+            (0..new.len()).find(|&index| !matched_new[index] && new[index].name == old_field.name)
+        {
+            matches[old_index] = Some(new_index);
+            matched_new[new_index] = true;
+        }
+    }
+    // Then match the rest by offset and type (like a renamed field)
+    for (old_index, old_field) in old.iter().enumerate() {
+        if matches[old_index].is_some() {
+            continue;
+        }
+        if let Some(new_index) = (0..new.len()).find(|&index| {
+            !matched_new[index]
+                && new[index].offset == old_field.offset
+                && new[index].value_type == old_field.value_type
+        }) {
+            matches[old_index] = Some(new_index);
+            matched_new[new_index] = true;
+        }
+    }
+    let mut changes = Vec::new();
+    for (old_field, matched) in old.iter().zip(&matches) {
+        match *matched {
+            Some(new_index) => {
+                let new_field = new[new_index];
+                if old_field.name != new_field.name
+                    || old_field.offset != new_field.offset
+                    || old_field.value_type != new_field.value_type
+                {
+                    changes.push(FieldChange::Changed {
+                        old: *old_field,
+                        new: new_field,
+                    });
+                }
+            }
+            None => changes.push(FieldChange::Removed(*old_field)),
+        }
+    }
+    for (new_field, matched) in new.iter().zip(&matched_new) {
+        if !matched {
+            changes.push(FieldChange::Added(*new_field));
+        }
+    }
+    changes
+}
+
+/// The differences between two versions of any type
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypeDiff {
+    /// The types are exactly the same
+    Unchanged,
+    /// Both versions are structures, or both are untagged unions,
+    /// which are compared field by field
+    Fields(StructureDiff),
+    /// The types are of some other kind (or of different kinds), and aren't equal
+    Replaced {
+        /// The name of the old type
+        old: String,
+        /// The name of the new type
+        new: String,
+        /// Whether the two types have the same layout ([TypeInfo::layout_eq])
+        layout_eq: bool,
+    },
+}
+impl TypeDiff {
+    /// Whether the new version is ABI compatible with the old one
+    ///
+    /// Structures and unions follow the rules of [StructureDiff::is_abi_compatible],
+    /// while other types are compatible if they have the same layout.
+    pub fn is_abi_compatible(&self, options: DiffOptions) -> bool {
+        match *self {
+            TypeDiff::Unchanged => true,
+            TypeDiff::Fields(ref diff) => diff.is_abi_compatible(options),
+            TypeDiff::Replaced { layout_eq, .. } => layout_eq,
+        }
+    }
+}
+impl Display for TypeDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            TypeDiff::Unchanged => f.write_str("unchanged"),
+            TypeDiff::Fields(ref diff) => Display::fmt(diff, f),
+            TypeDiff::Replaced {
+                ref old,
+                ref new,
+                layout_eq,
+            } => {
+                write!(f, "{} -> {}", old, new)?;
+                if layout_eq {
+                    f.write_str(" (same layout)")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Compute the differences between two versions of a type,
+/// dispatching on their kinds
+///
+/// Structures are compared with [diff_structures] and untagged unions with [diff_unions].
+pub fn diff_types(old: &TypeInfo, new: &TypeInfo) -> TypeDiff {
+    if old == new {
+        return TypeDiff::Unchanged;
+    }
+    match (*old, *new) {
+        (TypeInfo::Structure(old), TypeInfo::Structure(new)) => {
+            TypeDiff::Fields(diff_structures(old, new))
+        }
+        (TypeInfo::UntaggedUnion(old), TypeInfo::UntaggedUnion(new)) => {
+            TypeDiff::Fields(diff_unions(old, new))
+        }
+        _ => TypeDiff::Replaced {
+            old: old.to_string(),
+            new: new.to_string(),
+            layout_eq: old.layout_eq(new),
+        },
+    }
+}
@@ -0,0 +1,78 @@
+//! Recover the static types of erased definitions
+//!
+//! Erasing a [TypeId] (or a field) forgets its type parameter.
+//! The `downcast` methods are the checked inverse,
+//! verifying the stored [TypeInfo] is equal to the reflected information of the requested type.
+//!
+//! These aren't `const`, since comparing [TypeInfo] isn't possible in a const context.
+use super::{FieldDef, TypeId, TypeInfo, UnionFieldDef};
+use crate::StaticReflect;
+use std::fmt::{self, Display, Formatter};
+
+/// An error downcasting an erased definition to the wrong type
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TypeMismatch {
+    /// The name of the requested type
+    pub expected: String,
+    /// The name of the type that was actually stored
+    pub actual: String,
+}
+impl TypeMismatch {
+    fn new(expected: &TypeInfo, actual: &TypeInfo) -> Self {
+        TypeMismatch {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+    }
+}
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected type {}, but found {}",
+            self.expected, self.actual
+        )
+    }
+}
+impl std::error::Error for TypeMismatch {}
+
+impl TypeId {
+    /// Recover the static type of this erased id,
+    /// checking that it's actually the reflected type of `T`
+    pub fn downcast<T: StaticReflect>(self) -> Result<TypeId<T>, TypeMismatch> {
+        if *self.value == T::TYPE_INFO {
+            Ok(TypeId::from_static(self.value))
+        } else {
+            Err(TypeMismatch::new(&T::TYPE_INFO, self.value))
+        }
+    }
+}
+
+impl FieldDef {
+    /// Recover the static type of this erased field,
+    /// checking that its value type is actually `T`
+    pub fn downcast<T: StaticReflect>(self) -> Result<FieldDef<T>, TypeMismatch> {
+        Ok(FieldDef {
+            name: self.name,
+            value_type: self.value_type.downcast()?,
+            offset: self.offset,
+            index: self.index,
+            endianness: self.endianness,
+            assumed: self.assumed,
+            opaque_array: self.opaque_array,
+            meta: self.meta,
+        })
+    }
+}
+
+impl UnionFieldDef {
+    /// Recover the static type of this erased union field,
+    /// checking that its value type is actually `T`
+    pub fn downcast<T: StaticReflect>(self) -> Result<UnionFieldDef<T>, TypeMismatch> {
+        Ok(UnionFieldDef {
+            name: self.name,
+            value_type: self.value_type.downcast()?,
+            index: self.index,
+        })
+    }
+}
@@ -0,0 +1,461 @@
+//! A canonical, compact byte encoding of values
+//!
+//! Unlike the in-memory representation, the encoding has no padding
+//! and doesn't depend on the byte order of the platform.
+//! It's deterministic: equal values always have the same encoding.
+//!
+//! ## Format
+//! The encoding of a value is determined entirely by its [TypeInfo]:
+//! - The unit type `()` is empty
+//! - A `bool` is a single byte, either `0` or `1`
+//! - Integers are little-endian, at their natural width ([IntSize::bytes]).
+//!   Fields with an explicit byte order ([FieldDef::endianness]) are converted first,
+//!   so the encoding is always little-endian.
+//! - Floats are their IEEE 754 bits, encoded like an unsigned integer of the same width
+//! - Non-zero integers are encoded like the underlying integer (zero is rejected when decoding)
+//! - Structures are the concatenation of their fields in declaration order, without any padding.
+//!   The unsized tail of a structure ([StructureDef::has_unsized_tail]) isn't included.
+//! - C-style enums are their discriminant, encoded like an integer of the discriminant's type
+//! - Tagged unions are their discriminant (like a C-style enum),
+//!   followed by the fields of the active variant (like a structure)
+//! - Niche enums are encoded like their wrapped type, with the empty variant as zero
+//!
+//! Pointers, untagged unions, slices, strings, optionals, opaque arrays
+//! and opaque (extern or magic) types can't be encoded,
+//! since their contents can't be interpreted without more context.
+//! The never type has no values.
+use super::{
+    Endian, FieldDef, FloatSize, IntSize, IntType, RegionError, StructureDef, TaggedUnionDef,
+    TaggedUnionStyle, TaggedUnionVariant, TypeInfo, TypedRegion,
+};
+use std::alloc::Layout;
+use std::fmt::{self, Display, Formatter};
+
+/// Append the canonical encoding of a value to the output
+///
+/// Nothing is guaranteed to be written if this fails.
+///
+/// ## Safety
+/// The pointer must be valid for reads of the type's size,
+/// and the memory must contain a valid value of the type.
+/// It doesn't need to be aligned.
+pub unsafe fn encode_value(
+    ptr: *const u8,
+    info: &TypeInfo,
+    out: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    let old_len = out.len();
+    let result = encode_inner(ptr, info, None, out);
+    if result.is_err() {
+        out.truncate(old_len);
+    }
+    result
+}
+
+/// Append the canonical encoding of every element of a region to the output
+///
+/// The encoding is the concatenation of the elements, like a structure of `count` fields.
+/// Nothing is guaranteed to be written if this fails.
+///
+/// ## Safety
+/// The pointer must be valid for reads of the region's size ([TypedRegion::byte_len]),
+/// and each element must contain a valid value of the type.
+/// It doesn't need to be aligned.
+pub unsafe fn encode_region(
+    ptr: *const u8,
+    region: &TypedRegion,
+    out: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    let stride = match region.byte_len() {
+        Ok(_) => region.stride().unwrap(),
+        Err(RegionError::Overflow { type_name, count }) => {
+            return Err(EncodeError::Overflow { type_name, count })
+        }
+        Err(RegionError::Unsupported(_)) => return Err(EncodeError::unsupported(region.element)),
+    };
+    let old_len = out.len();
+    for index in 0..region.count {
+        let result = encode_inner(ptr.add(index * stride), region.element, None, out);
+        if result.is_err() {
+            out.truncate(old_len);
+            return result;
+        }
+    }
+    Ok(())
+}
+
+/// Decode a value from the start of the bytes (in the canonical encoding),
+/// writing it to the destination
+///
+/// Returns the number of bytes consumed.
+/// Padding bytes in the destination are left unchanged,
+/// and the destination may be partially written if this fails.
+///
+/// ## Safety
+/// The destination must be valid for writes of the type's size.
+/// It doesn't need to be aligned.
+pub unsafe fn decode_value(
+    bytes: &[u8],
+    info: &TypeInfo,
+    dst: *mut u8,
+) -> Result<usize, DecodeError> {
+    let mut decoder = Decoder { bytes, position: 0 };
+    decoder.decode(info, None, dst)?;
+    Ok(decoder.position)
+}
+
+/// The integer type with the same width as a float
+#[inline]
+const fn float_bits_type(size: FloatSize) -> IntType {
+    IntSize::unwrap_from_bytes(size.bytes()).unsigned()
+}
+
+/// Convert bits between the native byte order and the specified one
+fn convert_endian(int_type: IntType, bits: u64, endian: Option<Endian>) -> u64 {
+    match endian {
+        Some(endian) if !endian.is_native() => {
+            bits.swap_bytes() >> (64 - int_type.size.bytes() * 8)
+        }
+        _ => bits,
+    }
+}
+
+/// The fields included in the encoding of a structure,
+/// or `None` if it has an opaque array
+fn encoded_fields(def: &StructureDef) -> Option<&'static [FieldDef]> {
+    let fields = if def.has_unsized_tail {
+        &def.fields[..def.fields.len().saturating_sub(1)]
+    } else {
+        def.fields
+    };
+    if fields.iter().any(|field| field.opaque_array) {
+        None
+    } else {
+        Some(fields)
+    }
+}
+
+/// The offset of a variant's fields within a tagged union
+///
+/// This follows [TaggedUnionStyle::compute_layout]: the "traditional" style
+/// places all the variants after the (padded) discriminant, aligned like the most aligned variant,
+/// while the "primitive" style places each variant directly after the discriminant.
+fn payload_offset(def: &TaggedUnionDef, variant: &TaggedUnionVariant) -> usize {
+    let discriminant = Layout::from_size_align(
+        def.discriminant_type.size.bytes(),
+        def.discriminant_type.align(),
+    )
+    .unwrap();
+    let alignment = match def.style {
+        TaggedUnionStyle::Traditional => def
+            .variants
+            .iter()
+            .map(|variant| variant.equivalent_structure.alignment)
+            .max()
+            .unwrap_or(1),
+        TaggedUnionStyle::Primitive => variant.equivalent_structure.alignment,
+    };
+    let discriminant = match def.style {
+        TaggedUnionStyle::Traditional => discriminant.pad_to_align(),
+        TaggedUnionStyle::Primitive => discriminant,
+    };
+    let payload = Layout::from_size_align(0, alignment).unwrap();
+    discriminant.extend(payload).unwrap().1
+}
+
+fn find_variant(def: &TaggedUnionDef, bits: u64) -> Option<&'static TaggedUnionVariant> {
+    let bits = def.discriminant_type.truncate_bits(bits);
+    def.variants.iter().find(|variant| {
+        def.discriminant_type
+            .truncate_bits(variant.discriminant.bits())
+            == bits
+    })
+}
+
+/// The type wrapped by a niche enum, with the empty variant allowed
+fn niche_inner(wrapped: &'static TypeInfo) -> &'static TypeInfo {
+    match *wrapped {
+        TypeInfo::NonZero(inner) => inner,
+        _ => wrapped,
+    }
+}
+
+fn write_int(out: &mut Vec<u8>, int_type: IntType, bits: u64) {
+    out.extend_from_slice(&bits.to_le_bytes()[..int_type.size.bytes()]);
+}
+
+unsafe fn encode_inner(
+    ptr: *const u8,
+    info: &TypeInfo,
+    endian: Option<Endian>,
+    out: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    match *info {
+        TypeInfo::Unit => {}
+        TypeInfo::Bool => out.push(ptr.read()),
+        TypeInfo::Integer(int_type) => {
+            let bits = convert_endian(int_type, int_type.read_bits(ptr), endian);
+            write_int(out, int_type, bits);
+        }
+        TypeInfo::Float { size } => {
+            let int_type = float_bits_type(size);
+            let bits = convert_endian(int_type, int_type.read_bits(ptr), endian);
+            write_int(out, int_type, bits);
+        }
+        TypeInfo::NonZero(inner @ TypeInfo::Integer(_)) => {
+            encode_inner(ptr, inner, endian, out)?;
+        }
+        TypeInfo::Structure(def) => {
+            let fields = encoded_fields(def).ok_or_else(|| EncodeError::unsupported(info))?;
+            for field in fields {
+                encode_inner(
+                    ptr.add(field.offset),
+                    field.value_type.type_ref(),
+                    field.endianness,
+                    out,
+                )?;
+            }
+        }
+        TypeInfo::CStyleEnum(def) => {
+            let bits = def.discriminant.read_bits(ptr);
+            if def.variant_for_discriminant(bits).is_none() {
+                return Err(EncodeError::InvalidValue {
+                    type_name: info.to_string(),
+                });
+            }
+            write_int(out, def.discriminant, bits);
+        }
+        TypeInfo::TaggedUnion(def) => {
+            let bits = def.discriminant_type.read_bits(ptr);
+            let variant = find_variant(def, bits).ok_or_else(|| EncodeError::InvalidValue {
+                type_name: info.to_string(),
+            })?;
+            let fields = encoded_fields(&variant.equivalent_structure)
+                .ok_or_else(|| EncodeError::unsupported(info))?;
+            write_int(out, def.discriminant_type, bits);
+            let payload = ptr.add(payload_offset(def, variant));
+            for field in fields {
+                encode_inner(
+                    payload.add(field.offset),
+                    field.value_type.type_ref(),
+                    field.endianness,
+                    out,
+                )?;
+            }
+        }
+        TypeInfo::Niche(def) => encode_inner(ptr, niche_inner(def.wrapped), endian, out)?,
+        _ => return Err(EncodeError::unsupported(info)),
+    }
+    Ok(())
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+impl Decoder<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        let remaining = self.bytes.len() - self.position;
+        if len > remaining {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: self.position,
+                needed: len,
+                remaining,
+            });
+        }
+        let result = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(result)
+    }
+    fn read_int(&mut self, int_type: IntType) -> Result<u64, DecodeError> {
+        let mut buffer = [0u8; 8];
+        let len = int_type.size.bytes();
+        buffer[..len].copy_from_slice(self.take(len)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+    fn invalid(&self, info: &TypeInfo, offset: usize) -> DecodeError {
+        DecodeError::InvalidValue {
+            type_name: info.to_string(),
+            offset,
+        }
+    }
+    unsafe fn decode_fields(
+        &mut self,
+        fields: &[FieldDef],
+        dst: *mut u8,
+    ) -> Result<(), DecodeError> {
+        for field in fields {
+            self.decode(
+                field.value_type.type_ref(),
+                field.endianness,
+                dst.add(field.offset),
+            )?;
+        }
+        Ok(())
+    }
+    unsafe fn decode(
+        &mut self,
+        info: &TypeInfo,
+        endian: Option<Endian>,
+        dst: *mut u8,
+    ) -> Result<(), DecodeError> {
+        let start = self.position;
+        match *info {
+            TypeInfo::Unit => {}
+            TypeInfo::Bool => match self.take(1)?[0] {
+                byte @ (0 | 1) => dst.write(byte),
+                _ => return Err(self.invalid(info, start)),
+            },
+            TypeInfo::Integer(int_type) => {
+                let bits = self.read_int(int_type)?;
+                int_type.write_bits(dst, convert_endian(int_type, bits, endian));
+            }
+            TypeInfo::Float { size } => {
+                let int_type = float_bits_type(size);
+                let bits = self.read_int(int_type)?;
+                int_type.write_bits(dst, convert_endian(int_type, bits, endian));
+            }
+            TypeInfo::NonZero(TypeInfo::Integer(int_type)) => {
+                let bits = self.read_int(*int_type)?;
+                if bits == 0 {
+                    return Err(self.invalid(info, start));
+                }
+                int_type.write_bits(dst, convert_endian(*int_type, bits, endian));
+            }
+            TypeInfo::Structure(def) => {
+                let fields = encoded_fields(def).ok_or_else(|| DecodeError::unsupported(info))?;
+                self.decode_fields(fields, dst)?;
+            }
+            TypeInfo::CStyleEnum(def) => {
+                let bits = self.read_int(def.discriminant)?;
+                if def.variant_for_discriminant(bits).is_none() {
+                    return Err(self.invalid(info, start));
+                }
+                def.discriminant.write_bits(dst, bits);
+            }
+            TypeInfo::TaggedUnion(def) => {
+                let bits = self.read_int(def.discriminant_type)?;
+                let variant = find_variant(def, bits).ok_or_else(|| self.invalid(info, start))?;
+                let fields = encoded_fields(&variant.equivalent_structure)
+                    .ok_or_else(|| DecodeError::unsupported(info))?;
+                def.discriminant_type.write_bits(dst, bits);
+                self.decode_fields(fields, dst.add(payload_offset(def, variant)))?;
+            }
+            TypeInfo::Niche(def) => self.decode(niche_inner(def.wrapped), endian, dst)?,
+            _ => return Err(DecodeError::unsupported(info)),
+        }
+        Ok(())
+    }
+}
+
+/// An error encoding a value
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncodeError {
+    /// The type can't be encoded
+    Unsupported {
+        /// The name of the type
+        type_name: String,
+    },
+    /// The memory doesn't contain a valid value of the type
+    /// (like an enum with an unknown discriminant)
+    InvalidValue {
+        /// The name of the type
+        type_name: String,
+    },
+    /// The size of a region overflows
+    Overflow {
+        /// The name of the element type
+        type_name: String,
+        /// The number of elements
+        count: usize,
+    },
+}
+impl EncodeError {
+    fn unsupported(info: &TypeInfo) -> EncodeError {
+        EncodeError::Unsupported {
+            type_name: info.to_string(),
+        }
+    }
+}
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            EncodeError::Unsupported { ref type_name } => {
+                write!(f, "Type can't be encoded: {}", type_name)
+            }
+            EncodeError::InvalidValue { ref type_name } => {
+                write!(f, "Invalid value of type {}", type_name)
+            }
+            EncodeError::Overflow {
+                ref type_name,
+                count,
+            } => write!(
+                f,
+                "Region of {} elements of {} is too large",
+                count, type_name
+            ),
+        }
+    }
+}
+impl std::error::Error for EncodeError {}
+
+/// An error decoding a value
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The type can't be decoded
+    Unsupported {
+        /// The name of the type
+        type_name: String,
+    },
+    /// The input ended before the value was complete
+    UnexpectedEnd {
+        /// The offset in the input where more bytes were needed
+        offset: usize,
+        /// The number of bytes needed
+        needed: usize,
+        /// The number of bytes remaining
+        remaining: usize,
+    },
+    /// The input contains an invalid value of the type
+    /// (like a `bool` that isn't zero or one)
+    InvalidValue {
+        /// The name of the type
+        type_name: String,
+        /// The offset in the input where the value starts
+        offset: usize,
+    },
+}
+impl DecodeError {
+    fn unsupported(info: &TypeInfo) -> DecodeError {
+        DecodeError::Unsupported {
+            type_name: info.to_string(),
+        }
+    }
+}
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::Unsupported { ref type_name } => {
+                write!(f, "Type can't be decoded: {}", type_name)
+            }
+            DecodeError::UnexpectedEnd {
+                offset,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "Unexpected end of input at offset {}: needed {} bytes, but only {} remain",
+                offset, needed, remaining
+            ),
+            DecodeError::InvalidValue {
+                ref type_name,
+                offset,
+            } => write!(
+                f,
+                "Invalid value of type {} at offset {}",
+                type_name, offset
+            ),
+        }
+    }
+}
+impl std::error::Error for DecodeError {}
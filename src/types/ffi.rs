@@ -0,0 +1,101 @@
+//! Whether reflected types can be passed across `extern "C"` boundaries
+//!
+//! rustc's `improper_ctypes` lints only warn about a type once it's used in the signature
+//! of a foreign function, which may be far from where the type is defined
+//! (or not at all, for signatures which are only known at runtime).
+//! Instead, the derive applies similar rules to the types of the fields it can see,
+//! and records the result in [StaticReflect::FFI_SAFETY](crate::StaticReflect::FFI_SAFETY).
+//! `#[reflect_func]` combines the safety of the arguments and return type of a function
+//! into [SignatureDef::ffi_safety](crate::funcs::SignatureDef::ffi_safety).
+//!
+//! ## Rules
+//! | Type | Safety |
+//! |------|--------|
+//! | `char` | Unsafe, since it has no C equivalent (use `u32` instead) |
+//! | `u128` and `i128` | Warning, since not every target agrees with C's `__int128` |
+//! | Tuples (except `()`) | Unsafe, since their layout is unspecified |
+//! | `str` and slices, or pointers to them | Unsafe, since slice pointers have no C equivalent |
+//! | Function pointers without `extern "C"` | Unsafe, since they use the Rust calling convention |
+//! | Arrays | The same as their elements |
+//! | Anything else | The `FFI_SAFETY` of the type |
+//!
+//! Types without a rule use their `StaticReflect::FFI_SAFETY`,
+//! which is safe unless the type is a derived structure or union with an unsafe field.
+//! That includes `bool`, which has the same layout as C's `_Bool`,
+//! and pointers to sized types (regardless of what they point to).
+//!
+//! Since the rules only look at the syntax of a type, they don't see through type aliases.
+//! The declared type of a field is checked, even if it's reflected as another type
+//! with `#[reflect(assume_repr = "...")]`.
+//!
+//! A structure or union is as unsafe as its most unsafe field,
+//! and a signature is as unsafe as its most unsafe argument (or return type).
+//! Ties are broken by the first one declared.
+use std::fmt::{self, Display, Formatter};
+
+/// Whether a type can be passed across an `extern "C"` boundary
+///
+/// See the [module documentation](self) for the rules.
+/// The reasons name the field (or argument) they come from.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum FfiSafety {
+    /// The type has a C equivalent
+    #[default]
+    Safe,
+    /// The type usually works, but its ABI isn't guaranteed on every target
+    Warning(&'static str),
+    /// The type has no C equivalent
+    Unsafe(&'static str),
+}
+impl FfiSafety {
+    /// Check if the type is safe, without any warnings
+    #[inline]
+    pub const fn is_safe(&self) -> bool {
+        matches!(*self, FfiSafety::Safe)
+    }
+    /// The reason the type isn't safe, if any
+    #[inline]
+    pub const fn reason(&self) -> Option<&'static str> {
+        match *self {
+            FfiSafety::Safe => None,
+            FfiSafety::Warning(reason) | FfiSafety::Unsafe(reason) => Some(reason),
+        }
+    }
+    /// The more severe of the two, preferring this one if they're equally severe
+    pub const fn or(self, other: FfiSafety) -> FfiSafety {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+    /// The most severe of the specified values, preferring the first one if there's a tie
+    ///
+    /// This is how the derive combines the fields of a type.
+    pub const fn all(values: &[FfiSafety]) -> FfiSafety {
+        let mut result = FfiSafety::Safe;
+        // NOTE: Can't use for-loop since iterators aren't const
+        let mut index = 0;
+        while index < values.len() {
+            result = result.or(values[index]);
+            index += 1;
+        }
+        result
+    }
+    const fn severity(&self) -> u8 {
+        match *self {
+            FfiSafety::Safe => 0,
+            FfiSafety::Warning(_) => 1,
+            FfiSafety::Unsafe(_) => 2,
+        }
+    }
+}
+impl Display for FfiSafety {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            FfiSafety::Safe => f.write_str("FFI-safe"),
+            FfiSafety::Warning(reason) => write!(f, "possibly not FFI-safe: {}", reason),
+            FfiSafety::Unsafe(reason) => write!(f, "not FFI-safe: {}", reason),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Compact keys identifying a type, which can be used in patterns
+//!
+//! A [TypeInfo] can't be used as a pattern, since it contains references
+//! to definitions (and `&'static &'static str` ids) that don't qualify for structural matching.
+//! Instead, a [TypeInfoKey] can be computed at compile time from any `TYPE_INFO`:
+//! ````
+//! # use static_reflect::types::key::TypeInfoKey;
+//! # use static_reflect::{StaticReflect, TypeInfo};
+//! const U32: TypeInfoKey = TypeInfo::key(&u32::TYPE_INFO);
+//! const F64: TypeInfoKey = TypeInfoKey::of::<f64>();
+//! fn describe(info: &TypeInfo) -> &'static str {
+//!     match info.key() {
+//!         U32 => "u32",
+//!         F64 => "f64",
+//!         _ => "something else",
+//!     }
+//! }
+//! assert_eq!(describe(&u32::TYPE_INFO), "u32");
+//! assert_eq!(describe(&i32::TYPE_INFO), "something else");
+//! ````
+//!
+//! ## Collisions
+//! A key is the kind of the type (its [TypeInfo] variant) along with a 64-bit FNV-1a hash
+//! of everything its `PartialEq` compares, recursively including the types of its fields.
+//! Equal types always have equal keys, and types of different kinds never share a key.
+//! Different types of the same kind may collide, although this is unlikely
+//! (about one in 2<sup>64</sup> for any given pair).
+//!
+//! When a collision would be a problem, confirm a matching key with [TypeInfo::key_eq],
+//! which falls back to a full structural comparison.
+//! In a `match`, this can be done in a guard: `U32 if info.key_eq(&u32::TYPE_INFO) => ...`.
+//!
+//! Unions with [lazy fields](super::UnionFields::Lazy) are the exception:
+//! their fields can't be looked up at compile time, so only the union's name,
+//! number of fields and layout are hashed.
+//! Two lazy unions which only differ in the types (or names) of their fields
+//! always collide, so keys of lazy unions should always be confirmed with [TypeInfo::key_eq].
+//!
+//! The hash is stable across builds and platforms, but only for identical definitions.
+//! It changes with the sizes and offsets of types, so it isn't portable
+//! between targets with different layouts.
+//!
+//! Computing the key at runtime walks the whole type tree (like comparing two types),
+//! so prefer computing it once in a `const` where possible.
+use super::{
+    CStyleEnumDef, DiscriminantValue, FieldDef, IntType, PrimitiveType, StructureDef,
+    TaggedUnionDef, TypeInfo, UntaggedUnionDef,
+};
+use crate::StaticReflect;
+
+/// A compact key identifying a type, which can be used in patterns and as a `HashMap` key
+///
+/// See the [module documentation](self) for the collision properties.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct TypeInfoKey {
+    kind: u8,
+    hash: u64,
+}
+impl TypeInfoKey {
+    /// The key of a reflected type
+    #[inline]
+    pub const fn of<T: StaticReflect>() -> TypeInfoKey {
+        T::TYPE_INFO.key()
+    }
+    /// The hash of the type
+    #[inline]
+    pub const fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+impl TypeInfo {
+    /// The key identifying this type, which can be used in patterns
+    ///
+    /// This is a `const fn`, so a key can be computed from any `TYPE_INFO` at compile time.
+    /// See [TypeInfoKey] for details.
+    pub const fn key(&self) -> TypeInfoKey {
+        TypeInfoKey {
+            kind: kind(self),
+            hash: hash_type(KeyHasher::new(), self).finish(),
+        }
+    }
+    /// Check if two types are exactly equal,
+    /// quickly rejecting types with different keys
+    ///
+    /// This is the same as `==`, but guards against collisions
+    /// after a match on a [TypeInfoKey].
+    pub fn key_eq(&self, other: &TypeInfo) -> bool {
+        std::ptr::eq(self, other) || (self.key() == other.key() && *self == *other)
+    }
+}
+
+/// The position of the type's variant in [TypeInfo]
+const fn kind(info: &TypeInfo) -> u8 {
+    match *info {
+        TypeInfo::Unit => 0,
+        #[cfg(feature = "never")]
+        TypeInfo::Never => 1,
+        TypeInfo::Bool => 2,
+        TypeInfo::Integer(_) => 3,
+        TypeInfo::Float { .. } => 4,
+        #[cfg(feature = "builtins")]
+        TypeInfo::Slice { .. } => 5,
+        #[cfg(feature = "builtins")]
+        TypeInfo::Str => 6,
+        #[cfg(feature = "builtins")]
+        TypeInfo::Optional(_) => 7,
+        TypeInfo::Pointer { .. } => 8,
+        TypeInfo::NonZero(_) => 9,
+        TypeInfo::Structure(_) => 10,
+        TypeInfo::UntaggedUnion(_) => 11,
+        TypeInfo::TaggedUnion(_) => 12,
+        TypeInfo::CStyleEnum(_) => 13,
+        TypeInfo::Niche(_) => 14,
+        TypeInfo::Extern { .. } => 15,
+        TypeInfo::TypeParameter { .. } => 16,
+        TypeInfo::Magic { .. } => 17,
+        // Added after the others, so the existing keys don't change
+        TypeInfo::Vector { .. } => 18,
+    }
+}
+
+/// A 64-bit FNV-1a hasher, usable in a const context
+#[derive(Copy, Clone)]
+struct KeyHasher(u64);
+impl KeyHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    const fn new() -> KeyHasher {
+        KeyHasher(KeyHasher::OFFSET_BASIS)
+    }
+    const fn byte(self, byte: u8) -> KeyHasher {
+        KeyHasher((self.0 ^ byte as u64).wrapping_mul(KeyHasher::PRIME))
+    }
+    const fn bytes(mut self, bytes: &[u8]) -> KeyHasher {
+        let mut index = 0;
+        while index < bytes.len() {
+            self = self.byte(bytes[index]);
+            index += 1;
+        }
+        self
+    }
+    /// Hash an integer (as 8 little-endian bytes, regardless of the platform)
+    const fn int(self, value: u64) -> KeyHasher {
+        self.bytes(&value.to_le_bytes())
+    }
+    const fn bool(self, value: bool) -> KeyHasher {
+        self.byte(value as u8)
+    }
+    /// Hash a string, prefixed by its length (so consecutive strings can't run together)
+    const fn str(self, value: &str) -> KeyHasher {
+        self.int(value.len() as u64).bytes(value.as_bytes())
+    }
+    const fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+const fn hash_type(hasher: KeyHasher, info: &TypeInfo) -> KeyHasher {
+    let hasher = hasher.byte(kind(info));
+    match *info {
+        TypeInfo::Unit | TypeInfo::Bool => hasher,
+        #[cfg(feature = "never")]
+        TypeInfo::Never => hasher,
+        #[cfg(feature = "builtins")]
+        TypeInfo::Str => hasher,
+        TypeInfo::Integer(int_type) => hash_int_type(hasher, int_type),
+        TypeInfo::Float { size } => hasher.byte(size as u8),
+        TypeInfo::Vector {
+            element,
+            lanes,
+            alignment,
+        } => hash_primitive(hasher, element)
+            .int(lanes as u64)
+            .int(alignment as u64),
+        #[cfg(feature = "builtins")]
+        TypeInfo::Slice { element_type } => hash_type(hasher, element_type),
+        #[cfg(feature = "builtins")]
+        TypeInfo::Optional(inner) => hash_type(hasher, inner),
+        TypeInfo::Pointer { mutable } => hasher.bool(mutable),
+        TypeInfo::NonZero(inner) => hash_type(hasher, inner),
+        TypeInfo::Structure(def) => hash_structure(hasher, def),
+        TypeInfo::UntaggedUnion(def) => hash_untagged_union(hasher, def),
+        TypeInfo::TaggedUnion(def) => hash_tagged_union(hasher, def),
+        TypeInfo::CStyleEnum(def) => hash_c_style_enum(hasher, def),
+        TypeInfo::Niche(def) => hash_type(hasher.str(def.name), def.wrapped)
+            .int(def.none_value)
+            .str(def.none_variant)
+            .str(def.some_variant),
+        TypeInfo::Extern { name } => hasher.str(name),
+        TypeInfo::TypeParameter { name, index } => hasher.str(name).int(index as u64),
+        TypeInfo::Magic { id, extra } => {
+            let hasher = hasher.str(id);
+            match extra {
+                Some(extra) => hash_type(hasher.bool(true), extra),
+                None => hasher.bool(false),
+            }
+        }
+    }
+}
+
+const fn hash_primitive(hasher: KeyHasher, primitive: PrimitiveType) -> KeyHasher {
+    match primitive {
+        PrimitiveType::Unit => hasher.byte(0),
+        #[cfg(feature = "never")]
+        PrimitiveType::Never => hasher.byte(1),
+        PrimitiveType::Bool => hasher.byte(2),
+        PrimitiveType::Pointer => hasher.byte(3),
+        PrimitiveType::Integer(int_type) => hash_int_type(hasher.byte(4), int_type),
+        PrimitiveType::Float { size } => hasher.byte(5).byte(size as u8),
+    }
+}
+
+const fn hash_int_type(hasher: KeyHasher, int_type: IntType) -> KeyHasher {
+    hasher.byte(int_type.size as u8).bool(int_type.signed)
+}
+
+const fn hash_option_str(hasher: KeyHasher, value: Option<&str>) -> KeyHasher {
+    match value {
+        Some(value) => hasher.bool(true).str(value),
+        None => hasher.bool(false),
+    }
+}
+
+const fn hash_discriminant(hasher: KeyHasher, value: DiscriminantValue) -> KeyHasher {
+    match value {
+        DiscriminantValue::Default { declaration_index } => {
+            hasher.byte(0).int(declaration_index as u64)
+        }
+        DiscriminantValue::ImplicitlyOffset { bits } => hasher.byte(1).int(bits),
+        DiscriminantValue::ExplicitInteger { bits } => hasher.byte(2).int(bits),
+    }
+}
+
+const fn hash_field(hasher: KeyHasher, field: &FieldDef) -> KeyHasher {
+    let mut hasher = hash_option_str(hasher, field.name);
+    hasher = hash_type(hasher, field.value_type.type_ref())
+        .int(field.offset as u64)
+        .int(field.index as u64);
+    hasher = match field.endianness {
+        Some(endian) => hasher.bool(true).byte(endian as u8),
+        None => hasher.bool(false),
+    };
+    hasher = match field.meta.pointer_discipline {
+        Some(discipline) => hasher.bool(true).byte(discipline as u8),
+        None => hasher.bool(false),
+    };
+    hasher = match field.meta.bitset {
+        Some(bitset) => {
+            hasher = hasher.bool(true).int(bitset.names.len() as u64);
+            let mut index = 0;
+            while index < bitset.names.len() {
+                hasher = hasher.str(bitset.names[index]);
+                index += 1;
+            }
+            hasher
+        }
+        None => hasher.bool(false),
+    };
+    hasher.bool(field.assumed).bool(field.opaque_array)
+}
+
+const fn hash_structure(hasher: KeyHasher, def: &StructureDef) -> KeyHasher {
+    let mut hasher = hasher.str(def.name).int(def.fields.len() as u64);
+    let mut index = 0;
+    while index < def.fields.len() {
+        hasher = hash_field(hasher, &def.fields[index]);
+        index += 1;
+    }
+    hasher
+        .int(def.size as u64)
+        .int(def.alignment as u64)
+        .bool(def.is_pod)
+        .bool(def.has_unsized_tail)
+        .bool(def.needs_drop)
+}
+
+const fn hash_untagged_union(hasher: KeyHasher, def: &UntaggedUnionDef) -> KeyHasher {
+    let mut hasher = hasher.str(def.name).int(def.fields.len() as u64);
+    // Lazily constructed fields can't be looked up at compile time, so only their count is hashed
+    // (see the "Collisions" section of the module docs)
+    if let Some(fields) = def.fields.as_slice() {
+        let mut index = 0;
+        while index < fields.len() {
+            let field = &fields[index];
+            hasher = hash_type(hasher.str(field.name), field.value_type.type_ref())
+                .int(field.index as u64);
+            index += 1;
+        }
+    }
+    hasher
+        .int(def.size as u64)
+        .int(def.alignment as u64)
+        .bool(def.needs_drop)
+}
+
+const fn hash_tagged_union(hasher: KeyHasher, def: &TaggedUnionDef) -> KeyHasher {
+    let mut hasher = hasher.str(def.name).byte(def.style as u8);
+    hasher = hash_int_type(hasher, def.discriminant_type).int(def.variants.len() as u64);
+    let mut index = 0;
+    while index < def.variants.len() {
+        let variant = &def.variants[index];
+        hasher = hash_structure(
+            hasher.int(variant.index as u64),
+            &variant.equivalent_structure,
+        );
+        hasher = hash_discriminant(hasher, variant.discriminant);
+        index += 1;
+    }
+    hasher
+        .int(def.size as u64)
+        .int(def.alignment as u64)
+        .bool(def.needs_drop)
+}
+
+const fn hash_c_style_enum(hasher: KeyHasher, def: &CStyleEnumDef) -> KeyHasher {
+    let mut hasher = hash_int_type(hasher.str(def.name), def.discriminant);
+    hasher = hasher.int(def.variants.len() as u64);
+    let mut index = 0;
+    while index < def.variants.len() {
+        let variant = &def.variants[index];
+        hasher = hasher.int(variant.index as u64).str(variant.name);
+        hasher = hash_discriminant(hasher, variant.discriminant);
+        index += 1;
+    }
+    hasher
+}
@@ -0,0 +1,131 @@
+//! Conversions between primitive types,
+//! useful for type-checking arithmetic (and selecting instructions).
+//!
+//! Only integers and floats are considered numeric.
+//! Booleans, pointers and the unit type never participate in arithmetic,
+//! and they can only be "converted" to themselves.
+use super::{FloatSize, IntSize, IntType, PrimitiveType};
+
+/// The number of significant bits in the mantissa of a float
+/// (including the implicit leading bit)
+#[inline]
+const fn mantissa_bits(size: FloatSize) -> usize {
+    match size {
+        FloatSize::Single => 24,
+        FloatSize::Double => 53,
+    }
+}
+
+/// The number of bits needed to represent the magnitude of any value of the integer
+#[inline]
+const fn magnitude_bits(tp: IntType) -> usize {
+    tp.size.bytes() * 8 - (tp.signed as usize)
+}
+
+/// Check if every value of the first integer type
+/// can be represented by the second.
+///
+/// This is the case if:
+/// 1. Both have the same signedness, and the target is at least as large
+/// 2. An unsigned integer is converted into a strictly larger signed integer
+///
+/// Signed integers can never be widened into unsigned ones.
+pub const fn can_widen(from: IntType, to: IntType) -> bool {
+    match (from.signed, to.signed) {
+        (false, false) | (true, true) => from.size.bytes() <= to.size.bytes(),
+        (false, true) => from.size.bytes() < to.size.bytes(),
+        (true, false) => false,
+    }
+}
+
+/// Check if every value of the first type
+/// can be exactly represented by the second.
+///
+/// - Every type can be converted into itself
+/// - Integers follow [can_widen]
+/// - Floats can be converted into floats at least as large
+/// - Integers can be converted into floats if the float's mantissa
+///   can hold the integer's magnitude (`i16 -> f32` but not `i32 -> f32`)
+///
+/// Floats can never be converted into integers,
+/// and non-numeric types can only be converted into themselves.
+pub fn can_convert_lossless(from: PrimitiveType, to: PrimitiveType) -> bool {
+    match (from, to) {
+        (PrimitiveType::Integer(from), PrimitiveType::Integer(to)) => can_widen(from, to),
+        (PrimitiveType::Float { size: from }, PrimitiveType::Float { size: to }) => {
+            from.bytes() <= to.bytes()
+        }
+        (PrimitiveType::Integer(from), PrimitiveType::Float { size }) => {
+            magnitude_bits(from) <= mantissa_bits(size)
+        }
+        (from, to) => from == to,
+    }
+}
+
+/// Apply C's "integer promotion" to the specified type.
+///
+/// Integers smaller than `int` are promoted to a (signed) `i32`,
+/// which can represent all their values.
+#[inline]
+const fn promote(tp: IntType) -> IntType {
+    if tp.size.bytes() < IntSize::Int.bytes() {
+        IntType::I32
+    } else {
+        tp
+    }
+}
+
+/// Determine the type that the operands of a binary arithmetic operation are converted to,
+/// following C's "usual arithmetic conversions" for fixed-width types.
+///
+/// Returns `None` if either type isn't numeric.
+///
+/// The rules are applied in order:
+/// 1. If either operand is a float, the result is the larger of the float types.
+///    Integers are converted to the float, even if it can't represent them exactly.
+/// 2. Otherwise both operands are integers, and both undergo integer promotion:
+///    anything smaller than 4 bytes becomes an `i32`.
+/// 3. If the promoted types have the same signedness, the result is the larger type.
+/// 4. If the unsigned type is at least as large as the signed type,
+///    the result is the unsigned type.
+/// 5. Otherwise, the signed type is strictly larger (and can represent every value
+///    of the unsigned type), so the result is the signed type.
+pub fn common_arithmetic_type(a: PrimitiveType, b: PrimitiveType) -> Option<PrimitiveType> {
+    Some(match (a, b) {
+        (PrimitiveType::Float { size: first }, PrimitiveType::Float { size: second }) => {
+            PrimitiveType::Float {
+                size: if first.bytes() >= second.bytes() {
+                    first
+                } else {
+                    second
+                },
+            }
+        }
+        (PrimitiveType::Float { size }, PrimitiveType::Integer(_))
+        | (PrimitiveType::Integer(_), PrimitiveType::Float { size }) => {
+            PrimitiveType::Float { size }
+        }
+        (PrimitiveType::Integer(first), PrimitiveType::Integer(second)) => {
+            let (first, second) = (promote(first), promote(second));
+            PrimitiveType::Integer(if first.signed == second.signed {
+                if first.size.bytes() >= second.size.bytes() {
+                    first
+                } else {
+                    second
+                }
+            } else {
+                let (signed, unsigned) = if first.signed {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+                if unsigned.size.bytes() >= signed.size.bytes() {
+                    unsigned
+                } else {
+                    signed
+                }
+            })
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,173 @@
+//! Iterating over the leaf fields of a type, flattening nested structures
+//!
+//! A leaf is any field whose type isn't a structure (or an untagged union, see [LeafOptions]),
+//! like a primitive, pointer, enum, slice or string.
+//! Each leaf is yielded with its full [FieldPath] and its absolute offset from the root,
+//! which is what's needed to map a reflected type onto flat columns.
+//!
+//! Pointers are never followed, so the iteration always terminates
+//! (a structure can't contain itself by value).
+//! The unsized tail of a structure isn't part of its value, so it's skipped.
+//! An `opaque_array` field is a single leaf, for its first element.
+use super::path::{FieldPath, PathSegment};
+use super::{FieldDef, TypeInfo, UnionFieldIter, UnionFields};
+use std::fmt::{self, Display, Formatter};
+
+/// Options controlling which fields are considered leaves
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LeafOptions {
+    /// Yield the leaves of every member of an untagged union (which all start at its offset)
+    ///
+    /// Otherwise, unions are skipped entirely,
+    /// since only one of their members is meaningful at a time.
+    pub expand_unions: bool,
+}
+
+/// A leaf field, with its position relative to the root type
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LeafField {
+    /// The path to the field from the root type
+    ///
+    /// Compiling this path against the root ([FieldPath::compile]) gives the same offset.
+    pub path: FieldPath,
+    /// The absolute offset of the field from the start of the root type
+    pub offset: usize,
+    /// The type of the field
+    pub info: &'static TypeInfo,
+}
+impl Display for LeafField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} @ {}: {}", self.path, self.offset, self.info)
+    }
+}
+
+impl TypeInfo {
+    /// Iterate over the leaf fields of this type, recursing through nested structures
+    ///
+    /// Untagged unions are skipped (see [LeafOptions::expand_unions]).
+    /// A type without any fields has no leaves.
+    ///
+    /// ## Example
+    #[cfg_attr(feature = "derive", doc = "````")]
+    #[cfg_attr(not(feature = "derive"), doc = "````ignore")]
+    /// # use static_reflect::StaticReflect;
+    /// #[derive(StaticReflect)]
+    /// #[repr(C)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    /// #[derive(StaticReflect)]
+    /// #[repr(C)]
+    /// struct Player {
+    ///     id: u32,
+    ///     position: Position,
+    /// }
+    /// let leaves = Player::TYPE_INFO
+    ///     .leaf_fields()
+    ///     .map(|leaf| leaf.to_string())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(leaves, ["id @ 0: u32", "position.x @ 4: f32", "position.y @ 8: f32"]);
+    /// ````
+    #[inline]
+    pub fn leaf_fields(&self) -> LeafFieldIter {
+        self.leaf_fields_with(LeafOptions::default())
+    }
+    /// Iterate over the leaf fields of this type, with the specified options
+    pub fn leaf_fields_with(&self, options: LeafOptions) -> LeafFieldIter {
+        let mut iter = LeafFieldIter {
+            options,
+            stack: Vec::new(),
+            path: Vec::new(),
+        };
+        iter.push(self, 0);
+        iter
+    }
+}
+
+/// The remaining fields of a type being walked
+#[derive(Clone, Debug)]
+enum Fields {
+    Structure(std::slice::Iter<'static, FieldDef>),
+    Union(UnionFieldIter),
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    fields: Fields,
+    /// The offset of the type from the root
+    base: usize,
+}
+
+/// An iterator over the leaf fields of a type (see [TypeInfo::leaf_fields])
+///
+/// The leaves are yielded in declaration order, depth first.
+#[derive(Clone, Debug)]
+pub struct LeafFieldIter {
+    options: LeafOptions,
+    stack: Vec<Frame>,
+    /// The path to the type on top of the stack
+    path: Vec<PathSegment>,
+}
+impl LeafFieldIter {
+    /// Start walking the fields of the specified type,
+    /// returning false if it's a leaf
+    fn push(&mut self, info: &TypeInfo, base: usize) -> bool {
+        let fields = match *info {
+            TypeInfo::Structure(def) => Fields::Structure(if def.has_unsized_tail {
+                def.fields[..def.fields.len().saturating_sub(1)].iter()
+            } else {
+                def.fields.iter()
+            }),
+            TypeInfo::UntaggedUnion(def) => Fields::Union(if self.options.expand_unions {
+                def.fields.iter()
+            } else {
+                UnionFields::Eager(&[]).iter()
+            }),
+            _ => return false,
+        };
+        self.stack.push(Frame { fields, base });
+        true
+    }
+}
+impl Iterator for LeafFieldIter {
+    type Item = LeafField;
+    fn next(&mut self) -> Option<LeafField> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let next = match frame.fields {
+                Fields::Structure(ref mut fields) => fields.next().map(|field| {
+                    let segment = match field.name {
+                        Some(name) => PathSegment::Name(name.into()),
+                        None => PathSegment::Index(field.index),
+                    };
+                    (segment, field.offset, field.value_type.type_ref())
+                }),
+                Fields::Union(ref mut fields) => fields.next().map(|field| {
+                    (
+                        PathSegment::Name(field.name.into()),
+                        field.offset(),
+                        field.value_type.type_ref(),
+                    )
+                }),
+            };
+            let base = frame.base;
+            let (segment, offset, info) = match next {
+                Some(next) => next,
+                None => {
+                    // Finished this type, so continue with its parent
+                    self.stack.pop();
+                    self.path.pop();
+                    continue;
+                }
+            };
+            let offset = base + offset;
+            self.path.push(segment);
+            if !self.push(info, offset) {
+                let path = FieldPath::new(self.path.clone());
+                self.path.pop();
+                return Some(LeafField { path, offset, info });
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! The layout analysis behind `#[reflect(lint)]`
+//!
+//! These are all `const fn`s, so the derive can evaluate them at compile time
+//! and turn the results into warnings.
+//!
+//! ## Lints
+//! - Excessive padding: a structure has more padding than `lint(max_padding = N)` bytes
+//!   (by default [DEFAULT_MAX_PADDING]). See [padding_bytes].
+//! - Field ordering: sorting the fields of a structure by decreasing alignment
+//!   would make it smaller. See [reordered_size].
+//! - Discriminant width: an enum with an explicit integer `#[repr]`
+//!   uses a wider discriminant than its values need. See [minimal_discriminant_size].
+//! - Improper C types: a structure or union isn't FFI-safe,
+//!   according to its [StaticReflect::FFI_SAFETY](crate::StaticReflect::FFI_SAFETY).
+//!   Warnings (like a `u128` field) are reported too.
+//!
+//! Structures containing fields of unknown size (extern or magic types),
+//! opaque arrays or an unsized tail are never linted for their layout.
+use super::{CStyleEnumDef, IntSize, StructureDef, TypeInfo};
+
+/// The default value of `lint(max_padding = N)`
+pub const DEFAULT_MAX_PADDING: usize = 4;
+
+/// The total size of a structure's fields (excluding padding)
+///
+/// Returns `None` if the size of some field isn't really known.
+const fn field_sizes(def: &StructureDef) -> Option<usize> {
+    if def.has_unsized_tail {
+        return None;
+    }
+    let mut total = 0;
+    // NOTE: Can't use for-loop since iterators aren't const
+    let mut index = 0;
+    while index < def.fields.len() {
+        let field = &def.fields[index];
+        let value_type = field.value_type.type_ref();
+        if field.opaque_array
+            || matches!(
+                value_type,
+                TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. }
+            )
+        {
+            return None;
+        }
+        total += value_type.size();
+        index += 1;
+    }
+    Some(total)
+}
+
+/// The number of padding bytes in a structure (including trailing padding)
+///
+/// Returns `None` if the type isn't a structure that can be analyzed.
+pub const fn padding_bytes(info: &TypeInfo) -> Option<usize> {
+    match *info {
+        TypeInfo::Structure(def) => match field_sizes(def) {
+            Some(total) => Some(def.size - total),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+/// The size a structure would have if its fields were sorted by decreasing alignment
+///
+/// This is the smallest possible size of a `#[repr(C)]` structure with the same fields,
+/// since no padding is needed between them (only at the end).
+/// Returns `None` if the type isn't a structure that can be analyzed.
+pub const fn reordered_size(info: &TypeInfo) -> Option<usize> {
+    match *info {
+        TypeInfo::Structure(def) => match field_sizes(def) {
+            Some(total) => {
+                let rem = total % def.alignment;
+                Some(total + (if rem == 0 { 0 } else { def.alignment - rem }))
+            }
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+/// The smallest discriminant size which can hold all the values of an enum
+///
+/// The signedness of the discriminant is kept the same.
+pub const fn minimal_discriminant_size(def: &CStyleEnumDef) -> IntSize {
+    let (mut min, mut max) = (0i128, 0i128);
+    let mut index = 0;
+    while index < def.variants.len() {
+        let value = def
+            .discriminant
+            .extend_bits(def.variants[index].discriminant.bits());
+        if value < min {
+            min = value;
+        }
+        if value > max {
+            max = value;
+        }
+        index += 1;
+    }
+    let sizes = [IntSize::Byte, IntSize::Short, IntSize::Int];
+    let mut index = 0;
+    while index < sizes.len() {
+        let bits = sizes[index].bytes() as u32 * 8;
+        let fits = if def.discriminant.signed {
+            min >= -(1i128 << (bits - 1)) && max < (1i128 << (bits - 1))
+        } else {
+            max < (1i128 << bits)
+        };
+        if fits {
+            return sizes[index];
+        }
+        index += 1;
+    }
+    IntSize::Long
+}
+
+/// Check if a structure has more than `max_padding` bytes of padding
+pub const fn has_excessive_padding(info: &TypeInfo, max_padding: usize) -> bool {
+    matches!(padding_bytes(info), Some(padding) if padding > max_padding)
+}
+
+/// Check if sorting the fields of a structure by decreasing alignment would make it smaller
+pub const fn could_reorder_fields(info: &TypeInfo) -> bool {
+    match (*info, reordered_size(info)) {
+        (TypeInfo::Structure(def), Some(size)) => size < def.size,
+        _ => false,
+    }
+}
+
+/// Check if an enum uses a wider discriminant than its values need
+pub const fn has_wide_discriminant(info: &TypeInfo) -> bool {
+    match *info {
+        TypeInfo::CStyleEnum(def) => {
+            minimal_discriminant_size(def).bytes() < def.discriminant.size.bytes()
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,338 @@
+//! Migrating values between two versions of a structure
+//!
+//! A [MigrationPlan] describes where each field of the new version comes from,
+//! so data saved with an old layout can be converted to the current one.
+//! Fields are matched up by their names (or their indexes, for tuple structs),
+//! along with any renames given in the [MigrateOptions].
+//! Unlike [diff_structures](super::diff::diff_structures), fields are never matched by their offsets,
+//! since a field that was only moved is indistinguishable from one that was replaced.
+//!
+//! ## Actions
+//! Each field of the new version is either:
+//! - Copied from the old field, if they have the same layout ([TypeInfo::layout_eq])
+//! - Widened from the old field, if it's an integer that now has more bits.
+//!   Signed integers are sign-extended, and unsigned integers may widen into signed ones.
+//! - Left untouched, if the old version doesn't have it (so it should be initialized beforehand)
+//! - Incompatible with the old field, which is also left untouched
+//!
+//! Old fields without a counterpart in the new version are dropped.
+//!
+//! ## Example
+#![cfg_attr(feature = "derive", doc = "````")]
+#![cfg_attr(not(feature = "derive"), doc = "````ignore")]
+//! # use static_reflect::types::migrate::{plan, MigrateOptions};
+//! # use static_reflect::{StaticReflect, TypeInfo};
+//! mod v1 {
+//! #   use static_reflect::StaticReflect;
+//!     #[derive(StaticReflect)]
+//!     #[repr(C)]
+//!     pub struct Record {
+//!         pub id: u16,
+//!         pub size: u16,
+//!     }
+//! }
+//! #[derive(StaticReflect)]
+//! #[repr(C)]
+//! struct Record {
+//!     id: u64,
+//!     size: u16,
+//! }
+//! let (TypeInfo::Structure(old), TypeInfo::Structure(new)) =
+//!     (v1::Record::TYPE_INFO, Record::TYPE_INFO)
+//! else {
+//!     unreachable!()
+//! };
+//! // The `id` is widened to a `u64`, while the `size` is copied as-is
+//! let plan = plan(old, new, MigrateOptions::default());
+//! assert!(plan.is_complete());
+//! let old_record = v1::Record { id: 7, size: 12 };
+//! let mut new_record = Record { id: 0, size: 0 };
+//! unsafe {
+//!     plan.apply(
+//!         &old_record as *const v1::Record as *const u8,
+//!         &mut new_record as *mut Record as *mut u8,
+//!     );
+//! }
+//! assert_eq!((new_record.id, new_record.size), (7, 12));
+//! ````
+use std::fmt::{self, Display, Formatter};
+
+use super::diff::FieldSummary;
+use super::{Endian, FieldDef, IntType, StructureDef, TypeInfo};
+
+/// Options for matching up the fields of two versions of a structure
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MigrateOptions<'a> {
+    /// Fields that were renamed, as pairs of their old and new names
+    pub renames: &'a [(&'a str, &'a str)],
+}
+impl<'a> MigrateOptions<'a> {
+    /// The name of the old field corresponding to the specified new field
+    fn old_name(&self, new_name: &'a str) -> &'a str {
+        self.renames
+            .iter()
+            .find(|&&(_, new)| new == new_name)
+            .map_or(new_name, |&(old, _)| old)
+    }
+}
+
+/// The reason a field can't be migrated from its old version
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IncompatibleReason {
+    /// The type of the field changed, to one with a different layout
+    Retyped {
+        /// The old type of the field
+        old: &'static TypeInfo,
+        /// The new type of the field
+        new: &'static TypeInfo,
+    },
+    /// The field is an integer, but not every value of the old type fits in the new one
+    Lossy {
+        /// The old type of the integer
+        from: IntType,
+        /// The new type of the integer
+        to: IntType,
+    },
+    /// The byte order of the field changed,
+    /// or it's an integer that would need to be widened in a non-native byte order
+    ByteOrder,
+    /// The full layout of the field isn't known,
+    /// because it's opaque, an opaque array or an unsized tail
+    Opaque,
+}
+impl Display for IncompatibleReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            IncompatibleReason::Retyped { old, new } => {
+                write!(f, "Changed type from {} to {}", old, new)
+            }
+            IncompatibleReason::Lossy { from, to } => {
+                write!(f, "Not every {} fits in {}", from, to)
+            }
+            IncompatibleReason::ByteOrder => f.write_str("Incompatible byte order"),
+            IncompatibleReason::Opaque => f.write_str("Unknown layout"),
+        }
+    }
+}
+
+/// How to fill in a single field of the new version
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FieldAction {
+    /// Copy the bytes of the old field, which has the same layout
+    CopySame {
+        /// The offset of the field in the old version
+        old_offset: usize,
+        /// The offset of the field in the new version
+        new_offset: usize,
+        /// The number of bytes to copy
+        size: usize,
+    },
+    /// Convert the old integer to a wider one
+    Widen {
+        /// The offset of the field in the old version
+        old_offset: usize,
+        /// The offset of the field in the new version
+        new_offset: usize,
+        /// The old type of the integer
+        from: IntType,
+        /// The new type of the integer
+        to: IntType,
+    },
+    /// The old version doesn't have the field, so it keeps its existing value
+    Default,
+    /// The old field can't be converted, so the field keeps its existing value
+    Incompatible(IncompatibleReason),
+}
+
+/// The action for a single field of the new version
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FieldMigration {
+    /// The field in the new version
+    pub field: FieldSummary,
+    /// The matching field in the old version, if any
+    pub source: Option<FieldSummary>,
+    /// How to fill in the field
+    pub action: FieldAction,
+}
+impl Display for FieldMigration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.field)?;
+        match (self.action, self.source) {
+            (FieldAction::CopySame { .. }, Some(source)) => write!(f, "copy from {}", source),
+            (FieldAction::Widen { from, to, .. }, Some(source)) => {
+                write!(f, "widen from {} ({} -> {})", source, from, to)
+            }
+            (FieldAction::Incompatible(reason), Some(source)) => {
+                write!(f, "incompatible with {} ({})", source, reason)
+            }
+            _ => f.write_str("default"),
+        }
+    }
+}
+
+/// A plan for migrating a value from an old version of a structure to a new one
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MigrationPlan {
+    /// The name of the old version
+    pub old_name: &'static str,
+    /// The name of the new version
+    pub new_name: &'static str,
+    /// The action for each field of the new version, in the order they're declared
+    pub fields: Vec<FieldMigration>,
+    /// The fields of the old version which aren't in the new one
+    pub dropped: Vec<FieldSummary>,
+}
+impl MigrationPlan {
+    /// If every field present in both versions can be migrated
+    ///
+    /// New fields (with [FieldAction::Default]) don't prevent this.
+    pub fn is_complete(&self) -> bool {
+        self.incompatible().next().is_none()
+    }
+    /// The fields of the new version which can't be migrated
+    pub fn incompatible(&self) -> impl Iterator<Item = &FieldMigration> + '_ {
+        self.fields
+            .iter()
+            .filter(|field| matches!(field.action, FieldAction::Incompatible(_)))
+    }
+    /// Migrate a value of the old version to the new one
+    ///
+    /// Fields without a source, and those which are incompatible, are left untouched.
+    /// The destination should be initialized beforehand, so that they have a default value.
+    ///
+    /// ## Safety
+    /// The source must be a valid value of the old version,
+    /// and the destination must be valid for writes of the new version.
+    /// The two values must not overlap.
+    /// Neither pointer needs to be aligned.
+    pub unsafe fn apply(&self, src: *const u8, dst: *mut u8) {
+        for field in &self.fields {
+            match field.action {
+                FieldAction::CopySame {
+                    old_offset,
+                    new_offset,
+                    size,
+                } => std::ptr::copy_nonoverlapping(src.add(old_offset), dst.add(new_offset), size),
+                FieldAction::Widen {
+                    old_offset,
+                    new_offset,
+                    from,
+                    to,
+                } => {
+                    let value = from.extend_bits(from.read_bits(src.add(old_offset)));
+                    to.write_bits(dst.add(new_offset), value as u64);
+                }
+                FieldAction::Default | FieldAction::Incompatible(_) => {}
+            }
+        }
+    }
+}
+impl Display for MigrationPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.old_name, self.new_name)?;
+        for field in &self.fields {
+            write!(f, "\n  {}", field)?;
+        }
+        for field in &self.dropped {
+            write!(f, "\n  {}: dropped", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plan the migration of values from an old version of a structure to a new one
+pub fn plan(old: &StructureDef, new: &StructureDef, options: MigrateOptions) -> MigrationPlan {
+    let mut used = vec![false; old.fields.len()];
+    let fields = new
+        .fields
+        .iter()
+        .map(|field| {
+            let position = old.fields.iter().position(|old_field| match field.name {
+                Some(name) => old_field.name == Some(options.old_name(name)),
+                None => old_field.name.is_none() && old_field.index == field.index,
+            });
+            let (source, action) = match position {
+                Some(position) => {
+                    used[position] = true;
+                    let old_field = &old.fields[position];
+                    let action = match field_action(old, old_field, new, field) {
+                        Ok(action) => action,
+                        Err(reason) => FieldAction::Incompatible(reason),
+                    };
+                    (Some(FieldSummary::from_field(old_field)), action)
+                }
+                None => (None, FieldAction::Default),
+            };
+            FieldMigration {
+                field: FieldSummary::from_field(field),
+                source,
+                action,
+            }
+        })
+        .collect();
+    let dropped = old
+        .fields
+        .iter()
+        .zip(used)
+        .filter(|&(_, used)| !used)
+        .map(|(field, _)| FieldSummary::from_field(field))
+        .collect();
+    MigrationPlan {
+        old_name: old.name,
+        new_name: new.name,
+        fields,
+        dropped,
+    }
+}
+
+fn is_opaque(def: &StructureDef, field: &FieldDef) -> bool {
+    field.opaque_array || (def.has_unsized_tail && field.index + 1 == def.fields.len())
+}
+
+fn field_action(
+    old_def: &StructureDef,
+    old: &FieldDef,
+    new_def: &StructureDef,
+    new: &FieldDef,
+) -> Result<FieldAction, IncompatibleReason> {
+    if is_opaque(old_def, old) || is_opaque(new_def, new) {
+        return Err(IncompatibleReason::Opaque);
+    }
+    let (old_type, new_type) = (old.value_type.type_ref(), new.value_type.type_ref());
+    let byte_order = |field: &FieldDef| field.endianness.unwrap_or(Endian::NATIVE);
+    if old_type.layout_eq(new_type) {
+        if byte_order(old) != byte_order(new) {
+            return Err(IncompatibleReason::ByteOrder);
+        }
+        let size = old_type
+            .try_size()
+            .map_err(|_| IncompatibleReason::Opaque)?;
+        return Ok(FieldAction::CopySame {
+            old_offset: old.offset,
+            new_offset: new.offset,
+            size,
+        });
+    }
+    match (*old_type, *new_type) {
+        (TypeInfo::Integer(from), TypeInfo::Integer(to)) => {
+            if from.size.bytes() >= to.size.bytes() || (from.signed && !to.signed) {
+                Err(IncompatibleReason::Lossy { from, to })
+            } else if !byte_order(old).is_native() || !byte_order(new).is_native() {
+                Err(IncompatibleReason::ByteOrder)
+            } else {
+                Ok(FieldAction::Widen {
+                    old_offset: old.offset,
+                    new_offset: new.offset,
+                    from,
+                    to,
+                })
+            }
+        }
+        _ => Err(IncompatibleReason::Retyped {
+            old: old_type,
+            new: new_type,
+        }),
+    }
+}
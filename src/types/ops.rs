@@ -0,0 +1,540 @@
+//! Operations on raw memory, planned ahead of time from a [TypeInfo]
+//!
+//! Skipping padding isn't free: executing a [CopyPlan] is an order of magnitude
+//! slower than a `memcpy` of the whole value, so only use one when the padding matters.
+
+use super::value::ReflectValue;
+use super::{
+    DynamicSize, OpaqueTypeError, PrimitiveType, RegionError, StructureDef, TypeInfo, TypedRegion,
+};
+use crate::options::{ReflectOptions, UnionInspection};
+use std::alloc::Layout;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// A plan for copying the meaningful bytes of a type, skipping padding
+///
+/// The plan is computed once by [CopyPlan::analyze],
+/// and can then be executed repeatedly.
+/// It consists of sorted, non-overlapping byte ranges (runs),
+/// where adjacent runs have already been merged.
+///
+/// Unions contribute their full size, since any of their bytes may be meaningful.
+/// Nested structures are analyzed recursively,
+/// so their internal (and trailing) padding is skipped as well.
+///
+/// The plan relies on the reflected type of each field.
+/// In particular, an `opaque_array` field only covers its first element.
+/// Extern types are copied entirely, if their layout has been registered
+/// (see [crate::registry]).
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CopyPlan {
+    runs: Vec<Range<usize>>,
+    size: usize,
+}
+impl CopyPlan {
+    /// Analyze the specified type, computing the runs of bytes to copy
+    ///
+    /// Fails if the type (or one of its fields) is opaque,
+    /// so that its layout is unknown.
+    ///
+    /// This uses the [Default] options (see [CopyPlan::analyze_with]).
+    #[inline]
+    pub fn analyze(info: &TypeInfo) -> Result<CopyPlan, UnsupportedType> {
+        CopyPlan::analyze_with(info, &ReflectOptions::default())
+    }
+    /// Analyze the specified type using the specified options
+    ///
+    /// - Structures at [ReflectOptions::max_depth] (or deeper)
+    ///   are copied entirely, including their padding.
+    /// - Opaque types are handled according to the [ReflectOptions::opaque_size_policy].
+    ///   With [OpaqueSizePolicy::Sentinel](crate::options::OpaqueSizePolicy::Sentinel), they aren't copied at all
+    ///   (and the size of an opaque root is [TypeInfo::OPAQUE_SIZE]).
+    /// - Unions are copied entirely with [UnionInspection::Raw], and not at all with [UnionInspection::Skip].
+    pub fn analyze_with(
+        info: &TypeInfo,
+        options: &ReflectOptions,
+    ) -> Result<CopyPlan, UnsupportedType> {
+        let size = options.size_of(info)?;
+        let mut runs = Vec::new();
+        collect_runs(info, 0, 0, options, &mut runs)?;
+        runs.sort_by_key(|run| run.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(runs.len());
+        for run in runs {
+            match merged.last_mut() {
+                Some(last) if run.start <= last.end => {
+                    last.end = last.end.max(run.end);
+                }
+                _ => merged.push(run),
+            }
+        }
+        Ok(CopyPlan { runs: merged, size })
+    }
+    /// The runs of bytes to copy, relative to the start of the value
+    #[inline]
+    pub fn runs(&self) -> &[Range<usize>] {
+        &self.runs
+    }
+    /// The size of the type, including padding
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    /// The total number of bytes copied (excluding padding)
+    pub fn copied_bytes(&self) -> usize {
+        self.runs.iter().map(|run| run.len()).sum()
+    }
+    /// Execute the plan, copying every run from the source to the destination
+    ///
+    /// The padding bytes of the destination are left untouched.
+    ///
+    /// ## Safety
+    /// Both pointers must be valid for the type's size (see [CopyPlan::size]),
+    /// and the two values must not overlap.
+    /// Neither pointer needs to be aligned.
+    pub unsafe fn execute(&self, src: *const u8, dst: *mut u8) {
+        for run in &self.runs {
+            std::ptr::copy_nonoverlapping(src.add(run.start), dst.add(run.start), run.len());
+        }
+    }
+}
+
+fn collect_runs(
+    info: &TypeInfo,
+    base: usize,
+    depth: usize,
+    options: &ReflectOptions,
+    runs: &mut Vec<Range<usize>>,
+) -> Result<(), UnsupportedType> {
+    match *info {
+        TypeInfo::Structure(def) if depth < options.max_depth => {
+            // Only the header of a structure with an unsized tail is copied
+            let fields = if def.has_unsized_tail {
+                &def.fields[..def.fields.len() - 1]
+            } else {
+                def.fields
+            };
+            for field in fields {
+                collect_runs(
+                    field.value_type.type_ref(),
+                    base + field.offset,
+                    depth + 1,
+                    options,
+                    runs,
+                )?;
+            }
+        }
+        TypeInfo::NonZero(inner) => collect_runs(inner, base, depth, options, runs)?,
+        TypeInfo::Niche(def) => collect_runs(def.wrapped, base, depth, options, runs)?,
+        ref other if other.optional_inner_type().is_some() => {
+            return Err(UnsupportedType::new(info))
+        }
+        TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } => {
+            match info.resolved_size() {
+                Some(0) => {}
+                Some(size) => runs.push(base..base + size),
+                // Unless the policy fails, the opaque type isn't copied
+                None => {
+                    options.size_of(info)?;
+                }
+            }
+        }
+        TypeInfo::UntaggedUnion(_) if options.union_inspection == UnionInspection::Skip => {}
+        _ => {
+            // Unions, tagged unions and primitives are copied entirely
+            let size = info.size();
+            if size > 0 {
+                runs.push(base..base + size);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Swap two values of the specified type
+///
+/// Both pointers must be aligned for the type, and the values must not overlap
+/// (unless they're exactly the same, which does nothing).
+///
+/// ## Safety
+/// Both pointers must be valid for reads and writes of the type's size.
+pub unsafe fn swap_values(a: *mut u8, b: *mut u8, info: &TypeInfo) -> Result<(), ValueOpError> {
+    let size = check_pointers(a, b, info)?;
+    if a != b {
+        std::ptr::swap_nonoverlapping(a, b, size);
+    }
+    Ok(())
+}
+
+/// Copy a value of the specified type from the source to the destination
+///
+/// Types that need to be dropped (see [TypeInfo::needs_drop]) are rejected,
+/// since the copy would be a second owner of their resources.
+/// Structures known to be `Pod` are copied entirely,
+/// while other types are copied using a [CopyPlan] (leaving the destination's padding untouched).
+///
+/// Both pointers must be aligned for the type, and the values must not overlap
+/// (unless they're exactly the same, which does nothing).
+///
+/// ## Safety
+/// The source must be valid for reads of the type's size,
+/// and the destination must be valid for writes.
+pub unsafe fn copy_value(
+    src: *const u8,
+    dst: *mut u8,
+    info: &TypeInfo,
+) -> Result<(), ValueOpError> {
+    if info.needs_drop() {
+        return Err(ValueOpError::NeedsDrop {
+            type_name: info.to_string(),
+        });
+    }
+    let size = check_pointers(src, dst, info)?;
+    if std::ptr::eq(src, dst) {
+        return Ok(());
+    }
+    match *info {
+        TypeInfo::Structure(def) if def.is_pod => std::ptr::copy_nonoverlapping(src, dst, size),
+        _ => CopyPlan::analyze(info)?.execute(src, dst),
+    }
+    Ok(())
+}
+
+/// Copy every element of a region from the source to the destination
+///
+/// This is the same as calling [copy_value] on each element,
+/// except that the checks are only done once for the whole region.
+/// Structures known to be `Pod` are copied with a single `memcpy`.
+///
+/// Both pointers must be aligned for the element type, and the regions must not overlap
+/// (unless they're exactly the same, which does nothing).
+///
+/// ## Safety
+/// The source must be valid for reads of the region's size ([TypedRegion::byte_len]),
+/// and the destination must be valid for writes.
+pub unsafe fn copy_region(
+    src: *const u8,
+    dst: *mut u8,
+    region: &TypedRegion,
+) -> Result<(), ValueOpError> {
+    let info = region.element;
+    if info.needs_drop() {
+        return Err(ValueOpError::NeedsDrop {
+            type_name: info.to_string(),
+        });
+    }
+    let layout = region.layout()?;
+    check_layout(src, dst, layout)?;
+    if std::ptr::eq(src, dst) {
+        return Ok(());
+    }
+    match *info {
+        TypeInfo::Structure(def) if def.is_pod => {
+            std::ptr::copy_nonoverlapping(src, dst, layout.size())
+        }
+        _ => {
+            let plan = CopyPlan::analyze(info)?;
+            let stride = region.stride()?;
+            for index in 0..region.count {
+                let offset = index * stride;
+                plan.execute(src.add(offset), dst.add(offset));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that both pointers are aligned and don't partially overlap,
+/// returning the size of the type
+fn check_pointers(a: *const u8, b: *const u8, info: &TypeInfo) -> Result<usize, ValueOpError> {
+    let layout = info.try_layout().map_err(UnsupportedType::from)?;
+    check_layout(a, b, layout)
+}
+
+/// Check that both pointers are aligned for the layout and don't partially overlap,
+/// returning its size
+fn check_layout(a: *const u8, b: *const u8, layout: Layout) -> Result<usize, ValueOpError> {
+    for ptr in [a, b] {
+        let address = ptr as usize;
+        if !address.is_multiple_of(layout.align()) {
+            return Err(ValueOpError::Misaligned {
+                required: layout.align(),
+                actual: 1 << address.trailing_zeros(),
+            });
+        }
+    }
+    let (a, b) = (a as usize, b as usize);
+    if a != b && a < b + layout.size() && b < a + layout.size() {
+        return Err(ValueOpError::Overlapping {
+            size: layout.size(),
+        });
+    }
+    Ok(layout.size())
+}
+
+/// Compute the `#[repr(C)]` layout of a structure from the types of its fields
+///
+/// This uses the layout of each field (see [TypeInfo::try_layout]),
+/// so it works for extern types whose layout is only registered at runtime.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StructLayoutBuilder {
+    layout: Layout,
+    offsets: Vec<usize>,
+}
+impl StructLayoutBuilder {
+    /// Start building an empty structure
+    pub fn new() -> StructLayoutBuilder {
+        StructLayoutBuilder {
+            layout: Layout::new::<()>(),
+            offsets: Vec::new(),
+        }
+    }
+    /// Add a field with the specified type, returning its offset
+    pub fn field(&mut self, info: &TypeInfo) -> Result<usize, UnsupportedType> {
+        let field = info.try_layout()?;
+        let (layout, offset) = self
+            .layout
+            .extend(field)
+            .map_err(|_| UnsupportedType::new(info))?;
+        self.layout = layout;
+        self.offsets.push(offset);
+        Ok(offset)
+    }
+    /// The offsets of the fields added so far
+    #[inline]
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+    /// The layout of the whole structure, including trailing padding
+    #[inline]
+    pub fn finish(&self) -> Layout {
+        self.layout.pad_to_align()
+    }
+}
+impl Default for StructLayoutBuilder {
+    #[inline]
+    fn default() -> Self {
+        StructLayoutBuilder::new()
+    }
+}
+
+/// The full size of a structure with an unsized tail,
+/// reading the number of elements from its header
+///
+/// This is the size of the header, plus the size of the elements,
+/// rounded up to the alignment of the structure
+/// (which matches the size of the equivalent Rust DST).
+///
+/// Returns `None` if the length is negative, the element type is opaque,
+/// or the size overflows a `usize`.
+///
+/// ## Safety
+/// The base pointer must point to a valid header of the structure,
+/// which must be described by `dynamic`
+/// (see [DynamicSize::for_tail]).
+///
+/// ## Example
+#[cfg_attr(feature = "derive", doc = "````")]
+#[cfg_attr(not(feature = "derive"), doc = "````ignore")]
+/// # use static_reflect::StaticReflect;
+/// # use static_reflect::types::{DynamicSize, TypeInfo};
+/// # use static_reflect::types::ops::dynamic_size_of;
+/// #[derive(StaticReflect)]
+/// #[reflect(unsized_tail(len = "len"))]
+/// #[repr(C)]
+/// struct Packet {
+///     len: u32,
+///     data: [u16; 0],
+/// }
+/// let header = Packet { len: 3, data: [] };
+/// let dynamic = DynamicSize::for_tail(&Packet::TYPE_INFO, 0);
+/// let def = match Packet::TYPE_INFO {
+///     TypeInfo::Structure(def) => def,
+///     _ => unreachable!(),
+/// };
+/// let size = unsafe { dynamic_size_of(&header as *const Packet as *const u8, def, &dynamic) };
+/// assert_eq!(size, Some(12));
+/// ````
+pub unsafe fn dynamic_size_of(
+    base: *const u8,
+    def: &StructureDef,
+    dynamic: &DynamicSize,
+) -> Option<usize> {
+    let len_field = &def.fields[dynamic.len_field_index];
+    let int_type = match *len_field.value_type.type_ref() {
+        TypeInfo::Integer(int_type) => int_type,
+        _ => return None,
+    };
+    let mut value =
+        ReflectValue::read(base.add(len_field.offset), PrimitiveType::Integer(int_type));
+    if let Some(endian) = len_field.endianness {
+        value = value.convert_endian(endian);
+    }
+    let bits = match value {
+        ReflectValue::Integer { bits, .. } => bits,
+        _ => unreachable!(),
+    };
+    let len = usize::try_from(int_type.extend_bits(bits)).ok()?;
+    let stride = dynamic.element.try_size().ok()?;
+    let unpadded = def.size.checked_add(stride.checked_mul(len)?)?;
+    let rem = unpadded % def.alignment;
+    if rem == 0 {
+        Some(unpadded)
+    } else {
+        unpadded.checked_add(def.alignment - rem)
+    }
+}
+
+/// The largest size and alignment over a set of types,
+/// which is a layout that can hold a value of any of them
+///
+/// Use [Layout::pad_to_align] to get the stride of an array of such slots.
+/// An empty set has the layout of `()`.
+///
+/// This uses the layout of each type (see [TypeInfo::try_layout]),
+/// so it works for extern types whose layout is only registered at runtime.
+/// See [max_layout!](crate::macros::max_layout) for computing this at compile time.
+pub fn max_layout(infos: &[&TypeInfo]) -> Result<Layout, UnsupportedType> {
+    let mut size = 0;
+    let mut align = 1;
+    for info in infos {
+        let layout = info.try_layout()?;
+        size = size.max(layout.size());
+        align = align.max(layout.align());
+    }
+    Ok(Layout::from_size_align(size, align).unwrap())
+}
+
+/// The largest size and alignment over a set of types, computed at compile time
+///
+/// This is the implementation of [max_layout!](crate::macros::max_layout).
+/// Unlike [max_layout], it can't use the layouts registered at runtime,
+/// so it panics if any of the types are opaque (extern or magic types).
+pub const fn const_max_layout(infos: &[&TypeInfo]) -> Layout {
+    let mut size = 0;
+    let mut align = 1;
+    let mut index = 0;
+    while index < infos.len() {
+        let info = infos[index];
+        if let TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } =
+            *info
+        {
+            panic!("The layout of an opaque type is unknown at compile time");
+        }
+        if info.size() > size {
+            size = info.size();
+        }
+        if info.alignment() > align {
+            align = info.alignment();
+        }
+        index += 1;
+    }
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => layout,
+        Err(_) => panic!("Invalid layout"),
+    }
+}
+
+/// An error indicating that the layout of a type is unknown
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UnsupportedType {
+    /// The name of the type
+    pub type_name: String,
+}
+impl UnsupportedType {
+    fn new(info: &TypeInfo) -> UnsupportedType {
+        UnsupportedType {
+            type_name: info.to_string(),
+        }
+    }
+}
+/// Keeps the name of the opaque component, which may be nested in the type
+impl From<OpaqueTypeError> for UnsupportedType {
+    #[inline]
+    fn from(cause: OpaqueTypeError) -> Self {
+        UnsupportedType {
+            type_name: cause.type_name,
+        }
+    }
+}
+impl Display for UnsupportedType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Type has an unknown layout: {}", self.type_name)
+    }
+}
+impl std::error::Error for UnsupportedType {}
+
+/// An error performing an operation on values (like [swap_values])
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValueOpError {
+    /// A pointer isn't aligned for the type
+    Misaligned {
+        /// The alignment required by the type
+        required: usize,
+        /// The actual alignment of the pointer
+        /// (the largest power of two dividing its address)
+        actual: usize,
+    },
+    /// The two values partially overlap
+    Overlapping {
+        /// The size of the type
+        size: usize,
+    },
+    /// The type needs to be dropped, so it can't simply be copied
+    NeedsDrop {
+        /// The name of the type
+        type_name: String,
+    },
+    /// The layout of the type is unknown
+    Unsupported(UnsupportedType),
+    /// The size of a region overflows
+    Overflow {
+        /// The name of the element type
+        type_name: String,
+        /// The number of elements
+        count: usize,
+    },
+}
+impl From<UnsupportedType> for ValueOpError {
+    #[inline]
+    fn from(cause: UnsupportedType) -> Self {
+        ValueOpError::Unsupported(cause)
+    }
+}
+impl From<RegionError> for ValueOpError {
+    fn from(cause: RegionError) -> Self {
+        match cause {
+            RegionError::Overflow { type_name, count } => {
+                ValueOpError::Overflow { type_name, count }
+            }
+            RegionError::Unsupported(cause) => ValueOpError::Unsupported(cause),
+        }
+    }
+}
+impl Display for ValueOpError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ValueOpError::Misaligned { required, actual } => write!(
+                f,
+                "Pointer is aligned to {} bytes, but the type requires {}",
+                actual, required
+            ),
+            ValueOpError::Overlapping { size } => {
+                write!(f, "Values of {} bytes overlap", size)
+            }
+            ValueOpError::NeedsDrop { ref type_name } => {
+                write!(f, "Type needs to be dropped: {}", type_name)
+            }
+            ValueOpError::Unsupported(ref cause) => Display::fmt(cause, f),
+            ValueOpError::Overflow {
+                ref type_name,
+                count,
+            } => write!(
+                f,
+                "Region of {} elements of {} is too large",
+                count, type_name
+            ),
+        }
+    }
+}
+impl std::error::Error for ValueOpError {}
@@ -0,0 +1,237 @@
+//! Paths to (possibly nested) fields, like `player.position.x`
+//!
+//! A [FieldPath] can be compiled against a root type into a [CompiledPath],
+//! which is just a flat offset and the type of the final field.
+//! This avoids re-walking the type tree every time the field is accessed.
+//!
+//! Compiling dominates the cost of a read, so keep the [CompiledPath] around
+//! instead of resolving the same path over and over.
+use super::value::ReflectValue;
+use super::{Endian, TypeId, TypeInfo};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A single component of a [FieldPath]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PathSegment {
+    /// Access a field by its name
+    Name(String),
+    /// Access a field by its index
+    ///
+    /// This is needed for the fields of tuple-structs, which have no names.
+    Index(usize),
+}
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            PathSegment::Name(ref name) => f.write_str(name),
+            PathSegment::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// A path of field accesses, starting from some root type
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+impl FieldPath {
+    /// Create a path from the specified segments
+    #[inline]
+    pub fn new(segments: Vec<PathSegment>) -> FieldPath {
+        FieldPath { segments }
+    }
+    /// Parse a dot-separated path like `player.position.x`
+    ///
+    /// Segments consisting entirely of digits are treated as indices.
+    pub fn parse(s: &str) -> Result<FieldPath, PathError> {
+        let mut segments = Vec::new();
+        for (index, part) in s.split('.').enumerate() {
+            if part.is_empty() {
+                return Err(PathError::InvalidSyntax { segment: index });
+            }
+            segments.push(if part.bytes().all(|b| b.is_ascii_digit()) {
+                PathSegment::Index(
+                    part.parse()
+                        .map_err(|_| PathError::InvalidSyntax { segment: index })?,
+                )
+            } else {
+                PathSegment::Name(part.into())
+            });
+        }
+        Ok(FieldPath { segments })
+    }
+    /// The segments of this path
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+    /// Compile this path into a flat offset from the specified root type.
+    ///
+    /// This fails if the path would require indirection
+    /// (through a pointer, slice, or optional).
+    pub fn compile(&self, root: &TypeInfo) -> Result<CompiledPath, PathError> {
+        let mut total_offset = 0;
+        let mut current = root;
+        let mut leaf_type = None;
+        let mut endianness = None;
+        for (index, segment) in self.segments.iter().enumerate() {
+            let (offset, field_type, field_endianness) = match *current {
+                TypeInfo::Structure(def) => def
+                    .fields
+                    .iter()
+                    .find(|field| match *segment {
+                        PathSegment::Name(ref name) => field.name == Some(&**name),
+                        PathSegment::Index(i) => field.index == i,
+                    })
+                    .map(|field| (field.offset, field.value_type.type_ref(), field.endianness)),
+                TypeInfo::UntaggedUnion(def) => def
+                    .fields
+                    .iter()
+                    .find(|field| match *segment {
+                        PathSegment::Name(ref name) => field.name == name,
+                        PathSegment::Index(i) => field.index == i,
+                    })
+                    .map(|field| (field.offset(), field.value_type.type_ref(), None)),
+                TypeInfo::Pointer { .. } => return Err(PathError::Indirection { segment: index }),
+                ref other if other.is_builtin() => {
+                    return Err(PathError::Indirection { segment: index })
+                }
+                _ => return Err(PathError::NotAggregate { segment: index }),
+            }
+            .ok_or(PathError::NoSuchField { segment: index })?;
+            total_offset += offset;
+            current = field_type;
+            leaf_type = Some(field_type);
+            endianness = field_endianness;
+        }
+        Ok(CompiledPath {
+            total_offset,
+            leaf_type: leaf_type.ok_or(PathError::InvalidSyntax { segment: 0 })?,
+            endianness,
+        })
+    }
+}
+impl FromStr for FieldPath {
+    type Err = PathError;
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FieldPath::parse(s)
+    }
+}
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// A [FieldPath] that has been resolved into a flat offset.
+///
+/// Accessing the field doesn't require walking the type tree.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CompiledPath {
+    /// The offset of the final field, relative to the start of the root
+    pub total_offset: usize,
+    /// The type of the final field
+    pub leaf_type: &'static TypeInfo,
+    /// The byte order of the final field, or `None` if it's native
+    pub endianness: Option<Endian>,
+}
+impl CompiledPath {
+    /// Read the value of the field,
+    /// given a pointer to the start of the root value.
+    ///
+    /// Panics if the field isn't a primitive.
+    ///
+    /// If the field has an explicit [Endian]ness,
+    /// the value is converted to the native byte order.
+    ///
+    /// ## Safety
+    /// The base pointer must point to a valid value of the root type.
+    #[inline]
+    pub unsafe fn read(&self, base: *const u8) -> ReflectValue {
+        let primitive = TypeId::<()>::from_static(self.leaf_type)
+            .primitive()
+            .unwrap_or_else(|| panic!("Field type isn't primitive: {}", self.leaf_type));
+        let value = ReflectValue::read(base.add(self.total_offset), primitive);
+        match self.endianness {
+            Some(endian) => value.convert_endian(endian),
+            None => value,
+        }
+    }
+    /// Write the value of the field,
+    /// given a pointer to the start of the root value.
+    ///
+    /// Panics if the value doesn't match the type of the field.
+    ///
+    /// If the field has an explicit [Endian]ness,
+    /// the value is converted from the native byte order.
+    ///
+    /// ## Safety
+    /// The base pointer must point to a valid value of the root type.
+    #[inline]
+    pub unsafe fn write(&self, base: *mut u8, value: ReflectValue) {
+        let primitive = TypeId::<()>::from_static(self.leaf_type).primitive();
+        assert_eq!(
+            primitive,
+            Some(value.primitive_type()),
+            "Mismatched types for field"
+        );
+        let value = match self.endianness {
+            Some(endian) => value.convert_endian(endian),
+            None => value,
+        };
+        value.write(base.add(self.total_offset))
+    }
+}
+
+/// An error resolving a [FieldPath]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathError {
+    /// The path is syntactically invalid (or empty)
+    InvalidSyntax {
+        /// The index of the invalid segment
+        segment: usize,
+    },
+    /// The field doesn't exist
+    NoSuchField {
+        /// The index of the segment that is missing
+        segment: usize,
+    },
+    /// Accessing the field would require indirection,
+    /// through a pointer, slice or optional
+    Indirection {
+        /// The index of the segment that requires indirection
+        segment: usize,
+    },
+    /// Attempted to access a field of a type without any fields
+    NotAggregate {
+        /// The index of the segment that is invalid
+        segment: usize,
+    },
+}
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            PathError::InvalidSyntax { segment } => {
+                write!(f, "Invalid syntax for segment {}", segment)
+            }
+            PathError::NoSuchField { segment } => {
+                write!(f, "No such field for segment {}", segment)
+            }
+            PathError::Indirection { segment } => {
+                write!(f, "Segment {} requires indirection", segment)
+            }
+            PathError::NotAggregate { segment } => {
+                write!(f, "Segment {} accesses a type without fields", segment)
+            }
+        }
+    }
+}
+impl std::error::Error for PathError {}
@@ -0,0 +1,92 @@
+//! Reports comparing the layout computed by the derive against the real layout
+//!
+//! When the derive's layout checks fail, a [LayoutReport] shows
+//! which field's offset diverged, without having to expand the macro.
+use std::fmt::{self, Display, Formatter};
+
+/// The layout of a single field, as computed by the derive
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FieldLayout {
+    /// The name of the field in the source code
+    ///
+    /// Tuple fields are named by their index.
+    pub name: &'static str,
+    /// The offset computed by the derive,
+    /// which is the one recorded in the field's definition
+    pub computed_offset: usize,
+    /// The real offset of the field (given by `core::mem::offset_of`),
+    /// if it's available
+    pub real_offset: Option<usize>,
+}
+impl FieldLayout {
+    /// Check that the computed offset agrees with the real offset
+    ///
+    /// This is always true if the real offset is unavailable.
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        match self.real_offset {
+            Some(real_offset) => real_offset == self.computed_offset,
+            None => true,
+        }
+    }
+}
+
+/// A table of the layout of each field of a type
+///
+/// This is built by [FieldReflect::layout_report](crate::FieldReflect::layout_report),
+/// and displayed as a human-readable table.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LayoutReport {
+    /// The name of the type
+    pub type_name: &'static str,
+    /// The layout of each field, in declaration order
+    pub fields: &'static [FieldLayout],
+}
+impl LayoutReport {
+    /// Check that every field's computed offset agrees with its real offset
+    pub fn is_consistent(&self) -> bool {
+        self.fields.iter().all(FieldLayout::is_consistent)
+    }
+    /// The fields whose computed offset disagrees with their real offset
+    pub fn mismatches(&self) -> impl Iterator<Item = &'static FieldLayout> {
+        self.fields.iter().filter(|field| !field.is_consistent())
+    }
+}
+impl Display for LayoutReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .fields
+            .iter()
+            .map(|field| field.name.len())
+            .chain(std::iter::once("field".len()))
+            .max()
+            .unwrap();
+        writeln!(f, "Layout of {}:", self.type_name)?;
+        writeln!(
+            f,
+            "  {:<width$}  {:>8}  {:>8}",
+            "field",
+            "computed",
+            "real",
+            width = name_width
+        )?;
+        for field in self.fields {
+            write!(
+                f,
+                "  {:<width$}  {:>8}  ",
+                field.name,
+                field.computed_offset,
+                width = name_width
+            )?;
+            match field.real_offset {
+                Some(offset) => write!(f, "{:>8}", offset)?,
+                None => write!(f, "{:>8}", "?")?,
+            }
+            if !field.is_consistent() {
+                f.write_str("  MISMATCH")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Schemas of generic structures, with their type parameters left symbolic
+//!
+//! These are generated by `#[derive(StaticReflect)]` with `#[reflect(schema)]`,
+//! as the inherent `SCHEMA_INFO` constant of the type.
+//! They're purely informational (for documentation and other tooling):
+//! the layout of each instantiation is still given by its [StaticReflect::TYPE_INFO](crate::StaticReflect::TYPE_INFO).
+use super::TypeInfo;
+use std::fmt::{self, Display, Formatter};
+
+/// The schema of a (possibly generic) structure
+///
+/// Fields whose type is one of the structure's type parameters
+/// have the type [TypeInfo::TypeParameter].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SchemaStructureDef {
+    /// The name of the structure
+    pub name: &'static str,
+    /// The names of the structure's type parameters, in declaration order
+    ///
+    /// Lifetime parameters are omitted, since they never affect the layout.
+    pub type_parameters: &'static [&'static str],
+    /// The fields of the structure, in declaration order
+    pub fields: &'static [SchemaFieldDef],
+}
+impl SchemaStructureDef {
+    /// If the layout of the structure depends on its type parameters
+    ///
+    /// This is true if the offset of any field is unknown.
+    #[inline]
+    pub fn is_generic(&self) -> bool {
+        self.fields.iter().any(|field| field.offset.is_none())
+    }
+}
+impl Display for SchemaStructureDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        if !self.type_parameters.is_empty() {
+            f.write_str("<")?;
+            for (index, param) in self.type_parameters.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(param)?;
+            }
+            f.write_str(">")?;
+        }
+        f.write_str(" {")?;
+        for (index, field) in self.fields.iter().enumerate() {
+            f.write_str(if index == 0 { " " } else { ", " })?;
+            match field.name {
+                Some(name) => write!(f, "{}: {}", name, field.value_type)?,
+                None => write!(f, "{}: {}", field.index, field.value_type)?,
+            }
+        }
+        if !self.fields.is_empty() {
+            f.write_str(" ")?;
+        }
+        f.write_str("}")
+    }
+}
+
+/// A field in a [SchemaStructureDef]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SchemaFieldDef {
+    /// The name of the field, or `None` for tuple fields
+    pub name: Option<&'static str>,
+    /// The type of the field, which may be a [TypeInfo::TypeParameter]
+    pub value_type: &'static TypeInfo,
+    /// The offset of the field, if it's the same for every instantiation
+    ///
+    /// This is `None` if the field (or any field before it)
+    /// has a type depending on a type parameter.
+    pub offset: Option<usize>,
+    /// The index of the field, in declaration order
+    pub index: usize,
+}
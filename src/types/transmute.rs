@@ -0,0 +1,467 @@
+//! Check whether the bytes of one type can be reinterpreted as another
+//!
+//! The rules are conservative, so [TypeInfo::can_transmute_to] may reject
+//! some transmutes that are actually fine. Besides matching sizes and alignments,
+//! every component of the target type must be valid for whatever the source could contain:
+//!
+//! - Integers and floats accept any initialized bytes, and so do vectors of them.
+//!   The padding of the source is uninitialized, and so are the fields of unions
+//!   (except for bytes that every field initializes) and the payloads of tagged unions.
+//! - Pointers must come from pointers, since integers don't carry provenance.
+//! - A `bool` must come from a `bool`.
+//! - A [TypeInfo::NonZero] must come from another non-zero value.
+//!   Niches ([TypeInfo::Niche]) may be zero, so they're treated as their nullable representation.
+//! - A C-style enum must come from a C-style enum whose discriminants are all valid in the target,
+//!   a `bool` (if both `0` and `1` are valid), or anything initialized if every bit pattern
+//!   is a valid discriminant ([CStyleEnumDef::covers_all_bit_patterns]).
+//!   This uses the precomputed [CStyleEnumDef::valid_range] and [CStyleEnumDef::has_gaps],
+//!   so enums without gaps never need to look up individual variants.
+//! - Structures are checked field by field, while their padding accepts anything.
+//! - Unions accept anything, since Rust doesn't require their bytes to be valid for any field.
+//! - Tagged unions, builtins (slices, strings and optionals) and other vectors
+//!   must come from exactly the same type at the same offset.
+//! - The never type (`!`) has no valid values at all.
+//!
+//! Extern and magic types have unknown validity, so they're always rejected.
+use super::{CStyleEnumDef, IntType, PrimitiveType, TypeInfo};
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// The reason a transmute between two types was rejected
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TransmuteError {
+    /// The types have different sizes
+    SizeMismatch {
+        /// The size of the source type
+        source_size: usize,
+        /// The size of the target type
+        target_size: usize,
+    },
+    /// The types have different alignments
+    AlignmentMismatch {
+        /// The alignment of the source type
+        source_alignment: usize,
+        /// The alignment of the target type
+        target_alignment: usize,
+    },
+    /// A component of the target may be uninitialized in the source
+    /// (because it overlaps padding)
+    Uninitialized {
+        /// The path to the component of the target (like `Example.first`)
+        path: String,
+        /// The name of the component's type
+        type_name: String,
+    },
+    /// A component of the target may be invalid for the value in the source
+    InvalidValue {
+        /// The path to the component of the target (like `Example.first`)
+        path: String,
+        /// The name of the component's type
+        type_name: String,
+    },
+    /// One of the types has an unknown layout or validity
+    /// (like an extern type)
+    Unsupported {
+        /// The path to the unsupported component
+        ///
+        /// This starts with the name of the source type if the source is unsupported,
+        /// and with the name of the target otherwise.
+        path: String,
+        /// The name of the component's type
+        type_name: String,
+    },
+}
+impl Display for TransmuteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            TransmuteError::SizeMismatch {
+                source_size,
+                target_size,
+            } => write!(
+                f,
+                "Source size {} doesn't match target size {}",
+                source_size, target_size
+            ),
+            TransmuteError::AlignmentMismatch {
+                source_alignment,
+                target_alignment,
+            } => write!(
+                f,
+                "Source alignment {} doesn't match target alignment {}",
+                source_alignment, target_alignment
+            ),
+            TransmuteError::Uninitialized {
+                ref path,
+                ref type_name,
+            } => write!(f, "{} ({}) may be uninitialized", path, type_name),
+            TransmuteError::InvalidValue {
+                ref path,
+                ref type_name,
+            } => write!(f, "{} ({}) may be invalid", path, type_name),
+            TransmuteError::Unsupported {
+                ref path,
+                ref type_name,
+            } => write!(f, "{} ({}) has an unknown layout", path, type_name),
+        }
+    }
+}
+impl std::error::Error for TransmuteError {}
+
+impl TypeInfo {
+    /// Check whether a value of this type can be reinterpreted as the target type
+    ///
+    /// This is conservative, following the rules in the [module documentation](self).
+    /// The error names the first component of the target that may be invalid.
+    pub fn can_transmute_to(&self, target: &TypeInfo) -> Result<(), TransmuteError> {
+        let (source_layout, target_layout) =
+            match (self.resolved_layout(), target.resolved_layout()) {
+                (Some(source), Some(target)) => (source, target),
+                (None, _) => return Err(unsupported(&self.to_string(), self)),
+                (_, None) => return Err(unsupported(&target.to_string(), target)),
+            };
+        if source_layout.size() != target_layout.size() {
+            return Err(TransmuteError::SizeMismatch {
+                source_size: source_layout.size(),
+                target_size: target_layout.size(),
+            });
+        }
+        if source_layout.align() != target_layout.align() {
+            return Err(TransmuteError::AlignmentMismatch {
+                source_alignment: source_layout.align(),
+                target_alignment: target_layout.align(),
+            });
+        }
+        let mut initialized = Vec::new();
+        collect_initialized(self, 0, &mut initialized, &mut self.to_string())?;
+        let checker = Checker {
+            source: self,
+            initialized: merge_runs(initialized),
+        };
+        checker.check(target, 0, &mut target.to_string())
+    }
+}
+
+impl CStyleEnumDef {
+    /// Whether every bit pattern of the discriminant is a valid variant
+    ///
+    /// This is only really possible for enums with a one-byte discriminant and 256 variants.
+    pub fn covers_all_bit_patterns(&self) -> bool {
+        !self.has_gaps && self.valid_range == Some(value_range(self.discriminant))
+    }
+    /// Whether the (sign-extended) value is a valid discriminant
+    ///
+    /// Without any gaps, this only needs to check the valid range.
+    fn accepts_value(&self, value: i128) -> bool {
+        match self.valid_range {
+            Some((min, max)) if min <= value && value <= max => {
+                !self.has_gaps || self.name_of(value).is_some()
+            }
+            _ => false,
+        }
+    }
+    /// Whether every discriminant of the source (of the same size) is valid for this enum
+    fn accepts_all_of(&self, source: &CStyleEnumDef) -> bool {
+        if source.discriminant == self.discriminant && !self.has_gaps {
+            // Only need to compare the ranges
+            return match (source.valid_range, self.valid_range) {
+                (None, _) => true,
+                (Some((source_min, source_max)), Some((min, max))) => {
+                    min <= source_min && source_max <= max
+                }
+                (Some(_), None) => false,
+            };
+        }
+        source.variants.iter().all(|variant| {
+            self.variant_for_discriminant(variant.discriminant.bits())
+                .is_some()
+        })
+    }
+}
+
+/// The minimum and maximum values of an integer type
+fn value_range(int: IntType) -> (i128, i128) {
+    let width = int.size.bytes() as u32 * 8;
+    if int.signed {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    }
+}
+
+fn unsupported(path: &str, info: &TypeInfo) -> TransmuteError {
+    TransmuteError::Unsupported {
+        path: path.into(),
+        type_name: info.to_string(),
+    }
+}
+
+/// Run a function with a field appended to the path,
+/// restoring the path afterwards
+fn with_field<T>(
+    path: &mut String,
+    name: Option<&str>,
+    index: usize,
+    func: impl FnOnce(&mut String) -> T,
+) -> T {
+    let original_len = path.len();
+    path.push('.');
+    match name {
+        Some(name) => path.push_str(name),
+        None => path.push_str(&index.to_string()),
+    }
+    let result = func(path);
+    path.truncate(original_len);
+    result
+}
+
+/// Collect the byte ranges of the source that are definitely initialized
+fn collect_initialized(
+    info: &TypeInfo,
+    base: usize,
+    runs: &mut Vec<Range<usize>>,
+    path: &mut String,
+) -> Result<(), TransmuteError> {
+    match *info {
+        TypeInfo::Structure(def) => {
+            // The unsized tail isn't part of the value
+            let fields = if def.has_unsized_tail {
+                &def.fields[..def.fields.len() - 1]
+            } else {
+                def.fields
+            };
+            for field in fields {
+                with_field(path, field.name, field.index, |path| {
+                    collect_initialized(
+                        field.value_type.type_ref(),
+                        base + field.offset,
+                        runs,
+                        path,
+                    )
+                })?;
+            }
+        }
+        TypeInfo::UntaggedUnion(def) => {
+            // Only the bytes initialized by every field are known to be initialized
+            let mut common: Option<Vec<Range<usize>>> = None;
+            for field in def.fields {
+                let mut field_runs = Vec::new();
+                with_field(path, Some(field.name), field.index, |path| {
+                    collect_initialized(field.value_type.type_ref(), base, &mut field_runs, path)
+                })?;
+                let field_runs = merge_runs(field_runs);
+                common = Some(match common {
+                    Some(common) => intersect_runs(&common, &field_runs),
+                    None => field_runs,
+                });
+            }
+            runs.extend(common.unwrap_or_default());
+        }
+        TypeInfo::TaggedUnion(def) => {
+            // The payload depends on the variant, but the tag is always initialized
+            runs.push(base..base + def.discriminant_type.size.bytes());
+        }
+        TypeInfo::NonZero(inner) => collect_initialized(inner, base, runs, path)?,
+        TypeInfo::Niche(def) => collect_initialized(def.wrapped, base, runs, path)?,
+        TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } => {
+            return Err(unsupported(path, info))
+        }
+        ref other if other.optional_inner_type().is_some() => {
+            // Only the flag is initialized if the value is absent
+            runs.push(base..base + 1);
+        }
+        ref other if other.is_never() => {}
+        _ => {
+            // Primitives, C-style enums, slices and strings
+            let size = info.size();
+            if size > 0 {
+                runs.push(base..base + size);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn merge_runs(mut runs: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    runs.sort_by_key(|run| run.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(runs.len());
+    for run in runs {
+        match merged.last_mut() {
+            Some(last) if run.start <= last.end => {
+                last.end = last.end.max(run.end);
+            }
+            _ => merged.push(run),
+        }
+    }
+    merged
+}
+
+/// Intersect two lists of sorted, non-overlapping runs
+fn intersect_runs(first: &[Range<usize>], second: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() && j < second.len() {
+        let start = first[i].start.max(second[j].start);
+        let end = first[i].end.min(second[j].end);
+        if start < end {
+            result.push(start..end);
+        }
+        if first[i].end < second[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+struct Checker<'a> {
+    source: &'a TypeInfo,
+    initialized: Vec<Range<usize>>,
+}
+impl Checker<'_> {
+    fn check(
+        &self,
+        target: &TypeInfo,
+        offset: usize,
+        path: &mut String,
+    ) -> Result<(), TransmuteError> {
+        let invalid = |path: &str| TransmuteError::InvalidValue {
+            path: path.into(),
+            type_name: target.to_string(),
+        };
+        match *target {
+            TypeInfo::Unit | TypeInfo::UntaggedUnion(_) => Ok(()),
+            TypeInfo::Structure(def) => {
+                let fields = if def.has_unsized_tail {
+                    &def.fields[..def.fields.len() - 1]
+                } else {
+                    def.fields
+                };
+                for field in fields {
+                    with_field(path, field.name, field.index, |path| {
+                        self.check(field.value_type.type_ref(), offset + field.offset, path)
+                    })?;
+                }
+                Ok(())
+            }
+            TypeInfo::Niche(def) => self.check(&def.wrapped.nullable(), offset, path),
+            TypeInfo::Extern { .. } | TypeInfo::Magic { .. } | TypeInfo::TypeParameter { .. } => {
+                Err(unsupported(path, target))
+            }
+            ref never if never.is_never() => Err(invalid(path)),
+            TypeInfo::Integer(_)
+            | TypeInfo::Float { .. }
+            | TypeInfo::Vector {
+                element: PrimitiveType::Integer(_) | PrimitiveType::Float { .. },
+                ..
+            } => self.require_initialized(target, offset, path),
+            TypeInfo::Pointer { .. } => {
+                self.require_initialized(target, offset, path)?;
+                if self.source_matches(offset, target.size(), |source| {
+                    matches!(source.nullable(), TypeInfo::Pointer { .. })
+                }) {
+                    Ok(())
+                } else {
+                    Err(invalid(path))
+                }
+            }
+            TypeInfo::Bool => {
+                self.require_initialized(target, offset, path)?;
+                if self.source_matches(offset, 1, |source| *source == TypeInfo::Bool) {
+                    Ok(())
+                } else {
+                    Err(invalid(path))
+                }
+            }
+            TypeInfo::NonZero(inner) => {
+                self.require_initialized(target, offset, path)?;
+                let is_pointer = |info: &TypeInfo| matches!(*info, TypeInfo::Pointer { .. });
+                if self.source_matches(offset, target.size(), |source| match *source {
+                    TypeInfo::NonZero(source_inner) => {
+                        !is_pointer(inner) || is_pointer(source_inner)
+                    }
+                    _ => false,
+                }) {
+                    Ok(())
+                } else {
+                    Err(invalid(path))
+                }
+            }
+            TypeInfo::CStyleEnum(def) => {
+                self.require_initialized(target, offset, path)?;
+                let valid = def.covers_all_bit_patterns()
+                    || self.source_matches(offset, target.size(), |source| match *source {
+                        TypeInfo::CStyleEnum(source_def) => def.accepts_all_of(source_def),
+                        TypeInfo::Bool => def.accepts_value(0) && def.accepts_value(1),
+                        _ => false,
+                    });
+                if valid {
+                    Ok(())
+                } else {
+                    Err(invalid(path))
+                }
+            }
+            _ => {
+                // Tagged unions, builtins and vectors of other primitives
+                if self.source_matches(offset, target.size(), |source| source == target) {
+                    Ok(())
+                } else {
+                    Err(invalid(path))
+                }
+            }
+        }
+    }
+
+    fn require_initialized(
+        &self,
+        target: &TypeInfo,
+        offset: usize,
+        path: &str,
+    ) -> Result<(), TransmuteError> {
+        let end = offset + target.size();
+        if offset == end
+            || self
+                .initialized
+                .iter()
+                .any(|run| run.start <= offset && end <= run.end)
+        {
+            Ok(())
+        } else {
+            Err(TransmuteError::Uninitialized {
+                path: path.into(),
+                type_name: target.to_string(),
+            })
+        }
+    }
+
+    /// Check if any component of the source at the specified offset
+    /// (with the specified size) matches the predicate
+    fn source_matches(
+        &self,
+        offset: usize,
+        size: usize,
+        predicate: impl Fn(&TypeInfo) -> bool,
+    ) -> bool {
+        fn visit(
+            info: &TypeInfo,
+            base: usize,
+            offset: usize,
+            size: usize,
+            predicate: &dyn Fn(&TypeInfo) -> bool,
+        ) -> bool {
+            if base == offset && info.resolved_size() == Some(size) && predicate(info) {
+                return true;
+            }
+            match *info {
+                // NOTE: The fields of unions aren't known to be valid
+                TypeInfo::Structure(def) => def.fields.iter().any(|field| {
+                    let start = base + field.offset;
+                    start <= offset
+                        && visit(field.value_type.type_ref(), start, offset, size, predicate)
+                }),
+                _ => false,
+            }
+        }
+        visit(self.source, 0, offset, size, &predicate)
+    }
+}
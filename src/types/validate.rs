@@ -0,0 +1,210 @@
+//! Consistency checks for enum definitions
+//!
+//! Definitions generated by the derive are always valid
+//! (this is checked at compile time), but hand-written ones may not be.
+use std::alloc::Layout;
+use std::fmt::{self, Display, Formatter};
+
+use super::{CStyleEnumDef, DiscriminantValue, IntType, TaggedUnionDef, TypeInfo};
+
+/// An error indicating an enum definition is inconsistent
+///
+/// Variants are referred to by their position in the list of variants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EnumDefError {
+    /// Two variants have the same discriminant
+    DuplicateDiscriminant {
+        /// The position of the first variant
+        first: usize,
+        /// The position of the second variant, with the same discriminant
+        second: usize,
+    },
+    /// The discriminant of a variant doesn't fit in the enum's discriminant type
+    DiscriminantOutOfRange {
+        /// The position of the variant
+        position: usize,
+    },
+    /// The index of a variant doesn't match its position
+    ///
+    /// Indexes must be contiguous, in declaration order.
+    IndexMismatch {
+        /// The position of the variant
+        position: usize,
+        /// The index it claims to have
+        index: usize,
+    },
+    /// The structure of a tagged union's variant (including the tag)
+    /// doesn't fit in the tagged union
+    VariantTooLarge {
+        /// The position of the variant
+        position: usize,
+    },
+    /// The size or alignment of a tagged union
+    /// doesn't match the layout computed from its variants
+    LayoutMismatch,
+    /// The precomputed validity flags of a C-style enum don't match its variants
+    ///
+    /// Use [CStyleEnumDef::compute_flags] to fill them in.
+    InconsistentFlags,
+}
+impl Display for EnumDefError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            EnumDefError::DuplicateDiscriminant { first, second } => write!(
+                f,
+                "Variants #{} and #{} have the same discriminant",
+                first, second
+            ),
+            EnumDefError::DiscriminantOutOfRange { position } => write!(
+                f,
+                "Discriminant of variant #{} doesn't fit in the discriminant type",
+                position
+            ),
+            EnumDefError::IndexMismatch { position, index } => {
+                write!(f, "Variant #{} has mismatched index {}", position, index)
+            }
+            EnumDefError::VariantTooLarge { position } => {
+                write!(f, "Variant #{} doesn't fit in the tagged union", position)
+            }
+            EnumDefError::LayoutMismatch => {
+                f.write_str("Tagged union has a different layout than its variants")
+            }
+            EnumDefError::InconsistentFlags => {
+                f.write_str("Enum has validity flags inconsistent with its variants")
+            }
+        }
+    }
+}
+impl std::error::Error for EnumDefError {}
+
+/// Check a single variant's index and discriminant
+const fn validate_variant(
+    discriminant_type: IntType,
+    position: usize,
+    index: usize,
+    discriminant: DiscriminantValue,
+) -> Result<(), EnumDefError> {
+    if index != position {
+        return Err(EnumDefError::IndexMismatch { position, index });
+    }
+    let bits = discriminant.bits();
+    let truncated = discriminant_type.truncate_bits(bits);
+    // Negative values may also be given sign-extended to 64 bits
+    let fits = bits == truncated
+        || (discriminant_type.signed && bits == discriminant_type.extend_bits(truncated) as u64);
+    if !fits {
+        return Err(EnumDefError::DiscriminantOutOfRange { position });
+    }
+    Ok(())
+}
+
+impl CStyleEnumDef {
+    /// Check that this definition is consistent
+    ///
+    /// The index of each variant must match its position,
+    /// and the discriminants must be unique and fit in the discriminant type.
+    /// The precomputed [CStyleEnumDef::valid_range] and [CStyleEnumDef::has_gaps]
+    /// must also match the variants, since the transmute checks rely on them.
+    ///
+    /// This is a `const fn`, so the derive can check its definitions at compile time.
+    pub const fn validate(&self) -> Result<(), EnumDefError> {
+        // NOTE: Can't use for-loop since iterators aren't const
+        let mut position = 0;
+        while position < self.variants.len() {
+            let variant = &self.variants[position];
+            if let Err(error) = validate_variant(
+                self.discriminant,
+                position,
+                variant.index,
+                variant.discriminant,
+            ) {
+                return Err(error);
+            }
+            let bits = self.discriminant.truncate_bits(variant.discriminant.bits());
+            let mut first = 0;
+            while first < position {
+                let other = self.variants[first].discriminant.bits();
+                if self.discriminant.truncate_bits(other) == bits {
+                    return Err(EnumDefError::DuplicateDiscriminant {
+                        first,
+                        second: position,
+                    });
+                }
+                first += 1;
+            }
+            position += 1;
+        }
+        let (valid_range, has_gaps) = self.flags_from_variants();
+        let consistent_range = match (valid_range, self.valid_range) {
+            (Some((min, max)), Some((actual_min, actual_max))) => {
+                min == actual_min && max == actual_max
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if !consistent_range || has_gaps != self.has_gaps {
+            return Err(EnumDefError::InconsistentFlags);
+        }
+        Ok(())
+    }
+}
+
+impl TaggedUnionDef {
+    /// Check that this definition is consistent
+    ///
+    /// Like [CStyleEnumDef::validate], the indexes and discriminants must be valid.
+    /// In addition, each variant's structure (along with the tag) must fit in the tagged union,
+    /// and the size and alignment must match the layout computed by the [TaggedUnionStyle](super::TaggedUnionStyle).
+    pub fn validate(&self) -> Result<(), EnumDefError> {
+        for (position, variant) in self.variants.iter().enumerate() {
+            validate_variant(
+                self.discriminant_type,
+                position,
+                variant.index,
+                variant.discriminant,
+            )?;
+            let bits = self
+                .discriminant_type
+                .truncate_bits(variant.discriminant.bits());
+            if let Some(first) = self.variants[..position].iter().position(|other| {
+                self.discriminant_type
+                    .truncate_bits(other.discriminant.bits())
+                    == bits
+            }) {
+                return Err(EnumDefError::DuplicateDiscriminant {
+                    first,
+                    second: position,
+                });
+            }
+        }
+        if self.variants.is_empty() {
+            // Uninhabited, so there's no layout to compute
+            return Ok(());
+        }
+        let tag = TypeInfo::Integer(self.discriminant_type);
+        let tag_layout = Layout::from_size_align(tag.size(), tag.alignment()).unwrap();
+        let mut variant_layouts = Vec::with_capacity(self.variants.len());
+        for (position, variant) in self.variants.iter().enumerate() {
+            let structure = &variant.equivalent_structure;
+            let layout = Layout::from_size_align(structure.size, structure.alignment)
+                .map_err(|_| EnumDefError::VariantTooLarge { position })?;
+            let fits = self
+                .style
+                .compute_layout(tag_layout, std::iter::once(layout))
+                .size()
+                <= self.size;
+            if !fits {
+                return Err(EnumDefError::VariantTooLarge { position });
+            }
+            variant_layouts.push(layout);
+        }
+        let expected = self
+            .style
+            .compute_layout(tag_layout, variant_layouts.into_iter())
+            .pad_to_align();
+        if expected.size() != self.size || expected.align() != self.alignment {
+            return Err(EnumDefError::LayoutMismatch);
+        }
+        Ok(())
+    }
+}
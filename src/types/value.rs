@@ -0,0 +1,131 @@
+//! Dynamically typed values of primitive types
+//!
+//! These are read from (and written to) raw memory,
+//! according to a type's reflected information.
+use super::{Endian, FloatSize, IntType, PrimitiveType};
+use std::fmt::{self, Display, Formatter};
+
+/// A primitive value, whose type is only known at runtime
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReflectValue {
+    /// The zero-sized unit value `()`
+    Unit,
+    /// A boolean
+    Bool(bool),
+    /// An integer
+    Integer {
+        /// The type of the integer
+        int_type: IntType,
+        /// The raw bits of the integer, zero-extended to 64 bits
+        ///
+        /// Use [IntType::extend_bits] to get the (possibly signed) value.
+        bits: u64,
+    },
+    /// A single-precision float
+    F32(f32),
+    /// A double-precision float
+    F64(f64),
+    /// An untyped pointer
+    Pointer(*mut ()),
+}
+impl ReflectValue {
+    /// The primitive type of this value
+    pub fn primitive_type(&self) -> PrimitiveType {
+        match *self {
+            ReflectValue::Unit => PrimitiveType::Unit,
+            ReflectValue::Bool(_) => PrimitiveType::Bool,
+            ReflectValue::Integer { int_type, .. } => PrimitiveType::Integer(int_type),
+            ReflectValue::F32(_) => PrimitiveType::Float {
+                size: FloatSize::Single,
+            },
+            ReflectValue::F64(_) => PrimitiveType::Float {
+                size: FloatSize::Double,
+            },
+            ReflectValue::Pointer(_) => PrimitiveType::Pointer,
+        }
+    }
+    /// Reverse the bytes of this value
+    ///
+    /// Only integers and floats are affected.
+    pub fn swap_bytes(self) -> ReflectValue {
+        match self {
+            ReflectValue::Integer { int_type, bits } => ReflectValue::Integer {
+                int_type,
+                bits: bits.swap_bytes() >> (64 - int_type.size.bytes() * 8),
+            },
+            ReflectValue::F32(f) => ReflectValue::F32(f32::from_bits(f.to_bits().swap_bytes())),
+            ReflectValue::F64(f) => ReflectValue::F64(f64::from_bits(f.to_bits().swap_bytes())),
+            ReflectValue::Unit | ReflectValue::Bool(_) | ReflectValue::Pointer(_) => self,
+        }
+    }
+    /// Convert between the native byte order and the specified one
+    ///
+    /// The conversion is symmetric, so this works in both directions.
+    #[inline]
+    pub fn convert_endian(self, endian: Endian) -> ReflectValue {
+        if endian.is_native() {
+            self
+        } else {
+            self.swap_bytes()
+        }
+    }
+    /// Read a value of the specified primitive type from memory
+    ///
+    /// Panics if the type is [PrimitiveType::Never],
+    /// since values of that type can't exist.
+    ///
+    /// ## Safety
+    /// The pointer must be valid for reads of the primitive's size,
+    /// and the memory must contain a valid value of the type.
+    /// It doesn't need to be aligned.
+    pub unsafe fn read(ptr: *const u8, tp: PrimitiveType) -> ReflectValue {
+        match tp {
+            PrimitiveType::Unit => ReflectValue::Unit,
+            #[cfg(feature = "never")]
+            PrimitiveType::Never => panic!("Values of the never type can't exist"),
+            PrimitiveType::Bool => ReflectValue::Bool((ptr as *const bool).read_unaligned()),
+            PrimitiveType::Pointer => {
+                ReflectValue::Pointer((ptr as *const *mut ()).read_unaligned())
+            }
+            PrimitiveType::Integer(int_type) => ReflectValue::Integer {
+                int_type,
+                bits: int_type.read_bits(ptr),
+            },
+            PrimitiveType::Float {
+                size: FloatSize::Single,
+            } => ReflectValue::F32((ptr as *const f32).read_unaligned()),
+            PrimitiveType::Float {
+                size: FloatSize::Double,
+            } => ReflectValue::F64((ptr as *const f64).read_unaligned()),
+        }
+    }
+    /// Write this value to memory
+    ///
+    /// ## Safety
+    /// The pointer must be valid for writes of the value's size.
+    /// It doesn't need to be aligned.
+    pub unsafe fn write(self, ptr: *mut u8) {
+        match self {
+            ReflectValue::Unit => {}
+            ReflectValue::Bool(b) => (ptr as *mut bool).write_unaligned(b),
+            ReflectValue::Integer { int_type, bits } => int_type.write_bits(ptr, bits),
+            ReflectValue::F32(f) => (ptr as *mut f32).write_unaligned(f),
+            ReflectValue::F64(f) => (ptr as *mut f64).write_unaligned(f),
+            ReflectValue::Pointer(p) => (ptr as *mut *mut ()).write_unaligned(p),
+        }
+    }
+}
+impl Display for ReflectValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            ReflectValue::Unit => f.write_str("()"),
+            ReflectValue::Bool(b) => write!(f, "{}", b),
+            ReflectValue::Integer { int_type, bits } => {
+                write!(f, "{}{}", int_type.extend_bits(bits), int_type)
+            }
+            ReflectValue::F32(v) => write!(f, "{}f32", v),
+            ReflectValue::F64(v) => write!(f, "{}f64", v),
+            ReflectValue::Pointer(p) => write!(f, "{:p}", p),
+        }
+    }
+}
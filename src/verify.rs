@@ -0,0 +1,335 @@
+//! Check an implementation of [StaticReflect] against the real layout of the type
+//!
+//! The derive checks its definitions at compile time,
+//! but a manual `unsafe impl StaticReflect` has no such safety net.
+//! Calling [verify_layout] (or [assert_layout](crate::macros::assert_layout))
+//! from a test catches most mistakes, without needing any values of the type:
+//!
+//! - The reflected size and alignment must equal `size_of` and `align_of`
+//!   (only the header of a structure with an unsized tail is reflected, so it may be smaller).
+//!   For a C-style enum, this is the width of the discriminant.
+//! - Every field of a structure must fit inside it, be properly aligned,
+//!   and not overlap any other (non-empty) field.
+//! - Every field of a union must fit inside the union.
+//! - Enum definitions must pass [CStyleEnumDef::validate](crate::types::CStyleEnumDef::validate)
+//!   (or [TaggedUnionDef::validate](crate::types::TaggedUnionDef::validate)).
+//!
+//! Nested structures and unions are checked recursively,
+//! although only the outermost type can be compared with `size_of`.
+//! The layouts of extern and magic types are unknown, so they're skipped
+//! (unless an extern layout is registered, see [crate::registry]).
+use crate::options::{ReflectOptions, UnionInspection};
+use crate::types::validate::EnumDefError;
+use crate::types::TypeInfo;
+use crate::StaticReflect;
+use std::alloc::Layout;
+use std::fmt::{self, Display, Formatter};
+
+/// The way a reflected layout is inconsistent with the real one
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MismatchKind {
+    /// The reflected size differs from `size_of`
+    Size {
+        /// The reflected size
+        reflected: usize,
+        /// The real size of the type
+        actual: usize,
+    },
+    /// The reflected alignment differs from `align_of`
+    Alignment {
+        /// The reflected alignment
+        reflected: usize,
+        /// The real alignment of the type
+        actual: usize,
+    },
+    /// The width of a C-style enum's discriminant differs from the size of the enum
+    DiscriminantWidth {
+        /// The width of the reflected discriminant, in bytes
+        reflected: usize,
+        /// The real size of the enum
+        actual: usize,
+    },
+    /// A field of a structure extends past its end
+    FieldOutOfBounds {
+        /// The name of the field (or its index, for tuple structs)
+        field: String,
+        /// The offset of the end of the field
+        end: usize,
+        /// The size of the structure
+        size: usize,
+    },
+    /// The offset of a field isn't a multiple of its alignment
+    MisalignedField {
+        /// The name of the field (or its index, for tuple structs)
+        field: String,
+        /// The offset of the field
+        offset: usize,
+        /// The alignment of the field's type
+        alignment: usize,
+    },
+    /// Two fields of a structure occupy some of the same bytes
+    OverlappingFields {
+        /// The field with the smaller offset
+        first: String,
+        /// The field which starts before the first one ends
+        second: String,
+    },
+    /// A field of a union is larger than the union itself
+    UnionFieldTooLarge {
+        /// The name of the field
+        field: String,
+        /// The size of the field
+        field_size: usize,
+        /// The size of the union
+        union_size: usize,
+    },
+    /// An enum definition is inconsistent
+    InvalidEnum(EnumDefError),
+}
+impl Display for MismatchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            MismatchKind::Size { reflected, actual } => write!(
+                f,
+                "Reflected size {} doesn't match the real size {}",
+                reflected, actual
+            ),
+            MismatchKind::Alignment { reflected, actual } => write!(
+                f,
+                "Reflected alignment {} doesn't match the real alignment {}",
+                reflected, actual
+            ),
+            MismatchKind::DiscriminantWidth { reflected, actual } => write!(
+                f,
+                "Discriminant has {} bytes, but the enum has {}",
+                reflected, actual
+            ),
+            MismatchKind::FieldOutOfBounds {
+                ref field,
+                end,
+                size,
+            } => write!(
+                f,
+                "Field {} ends at {}, past the end of the structure ({})",
+                field, end, size
+            ),
+            MismatchKind::MisalignedField {
+                ref field,
+                offset,
+                alignment,
+            } => write!(
+                f,
+                "Field {} has offset {}, which isn't aligned to {}",
+                field, offset, alignment
+            ),
+            MismatchKind::OverlappingFields {
+                ref first,
+                ref second,
+            } => write!(f, "Fields {} and {} overlap", first, second),
+            MismatchKind::UnionFieldTooLarge {
+                ref field,
+                field_size,
+                union_size,
+            } => write!(
+                f,
+                "Field {} has size {}, larger than the union ({})",
+                field, field_size, union_size
+            ),
+            MismatchKind::InvalidEnum(ref cause) => Display::fmt(cause, f),
+        }
+    }
+}
+
+/// An error indicating the reflected layout of a type doesn't match its real layout
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayoutMismatch {
+    /// The path to the inconsistent type, starting with the name of the outermost type
+    /// (like `Outer.inner`)
+    pub path: String,
+    /// What exactly is wrong
+    pub kind: MismatchKind,
+}
+impl Display for LayoutMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+impl std::error::Error for LayoutMismatch {}
+
+/// Check that [StaticReflect::TYPE_INFO] is consistent with the real layout of the type
+///
+/// This is intended to be called from tests (see the [module documentation](self)).
+pub fn verify_layout<T: StaticReflect>() -> Result<(), LayoutMismatch> {
+    verify_type_info(&T::TYPE_INFO, Layout::new::<T>())
+}
+
+/// Check that the reflected type info is consistent with the specified layout
+///
+/// This is the same as [verify_layout], for types which are only known at runtime.
+/// It uses the [Default] options (see [verify_type_info_with]).
+#[inline]
+pub fn verify_type_info(info: &TypeInfo, actual: Layout) -> Result<(), LayoutMismatch> {
+    verify_type_info_with(info, actual, &ReflectOptions::default())
+}
+
+/// Check that the reflected type info is consistent with the specified layout,
+/// using the specified options
+///
+/// The fields of types at [ReflectOptions::max_depth] (or deeper) aren't checked,
+/// and neither are the fields of unions with [UnionInspection::Skip].
+/// The size and alignment of the root type are always checked.
+pub fn verify_type_info_with(
+    info: &TypeInfo,
+    actual: Layout,
+    options: &ReflectOptions,
+) -> Result<(), LayoutMismatch> {
+    let path = info.to_string();
+    let mismatch = |kind| {
+        Err(LayoutMismatch {
+            path: path.clone(),
+            kind,
+        })
+    };
+    // Inconsistent definitions give more specific errors than a mismatched size
+    verify_consistency(info, &path, 0, options)?;
+    if let TypeInfo::CStyleEnum(def) = *info {
+        let width = def.discriminant.size.bytes();
+        if width != actual.size() {
+            return mismatch(MismatchKind::DiscriminantWidth {
+                reflected: width,
+                actual: actual.size(),
+            });
+        }
+    }
+    if let Some(reflected) = known_layout(info) {
+        let size_matches = match *info {
+            TypeInfo::Structure(def) if def.has_unsized_tail => reflected.size() <= actual.size(),
+            _ => reflected.size() == actual.size(),
+        };
+        if !size_matches {
+            return mismatch(MismatchKind::Size {
+                reflected: reflected.size(),
+                actual: actual.size(),
+            });
+        }
+        if reflected.align() != actual.align() {
+            return mismatch(MismatchKind::Alignment {
+                reflected: reflected.align(),
+                actual: actual.align(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The reflected layout of the type, if it's known
+fn known_layout(info: &TypeInfo) -> Option<Layout> {
+    #[cfg(feature = "builtins")]
+    {
+        // The layouts of these builtins depend on their type parameters
+        if matches!(*info, TypeInfo::Slice { .. } | TypeInfo::Optional(_)) {
+            return None;
+        }
+    }
+    info.resolved_layout()
+}
+
+/// Check that the definition is consistent with itself,
+/// recursing into the types of its fields
+fn verify_consistency(
+    info: &TypeInfo,
+    path: &str,
+    depth: usize,
+    options: &ReflectOptions,
+) -> Result<(), LayoutMismatch> {
+    let mismatch = |kind| {
+        Err(LayoutMismatch {
+            path: path.into(),
+            kind,
+        })
+    };
+    if depth >= options.max_depth {
+        return Ok(());
+    }
+    match *info {
+        TypeInfo::Structure(def) => {
+            let mut occupied: Vec<(usize, usize, String)> = Vec::new();
+            for (index, field) in def.fields.iter().enumerate() {
+                let name = match field.name {
+                    Some(name) => name.to_string(),
+                    None => field.index.to_string(),
+                };
+                let value_type = field.value_type.type_ref();
+                verify_consistency(
+                    value_type,
+                    &format!("{}.{}", path, name),
+                    depth + 1,
+                    options,
+                )?;
+                let layout = match known_layout(value_type) {
+                    Some(layout) => layout,
+                    None => continue,
+                };
+                if field.offset % layout.align() != 0 {
+                    return mismatch(MismatchKind::MisalignedField {
+                        field: name,
+                        offset: field.offset,
+                        alignment: layout.align(),
+                    });
+                }
+                let is_tail = def.has_unsized_tail && index + 1 == def.fields.len();
+                let end = field.offset + layout.size();
+                if end > def.size && !is_tail {
+                    return mismatch(MismatchKind::FieldOutOfBounds {
+                        field: name,
+                        end,
+                        size: def.size,
+                    });
+                }
+                if layout.size() > 0 {
+                    occupied.push((field.offset, end, name));
+                }
+            }
+            occupied.sort_by_key(|&(start, _, _)| start);
+            for pair in occupied.windows(2) {
+                let (_, first_end, ref first) = pair[0];
+                let (second_start, _, ref second) = pair[1];
+                if second_start < first_end {
+                    return mismatch(MismatchKind::OverlappingFields {
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+            Ok(())
+        }
+        TypeInfo::UntaggedUnion(_) if options.union_inspection == UnionInspection::Skip => Ok(()),
+        TypeInfo::UntaggedUnion(def) => {
+            for field in def.fields {
+                let value_type = field.value_type.type_ref();
+                let field_path = format!("{}.{}", path, field.name);
+                verify_consistency(value_type, &field_path, depth + 1, options)?;
+                if let Some(layout) = known_layout(value_type) {
+                    if layout.size() > def.size {
+                        return mismatch(MismatchKind::UnionFieldTooLarge {
+                            field: field.name.into(),
+                            field_size: layout.size(),
+                            union_size: def.size,
+                        });
+                    }
+                }
+            }
+            Ok(())
+        }
+        TypeInfo::TaggedUnion(def) => match def.validate() {
+            Ok(()) => Ok(()),
+            Err(cause) => mismatch(MismatchKind::InvalidEnum(cause)),
+        },
+        TypeInfo::CStyleEnum(def) => match def.validate() {
+            Ok(()) => Ok(()),
+            Err(cause) => mismatch(MismatchKind::InvalidEnum(cause)),
+        },
+        TypeInfo::NonZero(inner) => verify_consistency(inner, path, depth, options),
+        _ => Ok(()),
+    }
+}
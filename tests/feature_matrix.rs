@@ -0,0 +1,64 @@
+//! Run the smoke test in `features.rs` with every meaningful combination of features
+//!
+//! Each combination is built with a separate invocation of cargo,
+//! sharing the `target/feature-matrix` directory to avoid contending
+//! for the lock on the main target directory.
+use std::path::Path;
+use std::process::Command;
+
+/// The documented combinations of features
+///
+/// Besides each feature on its own, this includes the combinations
+/// where one feature changes the behavior of another.
+const FEATURE_MATRIX: &[&str] = &[
+    "",
+    "never",
+    "builtins",
+    "never,builtins",
+    "derive",
+    "bytemuck",
+    "derive,bytemuck",
+    "capi",
+    "capi,never",
+    "codegen",
+    "codegen,never,builtins",
+    "register",
+    "register,derive",
+    "simd",
+    "portable_simd",
+    "dwarf",
+    "fuzzing",
+    "never,builtins,derive",
+    "never,builtins,derive,bytemuck,capi,codegen,register,portable_simd,dwarf,fuzzing",
+];
+
+#[test]
+fn feature_matrix() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut failures = Vec::new();
+    for features in FEATURE_MATRIX {
+        let output = Command::new(env!("CARGO"))
+            .current_dir(manifest_dir)
+            .env(
+                "CARGO_TARGET_DIR",
+                manifest_dir.join("target/feature-matrix"),
+            )
+            .args([
+                "test",
+                "--quiet",
+                "-p",
+                "static-reflect",
+                "--test",
+                "features",
+            ])
+            .args(["--no-default-features", "--features", features])
+            .output()
+            .expect("Failed to run cargo");
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+            eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+            failures.push(*features);
+        }
+    }
+    assert!(failures.is_empty(), "Failed with features: {:?}", failures);
+}
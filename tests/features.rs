@@ -0,0 +1,176 @@
+//! A smoke test of the behavior that depends on the enabled features
+//!
+//! This runs with every combination of features listed in `feature_matrix.rs`.
+#![cfg_attr(feature = "never", feature(never_type))]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
+use static_reflect::types::{IntType, PrimitiveType, TypeInfo};
+use static_reflect::StaticReflect;
+
+#[test]
+fn primitives() {
+    assert_eq!(u32::TYPE_INFO, TypeInfo::Integer(IntType::U32));
+    assert_eq!(u32::TYPE_INFO.to_string(), "u32");
+    assert_eq!(f64::TYPE_INFO.to_string(), "f64");
+    assert_eq!(PrimitiveType::Unit.type_info(), &<()>::TYPE_INFO);
+    assert!(<*mut u8>::TYPE_INFO.is_zero_initializable());
+}
+
+#[test]
+fn optional_variant_accessors() {
+    // Available (and false) without the corresponding features
+    assert!(!u32::TYPE_INFO.is_never());
+    assert!(!u32::TYPE_INFO.is_builtin());
+    assert!(!u32::TYPE_INFO.is_str());
+    assert_eq!(u32::TYPE_INFO.slice_element_type(), None);
+    assert_eq!(u32::TYPE_INFO.optional_inner_type(), None);
+}
+
+#[cfg(feature = "never")]
+#[test]
+fn never() {
+    assert_eq!(<!>::TYPE_INFO, TypeInfo::Never);
+    assert_eq!(<!>::TYPE_INFO.to_string(), "!");
+    assert_eq!(<!>::TYPE_INFO.size(), 0);
+    assert!(<!>::TYPE_INFO.is_never());
+}
+
+#[cfg(feature = "builtins")]
+#[test]
+fn builtins() {
+    use static_reflect::builtins::{AsmOption, AsmSlice, AsmStr};
+    assert_eq!(AsmStr::TYPE_INFO, TypeInfo::Str);
+    assert_eq!(AsmSlice::<u8>::TYPE_INFO.to_string(), "[u8]");
+    assert_eq!(AsmOption::<u16>::TYPE_INFO.to_string(), "Option<u16>");
+    assert_eq!(AsmStr::TYPE_INFO.size(), std::mem::size_of::<AsmStr>());
+    assert!(AsmStr::TYPE_INFO.is_str() && AsmStr::TYPE_INFO.is_builtin());
+    assert_eq!(
+        AsmSlice::<u8>::TYPE_INFO.slice_element_type(),
+        Some(&u8::TYPE_INFO)
+    );
+    assert_eq!(
+        AsmOption::<u16>::TYPE_INFO.optional_inner_type(),
+        Some(&u16::TYPE_INFO)
+    );
+    assert!(AsmOption::<u16>::TYPE_INFO.equivalent(
+        &AsmOption::<u16>::TYPE_INFO,
+        static_reflect::types::compare::CompareOptions::STRICT
+    ));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive() {
+    #[derive(StaticReflect)]
+    #[repr(C)]
+    struct Point {
+        x: u8,
+        y: u32,
+    }
+    assert_eq!(Point::TYPE_INFO.size(), 8);
+    assert_eq!(Point::TYPE_INFO.to_string(), "Point");
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn bytemuck() {
+    let bytes = [0u8; 8];
+    let values = static_reflect::pod::cast_slice_reflect::<u8>(&bytes).unwrap();
+    assert_eq!(values.len(), 8);
+}
+
+#[cfg(feature = "capi")]
+#[test]
+fn capi() {
+    use static_reflect::capi::SrTypeKind;
+    assert_eq!(SrTypeKind::of(&u32::TYPE_INFO), SrTypeKind::Integer);
+    assert_eq!(SrTypeKind::of(&TypeInfo::Str), SrTypeKind::Str);
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[test]
+fn simd() {
+    use std::arch::x86_64::{__m128, __m256d};
+    assert_eq!(__m128::TYPE_INFO.to_string(), "f32x4");
+    assert_eq!(__m128::TYPE_INFO.size(), 16);
+    assert_eq!(__m256d::TYPE_INFO.alignment(), 32);
+}
+
+#[cfg(feature = "portable_simd")]
+#[test]
+fn portable_simd() {
+    use std::simd::Simd;
+    assert_eq!(<Simd<u8, 16>>::TYPE_INFO.to_string(), "u8x16");
+    assert_eq!(
+        <Simd<f64, 4>>::TYPE_INFO.alignment(),
+        std::mem::align_of::<Simd<f64, 4>>()
+    );
+}
+
+#[cfg(feature = "codegen")]
+#[test]
+fn codegen() {
+    use static_reflect::funcs::codegen::to_extern_block;
+    use static_reflect::funcs::{
+        FunctionDeclaration, FunctionLocation, ParamDirection, SignatureDef, ThreadSafety,
+        UnwindBehavior,
+    };
+    use static_reflect::types::ffi::FfiSafety;
+    let decl = FunctionDeclaration {
+        name: "abs",
+        is_unsafe: true,
+        location: Some(FunctionLocation::DynamicallyLinked {
+            link_name: None,
+            library: None,
+            weak: false,
+        }),
+        unwind: UnwindBehavior::Unknown,
+        thread_safety: ThreadSafety::Unknown,
+        source: None,
+        signature: SignatureDef {
+            argument_types: &[TypeInfo::Integer(IntType::I32)],
+            argument_directions: &[ParamDirection::In],
+            argument_contracts: &[],
+            argument_units: &[],
+            return_type: &TypeInfo::Integer(IntType::I32),
+            calling_convention: Default::default(),
+            ffi_safety: FfiSafety::Safe,
+        },
+        return_type: std::marker::PhantomData,
+        arg_types: std::marker::PhantomData,
+    };
+    assert_eq!(
+        to_extern_block(&[decl]).unwrap(),
+        "extern \"C\" {\n    pub fn abs(arg0: i32) -> i32;\n}\n"
+    );
+}
+
+#[cfg(feature = "register")]
+#[test]
+fn register() {
+    use static_reflect::funcs::registry::{self, Registration};
+    use static_reflect::funcs::{FunctionDeclaration, SignatureDef, ThreadSafety, UnwindBehavior};
+    const DECL: FunctionDeclaration = FunctionDeclaration {
+        name: "features_smoke_test",
+        is_unsafe: false,
+        location: None,
+        unwind: UnwindBehavior::Aborts,
+        thread_safety: ThreadSafety::Unknown,
+        source: None,
+        signature: SignatureDef {
+            argument_types: &[],
+            argument_directions: &[],
+            argument_contracts: &[],
+            argument_units: &[],
+            return_type: &TypeInfo::Unit,
+            calling_convention: static_reflect::funcs::CallingConvention::StandardC,
+            ffi_safety: static_reflect::types::ffi::FfiSafety::Safe,
+        },
+        return_type: std::marker::PhantomData,
+        arg_types: std::marker::PhantomData,
+    };
+    static REGISTRATION: Registration = Registration::new(&DECL);
+    assert!(registry::find("features_smoke_test").is_empty());
+    registry::register(&REGISTRATION);
+    registry::register(&REGISTRATION);
+    assert_eq!(registry::find("features_smoke_test"), [&DECL]);
+}
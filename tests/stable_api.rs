@@ -0,0 +1,128 @@
+//! A snapshot of the stable subset of the API (see `static_reflect::stable`)
+//!
+//! Every item in the subset is named here, with its exact signature,
+//! so that any breaking change to it fails to compile.
+//! Only the paths of the facade are used, so the derive is checked to work through them.
+//!
+//! Changing this file means the stable API changed, which requires a new major version.
+use static_reflect::stable::types::{
+    FieldDef, FieldMeta, FloatSize, IntSize, IntType, StructureDef, TypeId, TypeInfo,
+};
+use static_reflect::stable::StaticReflect;
+
+/// The shape of each stable variant
+#[allow(dead_code)]
+fn stable_variants(info: &TypeInfo) -> &'static str {
+    match *info {
+        TypeInfo::Unit => "unit",
+        TypeInfo::Bool => "bool",
+        TypeInfo::Integer(IntType {
+            size: IntSize::Byte | IntSize::Short | IntSize::Int | IntSize::Long,
+            signed: _,
+        }) => "integer",
+        TypeInfo::Float {
+            size: FloatSize::Single | FloatSize::Double,
+        } => "float",
+        TypeInfo::Pointer { mutable: _ } => "pointer",
+        TypeInfo::NonZero(_) => "non-zero",
+        TypeInfo::Structure(_) => "structure",
+        _ => "unstable",
+    }
+}
+
+/// Every field of the stable structures (struct literals break if a field is added)
+const EMPTY: StructureDef = StructureDef {
+    name: "Empty",
+    fields: &[],
+    size: 0,
+    alignment: 1,
+    is_pod: false,
+    has_unsized_tail: false,
+    needs_drop: false,
+};
+const FIELD: FieldDef<u32> = FieldDef {
+    name: Some("field"),
+    value_type: TypeId::<u32>::get(),
+    offset: 0,
+    index: 0,
+    endianness: None,
+    assumed: false,
+    opaque_array: false,
+    meta: &FieldMeta::NONE,
+};
+
+/// The signatures of the stable functions
+#[test]
+fn signatures() {
+    let _: fn(&TypeInfo) -> usize = TypeInfo::size;
+    let _: fn(&TypeInfo) -> usize = TypeInfo::alignment;
+    let _: fn(&StructureDef, &str) -> Option<FieldDef> = StructureDef::field_def;
+    let _: fn(&FieldDef<u32>) -> FieldDef = FieldDef::erase;
+    let _: fn(&FieldDef<u32>) -> usize = FieldDef::offset;
+    let _: fn() -> TypeId<u32> = TypeId::<u32>::get;
+    let _: fn(TypeId<u32>) -> TypeId = TypeId::erase;
+    let _: fn(TypeId) -> &'static TypeInfo = TypeId::type_ref;
+    let _: fn(FloatSize) -> usize = FloatSize::bytes;
+    let _: fn(IntSize) -> usize = IntSize::bytes;
+    let _: &'static TypeInfo = &<u32 as StaticReflect>::TYPE_INFO;
+    assert_eq!(EMPTY.field_def("field"), None);
+    assert_eq!(FIELD.erase().value_type.type_ref(), &u32::TYPE_INFO);
+}
+
+#[test]
+fn stable_variant_values() {
+    assert_eq!(stable_variants(&<()>::TYPE_INFO), "unit");
+    assert_eq!(stable_variants(&bool::TYPE_INFO), "bool");
+    assert_eq!(stable_variants(&i64::TYPE_INFO), "integer");
+    assert_eq!(stable_variants(&f32::TYPE_INFO), "float");
+    assert_eq!(stable_variants(&<*const u8>::TYPE_INFO), "pointer");
+    assert_eq!(
+        stable_variants(&<std::ptr::NonNull<u8>>::TYPE_INFO),
+        "non-zero"
+    );
+    assert_eq!(stable_variants(&TypeInfo::Structure(&EMPTY)), "structure");
+    assert_eq!(u32::TYPE_INFO.to_string(), "u32");
+}
+
+#[cfg(feature = "derive")]
+mod derive {
+    use super::*;
+    use static_reflect::stable::FieldReflect;
+
+    #[derive(StaticReflect)]
+    #[reflect(crate = "static_reflect::stable")]
+    #[repr(C)]
+    struct Point {
+        x: f32,
+        #[reflect(rename = "vertical")]
+        y: f64,
+    }
+
+    /// The generated field info, which is part of [FieldReflect]
+    struct NamedFields {
+        x: FieldDef<f32>,
+        y: FieldDef<f64>,
+    }
+    fn named_fields() -> NamedFields {
+        let info = <Point as FieldReflect>::NAMED_FIELD_INFO;
+        NamedFields {
+            x: info.x,
+            y: info.y,
+        }
+    }
+
+    #[test]
+    fn derive_through_facade() {
+        let def = match Point::TYPE_INFO {
+            TypeInfo::Structure(def) => def,
+            other => panic!("Unexpected type: {}", other),
+        };
+        assert_eq!(def.name, "Point");
+        assert_eq!(def.size, std::mem::size_of::<Point>());
+        assert_eq!(def.field_def("vertical").unwrap().offset, 8);
+        let fields = named_fields();
+        assert_eq!(fields.x.offset(), 0);
+        assert_eq!(fields.y.offset(), 8);
+        assert_eq!(fields.y.name, Some("vertical"));
+    }
+}